@@ -1,9 +1,13 @@
 use directories_next::ProjectDirs;
 use std::env;
+use std::ffi::OsString;
 use std::ops::Deref;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::LazyLock;
+use std::sync::OnceLock;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 
 /// Helper that resolves the application config directory and provides helper methods
 #[derive(Clone, Debug, PartialEq)]
@@ -18,20 +22,52 @@ impl AppHome {
 
     /// Create directories for the app home if needed
     /// # Errors
-    /// Returns an error if the directory cannot be created.
+    /// Returns an error if the directory cannot be created, or if this is the default app home
+    /// and [`check_app_home_writable`] found it read-only.
     pub fn ensure_dir(&self) -> eyre::Result<()> {
+        if is_app_home_read_only() && self.is_default() {
+            eyre::bail!(
+                "App home {} is read-only; changes are kept for this session only",
+                self.0.display()
+            );
+        }
         std::fs::create_dir_all(&self.0)?;
         Ok(())
     }
 
+    /// Whether files can be created in the app home, creating it if needed
+    #[must_use]
+    pub fn is_writable(&self) -> bool {
+        let probe = self.file_path(".write-test");
+        let writable =
+            std::fs::create_dir_all(&self.0).is_ok() && std::fs::File::create(&probe).is_ok();
+        let _ = std::fs::remove_file(&probe);
+        writable
+    }
+
     /// Resolve the `AppHome` according to the same rules used previously:
+    /// * If `--app-home` was given, use that directory
+    /// * If `CM_APP_HOME` env var is set, use that directory
     /// * If `CM_CONFIG_DIR` env var is set, use that directory
     /// * Otherwise use the platform `ProjectDirs::config_dir()` for teamdman/cm
     /// # Errors
     /// Returns an error if the config directory cannot be determined.
     pub fn resolve() -> eyre::Result<AppHome> {
-        if let Ok(override_dir) = env::var("CM_CONFIG_DIR") {
-            return Ok(AppHome(PathBuf::from(override_dir)));
+        Self::resolve_from(
+            APP_HOME_OVERRIDE.get().cloned(),
+            env::var_os("CM_APP_HOME"),
+            env::var_os("CM_CONFIG_DIR"),
+        )
+    }
+
+    /// [`AppHome::resolve`] with the flag and environment variables passed in
+    fn resolve_from(
+        flag: Option<PathBuf>,
+        app_home_env: Option<OsString>,
+        config_dir_env: Option<OsString>,
+    ) -> eyre::Result<AppHome> {
+        if let Some(dir) = flag.or_else(|| app_home_env.or(config_dir_env).map(PathBuf::from)) {
+            return Ok(AppHome(dir));
         }
         if let Some(pd) = ProjectDirs::from("", "teamdman", "cm") {
             Ok(AppHome(pd.config_dir().to_path_buf()))
@@ -58,6 +94,41 @@ impl Deref for AppHome {
 
 use tracing::warn;
 
+/// Directory given with `--app-home`
+static APP_HOME_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Whether [`check_app_home_writable`] found [`APP_HOME`] read-only
+static APP_HOME_READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Use `dir` as the app home for the rest of the process. Must be called before [`APP_HOME`]
+/// is first used; later calls have no effect.
+pub fn override_app_home(dir: PathBuf) {
+    if APP_HOME_OVERRIDE.set(dir).is_err() {
+        warn!("App home was already overridden, ignoring");
+    }
+}
+
+/// Check whether [`APP_HOME`] can be written, warning when it can't. Settings keep working in
+/// memory, but saving them fails until the app is restarted with a writable `--app-home`.
+pub fn check_app_home_writable() -> bool {
+    let writable = APP_HOME.is_writable();
+    if !writable {
+        warn!(
+            "App home {} is not writable; settings changes will only last for this session. \
+             Pass --app-home <DIR> to use a writable directory",
+            APP_HOME.display()
+        );
+    }
+    APP_HOME_READ_ONLY.store(!writable, Ordering::SeqCst);
+    writable
+}
+
+/// Returns true if [`check_app_home_writable`] found the app home read-only
+#[must_use]
+pub fn is_app_home_read_only() -> bool {
+    APP_HOME_READ_ONLY.load(Ordering::SeqCst)
+}
+
 /// Cached `AppHome` instance
 pub static APP_HOME: LazyLock<AppHome> = LazyLock::new(|| match AppHome::resolve() {
     Ok(a) => a,
@@ -67,3 +138,56 @@ pub static APP_HOME: LazyLock<AppHome> = LazyLock::new(|| match AppHome::resolve
         AppHome(std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")))
     }
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn override_redirects_inputs_and_rules() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let flag_dir = td.path().join("flag");
+        let env_dir = td.path().join("env");
+        let config_dir = td.path().join("config");
+
+        let resolved = |flag: Option<&PathBuf>, app_home: Option<&PathBuf>| {
+            AppHome::resolve_from(
+                flag.cloned(),
+                app_home.map(OsString::from),
+                Some(config_dir.clone().into()),
+            )
+        };
+        assert_eq!(resolved(None, None)?, AppHome(config_dir.clone()));
+        assert_eq!(resolved(None, Some(&env_dir))?, AppHome(env_dir.clone()));
+        let home = resolved(Some(&flag_dir), Some(&env_dir))?;
+        assert_eq!(home, AppHome(flag_dir.clone()));
+        assert!(!home.is_default());
+
+        let photos = td.path().join("photos");
+        std::fs::create_dir_all(&photos)?;
+        crate::inputs::add_paths(&home, std::slice::from_ref(&photos))?;
+        crate::rename_rules::add_rule(&home, &crate::rename_rules::RenameRule::default())?;
+        assert!(flag_dir.join("inputs.txt").exists());
+        assert!(crate::rename_rules::rules_dir(&home)?.starts_with(&flag_dir));
+        assert_eq!(crate::rename_rules::list_rules(&home)?.len(), 1);
+        assert!(!env_dir.exists() && !config_dir.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn unwritable_home_is_detected() -> eyre::Result<()> {
+        let td = tempdir()?;
+        assert!(AppHome(td.path().join("home")).is_writable());
+        assert!(!td.path().join("home").join(".write-test").exists());
+
+        // A regular file where the directory should be makes every write fail, as a read-only
+        // location would (permissions don't stop root, so they can't be used here)
+        let blocker = td.path().join("blocker");
+        std::fs::write(&blocker, "")?;
+        let home = AppHome(blocker.join("home"));
+        assert!(!home.is_writable());
+        assert!(home.ensure_dir().is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,69 @@
+//! Crash-safe file replacement for configuration files
+//!
+//! Writes go to a temporary sibling that is flushed to disk and then renamed over the target,
+//! so an interrupted save leaves either the old or the new contents, never a truncated file.
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Path of the temporary sibling written before being renamed over `path` (`.<name>.tmp`)
+#[must_use]
+pub fn temp_path_for(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{name}.tmp"))
+}
+
+/// Replace the contents of `path` atomically
+/// # Errors
+/// Returns an error if the temporary file cannot be written or renamed over `path`.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> eyre::Result<()> {
+    let temp = temp_path_for(path);
+    let result = (|| {
+        let mut f = fs::File::create(&temp)?;
+        f.write_all(contents.as_ref())?;
+        f.sync_all()?;
+        drop(f);
+        fs::rename(&temp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    Ok(result?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn temp_file_is_a_separate_sibling() {
+        let target = Path::new("config").join("inputs.txt");
+        let temp = temp_path_for(&target);
+        assert_ne!(temp, target);
+        assert_eq!(temp.parent(), target.parent());
+    }
+
+    #[test]
+    fn interrupted_write_leaves_target_intact() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let target = td.path().join("inputs.txt");
+        write_atomic(&target, "old\n")?;
+        assert!(!temp_path_for(&target).exists());
+
+        // A crash after writing the temp file but before the rename
+        fs::write(temp_path_for(&target), "partial")?;
+        assert_eq!(fs::read_to_string(&target)?, "old\n");
+
+        // The next save replaces both the stale temp file and the target
+        write_atomic(&target, "new\n")?;
+        assert_eq!(fs::read_to_string(&target)?, "new\n");
+        assert!(!temp_path_for(&target).exists());
+        Ok(())
+    }
+}
@@ -0,0 +1,115 @@
+//! Input images completed by the current Process All run, so a batch interrupted by a crash
+//! can be resumed without redoing finished images. Entries are input paths rather than
+//! outputs because the conflict policy may have written an output under another name.
+
+use crate::app_home::AppHome;
+use std::collections::HashSet;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+const FILE_NAME: &str = "batch_checkpoint.txt";
+
+/// Serializes appends from concurrent per-image tasks
+static APPEND_LOCK: Mutex<()> = Mutex::new(());
+
+/// Whether an unfinished batch left a checkpoint behind
+#[must_use]
+pub fn exists(home: &AppHome) -> bool {
+    home.file_path(FILE_NAME).is_file()
+}
+
+/// The input paths recorded as completed
+///
+/// # Errors
+///
+/// Returns an error if the checkpoint file exists but cannot be read.
+pub fn completed_inputs(home: &AppHome) -> eyre::Result<HashSet<PathBuf>> {
+    let path = home.file_path(FILE_NAME);
+    if !path.exists() {
+        return Ok(HashSet::new());
+    }
+    Ok(fs::read_to_string(&path)?
+        .lines()
+        .filter(|l| !l.trim().is_empty())
+        .map(PathBuf::from)
+        .collect())
+}
+
+/// Start a fresh checkpoint, forgetting any previous batch
+///
+/// # Errors
+///
+/// Returns an error if the checkpoint file cannot be written.
+pub fn start(home: &AppHome) -> eyre::Result<()> {
+    home.ensure_dir()?;
+    fs::write(home.file_path(FILE_NAME), "")?;
+    Ok(())
+}
+
+/// Record `input` as processed. Each entry is appended and flushed on its own so a crash loses
+/// at most the image in flight.
+///
+/// # Errors
+///
+/// Returns an error if the checkpoint file cannot be opened or written.
+///
+/// # Panics
+///
+/// Panics if the append lock is poisoned.
+pub fn record_completed(home: &AppHome, input: &Path) -> eyre::Result<()> {
+    let _guard = APPEND_LOCK.lock().unwrap();
+    home.ensure_dir()?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(home.file_path(FILE_NAME))?;
+    writeln!(file, "{}", input.display())?;
+    file.flush()?;
+    Ok(())
+}
+
+/// Remove the checkpoint once a batch has finished cleanly
+///
+/// # Errors
+///
+/// Returns an error if the checkpoint file cannot be removed.
+pub fn clear(home: &AppHome) -> eyre::Result<()> {
+    let path = home.file_path(FILE_NAME);
+    if path.exists() {
+        fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn completed_inputs_round_trip_and_clear() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+        assert!(!exists(&home));
+        assert!(completed_inputs(&home)?.is_empty());
+
+        start(&home)?;
+        assert!(exists(&home));
+        record_completed(&home, Path::new("/photos/a.png"))?;
+        record_completed(&home, Path::new("/photos/b c.jpg"))?;
+        let done = completed_inputs(&home)?;
+        assert_eq!(done.len(), 2);
+        assert!(done.contains(Path::new("/photos/b c.jpg")));
+
+        // Starting again forgets the previous batch
+        start(&home)?;
+        assert!(completed_inputs(&home)?.is_empty());
+
+        clear(&home)?;
+        assert!(!exists(&home));
+        Ok(())
+    }
+}
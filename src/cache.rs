@@ -63,11 +63,22 @@ impl std::ops::Deref for CacheHome {
 pub struct CacheEntry {
     /// Directory for this cache entry.
     pub dir: PathBuf,
+    /// The URL this entry caches, used to verify hits against `url.txt`.
+    pub url: String,
 }
 
 impl CacheEntry {
     /// Create a new cache entry for the given URL.
     pub fn for_url(url: &str) -> Self {
+        Self::for_url_in(&CACHE_HOME.api_responses_dir(), url)
+    }
+
+    /// Create a cache entry for the given URL under an explicit responses directory.
+    ///
+    /// The directory name is a hash of the URL, so arbitrarily long URLs
+    /// never exceed filesystem name limits.
+    #[must_use]
+    pub fn for_url_in(responses_dir: &Path, url: &str) -> Self {
         let hash = {
             let mut hasher = Sha256::new();
             hasher.update(url.as_bytes());
@@ -76,8 +87,11 @@ impl CacheEntry {
         };
         // Use first 16 chars of hash for shorter paths
         let short_hash = &hash[..16];
-        let dir = CACHE_HOME.api_responses_dir().join(short_hash);
-        Self { dir }
+        let dir = responses_dir.join(short_hash);
+        Self {
+            dir,
+            url: url.to_string(),
+        }
     }
 
     /// Path to the response body file.
@@ -112,6 +126,17 @@ impl CacheEntry {
             return Ok(None);
         }
 
+        // Guard against hash prefix collisions by checking the stored URL
+        if let Ok(stored_url) = std::fs::read_to_string(self.url_path())
+            && stored_url != self.url
+        {
+            warn!(
+                cache_dir = %self.dir.display(),
+                "Cached URL does not match requested URL, ignoring cache entry"
+            );
+            return Ok(None);
+        }
+
         let body = std::fs::read_to_string(self.response_path())?;
 
         // Append access timestamp
@@ -184,3 +209,41 @@ pub struct CleanResult {
     /// Number of cache entries removed.
     pub entries_removed: usize,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn long_url_round_trips() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let mut url = "https://example.a.searchspring.io/api/search/search.json?q=test".to_string();
+        while url.len() < 300 {
+            url.push_str("&filter.sku=ABC-12345");
+        }
+
+        let entry = CacheEntry::for_url_in(td.path(), &url);
+        assert!(entry.dir.file_name().unwrap().len() < 64);
+        entry.write(&url, "body")?;
+
+        let reread = CacheEntry::for_url_in(td.path(), &url);
+        assert_eq!(reread.read()?.as_deref(), Some("body"));
+        assert_eq!(std::fs::read_to_string(reread.url_path())?, url);
+        Ok(())
+    }
+
+    #[test]
+    fn mismatched_url_is_a_miss() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let entry = CacheEntry::for_url_in(td.path(), "https://example.com/a");
+        entry.write("https://example.com/a", "body")?;
+
+        let other = CacheEntry {
+            dir: entry.dir.clone(),
+            url: "https://example.com/b".to_string(),
+        };
+        assert!(other.read()?.is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use crate::app_home::APP_HOME;
+use crate::cli::to_args::ToArgs;
+use crate::file_manager::reveal_in_file_manager;
+use arbitrary::Arbitrary;
+use clap::Args;
+use clap::Subcommand;
+use std::ffi::OsString;
+
+#[derive(Subcommand, Clone, Arbitrary, PartialEq, Debug)]
+pub enum AppHomeCommand {
+    /// Print the app home (config) directory
+    Path(AppHomePathArgs),
+
+    /// Reveal the app home directory in the system file manager
+    Open(AppHomeOpenArgs),
+}
+
+impl AppHomeCommand {
+    /// # Errors
+    ///
+    /// Returns an error if the app-home command fails.
+    pub fn invoke(self) -> eyre::Result<()> {
+        match self {
+            AppHomeCommand::Path(a) => a.invoke(),
+            AppHomeCommand::Open(a) => a.invoke(),
+        }
+    }
+}
+
+impl ToArgs for AppHomeCommand {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args = Vec::new();
+        match self {
+            AppHomeCommand::Path(a) => {
+                args.push("path".into());
+                args.extend(a.to_args());
+            }
+            AppHomeCommand::Open(a) => {
+                args.push("open".into());
+                args.extend(a.to_args());
+            }
+        }
+        args
+    }
+}
+
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct AppHomePathArgs {}
+
+impl AppHomePathArgs {
+    /// # Errors
+    ///
+    /// This command does not currently fail.
+    pub fn invoke(self) -> eyre::Result<()> {
+        println!("{}", APP_HOME.display());
+        Ok(())
+    }
+}
+
+impl ToArgs for AppHomePathArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        vec![]
+    }
+}
+
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct AppHomeOpenArgs {}
+
+impl AppHomeOpenArgs {
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created or the file manager cannot be launched.
+    pub fn invoke(self) -> eyre::Result<()> {
+        APP_HOME.ensure_dir()?;
+        reveal_in_file_manager(&APP_HOME)
+    }
+}
+
+impl ToArgs for AppHomeOpenArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        vec![]
+    }
+}
@@ -0,0 +1,30 @@
+//! `cm app-home` commands to locate the configuration directory.
+
+pub mod app_home_command;
+
+use crate::cli::command::app_home::app_home_command::AppHomeCommand;
+use crate::cli::to_args::ToArgs;
+use arbitrary::Arbitrary;
+use clap::Args;
+use std::ffi::OsString;
+
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct AppHomeArgs {
+    #[clap(subcommand)]
+    pub command: AppHomeCommand,
+}
+
+impl AppHomeArgs {
+    /// # Errors
+    ///
+    /// Returns an error if the app-home subcommand fails.
+    pub fn invoke(self) -> eyre::Result<()> {
+        self.command.invoke()
+    }
+}
+
+impl ToArgs for AppHomeArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        self.command.to_args()
+    }
+}
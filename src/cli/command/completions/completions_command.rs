@@ -0,0 +1,78 @@
+use crate::cli::Cli;
+use crate::cli::to_args::ToArgs;
+use arbitrary::Arbitrary;
+use clap::Args;
+use clap::CommandFactory;
+use clap::ValueEnum;
+use std::ffi::OsString;
+use std::io::Write;
+
+/// Shells supported by `cm completions`
+#[derive(ValueEnum, Arbitrary, Clone, Copy, PartialEq, Debug)]
+pub enum CompletionShell {
+    Bash,
+    Zsh,
+    Fish,
+    #[value(name = "powershell")]
+    PowerShell,
+}
+
+impl std::fmt::Display for CompletionShell {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bash => write!(f, "bash"),
+            Self::Zsh => write!(f, "zsh"),
+            Self::Fish => write!(f, "fish"),
+            Self::PowerShell => write!(f, "powershell"),
+        }
+    }
+}
+
+impl From<CompletionShell> for clap_complete::Shell {
+    fn from(shell: CompletionShell) -> Self {
+        match shell {
+            CompletionShell::Bash => clap_complete::Shell::Bash,
+            CompletionShell::Zsh => clap_complete::Shell::Zsh,
+            CompletionShell::Fish => clap_complete::Shell::Fish,
+            CompletionShell::PowerShell => clap_complete::Shell::PowerShell,
+        }
+    }
+}
+
+/// Print a shell completion script to stdout
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct CompletionsArgs {
+    /// Shell to generate completions for
+    #[clap(value_enum)]
+    pub shell: CompletionShell,
+}
+
+impl CompletionsArgs {
+    /// Write the completion script for `shell` to `out`.
+    pub fn write_to(&self, out: &mut dyn Write) {
+        let mut command = Cli::command();
+        let name = command.get_name().to_string();
+        clap_complete::generate(
+            clap_complete::Shell::from(self.shell),
+            &mut command,
+            name,
+            out,
+        );
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if writing the completion script to stdout fails.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let mut stdout = std::io::stdout().lock();
+        self.write_to(&mut stdout);
+        stdout.flush()?;
+        Ok(())
+    }
+}
+
+impl ToArgs for CompletionsArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        vec![OsString::from(self.shell.to_string())]
+    }
+}
@@ -0,0 +1,3 @@
+//! `cm completions` command to generate shell completion scripts.
+
+pub mod completions_command;
@@ -0,0 +1,150 @@
+use crate::app_home::APP_HOME;
+use crate::cli::to_args::ToArgs;
+use crate::cli::to_args::arbitrary_path;
+use crate::cli::to_args::flag_value;
+use crate::image_extensions::load_image_extensions;
+use crate::image_processing::ContactSheetOptions;
+use crate::image_processing::build_contact_sheet;
+use crate::image_processing::get_output_path;
+use crate::image_processing::is_image_file;
+use crate::inputs;
+use crate::inputs::NameFilter;
+use crate::name_length_unit::name_length_unit;
+use crate::output_root::load_output_root;
+use crate::rename_rules::RenameOptions;
+use crate::rename_rules::apply_rules_seq;
+use crate::rename_rules::list_rules;
+use crate::rename_rules::rules_enabled;
+use arbitrary::Arbitrary;
+use arbitrary::Unstructured;
+use clap::Args;
+use eyre::eyre;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+
+/// Most thumbnails `--columns` accepts per row
+const MAX_COLUMNS: u32 = 100;
+/// Smallest `--cell-size` accepted, big enough for a thumbnail inside its padding
+const MIN_CELL_SIZE: u32 = 16;
+/// Largest `--cell-size` accepted
+const MAX_CELL_SIZE: u32 = 4096;
+
+/// Tile thumbnails of the processed outputs into a single PNG with file names underneath
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct ContactSheetArgs {
+    /// Where to write the contact sheet PNG
+    #[arbitrary(with = arbitrary_path)]
+    pub output: PathBuf,
+    /// Only include outputs of images whose file name matches this glob (e.g. `*-front.*`)
+    #[clap(long)]
+    pub filter: Option<String>,
+    /// Thumbnails per row
+    #[clap(
+        long,
+        default_value_t = 6,
+        value_parser = clap::value_parser!(u32).range(1..=i64::from(MAX_COLUMNS))
+    )]
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(1..=MAX_COLUMNS))]
+    pub columns: u32,
+    /// Width and height of each thumbnail cell in pixels
+    #[clap(
+        long,
+        default_value_t = 200,
+        value_parser = clap::value_parser!(u32)
+            .range(i64::from(MIN_CELL_SIZE)..=i64::from(MAX_CELL_SIZE))
+    )]
+    #[arbitrary(with = |u: &mut Unstructured| u.int_in_range(MIN_CELL_SIZE..=MAX_CELL_SIZE))]
+    pub cell_size: u32,
+}
+
+impl ContactSheetArgs {
+    /// # Errors
+    ///
+    /// Returns an error if the filter is invalid, the inputs or rename rules cannot be loaded,
+    /// no outputs exist yet, or the sheet is too large or cannot be written.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let filter = self.filter.as_deref().map(NameFilter::parse).transpose()?;
+        let extensions = load_image_extensions(&APP_HOME)?;
+        let files: Vec<_> = inputs::list_files(&APP_HOME)?
+            .into_iter()
+            .filter(|p| is_image_file(p, &extensions))
+            .filter(|p| filter.as_ref().is_none_or(|f| f.matches(p)))
+            .collect();
+
+        let rules: Vec<_> = list_rules(&APP_HOME)?.into_iter().map(|(_, r)| r).collect();
+        let options = RenameOptions {
+            rules_enabled: rules_enabled(&APP_HOME)?,
+            length_unit: name_length_unit(),
+            ..RenameOptions::new(crate::MAX_NAME_LENGTH.load(Ordering::SeqCst))
+        };
+        let renamed = apply_rules_seq(&files, &rules, &options);
+        let roots = inputs::load_inputs(&APP_HOME)?;
+        let output_root = load_output_root(&APP_HOME)?;
+
+        // Only outputs that have actually been written
+        let outputs: Vec<PathBuf> = files
+            .iter()
+            .zip(&renamed)
+            .filter_map(|(file, target)| {
+                let root = roots.iter().find(|r| file.starts_with(r))?;
+                let name = target.file_name()?.to_string_lossy();
+                get_output_path(file, root, &name, output_root.as_deref())
+            })
+            .filter(|p| p.is_file())
+            .collect();
+        if outputs.is_empty() {
+            return Err(eyre!("No processed outputs found; run `cm process` first"));
+        }
+
+        let sheet = build_contact_sheet(
+            &outputs,
+            &ContactSheetOptions {
+                columns: self.columns,
+                cell_size: self.cell_size,
+            },
+        )?;
+        sheet
+            .save_with_format(&self.output, image::ImageFormat::Png)
+            .map_err(|e| eyre!("Failed to write {}: {}", self.output.display(), e))?;
+        println!(
+            "Wrote a contact sheet of {} outputs to {}",
+            outputs.len(),
+            self.output.display()
+        );
+        Ok(())
+    }
+}
+
+impl ToArgs for ContactSheetArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args = Vec::new();
+        if let Some(filter) = &self.filter {
+            args.push(flag_value("--filter", filter));
+        }
+        args.push("--columns".into());
+        args.push(self.columns.to_string().into());
+        args.push("--cell-size".into());
+        args.push(self.cell_size.to_string().into());
+        args.push("--".into());
+        args.push(self.output.clone().into_os_string());
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::Cli;
+    use clap::Parser;
+
+    #[test]
+    fn out_of_range_layouts_are_rejected() {
+        let parse = |flag: &str| Cli::try_parse_from(["cm", "contact-sheet", flag, "sheet.png"]);
+        assert!(parse("--columns=0").is_err());
+        assert!(parse("--columns=101").is_err());
+        assert!(parse("--cell-size=3").is_err());
+        assert!(parse("--cell-size=4097").is_err());
+        assert!(parse("--cell-size=16").is_ok());
+        assert!(parse("--columns=100").is_ok());
+    }
+}
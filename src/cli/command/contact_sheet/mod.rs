@@ -0,0 +1,3 @@
+//! `cm contact-sheet` command to tile the processed outputs into one overview image.
+
+pub mod contact_sheet_command;
@@ -0,0 +1,163 @@
+use crate::app_home::APP_HOME;
+use crate::cache::CACHE_HOME;
+use crate::cli::to_args::ToArgs;
+use crate::image_extensions::load_image_extensions;
+use crate::image_processing::is_image_file;
+use crate::inputs;
+use crate::rename_rules;
+use crate::session_id::current_session_id;
+use crate::site_id::current_site_id;
+use crate::user_id::current_user_id;
+use arbitrary::Arbitrary;
+use clap::Args;
+use facet::Facet;
+use std::ffi::OsString;
+use std::fs;
+use std::path::Path;
+
+/// Report environment and configuration details useful for troubleshooting
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct DoctorArgs {
+    /// Print the report as JSON
+    #[clap(long)]
+    pub json: bool,
+}
+
+/// Health check results gathered by `cm doctor`
+#[derive(Debug, Clone, PartialEq, Facet)]
+pub struct DoctorReport {
+    pub app_home: String,
+    pub app_home_writable: bool,
+    pub input_count: usize,
+    pub image_file_count: usize,
+    pub rename_rule_count: usize,
+    /// Rules whose find pattern does not compile, as "`<id>`: `<error>`"
+    pub invalid_rename_rules: Vec<String>,
+    pub site_id: String,
+    pub user_id: String,
+    pub session_id: String,
+    pub cache_dir: String,
+    pub cache_size_bytes: u64,
+}
+
+impl DoctorReport {
+    /// Gather the report from the global app home, cache home and ids
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the inputs, image extensions or rename rules cannot be read.
+    pub fn gather() -> eyre::Result<Self> {
+        let input_count = inputs::load_inputs(&APP_HOME)?.len();
+        let extensions = load_image_extensions(&APP_HOME)?;
+        let image_file_count = inputs::list_files(&APP_HOME)?
+            .iter()
+            .filter(|p| is_image_file(p, &extensions))
+            .count();
+        let rules = rename_rules::list_rules(&APP_HOME)?;
+        let invalid_rename_rules = rules
+            .iter()
+            .filter_map(|(_, rule)| rule.regex_error().map(|e| format!("{}: {}", rule.id, e)))
+            .collect();
+
+        Ok(DoctorReport {
+            app_home: APP_HOME.display().to_string(),
+            app_home_writable: APP_HOME.is_writable(),
+            input_count,
+            image_file_count,
+            rename_rule_count: rules.len(),
+            invalid_rename_rules,
+            site_id: current_site_id().0,
+            user_id: current_user_id().0.to_string(),
+            session_id: current_session_id().0.to_string(),
+            cache_dir: CACHE_HOME.display().to_string(),
+            cache_size_bytes: dir_size(&CACHE_HOME),
+        })
+    }
+}
+
+impl std::fmt::Display for DoctorReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let writable = if self.app_home_writable {
+            "writable"
+        } else {
+            "NOT writable"
+        };
+        writeln!(f, "App home:      {} ({writable})", self.app_home)?;
+        writeln!(f, "Inputs:        {}", self.input_count)?;
+        writeln!(f, "Image files:   {}", self.image_file_count)?;
+        writeln!(f, "Rename rules:  {}", self.rename_rule_count)?;
+        for invalid in &self.invalid_rename_rules {
+            writeln!(f, "  invalid regex in rule {invalid}")?;
+        }
+        writeln!(f, "Site id:       {}", self.site_id)?;
+        writeln!(f, "User id:       {}", self.user_id)?;
+        writeln!(f, "Session id:    {}", self.session_id)?;
+        write!(
+            f,
+            "Cache:         {} ({} bytes)",
+            self.cache_dir, self.cache_size_bytes
+        )
+    }
+}
+
+/// Total size of all files below `dir` (0 if it does not exist)
+fn dir_size(dir: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| {
+            let path = entry.path();
+            if path.is_dir() {
+                dir_size(&path)
+            } else {
+                entry.metadata().map_or(0, |m| m.len())
+            }
+        })
+        .sum()
+}
+
+impl DoctorArgs {
+    /// # Errors
+    ///
+    /// Returns an error if gathering or serializing the report fails.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let report = DoctorReport::gather()?;
+        if self.json {
+            let json = facet_json::to_string(&report)
+                .map_err(|e| eyre::eyre!("Failed to serialize report: {}", e))?;
+            println!("{json}");
+        } else {
+            println!("{report}");
+        }
+        Ok(())
+    }
+}
+
+impl ToArgs for DoctorArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut rtn = vec![];
+        if self.json {
+            rtn.push(OsString::from("--json"));
+        }
+        rtn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn dir_size_recurses() -> eyre::Result<()> {
+        let td = tempdir()?;
+        fs::write(td.path().join("a.txt"), b"12345")?;
+        fs::create_dir_all(td.path().join("sub"))?;
+        fs::write(td.path().join("sub").join("b.txt"), b"123")?;
+        assert_eq!(dir_size(td.path()), 8);
+        assert_eq!(dir_size(&td.path().join("missing")), 0);
+        Ok(())
+    }
+}
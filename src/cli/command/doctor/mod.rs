@@ -0,0 +1,3 @@
+//! `cm doctor` command to diagnose the environment and configuration.
+
+pub mod doctor_command;
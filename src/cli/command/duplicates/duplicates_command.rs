@@ -0,0 +1,60 @@
+use crate::app_home::APP_HOME;
+use crate::cli::to_args::ToArgs;
+use crate::duplicates::find_input_duplicates;
+use crate::duplicates::remove_duplicates_from_inputs;
+use arbitrary::Arbitrary;
+use clap::Args;
+use std::ffi::OsString;
+
+/// Find files with identical contents across the persisted inputs
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct DuplicatesArgs {
+    /// Keep the first file of each duplicate set and remove the rest from the inputs (not from disk)
+    #[clap(long)]
+    pub remove_duplicates: bool,
+}
+
+impl DuplicatesArgs {
+    /// # Errors
+    ///
+    /// Returns an error if the inputs cannot be listed or updated.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let groups = find_input_duplicates(&APP_HOME)?;
+        if groups.is_empty() {
+            println!("No duplicate files found");
+            return Ok(());
+        }
+
+        for (i, group) in groups.iter().enumerate() {
+            println!("Duplicate set {}:", i + 1);
+            for (j, path) in group.iter().enumerate() {
+                let marker = if j == 0 { "keep" } else { "dup " };
+                println!("  [{marker}] {}", path.display());
+            }
+        }
+
+        if self.remove_duplicates {
+            let (removed, kept_in_dir) = remove_duplicates_from_inputs(&APP_HOME, &groups)?;
+            for p in &removed {
+                println!("Removed from inputs: {}", p.display());
+            }
+            for p in &kept_in_dir {
+                println!(
+                    "Cannot remove {} individually: it is inside an input directory",
+                    p.display()
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ToArgs for DuplicatesArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut rtn = vec![];
+        if self.remove_duplicates {
+            rtn.push(OsString::from("--remove-duplicates"));
+        }
+        rtn
+    }
+}
@@ -0,0 +1,3 @@
+//! `cm duplicates` command to find identical files across inputs.
+
+pub mod duplicates_command;
@@ -1,10 +1,15 @@
 use crate::app_home::APP_HOME;
 use crate::cli::to_args::ToArgs;
+use crate::cli::to_args::arbitrary_optional_path;
+use crate::cli::to_args::flag_value;
+use crate::image_extensions::load_image_extensions;
+use crate::image_extensions::set_image_extensions;
 use crate::inputs;
 use arbitrary::Arbitrary;
 use clap::Args;
 use clap::Subcommand;
 use std::ffi::OsString;
+use std::path::PathBuf;
 
 #[derive(Subcommand, Clone, Arbitrary, PartialEq, Debug)]
 pub enum InputCommand {
@@ -16,6 +21,9 @@ pub enum InputCommand {
 
     /// Remove persisted input paths matching a glob
     Remove(InputRemoveArgs),
+
+    /// Show or edit the file extensions recognized as images
+    Extensions(InputExtensionsArgs),
 }
 
 impl InputCommand {
@@ -27,6 +35,7 @@ impl InputCommand {
             InputCommand::Add(a) => a.invoke(),
             InputCommand::List(a) => a.invoke(),
             InputCommand::Remove(a) => a.invoke(),
+            InputCommand::Extensions(a) => a.invoke(),
         }
     }
 }
@@ -47,6 +56,10 @@ impl ToArgs for InputCommand {
                 args.push("remove".into());
                 args.extend(a.to_args());
             }
+            InputCommand::Extensions(a) => {
+                args.push("extensions".into());
+                args.extend(a.to_args());
+            }
         }
         args
     }
@@ -55,39 +68,87 @@ impl ToArgs for InputCommand {
 #[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
 pub struct InputAddArgs {
     /// Glob pattern to add (file paths matched will be canonicalized and stored)
-    pub pattern: String,
+    pub pattern: Option<String>,
+    /// Also add the paths listed in this file, one per line (`#` comments and blank lines are
+    /// ignored, relative paths are resolved against the file's directory)
+    #[clap(long)]
+    #[arbitrary(with = arbitrary_optional_path)]
+    pub from_file: Option<PathBuf>,
 }
 
 impl InputAddArgs {
     /// # Errors
     ///
-    /// Returns an error if adding the input paths fails.
+    /// Returns an error if neither a pattern nor a list file is given, or adding the input paths
+    /// fails.
     pub fn invoke(self) -> eyre::Result<()> {
-        let added = inputs::add_from_glob(&APP_HOME, &self.pattern)?;
-        for p in &added {
-            println!("Added: {}", p.display());
+        if self.pattern.is_none() && self.from_file.is_none() {
+            eyre::bail!("Provide a glob pattern or --from-file <path>");
+        }
+        if let Some(list_file) = &self.from_file {
+            let result = inputs::add_from_list_file(&APP_HOME, list_file)?;
+            print_added(&result);
+            println!(
+                "Imported {}: {} added, {} skipped",
+                list_file.display(),
+                result.added.len(),
+                result.skipped.len()
+            );
         }
-        if added.is_empty() {
-            println!("No matching paths were found for '{}'.", self.pattern);
+        if let Some(pattern) = &self.pattern {
+            let result = inputs::add_from_glob(&APP_HOME, pattern)?;
+            print_added(&result);
+            if result.added.is_empty() && result.skipped.is_empty() {
+                println!("No matching paths were found for '{pattern}'.");
+            }
         }
         Ok(())
     }
 }
 
+fn print_added(result: &inputs::AddedInputs) {
+    for p in &result.added {
+        println!("Added: {}", p.display());
+    }
+    for (p, reason) in &result.skipped {
+        println!("Skipped: {} ({reason})", p.display());
+    }
+}
+
 impl ToArgs for InputAddArgs {
     fn to_args(&self) -> Vec<OsString> {
-        vec![OsString::from(self.pattern.clone())]
+        let mut args = Vec::new();
+        if let Some(from_file) = &self.from_file {
+            args.push(flag_value("--from-file", from_file));
+        }
+        if let Some(pattern) = &self.pattern {
+            args.push("--".into());
+            args.push(OsString::from(pattern.clone()));
+        }
+        args
     }
 }
 
 #[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
-pub struct InputListArgs {}
+pub struct InputListArgs {
+    /// Print each input as JSON with its kind, image count and total image bytes
+    #[clap(long)]
+    pub json: bool,
+}
 
 impl InputListArgs {
     /// # Errors
     ///
-    /// Returns an error if loading the input paths fails.
+    /// Returns an error if loading the input paths fails, or an input directory cannot be read
+    /// for `--json`.
     pub fn invoke(self) -> eyre::Result<()> {
+        if self.json {
+            let summaries = inputs::summarize_inputs(&APP_HOME)?;
+            let json = facet_json::to_string(&summaries)
+                .map_err(|e| eyre::eyre!("Failed to serialize inputs: {}", e))?;
+            println!("{json}");
+            return Ok(());
+        }
         let list = inputs::load_inputs(&APP_HOME)?;
         for p in list {
             println!("{}", p.display());
@@ -98,7 +159,11 @@ impl InputListArgs {
 
 impl ToArgs for InputListArgs {
     fn to_args(&self) -> Vec<OsString> {
-        vec![]
+        let mut args = Vec::new();
+        if self.json {
+            args.push("--json".into());
+        }
+        args
     }
 }
 
@@ -126,6 +191,61 @@ impl InputRemoveArgs {
 
 impl ToArgs for InputRemoveArgs {
     fn to_args(&self) -> Vec<OsString> {
-        vec![OsString::from(self.pattern.clone())]
+        vec!["--".into(), OsString::from(self.pattern.clone())]
+    }
+}
+
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct InputExtensionsArgs {
+    /// Extension to recognize as an image (repeatable, e.g. `--add heic`)
+    #[clap(long)]
+    pub add: Vec<String>,
+    /// Extension to stop recognizing (repeatable, e.g. `--remove gif`)
+    #[clap(long)]
+    pub remove: Vec<String>,
+    /// Restore the default extensions before applying any additions or removals
+    #[clap(long)]
+    pub reset: bool,
+}
+
+impl InputExtensionsArgs {
+    /// # Errors
+    ///
+    /// Returns an error if the extension list cannot be loaded or saved.
+    pub fn invoke(self) -> eyre::Result<()> {
+        if self.reset {
+            set_image_extensions(&APP_HOME, None)?;
+        }
+        if !self.add.is_empty() || !self.remove.is_empty() {
+            let removed: Vec<String> = self
+                .remove
+                .iter()
+                .map(|e| e.trim().trim_start_matches('.').to_lowercase())
+                .collect();
+            let mut extensions = load_image_extensions(&APP_HOME)?;
+            extensions.extend(self.add);
+            extensions.retain(|e| !removed.contains(&e.to_lowercase()));
+            set_image_extensions(&APP_HOME, Some(&extensions))?;
+        }
+        for ext in load_image_extensions(&APP_HOME)? {
+            println!("{ext}");
+        }
+        Ok(())
+    }
+}
+
+impl ToArgs for InputExtensionsArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args = Vec::new();
+        for ext in &self.add {
+            args.push(flag_value("--add", ext));
+        }
+        for ext in &self.remove {
+            args.push(flag_value("--remove", ext));
+        }
+        if self.reset {
+            args.push("--reset".into());
+        }
+        args
     }
 }
@@ -0,0 +1,93 @@
+use crate::app_home::APP_HOME;
+use crate::cli::to_args::ToArgs;
+use crate::image_extensions::load_image_extensions;
+use crate::image_processing::is_image_file;
+use crate::inputs;
+use crate::name_length_unit::name_length_unit;
+use crate::rename_rules::RenameOptions;
+use crate::rename_rules::apply_rules_seq;
+use crate::rename_rules::list_rules;
+use crate::rename_rules::preview;
+use crate::rename_rules::rules_enabled;
+use arbitrary::Arbitrary;
+use clap::Args;
+use std::ffi::OsString;
+use std::sync::atomic::Ordering;
+
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct MaxNameLengthCheckArgs {
+    /// Show what each over-long name would be truncated to
+    #[clap(long)]
+    pub auto_truncate: bool,
+}
+
+impl MaxNameLengthCheckArgs {
+    /// # Errors
+    ///
+    /// Returns an error if the inputs or rename rules cannot be loaded.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let max_name_length = crate::MAX_NAME_LENGTH.load(Ordering::SeqCst);
+        let extensions = load_image_extensions(&APP_HOME)?;
+        let files: Vec<_> = inputs::list_files(&APP_HOME)?
+            .into_iter()
+            .filter(|p| is_image_file(p, &extensions))
+            .collect();
+        let rules: Vec<_> = list_rules(&APP_HOME)?.into_iter().map(|(_, r)| r).collect();
+        let options = RenameOptions {
+            rules_enabled: rules_enabled(&APP_HOME)?,
+            length_unit: name_length_unit(),
+            ..RenameOptions::new(max_name_length)
+        };
+        let unit = options.length_unit;
+        let previews = preview(&files, &rules, &options);
+        let truncated = apply_rules_seq(
+            &files,
+            &rules,
+            &RenameOptions {
+                auto_truncate: true,
+                ..options
+            },
+        );
+
+        let mut too_long = 0usize;
+        for (entry, fixed) in previews.iter().zip(&truncated) {
+            if !entry.too_long {
+                continue;
+            }
+            too_long += 1;
+            let name = entry
+                .renamed
+                .file_name()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            println!(
+                "{} ({} > {max_name_length} {})",
+                name,
+                unit.measure(&name),
+                unit.name()
+            );
+            println!("    from {}", entry.original.display());
+            if self.auto_truncate
+                && let Some(fixed_name) = fixed.file_name()
+            {
+                println!("    truncated to {}", fixed_name.to_string_lossy());
+            }
+        }
+        println!(
+            "{too_long} of {} names exceed the max name length of {max_name_length} {}",
+            files.len(),
+            unit.name()
+        );
+        Ok(())
+    }
+}
+
+impl ToArgs for MaxNameLengthCheckArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args = Vec::new();
+        if self.auto_truncate {
+            args.push("--auto-truncate".into());
+        }
+        args
+    }
+}
@@ -1,6 +1,8 @@
+use crate::cli::command::max_name_length::max_name_length_check_command::MaxNameLengthCheckArgs;
 use crate::cli::command::max_name_length::max_name_length_reset_command::MaxNameLengthResetArgs;
 use crate::cli::command::max_name_length::max_name_length_set_command::MaxNameLengthSetArgs;
 use crate::cli::command::max_name_length::max_name_length_show_command::MaxNameLengthShowArgs;
+use crate::cli::command::max_name_length::max_name_length_unit_command::MaxNameLengthUnitArgs;
 use crate::cli::to_args::ToArgs;
 use arbitrary::Arbitrary;
 use clap::Subcommand;
@@ -16,6 +18,12 @@ pub enum MaxNameLengthCommand {
 
     /// Reset the max name length to the default value and write it to the config file
     Reset(MaxNameLengthResetArgs),
+
+    /// List renamed targets that exceed the max name length
+    Check(MaxNameLengthCheckArgs),
+
+    /// Show or set whether the max name length counts bytes or characters
+    Unit(MaxNameLengthUnitArgs),
 }
 
 impl MaxNameLengthCommand {
@@ -27,6 +35,8 @@ impl MaxNameLengthCommand {
             MaxNameLengthCommand::Show(args) => args.invoke(),
             MaxNameLengthCommand::Set(args) => args.invoke(),
             MaxNameLengthCommand::Reset(args) => args.invoke(),
+            MaxNameLengthCommand::Check(args) => args.invoke(),
+            MaxNameLengthCommand::Unit(args) => args.invoke(),
         }
     }
 }
@@ -47,6 +57,14 @@ impl ToArgs for MaxNameLengthCommand {
                 args.push("reset".into());
                 args.extend(a.to_args());
             }
+            MaxNameLengthCommand::Check(a) => {
+                args.push("check".into());
+                args.extend(a.to_args());
+            }
+            MaxNameLengthCommand::Unit(a) => {
+                args.push("unit".into());
+                args.extend(a.to_args());
+            }
         }
         args
     }
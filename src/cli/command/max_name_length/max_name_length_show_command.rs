@@ -1,4 +1,5 @@
 use crate::cli::to_args::ToArgs;
+use crate::name_length_unit::name_length_unit;
 use arbitrary::Arbitrary;
 use clap::Args;
 use std::ffi::OsString;
@@ -13,8 +14,9 @@ impl MaxNameLengthShowArgs {
     /// This function does not return any errors.
     pub fn invoke(self) -> eyre::Result<()> {
         println!(
-            "Max name length: {}",
-            crate::MAX_NAME_LENGTH.load(Ordering::SeqCst)
+            "Max name length: {} {}",
+            crate::MAX_NAME_LENGTH.load(Ordering::SeqCst),
+            name_length_unit().name()
         );
         Ok(())
     }
@@ -0,0 +1,68 @@
+use crate::app_home::APP_HOME;
+use crate::cli::to_args::ToArgs;
+use crate::name_length_unit::NameLengthUnit;
+use crate::name_length_unit::name_length_unit;
+use crate::name_length_unit::set_name_length_unit;
+use arbitrary::Arbitrary;
+use clap::Args;
+use clap::ValueEnum;
+use std::ffi::OsString;
+
+/// What the max name length counts
+#[derive(ValueEnum, Arbitrary, Clone, Copy, PartialEq, Debug)]
+pub enum LengthUnit {
+    /// UTF-8 bytes, as file systems limit them
+    Bytes,
+    /// Characters, so an accented letter counts once
+    Chars,
+}
+
+impl std::fmt::Display for LengthUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bytes => write!(f, "bytes"),
+            Self::Chars => write!(f, "chars"),
+        }
+    }
+}
+
+impl From<LengthUnit> for NameLengthUnit {
+    fn from(value: LengthUnit) -> Self {
+        match value {
+            LengthUnit::Bytes => Self::Bytes,
+            LengthUnit::Chars => Self::Chars,
+        }
+    }
+}
+
+/// Show or set whether the max name length counts bytes or characters
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct MaxNameLengthUnitArgs {
+    /// Unit to set; shows the current one when omitted
+    #[clap(value_enum)]
+    pub unit: Option<LengthUnit>,
+}
+
+impl MaxNameLengthUnitArgs {
+    /// # Errors
+    ///
+    /// Returns an error if the unit cannot be saved.
+    pub fn invoke(self) -> eyre::Result<()> {
+        if let Some(unit) = self.unit {
+            set_name_length_unit(&APP_HOME, Some(unit.into()))?;
+            println!("Setting max name length unit to: {unit}");
+        } else {
+            println!("Max name length unit: {}", name_length_unit().name());
+        }
+        Ok(())
+    }
+}
+
+impl ToArgs for MaxNameLengthUnitArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        self.unit
+            .map(|unit| unit.to_string().into())
+            .into_iter()
+            .collect()
+    }
+}
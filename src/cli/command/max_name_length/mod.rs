@@ -1,7 +1,9 @@
+pub mod max_name_length_check_command;
 pub mod max_name_length_command;
 pub mod max_name_length_reset_command;
 pub mod max_name_length_set_command;
 pub mod max_name_length_show_command;
+pub mod max_name_length_unit_command;
 
 use crate::cli::command::max_name_length::max_name_length_command::MaxNameLengthCommand;
 use crate::cli::to_args::ToArgs;
@@ -1,17 +1,39 @@
+pub mod app_home;
 pub mod clean;
+pub mod completions;
+pub mod contact_sheet;
+pub mod doctor;
+pub mod duplicates;
 pub mod gui;
 pub mod input;
 pub mod max_name_length;
+pub mod plan;
+pub mod process;
 pub mod rename_rule;
 pub mod search;
+pub mod search_manifest;
 pub mod site;
+pub mod stats;
+pub mod user;
+pub mod version;
 
+use crate::cli::command::app_home::AppHomeArgs;
 use crate::cli::command::clean::clean_command::CleanArgs;
+use crate::cli::command::completions::completions_command::CompletionsArgs;
+use crate::cli::command::contact_sheet::contact_sheet_command::ContactSheetArgs;
+use crate::cli::command::doctor::doctor_command::DoctorArgs;
+use crate::cli::command::duplicates::duplicates_command::DuplicatesArgs;
 use crate::cli::command::gui::GuiArgs;
 use crate::cli::command::input::InputArgs;
 use crate::cli::command::max_name_length::MaxNameLengthArgs;
+use crate::cli::command::plan::plan_command::PlanArgs;
+use crate::cli::command::process::process_command::ProcessArgs;
 use crate::cli::command::search::search_command::SearchArgs;
+use crate::cli::command::search_manifest::search_manifest_command::SearchManifestArgs;
 use crate::cli::command::site::SiteArgs;
+use crate::cli::command::stats::stats_command::StatsArgs;
+use crate::cli::command::user::UserArgs;
+use crate::cli::command::version::version_command::VersionArgs;
 use crate::cli::to_args::ToArgs;
 use arbitrary::Arbitrary;
 use clap::Subcommand;
@@ -22,12 +44,18 @@ pub enum Command {
     /// Site related commands
     Site(SiteArgs),
 
+    /// Show or reset the persisted user id
+    User(UserArgs),
+
     /// Max name length commands
     MaxNameLength(MaxNameLengthArgs),
 
     /// Search
     Search(SearchArgs),
 
+    /// Match each input image to its top search result and write the matches to a file
+    SearchManifest(SearchManifestArgs),
+
     /// Inputs persistent list (add/list/remove)
     Input(InputArgs),
 
@@ -39,6 +67,33 @@ pub enum Command {
 
     /// Clean cached API responses
     Clean(CleanArgs),
+
+    /// Find identical files across inputs
+    Duplicates(DuplicatesArgs),
+
+    /// Process the input images without the GUI
+    Process(ProcessArgs),
+
+    /// Show what processing would do to each image without writing anything
+    Plan(PlanArgs),
+
+    /// Tile the processed outputs into one overview image
+    ContactSheet(ContactSheetArgs),
+
+    /// Generate shell completion scripts
+    Completions(CompletionsArgs),
+
+    /// Diagnose the environment and configuration
+    Doctor(DoctorArgs),
+
+    /// Summarize the inputs, rename rules and last batch
+    Stats(StatsArgs),
+
+    /// Locate the app home (config) directory
+    AppHome(AppHomeArgs),
+
+    /// Print version and build information
+    Version(VersionArgs),
 }
 impl Default for Command {
     fn default() -> Self {
@@ -53,12 +108,23 @@ impl Command {
     pub fn invoke(self) -> eyre::Result<()> {
         match self {
             Command::Site(args) => args.invoke(),
+            Command::User(args) => args.invoke(),
             Command::MaxNameLength(args) => args.invoke(),
             Command::Search(args) => args.invoke(),
+            Command::SearchManifest(args) => args.invoke(),
             Command::Input(args) => args.invoke(),
             Command::RenameRule(args) => args.invoke(),
             Command::Gui(args) => args.invoke(),
             Command::Clean(args) => args.invoke(),
+            Command::Duplicates(args) => args.invoke(),
+            Command::Process(args) => args.invoke(),
+            Command::Plan(args) => args.invoke(),
+            Command::ContactSheet(args) => args.invoke(),
+            Command::Completions(args) => args.invoke(),
+            Command::Doctor(args) => args.invoke(),
+            Command::Stats(args) => args.invoke(),
+            Command::AppHome(args) => args.invoke(),
+            Command::Version(args) => args.invoke(),
         }
     }
 }
@@ -71,6 +137,10 @@ impl ToArgs for Command {
                 args.push("site".into());
                 args.extend(site_args.to_args());
             }
+            Command::User(user_args) => {
+                args.push("user".into());
+                args.extend(user_args.to_args());
+            }
             Command::MaxNameLength(max_args) => {
                 args.push("max-name-length".into());
                 args.extend(max_args.to_args());
@@ -79,6 +149,10 @@ impl ToArgs for Command {
                 args.push("search".into());
                 args.extend(search_args.to_args());
             }
+            Command::SearchManifest(manifest_args) => {
+                args.push("search-manifest".into());
+                args.extend(manifest_args.to_args());
+            }
             Command::Input(input_args) => {
                 args.push("input".into());
                 args.extend(input_args.to_args());
@@ -95,6 +169,42 @@ impl ToArgs for Command {
                 args.push("clean".into());
                 args.extend(clean_args.to_args());
             }
+            Command::Duplicates(duplicates_args) => {
+                args.push("duplicates".into());
+                args.extend(duplicates_args.to_args());
+            }
+            Command::Process(process_args) => {
+                args.push("process".into());
+                args.extend(process_args.to_args());
+            }
+            Command::Plan(plan_args) => {
+                args.push("plan".into());
+                args.extend(plan_args.to_args());
+            }
+            Command::ContactSheet(contact_sheet_args) => {
+                args.push("contact-sheet".into());
+                args.extend(contact_sheet_args.to_args());
+            }
+            Command::Completions(completions_args) => {
+                args.push("completions".into());
+                args.extend(completions_args.to_args());
+            }
+            Command::Doctor(doctor_args) => {
+                args.push("doctor".into());
+                args.extend(doctor_args.to_args());
+            }
+            Command::Stats(stats_args) => {
+                args.push("stats".into());
+                args.extend(stats_args.to_args());
+            }
+            Command::AppHome(app_home_args) => {
+                args.push("app-home".into());
+                args.extend(app_home_args.to_args());
+            }
+            Command::Version(version_args) => {
+                args.push("version".into());
+                args.extend(version_args.to_args());
+            }
         }
         args
     }
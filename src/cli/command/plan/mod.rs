@@ -0,0 +1,3 @@
+//! `cm plan` command to show what a process run would do without writing anything.
+
+pub mod plan_command;
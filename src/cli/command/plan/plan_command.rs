@@ -0,0 +1,55 @@
+use crate::app_home::APP_HOME;
+use crate::cli::command::process::processing_args::ProcessingArgs;
+use crate::cli::to_args::ToArgs;
+use crate::pipeline;
+use crate::pipeline::PipelineConfig;
+use arbitrary::Arbitrary;
+use clap::Args;
+use std::ffi::OsString;
+
+/// List every input image with its renamed name, output path, crop, estimated size and
+/// collisions, as CSV or JSON, without writing anything
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct PlanArgs {
+    #[clap(flatten)]
+    pub processing: ProcessingArgs,
+    /// Print the plan as JSON instead of CSV
+    #[clap(long)]
+    pub json: bool,
+}
+
+impl PlanArgs {
+    /// # Errors
+    ///
+    /// Returns an error if the filter is invalid, the inputs or rename rules cannot be loaded,
+    /// or the worker pool cannot be started.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let mut config = PipelineConfig::from_app_home(&APP_HOME)?;
+        self.processing.apply_to(&mut config)?;
+        config.settings.dry_run = true;
+        let plan = pipeline::plan(&config)?;
+        let planned = pipeline::preview(&config, &plan)?;
+        if self.json {
+            println!("{}", facet_json::to_string(&planned)?);
+        } else {
+            print!("{}", pipeline::preview_to_csv(&planned));
+        }
+        let collisions = planned.iter().filter(|p| p.collides).count();
+        if collisions > 0 {
+            eprintln!(
+                "{collisions} images would be written to an output path another image also uses"
+            );
+        }
+        Ok(())
+    }
+}
+
+impl ToArgs for PlanArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args = self.processing.to_args();
+        if self.json {
+            args.push("--json".into());
+        }
+        args
+    }
+}
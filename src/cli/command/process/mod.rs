@@ -0,0 +1,4 @@
+//! `cm process` command to process the input images without the GUI.
+
+pub mod process_command;
+pub mod processing_args;
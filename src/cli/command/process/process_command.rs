@@ -0,0 +1,381 @@
+use crate::app_home::APP_HOME;
+use crate::atomic_write::write_atomic;
+use crate::cli::command::process::processing_args::ProcessingArgs;
+use crate::cli::to_args::ToArgs;
+use crate::cli::to_args::arbitrary_optional_path;
+use crate::cli::to_args::flag_value;
+use crate::conversion_report::ConversionReport;
+use crate::image_processing::DecodeErrorPolicy;
+use crate::image_processing::OutputConflictPolicy;
+use crate::image_processing::ProcessProgress;
+use crate::image_processing::ProcessReport;
+use crate::last_batch::LastBatch;
+use crate::last_batch::save_last_batch;
+use crate::pipeline;
+use crate::pipeline::PipelineConfig;
+use crate::quiet::is_quiet;
+use arbitrary::Arbitrary;
+use clap::Args;
+use clap::ValueEnum;
+use image::ImageFormat;
+use std::ffi::OsString;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use tracing::warn;
+
+/// What to do with inputs that exist but can't be decoded as images
+#[derive(ValueEnum, Arbitrary, Clone, Copy, PartialEq, Debug)]
+pub enum OnDecodeError {
+    /// Count them as failures
+    Error,
+    /// Leave them out of the outputs
+    Skip,
+    /// Copy their original bytes to the output path
+    CopyThrough,
+}
+
+impl std::fmt::Display for OnDecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Skip => write!(f, "skip"),
+            Self::CopyThrough => write!(f, "copy-through"),
+        }
+    }
+}
+
+impl From<OnDecodeError> for DecodeErrorPolicy {
+    fn from(value: OnDecodeError) -> Self {
+        match value {
+            OnDecodeError::Error => Self::Error,
+            OnDecodeError::Skip => Self::Skip,
+            OnDecodeError::CopyThrough => Self::CopyThrough,
+        }
+    }
+}
+
+/// What to do when an output file already exists
+#[derive(ValueEnum, Arbitrary, Clone, Copy, PartialEq, Debug)]
+pub enum OnConflict {
+    /// Replace it
+    Overwrite,
+    /// Keep it and leave the input out
+    Skip,
+    /// Write the new output as `name (1).ext`, `name (2).ext`, ...
+    Rename,
+}
+
+impl std::fmt::Display for OnConflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Overwrite => write!(f, "overwrite"),
+            Self::Skip => write!(f, "skip"),
+            Self::Rename => write!(f, "rename"),
+        }
+    }
+}
+
+impl From<OnConflict> for OutputConflictPolicy {
+    fn from(value: OnConflict) -> Self {
+        match value {
+            OnConflict::Overwrite => Self::Overwrite,
+            OnConflict::Skip => Self::Skip,
+            OnConflict::Rename => Self::Rename,
+        }
+    }
+}
+
+/// How to report progress while processing
+#[derive(ValueEnum, Arbitrary, Clone, Copy, PartialEq, Debug)]
+pub enum ProgressFormat {
+    /// `[current/total] path` lines on stdout
+    Text,
+    /// One JSON object per finished file (`index`, `total`, `path`, `ok`, `error`) on stderr
+    Ndjson,
+}
+
+impl std::fmt::Display for ProgressFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Text => write!(f, "text"),
+            Self::Ndjson => write!(f, "ndjson"),
+        }
+    }
+}
+
+/// Format to convert outputs to
+#[derive(ValueEnum, Arbitrary, Clone, Copy, PartialEq, Debug)]
+pub enum OutputFormat {
+    Jpeg,
+    Png,
+    Webp,
+    /// Requires the `avif` feature
+    Avif,
+}
+
+impl std::fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Jpeg => write!(f, "jpeg"),
+            Self::Png => write!(f, "png"),
+            Self::Webp => write!(f, "webp"),
+            Self::Avif => write!(f, "avif"),
+        }
+    }
+}
+
+impl From<OutputFormat> for ImageFormat {
+    fn from(value: OutputFormat) -> Self {
+        match value {
+            OutputFormat::Jpeg => Self::Jpeg,
+            OutputFormat::Png => Self::Png,
+            OutputFormat::Webp => Self::WebP,
+            OutputFormat::Avif => Self::Avif,
+        }
+    }
+}
+
+/// Process the input images (rename, optionally crop) into `<input>-output` directories
+#[expect(clippy::struct_excessive_bools)]
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct ProcessArgs {
+    #[clap(flatten)]
+    pub processing: ProcessingArgs,
+    /// Process every image and report what would be written (output paths, crops, renames)
+    /// without creating directories or writing outputs
+    #[clap(long)]
+    pub dry_run: bool,
+    /// With `--dry-run`, only list the images and their new names instead of decoding them
+    #[clap(long)]
+    pub fast: bool,
+    /// Write a JSON report of each processed file (crop bounds, dimensions, size) to this path
+    #[clap(long)]
+    #[arbitrary(with = arbitrary_optional_path)]
+    pub report: Option<PathBuf>,
+    /// Hash each output with BLAKE3, writing `<output>.blake3` next to it and adding the hash to
+    /// `--report`
+    #[clap(long)]
+    pub hash: bool,
+    /// Write each file's input size vs output size with totals and percent saved to this path,
+    /// as CSV or as JSON when it ends in `.json`
+    #[clap(long)]
+    #[arbitrary(with = arbitrary_optional_path)]
+    pub conversion_report: Option<PathBuf>,
+    /// How to report progress; `ndjson` keeps stdout free for the summary
+    #[clap(long, value_enum, default_value_t = ProgressFormat::Text)]
+    pub progress_format: ProgressFormat,
+    /// Only process images whose source or settings changed since their output was written,
+    /// tracked by a `.cmstamp` file next to each output
+    #[clap(long)]
+    pub incremental: bool,
+    /// Delete stamped outputs whose source was removed or renamed; implies `--incremental`
+    #[clap(long)]
+    pub prune: bool,
+}
+
+impl ProcessArgs {
+    /// # Errors
+    ///
+    /// Returns an error if the filter is invalid, the inputs or rename rules cannot be loaded,
+    /// the worker pool cannot be started, or the report cannot be written.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let mut config = PipelineConfig::from_app_home(&APP_HOME)?;
+        self.processing.apply_to(&mut config)?;
+        config.settings.hash_outputs = self.hash;
+        config.settings.dry_run = self.dry_run;
+        let plan = pipeline::plan(&config)?;
+        if config.filter.is_some() {
+            println!(
+                "{} of {} images match the filter",
+                plan.files.len(),
+                plan.total
+            );
+        }
+
+        if self.dry_run && self.fast {
+            for (original, target) in plan.files.iter().zip(&plan.renamed) {
+                println!("Would process: {}", original.display());
+                if original.file_name() != target.file_name()
+                    && let Some(name) = target.file_name()
+                {
+                    println!("    as {}", name.to_string_lossy());
+                }
+            }
+            return Ok(());
+        }
+
+        if self.incremental || self.prune {
+            let ensured = pipeline::ensure_processed(&config, &plan, self.prune)?;
+            for (path, error) in &ensured.errors {
+                eprintln!("Error: {}: {error}", path.display());
+            }
+            let verb = if self.dry_run { "Would write" } else { "Wrote" };
+            println!(
+                "{verb} {} outputs, {} already up to date ({} failed)",
+                ensured.written.len(),
+                ensured.up_to_date,
+                ensured.errors.len()
+            );
+            if !ensured.pruned.is_empty() {
+                let verb = if self.dry_run {
+                    "Would prune"
+                } else {
+                    "Pruned"
+                };
+                println!("{verb} {} orphaned outputs", ensured.pruned.len());
+            }
+            return Ok(());
+        }
+
+        let result = match self.progress_format {
+            ProgressFormat::Text => {
+                let progress = |current: usize, total: usize, path: &Path| {
+                    println!("[{current}/{total}] {}", path.display());
+                };
+                let progress: Option<&(dyn Fn(usize, usize, &Path) + Sync)> =
+                    (!is_quiet()).then_some(&progress);
+                pipeline::execute(&config, &plan, progress, None)?
+            }
+            ProgressFormat::Ndjson => {
+                let (sender, receiver) = mpsc::channel();
+                std::thread::scope(|scope| {
+                    scope.spawn(|| print_ndjson_progress(receiver));
+                    let result = pipeline::execute(&config, &plan, None, Some(&sender));
+                    // Closing the channel lets the printer finish
+                    drop(sender);
+                    result
+                })?
+            }
+        };
+        for (path, error) in &result.errors {
+            eprintln!("Error: {}: {error}", path.display());
+        }
+        for entry in &result.report {
+            if let Some(warning) = &entry.warning {
+                eprintln!("Warning: {}: {warning}", entry.input);
+            }
+            if let Some(warning) = &entry.color_profile {
+                eprintln!("Warning: {}: {warning}", entry.input);
+            }
+        }
+        if self.dry_run {
+            for entry in &result.report {
+                let crop = match &entry.crop_bounds {
+                    Some(b) if entry.was_cropped => {
+                        format!(
+                            ", cropped to {}x{} at ({}, {})",
+                            b.width, b.height, b.x, b.y
+                        )
+                    }
+                    _ => String::new(),
+                };
+                println!(
+                    "Would write: {} -> {} ({}x{}, ~{} bytes{crop})",
+                    entry.input,
+                    entry.output,
+                    entry.output_width,
+                    entry.output_height,
+                    entry.estimated_size
+                );
+            }
+            println!(
+                "Would process {} images ({} failed); nothing was written",
+                result.processed_count, result.error_count
+            );
+        } else {
+            println!(
+                "Processed {} images ({} failed)",
+                result.processed_count, result.error_count
+            );
+            let last_batch = LastBatch {
+                processed: result.processed_count,
+                errors: result.error_count,
+                output_bytes: result.report.iter().map(|e| e.estimated_size).sum(),
+            };
+            if let Err(e) = save_last_batch(&APP_HOME, &last_batch) {
+                warn!("Failed to save last batch summary: {}", e);
+            }
+        }
+        if result.skipped_count > 0 || result.copied_count > 0 {
+            println!(
+                "Unreadable files: {} skipped, {} copied unchanged",
+                result.skipped_count, result.copied_count
+            );
+        }
+        if result.existing_count > 0 {
+            println!("Kept {} existing outputs unchanged", result.existing_count);
+        }
+        if let Some(path) = &self.conversion_report {
+            let conversion = ConversionReport::from_process_report(&result.report);
+            let is_json = path
+                .extension()
+                .is_some_and(|e| e.eq_ignore_ascii_case("json"));
+            let contents = if is_json {
+                facet_json::to_string(&conversion)?
+            } else {
+                conversion.to_csv()
+            };
+            write_atomic(path, contents)?;
+            println!(
+                "Inputs {} bytes, outputs {} bytes ({:.1}% saved); wrote {}",
+                conversion.total_input_bytes,
+                conversion.total_output_bytes,
+                conversion.percent_saved,
+                path.display()
+            );
+        }
+        if let Some(path) = &self.report {
+            let report = ProcessReport::from(result);
+            write_atomic(path, facet_json::to_string(&report)?)?;
+            println!("Wrote report to {}", path.display());
+        }
+        Ok(())
+    }
+}
+
+/// Print each finished input of `events` as a JSON line on stderr
+fn print_ndjson_progress(events: mpsc::Receiver<ProcessProgress>) {
+    let mut total = 0;
+    for event in events {
+        if let ProcessProgress::Started { total: started } = event {
+            total = started;
+        }
+        if let Some(line) = event.progress_line(total) {
+            match facet_json::to_string(&line) {
+                Ok(json) => eprintln!("{json}"),
+                Err(e) => eprintln!("Failed to serialize progress: {e}"),
+            }
+        }
+    }
+}
+
+impl ToArgs for ProcessArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args = self.processing.to_args();
+        if self.dry_run {
+            args.push("--dry-run".into());
+        }
+        if self.fast {
+            args.push("--fast".into());
+        }
+        if let Some(report) = &self.report {
+            args.push(flag_value("--report", report));
+        }
+        if self.hash {
+            args.push("--hash".into());
+        }
+        if let Some(conversion_report) = &self.conversion_report {
+            args.push(flag_value("--conversion-report", conversion_report));
+        }
+        args.push("--progress-format".into());
+        args.push(self.progress_format.to_string().into());
+        if self.incremental {
+            args.push("--incremental".into());
+        }
+        if self.prune {
+            args.push("--prune".into());
+        }
+        args
+    }
+}
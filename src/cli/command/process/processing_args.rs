@@ -0,0 +1,164 @@
+//! Flags shared by `cm process` and `cm plan`, so a plan is made with the same settings a run
+//! would use
+
+use crate::cli::command::process::process_command::OnConflict;
+use crate::cli::command::process::process_command::OnDecodeError;
+use crate::cli::command::process::process_command::OutputFormat;
+use crate::cli::to_args::ToArgs;
+use crate::cli::to_args::flag_value;
+use crate::image_processing::ProcessingSettings;
+use crate::inputs::NameFilter;
+use crate::pipeline::PipelineConfig;
+use arbitrary::Arbitrary;
+use clap::ArgAction;
+use clap::Args;
+use std::ffi::OsString;
+
+/// Which images to take and how to write them
+#[expect(clippy::struct_excessive_bools)]
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct ProcessingArgs {
+    /// Only process images whose file name matches this glob (e.g. `*-front.*`)
+    #[clap(long)]
+    pub filter: Option<String>,
+    /// Crop whitespace/transparency from images; `--crop=false` turns off a config default
+    #[clap(
+        long,
+        num_args = 0..=1,
+        require_equals = true,
+        default_value_t = false,
+        default_missing_value = "true",
+        action = ArgAction::Set
+    )]
+    pub crop: bool,
+    /// Threshold value for crop detection (0-255)
+    #[clap(long, default_value_t = 20)]
+    pub crop_threshold: u8,
+    /// JPEG quality (1-100)
+    #[clap(long, default_value_t = 90)]
+    pub jpeg_quality: u8,
+    /// Write progressive JPEGs, which render coarse-to-fine while loading;
+    /// `--jpeg-progressive=false` turns off a config default
+    #[clap(
+        long,
+        num_args = 0..=1,
+        require_equals = true,
+        default_value_t = false,
+        default_missing_value = "true",
+        action = ArgAction::Set
+    )]
+    pub jpeg_progressive: bool,
+    /// PNG deflate level (1-9, higher is smaller but slower; 0 uses the encoder default)
+    #[clap(long, default_value_t = 0)]
+    pub png_compression: u8,
+    /// Reduce PNG outputs to an indexed palette of at most 256 colors
+    #[clap(long)]
+    pub png_quantize: bool,
+    /// Convert outputs to this format instead of keeping each input's format
+    #[clap(long, value_enum)]
+    pub output_format: Option<OutputFormat>,
+    /// AVIF quality (1-100)
+    #[clap(long, default_value_t = 80)]
+    pub avif_quality: u8,
+    /// AVIF encoder speed (1-10, lower is smaller but slower)
+    #[clap(long, default_value_t = 6)]
+    pub avif_speed: u8,
+    /// What to do with files that exist but can't be decoded as images
+    #[clap(long, value_enum, default_value_t = OnDecodeError::Error)]
+    pub on_decode_error: OnDecodeError,
+    /// What to do when an output file already exists
+    #[clap(long, value_enum, default_value_t = OnConflict::Overwrite)]
+    pub on_conflict: OnConflict,
+    /// Leave animated GIF/WebP inputs out instead of processing their first frame
+    #[clap(long)]
+    pub skip_animated: bool,
+    /// Convert inputs with a non-sRGB embedded color profile (e.g. Display P3) to sRGB; needs
+    /// the `lcms2` feature, without it such inputs are only warned about
+    #[clap(long)]
+    pub convert_to_srgb: bool,
+    /// Write all outputs into one flat directory, folding subdirectories into the file names
+    /// (`sub_dir_name.png`) and numbering any names that still repeat
+    #[clap(long)]
+    pub flatten: bool,
+    /// How many images to process at once (defaults to the number of CPUs)
+    #[clap(long)]
+    pub jobs: Option<usize>,
+}
+
+impl ProcessingArgs {
+    /// Set the filter, worker count and settings of `config` from these flags, keeping the
+    /// settings the flags don't cover
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the filter is invalid.
+    pub fn apply_to(&self, config: &mut PipelineConfig) -> eyre::Result<()> {
+        config.filter = self.filter.as_deref().map(NameFilter::parse).transpose()?;
+        config.jobs = self.jobs;
+        config.settings = ProcessingSettings {
+            crop_to_content: self.crop,
+            crop_threshold: self.crop_threshold,
+            jpeg_quality: self.jpeg_quality,
+            jpeg_progressive: self.jpeg_progressive,
+            png_compression: self.png_compression,
+            png_quantize: self.png_quantize,
+            output_format: self.output_format.map(Into::into),
+            avif_quality: self.avif_quality,
+            avif_speed: self.avif_speed,
+            on_decode_error: self.on_decode_error.into(),
+            on_conflict: self.on_conflict.into(),
+            skip_animated: self.skip_animated,
+            convert_to_srgb: self.convert_to_srgb,
+            flatten: self.flatten,
+            ..config.settings.clone()
+        };
+        Ok(())
+    }
+}
+
+impl ToArgs for ProcessingArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args = Vec::new();
+        if let Some(filter) = &self.filter {
+            args.push(flag_value("--filter", filter));
+        }
+        // Always explicit, so a config default can't turn a switch back on when these are parsed
+        args.push(format!("--crop={}", self.crop).into());
+        args.push("--crop-threshold".into());
+        args.push(self.crop_threshold.to_string().into());
+        args.push("--jpeg-quality".into());
+        args.push(self.jpeg_quality.to_string().into());
+        args.push(format!("--jpeg-progressive={}", self.jpeg_progressive).into());
+        args.push("--png-compression".into());
+        args.push(self.png_compression.to_string().into());
+        if self.png_quantize {
+            args.push("--png-quantize".into());
+        }
+        if let Some(format) = self.output_format {
+            args.push("--output-format".into());
+            args.push(format.to_string().into());
+        }
+        args.push("--avif-quality".into());
+        args.push(self.avif_quality.to_string().into());
+        args.push("--avif-speed".into());
+        args.push(self.avif_speed.to_string().into());
+        args.push("--on-decode-error".into());
+        args.push(self.on_decode_error.to_string().into());
+        args.push("--on-conflict".into());
+        args.push(self.on_conflict.to_string().into());
+        if self.skip_animated {
+            args.push("--skip-animated".into());
+        }
+        if self.convert_to_srgb {
+            args.push("--convert-to-srgb".into());
+        }
+        if self.flatten {
+            args.push("--flatten".into());
+        }
+        if let Some(jobs) = self.jobs {
+            args.push("--jobs".into());
+            args.push(jobs.to_string().into());
+        }
+        args
+    }
+}
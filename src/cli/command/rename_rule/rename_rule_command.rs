@@ -4,6 +4,8 @@ use crate::rename_rules::RenameRule;
 use crate::rename_rules::add_rule;
 use crate::rename_rules::list_rules;
 use crate::rename_rules::remove_rule;
+use crate::rename_rules::rules_enabled;
+use crate::rename_rules::set_rules_enabled;
 use arbitrary::Arbitrary;
 use clap::Args;
 use clap::Subcommand;
@@ -16,6 +18,12 @@ pub enum RenameRuleCommand {
     /// Add a rename rule
     Add(RenameRuleAddArgs),
 
+    /// Disable all rename rules without removing them
+    Disable(RenameRuleDisableArgs),
+
+    /// Enable rename rules globally
+    Enable(RenameRuleEnableArgs),
+
     /// List rules
     List(RenameRuleListArgs),
 
@@ -24,6 +32,9 @@ pub enum RenameRuleCommand {
 
     /// Remove rule by id or --all
     Remove(RenameRuleRemoveArgs),
+
+    /// Show whether rename rules are enabled and list them
+    Status(RenameRuleStatusArgs),
 }
 
 impl RenameRuleCommand {
@@ -33,9 +44,12 @@ impl RenameRuleCommand {
     pub fn invoke(self) -> eyre::Result<()> {
         match self {
             RenameRuleCommand::Add(a) => a.invoke(),
+            RenameRuleCommand::Disable(a) => a.invoke(),
+            RenameRuleCommand::Enable(a) => a.invoke(),
             RenameRuleCommand::List(a) => a.invoke(),
             RenameRuleCommand::Path(a) => a.invoke(),
             RenameRuleCommand::Remove(a) => a.invoke(),
+            RenameRuleCommand::Status(a) => a.invoke(),
         }
     }
 }
@@ -48,6 +62,14 @@ impl ToArgs for RenameRuleCommand {
                 args.push("add".into());
                 args.extend(a.to_args());
             }
+            RenameRuleCommand::Disable(a) => {
+                args.push("disable".into());
+                args.extend(a.to_args());
+            }
+            RenameRuleCommand::Enable(a) => {
+                args.push("enable".into());
+                args.extend(a.to_args());
+            }
             RenameRuleCommand::List(a) => {
                 args.push("list".into());
                 args.extend(a.to_args());
@@ -60,6 +82,10 @@ impl ToArgs for RenameRuleCommand {
                 args.push("remove".into());
                 args.extend(a.to_args());
             }
+            RenameRuleCommand::Status(a) => {
+                args.push("status".into());
+                args.extend(a.to_args());
+            }
         }
         args
     }
@@ -104,10 +130,7 @@ impl RenameRuleAddArgs {
 
 impl ToArgs for RenameRuleAddArgs {
     fn to_args(&self) -> Vec<OsString> {
-        let mut rtn = vec![
-            OsString::from(self.find.clone()),
-            OsString::from(self.replace.clone()),
-        ];
+        let mut rtn = Vec::new();
         if self.only_when_too_long {
             rtn.push("--only-when-too-long".into());
         }
@@ -117,6 +140,11 @@ impl ToArgs for RenameRuleAddArgs {
         if self.disabled {
             rtn.push("--disabled".into());
         }
+        rtn.extend([
+            "--".into(),
+            OsString::from(self.find.clone()),
+            OsString::from(self.replace.clone()),
+        ]);
         rtn
     }
 }
@@ -131,6 +159,9 @@ impl RenameRuleListArgs {
     pub fn invoke(self) -> eyre::Result<()> {
         let listed = list_rules(&APP_HOME)?;
         info!("Found {} rename rules", listed.len());
+        if !rules_enabled(&APP_HOME)? {
+            info!("Rename rules are globally disabled");
+        }
         for (_i, rule) in listed {
             println!("{}: {}", rule.id, rule);
         }
@@ -198,6 +229,7 @@ impl ToArgs for RenameRuleRemoveArgs {
             rtn.push("--all".into());
         }
         if let Some(id) = &self.id {
+            rtn.push("--".into());
             rtn.push(OsString::from(id.clone()));
         }
         rtn
@@ -223,3 +255,70 @@ impl ToArgs for RenameRulePathArgs {
         vec![]
     }
 }
+
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct RenameRuleEnableArgs {}
+
+impl RenameRuleEnableArgs {
+    /// # Errors
+    ///
+    /// Returns an error if the enabled flag cannot be written.
+    pub fn invoke(self) -> eyre::Result<()> {
+        set_rules_enabled(&APP_HOME, true)?;
+        println!("Rename rules enabled");
+        Ok(())
+    }
+}
+
+impl ToArgs for RenameRuleEnableArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        vec![]
+    }
+}
+
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct RenameRuleDisableArgs {}
+
+impl RenameRuleDisableArgs {
+    /// # Errors
+    ///
+    /// Returns an error if the enabled flag cannot be written.
+    pub fn invoke(self) -> eyre::Result<()> {
+        set_rules_enabled(&APP_HOME, false)?;
+        println!("Rename rules disabled");
+        Ok(())
+    }
+}
+
+impl ToArgs for RenameRuleDisableArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        vec![]
+    }
+}
+
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct RenameRuleStatusArgs {}
+
+impl RenameRuleStatusArgs {
+    /// # Errors
+    ///
+    /// Returns an error if the enabled flag or the rename rules cannot be read.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let enabled = rules_enabled(&APP_HOME)?;
+        println!(
+            "Rename rules are {}",
+            if enabled { "enabled" } else { "disabled" }
+        );
+        for (_i, rule) in list_rules(&APP_HOME)? {
+            let state = if rule.enabled { "" } else { " (disabled)" };
+            println!("{}: {}{}", rule.id, rule, state);
+        }
+        Ok(())
+    }
+}
+
+impl ToArgs for RenameRuleStatusArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        vec![]
+    }
+}
@@ -1,2 +1,4 @@
+pub mod result_field;
 pub mod search_command;
 pub mod search_result_ok;
+pub mod suggest;
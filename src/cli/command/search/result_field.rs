@@ -0,0 +1,77 @@
+//! Selectable `ResultItem` fields for `cm search --fields`
+
+use crate::cli::command::search::search_result_ok::ResultItem;
+use arbitrary::Arbitrary;
+use clap::ValueEnum;
+
+/// A single `ResultItem` field that can be projected into the output
+#[derive(ValueEnum, Arbitrary, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ResultField {
+    Uid,
+    Sku,
+    Name,
+    Url,
+    Price,
+    Msrp,
+    ImageUrl,
+    ThumbnailImageUrl,
+    Rating,
+    RatingCount,
+    Description,
+    StockMessage,
+    Brand,
+    Popularity,
+}
+
+impl ResultField {
+    /// The key used for this field in the Searchspring JSON response
+    #[must_use]
+    pub fn json_key(self) -> &'static str {
+        match self {
+            Self::Uid => "uid",
+            Self::Sku => "sku",
+            Self::Name => "name",
+            Self::Url => "url",
+            Self::Price => "price",
+            Self::Msrp => "msrp",
+            Self::ImageUrl => "imageUrl",
+            Self::ThumbnailImageUrl => "thumbnailImageUrl",
+            Self::Rating => "rating",
+            Self::RatingCount => "ratingCount",
+            Self::Description => "description",
+            Self::StockMessage => "stockMessage",
+            Self::Brand => "brand",
+            Self::Popularity => "popularity",
+        }
+    }
+
+    /// The value of this field on `item`, if present
+    #[must_use]
+    pub fn value(self, item: &ResultItem) -> Option<&str> {
+        match self {
+            Self::Uid => item.uid.as_ref().map(|v| v.0.as_str()),
+            Self::Sku => item.sku.as_ref().map(|v| v.0.as_str()),
+            Self::Name => item.name.as_deref(),
+            Self::Url => item.url.as_ref().map(|v| v.0.as_str()),
+            Self::Price => item.price.as_ref().map(|v| v.0.as_str()),
+            Self::Msrp => item.msrp.as_deref(),
+            Self::ImageUrl => item.image_url.as_deref(),
+            Self::ThumbnailImageUrl => item.thumbnail_image_url.as_deref(),
+            Self::Rating => item.rating.as_deref(),
+            Self::RatingCount => item.rating_count.as_deref(),
+            Self::Description => item.description.as_deref(),
+            Self::StockMessage => item.stock_message.as_deref(),
+            Self::Brand => item.brand.as_deref(),
+            Self::Popularity => item.popularity.as_deref(),
+        }
+    }
+}
+
+impl std::fmt::Display for ResultField {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value = self
+            .to_possible_value()
+            .expect("ResultField has no skipped variants");
+        write!(f, "{}", value.get_name())
+    }
+}
@@ -1,9 +1,15 @@
-use crate::SESSION_ID;
-use crate::SITE_ID;
-use crate::USER_ID;
 use crate::cache::CacheEntry;
+use crate::cli::command::search::result_field::ResultField;
 use crate::cli::command::search::search_result_ok::SearchResultOk;
 use crate::cli::to_args::ToArgs;
+use crate::cli::to_args::flag_value;
+use crate::offline::is_offline;
+use crate::search_rate_limit::wait_for_search_slot;
+use crate::session_id::current_session_id;
+use crate::site_host::host_for_site;
+use crate::site_host::site_host;
+use crate::site_id::current_site_id;
+use crate::user_id::current_user_id;
 use arbitrary::Arbitrary;
 use clap::Args;
 use clap::ValueEnum;
@@ -21,10 +27,11 @@ use tracing::span;
 /// Global mutex to serialize product searches (maximizes cache hits when multiple images share SKUs)
 static SEARCH_MUTEX: LazyLock<Mutex<()>> = LazyLock::new(|| Mutex::new(()));
 
-#[derive(ValueEnum, Arbitrary, Clone, PartialEq, Debug)]
+#[derive(ValueEnum, Arbitrary, Clone, Copy, PartialEq, Debug)]
 pub enum OutputFormat {
     Auto,
     Json,
+    Jsonl,
     Pretty,
 }
 
@@ -33,6 +40,7 @@ impl std::fmt::Display for OutputFormat {
         match self {
             Self::Auto => write!(f, "auto"),
             Self::Json => write!(f, "json"),
+            Self::Jsonl => write!(f, "jsonl"),
             Self::Pretty => write!(f, "pretty"),
         }
     }
@@ -43,16 +51,19 @@ impl std::fmt::Display for OutputFormat {
 pub struct SearchArgs {
     /// Query to search for
     pub query: Option<String>,
-    /// SKU to search for
+    /// SKU to search for (repeat to search several SKUs at once)
     #[clap(long)]
-    pub sku: Option<String>,
+    pub sku: Vec<String>,
     /// Bypass the cache and fetch fresh data
     #[clap(long)]
     #[arbitrary(value = false)]
     pub no_cache: bool,
-    /// Output mode: auto|json|pretty
+    /// Output mode: auto|json|jsonl|pretty (jsonl prints one result item per line)
     #[clap(long, value_enum, default_value_t = OutputFormat::Auto)]
     pub output: OutputFormat,
+    /// Only emit these result item fields, e.g. `--fields name,sku,price`
+    #[clap(long, value_enum, value_delimiter = ',')]
+    pub fields: Vec<ResultField>,
 }
 
 impl SearchArgs {
@@ -74,14 +85,23 @@ impl SearchArgs {
                 other => other,
             } {
                 OutputFormat::Auto => unreachable!("output was resolved from Auto earlier"),
-                OutputFormat::Pretty => {
+                OutputFormat::Pretty if self.fields.is_empty() => {
                     println!("{}", result.pretty());
                 }
-                OutputFormat::Json => {
+                OutputFormat::Json if self.fields.is_empty() => {
                     let json = facet_json::to_string(&result)
                         .map_err(|e| eyre::eyre!("Failed to serialize result: {}", e))?;
                     println!("{json}");
                 }
+                OutputFormat::Json => {
+                    let lines = self.result_lines(&result)?;
+                    println!("[{}]", lines.join(","));
+                }
+                OutputFormat::Jsonl | OutputFormat::Pretty => {
+                    for line in self.result_lines(&result)? {
+                        println!("{line}");
+                    }
+                }
             }
 
             eyre::Ok(())
@@ -90,11 +110,29 @@ impl SearchArgs {
         Ok(())
     }
 
+    /// One JSON object per result item, projected onto `--fields` when any were given.
+    fn result_lines(&self, result: &SearchResultOk) -> eyre::Result<Vec<String>> {
+        let items = result.results.as_deref().unwrap_or_default();
+        items
+            .iter()
+            .map(|item| {
+                if self.fields.is_empty() {
+                    facet_json::to_string(item)
+                        .map_err(|e| eyre::eyre!("Failed to serialize result item: {}", e))
+                } else {
+                    item.project(&self.fields)
+                }
+            })
+            .collect()
+    }
+
     /// Perform a search against the Searchspring API.
     /// <https://docs.searchspring.com/reference/get-search>
     ///
     /// Note: Searches are serialized via a global mutex to maximize cache hits
-    /// when multiple images share the same SKU.
+    /// when multiple images share the same SKU. Requests that miss the cache are
+    /// spaced out to the configured search rate limit. In offline mode only the
+    /// cache is consulted and no request is ever issued.
     ///
     /// # Errors
     ///
@@ -104,10 +142,11 @@ impl SearchArgs {
         let _guard = SEARCH_MUTEX.lock().await;
 
         let query = self.query.as_deref().unwrap_or_default();
-        let site_id = SITE_ID.as_str().to_string();
-        let user = USER_ID.as_uuid().to_string();
-        let session = SESSION_ID.as_uuid().to_string();
-        let url = format!("https://{site_id}.a.searchspring.io/api/search/search.json");
+        let site_id = current_site_id().0;
+        let user = current_user_id().0.to_string();
+        let session = current_session_id().0.to_string();
+        let host = site_host();
+        let url = Self::endpoint(&host, &site_id);
         let git_rev = option_env!("GIT_REVISION").unwrap_or("unknown");
         let user_agent = format!(
             "{} v{} (rev {}) (+https://github.com/TeamDman/cm)",
@@ -127,41 +166,37 @@ impl SearchArgs {
             session,
             response_status = Empty,
         );
-        let mut query_params = vec![
-            // ("lastViewed", "664269"),
-            ("userId", user.as_str()),
-            ("siteId", site_id.as_str()),
-            ("sessionId", session.as_str()),
-            ("bgfilter.searchspring_exclude", "No"),
-            ("bgfilter.visibility", "Catalog"),
-            ("bgfilter.ss_advisor_exclusive", "0"),
-            ("bgfilter.ss_category", "Shop"),
-            ("bgfilter.ss_customer_visibility", "0"),
-            ("q", query),
-            ("noBeacon", "true"),
-            ("ajaxCatalog", "Snap"),
-            ("resultsFormat", "native"),
-            ("includedFacets", "none"),
-            ("page", "1"),
-            ("resultsPerPage", "8"),
-        ];
-        if let Some(sku) = &self.sku {
-            query_params.push(("filter.sku", sku.as_str()));
-        }
 
         // Build full URL with query params for caching
-        let full_url = reqwest::Url::parse_with_params(&url, &query_params)?;
+        let full_url = self.request_url(&host, &site_id, &user, &session)?;
         let full_url_str = full_url.to_string();
 
         // Check cache first (unless --no-cache is specified)
         let cache_entry = CacheEntry::for_url(&full_url_str);
+        if is_offline() {
+            return match cache_entry.read()? {
+                Some(cached_body) => {
+                    info!(
+                        "Offline: using cached search result for query '{}' sku '{}'",
+                        query,
+                        self.sku.join(",")
+                    );
+                    Self::parse_response(&cached_body)
+                }
+                None => Err(eyre::eyre!(
+                    "offline: no cached result for query '{}' sku '{}'",
+                    query,
+                    self.sku.join(",")
+                )),
+            };
+        }
         if !self.no_cache
             && let Some(cached_body) = cache_entry.read()?
         {
             info!(
                 "Using cached search result for query '{}' sku '{}'",
                 query,
-                self.sku.as_deref().unwrap_or("")
+                self.sku.join(",")
             );
             return Self::parse_response(&cached_body);
         }
@@ -169,13 +204,13 @@ impl SearchArgs {
         info!(
             "Performing search for query '{}' sku '{}'",
             query,
-            self.sku.as_deref().unwrap_or("")
+            self.sku.join(",")
         );
+        wait_for_search_slot().await;
         let _guard = span.enter();
         let resp = reqwest::Client::new()
-            .get(&url)
+            .get(full_url)
             .header(reqwest::header::USER_AGENT, user_agent)
-            .query(&query_params)
             .send()
             .instrument(span.clone())
             .await?;
@@ -196,6 +231,51 @@ impl SearchArgs {
         Self::parse_response(&body)
     }
 
+    /// The search endpoint for a site, on the host from `host_template`.
+    fn endpoint(host_template: &str, site_id: &str) -> String {
+        format!(
+            "https://{}/api/search/search.json",
+            host_for_site(host_template, site_id)
+        )
+    }
+
+    /// The full request URL for this search as the given site, user and session, sent to the
+    /// host from `host_template`.
+    fn request_url(
+        &self,
+        host_template: &str,
+        site_id: &str,
+        user: &str,
+        session: &str,
+    ) -> eyre::Result<reqwest::Url> {
+        let query = self.query.as_deref().unwrap_or_default();
+        let mut query_params = vec![
+            // ("lastViewed", "664269"),
+            ("userId", user),
+            ("siteId", site_id),
+            ("sessionId", session),
+            ("bgfilter.searchspring_exclude", "No"),
+            ("bgfilter.visibility", "Catalog"),
+            ("bgfilter.ss_advisor_exclusive", "0"),
+            ("bgfilter.ss_category", "Shop"),
+            ("bgfilter.ss_customer_visibility", "0"),
+            ("q", query),
+            ("noBeacon", "true"),
+            ("ajaxCatalog", "Snap"),
+            ("resultsFormat", "native"),
+            ("includedFacets", "none"),
+            ("page", "1"),
+            ("resultsPerPage", "8"),
+        ];
+        for sku in &self.sku {
+            query_params.push(("filter.sku", sku.as_str()));
+        }
+        Ok(reqwest::Url::parse_with_params(
+            &Self::endpoint(host_template, site_id),
+            &query_params,
+        )?)
+    }
+
     /// Parse the JSON response body into `SearchResultOk`.
     fn parse_response(body: &str) -> eyre::Result<SearchResultOk> {
         facet_json::from_str(body).map_err(|e| eyre::eyre!("Failed to parse response: {}", e))
@@ -205,12 +285,8 @@ impl SearchArgs {
 impl ToArgs for SearchArgs {
     fn to_args(&self) -> Vec<OsString> {
         let mut rtn = vec![];
-        if let Some(q) = &self.query {
-            rtn.push(OsString::from(q));
-        }
-        if let Some(sku) = &self.sku {
-            rtn.push(OsString::from("--sku"));
-            rtn.push(OsString::from(sku));
+        for sku in &self.sku {
+            rtn.push(flag_value("--sku", sku));
         }
         if self.no_cache {
             rtn.push(OsString::from("--no-cache"));
@@ -219,6 +295,20 @@ impl ToArgs for SearchArgs {
             rtn.push(OsString::from("--output"));
             rtn.push(OsString::from(self.output.to_string()));
         }
+        if !self.fields.is_empty() {
+            rtn.push(OsString::from("--fields"));
+            rtn.push(OsString::from(
+                self.fields
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(","),
+            ));
+        }
+        if let Some(q) = &self.query {
+            rtn.push(OsString::from("--"));
+            rtn.push(OsString::from(q));
+        }
         rtn
     }
 }
@@ -226,14 +316,74 @@ impl ToArgs for SearchArgs {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::cli::Cli;
+    use crate::cli::command::Command;
+    use crate::site_host::DEFAULT_SITE_HOST;
+    use clap::Parser;
+    use std::collections::HashMap;
+
+    #[test]
+    fn id_flags_override_the_request_url() -> eyre::Result<()> {
+        let user = "00000000-0000-4000-8000-000000000001";
+        let session = "00000000-0000-4000-8000-000000000002";
+        let cli = Cli::try_parse_from([
+            "cm",
+            "--site-id",
+            "staging1",
+            "--user-id",
+            user,
+            "--session-id",
+            session,
+            "search",
+            "widget",
+        ])?;
+        // Pass the parsed ids in rather than installing the process-wide overrides, which
+        // other tests in this binary read
+        let global = &cli.global_args;
+        let Some(Command::Search(args)) = &cli.command else {
+            panic!("expected a search command, got {:?}", cli.command);
+        };
+        let url = args.request_url(
+            DEFAULT_SITE_HOST,
+            global.site_id.as_deref().unwrap_or_default(),
+            &global.user_id.unwrap_or_default().to_string(),
+            &global.session_id.unwrap_or_default().to_string(),
+        )?;
+        assert_eq!(url.host_str(), Some("staging1.a.searchspring.io"));
+        let params: HashMap<_, _> = url.query_pairs().collect();
+        assert_eq!(params["siteId"], "staging1");
+        assert_eq!(params["userId"], user);
+        assert_eq!(params["sessionId"], session);
+        assert_eq!(params["q"], "widget");
+        Ok(())
+    }
+
+    #[test]
+    fn custom_host_template_is_used_for_the_request_url() -> eyre::Result<()> {
+        let args = SearchArgs {
+            query: Some("widget".to_owned()),
+            sku: vec![],
+            no_cache: false,
+            output: OutputFormat::Auto,
+            fields: vec![],
+        };
+        let url = args.request_url("search.{site_id}.example.com", "abc123", "user", "session")?;
+        assert_eq!(url.scheme(), "https");
+        assert_eq!(url.host_str(), Some("search.abc123.example.com"));
+        assert_eq!(url.path(), "/api/search/search.json");
+        let params: HashMap<_, _> = url.query_pairs().collect();
+        assert_eq!(params["siteId"], "abc123");
+        Ok(())
+    }
 
     #[test]
     fn to_args_includes_output_when_set() {
         let args = SearchArgs {
             query: None,
-            sku: None,
+            sku: vec![],
             no_cache: false,
             output: OutputFormat::Json,
+            fields: vec![],
         };
         let v = args.to_args();
         assert!(
@@ -241,4 +391,43 @@ mod tests {
                 .any(|w| w == [OsString::from("--output"), OsString::from("json")])
         );
     }
+
+    #[test]
+    fn to_args_repeats_sku_flag() {
+        let args = SearchArgs {
+            query: None,
+            sku: vec!["123456".to_string(), "654321".to_string()],
+            no_cache: false,
+            output: OutputFormat::Auto,
+            fields: vec![],
+        };
+        assert_eq!(
+            args.to_args(),
+            vec![
+                OsString::from("--sku"),
+                OsString::from("123456"),
+                OsString::from("--sku"),
+                OsString::from("654321"),
+            ]
+        );
+    }
+    #[test]
+    fn to_args_joins_fields() {
+        let args = SearchArgs {
+            query: None,
+            sku: vec![],
+            no_cache: false,
+            output: OutputFormat::Jsonl,
+            fields: vec![ResultField::Name, ResultField::Sku, ResultField::ImageUrl],
+        };
+        assert_eq!(
+            args.to_args(),
+            vec![
+                OsString::from("--output"),
+                OsString::from("jsonl"),
+                OsString::from("--fields"),
+                OsString::from("name,sku,image-url"),
+            ]
+        );
+    }
 }
@@ -1,6 +1,7 @@
 //! Strongly-typed representation of the Searchspring search response.
 //! See: <https://docs.searchspring.com/reference/get-search?siteId=4y9u7l>
 
+use crate::cli::command::search::result_field::ResultField;
 use facet::Facet;
 
 /// Pagination info from the search response.
@@ -84,6 +85,58 @@ pub struct ResultItem {
     pub intellisuggest_signature: Option<String>,
 }
 
+impl ResultItem {
+    /// Stable identifier for this item, preferring uid, then sku, then name.
+    #[must_use]
+    pub fn key(&self) -> String {
+        self.uid
+            .as_ref()
+            .map(|u| u.0.clone())
+            .or_else(|| self.sku.as_ref().map(|s| s.0.clone()))
+            .or_else(|| self.name.clone())
+            .unwrap_or_default()
+    }
+
+    /// Text suitable for embedding as an image description:
+    /// `"{name} ${price}"` followed by the item description when present.
+    #[must_use]
+    pub fn embed_description(&self) -> String {
+        let name = self.name.as_deref().unwrap_or("");
+        let price = self.price.as_ref().map_or("", |p| p.0.as_str());
+        let mut out = format!("{name} ${price}");
+        if let Some(description) = self.description.as_deref().map(str::trim)
+            && !description.is_empty()
+        {
+            out.push('\n');
+            out.push_str(description);
+        }
+        out
+    }
+
+    /// Serialize only the selected `fields` as a JSON object, in the order given.
+    /// Missing values are emitted as `null`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a value cannot be serialized.
+    pub fn project(&self, fields: &[ResultField]) -> eyre::Result<String> {
+        let mut out = String::from("{");
+        for (i, field) in fields.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            out.push_str(&facet_json::to_string(field.json_key())?);
+            out.push(':');
+            match field.value(self) {
+                Some(value) => out.push_str(&facet_json::to_string(value)?),
+                None => out.push_str("null"),
+            }
+        }
+        out.push('}');
+        Ok(out)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Facet)]
 pub struct FacetValue {
     pub active: Option<bool>,
@@ -166,6 +219,57 @@ pub struct QueryInfo {
     pub corrected: Option<String>,
 }
 
+/// Min/max/median of the parseable prices in a result set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PriceSummary {
+    pub min: f64,
+    pub max: f64,
+    pub median: f64,
+}
+
+impl PriceSummary {
+    /// Summarize the parseable prices of `items`, or `None` if no price parses.
+    #[must_use]
+    pub fn from_items(items: &[ResultItem]) -> Option<Self> {
+        let mut prices: Vec<f64> = items
+            .iter()
+            .filter_map(|i| i.price.as_ref().and_then(Price::as_f64))
+            .collect();
+        if prices.is_empty() {
+            return None;
+        }
+        prices.sort_by(f64::total_cmp);
+        let mid = prices.len() / 2;
+        let median = if prices.len().is_multiple_of(2) {
+            f64::midpoint(prices[mid - 1], prices[mid])
+        } else {
+            prices[mid]
+        };
+        Some(Self {
+            min: prices[0],
+            max: prices[prices.len() - 1],
+            median,
+        })
+    }
+}
+
+/// Sort result items by price, keeping items with unparseable prices last.
+///
+/// The sort is stable so items with equal prices keep their API order.
+pub fn sort_by_price(items: &mut [&ResultItem], descending: bool) {
+    items.sort_by(|a, b| {
+        let pa = a.price.as_ref().and_then(Price::as_f64);
+        let pb = b.price.as_ref().and_then(Price::as_f64);
+        match (pa, pb) {
+            (Some(x), Some(y)) if descending => y.total_cmp(&x),
+            (Some(x), Some(y)) => x.total_cmp(&y),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        }
+    });
+}
+
 /// The top-level search result response.
 #[derive(Debug, Clone, PartialEq, Facet)]
 pub struct SearchResultOk {
@@ -244,4 +348,48 @@ mod tests {
         assert_eq!(p.begin, Some(1));
         assert_eq!(p.per_page, Some(8));
     }
+
+    #[test]
+    fn sort_by_price_puts_unparseable_last() {
+        let raw = r#"{ "results": [
+            { "name": "b", "price": "20.00" },
+            { "name": "x", "price": "call us" },
+            { "name": "a", "price": "5.50" },
+            { "name": "c", "price": "12" }
+        ] }"#;
+        let got: SearchResultOk = facet_json::from_str(raw).expect("should deserialize");
+        let results = got.results.unwrap();
+
+        let mut items: Vec<&ResultItem> = results.iter().collect();
+        sort_by_price(&mut items, false);
+        let names: Vec<_> = items.iter().map(|i| i.name.as_deref().unwrap()).collect();
+        assert_eq!(names, ["a", "c", "b", "x"]);
+
+        sort_by_price(&mut items, true);
+        let names: Vec<_> = items.iter().map(|i| i.name.as_deref().unwrap()).collect();
+        assert_eq!(names, ["b", "c", "a", "x"]);
+
+        let summary = PriceSummary::from_items(&results).unwrap();
+        assert!((summary.min - 5.5).abs() < f64::EPSILON);
+        assert!((summary.max - 20.0).abs() < f64::EPSILON);
+        assert!((summary.median - 12.0).abs() < f64::EPSILON);
+    }
+    #[test]
+    fn project_emits_selected_fields_in_order() {
+        let raw = r#"{ "results": [{ "uid": "id-1", "sku": "123456", "name": "Item \"1\"", "price": "9.99", "brand": "Acme" }] }"#;
+        let got: SearchResultOk = facet_json::from_str(raw).expect("should deserialize");
+        let item = &got.results.unwrap()[0];
+        let projected = item
+            .project(&[
+                ResultField::Name,
+                ResultField::Sku,
+                ResultField::Price,
+                ResultField::Msrp,
+            ])
+            .expect("should project");
+        assert_eq!(
+            projected,
+            r#"{"name":"Item \"1\"","sku":"123456","price":"9.99","msrp":null}"#
+        );
+    }
 }
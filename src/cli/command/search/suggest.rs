@@ -0,0 +1,71 @@
+//! Turning image file names into product searches
+
+use crate::cli::command::search::search_command::OutputFormat;
+use crate::cli::command::search::search_command::SearchArgs;
+use regex::Regex;
+use std::path::Path;
+
+/// Suggest search args given a filename.
+/// If a six-digit SKU is found (\b(\d{6})\b) suggest a SKU search, otherwise
+/// suggest a query formed by replacing hyphens with spaces, inserting spaces
+/// before camel-case boundaries (but not inside ALL-CAPS), stripping numbers,
+/// and omitting any single-character tokens.
+#[must_use]
+pub fn suggest_search(filename: &str) -> SearchArgs {
+    let re_sku = Regex::new(r"\b(\d{6})\b").unwrap();
+    let re_digits = Regex::new(r"\d+").unwrap();
+    // Insert spaces for transitions like "HTMLParser" -> "HTML Parser"
+    let re_camel_acronym = Regex::new(r"([A-Z]+)([A-Z][a-z])").unwrap();
+    // Insert spaces for transitions like "forestGreen" -> "forest Green"
+    let re_camel = Regex::new(r"([a-z0-9])([A-Z])").unwrap();
+
+    // Use file stem (strip extension) when possible
+    let stem = Path::new(filename)
+        .file_stem()
+        .map_or_else(|| filename.to_string(), |s| s.to_string_lossy().to_string());
+
+    if let Some(cap) = re_sku.captures(&stem) {
+        let sku = cap.get(1).unwrap().as_str().to_string();
+        return SearchArgs {
+            query: None,
+            sku: vec![sku],
+            no_cache: false,
+            output: OutputFormat::Json,
+            fields: vec![],
+        };
+    }
+
+    // Replace hyphens/underscores with spaces first
+    let with_spaces = stem.replace(['-', '_'], " ");
+
+    // Insert spaces for camel/pascal boundaries. Do the acronym rule first so
+    // ALL-CAPS words aren't split internally ("ALL" stays "ALL").
+    let with_caps = re_camel_acronym
+        .replace_all(&with_spaces, "$1 $2")
+        .to_string();
+    let with_caps = re_camel.replace_all(&with_caps, "$1 $2").to_string();
+
+    // Strip digits
+    let stripped = re_digits.replace_all(&with_caps, "").to_string();
+
+    // Collapse whitespace, trim and remove any single-character tokens
+    let suggestion = stripped
+        .split_whitespace()
+        .filter(|s| s.chars().count() > 1)
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim()
+        .to_string();
+
+    SearchArgs {
+        query: if suggestion.is_empty() {
+            Some(stem)
+        } else {
+            Some(suggestion)
+        },
+        sku: vec![],
+        no_cache: false,
+        output: OutputFormat::Json,
+        fields: vec![],
+    }
+}
@@ -0,0 +1,3 @@
+//! `cm search-manifest` command to match every input image to a product and list the matches.
+
+pub mod search_manifest_command;
@@ -0,0 +1,97 @@
+use crate::app_home::APP_HOME;
+use crate::atomic_write::write_atomic;
+use crate::cli::to_args::ToArgs;
+use crate::cli::to_args::arbitrary_path;
+use crate::cli::to_args::flag_value;
+use crate::image_extensions::load_image_extensions;
+use crate::image_processing::is_image_file;
+use crate::inputs;
+use crate::inputs::NameFilter;
+use crate::search_manifest::ManifestOptions;
+use crate::search_manifest::build_manifest;
+use crate::search_manifest::to_csv;
+use arbitrary::Arbitrary;
+use clap::Args;
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Search for every input image by its file name and write the top matches as a CSV, or JSON
+/// when the output ends in `.json`
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct SearchManifestArgs {
+    /// Where to write the manifest
+    #[arbitrary(with = arbitrary_path)]
+    pub output: PathBuf,
+    /// Only include images whose file name matches this glob (e.g. `*-front.*`)
+    #[clap(long)]
+    pub filter: Option<String>,
+    /// Only search images whose file name contains a SKU; the others are listed unmatched
+    #[clap(long)]
+    pub only_if_sku: bool,
+    /// Milliseconds to wait between searches
+    #[clap(long, default_value_t = 500)]
+    pub delay_ms: u64,
+}
+
+impl SearchManifestArgs {
+    /// # Errors
+    ///
+    /// Returns an error if the filter is invalid, the inputs cannot be loaded, or the manifest
+    /// cannot be written.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let filter = self.filter.as_deref().map(NameFilter::parse).transpose()?;
+        let extensions = load_image_extensions(&APP_HOME)?;
+        let files: Vec<_> = inputs::list_files(&APP_HOME)?
+            .into_iter()
+            .filter(|p| is_image_file(p, &extensions))
+            .filter(|p| filter.as_ref().is_none_or(|f| f.matches(p)))
+            .collect();
+
+        let options = ManifestOptions {
+            only_if_sku: self.only_if_sku,
+            delay: Duration::from_millis(self.delay_ms),
+        };
+        let entries = tokio::runtime::Runtime::new()?.block_on(build_manifest(
+            &files,
+            options,
+            async |args| args.search().await,
+        ));
+
+        let is_json = self
+            .output
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("json"));
+        let contents = if is_json {
+            facet_json::to_string(&entries)?
+        } else {
+            to_csv(&entries)
+        };
+        write_atomic(&self.output, contents)?;
+
+        let matched = entries.iter().filter(|e| e.matched_name.is_some()).count();
+        println!(
+            "Matched {matched} of {} images; wrote {}",
+            entries.len(),
+            self.output.display()
+        );
+        Ok(())
+    }
+}
+
+impl ToArgs for SearchManifestArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args = Vec::new();
+        if let Some(filter) = &self.filter {
+            args.push(flag_value("--filter", filter));
+        }
+        if self.only_if_sku {
+            args.push("--only-if-sku".into());
+        }
+        args.push("--delay-ms".into());
+        args.push(self.delay_ms.to_string().into());
+        args.push("--".into());
+        args.push(self.output.clone().into_os_string());
+        args
+    }
+}
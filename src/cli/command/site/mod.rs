@@ -1,6 +1,8 @@
 pub mod site_command;
 pub mod site_reset_command;
+pub mod site_reset_host_command;
 pub mod site_set_command;
+pub mod site_set_host_command;
 pub mod site_show_command;
 
 use crate::cli::command::site::site_command::SiteCommand;
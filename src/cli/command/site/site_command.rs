@@ -1,5 +1,7 @@
 use crate::cli::command::site::site_reset_command::SiteResetArgs;
+use crate::cli::command::site::site_reset_host_command::SiteResetHostArgs;
 use crate::cli::command::site::site_set_command::SiteSetArgs;
+use crate::cli::command::site::site_set_host_command::SiteSetHostArgs;
 use crate::cli::command::site::site_show_command::SiteShowArgs;
 use crate::cli::to_args::ToArgs;
 use arbitrary::Arbitrary;
@@ -16,6 +18,12 @@ pub enum SiteCommand {
 
     /// Reset the site to the default value and write it to the config file
     Reset(SiteResetArgs),
+
+    /// Set the search host template, with `{site_id}` standing in for the site id
+    SetHost(SiteSetHostArgs),
+
+    /// Reset the search host template to the default
+    ResetHost(SiteResetHostArgs),
 }
 
 impl SiteCommand {
@@ -27,6 +35,8 @@ impl SiteCommand {
             SiteCommand::Show(args) => args.invoke(),
             SiteCommand::Set(args) => args.invoke(),
             SiteCommand::Reset(args) => args.invoke(),
+            SiteCommand::SetHost(args) => args.invoke(),
+            SiteCommand::ResetHost(args) => args.invoke(),
         }
     }
 }
@@ -47,6 +57,14 @@ impl ToArgs for SiteCommand {
                 args.push("reset".into());
                 args.extend(a.to_args());
             }
+            SiteCommand::SetHost(a) => {
+                args.push("set-host".into());
+                args.extend(a.to_args());
+            }
+            SiteCommand::ResetHost(a) => {
+                args.push("reset-host".into());
+                args.extend(a.to_args());
+            }
         }
         args
     }
@@ -0,0 +1,28 @@
+use crate::app_home::APP_HOME;
+use crate::cli::to_args::ToArgs;
+use crate::site_host::DEFAULT_SITE_HOST;
+use crate::site_host::set_site_host;
+use arbitrary::Arbitrary;
+use clap::Args;
+use std::ffi::OsString;
+
+/// Reset the search host to the default
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct SiteResetHostArgs {}
+
+impl SiteResetHostArgs {
+    /// # Errors
+    ///
+    /// Returns an error if the saved host cannot be removed.
+    pub fn invoke(self) -> eyre::Result<()> {
+        set_site_host(&APP_HOME, None)?;
+        println!("Reset search host to default: {DEFAULT_SITE_HOST}");
+        Ok(())
+    }
+}
+
+impl ToArgs for SiteResetHostArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        Vec::new()
+    }
+}
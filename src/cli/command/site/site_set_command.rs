@@ -24,6 +24,6 @@ impl SiteSetArgs {
 
 impl ToArgs for SiteSetArgs {
     fn to_args(&self) -> Vec<OsString> {
-        vec![self.id.clone().into()]
+        vec!["--".into(), self.id.clone().into()]
     }
 }
@@ -0,0 +1,30 @@
+use crate::app_home::APP_HOME;
+use crate::cli::to_args::ToArgs;
+use crate::site_host::set_site_host;
+use arbitrary::Arbitrary;
+use clap::Args;
+use std::ffi::OsString;
+
+/// Set the search host, with `{site_id}` standing in for the site id
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct SiteSetHostArgs {
+    /// Host template, e.g. `{site_id}.a.searchspring.io`
+    pub template: String,
+}
+
+impl SiteSetHostArgs {
+    /// # Errors
+    ///
+    /// Returns an error if the template is invalid or cannot be saved.
+    pub fn invoke(self) -> eyre::Result<()> {
+        set_site_host(&APP_HOME, Some(&self.template))?;
+        println!("Setting search host to: {}", self.template.trim());
+        Ok(())
+    }
+}
+
+impl ToArgs for SiteSetHostArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        vec!["--".into(), self.template.clone().into()]
+    }
+}
@@ -11,8 +11,9 @@ impl SiteShowArgs {
     ///
     /// This function does not return any errors.
     pub fn invoke(self) -> eyre::Result<()> {
-        // Use the static SITE_ID for the current value
-        println!("Site: {}", crate::SITE_ID.as_str());
+        // Use the site id in effect, honoring --site-id
+        println!("Site: {}", crate::site_id::current_site_id().as_str());
+        println!("Host: {}", crate::site_host::site_host());
         Ok(())
     }
 }
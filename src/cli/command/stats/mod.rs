@@ -0,0 +1,3 @@
+//! `cm stats` command to summarize the working set and the last batch.
+
+pub mod stats_command;
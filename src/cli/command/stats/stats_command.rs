@@ -0,0 +1,196 @@
+use crate::app_home::APP_HOME;
+use crate::app_home::AppHome;
+use crate::batch_checkpoint;
+use crate::cli::to_args::ToArgs;
+use crate::last_batch::LastBatch;
+use crate::last_batch::load_last_batch;
+use crate::pipeline;
+use crate::pipeline::PipelineConfig;
+use arbitrary::Arbitrary;
+use clap::Args;
+use facet::Facet;
+use std::ffi::OsString;
+use std::fs;
+use std::sync::atomic::Ordering;
+
+/// Summarize the inputs, rename rules and last batch
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct StatsArgs {
+    /// Print the summary as JSON
+    #[clap(long)]
+    pub json: bool,
+}
+
+/// Snapshot of the working set gathered by `cm stats`
+#[derive(Debug, Clone, PartialEq, Facet)]
+pub struct WorkingSetStats {
+    pub input_count: usize,
+    pub image_count: usize,
+    pub input_bytes: u64,
+    pub max_name_length: usize,
+    /// What `max_name_length` counts, `bytes` or `chars`
+    pub length_unit: String,
+    /// Images whose name is still longer than `max_name_length` after the rename rules
+    pub too_long_names: usize,
+    pub rename_rule_count: usize,
+    /// Outcome of the last finished batch, if any
+    pub last_batch: Option<LastBatch>,
+    /// Images already processed by an interrupted batch that can be resumed
+    pub unfinished_batch_outputs: Option<usize>,
+}
+
+impl WorkingSetStats {
+    /// Gather the stats of `home`, judging names against `max_name_length`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the inputs, rename rules, last batch or checkpoint cannot be read.
+    pub fn gather(home: &AppHome, max_name_length: usize) -> eyre::Result<Self> {
+        let mut config = PipelineConfig::from_app_home(home)?;
+        config.rename_options.max_name_length = max_name_length;
+        let plan = pipeline::plan(&config)?;
+        let input_bytes = plan
+            .files
+            .iter()
+            .filter_map(|p| fs::metadata(p).ok())
+            .map(|m| m.len())
+            .sum();
+        let too_long_names = plan
+            .renamed
+            .iter()
+            .filter_map(|p| p.file_name())
+            .filter(|name| config.rename_options.is_too_long(&name.to_string_lossy()))
+            .count();
+        let unfinished_batch_outputs = if batch_checkpoint::exists(home) {
+            Some(batch_checkpoint::completed_inputs(home)?.len())
+        } else {
+            None
+        };
+
+        Ok(WorkingSetStats {
+            input_count: config.inputs.len(),
+            image_count: plan.files.len(),
+            input_bytes,
+            max_name_length,
+            length_unit: config.rename_options.length_unit.name().to_owned(),
+            too_long_names,
+            rename_rule_count: config.rules.len(),
+            last_batch: load_last_batch(home)?,
+            unfinished_batch_outputs,
+        })
+    }
+}
+
+impl std::fmt::Display for WorkingSetStats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Inputs:          {}", self.input_count)?;
+        writeln!(
+            f,
+            "Images:          {} ({} bytes)",
+            self.image_count, self.input_bytes
+        )?;
+        writeln!(
+            f,
+            "Too-long names:  {} (max {} {})",
+            self.too_long_names, self.max_name_length, self.length_unit
+        )?;
+        write!(f, "Rename rules:    {}", self.rename_rule_count)?;
+        if let Some(batch) = &self.last_batch {
+            write!(
+                f,
+                "\nLast batch:      {} processed, {} failed, {} output bytes",
+                batch.processed, batch.errors, batch.output_bytes
+            )?;
+        }
+        if let Some(done) = self.unfinished_batch_outputs {
+            write!(
+                f,
+                "\nUnfinished batch: {done} images processed, can be resumed"
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl StatsArgs {
+    /// # Errors
+    ///
+    /// Returns an error if gathering or serializing the stats fails.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let stats =
+            WorkingSetStats::gather(&APP_HOME, crate::MAX_NAME_LENGTH.load(Ordering::SeqCst))?;
+        if self.json {
+            let json = facet_json::to_string(&stats)
+                .map_err(|e| eyre::eyre!("Failed to serialize stats: {}", e))?;
+            println!("{json}");
+        } else {
+            println!("{stats}");
+        }
+        Ok(())
+    }
+}
+
+impl ToArgs for StatsArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut rtn = vec![];
+        if self.json {
+            rtn.push(OsString::from("--json"));
+        }
+        rtn
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inputs;
+    use crate::last_batch::save_last_batch;
+    use crate::rename_rules;
+    use crate::rename_rules::RenameRule;
+    use tempfile::tempdir;
+
+    #[test]
+    fn stats_summarize_a_seeded_app_home() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().join("home"));
+        let photos = td.path().join("photos");
+        fs::create_dir_all(&photos)?;
+        fs::write(photos.join("a.png"), b"12345")?;
+        fs::write(photos.join("a-much-longer-name.jpg"), b"123")?;
+        fs::write(photos.join("notes.txt"), b"not an image")?;
+        inputs::add_paths(&home, std::slice::from_ref(&photos))?;
+        rename_rules::add_rule(
+            &home,
+            &RenameRule {
+                find: "zzz".to_string(),
+                ..RenameRule::default()
+            },
+        )?;
+
+        let stats = WorkingSetStats::gather(&home, 10)?;
+        assert_eq!(stats.input_count, 1);
+        assert_eq!(stats.image_count, 2);
+        assert_eq!(stats.input_bytes, 8);
+        assert_eq!(stats.too_long_names, 1);
+        assert_eq!(stats.rename_rule_count, 1);
+        assert_eq!(stats.last_batch, None);
+        assert_eq!(stats.unfinished_batch_outputs, None);
+
+        let batch = LastBatch {
+            processed: 1,
+            errors: 1,
+            output_bytes: 42,
+        };
+        save_last_batch(&home, &batch)?;
+        batch_checkpoint::start(&home)?;
+        let stats = WorkingSetStats::gather(&home, 10)?;
+        assert_eq!(stats.last_batch, Some(batch));
+        assert_eq!(stats.unfinished_batch_outputs, Some(0));
+        assert!(
+            stats
+                .to_string()
+                .contains("1 processed, 1 failed, 42 output bytes")
+        );
+        Ok(())
+    }
+}
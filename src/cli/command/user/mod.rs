@@ -0,0 +1,30 @@
+pub mod user_command;
+pub mod user_reset_command;
+pub mod user_show_command;
+
+use crate::cli::command::user::user_command::UserCommand;
+use crate::cli::to_args::ToArgs;
+use arbitrary::Arbitrary;
+use clap::Args;
+use std::ffi::OsString;
+
+#[derive(Args, Arbitrary, PartialEq, Debug)]
+pub struct UserArgs {
+    #[clap(subcommand)]
+    pub command: UserCommand,
+}
+
+impl UserArgs {
+    /// # Errors
+    ///
+    /// Returns an error if the user subcommand fails.
+    pub fn invoke(self) -> eyre::Result<()> {
+        self.command.invoke()
+    }
+}
+
+impl ToArgs for UserArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        self.command.to_args()
+    }
+}
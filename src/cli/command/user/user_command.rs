@@ -0,0 +1,44 @@
+use crate::cli::command::user::user_reset_command::UserResetArgs;
+use crate::cli::command::user::user_show_command::UserShowArgs;
+use crate::cli::to_args::ToArgs;
+use arbitrary::Arbitrary;
+use clap::Subcommand;
+use std::ffi::OsString;
+
+#[derive(Subcommand, Clone, Arbitrary, PartialEq, Debug)]
+pub enum UserCommand {
+    /// Show the user id sent with searches
+    Show(UserShowArgs),
+
+    /// Replace the stored user id with a newly generated one
+    Reset(UserResetArgs),
+}
+
+impl UserCommand {
+    /// # Errors
+    ///
+    /// Returns an error if the user command fails.
+    pub fn invoke(self) -> eyre::Result<()> {
+        match self {
+            UserCommand::Show(args) => args.invoke(),
+            UserCommand::Reset(args) => args.invoke(),
+        }
+    }
+}
+
+impl ToArgs for UserCommand {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut args = Vec::new();
+        match self {
+            UserCommand::Show(a) => {
+                args.push("show".into());
+                args.extend(a.to_args());
+            }
+            UserCommand::Reset(a) => {
+                args.push("reset".into());
+                args.extend(a.to_args());
+            }
+        }
+        args
+    }
+}
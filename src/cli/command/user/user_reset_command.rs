@@ -0,0 +1,27 @@
+use crate::app_home::APP_HOME;
+use crate::cli::to_args::ToArgs;
+use crate::user_id::UserId;
+use arbitrary::Arbitrary;
+use clap::Args;
+use std::ffi::OsString;
+
+/// Replace the stored user id with a newly generated one
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct UserResetArgs {}
+
+impl UserResetArgs {
+    /// # Errors
+    ///
+    /// Returns an error if the new user id cannot be written.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let id = UserId::reset_in(&APP_HOME)?;
+        println!("Reset user id to: {}", id.as_uuid());
+        Ok(())
+    }
+}
+
+impl ToArgs for UserResetArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        Vec::new()
+    }
+}
@@ -0,0 +1,24 @@
+use crate::cli::to_args::ToArgs;
+use arbitrary::Arbitrary;
+use clap::Args;
+use std::ffi::OsString;
+
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct UserShowArgs {}
+
+impl UserShowArgs {
+    /// # Errors
+    ///
+    /// This function does not return any errors.
+    pub fn invoke(self) -> eyre::Result<()> {
+        // Use the user id in effect, honoring --user-id
+        println!("User: {}", crate::user_id::current_user_id().as_uuid());
+        Ok(())
+    }
+}
+
+impl ToArgs for UserShowArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        Vec::new()
+    }
+}
@@ -0,0 +1,3 @@
+//! `cm version` command to print build provenance.
+
+pub mod version_command;
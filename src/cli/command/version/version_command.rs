@@ -0,0 +1,75 @@
+use crate::cli::to_args::ToArgs;
+use arbitrary::Arbitrary;
+use clap::Args;
+use facet::Facet;
+use std::ffi::OsString;
+
+/// Print version and build information
+#[derive(Args, Arbitrary, Clone, PartialEq, Debug)]
+pub struct VersionArgs {
+    /// Print the build information as JSON
+    #[clap(long)]
+    pub json: bool,
+}
+
+/// Version and build provenance captured at compile time by `build.rs`
+#[derive(Debug, Clone, PartialEq, Facet)]
+pub struct VersionInfo {
+    pub name: String,
+    pub version: String,
+    pub git_revision: String,
+    pub rustc: Option<String>,
+    /// RFC 3339 timestamp of the build
+    pub build_time: Option<String>,
+}
+
+impl VersionInfo {
+    /// Build information for the running binary
+    #[must_use]
+    pub fn current() -> Self {
+        let build_time = option_env!("BUILD_UNIX_TIME")
+            .and_then(|s| s.parse::<i64>().ok())
+            .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+            .map(|t| t.to_rfc3339());
+        VersionInfo {
+            name: env!("CARGO_PKG_NAME").to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            git_revision: option_env!("GIT_REVISION").unwrap_or("unknown").to_string(),
+            rustc: option_env!("RUSTC_VERSION").map(str::to_string),
+            build_time,
+        }
+    }
+}
+
+impl VersionArgs {
+    /// # Errors
+    ///
+    /// Returns an error if serializing the build information fails.
+    pub fn invoke(self) -> eyre::Result<()> {
+        let info = VersionInfo::current();
+        if self.json {
+            let json = facet_json::to_string(&info)
+                .map_err(|e| eyre::eyre!("Failed to serialize version info: {}", e))?;
+            println!("{json}");
+        } else {
+            println!("{} {} ({})", info.name, info.version, info.git_revision);
+            if let Some(rustc) = &info.rustc {
+                println!("{rustc}");
+            }
+            if let Some(build_time) = &info.build_time {
+                println!("built {build_time}");
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ToArgs for VersionArgs {
+    fn to_args(&self) -> Vec<OsString> {
+        let mut rtn = vec![];
+        if self.json {
+            rtn.push(OsString::from("--json"));
+        }
+        rtn
+    }
+}
@@ -1,8 +1,22 @@
 use crate::cli::json_log_behaviour::JsonLogBehaviour;
 use crate::cli::to_args::ToArgs;
+use crate::cli::to_args::arbitrary_optional_path;
+use crate::cli::to_args::flag_value;
+use crate::config_file::Config;
+use crate::config_file::layered;
+use crate::offline::offline_from_env;
+use crate::session_id::SessionIdOverride;
+use crate::session_id::override_session_id;
+use crate::site_id::SiteIdOverride;
+use crate::site_id::override_site_id;
+use crate::tracing::LogRotation;
+use crate::user_id::UserIdOverride;
+use crate::user_id::override_user_id;
 use arbitrary::Arbitrary;
 use clap::Args;
 use std::ffi::OsString;
+use std::path::PathBuf;
+use uuid::Uuid;
 
 #[derive(Args, Default, Arbitrary, PartialEq, Debug)]
 pub struct GlobalArgs {
@@ -10,6 +24,18 @@ pub struct GlobalArgs {
     #[clap(long, global = true)]
     pub debug: bool,
 
+    /// Only log errors and leave progress out of the output; overrides `--debug` and `-v`
+    #[clap(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Log more: `-v` for debug, `-vv` for trace; overrides `--debug`
+    #[clap(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Never touch the network; only use cached responses
+    #[clap(long, global = true)]
+    pub offline: bool,
+
     /// Emit structured JSON logs alongside stderr output.
     /// Optionally specify a filename; if not provided, a timestamped filename will be generated.
     #[clap(
@@ -21,15 +47,48 @@ pub struct GlobalArgs {
         require_equals = false
     )]
     log_file: Option<String>,
+
+    /// Rotate the log file once it reaches this many megabytes
+    #[clap(long, global = true, value_name = "MB")]
+    pub log_max_size: Option<u64>,
+
+    /// Number of rotated log files to keep alongside the active one (default 5)
+    #[clap(long, global = true, value_name = "N")]
+    pub log_max_files: Option<usize>,
+
+    /// Searchspring site id to use instead of the configured one (or set `CM_SITE_ID`)
+    #[clap(long, global = true)]
+    pub site_id: Option<String>,
+
+    /// User id sent with searches instead of the persisted one (or set `CM_USER_ID`)
+    #[clap(long, global = true, value_name = "UUID")]
+    pub user_id: Option<Uuid>,
+
+    /// Session id sent with searches in place of the one generated for this process (or set
+    /// `CM_SESSION_ID`)
+    #[clap(long, global = true, value_name = "UUID")]
+    pub session_id: Option<Uuid>,
+
+    /// Directory to keep settings in instead of the default one (or set `CM_APP_HOME`)
+    #[clap(long, global = true, value_name = "DIR")]
+    #[arbitrary(with = arbitrary_optional_path)]
+    pub app_home: Option<PathBuf>,
+
+    /// JSON file of flag defaults to use instead of `config.json` in the app home
+    #[clap(long, global = true, value_name = "FILE")]
+    #[arbitrary(with = arbitrary_optional_path)]
+    pub config: Option<PathBuf>,
 }
 
 impl GlobalArgs {
     #[must_use]
     pub fn log_level(&self) -> tracing::Level {
-        if self.debug {
-            tracing::Level::DEBUG
-        } else {
-            tracing::Level::INFO
+        match (self.quiet, self.verbose) {
+            (true, _) => tracing::Level::ERROR,
+            (false, 0) if self.debug => tracing::Level::DEBUG,
+            (false, 0) => tracing::Level::INFO,
+            (false, 1) => tracing::Level::DEBUG,
+            (false, _) => tracing::Level::TRACE,
         }
     }
 
@@ -42,6 +101,49 @@ impl GlobalArgs {
             Some(s) => JsonLogBehaviour::Some(s.into()),
         }
     }
+
+    /// Take `--site-id` and `--offline` from `config` where neither the flag nor its
+    /// environment variable is given; [`crate::cli::Cli::invoke`] then applies them.
+    pub fn fill_from_config(&mut self, config: &Config) {
+        self.site_id = layered(
+            self.site_id.take(),
+            std::env::var("CM_SITE_ID").ok(),
+            config.site_id.clone(),
+        );
+        self.offline = layered(
+            self.offline.then_some(true),
+            offline_from_env(),
+            config.offline,
+        )
+        .unwrap_or(false);
+    }
+
+    /// Make `--site-id`, `--user-id` and `--session-id` take effect until the returned
+    /// overrides are dropped.
+    pub fn apply_id_overrides(&self) -> IdOverrides {
+        IdOverrides {
+            _site_id: self.site_id.clone().map(override_site_id),
+            _user_id: self.user_id.map(override_user_id),
+            _session_id: self.session_id.map(override_session_id),
+        }
+    }
+
+    /// Get the log file rotation settings, if `--log-max-size` was given.
+    #[must_use]
+    pub fn log_rotation(&self) -> Option<LogRotation> {
+        self.log_max_size.map(|mb| LogRotation {
+            max_bytes: mb.saturating_mul(1024 * 1024),
+            max_files: self.log_max_files.unwrap_or(5),
+        })
+    }
+}
+
+/// The id overrides applied by [`GlobalArgs::apply_id_overrides`], undone when dropped
+#[must_use = "the overrides are undone as soon as this is dropped"]
+pub struct IdOverrides {
+    _site_id: Option<SiteIdOverride>,
+    _user_id: Option<UserIdOverride>,
+    _session_id: Option<SessionIdOverride>,
 }
 
 impl ToArgs for GlobalArgs {
@@ -50,16 +152,91 @@ impl ToArgs for GlobalArgs {
         if self.debug {
             args.push("--debug".into());
         }
-        match &self.log_file {
-            None => {}
-            Some(s) if s.is_empty() => {
-                args.push("--log-file".into());
-            }
-            Some(path) => {
-                args.push("--log-file".into());
-                args.push(path.into());
-            }
+        if self.quiet {
+            args.push("--quiet".into());
+        }
+        if self.verbose > 0 {
+            args.push(format!("-{}", "v".repeat(self.verbose.into())).into());
+        }
+        if self.offline {
+            args.push("--offline".into());
+        }
+        // A bare `--log-file` would take the subcommand name as its file
+        if let Some(path) = &self.log_file {
+            args.push(flag_value("--log-file", path));
+        }
+        if let Some(mb) = self.log_max_size {
+            args.push("--log-max-size".into());
+            args.push(mb.to_string().into());
+        }
+        if let Some(n) = self.log_max_files {
+            args.push("--log-max-files".into());
+            args.push(n.to_string().into());
+        }
+        if let Some(site_id) = &self.site_id {
+            args.push(flag_value("--site-id", site_id));
+        }
+        if let Some(user_id) = self.user_id {
+            args.push("--user-id".into());
+            args.push(user_id.to_string().into());
+        }
+        if let Some(session_id) = self.session_id {
+            args.push("--session-id".into());
+            args.push(session_id.to_string().into());
+        }
+        if let Some(app_home) = &self.app_home {
+            args.push(flag_value("--app-home", app_home));
+        }
+        if let Some(config) = &self.config {
+            args.push(flag_value("--config", config));
         }
         args
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::cli::Cli;
+    use crate::config_file::Config;
+    use clap::Parser;
+
+    fn level(args: &[&str]) -> tracing::Level {
+        let cli = Cli::try_parse_from(std::iter::once("cm").chain(args.iter().copied())).unwrap();
+        cli.global_args.log_level()
+    }
+
+    #[test]
+    fn verbosity_flags_map_to_log_levels() {
+        assert_eq!(level(&[]), tracing::Level::INFO);
+        assert_eq!(level(&["-q"]), tracing::Level::ERROR);
+        assert_eq!(level(&["--quiet", "--debug", "-v"]), tracing::Level::ERROR);
+        assert_eq!(level(&["--debug"]), tracing::Level::DEBUG);
+        assert_eq!(level(&["-v"]), tracing::Level::DEBUG);
+        assert_eq!(level(&["-vv", "--debug"]), tracing::Level::TRACE);
+    }
+
+    #[test]
+    fn config_fills_site_id_and_offline_only_where_no_flag_is_given() {
+        let config = Config {
+            site_id: Some("from-config".to_string()),
+            offline: Some(true),
+            ..Config::default()
+        };
+        let parse = |args: &[&str]| {
+            let cli =
+                Cli::try_parse_from(std::iter::once("cm").chain(args.iter().copied())).unwrap();
+            let mut global_args = cli.global_args;
+            global_args.fill_from_config(&config);
+            global_args
+        };
+
+        let flagged = parse(&["--site-id", "from-flag"]);
+        assert_eq!(flagged.site_id.as_deref(), Some("from-flag"));
+
+        if std::env::var_os("CM_SITE_ID").is_none() && std::env::var_os("CM_OFFLINE").is_none() {
+            let unflagged = parse(&[]);
+            assert_eq!(unflagged.site_id.as_deref(), Some("from-config"));
+            assert!(unflagged.offline);
+        }
+    }
+}
@@ -25,6 +25,13 @@ impl Cli {
     ///
     /// Returns an error if the CLI command fails.
     pub fn invoke(self) -> eyre::Result<()> {
+        if self.global_args.offline {
+            crate::offline::set_offline(true);
+        }
+        if self.global_args.quiet {
+            crate::quiet::set_quiet(true);
+        }
+        let _id_overrides = self.global_args.apply_id_overrides();
         self.command.unwrap_or_default().invoke()
     }
 }
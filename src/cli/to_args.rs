@@ -1,5 +1,51 @@
+use std::ffi::OsStr;
 use std::ffi::OsString;
+use std::path::PathBuf;
 
+/// Render parsed arguments back into a command line that parses to the same value.
+///
+/// Free-form values are written as `--flag=value` and positionals follow a `--`, so values
+/// starting with `-` aren't read as flags. Clap rejects empty paths, so the `Arbitrary` impls
+/// generate path fields with [`arbitrary_path`] and [`arbitrary_optional_path`]; an empty path
+/// built by hand does not round-trip.
 pub trait ToArgs {
     fn to_args(&self) -> Vec<OsString>;
 }
+
+/// `--flag=value`, which clap reads back even when `value` starts with `-` or is empty
+#[must_use]
+pub fn flag_value(flag: &str, value: impl AsRef<OsStr>) -> OsString {
+    let mut arg = OsString::from(flag);
+    arg.push("=");
+    arg.push(value);
+    arg
+}
+
+/// A path clap accepts, i.e. any arbitrary path except the empty one
+///
+/// # Errors
+///
+/// Returns an error if `u` runs out of data.
+pub fn arbitrary_path(u: &mut arbitrary::Unstructured<'_>) -> arbitrary::Result<PathBuf> {
+    let path: PathBuf = u.arbitrary()?;
+    if path.as_os_str().is_empty() {
+        Ok(PathBuf::from("."))
+    } else {
+        Ok(path)
+    }
+}
+
+/// [`arbitrary_path`] for optional path flags
+///
+/// # Errors
+///
+/// Returns an error if `u` runs out of data.
+pub fn arbitrary_optional_path(
+    u: &mut arbitrary::Unstructured<'_>,
+) -> arbitrary::Result<Option<PathBuf>> {
+    if u.arbitrary()? {
+        arbitrary_path(u).map(Some)
+    } else {
+        Ok(None)
+    }
+}
@@ -0,0 +1,201 @@
+//! Defaults for command-line flags, read from `config.json` in the app home or from
+//! `--config <path>`, so the same flags don't have to be passed on every run.
+//!
+//! Precedence, highest first: a flag given on the command line, an environment variable
+//! (`CM_SITE_ID`, `CM_OFFLINE`), the config file, the built-in default. Processing defaults are
+//! installed as clap defaults, so `--help` shows them and any explicit flag still wins; a
+//! switch like `crop` that the config turns on is turned off with `--crop=false`.
+
+use crate::app_home::AppHome;
+use clap::Command;
+use facet::Facet;
+use std::fs;
+use std::path::Path;
+
+const FILE_NAME: &str = "config.json";
+
+/// Subcommands that take the processing flags
+const PROCESSING_COMMANDS: [&str; 2] = ["process", "plan"];
+
+/// Values used when the matching flag isn't given
+#[derive(Clone, Debug, Default, PartialEq, Eq, Facet)]
+pub struct Config {
+    /// `--site-id`
+    pub site_id: Option<String>,
+    /// `--offline`
+    pub offline: Option<bool>,
+    /// `--crop`
+    pub crop: Option<bool>,
+    /// `--crop-threshold`
+    pub crop_threshold: Option<u8>,
+    /// `--jpeg-quality`
+    pub jpeg_quality: Option<u8>,
+    /// `--jpeg-progressive`
+    pub jpeg_progressive: Option<bool>,
+    /// `--png-compression`
+    pub png_compression: Option<u8>,
+    /// `--output-format` (`jpeg`, `png`, `webp` or `avif`)
+    pub output_format: Option<String>,
+    /// `--jobs`
+    pub jobs: Option<usize>,
+}
+
+impl Config {
+    /// Processing flags this config sets, as clap argument ids and values
+    fn flag_defaults(&self) -> Vec<(&'static str, String)> {
+        [
+            ("crop", self.crop.map(|v| v.to_string())),
+            ("crop_threshold", self.crop_threshold.map(|v| v.to_string())),
+            ("jpeg_quality", self.jpeg_quality.map(|v| v.to_string())),
+            (
+                "jpeg_progressive",
+                self.jpeg_progressive.map(|v| v.to_string()),
+            ),
+            (
+                "png_compression",
+                self.png_compression.map(|v| v.to_string()),
+            ),
+            ("output_format", self.output_format.clone()),
+            ("jobs", self.jobs.map(|v| v.to_string())),
+        ]
+        .into_iter()
+        .filter_map(|(id, value)| Some((id, value?)))
+        .collect()
+    }
+
+    /// Whether parsing has to be redone with [`Config::apply_to`] for this config to matter
+    #[must_use]
+    pub fn has_flag_defaults(&self) -> bool {
+        !self.flag_defaults().is_empty()
+    }
+
+    /// `command` with the processing flags of this config as the defaults of the subcommands
+    /// that take them. Values are checked by clap when the command line is parsed.
+    #[must_use]
+    pub fn apply_to(&self, mut command: Command) -> Command {
+        let defaults = self.flag_defaults();
+        for name in PROCESSING_COMMANDS {
+            command = command.mut_subcommand(name, |mut sub| {
+                for (id, value) in &defaults {
+                    if sub.get_arguments().any(|arg| arg.get_id() == *id) {
+                        sub = sub.mut_arg(*id, |arg| arg.default_value(value.clone()));
+                    }
+                }
+                sub
+            });
+        }
+        command
+    }
+}
+
+/// The first value given: the command line, then the environment, then the config file
+#[must_use]
+pub fn layered<T>(flag: Option<T>, env: Option<T>, config: Option<T>) -> Option<T> {
+    flag.or(env).or(config)
+}
+
+/// Load `path`, or `config.json` in `home` when no path is given. A missing default file is an
+/// empty config; a missing explicit file is an error.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read or parsed, or `path` does not exist.
+pub fn load_config(home: &AppHome, path: Option<&Path>) -> eyre::Result<Config> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => {
+            let path = home.file_path(FILE_NAME);
+            if !path.exists() {
+                return Ok(Config::default());
+            }
+            path
+        }
+    };
+    let text = fs::read_to_string(&path)
+        .map_err(|e| eyre::eyre!("Failed to read config {}: {}", path.display(), e))?;
+    facet_json::from_str(&text)
+        .map_err(|e| eyre::eyre!("Failed to parse config {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::Cli;
+    use crate::cli::command::Command as CliCommand;
+    use crate::cli::command::process::processing_args::ProcessingArgs;
+    use crate::cli::to_args::ToArgs;
+    use clap::CommandFactory;
+    use clap::FromArgMatches;
+    use tempfile::tempdir;
+
+    fn processing_args(config: &Config, args: &[&str]) -> ProcessingArgs {
+        let matches = config
+            .apply_to(Cli::command())
+            .try_get_matches_from(["cm", "process"].into_iter().chain(args.iter().copied()))
+            .unwrap();
+        match Cli::from_arg_matches(&matches).unwrap().command {
+            Some(CliCommand::Process(args)) => args.processing,
+            other => panic!("expected process, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn flags_beat_env_beat_config_beat_defaults() {
+        assert_eq!(layered(Some(1), Some(2), Some(3)), Some(1));
+        assert_eq!(layered(None, Some(2), Some(3)), Some(2));
+        assert_eq!(layered(None, None, Some(3)), Some(3));
+        assert_eq!(layered::<u8>(None, None, None), None);
+
+        let config = Config {
+            jpeg_quality: Some(70),
+            crop: Some(true),
+            ..Config::default()
+        };
+        let from_config = processing_args(&config, &[]);
+        assert_eq!(from_config.jpeg_quality, 70);
+        assert!(from_config.crop);
+        assert_eq!(from_config.crop_threshold, 20);
+
+        let from_flag = processing_args(&config, &["--jpeg-quality", "95", "--crop=false"]);
+        assert_eq!(from_flag.jpeg_quality, 95);
+        assert!(!from_flag.crop);
+        assert!(processing_args(&Config::default(), &["--crop"]).crop);
+        assert_eq!(processing_args(&Config::default(), &[]).jpeg_quality, 90);
+    }
+
+    #[test]
+    fn switches_turned_off_over_a_config_default_round_trip() {
+        let config = Config {
+            crop: Some(true),
+            jpeg_progressive: Some(true),
+            ..Config::default()
+        };
+        let parsed = processing_args(&config, &["--crop=false", "--jpeg-progressive=false"]);
+        assert!(!parsed.crop && !parsed.jpeg_progressive);
+        let args: Vec<String> = parsed
+            .to_args()
+            .iter()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        assert_eq!(processing_args(&config, &args), parsed);
+    }
+
+    #[test]
+    fn missing_default_file_is_empty_but_missing_explicit_file_is_an_error() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+        assert_eq!(load_config(&home, None)?, Config::default());
+        assert!(load_config(&home, Some(&td.path().join("nope.json"))).is_err());
+
+        fs::write(
+            home.file_path(FILE_NAME),
+            r#"{"site_id":"abc123","jpeg_quality":75}"#,
+        )?;
+        let config = load_config(&home, None)?;
+        assert_eq!(config.site_id.as_deref(), Some("abc123"));
+        assert_eq!(config.jpeg_quality, Some(75));
+        assert!(config.has_flag_defaults());
+        Ok(())
+    }
+}
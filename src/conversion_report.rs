@@ -0,0 +1,151 @@
+//! Before/after file sizes of a batch, to quantify what a quality or format change saves
+
+use crate::image_processing::ProcessReportEntry;
+use facet::Facet;
+use std::path::Path;
+
+/// Input and output size of one processed image
+#[derive(Clone, Debug, PartialEq, Eq, Facet)]
+pub struct ConversionEntry {
+    pub input: String,
+    pub output: String,
+    pub input_size: u64,
+    pub output_size: u64,
+}
+
+impl ConversionEntry {
+    /// Pair `output_size` with the size of `input` on disk, or `None` if it can't be read
+    #[must_use]
+    pub fn from_input(input: &Path, output: &Path, output_size: u64) -> Option<Self> {
+        let input_size = std::fs::metadata(input).ok()?.len();
+        Some(Self {
+            input: input.display().to_string(),
+            output: output.display().to_string(),
+            input_size,
+            output_size,
+        })
+    }
+
+    /// Percentage of the input size saved by the output (negative when it grew)
+    #[must_use]
+    pub fn percent_saved(&self) -> f64 {
+        percent_saved(self.input_size, self.output_size)
+    }
+}
+
+/// Per-file sizes of a batch with its totals
+#[derive(Clone, Debug, Default, PartialEq, Facet)]
+pub struct ConversionReport {
+    pub files: Vec<ConversionEntry>,
+    pub total_input_bytes: u64,
+    pub total_output_bytes: u64,
+    pub percent_saved: f64,
+}
+
+impl ConversionReport {
+    #[must_use]
+    pub fn new(files: Vec<ConversionEntry>) -> Self {
+        let total_input_bytes = files.iter().map(|f| f.input_size).sum();
+        let total_output_bytes = files.iter().map(|f| f.output_size).sum();
+        Self {
+            files,
+            total_input_bytes,
+            total_output_bytes,
+            percent_saved: percent_saved(total_input_bytes, total_output_bytes),
+        }
+    }
+
+    /// Build from a process report, reading each input's size from disk. Inputs that can no
+    /// longer be read are left out.
+    #[must_use]
+    pub fn from_process_report(entries: &[ProcessReportEntry]) -> Self {
+        Self::new(
+            entries
+                .iter()
+                .filter_map(|e| {
+                    ConversionEntry::from_input(
+                        Path::new(&e.input),
+                        Path::new(&e.output),
+                        e.estimated_size,
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    /// Bytes saved over the whole batch (negative when the outputs are larger)
+    #[must_use]
+    pub fn saved_bytes(&self) -> i128 {
+        i128::from(self.total_input_bytes) - i128::from(self.total_output_bytes)
+    }
+
+    /// The report as CSV with a header row, one row per file and a final totals row
+    #[must_use]
+    pub fn to_csv(&self) -> String {
+        fn field(value: &str) -> String {
+            if value.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", value.replace('"', "\"\""))
+            } else {
+                value.to_owned()
+            }
+        }
+        let mut out = String::from("input,output,input_size,output_size,percent_saved\n");
+        for entry in &self.files {
+            out.push_str(&format!(
+                "{},{},{},{},{:.1}\n",
+                field(&entry.input),
+                field(&entry.output),
+                entry.input_size,
+                entry.output_size,
+                entry.percent_saved()
+            ));
+        }
+        out.push_str(&format!(
+            "total,,{},{},{:.1}\n",
+            self.total_input_bytes, self.total_output_bytes, self.percent_saved
+        ));
+        out
+    }
+}
+
+#[expect(clippy::cast_precision_loss)]
+fn percent_saved(input: u64, output: u64) -> f64 {
+    if input == 0 {
+        return 0.0;
+    }
+    (input as f64 - output as f64) / input as f64 * 100.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, input_size: u64, output_size: u64) -> ConversionEntry {
+        ConversionEntry {
+            input: format!("in/{name}"),
+            output: format!("out/{name}"),
+            input_size,
+            output_size,
+        }
+    }
+
+    #[test]
+    fn report_totals_and_percent_saved() {
+        let report =
+            ConversionReport::new(vec![entry("a.png", 1000, 250), entry("b.jpg", 200, 250)]);
+        assert_eq!(report.total_input_bytes, 1200);
+        assert_eq!(report.total_output_bytes, 500);
+        assert_eq!(report.saved_bytes(), 700);
+        assert!((report.percent_saved - 58.333).abs() < 0.001);
+        assert!((report.files[0].percent_saved() - 75.0).abs() < f64::EPSILON);
+        assert!((report.files[1].percent_saved() + 25.0).abs() < f64::EPSILON);
+        assert_eq!(
+            report.to_csv(),
+            "input,output,input_size,output_size,percent_saved\n\
+             in/a.png,out/a.png,1000,250,75.0\n\
+             in/b.jpg,out/b.jpg,200,250,-25.0\n\
+             total,,1200,500,58.3\n"
+        );
+        assert!(ConversionReport::default().percent_saved.abs() < f64::EPSILON);
+    }
+}
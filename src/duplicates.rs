@@ -0,0 +1,137 @@
+//! Detection of identical files across the persisted inputs.
+
+use crate::app_home::AppHome;
+use crate::inputs;
+use sha2::Digest;
+use sha2::Sha256;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use tracing::warn;
+
+/// Hash a file's contents with SHA-256, returning the hex digest
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be read.
+pub fn hash_file(path: &Path) -> eyre::Result<String> {
+    let mut file = fs::File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Group files with identical contents.
+///
+/// Only groups with more than one member are returned. Within a group, files keep
+/// the order they had in `files`, so the first entry is the one to keep.
+/// Files that cannot be read are skipped with a warning.
+#[must_use]
+pub fn find_duplicates(files: &[PathBuf]) -> Vec<Vec<PathBuf>> {
+    // Bucket by size first so only files that could match are hashed
+    let mut by_size: BTreeMap<u64, Vec<&PathBuf>> = BTreeMap::new();
+    for file in files {
+        match fs::metadata(file) {
+            Ok(meta) => by_size.entry(meta.len()).or_default().push(file),
+            Err(e) => warn!("Failed to read metadata for {}: {}", file.display(), e),
+        }
+    }
+
+    let mut groups: Vec<Vec<PathBuf>> = Vec::new();
+    for candidates in by_size.into_values().filter(|v| v.len() > 1) {
+        let mut by_hash: HashMap<String, usize> = HashMap::new();
+        let mut local: Vec<Vec<PathBuf>> = Vec::new();
+        for file in candidates {
+            let hash = match hash_file(file) {
+                Ok(h) => h,
+                Err(e) => {
+                    warn!("Failed to hash {}: {}", file.display(), e);
+                    continue;
+                }
+            };
+            if let Some(&idx) = by_hash.get(&hash) {
+                local[idx].push(file.clone());
+            } else {
+                by_hash.insert(hash, local.len());
+                local.push(vec![file.clone()]);
+            }
+        }
+        groups.extend(local.into_iter().filter(|g| g.len() > 1));
+    }
+
+    // Report groups in the order their first member appears in the input
+    let position: HashMap<&PathBuf, usize> =
+        files.iter().enumerate().map(|(i, p)| (p, i)).collect();
+    groups.sort_by_key(|g| position.get(&g[0]).copied().unwrap_or(usize::MAX));
+    groups
+}
+
+/// Find duplicate sets among all files reachable from the persisted inputs
+///
+/// # Errors
+///
+/// Returns an error if the inputs cannot be listed.
+pub fn find_input_duplicates(home: &AppHome) -> eyre::Result<Vec<Vec<PathBuf>>> {
+    Ok(find_duplicates(&inputs::list_files(home)?))
+}
+
+/// Remove all but the first file of each duplicate set from the persisted inputs.
+///
+/// Files are never deleted from disk. A duplicate that is only reachable through an
+/// input directory cannot be removed individually and is reported in the second list.
+///
+/// # Errors
+///
+/// Returns an error if the inputs cannot be loaded or saved.
+pub fn remove_duplicates_from_inputs(
+    home: &AppHome,
+    groups: &[Vec<PathBuf>],
+) -> eyre::Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let mut removed = Vec::new();
+    let mut kept_in_dir = Vec::new();
+    for group in groups {
+        for dup in group.iter().skip(1) {
+            if inputs::remove_path(home, dup)? {
+                removed.push(dup.clone());
+            } else {
+                kept_in_dir.push(dup.clone());
+            }
+        }
+    }
+    Ok((removed, kept_in_dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn identical_files_are_grouped() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().join("home"));
+
+        let a = td.path().join("a.jpg");
+        let b = td.path().join("b.jpg");
+        let c = td.path().join("c.jpg");
+        fs::write(&a, b"same bytes")?;
+        fs::write(&b, b"same bytes")?;
+        fs::write(&c, b"different!")?;
+
+        inputs::add_paths(&home, &[a.clone(), b.clone(), c.clone()])?;
+
+        let groups = find_input_duplicates(&home)?;
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].len(), 2);
+
+        let (removed, kept_in_dir) = remove_duplicates_from_inputs(&home, &groups)?;
+        assert_eq!(removed.len(), 1);
+        assert!(kept_in_dir.is_empty());
+        assert_eq!(inputs::load_inputs(&home)?.len(), 2);
+        // Files stay on disk
+        assert!(a.exists() && b.exists());
+        Ok(())
+    }
+}
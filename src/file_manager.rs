@@ -0,0 +1,130 @@
+//! Reveal files and folders in the host file manager.
+
+use std::path::Path;
+#[cfg(not(windows))]
+use std::process::Command;
+use tracing::debug;
+
+/// Reveal `path` in the host file manager (Explorer/Finder/the freedesktop file manager).
+///
+/// The file is selected inside its folder where the platform supports it. On Linux that needs
+/// a file manager implementing the `org.freedesktop.FileManager1` D-Bus interface; otherwise the
+/// containing folder (or `path` itself, if it is a directory) is opened with `xdg-open`.
+///
+/// # Errors
+///
+/// Returns an error if the file manager could not be launched.
+pub fn reveal_in_file_manager(path: &Path) -> eyre::Result<()> {
+    debug!("Revealing in file manager: {}", path.display());
+
+    #[cfg(windows)]
+    {
+        // Pass the path as a single-element iterator. Passing `path` directly can
+        // cause the function to iterate the path's components (treating it like an
+        // iterator), which results in incorrect behavior (e.g., paths like `C:\`).
+        teamy_windows::shell::select::open_folder_and_select_items(&[path])
+            .map_err(|e| eyre::eyre!("Failed to open in explorer: {:?}", e))?;
+    }
+    #[cfg(not(windows))]
+    {
+        let mut commands = reveal_commands(path).into_iter().peekable();
+        while let Some(mut command) = commands.next() {
+            if commands.peek().is_none() {
+                // Last resort: report failures to launch it
+                command.spawn()?;
+                break;
+            }
+            match command.output() {
+                Ok(output) if output.status.success() => break,
+                Ok(output) => debug!(
+                    "{:?} failed ({}), trying the next way to reveal",
+                    command.get_program(),
+                    output.status
+                ),
+                Err(e) => debug!(
+                    "{:?} could not be run ({}), trying the next way to reveal",
+                    command.get_program(),
+                    e
+                ),
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The commands that can reveal `path`, in the order they should be tried. All but the last
+/// are run to completion to see whether they worked.
+#[cfg(target_os = "macos")]
+fn reveal_commands(path: &Path) -> Vec<Command> {
+    let mut open = Command::new("open");
+    open.arg("-R").arg(path);
+    vec![open]
+}
+
+/// The commands that can reveal `path`, in the order they should be tried. All but the last
+/// are run to completion to see whether they worked.
+#[cfg(all(not(windows), not(target_os = "macos")))]
+fn reveal_commands(path: &Path) -> Vec<Command> {
+    let mut commands = Vec::new();
+    if let Some(uri) = std::path::absolute(path)
+        .ok()
+        .and_then(|p| reqwest::Url::from_file_path(p).ok())
+    {
+        let mut show_items = Command::new("dbus-send");
+        show_items.args([
+            "--session",
+            "--print-reply",
+            "--dest=org.freedesktop.FileManager1",
+            "--type=method_call",
+            "/org/freedesktop/FileManager1",
+            "org.freedesktop.FileManager1.ShowItems",
+            &format!("array:string:{uri}"),
+            "string:",
+        ]);
+        commands.push(show_items);
+    }
+
+    let dir = if path.is_dir() {
+        path
+    } else {
+        path.parent().unwrap_or(path)
+    };
+    let mut xdg_open = Command::new("xdg-open");
+    xdg_open.arg(dir);
+    commands.push(xdg_open);
+    commands
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(not(windows))]
+    use super::*;
+
+    #[cfg(not(windows))]
+    fn describe(command: &Command) -> Vec<String> {
+        std::iter::once(command.get_program())
+            .chain(command.get_args())
+            .map(|s| s.to_string_lossy().into_owned())
+            .collect()
+    }
+
+    #[cfg(target_os = "macos")]
+    #[test]
+    fn macos_reveals_with_open() {
+        let commands = reveal_commands(Path::new("/tmp/out/a b.png"));
+        let described: Vec<_> = commands.iter().map(describe).collect();
+        assert_eq!(described, [["open", "-R", "/tmp/out/a b.png"]]);
+    }
+
+    #[cfg(all(not(windows), not(target_os = "macos")))]
+    #[test]
+    fn linux_selects_over_dbus_then_falls_back_to_the_folder() {
+        let commands = reveal_commands(Path::new("/tmp/out/a b.png"));
+        let described: Vec<_> = commands.iter().map(describe).collect();
+        assert_eq!(described.len(), 2);
+        assert_eq!(described[0][0], "dbus-send");
+        assert!(described[0].contains(&"org.freedesktop.FileManager1.ShowItems".to_owned()));
+        assert!(described[0].contains(&"array:string:file:///tmp/out/a%20b.png".to_owned()));
+        assert_eq!(described[1], ["xdg-open", "/tmp/out"]);
+    }
+}
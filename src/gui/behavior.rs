@@ -32,6 +32,8 @@ pub enum CmPane {
     ImageDescription,
     /// Product Search (Searchspring)
     ProductSearch,
+    /// Wrapping grid of input image thumbnails
+    ThumbnailGrid,
 }
 
 impl CmPane {
@@ -49,6 +51,7 @@ impl CmPane {
             CmPane::OutputImagePreview => "Output Preview Image",
             CmPane::ImageDescription => "Image Description",
             CmPane::ProductSearch => "Product Search",
+            CmPane::ThumbnailGrid => "Thumbnail Grid",
         }
     }
 
@@ -66,6 +69,7 @@ impl CmPane {
             CmPane::OutputImagePreview => "OutputImagePreview",
             CmPane::ImageDescription => "ImageDescription",
             CmPane::ProductSearch => "ProductSearch",
+            CmPane::ThumbnailGrid => "ThumbnailGrid",
         }
     }
 
@@ -83,6 +87,7 @@ impl CmPane {
             "OutputImagePreview" => CmPane::OutputImagePreview,
             "ImageDescription" => CmPane::ImageDescription,
             "ProductSearch" => CmPane::ProductSearch,
+            "ThumbnailGrid" => CmPane::ThumbnailGrid,
             _ => return None,
         })
     }
@@ -97,8 +102,12 @@ pub struct CmBehavior<'a> {
     pub threshold_texture_path: &'a mut Option<PathBuf>,
     pub input_pan_zoom: &'a mut tiles::PanZoomState,
     pub threshold_pan_zoom: &'a mut tiles::PanZoomState,
+    pub threshold_montage: &'a mut tiles::ThresholdMontage,
     pub output_pan_zoom: &'a mut tiles::PanZoomState,
     pub thumbnail_textures: &'a mut HashMap<PathBuf, TextureHandle>,
+    pub product_thumbnail_textures: &'a mut HashMap<String, TextureHandle>,
+    /// Set when the user moves, resizes, or closes tiles, so the layout gets autosaved
+    pub layout_edited: &'a mut bool,
 }
 
 impl egui_tiles::Behavior<CmPane> for CmBehavior<'_> {
@@ -130,6 +139,7 @@ impl egui_tiles::Behavior<CmPane> for CmBehavior<'_> {
                 self.threshold_texture,
                 self.threshold_texture_path,
                 self.threshold_pan_zoom,
+                self.threshold_montage,
             ),
             CmPane::OutputImagePreview => tiles::draw_output_image_preview_tile(
                 ui,
@@ -139,13 +149,22 @@ impl egui_tiles::Behavior<CmPane> for CmBehavior<'_> {
                 self.output_pan_zoom,
             ),
             CmPane::ImageDescription => tiles::draw_image_description_tile(ui, self.state),
-            CmPane::ProductSearch => tiles::draw_product_search_tile(ui, self.state),
+            CmPane::ProductSearch => {
+                tiles::draw_product_search_tile(ui, self.state, self.product_thumbnail_textures);
+            }
+            CmPane::ThumbnailGrid => {
+                tiles::draw_thumbnail_grid_tile(ui, self.state, self.thumbnail_textures);
+            }
         }
 
         // For now, no drag response
         egui_tiles::UiResponse::None
     }
 
+    fn on_edit(&mut self, _edit_action: egui_tiles::EditAction) {
+        *self.layout_edited = true;
+    }
+
     fn simplification_options(&self) -> egui_tiles::SimplificationOptions {
         egui_tiles::SimplificationOptions {
             all_panes_must_have_tabs: true,
@@ -178,9 +197,11 @@ pub fn create_default_tree() -> egui_tiles::Tree<CmPane> {
     let output_image_preview_id = tiles.insert_pane(CmPane::OutputImagePreview);
     let image_description_id = tiles.insert_pane(CmPane::ImageDescription);
     let product_search_id = tiles.insert_pane(CmPane::ProductSearch);
+    let thumbnail_grid_id = tiles.insert_pane(CmPane::ThumbnailGrid);
 
-    // Left column: Input Paths + Input Images (vertical)
-    let left_column = tiles.insert_vertical_tile(vec![input_paths_id, input_images_id]);
+    // Left column: Input Paths + Input Images/Thumbnail Grid tabs (vertical)
+    let images_tabs = tiles.insert_tab_tile(vec![input_images_id, thumbnail_grid_id]);
+    let left_column = tiles.insert_vertical_tile(vec![input_paths_id, images_tabs]);
 
     // Middle-left column: Image previews stacked vertically (input, threshold, output)
     let previews_column = tiles.insert_vertical_tile(vec![
@@ -0,0 +1,66 @@
+//! Fonts of the GUI. egui's built-in fonts cover the emoji used in labels; DejaVu Sans is
+//! bundled as the last fallback for the arrows, shapes and dingbats they lack, so labels look
+//! the same on every platform instead of depending on what the system has installed.
+
+use eframe::egui::FontData;
+use eframe::egui::FontDefinitions;
+use eframe::egui::FontFamily;
+use eframe::egui::{self};
+use std::sync::Arc;
+
+const SYMBOL_FONT_NAME: &str = "DejaVuSans";
+const SYMBOL_FONT: &[u8] = include_bytes!("../../resources/fonts/DejaVuSans.ttf");
+
+/// egui's default fonts with the bundled symbol font appended to every family
+#[must_use]
+pub fn font_definitions() -> FontDefinitions {
+    let mut fonts = FontDefinitions::default();
+    fonts.font_data.insert(
+        SYMBOL_FONT_NAME.to_owned(),
+        Arc::new(FontData::from_static(SYMBOL_FONT)),
+    );
+    for family in [FontFamily::Proportional, FontFamily::Monospace] {
+        fonts
+            .families
+            .entry(family)
+            .or_default()
+            .push(SYMBOL_FONT_NAME.to_owned());
+    }
+    fonts
+}
+
+/// Use [`font_definitions`] for `ctx`
+pub fn install_fonts(ctx: &egui::Context) {
+    ctx.set_fonts(font_definitions());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ab_glyph::Font;
+    use ab_glyph::FontRef;
+
+    #[test]
+    fn label_symbols_have_a_glyph_in_some_bundled_font() {
+        let fonts: Vec<FontRef<'static>> = [
+            epaint_default_fonts::UBUNTU_LIGHT,
+            epaint_default_fonts::NOTO_EMOJI_REGULAR,
+            epaint_default_fonts::EMOJI_ICON,
+            SYMBOL_FONT,
+        ]
+        .into_iter()
+        .map(|data| FontRef::try_from_slice(data).unwrap())
+        .collect();
+        for symbol in "✖⚠●→…▶⚙⊞⊟⬆⬇✔↻▦☰❐".chars() {
+            assert!(
+                fonts.iter().any(|font| font.glyph_id(symbol).0 != 0),
+                "no bundled font has {symbol}"
+            );
+        }
+        let definitions = font_definitions();
+        assert_eq!(
+            definitions.families[&FontFamily::Proportional].last(),
+            Some(&SYMBOL_FONT_NAME.to_owned())
+        );
+    }
+}
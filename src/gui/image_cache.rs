@@ -0,0 +1,166 @@
+//! Cache of image metadata and thumbnails that evicts the least recently used entries once the
+//! thumbnails grow past a byte budget
+
+use crate::gui::state::CachedImageInfo;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+struct Entry {
+    info: CachedImageInfo,
+    last_access: Cell<u64>,
+}
+
+/// Least-recently-used cache of [`CachedImageInfo`], bounded by total thumbnail bytes
+#[derive(Debug)]
+pub struct ImageCache {
+    entries: HashMap<PathBuf, Entry>,
+    /// Bumped on every access so entries can be ordered by recency
+    clock: Cell<u64>,
+    total_bytes: usize,
+    max_bytes: usize,
+}
+
+impl ImageCache {
+    #[must_use]
+    pub fn new(max_bytes: usize) -> Self {
+        Self {
+            entries: HashMap::new(),
+            clock: Cell::new(0),
+            total_bytes: 0,
+            max_bytes,
+        }
+    }
+
+    fn tick(&self) -> u64 {
+        let now = self.clock.get() + 1;
+        self.clock.set(now);
+        now
+    }
+
+    /// Look up an entry, marking it as recently used
+    #[must_use]
+    pub fn get(&self, path: &Path) -> Option<&CachedImageInfo> {
+        let entry = self.entries.get(path)?;
+        entry.last_access.set(self.tick());
+        Some(&entry.info)
+    }
+
+    /// Look up an entry without affecting eviction order, for bulk scans like sorting
+    #[must_use]
+    pub fn peek(&self, path: &Path) -> Option<&CachedImageInfo> {
+        self.entries.get(path).map(|e| &e.info)
+    }
+
+    #[must_use]
+    pub fn contains_key(&self, path: &Path) -> bool {
+        self.entries.contains_key(path)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Thumbnail bytes currently held
+    #[must_use]
+    pub fn total_bytes(&self) -> usize {
+        self.total_bytes
+    }
+
+    /// Insert or replace an entry, then evict least recently used entries until the thumbnails
+    /// fit the budget again. The new entry is never evicted. Returns the evicted paths.
+    pub fn insert(&mut self, path: PathBuf, info: CachedImageInfo) -> Vec<PathBuf> {
+        if let Some(old) = self.entries.remove(&path) {
+            self.total_bytes -= old.info.thumbnail_data.len();
+        }
+        let new_bytes = info.thumbnail_data.len();
+        let evicted = self.evict_to_fit(self.max_bytes.saturating_sub(new_bytes));
+        self.total_bytes += new_bytes;
+        let entry = Entry {
+            info,
+            last_access: Cell::new(self.tick()),
+        };
+        self.entries.insert(path, entry);
+        evicted
+    }
+
+    /// Change the thumbnail byte budget, returning any paths evicted to meet it
+    pub fn set_max_bytes(&mut self, max_bytes: usize) -> Vec<PathBuf> {
+        self.max_bytes = max_bytes;
+        self.evict_to_fit(max_bytes)
+    }
+
+    fn evict_to_fit(&mut self, budget: usize) -> Vec<PathBuf> {
+        let mut evicted = Vec::new();
+        while self.total_bytes > budget {
+            let Some(oldest) = self
+                .entries
+                .iter()
+                .min_by_key(|(_, e)| e.last_access.get())
+                .map(|(p, _)| p.clone())
+            else {
+                break;
+            };
+            if let Some(entry) = self.entries.remove(&oldest) {
+                self.total_bytes -= entry.info.thumbnail_data.len();
+            }
+            evicted.push(oldest);
+        }
+        evicted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(bytes: usize) -> CachedImageInfo {
+        CachedImageInfo {
+            width: 1,
+            height: 1,
+            file_size: 1,
+            modified: None,
+            thumbnail_data: vec![0; bytes],
+        }
+    }
+
+    #[test]
+    fn inserting_past_the_cap_evicts_least_recently_used() {
+        let mut cache = ImageCache::new(30);
+        for name in ["a", "b", "c"] {
+            assert!(cache.insert(PathBuf::from(name), info(10)).is_empty());
+        }
+
+        // Reading "a" makes "b" the oldest
+        assert!(cache.get(Path::new("a")).is_some());
+        assert!(cache.peek(Path::new("b")).is_some());
+        assert_eq!(
+            cache.insert(PathBuf::from("d"), info(10)),
+            vec![PathBuf::from("b")]
+        );
+        assert_eq!(cache.len(), 3);
+        assert_eq!(cache.total_bytes(), 30);
+
+        // Shrinking the budget evicts oldest first
+        assert_eq!(
+            cache.set_max_bytes(10),
+            vec![PathBuf::from("c"), PathBuf::from("a")]
+        );
+        assert!(cache.contains_key(Path::new("d")));
+
+        // An entry bigger than the whole budget is still kept
+        assert_eq!(
+            cache.insert(PathBuf::from("big"), info(50)),
+            vec![PathBuf::from("d")]
+        );
+        assert!(cache.contains_key(Path::new("big")));
+    }
+}
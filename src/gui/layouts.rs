@@ -1,12 +1,31 @@
 use crate::app_home::APP_HOME;
+use crate::atomic_write::write_atomic;
 use crate::gui::behavior::CmPane;
 use eframe::egui::Id;
 use egui_tiles::Tree;
 use facet::Facet;
 use std::fs;
-use std::io::Write;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
+use tracing::warn;
+
+/// Layout presets compiled into the binary, as `(name, json)` pairs
+const BUILTIN_PRESETS: &[(&str, &str)] = &[
+    (
+        "Processing",
+        include_str!("../../resources/layouts/processing.layout"),
+    ),
+    (
+        "Review",
+        include_str!("../../resources/layouts/review.layout"),
+    ),
+    (
+        "Search focused",
+        include_str!("../../resources/layouts/search_focused.layout"),
+    ),
+];
 
 #[derive(Debug, Clone, PartialEq, Facet)]
 pub struct Node {
@@ -34,6 +53,42 @@ impl Layout {
         Some(Layout { name, root })
     }
 
+    /// Check that every pane and container key in the layout is known to this build.
+    ///
+    /// Each problem is reported with its path in the tree, e.g. `root/1/0: unknown pane "Foo"`.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        fn visit(node: &Node, path: &str, problems: &mut Vec<String>) {
+            match node.variant.as_str() {
+                "Pane" => match node.pane.as_deref() {
+                    Some(key) if CmPane::from_key(key).is_some() => {}
+                    Some(key) => problems.push(format!("{path}: unknown pane {key:?}")),
+                    None => problems.push(format!("{path}: pane has no key")),
+                },
+                "Container" => {
+                    match node.kind.as_deref() {
+                        Some("Tabs" | "Horizontal" | "Vertical" | "Grid") => {}
+                        Some(kind) => {
+                            problems.push(format!("{path}: unknown container kind {kind:?}"));
+                        }
+                        None => problems.push(format!("{path}: container has no kind")),
+                    }
+                    for (i, child) in node.children.iter().flatten().enumerate() {
+                        visit(child, &format!("{path}/{i}"), problems);
+                    }
+                }
+                other => problems.push(format!("{path}: unknown node variant {other:?}")),
+            }
+        }
+
+        let mut problems = Vec::new();
+        visit(&self.root, "root", &mut problems);
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems)
+        }
+    }
+
     pub fn apply_to_tree(&self, tree_id: impl Into<Id>) -> Tree<CmPane> {
         fn build(node: &Node, tiles: &mut egui_tiles::Tiles<CmPane>) -> egui_tiles::TileId {
             if node.variant == "Pane" {
@@ -102,22 +157,98 @@ fn node_from_tile(tree: &Tree<CmPane>, tile_id: egui_tiles::TileId) -> Node {
 pub struct LayoutManager {
     pub custom_dir: PathBuf,
     pub preset_dir: PathBuf,
+    /// File holding the name of the active layout, so it survives restarts
+    pub active_file: PathBuf,
     pub active: Option<String>,
     last_saved_text: Option<String>,
+    /// Set when the tree may have changed since the last autosave check
+    dirty: bool,
+    last_autosave: Option<Instant>,
 }
 
+/// Minimum time between autosave checks, so dragging tiles doesn't rewrite the layout every frame
+pub const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(1);
+
 impl LayoutManager {
     pub fn new() -> Self {
-        let dir = APP_HOME.file_path("layouts");
+        Self::in_dir(&APP_HOME.file_path("layouts"))
+    }
+
+    /// Manager for layouts stored under `dir`, restoring the previously active layout if it
+    /// still loads and validates
+    pub fn in_dir(dir: &Path) -> Self {
         let custom_dir = dir.join("custom");
         let preset_dir = dir.join("presets");
         let _ = fs::create_dir_all(&custom_dir);
         let _ = fs::create_dir_all(&preset_dir);
-        LayoutManager {
+        let mut manager = LayoutManager {
             custom_dir,
             preset_dir,
+            active_file: dir.join("active.txt"),
             active: None,
             last_saved_text: None,
+            dirty: false,
+            last_autosave: None,
+        };
+        manager.install_builtin_presets();
+        manager.active = manager.restore_active();
+        manager
+    }
+
+    /// Read the persisted active layout name, dropping it if the layout is gone or invalid
+    fn restore_active(&self) -> Option<String> {
+        let name = fs::read_to_string(&self.active_file).ok()?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        let layout = match self.load_named(name) {
+            Ok(layout) => layout,
+            Err(e) => {
+                warn!(
+                    "Active layout {} could not be loaded, using default: {}",
+                    name, e
+                );
+                return None;
+            }
+        };
+        if let Err(problems) = layout.validate() {
+            warn!(
+                "Active layout {} is invalid, using default: {}",
+                name,
+                problems.join("; ")
+            );
+            return None;
+        }
+        Some(name.to_string())
+    }
+
+    /// Persist the active layout name (or its absence)
+    fn persist_active(&self) {
+        let result = match &self.active {
+            Some(name) => write_atomic(&self.active_file, name),
+            None if self.active_file.exists() => {
+                fs::remove_file(&self.active_file).map_err(eyre::Report::from)
+            }
+            None => Ok(()),
+        };
+        if let Err(e) = result {
+            warn!("Failed to persist active layout: {}", e);
+        }
+    }
+
+    /// Write any built-in preset that is missing from the preset dir
+    fn install_builtin_presets(&self) {
+        for (name, text) in BUILTIN_PRESETS {
+            if self.layout_file_for_preset(name).exists() {
+                continue;
+            }
+            let result = facet_json::from_str::<Layout>(text)
+                .map_err(|e| eyre::eyre!("{e}"))
+                .and_then(|layout| self.save_preset(name, &layout));
+            if let Err(e) = result {
+                warn!("Failed to install preset {}: {}", name, e);
+            }
         }
     }
 
@@ -153,19 +284,14 @@ impl LayoutManager {
         }
         let path = self.layout_file_for_custom(&new_name);
         let text = facet_json::to_string(layout)?;
-        fs::write(&path, text)?;
+        write_atomic(&path, text)?;
         Ok(new_name)
     }
 
     pub fn save_preset(&self, name: &str, layout: &Layout) -> eyre::Result<()> {
         let path = self.layout_file_for_preset(name);
         let text = facet_json::to_string(layout)?;
-        let mut f = fs::OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(&path)?;
-        f.write_all(text.as_bytes())?;
+        write_atomic(&path, text)?;
         Ok(())
     }
 
@@ -173,12 +299,7 @@ impl LayoutManager {
         if let Some(active) = &self.active {
             let path = self.layout_file_for_custom(active);
             let text = facet_json::to_string(layout)?;
-            let mut f = fs::OpenOptions::new()
-                .create(true)
-                .write(true)
-                .truncate(true)
-                .open(&path)?;
-            f.write_all(text.as_bytes())?;
+            write_atomic(&path, &text)?;
             self.last_saved_text = Some(text);
         }
         Ok(())
@@ -186,18 +307,23 @@ impl LayoutManager {
 
     pub fn load_named(&self, name: &str) -> eyre::Result<Layout> {
         let path_custom = self.layout_file_for_custom(name);
-        if path_custom.exists() {
-            let s = fs::read_to_string(&path_custom)?;
-            let l: Layout = facet_json::from_str(&s)?;
-            return Ok(l);
-        }
-        let path_preset = self.layout_file_for_preset(name);
-        if path_preset.exists() {
-            let s = fs::read_to_string(&path_preset)?;
-            let l: Layout = facet_json::from_str(&s)?;
-            return Ok(l);
+        let path = if path_custom.exists() {
+            path_custom
+        } else {
+            let path_preset = self.layout_file_for_preset(name);
+            if !path_preset.exists() {
+                return Err(eyre::eyre!("Unknown layout: {}", name));
+            }
+            path_preset
+        };
+        let s = fs::read_to_string(&path)?;
+        let l: Layout = facet_json::from_str(&s)?;
+        if let Err(problems) = l.validate() {
+            for problem in problems {
+                warn!("Layout {} ({}): {}", name, path.display(), problem);
+            }
         }
-        Err(eyre::eyre!("Unknown layout: {}", name))
+        Ok(l)
     }
 
     /// Activate a preset by copying it into a new custom layout, then returning its new name.
@@ -210,13 +336,26 @@ impl LayoutManager {
         let new_name = format!("Custom from {preset_name}");
         let new_name = self.create_custom_from_layout(&new_name, &layout)?;
         self.active = Some(new_name.clone());
+        self.persist_active();
         Ok(new_name)
     }
 
     pub fn set_active(&mut self, name: &str) {
         self.active = Some(name.to_string());
+        self.persist_active();
         // reset last_saved so first save will write to disk
         self.last_saved_text = None;
+        self.dirty = true;
+    }
+
+    /// Note that the tree was edited so the next [`Self::maybe_autosave`] checks it
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Whether an edit is still waiting to be autosaved
+    pub fn is_dirty(&self) -> bool {
+        self.dirty
     }
 
     pub fn active_name(&self) -> Option<&str> {
@@ -229,16 +368,120 @@ impl LayoutManager {
             if path.exists() {
                 fs::remove_file(path)?;
                 self.active = None;
+                self.persist_active();
             }
         }
         Ok(())
     }
 
-    /// Compare layout text and save if changed
-    pub fn maybe_autosave(&mut self, layout: &Layout) -> eyre::Result<()> {
-        let text = facet_json::to_string(layout)?;
+    /// Rename a custom layout, moving its `.layout` file. Returns the final (unique) name.
+    pub fn rename(&mut self, old: &str, new: &str) -> eyre::Result<String> {
+        let new = new.trim();
+        if new.is_empty() {
+            return Err(eyre::eyre!("Layout name cannot be empty"));
+        }
+        let old_path = self.layout_file_for_custom(old);
+        if !old_path.exists() {
+            return Err(eyre::eyre!("Unknown layout: {}", old));
+        }
+        if sanitize_name(old) == sanitize_name(new) {
+            return Ok(old.to_string());
+        }
+        let mut new_name = new.to_string();
+        let mut i = 1;
+        while self.layout_file_for_custom(&new_name).exists() {
+            i += 1;
+            new_name = format!("{new} {i}");
+        }
+        let mut layout = self.load_named(old)?;
+        layout.name.clone_from(&new_name);
+        write_atomic(
+            &self.layout_file_for_custom(&new_name),
+            facet_json::to_string(&layout)?,
+        )?;
+        fs::remove_file(old_path)?;
+        if self.active.as_deref() == Some(old) {
+            self.active = Some(new_name.clone());
+            self.persist_active();
+        }
+        Ok(new_name)
+    }
+
+    /// Delete a custom layout by name, clearing the active layout if it was deleted
+    pub fn delete_named(&mut self, name: &str) -> eyre::Result<()> {
+        let path = self.layout_file_for_custom(name);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+        if self.active.as_deref() == Some(name) {
+            self.active = None;
+            self.persist_active();
+        }
+        Ok(())
+    }
+
+    /// Write a layout (custom or preset) to an arbitrary `.layout` file for sharing
+    pub fn export_named(&self, name: &str, path: &Path) -> eyre::Result<()> {
+        let layout = self.load_named(name)?;
+        if let Some(parent) = path.parent()
+            && !parent.as_os_str().is_empty()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, facet_json::to_string(&layout)?)?;
+        Ok(())
+    }
+
+    /// Import a `.layout` file as a new custom layout. Returns the new layout's name.
+    ///
+    /// Fails if the file references panes this build does not know about.
+    pub fn import_from_path(&mut self, path: &Path) -> eyre::Result<String> {
+        let text = fs::read_to_string(path)?;
+        let layout: Layout = facet_json::from_str(&text)
+            .map_err(|e| eyre::eyre!("Failed to parse {}: {}", path.display(), e))?;
+        if let Err(problems) = layout.validate() {
+            return Err(eyre::eyre!(
+                "Layout {} is invalid: {}",
+                path.display(),
+                problems.join("; ")
+            ));
+        }
+        let name = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map_or_else(|| layout.name.clone(), desanitize_name);
+        self.create_custom_from_layout(&name, &layout)
+    }
+
+    /// Copy a custom layout under a new unique name. Returns the copy's name.
+    pub fn duplicate(&mut self, name: &str) -> eyre::Result<String> {
+        let layout = self.load_named(name)?;
+        self.create_custom_from_layout(&format!("{name} copy"), &layout)
+    }
+
+    /// Save the tree as the active layout if it was edited and [`AUTOSAVE_INTERVAL`] has passed
+    /// since the last save. The serialized text is still compared so unchanged layouts are not
+    /// rewritten.
+    pub fn maybe_autosave(&mut self, tree: &Tree<CmPane>) -> eyre::Result<()> {
+        self.maybe_autosave_at(tree, Instant::now())
+    }
+
+    fn maybe_autosave_at(&mut self, tree: &Tree<CmPane>, now: Instant) -> eyre::Result<()> {
+        if !self.dirty
+            || self
+                .last_autosave
+                .is_some_and(|last| now.duration_since(last) < AUTOSAVE_INTERVAL)
+        {
+            return Ok(());
+        }
+        self.dirty = false;
+        self.last_autosave = Some(now);
+        let Some(layout) = Layout::from_tree(tree) else {
+            return Ok(());
+        };
+        let text = facet_json::to_string(&layout)?;
         if self.last_saved_text.as_deref() != Some(&text) {
-            self.save_active(layout)?;
+            self.save_active(&layout)?;
         }
         Ok(())
     }
@@ -257,6 +500,7 @@ fn list_names_in_dir(dir: &Path) -> Vec<String> {
             }
         }
     }
+    out.sort();
     out
 }
 
@@ -267,3 +511,140 @@ fn sanitize_name(name: &str) -> String {
 fn desanitize_name(name: &str) -> String {
     name.replace('_', " ")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_presets_parse() {
+        for (name, text) in BUILTIN_PRESETS {
+            let layout: Layout = facet_json::from_str(text)
+                .unwrap_or_else(|e| panic!("preset {name} should parse: {e}"));
+            assert_eq!(&layout.name, name);
+            assert_eq!(layout.validate(), Ok(()));
+        }
+    }
+
+    #[test]
+    fn rename_and_delete_custom_layout() -> eyre::Result<()> {
+        let td = tempfile::tempdir()?;
+        let mut manager = LayoutManager::in_dir(td.path());
+        let layout = Layout::from_tree(&crate::gui::behavior::create_default_tree())
+            .ok_or_else(|| eyre::eyre!("default tree should produce a layout"))?;
+        let name = manager.create_custom_from_layout("First", &layout)?;
+        manager.set_active(&name);
+
+        let renamed = manager.rename(&name, "Second")?;
+        assert_eq!(renamed, "Second");
+        assert_eq!(manager.active_name(), Some("Second"));
+        assert_eq!(manager.list_custom(), vec!["Second".to_string()]);
+
+        let copy = manager.duplicate("Second")?;
+        assert_eq!(manager.list_custom().len(), 2);
+
+        manager.delete_named("Second")?;
+        assert_eq!(manager.active_name(), None);
+        assert_eq!(manager.list_custom(), vec![copy]);
+        Ok(())
+    }
+
+    #[test]
+    fn active_layout_persists_across_managers() -> eyre::Result<()> {
+        let td = tempfile::tempdir()?;
+        let mut manager = LayoutManager::in_dir(td.path());
+        assert_eq!(manager.active_name(), None);
+        let layout = Layout::from_tree(&crate::gui::behavior::create_default_tree())
+            .ok_or_else(|| eyre::eyre!("default tree should produce a layout"))?;
+        let name = manager.create_custom_from_layout("Mine", &layout)?;
+        manager.set_active(&name);
+
+        let restored = LayoutManager::in_dir(td.path());
+        assert_eq!(restored.active_name(), Some("Mine"));
+
+        // A pointer to a deleted layout falls back to no active layout
+        manager.delete_named(&name)?;
+        fs::write(&manager.active_file, "Mine")?;
+        assert_eq!(LayoutManager::in_dir(td.path()).active_name(), None);
+
+        // So does a pointer to a layout that no longer validates
+        fs::write(
+            manager.custom_dir.join("Broken.layout"),
+            r#"{"name":"Broken","root":{"variant":"Pane","pane":"NoSuchPane","kind":null,"children":null}}"#,
+        )?;
+        fs::write(&manager.active_file, "Broken")?;
+        assert_eq!(LayoutManager::in_dir(td.path()).active_name(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn autosave_waits_for_edits_and_interval() -> eyre::Result<()> {
+        let td = tempfile::tempdir()?;
+        let mut manager = LayoutManager::in_dir(td.path());
+        let tree = crate::gui::behavior::create_default_tree();
+        let layout = Layout::from_tree(&tree)
+            .ok_or_else(|| eyre::eyre!("default tree should produce a layout"))?;
+        let name = manager.create_custom_from_layout("Mine", &layout)?;
+        let path = manager.layout_file_for_custom(&name);
+        manager.set_active(&name);
+
+        let start = Instant::now();
+        fs::remove_file(&path)?;
+        manager.maybe_autosave_at(&tree, start)?;
+        assert!(path.exists(), "activating should save on the first check");
+        assert!(!manager.is_dirty());
+
+        // Clean trees are never written
+        fs::remove_file(&path)?;
+        manager.maybe_autosave_at(&tree, start + AUTOSAVE_INTERVAL * 2)?;
+        assert!(!path.exists());
+
+        // Edits within the interval wait, then save once it has passed
+        manager.set_active(&name);
+        manager.maybe_autosave_at(&tree, start + AUTOSAVE_INTERVAL / 2)?;
+        assert!(!path.exists());
+        assert!(manager.is_dirty());
+        manager.maybe_autosave_at(&tree, start + AUTOSAVE_INTERVAL)?;
+        assert!(path.exists());
+        assert!(!manager.is_dirty());
+        Ok(())
+    }
+
+    #[test]
+    fn import_rejects_unknown_panes() -> eyre::Result<()> {
+        let td = tempfile::tempdir()?;
+        let mut manager = LayoutManager::in_dir(td.path());
+        let good = td.path().join("shared.layout");
+        fs::write(&good, BUILTIN_PRESETS[0].1)?;
+        assert_eq!(manager.import_from_path(&good)?, "shared");
+
+        let bad = td.path().join("bad.layout");
+        fs::write(
+            &bad,
+            r#"{"name":"Bad","root":{"variant":"Pane","pane":"NoSuchPane","kind":null,"children":null}}"#,
+        )?;
+        let err = manager.import_from_path(&bad).unwrap_err();
+        assert!(err.to_string().contains("NoSuchPane"));
+        Ok(())
+    }
+
+    #[test]
+    fn validate_reports_paths() {
+        let layout: Layout = facet_json::from_str(
+            r#"{"name":"Bad","root":{"variant":"Container","pane":null,"kind":"Tabs","children":[
+                {"variant":"Pane","pane":"InputPaths","kind":null,"children":null},
+                {"variant":"Container","pane":null,"kind":"Diagonal","children":[
+                    {"variant":"Pane","pane":"Renamed","kind":null,"children":null}
+                ]}
+            ]}}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            layout.validate(),
+            Err(vec![
+                "root/1: unknown container kind \"Diagonal\"".to_string(),
+                "root/1/0: unknown pane \"Renamed\"".to_string(),
+            ])
+        );
+    }
+}
@@ -1,23 +1,47 @@
 //! CM GUI using `egui_tiles` for layout management
 
 mod behavior;
+mod fonts;
+mod image_cache;
 mod layouts;
 pub mod state;
+pub mod thumbnail_queue;
 mod tiles;
 pub mod tree_view;
 
 use crate::app_home::APP_HOME;
+use crate::app_home::is_app_home_read_only;
+use crate::gui::layouts::AUTOSAVE_INTERVAL;
 use crate::gui::layouts::Layout;
 use crate::gui::layouts::LayoutManager;
 use crate::inputs;
+use crate::scroll_zoom::SCROLL_ZOOM_SENSITIVITY_RANGE;
+use crate::scroll_zoom::scroll_zoom;
+use crate::scroll_zoom::set_scroll_zoom;
+use crate::search_rate_limit::SEARCH_RATE_RANGE;
+use crate::search_rate_limit::search_rate;
+use crate::search_rate_limit::set_search_rate;
+use crate::thumbnail_cache_size::set_thumbnail_cache_mb;
+use crate::thumbnail_cache_size::thumbnail_cache_bytes;
+use crate::thumbnail_cache_size::thumbnail_cache_mb;
+use crate::thumbnail_concurrency::MAX_THUMBNAIL_CONCURRENCY;
+use crate::thumbnail_concurrency::default_thumbnail_concurrency;
+use crate::thumbnail_concurrency::set_thumbnail_concurrency;
+use crate::thumbnail_concurrency::thumbnail_concurrency;
+use crate::window_state::WindowState;
+use crate::window_state::load_window_state;
+use crate::window_state::save_window_state;
 use behavior::CmBehavior;
 use behavior::CmPane;
 use behavior::create_default_tree;
 use eframe::egui::Align2;
 use eframe::egui::Color32;
 use eframe::egui::Id;
+use eframe::egui::KeyboardShortcut;
 use eframe::egui::LayerId;
+use eframe::egui::Modifiers;
 use eframe::egui::Order;
+use eframe::egui::RichText;
 use eframe::egui::TextStyle;
 use eframe::egui::TextureHandle;
 use eframe::egui::{self};
@@ -35,33 +59,59 @@ use tracing::debug;
 use tracing::error;
 use tracing::info;
 
-/// Run the GUI; the function blocks in place on the eframe app using
-/// `tokio::task::block_in_place`.
+/// Run the GUI. eframe runs on the calling (main) thread while a dedicated tokio runtime
+/// drives background tasks through the handle stored in [`AppState`].
 /// # Errors
-/// Returns an error if the GUI fails to start or run.
+/// Returns an error if the runtime cannot be created or the GUI fails to start or run.
 pub fn run_gui() -> eyre::Result<()> {
     info!("Starting CM GUI");
-    // Create a dedicated runtime and run the GUI
     let rt = tokio::runtime::Runtime::new()?;
-    rt.block_on(async {
-        let native_options = eframe::NativeOptions::default();
-
-        let res = tokio::task::block_in_place(move || {
-            eframe::run_native(
-                "CM - Creative Memories Photo Manager",
-                native_options,
-                Box::new(|cc| Ok(Box::new(CmApp::new(cc)))),
-            )
-            .map_err(|e| eyre!("Failed to run eframe: {}", e))
-        });
-
-        res.expect("GUI runtime failed");
-
-        info!("GUI exited");
-        debug!("Finishing process aggressively, any unfinished tasks are pooched");
-        std::process::exit(0);
+    // Enter the runtime so code that expects a tokio context (e.g. `spawn_blocking` inside
+    // library calls) works from the GUI thread too
+    let _guard = rt.enter();
+    let runtime = rt.handle().clone();
+
+    let window_state = load_window_state(&APP_HOME).unwrap_or_else(|e| {
+        error!("Failed to load window state: {}", e);
+        None
     });
-    Ok(())
+    let mut viewport = egui::ViewportBuilder::default();
+    if let Some(saved) = window_state {
+        viewport = viewport
+            .with_position([saved.x, saved.y])
+            .with_inner_size([saved.width, saved.height])
+            .with_maximized(saved.maximized);
+    }
+    let native_options = eframe::NativeOptions {
+        viewport,
+        // The geometry is kept under the app home with the rest of the settings instead
+        persist_window: false,
+        ..eframe::NativeOptions::default()
+    };
+    eframe::run_native(
+        "CM - Creative Memories Photo Manager",
+        native_options,
+        Box::new(move |cc| {
+            Ok(Box::new(CmApp::new(
+                cc,
+                runtime,
+                window_state.unwrap_or_default(),
+            )))
+        }),
+    )
+    .map_err(|e| eyre!("Failed to run eframe: {}", e))?;
+
+    info!("GUI exited");
+    debug!("Finishing process aggressively, any unfinished tasks are pooched");
+    std::process::exit(0);
+}
+
+/// Pan/zoom of the three image previews for one image
+#[derive(Clone, Debug)]
+struct PreviewViews {
+    input: tiles::PanZoomState,
+    threshold: tiles::PanZoomState,
+    output: tiles::PanZoomState,
 }
 
 struct CmApp {
@@ -79,39 +129,59 @@ struct CmApp {
     input_pan_zoom: tiles::PanZoomState,
     /// Pan/zoom state for threshold preview
     threshold_pan_zoom: tiles::PanZoomState,
+    /// Textures for the threshold comparison grid
+    threshold_montage: tiles::ThresholdMontage,
     /// Pan/zoom state for output preview
     output_pan_zoom: tiles::PanZoomState,
+    /// Preview pan/zoom saved for each image selected before, restored when it is reselected
+    pan_zoom_by_image: HashMap<PathBuf, PreviewViews>,
+    /// Image the current preview pan/zoom states belong to
+    pan_zoom_image: Option<PathBuf>,
     /// Texture handles for thumbnail previews in tree view
     thumbnail_textures: HashMap<PathBuf, TextureHandle>,
+    /// Texture handles for product search result thumbnails, by URL
+    product_thumbnail_textures: HashMap<String, TextureHandle>,
     /// Toast notifications manager
     toasts: Toasts,
     /// Number of events we've already processed for toasts
     last_seen_event_count: usize,
     /// Layout manager (persistence + active layout)
     layout_manager: LayoutManager,
+    /// Whether the manage layouts window is open
+    manage_layouts_open: bool,
+    /// Layout currently being renamed in the manage layouts window, with the edited name
+    layout_rename: Option<(String, String)>,
+    /// File path used by the manage layouts window to import/export a layout
+    layout_transfer_path: String,
+    /// Window geometry and panel visibility as of the last frame, saved on exit
+    window_state: WindowState,
 }
 
 impl CmApp {
-    fn new(cc: &eframe::CreationContext) -> Self {
+    fn new(
+        cc: &eframe::CreationContext,
+        runtime: tokio::runtime::Handle,
+        window_state: WindowState,
+    ) -> Self {
         // Install image loaders for egui
         egui_extras::install_image_loaders(&cc.egui_ctx);
+        fonts::install_fonts(&cc.egui_ctx);
 
-        let tree = create_default_tree();
-        let state = AppState::default();
+        let mut tree = create_default_tree();
+        let mut state = AppState::new(runtime);
+        state.logs_visible = window_state.logs_visible;
+        state.about_open = window_state.about_open;
 
-        // Initialize layout manager and ensure we have at least one preset and one custom
+        // Initialize layout manager (installs built-in presets, restores the active layout) and
+        // ensure we have one custom
         let mut layout_manager = LayoutManager::new();
-        // If no presets exist, save a "Preset 1" based on current tree
-        if layout_manager.list_presets().is_empty()
-            && let Some(mut preset_layout) = Layout::from_tree(&tree)
+        if let Some(name) = layout_manager.active_name()
+            && let Ok(layout) = layout_manager.load_named(name)
         {
-            preset_layout.name = "Preset 1".to_string();
-            let _ = layout_manager.save_preset("Preset 1", &preset_layout);
-        }
-        // If no custom layouts exist, create a Custom 1 from the first preset
-        if layout_manager.list_custom().is_empty()
-            && let Some(preset_name) = layout_manager.list_presets().first().cloned()
-            && let Ok(new_name) = layout_manager.activate_preset_as_custom(&preset_name, tree.id())
+            tree = layout.apply_to_tree(tree.id());
+        } else if layout_manager.list_custom().is_empty()
+            && let Some(layout) = Layout::from_tree(&tree)
+            && let Ok(new_name) = layout_manager.create_custom_from_layout("Custom 1", &layout)
         {
             layout_manager.set_active(&new_name);
         }
@@ -128,18 +198,218 @@ impl CmApp {
             threshold_texture_path: None,
             input_pan_zoom: tiles::PanZoomState::new(),
             threshold_pan_zoom: tiles::PanZoomState::new(),
+            threshold_montage: tiles::ThresholdMontage::default(),
             output_pan_zoom: tiles::PanZoomState::new(),
+            pan_zoom_by_image: HashMap::new(),
+            pan_zoom_image: None,
             thumbnail_textures: HashMap::new(),
+            product_thumbnail_textures: HashMap::new(),
             toasts: Toasts::new()
                 .anchor(Align2::RIGHT_BOTTOM, (-10.0, -10.0))
                 .direction(egui::Direction::BottomUp),
             last_seen_event_count: initial_event_count,
             layout_manager,
+            manage_layouts_open: false,
+            layout_rename: None,
+            layout_transfer_path: String::new(),
+            window_state,
         }
     }
+
+    /// Remember the window geometry and panel visibility so `on_exit` can save them. While
+    /// maximized the last normal geometry is kept, so the window restores to it later.
+    fn track_window_state(&mut self, ctx: &egui::Context) {
+        ctx.input(|i| {
+            let viewport = i.viewport();
+            self.window_state.maximized = viewport.maximized.unwrap_or(false);
+            if self.window_state.maximized {
+                return;
+            }
+            if let Some(outer) = viewport.outer_rect {
+                self.window_state.x = outer.min.x;
+                self.window_state.y = outer.min.y;
+            }
+            if let Some(inner) = viewport.inner_rect {
+                self.window_state.width = inner.width();
+                self.window_state.height = inner.height();
+            }
+        });
+        self.window_state.logs_visible = self.state.logs_visible;
+        self.window_state.about_open = self.state.about_open;
+    }
+
+    /// When the selection changes, save the previews' pan/zoom for the previous image and
+    /// restore the new image's saved view, or fit it if it has none or views reset on select
+    fn switch_pan_zoom_to_selection(&mut self) {
+        let selected = self.state.selected_input_file.clone();
+        if selected == self.pan_zoom_image {
+            return;
+        }
+        if let Some(previous) = self.pan_zoom_image.take() {
+            self.pan_zoom_by_image.insert(
+                previous,
+                PreviewViews {
+                    input: self.input_pan_zoom.clone(),
+                    threshold: self.threshold_pan_zoom.clone(),
+                    output: self.output_pan_zoom.clone(),
+                },
+            );
+        }
+        let saved = selected
+            .as_ref()
+            .filter(|_| !self.state.reset_view_on_select)
+            .and_then(|path| self.pan_zoom_by_image.get(path));
+        if let Some(views) = saved {
+            self.input_pan_zoom = views.input.clone();
+            self.threshold_pan_zoom = views.threshold.clone();
+            self.output_pan_zoom = views.output.clone();
+        } else {
+            self.input_pan_zoom.reset();
+            self.threshold_pan_zoom.reset();
+            self.output_pan_zoom.reset();
+        }
+        self.pan_zoom_image = selected;
+    }
+
+    /// Global keyboard shortcuts; ignored while a text field has keyboard focus
+    fn handle_keyboard_shortcuts(&mut self, ctx: &egui::Context) {
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        let process_all =
+            KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, egui::Key::P);
+        let process_selected = KeyboardShortcut::new(Modifiers::COMMAND, egui::Key::P);
+        let refresh = KeyboardShortcut::new(Modifiers::COMMAND, egui::Key::R);
+        let cancel = KeyboardShortcut::new(Modifiers::NONE, egui::Key::Escape);
+
+        // Check Ctrl+Shift+P before Ctrl+P since the latter also matches with Shift held
+        if ctx.input_mut(|i| i.consume_shortcut(&process_all)) {
+            if !self.state.process_all_running {
+                self.state.process_all();
+            }
+        } else if ctx.input_mut(|i| i.consume_shortcut(&process_selected))
+            && !self.state.process_all_running
+            && self.state.selected_input_file.is_some()
+        {
+            self.state.process_selected();
+        }
+        if ctx.input_mut(|i| i.consume_shortcut(&refresh)) {
+            self.state.reload_data();
+        }
+        if self.state.process_all_running && ctx.input_mut(|i| i.consume_shortcut(&cancel)) {
+            self.state.cancel_process_all();
+        }
+        if ctx.input_mut(|i| i.consume_key(Modifiers::NONE, egui::Key::ArrowDown)) {
+            self.state.select_next();
+        }
+        if ctx.input_mut(|i| i.consume_key(Modifiers::NONE, egui::Key::ArrowUp)) {
+            self.state.select_prev();
+        }
+    }
+
+    /// Window listing custom layouts with rename, duplicate and delete actions
+    fn show_manage_layouts_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.manage_layouts_open;
+        egui::Window::new("Manage Layouts")
+            .collapsible(false)
+            .open(&mut open)
+            .show(ctx, |ui| {
+                let mut load: Option<String> = None;
+                for name in self.layout_manager.list_custom() {
+                    let is_active = Some(name.as_str()) == self.layout_manager.active_name();
+                    ui.horizontal(|ui| {
+                        let renaming = self
+                            .layout_rename
+                            .as_ref()
+                            .is_some_and(|(old, _)| *old == name);
+                        if renaming {
+                            let mut commit = false;
+                            if let Some((_, edited)) = self.layout_rename.as_mut() {
+                                let resp = ui.text_edit_singleline(edited);
+                                commit = resp.lost_focus()
+                                    && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                            }
+                            if ui.button("Save").clicked() || commit {
+                                if let Some((old, edited)) = self.layout_rename.take()
+                                    && let Err(e) = self.layout_manager.rename(&old, &edited)
+                                {
+                                    error!("Failed to rename layout {}: {}", old, e);
+                                }
+                            } else if ui.button("Cancel").clicked() {
+                                self.layout_rename = None;
+                            }
+                        } else {
+                            if is_active {
+                                ui.label(RichText::new(&name).strong());
+                            } else if ui.button(&name).on_hover_text("Switch to layout").clicked() {
+                                load = Some(name.clone());
+                            }
+                            if ui.small_button("Rename").clicked() {
+                                self.layout_rename = Some((name.clone(), name.clone()));
+                            }
+                            if ui.small_button("Duplicate").clicked()
+                                && let Err(e) = self.layout_manager.duplicate(&name)
+                            {
+                                error!("Failed to duplicate layout {}: {}", name, e);
+                            }
+                            if ui
+                                .small_button("Export")
+                                .on_hover_text("Write this layout to the file path below")
+                                .clicked()
+                            {
+                                let path = PathBuf::from(self.layout_transfer_path.trim());
+                                match self.layout_manager.export_named(&name, &path) {
+                                    Ok(()) => {
+                                        info!("Exported layout {} to {}", name, path.display());
+                                    }
+                                    Err(e) => error!("Failed to export layout {}: {}", name, e),
+                                }
+                            }
+                            if ui.small_button("Delete").clicked()
+                                && let Err(e) = self.layout_manager.delete_named(&name)
+                            {
+                                error!("Failed to delete layout {}: {}", name, e);
+                            }
+                        }
+                    });
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("File:");
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.layout_transfer_path)
+                            .hint_text("path/to/shared.layout"),
+                    );
+                    if ui.button("Import").clicked() {
+                        let path = PathBuf::from(self.layout_transfer_path.trim());
+                        match self.layout_manager.import_from_path(&path) {
+                            Ok(name) => info!("Imported layout {} from {}", name, path.display()),
+                            Err(e) => error!("Failed to import layout: {}", e),
+                        }
+                    }
+                });
+                if let Some(name) = load
+                    && let Ok(layout) = self.layout_manager.load_named(&name)
+                {
+                    self.tree = layout.apply_to_tree(self.tree.id());
+                    self.layout_manager.set_active(&name);
+                }
+            });
+        self.manage_layouts_open = open;
+    }
 }
 
 impl eframe::App for CmApp {
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        info!("Window closed, stopping background work");
+        if !is_app_home_read_only()
+            && let Err(e) = save_window_state(&APP_HOME, &self.window_state)
+        {
+            error!("Failed to save window state: {}", e);
+        }
+        self.state.shutdown();
+    }
+
     #[expect(clippy::too_many_lines)]
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Initialize on first frame
@@ -150,6 +420,7 @@ impl eframe::App for CmApp {
 
         // Poll background tasks for completions
         self.state.poll_background_tasks();
+        self.track_window_state(ctx);
 
         // Clear textures if output info is being recalculated
         if self.state.output_info_loading {
@@ -162,11 +433,15 @@ impl eframe::App for CmApp {
         // Handle deferred actions from previous frame
         self.state.handle_deferred_actions();
 
+        self.switch_pan_zoom_to_selection();
+
+        self.handle_keyboard_shortcuts(ctx);
+
         // Top menu bar
         egui::TopBottomPanel::top("top_panel").show(ctx, |ui| {
             egui::MenuBar::new().ui(ui, |ui| {
                 // Refresh button
-                if ui.button("Refresh").clicked() {
+                if ui.button("Refresh").on_hover_text("Ctrl+R").clicked() {
                     self.state.reload_data();
                 }
 
@@ -182,11 +457,23 @@ impl eframe::App for CmApp {
                     self.state.logs_visible = !self.state.logs_visible;
                 }
 
+                if ui.button("Open app data folder").clicked() {
+                    if let Err(e) = APP_HOME.ensure_dir() {
+                        error!("Failed to create app data folder: {}", e);
+                    } else if let Err(e) = crate::file_manager::reveal_in_file_manager(&APP_HOME) {
+                        error!("{}", e);
+                    }
+                }
+
                 // About button
                 if ui.button("About").clicked() {
                     self.state.about_open = !self.state.about_open;
                 }
 
+                ui.menu_button("Settings", |ui| {
+                    draw_settings_menu(ui, &mut self.state);
+                });
+
                 // Layout menu
                 ui.menu_button("Layout", |ui| {
                     // Custom layouts (active shown)
@@ -223,7 +510,10 @@ impl eframe::App for CmApp {
 
                     ui.separator();
 
-                    if ui.button("Create New").clicked()
+                    let writable = !is_app_home_read_only();
+                    if ui
+                        .add_enabled(writable, egui::Button::new("Create New"))
+                        .clicked()
                         && let Some(layout) = Layout::from_tree(&self.tree)
                     {
                         let name =
@@ -236,14 +526,35 @@ impl eframe::App for CmApp {
                         }
                     }
 
-                    if ui.button("Delete Active").clicked() {
+                    if ui
+                        .add_enabled(writable, egui::Button::new("Delete Active"))
+                        .clicked()
+                    {
                         let _ = self.layout_manager.delete_active();
                     }
+
+                    if ui.button("Reset to default layout").clicked() {
+                        self.tree = create_default_tree();
+                        self.layout_manager.mark_dirty();
+                    }
+
+                    if ui.button("Manage Layouts…").clicked() {
+                        self.manage_layouts_open = true;
+                    }
                 });
 
                 // Theme switch
                 egui::widgets::global_theme_preference_switch(ui);
 
+                if is_app_home_read_only() {
+                    ui.colored_label(Color32::YELLOW, "⚠ Settings are not saved")
+                        .on_hover_text(format!(
+                            "{} is read-only, so changes only last for this session. Restart \
+                             with --app-home <DIR> to use a writable directory.",
+                            APP_HOME.display()
+                        ));
+                }
+
                 // Right-align the Quit button
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     if ui.button("Quit").clicked() {
@@ -255,6 +566,7 @@ impl eframe::App for CmApp {
 
         // Main tile panel
         egui::CentralPanel::default().show(ctx, |ui| {
+            let mut layout_edited = false;
             let mut behavior = CmBehavior {
                 state: &mut self.state,
                 output_texture: &mut self.output_texture,
@@ -263,14 +575,24 @@ impl eframe::App for CmApp {
                 threshold_texture_path: &mut self.threshold_texture_path,
                 input_pan_zoom: &mut self.input_pan_zoom,
                 threshold_pan_zoom: &mut self.threshold_pan_zoom,
+                threshold_montage: &mut self.threshold_montage,
                 output_pan_zoom: &mut self.output_pan_zoom,
                 thumbnail_textures: &mut self.thumbnail_textures,
+                product_thumbnail_textures: &mut self.product_thumbnail_textures,
+                layout_edited: &mut layout_edited,
             };
             self.tree.ui(&mut behavior, ui);
+            for path in self.state.evicted_thumbnails.drain(..) {
+                self.thumbnail_textures.remove(&path);
+            }
 
-            // Autosave active layout if tree changed
-            if let Some(layout) = Layout::from_tree(&self.tree) {
-                let _ = self.layout_manager.maybe_autosave(&layout);
+            // Autosave active layout at most once per interval after the tree was edited
+            if layout_edited {
+                self.layout_manager.mark_dirty();
+            }
+            let _ = self.layout_manager.maybe_autosave(&self.tree);
+            if self.layout_manager.is_dirty() {
+                ctx.request_repaint_after(AUTOSAVE_INTERVAL);
             }
         });
 
@@ -289,10 +611,30 @@ impl eframe::App for CmApp {
             }
         }
 
-        // Clear dirty flags for next frame
+        // Show the loupe in every synced preview at the spot hovered in any of them
+        let loupe_uv = if self.state.sync_preview_pan_zoom && self.state.preview_loupe {
+            self.input_pan_zoom
+                .hover_uv
+                .or(self.threshold_pan_zoom.hover_uv)
+                .or(self.output_pan_zoom.hover_uv)
+        } else {
+            None
+        };
+        self.input_pan_zoom.loupe_uv = loupe_uv;
+        self.threshold_pan_zoom.loupe_uv = loupe_uv;
+        self.output_pan_zoom.loupe_uv = loupe_uv;
+
+        // Clear dirty flags and hover positions for next frame; hidden previews don't update them
         self.input_pan_zoom.dirty = false;
         self.threshold_pan_zoom.dirty = false;
         self.output_pan_zoom.dirty = false;
+        self.input_pan_zoom.hover_uv = None;
+        self.threshold_pan_zoom.hover_uv = None;
+        self.output_pan_zoom.hover_uv = None;
+
+        if self.manage_layouts_open {
+            self.show_manage_layouts_window(ctx);
+        }
 
         // About window
         if self.state.about_open {
@@ -318,14 +660,51 @@ impl eframe::App for CmApp {
                 });
         }
 
+        // Clear All confirmation
+        if self.state.confirm_clear_open {
+            egui::Window::new("Clear all inputs?")
+                .resizable(false)
+                .collapsible(false)
+                .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+                .show(ctx, |ui| {
+                    ui.label(format!(
+                        "Remove all {} input paths from the list? Files on disk are not touched.",
+                        self.state.input_paths.len()
+                    ));
+                    ui.horizontal(|ui| {
+                        if ui.button("Yes, clear all").clicked() {
+                            self.state.clear_all = true;
+                            self.state.confirm_clear_open = false;
+                        }
+                        if ui.button("Cancel").clicked() {
+                            self.state.confirm_clear_open = false;
+                        }
+                    });
+                });
+        }
+
+        // Processing results window (opens after a batch with errors)
+        if self.state.processing_results_open {
+            let mut open = true;
+            egui::Window::new("Processing results")
+                .default_size([600.0, 300.0])
+                .open(&mut open)
+                .show(ctx, |ui| {
+                    tiles::draw_processing_results_tile(ui, &mut self.state);
+                });
+            self.state.processing_results_open = open;
+        }
+
         // Logs window (separate window instead of tile)
         if self.state.logs_visible {
+            let mut logs_visible = true;
             egui::Window::new("Logs")
                 .default_size([800.0, 400.0])
-                .open(&mut self.state.logs_visible)
+                .open(&mut logs_visible)
                 .show(ctx, |ui| {
-                    tiles::draw_logs_tile(ui);
+                    tiles::draw_logs_tile(ui, &mut self.state);
                 });
+            self.state.logs_visible = logs_visible;
         }
 
         // Process new log events and create toasts for INFO and ERROR levels
@@ -389,24 +768,147 @@ impl eframe::App for CmApp {
             );
         }
 
-        // Global drop handling (adds dropped files persistently)
+        // Global drop handling (adds dropped files persistently). Paths that can't be read are
+        // rejected up front so the rest of a mixed selection still gets added.
         let dropped_files = ctx.input(|i| i.raw.dropped_files.clone());
         if !dropped_files.is_empty() {
             let mut added_paths = Vec::new();
-            for file in dropped_files {
-                if let Some(path) = file.path {
-                    added_paths.push(path);
+            let mut rejected = Vec::new();
+            for path in dropped_files.into_iter().filter_map(|file| file.path) {
+                match inputs::check_accessible(&path) {
+                    Ok(()) => added_paths.push(path),
+                    Err(reason) => rejected.push((path, reason)),
                 }
             }
             if !added_paths.is_empty() {
                 match inputs::add_paths(&APP_HOME, &added_paths) {
-                    Ok(added) => {
-                        info!("Added {} inputs", added.len());
+                    Ok(result) => {
+                        info!("Added {} inputs", result.added.len());
+                        rejected.extend(result.skipped);
+                        let folders = result.added.into_iter().filter(|p| p.is_dir()).collect();
+                        self.state.check_for_empty_folders(folders);
                         self.state.reload_data();
                     }
                     Err(e) => error!("{}", e),
                 }
             }
+            if !rejected.is_empty() {
+                let mut text = format!("Skipped {} dropped items:", rejected.len());
+                for (path, reason) in &rejected {
+                    let _ = write!(text, "\n{} ({reason})", path.display());
+                }
+                self.toasts.add(
+                    Toast::default()
+                        .kind(ToastKind::Warning)
+                        .text(text)
+                        .options(
+                            ToastOptions::default()
+                                .duration_in_seconds(10.0)
+                                .show_progress(true)
+                                .show_icon(true),
+                        ),
+                );
+            }
         }
     }
 }
+
+/// Application-wide settings that don't belong to a particular tile
+fn draw_settings_menu(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.horizontal(|ui| {
+        ui.label("Parallel thumbnail loads:");
+        let mut limit = thumbnail_concurrency();
+        if ui
+            .add(egui::DragValue::new(&mut limit).range(1..=MAX_THUMBNAIL_CONCURRENCY))
+            .on_hover_text(
+                "How many images are read and thumbnailed at once. \
+                 Lower this for spinning disks, raise it for fast SSDs.",
+            )
+            .changed()
+            && let Err(e) = set_thumbnail_concurrency(&APP_HOME, Some(limit))
+        {
+            error!("Failed to save thumbnail concurrency: {}", e);
+        }
+    });
+    if ui
+        .button(format!(
+            "Reset to default ({})",
+            default_thumbnail_concurrency()
+        ))
+        .clicked()
+        && let Err(e) = set_thumbnail_concurrency(&APP_HOME, None)
+    {
+        error!("Failed to reset thumbnail concurrency: {}", e);
+    }
+    ui.separator();
+    let mut zoom = scroll_zoom();
+    let mut zoom_changed = false;
+    ui.horizontal(|ui| {
+        ui.label("Scroll zoom sensitivity:");
+        zoom_changed |= ui
+            .add(
+                egui::DragValue::new(&mut zoom.sensitivity)
+                    .range(SCROLL_ZOOM_SENSITIVITY_RANGE)
+                    .speed(0.05)
+                    .suffix("x"),
+            )
+            .on_hover_text("How fast scrolling zooms image previews")
+            .changed();
+    });
+    zoom_changed |= ui
+        .checkbox(&mut zoom.invert, "Invert scroll zoom")
+        .on_hover_text("Zoom out when scrolling up, for trackpads that feel backwards")
+        .changed();
+    if zoom_changed && let Err(e) = set_scroll_zoom(&APP_HOME, Some(zoom)) {
+        error!("Failed to save scroll zoom preferences: {}", e);
+    }
+    if ui.button("Reset scroll zoom").clicked()
+        && let Err(e) = set_scroll_zoom(&APP_HOME, None)
+    {
+        error!("Failed to reset scroll zoom preferences: {}", e);
+    }
+    ui.separator();
+    ui.horizontal(|ui| {
+        ui.label("Product searches:");
+        let mut rate = search_rate();
+        if ui
+            .add(
+                egui::DragValue::new(&mut rate)
+                    .range(SEARCH_RATE_RANGE)
+                    .speed(0.1)
+                    .suffix(" per second"),
+            )
+            .on_hover_text(
+                "Most search requests sent to the API each second, e.g. while auto-searching \
+                 during processing. Cached results aren't limited.",
+            )
+            .changed()
+            && let Err(e) = set_search_rate(&APP_HOME, Some(rate))
+        {
+            error!("Failed to save search rate limit: {}", e);
+        }
+    });
+    ui.separator();
+    ui.horizontal(|ui| {
+        ui.label("Thumbnail cache:");
+        let mut mb = thumbnail_cache_mb();
+        if ui
+            .add(egui::DragValue::new(&mut mb).range(1..=16_384).suffix(" MB"))
+            .on_hover_text(format!(
+                "Least recently viewed thumbnails are dropped past this size ({} in use)",
+                tree_view::format_size(
+                    u64::try_from(state.image_cache.total_bytes()).unwrap_or(u64::MAX)
+                )
+            ))
+            .changed()
+        {
+            match set_thumbnail_cache_mb(&APP_HOME, Some(mb)) {
+                Ok(()) => {
+                    let evicted = state.image_cache.set_max_bytes(thumbnail_cache_bytes());
+                    state.evicted_thumbnails.extend(evicted);
+                }
+                Err(e) => error!("Failed to save thumbnail cache size: {}", e),
+            }
+        }
+    });
+}
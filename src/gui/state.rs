@@ -2,27 +2,72 @@
 
 use crate::MAX_NAME_LENGTH;
 use crate::app_home::APP_HOME;
+use crate::app_home::AppHome;
+use crate::batch_checkpoint;
 use crate::cli::command::search::search_result_ok::SearchResultOk;
+use crate::conversion_report::ConversionEntry;
+use crate::conversion_report::ConversionReport;
+use crate::gui::image_cache::ImageCache;
+use crate::gui::thumbnail_queue::ThumbnailQueue;
+use crate::gui::tree_view::TreeOpenState;
+use crate::image_extensions::default_image_extensions;
+use crate::image_extensions::load_image_extensions;
+use crate::image_processing::BackgroundDetection;
+use crate::image_processing::BatchSizeEstimate;
 use crate::image_processing::BinarizationMode;
+use crate::image_processing::DEFAULT_MAX_DESKEW_DEGREES;
+use crate::image_processing::DecodeErrorPolicy;
+use crate::image_processing::ExifSummary;
+use crate::image_processing::JpegSubsampling;
+use crate::image_processing::OutputConflictPolicy;
+use crate::image_processing::ProcessError;
+use crate::image_processing::ProcessItemResult;
+use crate::image_processing::ProcessProgress;
 use crate::image_processing::ProcessingSettings;
 use crate::image_processing::get_output_path;
+use crate::image_processing::is_image_file;
 use crate::image_processing::{self};
+use crate::input_overrides::InputOverrides;
+use crate::input_overrides::load_input_overrides;
 use crate::inputs;
+use crate::inputs::NameFilter;
+use crate::last_batch::LastBatch;
+use crate::last_batch::save_last_batch;
+use crate::name_length_unit::name_length_unit;
+use crate::natural_sort::natural_path_cmp;
+use crate::output_root::load_output_root;
+use crate::pipeline;
+use crate::pipeline::PipelineConfig;
+use crate::pipeline::PipelinePlan;
+use crate::process_timing::TimingSummary;
+use crate::rename_rules::RenameOptions;
+use crate::rename_rules::RenamePreview;
 use crate::rename_rules::RenameRule;
+use crate::rename_rules::SkippedRuleFile;
+use crate::search_history;
+use crate::thumbnail_cache_size::thumbnail_cache_bytes;
+use crate::thumbnail_concurrency::thumbnail_concurrency;
+use crate::watermark::WatermarkContent;
+use crate::watermark::WatermarkPosition;
+use crate::watermark::WatermarkSettings;
 use chrono::DateTime;
 use chrono::Local;
-use humantime::format_duration;
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
-use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::AtomicBool;
 use std::sync::atomic::Ordering;
-use std::time::Instant;
+use std::time::SystemTime;
+use tokio::runtime::Handle;
 use tokio::sync::mpsc::UnboundedReceiver;
 use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::mpsc::{self};
+use tokio_util::sync::CancellationToken;
 use tracing::error;
 use tracing::info;
 use tracing::warn;
@@ -30,6 +75,9 @@ use tracing::warn;
 /// Thumbnail size for cached previews
 pub const THUMBNAIL_SIZE: u32 = 128;
 
+/// Number of images processed when estimating the total batch output size
+const BATCH_ESTIMATE_SAMPLE: usize = 8;
+
 /// Cached image metadata and thumbnail
 #[derive(Clone, Debug)]
 pub struct CachedImageInfo {
@@ -39,10 +87,64 @@ pub struct CachedImageInfo {
     pub height: u32,
     /// File size in bytes
     pub file_size: u64,
+    /// Last modification time, if the platform reports one
+    pub modified: Option<SystemTime>,
     /// Thumbnail PNG data (small, for tooltips)
     pub thumbnail_data: Vec<u8>,
 }
 
+/// Display order for the input image list
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ImageSort {
+    /// Path, alphabetically
+    #[default]
+    Name,
+    /// Most recently modified first
+    Modified,
+    /// Largest file first
+    Size,
+    /// Most pixels first
+    Dimensions,
+}
+
+impl ImageSort {
+    pub const ALL: [ImageSort; 4] = [
+        ImageSort::Name,
+        ImageSort::Modified,
+        ImageSort::Size,
+        ImageSort::Dimensions,
+    ];
+
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            ImageSort::Name => "Name",
+            ImageSort::Modified => "Newest first",
+            ImageSort::Size => "Largest file first",
+            ImageSort::Dimensions => "Largest dimensions first",
+        }
+    }
+}
+
+/// Display order for product search results
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PriceSort {
+    /// Keep the order returned by the API
+    #[default]
+    ApiOrder,
+    Ascending,
+    Descending,
+}
+
+/// A product search result thumbnail, as fetched from its URL
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProductThumbnail {
+    Loading,
+    /// Encoded image bytes
+    Loaded(Vec<u8>),
+    Failed,
+}
+
 /// Loading state for async operations
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub enum LoadingState {
@@ -72,24 +174,74 @@ pub struct AppState {
     pub image_files: Vec<PathBuf>,
     /// Loading state for image file discovery
     pub image_files_loading: LoadingState,
+    /// File name glob restricting which images are shown and processed (blank = all)
+    pub image_filter: String,
+    /// Case-insensitive file name substring narrowing the input images tree (display only)
+    pub tree_filter: String,
+    /// Expand/collapse-all state of the input images tree
+    pub input_tree_open: TreeOpenState,
+    /// Expand/collapse-all state of the output rename tree
+    pub output_tree_open: TreeOpenState,
+    /// Display order of the input image list
+    pub image_sort: ImageSort,
+    /// Compare numbers in file names by value (`img2` before `img10`) in the image and rename trees
+    pub natural_sort: bool,
     /// Path to remove (deferred action)
     pub path_to_remove: Option<PathBuf>,
+    /// Folder collecting all outputs (`None` = `-output` sibling of each input)
+    pub output_root: Option<PathBuf>,
+    /// File extensions recognized as images when discovering input files
+    pub image_extensions: Vec<String>,
+    /// Editable output folder (blank = `-output` sibling of each input)
+    pub output_root_edit: String,
+    /// Input path to move, as `(from, to)` indices into `input_paths` (deferred action)
+    pub input_path_move: Option<(usize, usize)>,
+    /// Processing settings overrides per input root
+    pub input_overrides: InputOverrides,
+    /// Input root whose overrides are being edited
+    pub override_editor_root: Option<PathBuf>,
     /// Whether to clear all inputs (deferred action)
     pub clear_all: bool,
+    /// Whether the Clear All confirmation dialog is open
+    pub confirm_clear_open: bool,
+    /// Folders from the last drop that contained no recognized images
+    pub empty_input_folders: Vec<PathBuf>,
+    /// Path typed into the Import list box
+    pub import_list_path: String,
+    /// Outcome of the last Import list, shown under the box
+    pub import_list_status: Option<String>,
     /// Cached rename rules
     pub rename_rules: Vec<RenameRule>,
+    /// Rule files that failed to load on the last reload
+    pub rename_rules_skipped: Vec<SkippedRuleFile>,
     /// Whether rename rules are globally enabled
     pub rename_rules_enabled: bool,
     /// Whether to hyphenate camelCase in renamed file names
     pub rename_hyphenate: bool,
+    /// Whether to truncate names still over the max name length after renaming
+    pub rename_auto_truncate: bool,
     /// Cached renamed file paths (after applying rules)
     pub renamed_files: Vec<PathBuf>,
+    /// Cached rename outcome of each image file, index for index with `image_files`
+    pub rename_previews: Vec<RenamePreview>,
+    /// Rule being drafted in the rename rules tile; it is not saved until added
+    pub draft_rule: RenameRule,
+    /// Whether the output tree previews `draft_rule` layered over the saved rules
+    pub draft_rule_preview: bool,
+    /// `rename_previews` with `draft_rule` applied after the saved rules, while previewing it
+    pub draft_rename_previews: Vec<RenamePreview>,
     /// Hash key for rename preview cache invalidation
     pub rename_preview_key: u64,
     /// Current max name length value
     pub max_name_length: usize,
     /// Whether the logs window/tile is visible
     pub logs_visible: bool,
+    /// Destination path typed into the logs window's export field
+    pub log_export_path: String,
+    /// Minimum severity shown in the logs window
+    pub log_min_level: tracing::Level,
+    /// File typed into the logs window for JSON log output
+    pub json_log_path: String,
     /// Whether the about window is open
     pub about_open: bool,
     /// Currently selected input file (the source of truth for preview)
@@ -104,28 +256,112 @@ pub struct AppState {
     pub crop_to_content: bool,
     /// Threshold value for crop detection (0-255)
     pub crop_threshold: u8,
+    /// How the crop background color is picked from the image edges
+    pub background_detection: BackgroundDetection,
+    /// Fixed crop background color used instead of `background_detection`
+    pub background_override: Option<[u8; 3]>,
+    /// Whether the next click on the input preview picks `background_override`
+    pub eyedropper_active: bool,
+    /// Smallest width/height a crop may produce (0 = no minimum)
+    pub min_output_dimension: u32,
     /// Binarization preview mode ("`keep_white`" or "`keep_black`")
     pub binarization_mode: BinarizationMode,
     /// Thickness of the red bounding box in threshold preview (1-10)
     pub box_thickness: u8,
+    /// Straighten slightly rotated scans before cropping
+    pub deskew: bool,
+    /// Largest skew, in degrees, that deskewing will correct
+    pub max_deskew_degrees: f32,
+    /// Brightness offset applied before cropping (-255 to 255)
+    pub brightness: i32,
+    /// Contrast change in percent (-100 to 100)
+    pub contrast: f32,
+    /// Gamma correction (1 = unchanged)
+    pub gamma: f32,
+    /// Unsharp mask blur radius for outputs (0 = off)
+    pub sharpen_sigma: f32,
+    /// Unsharp mask threshold; smaller brightness differences are left alone
+    pub sharpen_threshold: i32,
+    /// Show a grid of previews at several thresholds instead of the single threshold preview
+    pub threshold_compare: bool,
     /// Synchronize pan/zoom across all image previews
     pub sync_preview_pan_zoom: bool,
+    /// Show a magnifier loupe at the hovered spot in every synced preview
+    pub preview_loupe: bool,
+    /// Fit previews when an image is selected instead of restoring its last pan/zoom
+    pub reset_view_on_select: bool,
+    /// Copy source modified/accessed times onto processed outputs
+    pub preserve_timestamps: bool,
+    /// How Process All handles inputs that fail to decode
+    pub on_decode_error: DecodeErrorPolicy,
+    /// Leave animated GIF/WebP inputs out of Process All instead of using their first frame
+    pub skip_animated: bool,
+    /// Convert inputs tagged with a non-sRGB color profile to sRGB (needs the `lcms2` feature)
+    pub convert_to_srgb: bool,
+    /// What Process All does when an output file already exists
+    pub on_conflict: OutputConflictPolicy,
     /// JPEG output quality (1-100)
     pub jpeg_quality: u8,
+    /// Write progressive instead of baseline JPEGs
+    pub jpeg_progressive: bool,
+    /// Chroma subsampling of JPEG outputs
+    pub jpeg_subsampling: JpegSubsampling,
+    /// Preview the encoded output bytes at full resolution instead of a downsampled PNG, so
+    /// compression artifacts are visible
+    pub true_output_preview: bool,
+    /// PNG deflate level (1-9, 0 = encoder default)
+    pub png_compression: u8,
+    /// Reduce PNG outputs to a 256-color palette
+    pub png_quantize: bool,
+    /// Composite a watermark over outputs
+    pub watermark_enabled: bool,
+    /// Use the image at `watermark_image` instead of `watermark_text`
+    pub watermark_use_image: bool,
+    /// Text drawn by a text watermark
+    pub watermark_text: String,
+    /// Path (as typed) of the image drawn by an image watermark
+    pub watermark_image: String,
+    /// Where the watermark is placed
+    pub watermark_position: WatermarkPosition,
+    /// Watermark opacity (0-1)
+    pub watermark_opacity: f32,
+    /// Watermark width as a fraction of the image width
+    pub watermark_scale: f32,
     /// Cached output info for the selected image
     pub selected_output_info: Option<OutputImageInfo>,
+    /// Images whose last processing had cropping on but found nothing to trim
+    pub no_crop_files: HashSet<PathBuf>,
+    /// Images that decoded but look damaged (e.g. truncated), with the reason
+    pub suspicious_files: HashMap<PathBuf, String>,
     /// Whether output info is being calculated in the background
     pub output_info_loading: bool,
     /// Whether `process_all` is running in the background
     pub process_all_running: bool,
     /// Progress for `process_all` (current, total)
     pub process_all_progress: Option<(usize, usize)>,
-    /// Join handles for per-image tasks (used for cancellation)
-    pub process_all_handles: Option<Arc<Mutex<Vec<tokio::task::JoinHandle<()>>>>>,
+    /// Runtime that drives background tasks; the GUI itself runs on the main thread
+    pub runtime: Handle,
+    /// Cancelled when the app exits; long-running background tasks stop once they see it
+    pub shutdown: CancellationToken,
+    /// Raised to stop the running Process All batch
+    pub process_all_cancel: Option<Arc<AtomicBool>>,
     /// Cache of image metadata and thumbnails (path -> info)
-    pub image_cache: HashMap<PathBuf, CachedImageInfo>,
-    /// Set of paths currently being loaded in background
+    pub image_cache: ImageCache,
+    /// Paths evicted from `image_cache` whose thumbnail textures should be dropped
+    pub evicted_thumbnails: Vec<PathBuf>,
+    /// EXIF per image (or why it couldn't be read) for the description tile and tree tooltips,
+    /// filled by the background prefetch
+    pub exif_summaries: HashMap<PathBuf, Result<ExifSummary, String>>,
+    /// Cancels the running EXIF prefetch when the inputs change
+    pub exif_prefetch: Option<CancellationToken>,
+    /// Paths whose metadata failed to load, so they aren't retried every frame
+    pub image_load_failed: HashSet<PathBuf>,
+    /// Set of paths queued or currently being loaded in background
     pub images_loading: HashSet<PathBuf>,
+    /// Images waiting for background loading, visible ones first
+    pub thumbnail_queue: Arc<Mutex<ThumbnailQueue>>,
+    /// Bumped by `reload_data` so image cache results for previous inputs can be discarded
+    pub image_cache_generation: u64,
     /// Product search tile: query string
     pub product_search_query: String,
     /// Product search tile: SKU string
@@ -136,20 +372,73 @@ pub struct AppState {
     pub product_search_result_raw: Option<SearchResultOk>,
     /// Product search tile: result JSON (pretty-printed) stored to avoid re-prettifying
     pub product_search_result_pretty: String,
+    /// Recently searched queries, most recent first
+    pub product_search_history: Vec<String>,
+    /// Why the last product search failed, if it did
+    pub product_search_error: Option<String>,
     /// When the last response was received (if any)
     pub product_search_last_response: Option<DateTime<Local>>,
     /// Whether the raw pretty JSON is expanded
     pub product_search_show_raw: bool,
+    /// Display order of the product search results list
+    pub product_search_price_sort: PriceSort,
+    /// Search result thumbnails by URL
+    pub product_thumbnails: HashMap<String, ProductThumbnail>,
+    /// Description chosen from a search result; preferred over auto-search when processing
+    pub manual_description: Option<String>,
+    /// Key of the search result `manual_description` was built from
+    pub manual_description_item: Option<String>,
     /// Whether to perform auto-search when processing images
     pub auto_search_on_process: bool,
     /// Only perform auto-search if a SKU is found in the filename
     pub auto_search_only_if_sku: bool,
+    /// Editable EXIF description for the selected image
+    pub description_edit: String,
+    /// Editable EXIF `Artist` for the selected image
+    pub artist_edit: String,
+    /// Editable EXIF `Copyright` for the selected image
+    pub copyright_edit: String,
+    /// Editable EXIF `XPKeywords` for the selected image
+    pub keywords_edit: String,
+    /// Image the `*_edit` fields were loaded from (reloaded when the selection changes)
+    pub description_edit_path: Option<PathBuf>,
+    /// Write the edited description to a copy instead of the original
+    pub description_write_to_copy: bool,
+    /// An in-place write is awaiting confirmation
+    pub description_confirm_overwrite: bool,
+    /// EXIF `Artist` stamped on every processed output (empty = not written)
+    pub batch_artist: String,
+    /// EXIF `Copyright` stamped on every processed output (empty = not written)
+    pub batch_copyright: String,
+    /// EXIF `XPKeywords` stamped on every processed output (empty = not written)
+    pub batch_keywords: String,
+    /// Files that failed in the most recent batch run
+    pub last_batch_errors: Vec<FailedImage>,
+    /// Per-stage timing of the most recent batch run
+    pub last_batch_timing: Option<TimingSummary>,
+    /// Input vs output sizes of the most recent batch run
+    pub last_batch_conversion: Option<ConversionReport>,
+    /// An interrupted Process All left a checkpoint that can be resumed
+    pub resume_batch_available: bool,
+    /// Whether the processing results window is open
+    pub processing_results_open: bool,
+    /// Last estimate of the total output size for all image files
+    pub batch_size_estimate: Option<BatchSizeEstimate>,
+    /// Whether a batch size estimate is being computed
+    pub batch_size_estimating: bool,
     /// Sender for background tasks
     pub background_sender: UnboundedSender<BackgroundMessage>,
     /// Receiver for background task results
     background_receiver: UnboundedReceiver<BackgroundMessage>,
 }
 
+/// An image that failed during a batch run, with the reason
+#[derive(Clone, Debug)]
+pub struct FailedImage {
+    pub path: PathBuf,
+    pub error: ProcessError,
+}
+
 /// Info about a processed output image
 #[derive(Clone, Debug)]
 pub struct OutputImageInfo {
@@ -159,7 +448,12 @@ pub struct OutputImageInfo {
     pub output_width: u32,
     pub output_height: u32,
     pub was_cropped: bool,
-    /// Downsampled PNG bytes of the processed image (for GUI preview)
+    /// Cropping was on but found nothing to trim
+    pub no_crop_detected: bool,
+    /// Why the input looks damaged even though it decoded
+    pub integrity_warning: Option<String>,
+    /// Downsampled PNG bytes of the processed image (for GUI preview), or the encoded output
+    /// itself when `true_output_preview` is on
     pub preview_data: Vec<u8>,
     /// PNG bytes of the binarized threshold preview (downsampled)
     pub threshold_preview_data: Vec<u8>,
@@ -179,6 +473,8 @@ pub enum BackgroundMessage {
     ImageFilesReady { files: Vec<PathBuf> },
     /// Image files discovery failed
     ImageFilesError { error: String },
+    /// Newly added folders were scanned; these held no recognized images
+    EmptyFoldersFound { folders: Vec<PathBuf> },
     /// Output info for a selected image is ready
     OutputInfoReady {
         input_path: PathBuf,
@@ -190,7 +486,9 @@ pub enum BackgroundMessage {
     ProcessAllComplete {
         processed_count: usize,
         error_count: usize,
-        errors: Vec<String>,
+        errors: Vec<FailedImage>,
+        timing: TimingSummary,
+        conversion: ConversionReport,
     },
     /// Progress update for processing all images
     ProcessAllProgress {
@@ -202,41 +500,99 @@ pub enum BackgroundMessage {
     ImageCacheReady {
         path: PathBuf,
         info: CachedImageInfo,
+        /// `image_cache_generation` when the load was queued
+        generation: u64,
     },
     /// Image cache loading failed
-    ImageCacheError { path: PathBuf },
+    ImageCacheError { path: PathBuf, generation: u64 },
+    /// EXIF of an image was prefetched (or failed to be)
+    ExifReady {
+        path: PathBuf,
+        summary: Result<ExifSummary, String>,
+        /// `image_cache_generation` when the prefetch was started
+        generation: u64,
+    },
     /// Processing a single selected image completed
     ProcessSelectedComplete {
         success: bool,
         error: Option<String>,
     },
+    /// An image was processed; records whether cropping found anything to trim and whether
+    /// the input looked damaged
+    CropChecked {
+        path: PathBuf,
+        no_crop_detected: bool,
+        integrity_warning: Option<String>,
+    },
+    /// Batch output size estimate finished
+    BatchSizeEstimateReady {
+        result: Result<BatchSizeEstimate, String>,
+    },
     /// Product search result (parsed struct and prettified JSON) from Searchspring
     ProductSearchResult {
+        /// The query that was searched for, if any
+        query: Option<String>,
         result: Option<SearchResultOk>,
         pretty: Option<String>,
         error: Option<String>,
         /// When the response was received on the background thread
         received_at: DateTime<Local>,
     },
+    /// A product search result thumbnail was fetched (or failed to be)
+    ProductThumbnailLoaded {
+        url: String,
+        result: Result<Vec<u8>, String>,
+    },
 }
 
-impl Default for AppState {
-    fn default() -> Self {
+impl AppState {
+    /// Create the GUI state, spawning background work onto `runtime`
+    #[expect(clippy::too_many_lines)]
+    #[must_use]
+    pub fn new(runtime: Handle) -> Self {
         let (background_sender, background_receiver) = mpsc::unbounded_channel();
         Self {
             input_paths: Vec::new(),
             input_paths_loading: LoadingState::NotStarted,
             image_files: Vec::new(),
+            image_filter: String::new(),
+            tree_filter: String::new(),
+            input_tree_open: TreeOpenState::default(),
+            output_tree_open: TreeOpenState::default(),
+            image_sort: ImageSort::default(),
+            natural_sort: false,
             image_files_loading: LoadingState::NotStarted,
             path_to_remove: None,
+            input_path_move: None,
+            input_overrides: InputOverrides::default(),
+            override_editor_root: None,
+            output_root: None,
+            image_extensions: default_image_extensions(),
+            output_root_edit: String::new(),
             clear_all: false,
+            confirm_clear_open: false,
+            empty_input_folders: Vec::new(),
+            import_list_path: String::new(),
+            import_list_status: None,
             rename_rules: Vec::new(),
-            rename_rules_enabled: true,
+            rename_rules_skipped: Vec::new(),
+            rename_rules_enabled: crate::rename_rules::rules_enabled(&APP_HOME).unwrap_or(true),
             rename_hyphenate: false,
+            rename_auto_truncate: false,
             renamed_files: Vec::new(),
+            rename_previews: Vec::new(),
+            draft_rule: RenameRule {
+                only_when_name_too_long: false,
+                ..RenameRule::default()
+            },
+            draft_rule_preview: false,
+            draft_rename_previews: Vec::new(),
             rename_preview_key: 0,
             max_name_length: MAX_NAME_LENGTH.load(Ordering::SeqCst),
             logs_visible: false,
+            log_export_path: String::new(),
+            log_min_level: tracing::Level::TRACE,
+            json_log_path: String::new(),
             about_open: false,
             selected_input_file: None,
             input_preview_path: None,
@@ -244,26 +600,90 @@ impl Default for AppState {
             initialized: false,
             crop_to_content: true,
             crop_threshold: 20,
+            background_detection: BackgroundDetection::default(),
+            background_override: None,
+            eyedropper_active: false,
+            min_output_dimension: 0,
             binarization_mode: BinarizationMode::KeepWhite,
             box_thickness: 10,
+            deskew: false,
+            max_deskew_degrees: DEFAULT_MAX_DESKEW_DEGREES,
+            brightness: 0,
+            contrast: 0.0,
+            gamma: 1.0,
+            sharpen_sigma: 0.0,
+            sharpen_threshold: 0,
+            threshold_compare: false,
             sync_preview_pan_zoom: true,
+            preview_loupe: false,
+            reset_view_on_select: false,
+            preserve_timestamps: false,
+            on_decode_error: DecodeErrorPolicy::default(),
+            skip_animated: false,
+            convert_to_srgb: false,
+            on_conflict: OutputConflictPolicy::default(),
             jpeg_quality: 90,
+            jpeg_progressive: false,
+            jpeg_subsampling: JpegSubsampling::default(),
+            true_output_preview: false,
+            png_compression: 0,
+            png_quantize: false,
+            watermark_enabled: false,
+            watermark_use_image: false,
+            watermark_text: "PROOF".to_owned(),
+            watermark_image: String::new(),
+            watermark_position: WatermarkPosition::default(),
+            watermark_opacity: 0.5,
+            watermark_scale: 0.3,
             selected_output_info: None,
+            no_crop_files: HashSet::new(),
+            suspicious_files: HashMap::new(),
             output_info_loading: false,
             process_all_running: false,
             process_all_progress: None,
-            process_all_handles: None,
-            image_cache: HashMap::new(),
+            runtime,
+            shutdown: CancellationToken::new(),
+            process_all_cancel: None,
+            image_cache: ImageCache::new(thumbnail_cache_bytes()),
+            exif_summaries: HashMap::new(),
+            exif_prefetch: None,
+            evicted_thumbnails: Vec::new(),
+            image_load_failed: HashSet::new(),
             images_loading: HashSet::new(),
+            thumbnail_queue: Arc::new(Mutex::new(ThumbnailQueue::default())),
+            image_cache_generation: 0,
             product_search_query: String::new(),
             product_search_sku: String::new(),
             product_search_use_suggestion: true,
             product_search_result_raw: None,
             product_search_result_pretty: String::new(),
+            product_search_history: Vec::new(),
+            product_search_error: None,
             product_search_last_response: None,
             product_search_show_raw: false,
+            product_search_price_sort: PriceSort::ApiOrder,
+            product_thumbnails: HashMap::new(),
+            manual_description: None,
+            manual_description_item: None,
             auto_search_on_process: false,
             auto_search_only_if_sku: true,
+            description_edit: String::new(),
+            artist_edit: String::new(),
+            copyright_edit: String::new(),
+            keywords_edit: String::new(),
+            description_edit_path: None,
+            description_write_to_copy: true,
+            description_confirm_overwrite: false,
+            batch_artist: String::new(),
+            batch_copyright: String::new(),
+            batch_keywords: String::new(),
+            last_batch_errors: Vec::new(),
+            last_batch_timing: None,
+            last_batch_conversion: None,
+            resume_batch_available: batch_checkpoint::exists(&APP_HOME),
+            processing_results_open: false,
+            batch_size_estimate: None,
+            batch_size_estimating: false,
             background_sender,
             background_receiver,
         }
@@ -273,18 +693,58 @@ impl Default for AppState {
 impl AppState {
     /// Start async reload of all data - does NOT block!
     pub fn reload_data(&mut self) {
+        // Drop queued thumbnail loads; results already in flight are ignored once they arrive
+        self.image_cache_generation += 1;
+        self.thumbnail_queue
+            .lock()
+            .unwrap()
+            .reset(self.image_cache_generation, []);
+        self.images_loading.clear();
+        self.exif_summaries.clear();
+        if let Some(prefetch) = self.exif_prefetch.take() {
+            prefetch.cancel();
+        }
+
         // Start loading input paths in background
         self.start_load_input_paths();
 
         // Load rename rules (these are small, can stay sync for now)
-        match crate::rename_rules::list_rules(&APP_HOME) {
-            Ok(rules) => {
-                self.rename_rules = rules.into_iter().map(|(_, r)| r).collect();
+        match crate::rename_rules::list_rules_with_skipped(&APP_HOME) {
+            Ok(listing) => {
+                self.rename_rules = listing.rules.into_iter().map(|(_, r)| r).collect();
+                self.rename_rules_skipped = listing.skipped;
             }
             Err(e) => {
                 error!("Failed to load rename rules: {}", e);
                 self.rename_rules.clear();
+                self.rename_rules_skipped.clear();
+            }
+        }
+        match crate::rename_rules::rules_enabled(&APP_HOME) {
+            Ok(enabled) => self.rename_rules_enabled = enabled,
+            Err(e) => error!("Failed to load rename rules enabled flag: {}", e),
+        }
+        match load_input_overrides(&APP_HOME) {
+            Ok(overrides) => self.input_overrides = overrides,
+            Err(e) => error!("Failed to load input overrides: {}", e),
+        }
+        match load_output_root(&APP_HOME) {
+            Ok(root) => {
+                self.output_root_edit = root
+                    .as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_default();
+                self.output_root = root;
             }
+            Err(e) => error!("Failed to load output folder: {}", e),
+        }
+        match load_image_extensions(&APP_HOME) {
+            Ok(extensions) => self.image_extensions = extensions,
+            Err(e) => error!("Failed to load image extensions: {}", e),
+        }
+        match search_history::list_queries(&APP_HOME) {
+            Ok(history) => self.product_search_history = history,
+            Err(e) => error!("Failed to load search history: {}", e),
         }
 
         // Update max name length
@@ -294,12 +754,54 @@ impl AppState {
         self.rename_preview_key = 0;
     }
 
+    /// Record a product search response, keeping a failed search apart from one that found
+    /// nothing. Successfully searched queries are added to the history.
+    fn on_product_search_result(
+        &mut self,
+        query: Option<String>,
+        result: Option<SearchResultOk>,
+        pretty: Option<String>,
+        error: Option<String>,
+        received_at: DateTime<Local>,
+    ) {
+        // Record when we got the response so UI can show it
+        self.product_search_last_response = Some(received_at);
+
+        if let Some(err) = &error {
+            error!("Product search failed: {}", err);
+            self.product_search_result_raw = None;
+            self.product_search_result_pretty.clear();
+        } else {
+            self.product_search_result_raw = result;
+            self.product_search_result_pretty = pretty.unwrap_or_default();
+            if let Some(query) = query {
+                match search_history::add_query(&APP_HOME, &query) {
+                    Ok(history) => self.product_search_history = history,
+                    Err(e) => error!("Failed to save search history: {}", e),
+                }
+            }
+        }
+        self.product_search_error = error;
+    }
+
+    /// Record a fetched product thumbnail, or that it failed so a placeholder is shown
+    fn on_product_thumbnail_loaded(&mut self, url: String, result: Result<Vec<u8>, String>) {
+        let thumbnail = match result {
+            Ok(bytes) => ProductThumbnail::Loaded(bytes),
+            Err(e) => {
+                warn!("Failed to load product thumbnail {}: {}", url, e);
+                ProductThumbnail::Failed
+            }
+        };
+        self.product_thumbnails.insert(url, thumbnail);
+    }
+
     /// Start loading input paths in background
     fn start_load_input_paths(&mut self) {
         self.input_paths_loading = LoadingState::Loading;
         let sender = self.background_sender.clone();
 
-        tokio::spawn(async move {
+        self.runtime.spawn(async move {
             // Use spawn_blocking for the file I/O
             let result = tokio::task::spawn_blocking(|| inputs::load_inputs(&APP_HOME)).await;
 
@@ -325,8 +827,9 @@ impl AppState {
     fn start_discover_image_files(&mut self) {
         self.image_files_loading = LoadingState::Loading;
         let sender = self.background_sender.clone();
+        let extensions = self.image_extensions.clone();
 
-        tokio::spawn(async move {
+        self.runtime.spawn(async move {
             // Use spawn_blocking for the recursive directory walk
             let result = tokio::task::spawn_blocking(|| inputs::list_files(&APP_HOME)).await;
 
@@ -335,7 +838,7 @@ impl AppState {
                     // Filter to image files
                     let image_files: Vec<PathBuf> = files
                         .into_iter()
-                        .filter(|p| is_image_file(p.as_path()))
+                        .filter(|p| is_image_file(p.as_path(), &extensions))
                         .collect();
                     let _ = sender.send(BackgroundMessage::ImageFilesReady { files: image_files });
                 }
@@ -353,42 +856,71 @@ impl AppState {
         });
     }
 
-    /// Start background loading for all images not yet in cache
-    /// Uses a single background task that processes images with limited concurrency
+    /// Queue every image not yet in cache for background loading. Images drawn on screen can be
+    /// moved to the front of the line with [`Self::request_visible_thumbnails`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the thumbnail queue mutex is poisoned.
     pub fn start_image_cache_loading(&mut self) {
-        // Collect paths that need loading
+        self.image_load_failed.clear();
+        let mut queue = self.thumbnail_queue.lock().unwrap();
+        // Paths a worker already picked up will report back on their own
+        self.images_loading.retain(|p| !queue.is_pending(p));
         let paths_to_load: Vec<PathBuf> = self
             .image_files
             .iter()
-            .filter(|p| !self.image_cache.contains_key(*p) && !self.images_loading.contains(*p))
+            .filter(|p| !self.image_cache.contains_key(p) && !self.images_loading.contains(*p))
             .cloned()
             .collect();
+        queue.reset(self.image_cache_generation, paths_to_load.iter().cloned());
+        drop(queue);
+        self.images_loading.extend(paths_to_load);
+        self.spawn_thumbnail_workers();
+    }
 
-        if paths_to_load.is_empty() {
+    /// Load these on-screen images ahead of the background queue, re-queueing any that were
+    /// evicted from the cache
+    ///
+    /// # Panics
+    ///
+    /// Panics if the thumbnail queue mutex is poisoned.
+    pub fn request_visible_thumbnails(&mut self, paths: &[PathBuf]) {
+        if paths.is_empty() {
             return;
         }
-
-        // Mark all as loading
-        for path in &paths_to_load {
-            self.images_loading.insert(path.clone());
+        let mut queue = self.thumbnail_queue.lock().unwrap();
+        // Prioritize in reverse so the first path drawn ends up first in line
+        for path in paths.iter().rev() {
+            if self.image_cache.contains_key(path) || self.image_load_failed.contains(path) {
+                continue;
+            }
+            if self.images_loading.insert(path.clone()) {
+                queue.request(path);
+            } else {
+                queue.prioritize(path);
+            }
         }
+        drop(queue);
+        self.spawn_thumbnail_workers();
+    }
 
-        let sender = self.background_sender.clone();
-
-        // Spawn a single task that processes images with concurrency limit
-        tokio::spawn(async move {
-            // Process images with limited concurrency (4 at a time)
-            let semaphore = Arc::new(tokio::sync::Semaphore::new(16));
-
-            let mut handles = Vec::new();
-
-            for path in paths_to_load {
-                let sender = sender.clone();
-                let semaphore = semaphore.clone();
-
-                let handle = tokio::spawn(async move {
-                    let _permit = semaphore.acquire().await;
-
+    /// Start loader tasks until the queue has as many as it wants
+    fn spawn_thumbnail_workers(&self) {
+        let limit = thumbnail_concurrency();
+        while self.thumbnail_queue.lock().unwrap().try_add_worker(limit) {
+            let queue = self.thumbnail_queue.clone();
+            let sender = self.background_sender.clone();
+            let shutdown = self.shutdown.clone();
+            self.runtime.spawn(async move {
+                loop {
+                    if shutdown.is_cancelled() {
+                        break;
+                    }
+                    let next = queue.lock().unwrap().next_or_retire();
+                    let Some((path, generation)) = next else {
+                        break;
+                    };
                     let path_clone = path.clone();
                     let result = tokio::task::spawn_blocking(move || {
                         image_processing::load_image_metadata(&path_clone, THUMBNAIL_SIZE)
@@ -397,22 +929,101 @@ impl AppState {
 
                     match result {
                         Ok(Ok(info)) => {
-                            let _ = sender.send(BackgroundMessage::ImageCacheReady { path, info });
+                            let _ = sender.send(BackgroundMessage::ImageCacheReady {
+                                path,
+                                info,
+                                generation,
+                            });
                         }
                         _ => {
-                            let _ = sender.send(BackgroundMessage::ImageCacheError { path });
+                            let _ = sender
+                                .send(BackgroundMessage::ImageCacheError { path, generation });
                         }
                     }
-                });
+                }
+            });
+        }
+    }
 
-                handles.push(handle);
+    /// Record a finished image cache load, ignoring results queued before the last reload since
+    /// they belong to a previous set of inputs
+    fn on_image_cache_result(
+        &mut self,
+        path: PathBuf,
+        generation: u64,
+        info: Option<CachedImageInfo>,
+    ) {
+        if generation != self.image_cache_generation {
+            return;
+        }
+        self.images_loading.remove(&path);
+        match info {
+            Some(info) => {
+                let evicted = self.image_cache.insert(path, info);
+                self.evicted_thumbnails.extend(evicted);
             }
-
-            // Wait for all to complete
-            for handle in handles {
-                let _ = handle.await;
+            None => {
+                self.image_load_failed.insert(path);
             }
-        });
+        }
+    }
+
+    /// Parse the EXIF of every discovered image in the background, a few at a time, so the
+    /// description tile and tooltips don't read files while drawing. Any prefetch still running
+    /// for earlier inputs is cancelled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the prefetch queue mutex is poisoned.
+    pub fn start_exif_prefetch(&mut self) {
+        if let Some(previous) = self.exif_prefetch.take() {
+            previous.cancel();
+        }
+        let paths: VecDeque<PathBuf> = self
+            .image_files
+            .iter()
+            .filter(|p| !self.exif_summaries.contains_key(*p))
+            .cloned()
+            .collect();
+        if paths.is_empty() {
+            return;
+        }
+        let cancel = self.shutdown.child_token();
+        self.exif_prefetch = Some(cancel.clone());
+        let queue = Arc::new(Mutex::new(paths));
+        let generation = self.image_cache_generation;
+        for _ in 0..thumbnail_concurrency() {
+            let queue = queue.clone();
+            let sender = self.background_sender.clone();
+            let cancel = cancel.clone();
+            self.runtime.spawn(async move {
+                while !cancel.is_cancelled() {
+                    let Some(path) = queue.lock().unwrap().pop_front() else {
+                        break;
+                    };
+                    let path_clone = path.clone();
+                    let Ok(summary) = tokio::task::spawn_blocking(move || {
+                        image_processing::read_exif_data(&path_clone).map_err(|e| e.to_string())
+                    })
+                    .await
+                    else {
+                        break;
+                    };
+                    let _ = sender.send(BackgroundMessage::ExifReady {
+                        path,
+                        summary,
+                        generation,
+                    });
+                }
+            });
+        }
+    }
+
+    /// The EXIF of `path`, read now if the prefetch hasn't reached it yet
+    pub fn exif_summary(&mut self, path: &Path) -> &Result<ExifSummary, String> {
+        self.exif_summaries
+            .entry(path.to_path_buf())
+            .or_insert_with(|| image_processing::read_exif_data(path).map_err(|e| e.to_string()))
     }
 
     /// Check if an image is still loading
@@ -421,12 +1032,70 @@ impl AppState {
         self.images_loading.contains(path)
     }
 
-    /// Get cached image info if available
+    /// Get cached image info if available, marking it as recently used
     #[must_use]
-    pub fn get_cached_image(&self, path: &PathBuf) -> Option<&CachedImageInfo> {
+    pub fn get_cached_image(&self, path: &Path) -> Option<&CachedImageInfo> {
         self.image_cache.get(path)
     }
 
+    /// Scan newly added folders in the background and remember the ones holding no images, so
+    /// dropping the wrong folder is noticed right away
+    pub fn check_for_empty_folders(&mut self, folders: Vec<PathBuf>) {
+        self.empty_input_folders.clear();
+        if folders.is_empty() {
+            return;
+        }
+        let sender = self.background_sender.clone();
+        let output_root = self.output_root.clone();
+        let extensions = self.image_extensions.clone();
+        self.runtime.spawn(async move {
+            let result = tokio::task::spawn_blocking(move || {
+                folders
+                    .into_iter()
+                    .filter(|folder| {
+                        inputs::count_images_under(folder, output_root.as_deref(), &extensions)
+                            .is_ok_and(|count| count == 0)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .await;
+            if let Ok(folders) = result {
+                let _ = sender.send(BackgroundMessage::EmptyFoldersFound { folders });
+            }
+        });
+    }
+
+    /// Add the inputs listed in the file at [`Self::import_list_path`], one path per line
+    pub fn import_input_list(&mut self) {
+        let list_file = PathBuf::from(self.import_list_path.trim());
+        match inputs::add_from_list_file(&APP_HOME, &list_file) {
+            Ok(result) => {
+                info!(
+                    "Imported {}: {} added, {} skipped",
+                    list_file.display(),
+                    result.added.len(),
+                    result.skipped.len()
+                );
+                let mut status = format!(
+                    "{} added, {} skipped",
+                    result.added.len(),
+                    result.skipped.len()
+                );
+                for (path, reason) in &result.skipped {
+                    status.push_str(&format!("\n{} ({reason})", path.display()));
+                }
+                self.import_list_status = Some(status);
+                let folders = result.added.into_iter().filter(|p| p.is_dir()).collect();
+                self.check_for_empty_folders(folders);
+                self.reload_data();
+            }
+            Err(e) => {
+                error!("{}", e);
+                self.import_list_status = Some(e.to_string());
+            }
+        }
+    }
+
     /// Handle deferred actions from previous frame
     pub fn handle_deferred_actions(&mut self) {
         // Handle clear all
@@ -435,7 +1104,7 @@ impl AppState {
             self.input_paths_loading = LoadingState::Loading;
             let sender = self.background_sender.clone();
 
-            tokio::spawn(async move {
+            self.runtime.spawn(async move {
                 let result = tokio::task::spawn_blocking(|| inputs::clear_all(&APP_HOME)).await;
 
                 match result {
@@ -464,7 +1133,7 @@ impl AppState {
             self.input_paths_loading = LoadingState::Loading;
             let sender = self.background_sender.clone();
 
-            tokio::spawn(async move {
+            self.runtime.spawn(async move {
                 let path_clone = path.clone();
                 let result = tokio::task::spawn_blocking(move || {
                     inputs::remove_path(&APP_HOME, &path_clone)
@@ -500,20 +1169,81 @@ impl AppState {
                 }
             });
         }
+
+        // Handle input path reordering: apply locally right away, persist in the background
+        if let Some((from, to)) = self.input_path_move.take()
+            && from < self.input_paths.len()
+            && from != to
+        {
+            let path = self.input_paths.remove(from);
+            self.input_paths
+                .insert(to.min(self.input_paths.len()), path);
+            let order = self.input_paths.clone();
+            self.runtime.spawn(async move {
+                let result =
+                    tokio::task::spawn_blocking(move || inputs::set_input_order(&APP_HOME, &order))
+                        .await;
+                match result {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => error!("Failed to save input order: {}", e),
+                    Err(e) => error!("Task panicked: {}", e),
+                }
+            });
+        }
     }
 
     /// Update the renamed files cache if needed
     pub fn update_rename_preview(&mut self) {
+        let key = self.compute_rename_preview_key();
+        if self.rename_preview_key != key {
+            let options = RenameOptions {
+                max_name_length: self.max_name_length,
+                length_unit: name_length_unit(),
+                rules_enabled: self.rename_rules_enabled,
+                hyphenate: self.rename_hyphenate,
+                auto_truncate: self.rename_auto_truncate,
+            };
+            self.rename_previews =
+                crate::rename_rules::preview(&self.image_files, &self.rename_rules, &options);
+            self.renamed_files = self
+                .rename_previews
+                .iter()
+                .map(|p| p.renamed.clone())
+                .collect();
+            self.draft_rename_previews = if self.draft_rule_preview {
+                crate::rename_rules::preview(&self.image_files, &self.draft_rules(), &options)
+            } else {
+                Vec::new()
+            };
+            self.rename_preview_key = key;
+        }
+    }
+
+    /// The saved rules followed by `draft_rule`
+    #[must_use]
+    pub fn draft_rules(&self) -> Vec<RenameRule> {
+        let mut rules = self.rename_rules.clone();
+        rules.push(self.draft_rule.clone());
+        rules
+    }
+
+    /// Hash of everything the rename preview depends on. Covers the file paths themselves, not
+    /// just their count, so swapping in a different set of the same size refreshes it.
+    fn compute_rename_preview_key(&self) -> u64 {
         use std::collections::hash_map::DefaultHasher;
         use std::hash::Hash;
         use std::hash::Hasher;
 
         let mut hasher = DefaultHasher::new();
-        self.image_files.len().hash(&mut hasher);
+        self.image_files.hash(&mut hasher);
         self.max_name_length.hash(&mut hasher);
+        name_length_unit().hash(&mut hasher);
         self.rename_rules_enabled.hash(&mut hasher);
         self.rename_hyphenate.hash(&mut hasher);
-        for r in &self.rename_rules {
+        self.rename_auto_truncate.hash(&mut hasher);
+        self.draft_rule_preview.hash(&mut hasher);
+        let draft = self.draft_rule_preview.then_some(&self.draft_rule);
+        for r in self.rename_rules.iter().chain(draft) {
             r.id.hash(&mut hasher);
             r.find.hash(&mut hasher);
             r.replace.hash(&mut hasher);
@@ -521,17 +1251,24 @@ impl AppState {
             r.case_sensitive.hash(&mut hasher);
             r.only_when_name_too_long.hash(&mut hasher);
         }
-        let key = hasher.finish();
+        hasher.finish()
+    }
+
+    /// Record whether the last processing of `path` found nothing to crop
+    fn set_no_crop_detected(&mut self, path: &Path, no_crop_detected: bool) {
+        if no_crop_detected {
+            self.no_crop_files.insert(path.to_path_buf());
+        } else {
+            self.no_crop_files.remove(path);
+        }
+    }
 
-        if self.rename_preview_key != key {
-            self.renamed_files = apply_rules_seq(
-                &self.image_files,
-                &self.rename_rules,
-                self.max_name_length,
-                self.rename_rules_enabled,
-                self.rename_hyphenate,
-            );
-            self.rename_preview_key = key;
+    /// Record why the last processing of `path` found it damaged, or that it looked fine
+    fn set_integrity_warning(&mut self, path: &Path, warning: Option<String>) {
+        if let Some(warning) = warning {
+            self.suspicious_files.insert(path.to_path_buf(), warning);
+        } else {
+            self.suspicious_files.remove(path);
         }
     }
 
@@ -554,7 +1291,12 @@ impl AppState {
                     .map(|s| s.to_string_lossy().to_string())
                     .unwrap_or_default();
 
-                if let Some(output_path) = get_output_path(input_path, input_root, &renamed_name) {
+                if let Some(output_path) = get_output_path(
+                    input_path,
+                    input_root,
+                    &renamed_name,
+                    self.output_root.as_deref(),
+                ) {
                     self.output_preview_path = Some(output_path);
                 }
             }
@@ -564,6 +1306,121 @@ impl AppState {
         self.update_selected_output_info();
     }
 
+    /// Select the next image file after the current selection (or the first if none)
+    pub fn select_next(&mut self) {
+        self.select_relative(true);
+    }
+
+    /// Select the image file before the current selection (or the last if none)
+    pub fn select_prev(&mut self) {
+        self.select_relative(false);
+    }
+
+    fn select_relative(&mut self, forward: bool) {
+        // Follow the order the image list is displayed in
+        let files = self.sorted_image_files();
+        if files.is_empty() {
+            return;
+        }
+        let last = files.len() - 1;
+        let current = self
+            .selected_input_file
+            .as_ref()
+            .and_then(|sel| files.iter().position(|p| p == sel));
+        let idx = match (current, forward) {
+            (Some(i), true) => (i + 1).min(last),
+            (Some(i), false) => i.saturating_sub(1),
+            (None, true) => 0,
+            (None, false) => last,
+        };
+        if current == Some(idx) {
+            return;
+        }
+        self.select_file(&files[idx]);
+    }
+
+    /// Settings the current GUI state processes with; previews and estimates start from these
+    #[must_use]
+    pub fn processing_settings(&self) -> ProcessingSettings {
+        ProcessingSettings {
+            crop_to_content: self.crop_to_content,
+            crop_threshold: self.crop_threshold,
+            background_detection: self.background_detection,
+            background_override: self.background_override,
+            min_output_dimension: self.min_output_dimension,
+            binarization_mode: self.binarization_mode,
+            box_thickness: self.box_thickness,
+            deskew: self.deskew,
+            max_deskew_degrees: self.max_deskew_degrees,
+            brightness: self.brightness,
+            contrast: self.contrast,
+            gamma: self.gamma,
+            sharpen_sigma: self.sharpen_sigma,
+            sharpen_threshold: self.sharpen_threshold,
+            jpeg_quality: self.jpeg_quality,
+            jpeg_progressive: self.jpeg_progressive,
+            jpeg_subsampling: self.jpeg_subsampling,
+            png_compression: self.png_compression,
+            png_quantize: self.png_quantize,
+            output_format: None,
+            avif_quality: 0,
+            avif_speed: 0,
+            watermark: self.watermark_settings(),
+            // Auto-search fills `input_descriptions` when enabled and no manual description is
+            // chosen
+            description: self.manual_description.clone(),
+            artist: non_empty(&self.batch_artist),
+            copyright: non_empty(&self.batch_copyright),
+            keywords: non_empty(&self.batch_keywords),
+            preserve_timestamps: self.preserve_timestamps,
+            on_decode_error: self.on_decode_error,
+            on_conflict: self.on_conflict,
+            hash_outputs: false,
+            flatten: false,
+            output_root: self.output_root.clone(),
+            dry_run: false,
+            skip_animated: self.skip_animated,
+            convert_to_srgb: self.convert_to_srgb,
+            input_descriptions: Arc::default(),
+            cancel: None,
+        }
+    }
+
+    /// The watermark outputs get, or `None` when watermarking is off
+    #[must_use]
+    pub fn watermark_settings(&self) -> Option<WatermarkSettings> {
+        if !self.watermark_enabled {
+            return None;
+        }
+        let content = if self.watermark_use_image {
+            let path = self.watermark_image.trim();
+            if path.is_empty() {
+                return None;
+            }
+            WatermarkContent::Image(PathBuf::from(path))
+        } else {
+            WatermarkContent::Text(self.watermark_text.clone())
+        };
+        Some(WatermarkSettings {
+            content,
+            position: self.watermark_position,
+            opacity: self.watermark_opacity,
+            scale: self.watermark_scale,
+        })
+    }
+
+    /// `settings` with the overrides of the input root containing `path` applied
+    fn with_input_overrides(
+        &self,
+        path: &Path,
+        settings: ProcessingSettings,
+    ) -> ProcessingSettings {
+        match self.input_paths.iter().find(|r| path.starts_with(r)) {
+            Some(root) => self.input_overrides.settings_for(root, &settings),
+            None => settings,
+        }
+    }
+
     /// Update the output info for the selected file (runs in background)
     pub fn update_selected_output_info(&mut self) {
         let Some(ref input_path) = self.selected_input_file else {
@@ -576,18 +1433,21 @@ impl AppState {
         self.output_info_loading = true;
         self.selected_output_info = None;
 
+        // Preview doesn't need metadata, and shows animated inputs' first frame
         let settings = ProcessingSettings {
-            crop_to_content: self.crop_to_content,
-            crop_threshold: self.crop_threshold,
-            binarization_mode: self.binarization_mode,
-            box_thickness: self.box_thickness,
-            jpeg_quality: self.jpeg_quality,
-            description: None, // Preview doesn't need metadata
+            description: None,
+            artist: None,
+            copyright: None,
+            keywords: None,
+            skip_animated: false,
+            ..self.processing_settings()
         };
+        let settings = self.with_input_overrides(input_path, settings);
         let input_path = input_path.clone();
         let sender = self.background_sender.clone();
+        let true_output_preview = self.true_output_preview;
 
-        tokio::spawn(async move {
+        self.runtime.spawn(async move {
             let input_path_clone = input_path.clone();
             let result = tokio::task::spawn_blocking(move || {
                 image_processing::process_image(&input_path_clone, &settings)
@@ -603,7 +1463,13 @@ impl AppState {
                         output_width: processed.output_width,
                         output_height: processed.output_height,
                         was_cropped: processed.was_cropped,
-                        preview_data: processed.output_preview_data,
+                        no_crop_detected: processed.no_crop_detected,
+                        integrity_warning: processed.integrity_warning,
+                        preview_data: if true_output_preview {
+                            processed.data
+                        } else {
+                            processed.output_preview_data
+                        },
                         threshold_preview_data: processed.threshold_preview_data,
                         crop_bounds: processed.crop_bounds,
                     };
@@ -625,298 +1491,354 @@ impl AppState {
         });
     }
 
-    /// Process all images according to current settings (runs in background)
-    /// # Panics
-    /// Panics if the mutex for errors cannot be locked.
-    #[expect(clippy::too_many_lines)]
-    pub fn process_all(&mut self) {
-        if self.process_all_running {
-            warn!("Process all already running, ignoring request");
+    /// Estimate the total output size of all image files with the current settings (runs in background)
+    pub fn estimate_batch_size(&mut self) {
+        if self.batch_size_estimating || self.image_files.is_empty() {
             return;
         }
+        self.batch_size_estimating = true;
+        let settings = self.processing_settings();
+        let files = self.image_files.clone();
+        let sender = self.background_sender.clone();
 
-        self.update_rename_preview();
+        self.runtime.spawn(async move {
+            let result = tokio::task::spawn_blocking(move || {
+                image_processing::estimate_batch_size(&files, &settings, BATCH_ESTIMATE_SAMPLE)
+                    .map_err(|e| e.to_string())
+            })
+            .await
+            .unwrap_or_else(|e| Err(format!("Task panicked: {e}")));
+            let _ = sender.send(BackgroundMessage::BatchSizeEstimateReady { result });
+        });
+    }
 
-        let base_settings = ProcessingSettings {
-            crop_to_content: self.crop_to_content,
-            crop_threshold: self.crop_threshold,
-            binarization_mode: self.binarization_mode,
-            box_thickness: self.box_thickness,
-            jpeg_quality: self.jpeg_quality,
-            description: None, // Will be set per-image if auto-search is enabled
+    /// The compiled `image_filter`, or `None` when it is blank
+    /// # Errors
+    /// Returns an error if the filter is not a valid glob.
+    pub fn image_name_filter(&self) -> eyre::Result<Option<NameFilter>> {
+        if self.image_filter.trim().is_empty() {
+            return Ok(None);
+        }
+        NameFilter::parse(&self.image_filter).map(Some)
+    }
+
+    /// Whether `path` passes the image filter (an invalid filter matches nothing)
+    fn passes_filter(filter: &eyre::Result<Option<NameFilter>>, path: &Path) -> bool {
+        match filter {
+            Ok(None) => true,
+            Ok(Some(f)) => f.matches(path),
+            Err(_) => false,
+        }
+    }
+
+    /// `image_files` restricted to those matching the image filter
+    #[must_use]
+    pub fn filtered_image_files(&self) -> Vec<PathBuf> {
+        let filter = self.image_name_filter();
+        self.image_files
+            .iter()
+            .filter(|p| Self::passes_filter(&filter, p))
+            .cloned()
+            .collect()
+    }
+
+    /// Total number of image files across all inputs and their aggregate size in bytes. Sizes
+    /// come from `image_cache`, falling back to filesystem metadata for images not cached yet.
+    #[must_use]
+    pub fn image_totals(&self) -> (usize, u64) {
+        let bytes = self
+            .image_files
+            .iter()
+            .map(|p| match self.image_cache.peek(p) {
+                Some(info) => info.file_size,
+                None => std::fs::metadata(p).map_or(0, |m| m.len()),
+            })
+            .sum();
+        (self.image_files.len(), bytes)
+    }
+
+    /// `filtered_image_files` in the order chosen by `image_sort`. Size, dimensions and modified
+    /// time come from `image_cache`; images not cached yet sort after the rest, by name.
+    #[must_use]
+    pub fn sorted_image_files(&self) -> Vec<PathBuf> {
+        let mut files = self.filtered_image_files();
+        let by_name = |a: &PathBuf, b: &PathBuf| {
+            if self.natural_sort {
+                natural_path_cmp(a, b)
+            } else {
+                a.cmp(b)
+            }
         };
+        let cached = |p: &PathBuf| self.image_cache.peek(p);
+        match self.image_sort {
+            ImageSort::Name => files.sort_by(by_name),
+            ImageSort::Modified => files.sort_by(|a, b| {
+                let modified = |p| cached(p).and_then(|i| i.modified);
+                modified(b).cmp(&modified(a)).then_with(|| by_name(a, b))
+            }),
+            ImageSort::Size => files.sort_by(|a, b| {
+                let size = |p| cached(p).map(|i| i.file_size);
+                size(b).cmp(&size(a)).then_with(|| by_name(a, b))
+            }),
+            ImageSort::Dimensions => files.sort_by(|a, b| {
+                let pixels = |p| cached(p).map(|i| u64::from(i.width) * u64::from(i.height));
+                pixels(b).cmp(&pixels(a)).then_with(|| by_name(a, b))
+            }),
+        }
+        files
+    }
 
-        let image_files = self.image_files.clone();
-        let renamed_files = self.renamed_files.clone();
-        let input_paths = self.input_paths.clone();
-        let sender = self.background_sender.clone();
-        let auto_search_on_process = self.auto_search_on_process;
-        let auto_search_only_if_sku = self.auto_search_only_if_sku;
+    /// `rename_previews` restricted to the image filter
+    #[must_use]
+    pub fn filtered_rename_previews(&self) -> Vec<RenamePreview> {
+        Self::filter_previews(&self.image_name_filter(), &self.rename_previews)
+    }
 
-        let total = image_files.len();
+    /// `draft_rename_previews` restricted to the image filter
+    #[must_use]
+    pub fn filtered_draft_rename_previews(&self) -> Vec<RenamePreview> {
+        Self::filter_previews(&self.image_name_filter(), &self.draft_rename_previews)
+    }
 
-        self.process_all_running = true;
-        self.process_all_progress = Some((0, total));
-
-        // Shared structures for handles and counters so we can cancel and report final totals
-        let handles_arc: Arc<Mutex<Vec<tokio::task::JoinHandle<()>>>> =
-            Arc::new(Mutex::new(Vec::new()));
-        self.process_all_handles = Some(handles_arc.clone());
-
-        let processed_count = Arc::new(AtomicUsize::new(0));
-        let error_count = Arc::new(AtomicUsize::new(0));
-        let errors: Arc<Mutex<Vec<String>>> = Arc::new(Mutex::new(Vec::new()));
-
-        for (idx, input_path) in image_files.into_iter().enumerate() {
-            let renamed_opt = renamed_files.get(idx).cloned();
-            let input_paths_clone = input_paths.clone();
-            let base_settings = base_settings.clone();
-            let sender = sender.clone();
-            let processed_count = processed_count.clone();
-            let error_count = error_count.clone();
-            let errors = errors.clone();
-            let handles_arc = handles_arc.clone();
-
-            let handle = tokio::spawn(async move {
-                let start = Instant::now();
-
-                // Resolve renamed filename and input root
-                if renamed_opt.is_none() {
-                    let msg = format!("Missing renamed file for {}", input_path.display());
-                    errors.lock().unwrap().push(msg.clone());
-                    error_count.fetch_add(1, Ordering::SeqCst);
-                    let current = processed_count.fetch_add(1, Ordering::SeqCst) + 1;
-                    let _ = sender.send(BackgroundMessage::ProcessAllProgress {
-                        current,
-                        total,
-                        current_file: input_path.clone(),
-                    });
-                    return;
-                }
+    /// The entries of `previews` whose original passes `filter`
+    fn filter_previews(
+        filter: &eyre::Result<Option<NameFilter>>,
+        previews: &[RenamePreview],
+    ) -> Vec<RenamePreview> {
+        previews
+            .iter()
+            .filter(|p| Self::passes_filter(filter, &p.original))
+            .cloned()
+            .collect()
+    }
 
-                let renamed_name = renamed_opt
-                    .unwrap()
-                    .file_name()
-                    .map(|s| s.to_string_lossy().to_string())
-                    .unwrap_or_default();
+    /// Process all images matching the image filter according to current settings (runs in
+    /// background)
+    pub fn process_all(&mut self) {
+        if self.process_all_running {
+            warn!("Process all already running, ignoring request");
+            return;
+        }
+        if let Err(e) = batch_checkpoint::start(&APP_HOME) {
+            warn!("Failed to start batch checkpoint, the batch can't be resumed: {}", e);
+        }
+        self.process_paths(self.filtered_image_files());
+    }
 
-                let input_root = input_paths_clone
-                    .iter()
-                    .find(|r| input_path.starts_with(r))
-                    .cloned();
-
-                if input_root.is_none() {
-                    let msg = format!("Could not find input root for {}", input_path.display());
-                    errors.lock().unwrap().push(msg.clone());
-                    error_count.fetch_add(1, Ordering::SeqCst);
-                    let current = processed_count.fetch_add(1, Ordering::SeqCst) + 1;
-                    let _ = sender.send(BackgroundMessage::ProcessAllProgress {
-                        current,
-                        total,
-                        current_file: input_path.clone(),
-                    });
-                    return;
-                }
+    /// Continue an interrupted Process All, skipping images the checkpoint lists as done
+    pub fn resume_last_batch(&mut self) {
+        let completed = match batch_checkpoint::completed_inputs(&APP_HOME) {
+            Ok(completed) => completed,
+            Err(e) => {
+                error!("Failed to read batch checkpoint: {}", e);
+                return;
+            }
+        };
+        self.update_rename_preview();
+        let files = self.filtered_image_files();
+        let total = files.len();
+        let pending = Self::skip_completed(files, &completed);
+        info!(
+            "Resuming last batch: {} of {} files already done",
+            total - pending.len(),
+            total
+        );
+        self.process_paths(pending);
+    }
 
-                // Calculate output path
-                let Some(output_path) = image_processing::get_output_path(
-                    &input_path,
-                    &input_root.clone().unwrap(),
-                    &renamed_name,
-                ) else {
-                    errors.lock().unwrap().push(format!(
-                        "Could not calculate output path for {}",
-                        input_path.display()
-                    ));
-                    error_count.fetch_add(1, Ordering::SeqCst);
-                    let current = processed_count.fetch_add(1, Ordering::SeqCst) + 1;
-                    let _ = sender.send(BackgroundMessage::ProcessAllProgress {
-                        current,
-                        total,
-                        current_file: input_path.clone(),
-                    });
-                    return;
-                };
-
-                if let Some(parent) = output_path.parent()
-                    && let Err(e) = std::fs::create_dir_all(parent)
-                {
-                    errors.lock().unwrap().push(format!(
-                        "Failed to create dir {}: {}",
-                        parent.display(),
-                        e
-                    ));
-                    error_count.fetch_add(1, Ordering::SeqCst);
-                    let current = processed_count.fetch_add(1, Ordering::SeqCst) + 1;
-                    let _ = sender.send(BackgroundMessage::ProcessAllProgress {
-                        current,
-                        total,
-                        current_file: input_path.clone(),
-                    });
-                    return;
-                }
+    /// `files` without those listed in `completed`
+    fn skip_completed(files: Vec<PathBuf>, completed: &HashSet<PathBuf>) -> Vec<PathBuf> {
+        files
+            .into_iter()
+            .filter(|file| !completed.contains(file))
+            .collect()
+    }
 
-                // Build settings with optional auto-search description
-                let mut settings = base_settings.clone();
-                if auto_search_on_process {
-                    // Get the filename for search suggestion
-                    if let Some(filename) = input_path.file_name().and_then(|s| s.to_str()) {
-                        use crate::gui::tiles::suggest_search;
-                        let suggestion = suggest_search(filename);
-
-                        // Check if we should perform the search
-                        let should_search = if auto_search_only_if_sku {
-                            suggestion.sku.is_some()
-                        } else {
-                            true
-                        };
-
-                        if should_search {
-                            // Perform the search
-                            let search_result = suggestion.search().await;
-
-                            if let Ok(result) = search_result
-                                && let Some(results) = &result.results
-                            {
-                                // Build description from search results
-                                let mut description_parts: Vec<String> = Vec::new();
-                                for item in results {
-                                    let name = item.name.as_deref().unwrap_or("");
-                                    let price = item.price.as_ref().map_or("", |p| p.0.as_str());
-                                    if !name.is_empty() || !price.is_empty() {
-                                        description_parts.push(format!("{name} ${price}"));
-                                    }
-                                }
-                                if !description_parts.is_empty() {
-                                    settings.description = Some(description_parts.join("\n"));
-                                }
-                            }
-                        }
-                    }
-                }
+    /// Process the given image files (a subset of `image_files`) according to current settings
+    /// (runs in background). Progress and totals cover only these files. The batch goes through
+    /// [`pipeline::execute`], so outputs get the same paths, collision check and conflict
+    /// handling as `cm process`.
+    fn process_paths(&mut self, image_files: Vec<PathBuf>) {
+        if self.process_all_running {
+            warn!("Process all already running, ignoring request");
+            return;
+        }
 
-                // Run image processing in blocking thread pool
-                let input_path_block = input_path.clone();
-                let output_path_block = output_path.clone();
-                let settings_block = settings.clone();
-                let result = tokio::task::spawn_blocking(move || -> eyre::Result<()> {
-                    let processed =
-                        image_processing::process_image(&input_path_block, &settings_block)?;
-                    std::fs::write(&output_path_block, &processed.data)?;
-                    Ok(())
-                })
-                .await;
+        self.update_rename_preview();
 
-                match result {
-                    Ok(Ok(())) => {
-                        let dur = start.elapsed();
-                        let current = processed_count.fetch_add(1, Ordering::SeqCst) + 1;
-                        let remaining = total.saturating_sub(current);
-                        info!(
-                            "Processed image {} in {}, {} remain",
-                            input_path.display(),
-                            format_duration(dur),
-                            remaining
-                        );
-                        let _ = sender.send(BackgroundMessage::ProcessAllProgress {
-                            current,
-                            total,
-                            current_file: input_path.clone(),
-                        });
-                    }
-                    Ok(Err(e)) => {
-                        error_count.fetch_add(1, Ordering::SeqCst);
-                        errors.lock().unwrap().push(format!(
-                            "Failed to process {}: {}",
-                            input_path.display(),
-                            e
-                        ));
-                        let current = processed_count.fetch_add(1, Ordering::SeqCst) + 1;
-                        let _ = sender.send(BackgroundMessage::ProcessAllProgress {
-                            current,
-                            total,
-                            current_file: input_path.clone(),
-                        });
-                    }
-                    Err(e) => {
-                        error_count.fetch_add(1, Ordering::SeqCst);
-                        errors.lock().unwrap().push(format!(
-                            "Task panicked for {}: {}",
-                            input_path.display(),
-                            e
-                        ));
-                        let current = processed_count.fetch_add(1, Ordering::SeqCst) + 1;
-                        let _ = sender.send(BackgroundMessage::ProcessAllProgress {
-                            current,
-                            total,
-                            current_file: input_path.clone(),
-                        });
-                    }
+        let renamed_by_input: HashMap<&PathBuf, &PathBuf> =
+            self.image_files.iter().zip(&self.renamed_files).collect();
+        let mut plan = PipelinePlan {
+            roots: self.input_paths.clone(),
+            total: image_files.len(),
+            ..PipelinePlan::default()
+        };
+        let mut failed = Vec::new();
+        for file in image_files {
+            match renamed_by_input.get(&file) {
+                Some(&renamed) => {
+                    plan.files.push(file);
+                    plan.renamed.push(renamed.clone());
                 }
-            });
-
-            // Store handle so we can cancel later
-            handles_arc.lock().unwrap().push(handle);
+                None => failed.push(FailedImage {
+                    path: file,
+                    error: ProcessError::OutputPathFailed,
+                }),
+            }
         }
 
-        // Spawn a supervisor that awaits all per-image tasks and reports final result
-        let handles_supervisor = handles_arc.clone();
-        let errors_supervisor = errors.clone();
-        let sender_supervisor = sender.clone();
-        let processed_supervisor = processed_count.clone();
-        let error_count_supervisor = error_count.clone();
-
-        tokio::spawn(async move {
-            // Pop and await each handle until none left
-            loop {
-                let maybe_handle = {
-                    let mut g = handles_supervisor.lock().unwrap();
-                    g.pop()
-                };
-                if let Some(h) = maybe_handle {
-                    let _ = h.await;
-                } else {
-                    break;
+        let cancel = Arc::new(AtomicBool::new(false));
+        let mut config = PipelineConfig {
+            image_extensions: self.image_extensions.clone(),
+            settings: ProcessingSettings {
+                cancel: Some(cancel.clone()),
+                ..self.processing_settings()
+            },
+            overrides: self.input_overrides.clone(),
+            ..PipelineConfig::new(self.input_paths.clone())
+        };
+        let auto_search_on_process =
+            self.auto_search_on_process && self.manual_description.is_none();
+        let auto_search_only_if_sku = self.auto_search_only_if_sku;
+        let sender = self.background_sender.clone();
+        let shutdown = self.shutdown.clone();
+
+        self.process_all_running = true;
+        self.process_all_progress = Some((0, plan.total));
+        self.process_all_cancel = Some(cancel.clone());
+
+        self.runtime.spawn(async move {
+            if auto_search_on_process {
+                let mut descriptions = BTreeMap::new();
+                for file in &plan.files {
+                    if shutdown.is_cancelled() || cancel.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    if let Some(description) =
+                        auto_search_description(file, auto_search_only_if_sku).await
+                    {
+                        descriptions.insert(file.clone(), description);
+                    }
                 }
+                config.settings.input_descriptions = Arc::new(descriptions);
             }
+            let batch = tokio::task::spawn_blocking(move || {
+                run_batch(&APP_HOME, &config, &plan, failed, &sender);
+            })
+            .await;
+            if let Err(e) = batch {
+                error!("Process All task panicked: {}", e);
+            }
+        });
+    }
 
-            let processed = processed_supervisor.load(Ordering::SeqCst);
-            let error_count = error_count_supervisor.load(Ordering::SeqCst);
-            let errors = errors_supervisor.lock().unwrap().clone();
+    /// Tile the outputs written so far into `contact-sheet.png` in the first input's output
+    /// folder (runs in background)
+    pub fn write_contact_sheet(&mut self) {
+        self.update_rename_preview();
+        let mut sheet_dir = None;
+        let mut outputs = Vec::new();
+        for (file, renamed) in self.image_files.iter().zip(&self.renamed_files) {
+            let Some(root) = self.input_paths.iter().find(|r| file.starts_with(r)) else {
+                continue;
+            };
+            let name = renamed
+                .file_name()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let output_root = self.output_root.as_deref();
+            if let Some(output) = image_processing::get_output_path(file, root, &name, output_root)
+                && output.is_file()
+            {
+                sheet_dir.get_or_insert_with(|| image_processing::get_output_dir(root, output_root));
+                outputs.push(output);
+            }
+        }
+        let Some(sheet_dir) = sheet_dir else {
+            warn!("No processed outputs yet, run Process All first");
+            return;
+        };
 
-            let _ = sender_supervisor.send(BackgroundMessage::ProcessAllComplete {
-                processed_count: processed,
-                error_count,
-                errors,
+        let sheet_path = sheet_dir.join("contact-sheet.png");
+        self.runtime.spawn_blocking(move || {
+            let sheet = image_processing::build_contact_sheet(
+                &outputs,
+                &image_processing::ContactSheetOptions::default(),
+            );
+            let saved = sheet.and_then(|sheet| {
+                sheet
+                    .save_with_format(&sheet_path, image::ImageFormat::Png)
+                    .map_err(eyre::Report::from)
             });
+            match saved {
+                Ok(()) => info!(
+                    "Wrote a contact sheet of {} outputs to {}",
+                    outputs.len(),
+                    sheet_path.display()
+                ),
+                Err(e) => error!(
+                    "Failed to write contact sheet {}: {}",
+                    sheet_path.display(),
+                    e
+                ),
+            }
         });
     }
 
-    /// Cancel any running Process All tasks
-    /// # Panics
-    /// Panics if the mutex for handles cannot be locked.
-    pub fn cancel_process_all(&mut self) {
-        if let Some(handles_arc) = self.process_all_handles.take() {
-            let mut handles = handles_arc.lock().unwrap();
-            for h in handles.drain(..) {
-                h.abort();
+    /// Re-run processing over the files that failed in the last batch
+    pub fn retry_failed(&mut self) {
+        let failed = self
+            .last_batch_errors
+            .iter()
+            .map(|f| f.path.clone())
+            .collect();
+        self.process_failed(failed);
+    }
+
+    /// Re-run the pipeline with the current settings over exactly `failed`.
+    /// Files no longer among the discovered image files are skipped.
+    pub fn process_failed(&mut self, failed: Vec<PathBuf>) {
+        let known: HashSet<&PathBuf> = self.image_files.iter().collect();
+        let mut retry = Vec::with_capacity(failed.len());
+        for path in failed {
+            if known.contains(&path) {
+                retry.push(path);
+            } else {
+                warn!("Not retrying {}: no longer an input image", path.display());
             }
         }
+        if retry.is_empty() {
+            return;
+        }
+        info!("Retrying {} failed files", retry.len());
+        self.process_paths(retry);
+    }
 
-        let processed = self.process_all_progress.map_or(0, |(c, _)| c);
-        let _ = self
-            .background_sender
-            .send(BackgroundMessage::ProcessAllComplete {
-                processed_count: processed,
-                error_count: 0,
-                errors: vec!["Cancelled by user".to_string()],
-            });
+    /// Cancel the running Process All: images not started yet are left out, and the batch
+    /// reports what it finished once the images in flight are done
+    pub fn cancel_process_all(&mut self) {
+        if let Some(cancel) = &self.process_all_cancel {
+            cancel.store(true, Ordering::SeqCst);
+            info!("Processing cancelled by user");
+        }
+    }
 
-        self.process_all_running = false;
-        self.process_all_progress = None;
+    /// Stop background work so the app can exit promptly: cancels `shutdown`, cancels a running
+    /// batch and drops queued thumbnail loads
+    /// # Panics
+    /// Panics if the thumbnail queue mutex is poisoned.
+    pub fn shutdown(&mut self) {
+        self.shutdown.cancel();
+        if self.process_all_running {
+            self.cancel_process_all();
+        }
+        self.thumbnail_queue
+            .lock()
+            .unwrap()
+            .reset(self.image_cache_generation, []);
+        self.images_loading.clear();
     }
 
-    #[expect(clippy::too_many_lines)]
     pub fn process_selected(&mut self) {
         if self.process_all_running {
             warn!("Processing already running, ignoring request");
@@ -939,99 +1861,45 @@ impl AppState {
             return;
         };
 
-        // Find input root
-        let Some(input_root) = self
-            .input_paths
-            .iter()
-            .find(|r| selected_input.starts_with(r))
-            .cloned()
-        else {
-            error!("Could not find input root for selected file");
-            return;
-        };
-
         self.update_rename_preview();
 
-        let base_settings = ProcessingSettings {
-            crop_to_content: self.crop_to_content,
-            crop_threshold: self.crop_threshold,
-            binarization_mode: self.binarization_mode,
-            box_thickness: self.box_thickness,
-            jpeg_quality: self.jpeg_quality,
-            description: None,
+        // A batch of one, so the output gets the same path, extension and conflict handling as
+        // in Process All
+        let plan = PipelinePlan {
+            roots: self.input_paths.clone(),
+            files: vec![selected_input.clone()],
+            renamed: vec![renamed_file],
+            total: 1,
+        };
+        let mut config = PipelineConfig {
+            image_extensions: self.image_extensions.clone(),
+            settings: self.processing_settings(),
+            overrides: self.input_overrides.clone(),
+            ..PipelineConfig::new(self.input_paths.clone())
         };
 
         let sender = self.background_sender.clone();
-        let auto_search_on_process = self.auto_search_on_process;
+        let auto_search_on_process =
+            self.auto_search_on_process && self.manual_description.is_none();
         let auto_search_only_if_sku = self.auto_search_only_if_sku;
 
         self.process_all_running = true;
         self.process_all_progress = Some((0, 1));
 
-        tokio::spawn(async move {
-            // Build settings with optional auto-search description
-            let mut settings = base_settings.clone();
-            if auto_search_on_process {
-                // Get the filename for search suggestion
-                if let Some(filename) = selected_input.file_name().and_then(|s| s.to_str()) {
-                    use crate::gui::tiles::suggest_search;
-                    let suggestion = suggest_search(filename);
-
-                    // Check if we should perform the search
-                    let should_search = if auto_search_only_if_sku {
-                        suggestion.sku.is_some()
-                    } else {
-                        true
-                    };
-
-                    if should_search {
-                        // Perform the search (mutex is inside search())
-                        if let Ok(result) = suggestion.search().await
-                            && let Some(results) = &result.results
-                        {
-                            // Build description from search results
-                            let mut description_parts: Vec<String> = Vec::new();
-                            for item in results {
-                                let name = item.name.as_deref().unwrap_or("");
-                                let price = item.price.as_ref().map_or("", |p| p.0.as_str());
-                                if !name.is_empty() || !price.is_empty() {
-                                    description_parts.push(format!("{name} ${price}"));
-                                }
-                            }
-                            if !description_parts.is_empty() {
-                                settings.description = Some(description_parts.join("\n"));
-                            }
-                        }
-                    }
-                }
+        self.runtime.spawn(async move {
+            if auto_search_on_process
+                && let Some(description) =
+                    auto_search_description(&selected_input, auto_search_only_if_sku).await
+            {
+                config.settings.description = Some(description);
             }
 
             let result = tokio::task::spawn_blocking(move || -> eyre::Result<()> {
-                // Get the renamed filename
-                let renamed_name = renamed_file
-                    .file_name()
-                    .map(|s| s.to_string_lossy().to_string())
-                    .unwrap_or_default();
-
-                // Calculate output path
-                let Some(output_path) =
-                    image_processing::get_output_path(&selected_input, &input_root, &renamed_name)
-                else {
-                    return Err(eyre::eyre!("Could not calculate output path"));
-                };
-
-                // Create output directory if needed
-                if let Some(parent) = output_path.parent() {
-                    std::fs::create_dir_all(parent)?;
+                let result = pipeline::execute(&config, &plan, None, None)?;
+                match result.errors.into_iter().next() {
+                    Some((_, error)) => Err(eyre::eyre!("{error}")),
+                    None => Ok(()),
                 }
-
-                // Process the image
-                let processed = image_processing::process_image(&selected_input, &settings)?;
-
-                // Write output file
-                std::fs::write(&output_path, &processed.data)?;
-
-                Ok(())
             })
             .await;
 
@@ -1080,13 +1948,22 @@ impl AppState {
                     self.image_files_loading = LoadingState::Loaded;
                     // Now start loading image metadata in background
                     self.start_image_cache_loading();
+                    self.start_exif_prefetch();
                 }
                 BackgroundMessage::ImageFilesError { error } => {
                     self.image_files_loading = LoadingState::Failed(error.clone());
                     error!("Failed to list files: {}", error);
                     self.image_files.clear();
                 }
+                BackgroundMessage::EmptyFoldersFound { folders } => {
+                    for folder in &folders {
+                        warn!("No images found in {}", folder.display());
+                    }
+                    self.empty_input_folders = folders;
+                }
                 BackgroundMessage::OutputInfoReady { input_path, info } => {
+                    self.set_no_crop_detected(&input_path, info.no_crop_detected);
+                    self.set_integrity_warning(&input_path, info.integrity_warning.clone());
                     // Only update if this is still the selected file
                     if self.selected_input_file.as_ref() == Some(&input_path) {
                         self.selected_output_info = Some(info);
@@ -1107,18 +1984,27 @@ impl AppState {
                     processed_count,
                     error_count,
                     errors,
+                    timing,
+                    conversion,
                 } => {
-                    // Clear handles if any
-                    self.process_all_handles = None;
+                    self.process_all_cancel = None;
                     self.process_all_running = false;
                     self.process_all_progress = None;
                     info!(
                         "Processing complete: {} files processed, {} errors",
                         processed_count, error_count
                     );
+                    for failed in &errors {
+                        error!("{}: {}", failed.path.display(), failed.error);
+                    }
                     if !errors.is_empty() {
-                        error!("Processing errors: {:?}", errors);
+                        self.processing_results_open = true;
                     }
+                    self.last_batch_errors = errors;
+                    self.last_batch_timing = (!timing.is_empty()).then_some(timing);
+                    self.last_batch_conversion =
+                        (!conversion.files.is_empty()).then_some(conversion);
+                    self.resume_batch_available = batch_checkpoint::exists(&APP_HOME);
                 }
                 BackgroundMessage::ProcessAllProgress {
                     current,
@@ -1127,29 +2013,49 @@ impl AppState {
                 } => {
                     self.process_all_progress = Some((current, total));
                 }
-                BackgroundMessage::ImageCacheReady { path, info } => {
-                    self.images_loading.remove(&path);
-                    self.image_cache.insert(path, info);
+                BackgroundMessage::ImageCacheReady {
+                    path,
+                    info,
+                    generation,
+                } => self.on_image_cache_result(path, generation, Some(info)),
+                BackgroundMessage::ImageCacheError { path, generation } => {
+                    self.on_image_cache_result(path, generation, None);
+                }
+                BackgroundMessage::ExifReady {
+                    path,
+                    summary,
+                    generation,
+                } => {
+                    if generation == self.image_cache_generation {
+                        self.exif_summaries.insert(path, summary);
+                    }
                 }
-                BackgroundMessage::ImageCacheError { path } => {
-                    self.images_loading.remove(&path);
+                BackgroundMessage::CropChecked {
+                    path,
+                    no_crop_detected,
+                    integrity_warning,
+                } => {
+                    self.set_no_crop_detected(&path, no_crop_detected);
+                    self.set_integrity_warning(&path, integrity_warning);
                 }
                 BackgroundMessage::ProductSearchResult {
+                    query,
                     result,
                     pretty,
                     error,
                     received_at,
-                } => {
-                    // Record when we got the response so UI can show it
-                    self.product_search_last_response = Some(received_at);
-
-                    if let Some(err) = error {
-                        error!("Product search failed: {}", err);
-                        self.product_search_result_raw = None;
-                        self.product_search_result_pretty.clear();
-                    } else {
-                        self.product_search_result_raw = result;
-                        self.product_search_result_pretty = pretty.unwrap_or_default();
+                } => self.on_product_search_result(query, result, pretty, error, received_at),
+                BackgroundMessage::ProductThumbnailLoaded { url, result } => {
+                    self.on_product_thumbnail_loaded(url, result);
+                }
+                BackgroundMessage::BatchSizeEstimateReady { result } => {
+                    self.batch_size_estimating = false;
+                    match result {
+                        Ok(estimate) => self.batch_size_estimate = Some(estimate),
+                        Err(e) => {
+                            self.batch_size_estimate = None;
+                            error!("Failed to estimate output size: {}", e);
+                        }
                     }
                 }
                 BackgroundMessage::ProcessSelectedComplete { success, error } => {
@@ -1169,100 +2075,440 @@ impl AppState {
     }
 }
 
-/// Check if a path is an image file
-#[must_use]
-pub fn is_image_file(path: &std::path::Path) -> bool {
-    if let Some(ext) = path.extension().and_then(|s| s.to_str()) {
-        matches!(
-            ext.to_ascii_lowercase().as_str(),
-            "png" | "jpg" | "jpeg" | "gif" | "bmp" | "webp" | "tiff"
-        )
-    } else {
-        false
+/// Description for `input` built from a product search on its file name, one `name $price`
+/// line per result. With `only_if_sku`, files whose name has no SKU aren't searched.
+async fn auto_search_description(input: &Path, only_if_sku: bool) -> Option<String> {
+    use crate::gui::tiles::suggest_search;
+    let filename = input.file_name()?.to_str()?;
+    let suggestion = suggest_search(filename);
+    if only_if_sku && suggestion.sku.is_empty() {
+        return None;
     }
+    // The search mutex is inside search()
+    let result = suggestion.search().await.ok()?;
+    let description_parts: Vec<String> = result
+        .results?
+        .iter()
+        .filter_map(|item| {
+            let name = item.name.as_deref().unwrap_or("");
+            let price = item.price.as_ref().map_or("", |p| p.0.as_str());
+            (!name.is_empty() || !price.is_empty()).then(|| format!("{name} ${price}"))
+        })
+        .collect();
+    (!description_parts.is_empty()).then(|| description_parts.join("\n"))
 }
 
-/// Hyphenate camelCase strings by inserting '-' before uppercase letters that follow lowercase
-fn hyphenate_name(name: &str) -> String {
-    let mut result = String::new();
-    let chars: Vec<char> = name.chars().collect();
-    for (i, &c) in chars.iter().enumerate() {
-        if i > 0 && c.is_uppercase() && chars[i - 1].is_lowercase() {
-            result.push('-');
+/// Run `plan` through [`pipeline::execute`] on the current thread, sending its progress, crop
+/// checks and final totals to `sender` and recording finished inputs in the batch checkpoint
+/// of `home`. `failed` are inputs that were left out of the plan, counted as errors.
+fn run_batch(
+    home: &AppHome,
+    config: &PipelineConfig,
+    plan: &PipelinePlan,
+    mut failed: Vec<FailedImage>,
+    sender: &UnboundedSender<BackgroundMessage>,
+) {
+    let total = plan.total;
+    let mut current = failed.len();
+    let mut timing = TimingSummary::default();
+    let mut conversion = Vec::new();
+    let (events, received) = std::sync::mpsc::channel();
+    let batch = std::thread::scope(|scope| {
+        let batch = scope.spawn(move || pipeline::execute(config, plan, None, Some(&events)));
+        for event in received {
+            let (path, result) = match event {
+                ProcessProgress::Item { path, result, .. } => (path, result),
+                ProcessProgress::Finished { result } => {
+                    timing = result.timing;
+                    continue;
+                }
+                ProcessProgress::Started { .. } => continue,
+            };
+            // Inputs a cancel left out don't count towards the batch
+            if matches!(result, ProcessItemResult::Failed(ProcessError::Cancelled)) {
+                continue;
+            }
+            current += 1;
+            match result {
+                ProcessItemResult::Failed(error) => failed.push(FailedImage {
+                    path: path.clone(),
+                    error,
+                }),
+                result => {
+                    if let Err(e) = batch_checkpoint::record_completed(home, &path) {
+                        warn!("Failed to update batch checkpoint: {}", e);
+                    }
+                    if let ProcessItemResult::Processed(entry) = result {
+                        if let Some(converted) = ConversionEntry::from_input(
+                            &path,
+                            Path::new(&entry.output),
+                            entry.estimated_size,
+                        ) {
+                            conversion.push(converted);
+                        }
+                        let _ = sender.send(BackgroundMessage::CropChecked {
+                            path: path.clone(),
+                            no_crop_detected: entry.no_crop_detected,
+                            integrity_warning: entry.warning,
+                        });
+                        info!(
+                            "Processed image {}, {} remain",
+                            path.display(),
+                            total.saturating_sub(current)
+                        );
+                    }
+                }
+            }
+            let _ = sender.send(BackgroundMessage::ProcessAllProgress {
+                current,
+                total,
+                current_file: path,
+            });
         }
-        result.push(c);
+        batch.join()
+    });
+    match batch {
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) => error!("Process All failed: {}", e),
+        Err(_) => error!("Process All panicked"),
+    }
+
+    let error_count = failed.len();
+    let conversion = ConversionReport::new(conversion);
+    // A cancelled or refused batch stops short of `total`; keep its checkpoint so it can resume
+    if error_count == 0
+        && current == total
+        && let Err(e) = batch_checkpoint::clear(home)
+    {
+        warn!("Failed to clear batch checkpoint: {}", e);
     }
-    result
+    let last_batch = LastBatch {
+        processed: current.saturating_sub(error_count),
+        errors: error_count,
+        output_bytes: conversion.total_output_bytes,
+    };
+    if let Err(e) = save_last_batch(home, &last_batch) {
+        warn!("Failed to save last batch summary: {}", e);
+    }
+
+    let _ = sender.send(BackgroundMessage::ProcessAllComplete {
+        processed_count: current,
+        error_count,
+        errors: failed,
+        timing,
+        conversion,
+    });
+}
+
+/// Trimmed text as an optional metadata value (`None` when blank)
+fn non_empty(text: &str) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
 }
 
-/// Apply rename rules sequentially to file base names
-fn apply_rules_seq(
-    files: &[PathBuf],
-    rules: &[RenameRule],
-    max_name_length: usize,
-    global_enabled: bool,
-    hyphenate: bool,
-) -> Vec<PathBuf> {
-    if !global_enabled {
-        return files.iter().cloned().collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info() -> CachedImageInfo {
+        CachedImageInfo {
+            width: 1,
+            height: 1,
+            file_size: 1,
+            modified: None,
+            thumbnail_data: vec![0; 4],
+        }
     }
 
-    // Precompile regexes once per rule
-    let compiled: Vec<Option<regex::Regex>> = rules
-        .iter()
-        .map(|r| {
-            let mut builder = regex::RegexBuilder::new(&r.find);
-            if !r.case_sensitive {
-                builder.case_insensitive(true);
-            }
-            builder.build().ok()
-        })
-        .collect();
+    #[test]
+    fn rename_preview_refreshes_when_files_change_but_count_does_not() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let mut state = AppState::new(rt.handle().clone());
+        state.image_files = vec![PathBuf::from("in/a.png"), PathBuf::from("in/b.png")];
+        let key = state.compute_rename_preview_key();
+        assert_eq!(key, state.compute_rename_preview_key());
+        state.update_rename_preview();
+        assert_eq!(state.renamed_files[1].file_name().unwrap(), "b.png");
+
+        state.image_files[1] = PathBuf::from("in/c.png");
+        assert_ne!(key, state.compute_rename_preview_key());
+        state.update_rename_preview();
+        assert_eq!(state.renamed_files[1].file_name().unwrap(), "c.png");
+    }
 
-    files
-        .iter()
-        .map(|path| {
-            let original = path
-                .file_name()
-                .map(|s| s.to_string_lossy().to_string())
-                .unwrap_or_default();
+    #[test]
+    fn draft_rule_preview_layers_over_saved_rules_without_replacing_them() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let mut state = AppState::new(rt.handle().clone());
+        state.image_files = vec![PathBuf::from("in/IMG_a.png"), PathBuf::from("in/b.png")];
+        state.rename_rules = vec![RenameRule {
+            find: "IMG_".to_owned(),
+            replace: String::new(),
+            only_when_name_too_long: false,
+            ..RenameRule::default()
+        }];
+        state.draft_rule.find = "a".to_owned();
+        state.draft_rule.replace = "z".to_owned();
+        state.update_rename_preview();
+        assert!(state.draft_rename_previews.is_empty());
+
+        state.draft_rule_preview = true;
+        state.update_rename_preview();
+        assert_eq!(state.renamed_files[0], PathBuf::from("in/a.png"));
+        assert_eq!(
+            state.draft_rename_previews[0].renamed,
+            PathBuf::from("in/z.png")
+        );
+        assert_eq!(
+            state.draft_rename_previews[0].applied_rules,
+            vec![state.rename_rules[0].id, state.draft_rule.id]
+        );
+        assert!(!state.draft_rename_previews[1].changed);
+
+        // Editing the draft refreshes only the draft preview
+        state.draft_rule.replace = "y".to_owned();
+        state.update_rename_preview();
+        assert_eq!(
+            state.draft_rename_previews[0].renamed,
+            PathBuf::from("in/y.png")
+        );
+        assert_eq!(state.renamed_files[0], PathBuf::from("in/a.png"));
+    }
 
-            let mut cur = original.clone();
-            for (i, rule) in rules.iter().enumerate() {
-                // Skip disabled rules
-                if !rule.enabled {
-                    continue;
-                }
+    #[test]
+    fn image_cache_results_from_previous_generation_are_dropped() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let mut state = AppState::new(rt.handle().clone());
+        let old_ready = PathBuf::from("old/a.png");
+        let old_failed = PathBuf::from("old/b.png");
+        let current = PathBuf::from("new/a.png");
+        state.image_cache_generation = 1;
+        state.images_loading.insert(current.clone());
+
+        let send = |msg| state.background_sender.send(msg).unwrap();
+        send(BackgroundMessage::ImageCacheReady {
+            path: old_ready.clone(),
+            info: info(),
+            generation: 0,
+        });
+        send(BackgroundMessage::ImageCacheError {
+            path: old_failed,
+            generation: 0,
+        });
+        send(BackgroundMessage::ImageCacheReady {
+            path: current.clone(),
+            info: info(),
+            generation: 1,
+        });
+        state.poll_background_tasks();
 
-                // Check if rule only applies when name is too long
-                if rule.only_when_name_too_long && cur.len() <= max_name_length {
-                    continue;
-                }
+        assert!(!state.image_cache.contains_key(&old_ready));
+        assert!(state.image_load_failed.is_empty());
+        assert!(state.image_cache.contains_key(&current));
+        assert!(state.images_loading.is_empty());
+    }
 
-                if let Some(re) = &compiled[i] {
-                    let replaced = re.replace_all(&cur, &rule.replace).to_string();
-                    if replaced != cur {
-                        cur = replaced;
-                    }
-                }
-            }
+    #[test]
+    fn exif_prefetch_fills_the_summary_cache() -> eyre::Result<()> {
+        let td = tempfile::tempdir()?;
+        let path = td.path().join("scan.tif");
+        let mut writer = exif::experimental::Writer::new();
+        let field = exif::Field {
+            tag: exif::Tag::DateTimeOriginal,
+            ifd_num: exif::In::PRIMARY,
+            value: exif::Value::Ascii(vec![b"2024:01:02 03:04:05".to_vec()]),
+        };
+        writer.push_field(&field);
+        let mut tiff = std::io::Cursor::new(Vec::new());
+        writer
+            .write(&mut tiff, true)
+            .map_err(|e| eyre::eyre!("write tiff: {}", e))?;
+        std::fs::write(&path, tiff.into_inner())?;
+        let missing = td.path().join("missing.jpg");
+
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let mut state = AppState::new(rt.handle().clone());
+        state.image_files = vec![path.clone(), missing.clone()];
+        state.start_exif_prefetch();
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(10);
+        while state.exif_summaries.len() < 2 && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(10));
+            state.poll_background_tasks();
+        }
 
-            if hyphenate {
-                // Hyphenate the base name, preserving extension
-                if let Some(dot_pos) = cur.rfind('.') {
-                    let base = &cur[..dot_pos];
-                    let ext = &cur[dot_pos..];
-                    cur = format!("{}{}", hyphenate_name(base), ext);
-                } else {
-                    cur = hyphenate_name(&cur);
-                }
-            }
+        let summary = state.exif_summaries[&path].as_ref().unwrap();
+        assert_eq!(
+            summary.snippet(),
+            vec![("Taken", "2024-01-02 03:04:05".to_owned())]
+        );
+        assert!(state.exif_summaries[&missing].is_err());
+
+        // Results of a prefetch started before the inputs were reloaded are dropped
+        let stale = state.image_cache_generation;
+        state.image_cache_generation += 1;
+        state.exif_summaries.clear();
+        state
+            .background_sender
+            .send(BackgroundMessage::ExifReady {
+                path: path.clone(),
+                summary: Ok(ExifSummary::default()),
+                generation: stale,
+            })
+            .unwrap();
+        state.poll_background_tasks();
+        assert!(!state.exif_summaries.contains_key(&path));
+        Ok(())
+    }
 
-            if let Some(parent) = path.parent() {
-                parent.join(cur)
-            } else {
-                PathBuf::from(cur)
-            }
-        })
-        .collect()
+    #[test]
+    fn background_tasks_spawn_without_an_entered_runtime() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let state = AppState::new(rt.handle().clone());
+        // The test thread never enters `rt`, so an ambient `tokio::spawn` would panic here
+        assert!(tokio::runtime::Handle::try_current().is_err());
+        let task = state.runtime.spawn(async { 1 + 1 });
+        assert_eq!(rt.block_on(task).unwrap(), 2);
+    }
+
+    #[test]
+    fn shutdown_cancels_tasks_watching_the_token() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let mut state = AppState::new(rt.handle().clone());
+        let token = state.shutdown.clone();
+        let task = state.runtime.spawn(async move { token.cancelled().await });
+        let batch = Arc::new(AtomicBool::new(false));
+        state.process_all_running = true;
+        state.process_all_cancel = Some(batch.clone());
+
+        state.shutdown();
+
+        rt.block_on(task).unwrap();
+        assert!(state.shutdown.is_cancelled());
+        assert!(batch.load(Ordering::SeqCst));
+    }
+
+    /// Run `plan` like Process All does, returning the processed count it reports
+    fn run_test_batch(home: &AppHome, config: &PipelineConfig, plan: &PipelinePlan) -> usize {
+        let (sender, mut received) = mpsc::unbounded_channel();
+        run_batch(home, config, plan, Vec::new(), &sender);
+        std::iter::from_fn(|| received.try_recv().ok())
+            .find_map(|message| match message {
+                BackgroundMessage::ProcessAllComplete {
+                    processed_count, ..
+                } => Some(processed_count),
+                _ => None,
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn batches_refuse_inputs_renamed_onto_the_same_output() -> eyre::Result<()> {
+        let td = tempfile::tempdir()?;
+        let home = AppHome(td.path().join("home"));
+        let root = td.path().join("photos");
+        std::fs::create_dir_all(&root)?;
+        let files: Vec<PathBuf> = ["a.png", "b.png"].iter().map(|n| root.join(n)).collect();
+        for file in &files {
+            image::RgbaImage::from_pixel(4, 4, image::Rgba([200, 30, 30, 255])).save(file)?;
+        }
+        let plan = PipelinePlan {
+            roots: vec![root.clone()],
+            files: files.clone(),
+            renamed: vec![root.join("same.png"); 2],
+            total: 2,
+        };
+        let config = PipelineConfig::new(vec![root.clone()]);
+        assert_eq!(config.settings.on_conflict, OutputConflictPolicy::Overwrite);
+
+        assert_eq!(run_test_batch(&home, &config, &plan), 0);
+        let output = get_output_path(&files[0], &root, "same.png", None).unwrap();
+        assert!(!output.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn batches_write_the_extension_matching_the_encoded_bytes() -> eyre::Result<()> {
+        let td = tempfile::tempdir()?;
+        let home = AppHome(td.path().join("home"));
+        let root = td.path().join("photos");
+        std::fs::create_dir_all(&root)?;
+        let files = vec![root.join("a.jpg"), root.join("b.png")];
+        image::RgbImage::from_pixel(4, 4, image::Rgb([200, 30, 30])).save(&files[0])?;
+        image::RgbaImage::from_pixel(4, 4, image::Rgba([30, 200, 30, 255])).save(&files[1])?;
+        // As rename rules that turn the names into `.gif` and `.txt` would leave them
+        let plan = PipelinePlan {
+            roots: vec![root.clone()],
+            files,
+            renamed: vec![root.join("a.gif"), root.join("b.txt")],
+            total: 2,
+        };
+        let config = PipelineConfig::new(vec![root.clone()]);
+
+        assert_eq!(run_test_batch(&home, &config, &plan), 2);
+        let output_dir = image_processing::get_output_dir(&root, None);
+        let outputs: Vec<PathBuf> = std::fs::read_dir(&output_dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<std::io::Result<_>>()?;
+        assert_eq!(outputs.len(), 2);
+        for output in &outputs {
+            let written = image::guess_format(&std::fs::read(output)?)?;
+            assert_eq!(image::ImageFormat::from_path(output)?, written, "{}", output.display());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn resuming_under_rename_skips_inputs_listed_in_the_checkpoint() -> eyre::Result<()> {
+        let td = tempfile::tempdir()?;
+        let home = AppHome(td.path().join("home"));
+        let root = td.path().join("photos");
+        let files: Vec<PathBuf> = ["a.png", "b.png", "c.png"]
+            .iter()
+            .map(|n| root.join(n))
+            .collect();
+
+        // An earlier batch left b.png behind, so the interrupted one renamed b's new output
+        let output = get_output_path(&files[1], &root, "b.png", None).unwrap();
+        std::fs::create_dir_all(output.parent().unwrap())?;
+        std::fs::write(&output, b"earlier")?;
+        let renamed = image_processing::resolve_output_conflict(
+            &output,
+            OutputConflictPolicy::Rename,
+            |_| false,
+        )
+        .unwrap();
+        assert_ne!(renamed, output);
+        std::fs::write(&renamed, b"interrupted batch")?;
+
+        batch_checkpoint::start(&home)?;
+        batch_checkpoint::record_completed(&home, &files[1])?;
+
+        let completed = batch_checkpoint::completed_inputs(&home)?;
+        let pending = AppState::skip_completed(files.clone(), &completed);
+        assert_eq!(pending, vec![files[0].clone(), files[2].clone()]);
+        Ok(())
+    }
+
+    #[test]
+    fn failed_searches_are_told_apart_from_empty_results() {
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        let mut state = AppState::new(rt.handle().clone());
+        state.on_product_search_result(
+            None,
+            None,
+            None,
+            Some("connection refused".to_owned()),
+            Local::now(),
+        );
+        assert_eq!(
+            state.product_search_error.as_deref(),
+            Some("connection refused")
+        );
+        assert!(state.product_search_result_raw.is_none());
+
+        let empty = facet_json::from_str::<SearchResultOk>(r#"{ "results": [] }"#).unwrap();
+        state.on_product_search_result(None, Some(empty), None, None, Local::now());
+        assert_eq!(state.product_search_error, None);
+        assert!(state.product_search_result_raw.is_some());
+    }
 }
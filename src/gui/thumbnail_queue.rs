@@ -0,0 +1,177 @@
+//! Queue of images waiting for their metadata and thumbnail to load, with paths that are on
+//! screen served before the rest
+
+use std::collections::HashSet;
+use std::collections::VecDeque;
+use std::path::Path;
+use std::path::PathBuf;
+
+/// Most visible requests remembered; older ones fall back to their place in the background order
+const MAX_VISIBLE: usize = 512;
+
+/// Images waiting to load plus the number of loader tasks draining them.
+///
+/// Visible paths are loaded by up to `limit` workers at once. Once only background paths remain,
+/// extra workers retire so the rest of the library trickles in on a single worker.
+#[derive(Debug, Default)]
+pub struct ThumbnailQueue {
+    /// Paths that have not started loading yet
+    pending: HashSet<PathBuf>,
+    /// Paths recently drawn on screen, most recent first
+    visible: VecDeque<PathBuf>,
+    /// Every queued path, in the order it was queued
+    background: VecDeque<PathBuf>,
+    /// Loader tasks currently draining the queue
+    workers: usize,
+    /// Generation the queued paths belong to, handed out with each path
+    generation: u64,
+}
+
+impl ThumbnailQueue {
+    /// Replace the queue contents with `paths` of the given generation, in background order
+    pub fn reset(&mut self, generation: u64, paths: impl IntoIterator<Item = PathBuf>) {
+        self.generation = generation;
+        self.pending.clear();
+        self.visible.clear();
+        self.background.clear();
+        for path in paths {
+            if self.pending.insert(path.clone()) {
+                self.background.push_back(path);
+            }
+        }
+    }
+
+    /// Move a pending path to the front of the line. Returns false if it isn't waiting to load.
+    pub fn prioritize(&mut self, path: &Path) -> bool {
+        if !self.pending.contains(path) {
+            return false;
+        }
+        if self.visible.front().is_some_and(|p| p == path) {
+            return true;
+        }
+        self.visible.retain(|p| p != path);
+        self.visible.push_front(path.to_path_buf());
+        self.visible.truncate(MAX_VISIBLE);
+        true
+    }
+
+    /// Queue a path that isn't already waiting (e.g. one evicted from the cache) and move it to
+    /// the front of the line
+    pub fn request(&mut self, path: &Path) {
+        if self.pending.insert(path.to_path_buf()) {
+            self.background.push_back(path.to_path_buf());
+        }
+        self.prioritize(path);
+    }
+
+    /// Whether `path` is waiting to load and hasn't been picked up by a worker yet
+    #[must_use]
+    pub fn is_pending(&self, path: &Path) -> bool {
+        self.pending.contains(path)
+    }
+
+    /// Whether any path is still waiting to load
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Number of loader tasks currently running
+    #[must_use]
+    pub fn workers(&self) -> usize {
+        self.workers
+    }
+
+    /// Register a new worker if there is work for it: up to `limit` while visible paths are
+    /// waiting, otherwise just one for the background drain
+    pub fn try_add_worker(&mut self, limit: usize) -> bool {
+        let wanted = if self.has_visible() { limit.max(1) } else { 1 };
+        if self.is_empty() || self.workers >= wanted {
+            return false;
+        }
+        self.workers += 1;
+        true
+    }
+
+    /// Next path for a worker to load, with its generation. When `None` is returned the worker
+    /// has been retired and must stop.
+    pub fn next_or_retire(&mut self) -> Option<(PathBuf, u64)> {
+        while let Some(path) = self.visible.pop_front() {
+            if self.pending.remove(&path) {
+                return Some((path, self.generation));
+            }
+        }
+        if self.workers <= 1 {
+            while let Some(path) = self.background.pop_front() {
+                if self.pending.remove(&path) {
+                    return Some((path, self.generation));
+                }
+            }
+        }
+        self.workers = self.workers.saturating_sub(1);
+        None
+    }
+
+    fn has_visible(&self) -> bool {
+        self.visible.iter().any(|p| self.pending.contains(p))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn paths(names: &[&str]) -> Vec<PathBuf> {
+        names.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn visible_paths_load_first() {
+        let mut queue = ThumbnailQueue::default();
+        queue.reset(0, paths(&["a", "b", "c", "d"]));
+        assert!(queue.prioritize(Path::new("c")));
+        assert!(queue.prioritize(Path::new("d")));
+        assert!(!queue.prioritize(Path::new("missing")));
+
+        assert!(queue.try_add_worker(4));
+        let order: Vec<PathBuf> = std::iter::from_fn(|| queue.next_or_retire())
+            .map(|(path, _)| path)
+            .collect();
+        assert_eq!(order, paths(&["d", "c", "a", "b"]));
+        assert!(queue.is_empty());
+        assert_eq!(queue.workers(), 0);
+
+        // Loaded paths can't be prioritized again, but can be requested again
+        assert!(!queue.prioritize(Path::new("a")));
+        queue.request(Path::new("a"));
+        assert!(queue.try_add_worker(4));
+        assert_eq!(queue.next_or_retire(), Some((PathBuf::from("a"), 0)));
+        assert_eq!(queue.next_or_retire(), None);
+    }
+
+    #[test]
+    fn worker_limit_is_honored() {
+        let mut queue = ThumbnailQueue::default();
+        queue.reset(0, paths(&["a", "b", "c", "d", "e"]));
+
+        // Background work only gets a single worker
+        assert!(queue.try_add_worker(3));
+        assert!(!queue.try_add_worker(3));
+
+        // Visible work scales up to the limit
+        queue.prioritize(Path::new("d"));
+        queue.prioritize(Path::new("e"));
+        assert!(queue.try_add_worker(3));
+        assert!(queue.try_add_worker(3));
+        assert!(!queue.try_add_worker(3));
+        assert_eq!(queue.workers(), 3);
+
+        // Extra workers retire once only background paths remain
+        assert_eq!(queue.next_or_retire(), Some((PathBuf::from("e"), 0)));
+        assert_eq!(queue.next_or_retire(), Some((PathBuf::from("d"), 0)));
+        assert_eq!(queue.next_or_retire(), None);
+        assert_eq!(queue.next_or_retire(), None);
+        assert_eq!(queue.workers(), 1);
+        assert_eq!(queue.next_or_retire(), Some((PathBuf::from("a"), 0)));
+    }
+}
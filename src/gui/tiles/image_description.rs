@@ -1,220 +1,139 @@
 //! Image description tile - shows EXIF metadata of the selected image
 
 use crate::gui::state::AppState;
+use crate::image_processing;
+use crate::image_processing::ExifFields;
+use crate::image_processing::ExifSummary;
 use eframe::egui::ScrollArea;
 use eframe::egui::{self};
-use exif::In;
-use exif::Tag;
 use std::collections::BTreeMap;
-use std::fs::File;
-use std::io::BufReader;
 use std::path::Path;
-
-/// EXIF data organized by IFD (Image File Directory)
-#[derive(Debug, Default)]
-struct ExifData {
-    /// Primary image data (IFD0)
-    primary: BTreeMap<String, String>,
-    /// EXIF-specific data
-    exif: BTreeMap<String, String>,
-    /// GPS data
-    gps: BTreeMap<String, String>,
-    /// Thumbnail data (IFD1)
-    thumbnail: BTreeMap<String, String>,
-    /// Interoperability data
-    interop: BTreeMap<String, String>,
-    /// Maker notes (often proprietary)
-    maker_notes: BTreeMap<String, String>,
+use std::path::PathBuf;
+use tracing::error;
+use tracing::info;
+
+/// Path of the copy written when "write to a copy" is chosen (`name-described.ext`)
+fn described_copy_path(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let name = match path.extension() {
+        Some(ext) => format!("{stem}-described.{}", ext.to_string_lossy()),
+        None => format!("{stem}-described"),
+    };
+    path.with_file_name(name)
 }
 
-impl ExifData {
-    fn is_empty(&self) -> bool {
-        self.primary.is_empty()
-            && self.exif.is_empty()
-            && self.gps.is_empty()
-            && self.thumbnail.is_empty()
-            && self.interop.is_empty()
-            && self.maker_notes.is_empty()
+/// Editor for the EXIF description, artist, copyright and keywords with write-back to the
+/// file or a copy
+fn draw_description_editor(ui: &mut egui::Ui, state: &mut AppState, selected_path: &Path) {
+    if state.description_edit_path.as_deref() != Some(selected_path) {
+        let fields = image_processing::read_exif_fields(selected_path);
+        state.description_edit = fields.description.unwrap_or_default();
+        state.artist_edit = fields.artist.unwrap_or_default();
+        state.copyright_edit = fields.copyright.unwrap_or_default();
+        state.keywords_edit = fields.keywords.unwrap_or_default();
+        state.description_edit_path = Some(selected_path.to_path_buf());
+        state.description_confirm_overwrite = false;
     }
-}
-
-/// Read EXIF data from an image file
-fn read_exif_data(path: &Path) -> Result<ExifData, String> {
-    let file = File::open(path).map_err(|e| format!("Failed to open file: {e}"))?;
-    let mut bufreader = BufReader::new(file);
 
-    let exif_reader = exif::Reader::new();
-    let exif = exif_reader
-        .read_from_container(&mut bufreader)
-        .map_err(|e| format!("Failed to read EXIF: {e}"))?;
-
-    let mut data = ExifData::default();
-
-    for field in exif.fields() {
-        let tag_name = field.tag.to_string();
-        let value = field.display_value().with_unit(&exif).to_string();
+    ui.label("Description:");
+    ui.add(
+        egui::TextEdit::multiline(&mut state.description_edit)
+            .desired_rows(3)
+            .desired_width(f32::INFINITY),
+    );
+    egui::Grid::new("exif_editor_fields")
+        .num_columns(2)
+        .show(ui, |ui| {
+            ui.label("Artist:");
+            ui.text_edit_singleline(&mut state.artist_edit);
+            ui.end_row();
+            ui.label("Copyright:");
+            ui.text_edit_singleline(&mut state.copyright_edit);
+            ui.end_row();
+            ui.label("Keywords:");
+            ui.text_edit_singleline(&mut state.keywords_edit)
+                .on_hover_text("Separate keywords with semicolons");
+            ui.end_row();
+        });
 
-        // Categorize by IFD
-        match field.ifd_num {
-            In::PRIMARY => {
-                data.primary.insert(tag_name, value);
+    let mut write_to: Option<PathBuf> = None;
+    ui.horizontal(|ui| {
+        ui.checkbox(&mut state.description_write_to_copy, "Write to a copy")
+            .on_hover_text(format!(
+                "Save as {} instead of modifying the original",
+                described_copy_path(selected_path).display()
+            ));
+        if state.description_confirm_overwrite {
+            ui.colored_label(egui::Color32::YELLOW, "Overwrite the original file?");
+            if ui.button("Overwrite").clicked() {
+                write_to = Some(selected_path.to_path_buf());
+                state.description_confirm_overwrite = false;
             }
-            In::THUMBNAIL => {
-                data.thumbnail.insert(tag_name, value);
+            if ui.button("Cancel").clicked() {
+                state.description_confirm_overwrite = false;
             }
-            _ => {
-                // Check for specific tag categories
-                if is_gps_tag(field.tag) {
-                    data.gps.insert(tag_name, value);
-                } else if is_exif_tag(field.tag) {
-                    data.exif.insert(tag_name, value);
-                } else if is_interop_tag(field.tag) {
-                    data.interop.insert(tag_name, value);
-                } else if is_maker_note_tag(field.tag) {
-                    data.maker_notes.insert(tag_name, value);
-                } else {
-                    // Default to primary for unknown IFDs
-                    data.primary.insert(tag_name, value);
-                }
+        } else if ui.button("Write to file").clicked() {
+            if state.description_write_to_copy {
+                write_to = Some(described_copy_path(selected_path));
+            } else {
+                state.description_confirm_overwrite = true;
             }
         }
-    }
-
-    Ok(data)
-}
-
-/// Check if a tag is GPS-related
-fn is_gps_tag(tag: Tag) -> bool {
-    matches!(
-        tag,
-        Tag::GPSVersionID
-            | Tag::GPSLatitudeRef
-            | Tag::GPSLatitude
-            | Tag::GPSLongitudeRef
-            | Tag::GPSLongitude
-            | Tag::GPSAltitudeRef
-            | Tag::GPSAltitude
-            | Tag::GPSTimeStamp
-            | Tag::GPSSatellites
-            | Tag::GPSStatus
-            | Tag::GPSMeasureMode
-            | Tag::GPSDOP
-            | Tag::GPSSpeedRef
-            | Tag::GPSSpeed
-            | Tag::GPSTrackRef
-            | Tag::GPSTrack
-            | Tag::GPSImgDirectionRef
-            | Tag::GPSImgDirection
-            | Tag::GPSMapDatum
-            | Tag::GPSDestLatitudeRef
-            | Tag::GPSDestLatitude
-            | Tag::GPSDestLongitudeRef
-            | Tag::GPSDestLongitude
-            | Tag::GPSDestBearingRef
-            | Tag::GPSDestBearing
-            | Tag::GPSDestDistanceRef
-            | Tag::GPSDestDistance
-            | Tag::GPSProcessingMethod
-            | Tag::GPSAreaInformation
-            | Tag::GPSDateStamp
-            | Tag::GPSDifferential
-    )
-}
-
-/// Check if a tag is EXIF-specific
-fn is_exif_tag(tag: Tag) -> bool {
-    matches!(
-        tag,
-        Tag::ExposureTime
-            | Tag::FNumber
-            | Tag::ExposureProgram
-            | Tag::SpectralSensitivity
-            | Tag::PhotographicSensitivity
-            | Tag::SensitivityType
-            | Tag::StandardOutputSensitivity
-            | Tag::RecommendedExposureIndex
-            | Tag::ISOSpeed
-            | Tag::ISOSpeedLatitudeyyy
-            | Tag::ISOSpeedLatitudezzz
-            | Tag::ExifVersion
-            | Tag::DateTimeOriginal
-            | Tag::DateTimeDigitized
-            | Tag::OffsetTime
-            | Tag::OffsetTimeOriginal
-            | Tag::OffsetTimeDigitized
-            | Tag::ComponentsConfiguration
-            | Tag::CompressedBitsPerPixel
-            | Tag::ShutterSpeedValue
-            | Tag::ApertureValue
-            | Tag::BrightnessValue
-            | Tag::ExposureBiasValue
-            | Tag::MaxApertureValue
-            | Tag::SubjectDistance
-            | Tag::MeteringMode
-            | Tag::LightSource
-            | Tag::Flash
-            | Tag::FocalLength
-            | Tag::SubjectArea
-            | Tag::MakerNote
-            | Tag::UserComment
-            | Tag::SubSecTime
-            | Tag::SubSecTimeOriginal
-            | Tag::SubSecTimeDigitized
-            | Tag::FlashpixVersion
-            | Tag::ColorSpace
-            | Tag::PixelXDimension
-            | Tag::PixelYDimension
-            | Tag::RelatedSoundFile
-            | Tag::FlashEnergy
-            | Tag::FocalPlaneXResolution
-            | Tag::FocalPlaneYResolution
-            | Tag::FocalPlaneResolutionUnit
-            | Tag::SubjectLocation
-            | Tag::ExposureIndex
-            | Tag::SensingMethod
-            | Tag::FileSource
-            | Tag::SceneType
-            | Tag::CFAPattern
-            | Tag::CustomRendered
-            | Tag::ExposureMode
-            | Tag::WhiteBalance
-            | Tag::DigitalZoomRatio
-            | Tag::FocalLengthIn35mmFilm
-            | Tag::SceneCaptureType
-            | Tag::GainControl
-            | Tag::Contrast
-            | Tag::Saturation
-            | Tag::Sharpness
-            | Tag::DeviceSettingDescription
-            | Tag::SubjectDistanceRange
-            | Tag::ImageUniqueID
-            | Tag::CameraOwnerName
-            | Tag::BodySerialNumber
-            | Tag::LensSpecification
-            | Tag::LensMake
-            | Tag::LensModel
-            | Tag::LensSerialNumber
-            | Tag::Gamma
-    )
-}
+    });
 
-/// Check if a tag is interoperability-related
-fn is_interop_tag(tag: Tag) -> bool {
-    matches!(
-        tag,
-        Tag::InteroperabilityIndex | Tag::InteroperabilityVersion
-    )
+    if let Some(output) = write_to {
+        let fields = ExifFields {
+            description: Some(state.description_edit.clone()),
+            artist: Some(state.artist_edit.clone()),
+            copyright: Some(state.copyright_edit.clone()),
+            keywords: Some(state.keywords_edit.clone()),
+        };
+        match image_processing::write_exif_fields_to_file(selected_path, &output, &fields) {
+            Ok(()) => info!("Wrote EXIF metadata to {}", output.display()),
+            Err(e) => error!("Failed to write EXIF metadata: {}", e),
+        }
+    }
 }
 
-/// Check if a tag is a maker note
-fn is_maker_note_tag(tag: Tag) -> bool {
-    matches!(tag, Tag::MakerNote)
+/// Artist/copyright/keywords stamped onto every output of Process All / Process Selected
+fn draw_batch_metadata(ui: &mut egui::Ui, state: &mut AppState) {
+    egui::CollapsingHeader::new("Batch metadata")
+        .id_salt("exif_batch_metadata")
+        .show(ui, |ui| {
+            ui.label("Written to every processed output. Leave blank to skip a tag.");
+            egui::Grid::new("exif_batch_fields")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Artist:");
+                    ui.text_edit_singleline(&mut state.batch_artist);
+                    ui.end_row();
+                    ui.label("Copyright:");
+                    ui.text_edit_singleline(&mut state.batch_copyright);
+                    ui.end_row();
+                    ui.label("Keywords:");
+                    ui.text_edit_singleline(&mut state.batch_keywords)
+                        .on_hover_text("Separate keywords with semicolons");
+                    ui.end_row();
+                });
+            if ui
+                .button("Use values from this image")
+                .on_hover_text("Copy the artist, copyright and keywords shown above")
+                .clicked()
+            {
+                state.batch_artist.clone_from(&state.artist_edit);
+                state.batch_copyright.clone_from(&state.copyright_edit);
+                state.batch_keywords.clone_from(&state.keywords_edit);
+            }
+        });
 }
 
 /// Draw the image description tile UI
-pub fn draw_image_description_tile(ui: &mut egui::Ui, state: &AppState) {
+pub fn draw_image_description_tile(ui: &mut egui::Ui, state: &mut AppState) {
     // Check if we have a selected image
-    let Some(ref selected_path) = state.selected_input_file else {
+    let Some(selected_path) = state.selected_input_file.clone() else {
         ui.vertical_centered(|ui| {
             ui.add_space(20.0);
             ui.label("No image selected");
@@ -236,13 +155,17 @@ pub fn draw_image_description_tile(ui: &mut egui::Ui, state: &AppState) {
     });
     ui.separator();
 
-    // Try to read EXIF data
-    match read_exif_data(selected_path) {
+    draw_description_editor(ui, state, &selected_path);
+    draw_batch_metadata(ui, state);
+    ui.separator();
+
+    // Prefetched in the background; read now if the prefetch hasn't reached this image yet
+    match state.exif_summary(&selected_path) {
         Ok(exif_data) => {
             if exif_data.is_empty() {
                 ui.label("No EXIF metadata found in this image.");
             } else {
-                draw_exif_tree(ui, &exif_data);
+                draw_exif_tree(ui, exif_data);
             }
         }
         Err(e) => {
@@ -254,7 +177,7 @@ pub fn draw_image_description_tile(ui: &mut egui::Ui, state: &AppState) {
 }
 
 /// Draw the EXIF data as a collapsible tree
-fn draw_exif_tree(ui: &mut egui::Ui, data: &ExifData) {
+fn draw_exif_tree(ui: &mut egui::Ui, data: &ExifSummary) {
     ScrollArea::both()
         .id_salt("exif_scroll")
         .auto_shrink([false, false])
@@ -1,9 +1,21 @@
 //! Image manipulation settings tile
 
+use crate::app_home::APP_HOME;
 use crate::gui::state::AppState;
+use crate::image_processing::BackgroundDetection;
 use crate::image_processing::BinarizationMode;
+use crate::image_processing::DecodeErrorPolicy;
+use crate::image_processing::JpegSubsampling;
+use crate::image_processing::OutputConflictPolicy;
+use crate::image_processing::detect_format_from_path;
+use crate::output_root::set_output_root;
+use crate::watermark::WatermarkPosition;
 use eframe::egui;
+use image::ImageFormat;
 use std::fs;
+use std::path::PathBuf;
+use tracing::error;
+use tracing::info;
 
 /// Draw the image manipulation settings tile UI
 #[expect(clippy::too_many_lines)]
@@ -31,6 +43,109 @@ pub fn draw_image_manipulation_tile(ui: &mut egui::Ui, state: &mut AppState) {
         }
     });
 
+    // Background color detection
+    ui.horizontal(|ui| {
+        ui.label("Background:");
+        egui::ComboBox::from_id_salt("background_detection")
+            .selected_text(state.background_detection.label())
+            .show_ui(ui, |ui| {
+                for detection in BackgroundDetection::ALL {
+                    if ui
+                        .selectable_value(
+                            &mut state.background_detection,
+                            detection,
+                            detection.label(),
+                        )
+                        .clicked()
+                    {
+                        crop_changed = true;
+                    }
+                }
+            })
+            .response
+            .on_hover_text("How the background color is picked from the image edges");
+    });
+
+    // Fixed background color, for images whose border is mistaken for the background
+    ui.horizontal(|ui| {
+        let mut fixed = state.background_override.is_some();
+        if ui
+            .checkbox(&mut fixed, "Fixed color")
+            .on_hover_text("Crop against this color instead of the one detected from the edges")
+            .changed()
+        {
+            state.background_override = fixed.then_some([255, 255, 255]);
+            crop_changed = true;
+        }
+        if let Some(color) = &mut state.background_override
+            && ui.color_edit_button_srgb(color).changed()
+        {
+            crop_changed = true;
+        }
+        ui.toggle_value(&mut state.eyedropper_active, "💧 Pick")
+            .on_hover_text("Click a pixel in the input preview to use its color");
+    });
+
+    // Minimum crop size
+    ui.horizontal(|ui| {
+        ui.label("Minimum size:");
+        if ui
+            .add(
+                egui::DragValue::new(&mut state.min_output_dimension)
+                    .range(0..=10_000)
+                    .suffix(" px"),
+            )
+            .on_hover_text("Expand crops smaller than this on either side (0 = no minimum)")
+            .changed()
+        {
+            crop_changed = true;
+        }
+    });
+
+    ui.add_space(4.0);
+
+    // Deskew scans
+    ui.horizontal(|ui| {
+        crop_changed |= ui
+            .checkbox(&mut state.deskew, "Straighten scans")
+            .on_hover_text("Detect and undo a small rotation before cropping")
+            .changed();
+        ui.add_enabled_ui(state.deskew, |ui| {
+            ui.label("up to");
+            crop_changed |= ui
+                .add(
+                    egui::DragValue::new(&mut state.max_deskew_degrees)
+                        .range(0.5..=45.0)
+                        .speed(0.1)
+                        .suffix("°"),
+                )
+                .changed();
+        });
+    });
+
+    ui.add_space(4.0);
+
+    // Tone adjustments, applied before crop detection
+    ui.horizontal(|ui| {
+        ui.label("Brightness:");
+        crop_changed |= ui
+            .add(egui::Slider::new(&mut state.brightness, -255..=255))
+            .changed();
+    });
+    ui.horizontal(|ui| {
+        ui.label("Contrast:");
+        crop_changed |= ui
+            .add(egui::Slider::new(&mut state.contrast, -100.0..=100.0).text("%"))
+            .changed();
+    });
+    ui.horizontal(|ui| {
+        ui.label("Gamma:");
+        crop_changed |= ui
+            .add(egui::Slider::new(&mut state.gamma, 0.1..=5.0).logarithmic(true))
+            .on_hover_text("Values above 1 brighten midtones, below 1 darken them")
+            .changed();
+    });
+
     ui.add_space(4.0);
 
     // Binarization mode dropdown (always show)
@@ -87,6 +202,28 @@ pub fn draw_image_manipulation_tile(ui: &mut egui::Ui, state: &mut AppState) {
     ui.separator();
     ui.add_space(4.0);
 
+    // Unsharp mask sliders
+    ui.horizontal(|ui| {
+        ui.label("Sharpen:");
+        crop_changed |= ui
+            .add(egui::Slider::new(&mut state.sharpen_sigma, 0.0..=5.0).text("radius"))
+            .on_hover_text(
+                "Unsharp mask blur radius; higher values sharpen coarser detail (0 = off)",
+            )
+            .changed();
+    });
+    ui.add_enabled_ui(state.sharpen_sigma > 0.0, |ui| {
+        ui.horizontal(|ui| {
+            ui.label("Sharpen threshold:");
+            crop_changed |= ui
+                .add(egui::Slider::new(&mut state.sharpen_threshold, 0..=255))
+                .on_hover_text("Only sharpen edges with at least this much contrast, sparing noise")
+                .changed();
+        });
+    });
+
+    ui.add_space(4.0);
+
     // JPEG quality slider
     ui.horizontal(|ui| {
         ui.label("JPEG quality:");
@@ -99,11 +236,116 @@ pub fn draw_image_manipulation_tile(ui: &mut egui::Ui, state: &mut AppState) {
         }
     });
 
+    if ui
+        .checkbox(&mut state.jpeg_progressive, "Progressive JPEG")
+        .on_hover_text("Encode JPEGs so they load coarse-to-fine, which is nicer on the web")
+        .changed()
+        && state.selected_input_file.is_some()
+    {
+        state.update_selected_output_info();
+    }
+
+    ui.horizontal(|ui| {
+        ui.label("Chroma subsampling:");
+        let mut subsampling_changed = false;
+        egui::ComboBox::from_id_salt("jpeg_subsampling")
+            .selected_text(state.jpeg_subsampling.label())
+            .show_ui(ui, |ui| {
+                for subsampling in JpegSubsampling::ALL {
+                    subsampling_changed |= ui
+                        .selectable_value(
+                            &mut state.jpeg_subsampling,
+                            subsampling,
+                            subsampling.label(),
+                        )
+                        .clicked();
+                }
+            })
+            .response
+            .on_hover_text("4:4:4 keeps colored edges such as red text crisp at a larger size");
+        if subsampling_changed && state.selected_input_file.is_some() {
+            state.update_selected_output_info();
+        }
+    });
+
+    if ui
+        .checkbox(&mut state.true_output_preview, "True preview")
+        .on_hover_text(
+            "Preview the exact bytes that will be written, so JPEG artifacts at the chosen \
+             quality are visible (slower than the default downsampled preview)",
+        )
+        .changed()
+        && state.selected_input_file.is_some()
+    {
+        state.update_selected_output_info();
+    }
+
+    if selected_output_is_png(state) {
+        draw_png_options(ui, state);
+    }
+
+    draw_watermark(ui, state);
+
+    ui.checkbox(&mut state.preserve_timestamps, "Preserve file timestamps")
+        .on_hover_text("Give processed outputs the same modified time as their source image");
+
+    ui.horizontal(|ui| {
+        ui.label("Unreadable files:");
+        egui::ComboBox::from_id_salt("on_decode_error")
+            .selected_text(state.on_decode_error.label())
+            .show_ui(ui, |ui| {
+                for policy in DecodeErrorPolicy::ALL {
+                    ui.selectable_value(&mut state.on_decode_error, policy, policy.label());
+                }
+            })
+            .response
+            .on_hover_text("What Process All does with files that exist but can't be decoded");
+    });
+
+    ui.checkbox(&mut state.skip_animated, "Skip animated GIF/WebP")
+        .on_hover_text(
+            "Leave animated images out of Process All instead of using their first frame",
+        );
+
+    ui.add_enabled(
+        cfg!(feature = "lcms2"),
+        egui::Checkbox::new(&mut state.convert_to_srgb, "Convert color profiles to sRGB"),
+    )
+    .on_hover_text("Convert inputs tagged with a wide-gamut color profile to sRGB")
+    .on_disabled_hover_text("Requires building with the `lcms2` feature");
+
+    ui.horizontal(|ui| {
+        ui.label("Existing outputs:");
+        egui::ComboBox::from_id_salt("on_conflict")
+            .selected_text(state.on_conflict.label())
+            .show_ui(ui, |ui| {
+                for policy in OutputConflictPolicy::ALL {
+                    ui.selectable_value(&mut state.on_conflict, policy, policy.label());
+                }
+            })
+            .response
+            .on_hover_text(
+                "What Process All does when an output file is already there; Keep both adds \
+                 (1), (2), ... to the new file's name",
+            );
+    });
+
+    draw_output_root(ui, state);
+
     ui.add_space(4.0);
 
     // Sync pan/zoom checkbox
     ui.checkbox(&mut state.sync_preview_pan_zoom, "Sync preview pan/zoom")
         .on_hover_text("Synchronize pan and zoom across input, threshold, and output previews");
+    ui.add_enabled(
+        state.sync_preview_pan_zoom,
+        egui::Checkbox::new(&mut state.preview_loupe, "Magnifier loupe"),
+    )
+    .on_hover_text("Magnify the spot under the cursor in all synced previews to compare them");
+    ui.checkbox(&mut state.reset_view_on_select, "Reset view on select")
+        .on_hover_text(
+            "Fit previews to each newly selected image instead of restoring its last view",
+        );
 
     // Recalculate output info if settings changed
     if crop_changed && state.selected_input_file.is_some() {
@@ -160,6 +402,172 @@ pub fn draw_image_manipulation_tile(ui: &mut egui::Ui, state: &mut AppState) {
         ui.add_space(8.0);
         ui.label("Select an image to see size info");
     }
+
+    ui.separator();
+    ui.horizontal(|ui| {
+        let can_estimate = !state.batch_size_estimating && !state.image_files.is_empty();
+        if ui
+            .add_enabled(can_estimate, egui::Button::new("Estimate batch size"))
+            .on_hover_text("Process a sample of images with the current settings and extrapolate the total output size")
+            .clicked()
+        {
+            state.estimate_batch_size();
+        }
+        if state.batch_size_estimating {
+            ui.spinner();
+        }
+    });
+    if let Some(ref estimate) = state.batch_size_estimate {
+        ui.horizontal(|ui| {
+            ui.label("Estimated total output:");
+            ui.strong(format_size(estimate.estimated_output_bytes));
+        });
+        ui.label(format!(
+            "Input {} across {} files (sampled {})",
+            format_size(estimate.total_input_bytes),
+            estimate.total_files,
+            estimate.sampled
+        ));
+    }
+}
+
+/// Watermark text or image laid over every output
+fn draw_watermark(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut changed = ui
+        .checkbox(&mut state.watermark_enabled, "Watermark")
+        .on_hover_text("Lay semi-transparent text or an image over every output")
+        .changed();
+    if state.watermark_enabled {
+        ui.indent("watermark", |ui| {
+            ui.horizontal(|ui| {
+                changed |= ui
+                    .radio_value(&mut state.watermark_use_image, false, "Text")
+                    .changed();
+                changed |= ui
+                    .radio_value(&mut state.watermark_use_image, true, "Image")
+                    .changed();
+            });
+            let (edit, hint) = if state.watermark_use_image {
+                (&mut state.watermark_image, "path to a PNG or other image")
+            } else {
+                (&mut state.watermark_text, "watermark text")
+            };
+            changed |= ui
+                .add(
+                    egui::TextEdit::singleline(edit)
+                        .hint_text(hint)
+                        .desired_width(220.0),
+                )
+                .changed();
+            ui.horizontal(|ui| {
+                ui.label("Position:");
+                egui::ComboBox::from_id_salt("watermark_position")
+                    .selected_text(state.watermark_position.label())
+                    .show_ui(ui, |ui| {
+                        for position in WatermarkPosition::ALL {
+                            changed |= ui
+                                .selectable_value(
+                                    &mut state.watermark_position,
+                                    position,
+                                    position.label(),
+                                )
+                                .clicked();
+                        }
+                    });
+            });
+            ui.horizontal(|ui| {
+                ui.label("Opacity:");
+                changed |= ui
+                    .add(egui::Slider::new(&mut state.watermark_opacity, 0.0..=1.0))
+                    .changed();
+            });
+            ui.horizontal(|ui| {
+                ui.label("Width:");
+                changed |= ui
+                    .add(egui::Slider::new(&mut state.watermark_scale, 0.05..=1.0))
+                    .on_hover_text("Watermark width as a fraction of the image width")
+                    .changed();
+            });
+        });
+    }
+    if changed && state.selected_input_file.is_some() {
+        state.update_selected_output_info();
+    }
+}
+
+/// Whether the selected input is written out as a PNG
+fn selected_output_is_png(state: &AppState) -> bool {
+    state
+        .selected_input_file
+        .as_deref()
+        .is_some_and(|path| detect_format_from_path(path) == ImageFormat::Png)
+}
+
+fn draw_png_options(ui: &mut egui::Ui, state: &mut AppState) {
+    let mut changed = false;
+    ui.horizontal(|ui| {
+        ui.label("PNG compression:");
+        changed |= ui
+            .add(egui::Slider::new(&mut state.png_compression, 0..=9))
+            .on_hover_text("Higher levels are smaller but slower to write; 0 uses the default")
+            .changed();
+    });
+    changed |= ui
+        .checkbox(&mut state.png_quantize, "Reduce to 256-color palette")
+        .on_hover_text("Shrinks logos and graphics a lot; photos may show banding")
+        .changed();
+    if changed {
+        state.update_selected_output_info();
+    }
+}
+
+/// Output folder: either `-output` siblings of each input, or one folder collecting everything
+fn draw_output_root(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.add_space(4.0);
+    ui.horizontal(|ui| {
+        ui.label("Output folder:");
+        ui.add(
+            egui::TextEdit::singleline(&mut state.output_root_edit)
+                .hint_text("next to each input (<input>-output)")
+                .desired_width(220.0),
+        )
+        .on_hover_text(
+            "Collect all outputs under this folder as <folder>/<input name>/..., \
+             instead of an -output folder next to each input",
+        );
+        let current = state.output_root.clone();
+        let edited = state.output_root_edit.trim();
+        let new_root = (!edited.is_empty()).then(|| PathBuf::from(edited));
+        if ui
+            .add_enabled(new_root != current, egui::Button::new("Apply"))
+            .clicked()
+        {
+            match set_output_root(&APP_HOME, new_root.as_deref()) {
+                Ok(()) => {
+                    state.output_root.clone_from(&new_root);
+                    if let Some(root) = &new_root {
+                        info!("Outputs will be written under {}", root.display());
+                    } else {
+                        info!("Outputs will be written next to each input");
+                    }
+                    if state.selected_input_file.is_some() {
+                        state.update_selected_output_info();
+                    }
+                }
+                Err(e) => error!("Failed to save output folder: {}", e),
+            }
+        }
+        if current.is_some() && ui.button("Reset").clicked() {
+            state.output_root_edit.clear();
+            match set_output_root(&APP_HOME, None) {
+                Ok(()) => {
+                    state.output_root = None;
+                    info!("Outputs will be written next to each input");
+                }
+                Err(e) => error!("Failed to reset output folder: {}", e),
+            }
+        }
+    });
 }
 
 /// Format file size in human-readable form
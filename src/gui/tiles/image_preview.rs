@@ -4,10 +4,15 @@ use crate::gui::state::AppState;
 use crate::gui::tiles::pan_zoom::PanZoomState;
 use crate::gui::tiles::pan_zoom::draw_pan_zoom_image;
 use crate::gui::tiles::pan_zoom::draw_pan_zoom_image_uri;
+use crate::gui::tree_view::NO_CROP_COLOR;
+use crate::gui::tree_view::SUSPICIOUS_COLOR;
 use eframe::egui::TextureHandle;
 use eframe::egui::TextureOptions;
 use eframe::egui::{self};
+use image::GenericImageView;
+use std::path::Path;
 use std::path::PathBuf;
+use tracing::warn;
 
 /// Draw an image preview tile for input images
 pub fn draw_input_image_preview_tile(
@@ -17,6 +22,16 @@ pub fn draw_input_image_preview_tile(
 ) {
     let path = state.input_preview_path.clone();
     let should_clear = draw_image_preview_with_pan_zoom(ui, path.as_ref(), "input", pan_zoom);
+    if state.eyedropper_active
+        && let Some(path) = &path
+    {
+        if pan_zoom.hover_uv.is_some() {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::Crosshair);
+        }
+        if let Some(uv) = pan_zoom.clicked_uv {
+            pick_background_color(state, path, uv);
+        }
+    }
     if should_clear {
         state.selected_input_file = None;
         state.input_preview_path = None;
@@ -26,6 +41,37 @@ pub fn draw_input_image_preview_tile(
     }
 }
 
+/// Use the color at `uv` in the cached thumbnail of `path` as the crop background
+#[expect(clippy::cast_possible_truncation)]
+#[expect(clippy::cast_precision_loss)]
+#[expect(clippy::cast_sign_loss)]
+fn pick_background_color(state: &mut AppState, path: &Path, uv: egui::Pos2) {
+    let Some(cached) = state.image_cache.get(path) else {
+        warn!(
+            "No thumbnail of {} to pick a color from yet",
+            path.display()
+        );
+        return;
+    };
+    let thumbnail = match image::load_from_memory(&cached.thumbnail_data) {
+        Ok(thumbnail) => thumbnail,
+        Err(e) => {
+            warn!("Failed to decode thumbnail for {}: {}", path.display(), e);
+            return;
+        }
+    };
+    let (width, height) = thumbnail.dimensions();
+    if width == 0 || height == 0 {
+        return;
+    }
+    let x = ((uv.x * width as f32) as u32).min(width - 1);
+    let y = ((uv.y * height as f32) as u32).min(height - 1);
+    let [r, g, b, _] = thumbnail.get_pixel(x, y).0;
+    state.background_override = Some([r, g, b]);
+    state.eyedropper_active = false;
+    state.update_selected_output_info();
+}
+
 /// Draw an image preview tile for output images (shows processed/cropped version)
 pub fn draw_output_image_preview_tile(
     ui: &mut egui::Ui,
@@ -51,6 +97,14 @@ pub fn draw_output_image_preview_tile(
                     output_info.original_width, output_info.original_height
                 ));
             }
+            if output_info.no_crop_detected {
+                ui.colored_label(NO_CROP_COLOR, "No crop detected")
+                    .on_hover_text("The background was not found, so nothing was trimmed");
+            }
+            if let Some(warning) = &output_info.integrity_warning {
+                ui.colored_label(SUSPICIOUS_COLOR, "⚠ Possibly damaged")
+                    .on_hover_text(warning);
+            }
         });
         ui.separator();
     } else if state.output_info_loading {
@@ -117,7 +171,8 @@ fn draw_output_preview_with_texture(
             let needs_reload = texture_path.as_ref() != Some(input_path) || texture.is_none();
 
             if needs_reload {
-                // Load the processed image from PNG bytes
+                // Load the processed image from its preview PNG or, for a true preview, the
+                // encoded output
                 if let Ok(image) = image::load_from_memory(&output_info.preview_data) {
                     let size = [image.width() as _, image.height() as _];
                     let rgba = image.to_rgba8();
@@ -128,10 +183,14 @@ fn draw_output_preview_with_texture(
                     *texture = Some(ui.ctx().load_texture(
                         format!("output_preview_{}", input_path.display()),
                         color_image,
-                        TextureOptions::default(),
+                        // Keep compression artifacts crisp when zoomed into a true preview
+                        if state.true_output_preview {
+                            TextureOptions::NEAREST
+                        } else {
+                            TextureOptions::default()
+                        },
                     ));
                     *texture_path = Some(input_path.clone());
-                    pan_zoom.reset(); // Reset pan/zoom when loading new image
                 }
             }
 
@@ -1,8 +1,12 @@
 //! Input images tree tile - shows discovered image files
 
 use crate::gui::state::AppState;
+use crate::gui::state::ImageSort;
 use crate::gui::state::LoadingState;
 use crate::gui::tree_view::TreeRenderContext;
+use crate::gui::tree_view::draw_expand_collapse_buttons;
+use crate::gui::tree_view::filter_by_file_name;
+use crate::gui::tree_view::format_size;
 use crate::gui::tree_view::group_files_by_input;
 use crate::gui::tree_view::show_input_group_with_cache;
 use eframe::egui::ScrollArea;
@@ -38,10 +42,17 @@ pub fn draw_input_images_tile(
         return;
     }
 
+    let (total_images, total_bytes) = state.image_totals();
+    ui.strong(format!(
+        "{total_images} images, {} total",
+        format_size(total_bytes)
+    ));
+
     // Show loading progress if images are still being cached
     let loading_count = state.images_loading.len();
     let cached_count = state.image_cache.len();
     let total_count = state.image_files.len();
+    let files = state.sorted_image_files();
 
     if loading_count > 0 {
         ui.horizontal(|ui| {
@@ -50,17 +61,58 @@ pub fn draw_input_images_tile(
                 "Loading thumbnails... ({cached_count}/{total_count} cached)"
             ));
         });
-    } else {
+    } else if files.len() == total_count {
         ui.label(format!(
             "Click an image to preview it ({total_count} images):"
         ));
+    } else {
+        ui.label(format!(
+            "Click an image to preview it ({} of {total_count} images match the filter):",
+            files.len()
+        ));
+    }
+    ui.horizontal(|ui| {
+        ui.label("Sort:");
+        egui::ComboBox::from_id_salt("image_sort")
+            .selected_text(state.image_sort.label())
+            .show_ui(ui, |ui| {
+                for sort in ImageSort::ALL {
+                    ui.selectable_value(&mut state.image_sort, sort, sort.label());
+                }
+            });
+        ui.checkbox(&mut state.natural_sort, "Natural order")
+            .on_hover_text("Sort numbers in names by value, so img2 comes before img10");
+    });
+    ui.horizontal(|ui| {
+        ui.label("Find:");
+        ui.add(
+            egui::TextEdit::singleline(&mut state.tree_filter)
+                .hint_text("part of a file name")
+                .desired_width(160.0),
+        )
+        .on_hover_text("Only show images whose file name contains this text (ignoring case)");
+        if !state.tree_filter.is_empty() && ui.small_button("✖").on_hover_text("Clear").clicked()
+        {
+            state.tree_filter.clear();
+        }
+        draw_expand_collapse_buttons(ui, &mut state.input_tree_open);
+    });
+    let shown = filter_by_file_name(files, &state.tree_filter);
+    if !state.tree_filter.trim().is_empty() {
+        ui.label(format!(
+            "{} images contain \"{}\"",
+            shown.len(),
+            state.tree_filter.trim()
+        ));
     }
+    let files = shown;
     ui.separator();
 
     // Build a tree structure grouped by input directories
-    let grouped = group_files_by_input(&state.input_paths, &state.image_files);
+    let grouped = group_files_by_input(&state.input_paths, &files);
 
     let mut clicked_file: Option<PathBuf> = None;
+    let mut visible_uncached = Vec::new();
 
     ScrollArea::both()
         .id_salt("images_scroll")
@@ -70,6 +122,9 @@ pub fn draw_input_images_tile(
                 image_cache: &state.image_cache,
                 images_loading: &state.images_loading,
                 thumbnail_textures,
+                exif_summaries: &mut state.exif_summaries,
+                open_state: state.input_tree_open,
+                visible_uncached: Vec::new(),
             };
 
             for (input_path, relative_files) in &grouped {
@@ -84,7 +139,9 @@ pub fn draw_input_images_tile(
                     clicked_file = result.clicked_path;
                 }
             }
+            visible_uncached = ctx.visible_uncached;
         });
+    state.request_visible_thumbnails(&visible_uncached);
 
     // Handle clicked file after the borrow ends
     if let Some(clicked) = clicked_file {
@@ -1,19 +1,65 @@
 //! Input paths tile - shows and manages input directories
 
+use crate::app_home::APP_HOME;
 use crate::gui::state::AppState;
+use crate::input_overrides::SettingsOverride;
+use crate::input_overrides::save_input_overrides;
 use eframe::egui::ScrollArea;
 use eframe::egui::{self};
+use std::path::Path;
+use tracing::error;
 
 /// Draw the input paths tile UI
 pub fn draw_input_paths_tile(ui: &mut egui::Ui, state: &mut AppState) {
     // Clear all button and instructions
     ui.horizontal(|ui| {
-        if ui.button("🗑 Clear All").clicked() {
-            state.clear_all = true;
+        if ui
+            .add_enabled(!state.input_paths.is_empty(), egui::Button::new("🗑 Clear All"))
+            .clicked()
+        {
+            state.confirm_clear_open = true;
         }
         ui.label("Drag & drop folders here");
     });
 
+    ui.horizontal(|ui| {
+        ui.label("Import list:");
+        let response = ui
+            .text_edit_singleline(&mut state.import_list_path)
+            .on_hover_text(
+                "Text file with one input path per line; blank lines and lines starting with # \
+                 are ignored",
+            );
+        let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+        let has_path = !state.import_list_path.trim().is_empty();
+        let clicked = ui
+            .add_enabled(has_path, egui::Button::new("Import"))
+            .clicked();
+        if has_path && (clicked || submitted) {
+            state.import_input_list();
+        }
+    });
+    if let Some(status) = &state.import_list_status {
+        ui.horizontal(|ui| {
+            ui.label(status);
+            if ui.small_button("Dismiss").clicked() {
+                state.import_list_status = None;
+            }
+        });
+    }
+
+    if !state.empty_input_folders.is_empty() {
+        ui.horizontal(|ui| {
+            ui.colored_label(egui::Color32::YELLOW, "⚠ No images found in:");
+            if ui.small_button("Dismiss").clicked() {
+                state.empty_input_folders.clear();
+            }
+        });
+        for folder in &state.empty_input_folders {
+            ui.colored_label(egui::Color32::YELLOW, folder.display().to_string());
+        }
+    }
+
     ui.separator();
 
     // Show loading state
@@ -30,13 +76,25 @@ pub fn draw_input_paths_tile(ui: &mut egui::Ui, state: &mut AppState) {
         return;
     }
 
-    // Show paths with remove buttons - use both scroll areas for long paths
+    if let Some(root) = state.override_editor_root.clone() {
+        draw_override_editor(ui, state, &root);
+        ui.separator();
+    }
+
+    // Show paths with drag handles and remove buttons - use both scroll areas for long paths
     ScrollArea::both()
         .id_salt("inputs_paths_scroll")
         .auto_shrink([false, false])
         .show(ui, |ui| {
-            for path in &state.input_paths {
-                ui.horizontal(|ui| {
+            for (idx, path) in state.input_paths.iter().enumerate() {
+                let row = ui.horizontal(|ui| {
+                    // Drag handle - dropping onto another row moves this input there
+                    ui.dnd_drag_source(egui::Id::new(("input_path_drag", idx)), idx, |ui| {
+                        ui.label("☰");
+                    })
+                    .response
+                    .on_hover_text("Drag to reorder");
+
                     // Show spinner if image files are still being discovered
                     if state.image_files_loading.is_loading() {
                         ui.spinner();
@@ -46,10 +104,145 @@ pub fn draw_input_paths_tile(ui: &mut egui::Ui, state: &mut AppState) {
                     if ui.small_button("✖").clicked() {
                         state.path_to_remove = Some(path.clone());
                     }
+                    // Settings override editor toggle, highlighted when this root has overrides
+                    let has_overrides = state.input_overrides.get(path).is_some();
+                    let editing = state.override_editor_root.as_ref() == Some(path);
+                    let gear = ui
+                        .selectable_label(
+                            editing || has_overrides,
+                            if has_overrides { "⚙*" } else { "⚙" },
+                        )
+                        .on_hover_text("Override processing settings for this input");
+                    if gear.clicked() {
+                        state.override_editor_root = (!editing).then(|| path.clone());
+                    }
                     // Path label - use selectable_label to allow text selection
                     let display = path.display().to_string();
                     ui.add(egui::Label::new(&display).wrap_mode(egui::TextWrapMode::Extend));
                 });
+
+                let row = row.response;
+                if row.dnd_hover_payload::<usize>().is_some() {
+                    let stroke = ui.visuals().selection.stroke;
+                    ui.painter()
+                        .hline(row.rect.x_range(), row.rect.top(), stroke);
+                }
+                if let Some(from) = row.dnd_release_payload::<usize>() {
+                    state.input_path_move = Some((*from, idx));
+                }
             }
         });
 }
+
+/// Per-field checkboxes and values overriding the global processing settings for `root`
+fn draw_override_editor(ui: &mut egui::Ui, state: &mut AppState, root: &Path) {
+    let mut overrides = state.input_overrides.get(root).cloned().unwrap_or_default();
+    let before = overrides.clone();
+
+    ui.horizontal(|ui| {
+        ui.strong("Overrides for");
+        ui.add(
+            egui::Label::new(root.display().to_string()).wrap_mode(egui::TextWrapMode::Truncate),
+        );
+    });
+    ui.label("Checked settings replace the global ones for files under this input.");
+
+    override_row(
+        ui,
+        "Crop to content",
+        &mut overrides.crop_to_content,
+        state.crop_to_content,
+        |ui, v| ui.checkbox(v, ""),
+    );
+    override_row(
+        ui,
+        "Crop threshold",
+        &mut overrides.crop_threshold,
+        state.crop_threshold,
+        |ui, v| ui.add(egui::Slider::new(v, 0..=255)),
+    );
+    override_row(
+        ui,
+        "Min output size",
+        &mut overrides.min_output_dimension,
+        state.min_output_dimension,
+        |ui, v| ui.add(egui::DragValue::new(v).range(0..=10_000).suffix(" px")),
+    );
+    override_row(
+        ui,
+        "Straighten scans",
+        &mut overrides.deskew,
+        state.deskew,
+        |ui, v| ui.checkbox(v, ""),
+    );
+    override_row(
+        ui,
+        "Brightness",
+        &mut overrides.brightness,
+        state.brightness,
+        |ui, v| ui.add(egui::Slider::new(v, -255..=255)),
+    );
+    override_row(
+        ui,
+        "Contrast",
+        &mut overrides.contrast,
+        state.contrast,
+        |ui, v| ui.add(egui::Slider::new(v, -100.0..=100.0)),
+    );
+    override_row(ui, "Gamma", &mut overrides.gamma, state.gamma, |ui, v| {
+        ui.add(egui::Slider::new(v, 0.1..=5.0).logarithmic(true))
+    });
+    override_row(
+        ui,
+        "Sharpen",
+        &mut overrides.sharpen_sigma,
+        state.sharpen_sigma,
+        |ui, v| ui.add(egui::Slider::new(v, 0.0..=5.0)),
+    );
+    override_row(
+        ui,
+        "JPEG quality",
+        &mut overrides.jpeg_quality,
+        state.jpeg_quality,
+        |ui, v| ui.add(egui::Slider::new(v, 1..=100)),
+    );
+
+    ui.horizontal(|ui| {
+        if ui
+            .add_enabled(!overrides.is_empty(), egui::Button::new("Clear overrides"))
+            .clicked()
+        {
+            overrides = SettingsOverride::default();
+        }
+        if ui.button("Close").clicked() {
+            state.override_editor_root = None;
+        }
+    });
+
+    if overrides != before {
+        state.input_overrides.set(root, overrides);
+        if let Err(e) = save_input_overrides(&APP_HOME, &state.input_overrides) {
+            error!("Failed to save input overrides: {}", e);
+        }
+        state.update_selected_output_info();
+    }
+}
+
+/// A checkbox enabling the override, and the widget editing its value (seeded from `global`)
+fn override_row<T: Copy>(
+    ui: &mut egui::Ui,
+    label: &str,
+    value: &mut Option<T>,
+    global: T,
+    widget: impl FnOnce(&mut egui::Ui, &mut T) -> egui::Response,
+) {
+    ui.horizontal(|ui| {
+        let mut enabled = value.is_some();
+        if ui.checkbox(&mut enabled, label).changed() {
+            *value = enabled.then_some(global);
+        }
+        if let Some(value) = value {
+            widget(ui, value);
+        }
+    });
+}
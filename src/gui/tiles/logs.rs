@@ -1,9 +1,140 @@
 //! Logs tile - shows tracing logs
 
+use crate::gui::state::AppState;
 use eframe::egui;
+use std::path::PathBuf;
+use tracing::Level;
+use tracing::error;
+use tracing::info;
+use tracing_subscriber::filter::LevelFilter;
+
+const LEVELS: [Level; 5] = [
+    Level::TRACE,
+    Level::DEBUG,
+    Level::INFO,
+    Level::WARN,
+    Level::ERROR,
+];
+
+fn level_color(level: Level) -> egui::Color32 {
+    match level {
+        Level::ERROR => egui::Color32::RED,
+        Level::WARN => egui::Color32::YELLOW,
+        Level::INFO => egui::Color32::LIGHT_GREEN,
+        Level::DEBUG => egui::Color32::LIGHT_BLUE,
+        Level::TRACE => egui::Color32::GRAY,
+    }
+}
 
 /// Draw the logs tile UI
-pub fn draw_logs_tile(ui: &mut egui::Ui) {
-    let collector = crate::tracing::event_collector();
-    ui.add(egui_tracing::Logs::new(collector));
+pub fn draw_logs_tile(ui: &mut egui::Ui, state: &mut AppState) {
+    let lines = crate::tracing::formatted_events(state.log_min_level);
+
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_label("Min level")
+            .selected_text(state.log_min_level.to_string())
+            .show_ui(ui, |ui| {
+                for level in LEVELS {
+                    ui.selectable_value(&mut state.log_min_level, level, level.to_string());
+                }
+            });
+        if let Some(switch) = crate::tracing::log_level_switch() {
+            let current = switch.level();
+            let mut selected = current.and_then(LevelFilter::into_level);
+            egui::ComboBox::from_label("Capture level")
+                .selected_text(current.map_or_else(|| "off".to_owned(), |l| l.to_string()))
+                .show_ui(ui, |ui| {
+                    for level in LEVELS {
+                        ui.selectable_value(&mut selected, Some(level), level.to_string());
+                    }
+                });
+            if let Some(level) = selected
+                && current != Some(LevelFilter::from_level(level))
+                && let Err(e) = switch.set_level(level)
+            {
+                error!("{}", e);
+            }
+        }
+        if ui.button("Copy all").clicked() {
+            let text = lines
+                .iter()
+                .map(|(_, line)| line.as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+            ui.ctx().copy_text(text);
+        }
+        ui.label(format!("{} events", lines.len()));
+    });
+
+    ui.horizontal(|ui| {
+        if state.log_export_path.is_empty() {
+            state.log_export_path = crate::tracing::default_log_export_path()
+                .display()
+                .to_string();
+        }
+        ui.label("Export to:");
+        ui.add(egui::TextEdit::singleline(&mut state.log_export_path).desired_width(300.0));
+        if ui.button("Export logs").clicked() {
+            let path = PathBuf::from(&state.log_export_path);
+            match crate::tracing::export_events(&path) {
+                Ok(count) => info!("Exported {} log events to {}", count, path.display()),
+                Err(e) => error!("Failed to export logs to {}: {}", path.display(), e),
+            }
+        }
+    });
+    draw_json_log_toggle(ui, state);
+    ui.separator();
+
+    if state.log_min_level == Level::TRACE {
+        let collector = crate::tracing::event_collector();
+        ui.add(egui_tracing::Logs::new(collector));
+    } else {
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::both()
+            .id_salt("filtered_logs_scroll")
+            .auto_shrink([false, false])
+            .stick_to_bottom(true)
+            .show_rows(ui, row_height, lines.len(), |ui, range| {
+                for (level, line) in &lines[range] {
+                    ui.label(
+                        egui::RichText::new(line)
+                            .monospace()
+                            .color(level_color(*level)),
+                    );
+                }
+            });
+    }
+}
+
+/// Checkbox switching JSON output to a log file on and off, e.g. to capture a bug report
+fn draw_json_log_toggle(ui: &mut egui::Ui, state: &mut AppState) {
+    let Some(switch) = crate::tracing::json_log_switch() else {
+        return;
+    };
+    let current = switch.path();
+    if state.json_log_path.is_empty() {
+        state.json_log_path = current
+            .clone()
+            .unwrap_or_else(crate::tracing::default_json_log_path)
+            .display()
+            .to_string();
+    }
+
+    ui.horizontal(|ui| {
+        let mut enabled = current.is_some();
+        if ui.checkbox(&mut enabled, "Write JSON log to:").changed() {
+            let result = if enabled {
+                switch.enable(&PathBuf::from(&state.json_log_path))
+            } else {
+                switch.disable()
+            };
+            if let Err(e) = result {
+                error!("{}", e);
+            }
+        }
+        ui.add_enabled(
+            current.is_none(),
+            egui::TextEdit::singleline(&mut state.json_log_path).desired_width(300.0),
+        );
+    });
 }
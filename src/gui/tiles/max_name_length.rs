@@ -2,7 +2,11 @@
 
 use crate::MAX_NAME_LENGTH;
 use crate::MaxNameLength;
+use crate::app_home::APP_HOME;
 use crate::gui::state::AppState;
+use crate::name_length_unit::NameLengthUnit;
+use crate::name_length_unit::name_length_unit;
+use crate::name_length_unit::set_name_length_unit;
 use eframe::egui;
 use std::sync::atomic::Ordering;
 use tracing::error;
@@ -27,7 +31,22 @@ pub fn draw_max_name_length_tile(ui: &mut egui::Ui, state: &mut AppState) {
             state.rename_preview_key = 0; // Invalidate cache
         }
 
-        ui.label("characters");
+        let mut unit = name_length_unit();
+        egui::ComboBox::from_id_salt("name_length_unit")
+            .selected_text(unit.name())
+            .show_ui(ui, |ui| {
+                for option in NameLengthUnit::ALL {
+                    ui.selectable_value(&mut unit, option, option.name());
+                }
+            })
+            .response
+            .on_hover_text("Bytes match file system limits; chars count an accented letter once");
+        if unit != name_length_unit() {
+            if let Err(e) = set_name_length_unit(&APP_HOME, Some(unit)) {
+                error!("Failed to save name length unit: {}", e);
+            }
+            state.rename_preview_key = 0;
+        }
     });
 
     ui.add_space(8.0);
@@ -9,9 +9,11 @@ mod logs;
 mod max_name_length;
 mod output_preview;
 mod pan_zoom;
+mod processing_results;
 mod product_search;
 mod rename_rules;
 mod threshold_preview;
+mod thumbnail_grid;
 
 pub use image_description::*;
 pub use image_manipulation::*;
@@ -22,6 +24,8 @@ pub use logs::*;
 pub use max_name_length::*;
 pub use output_preview::*;
 pub use pan_zoom::*;
+pub use processing_results::*;
 pub use product_search::*;
 pub use rename_rules::*;
 pub use threshold_preview::*;
+pub use thumbnail_grid::*;
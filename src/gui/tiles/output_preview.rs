@@ -1,9 +1,12 @@
 //! Output preview tile - shows renamed files with status colors
 
 use crate::gui::state::AppState;
+use crate::gui::tree_view::NO_CROP_COLOR;
+use crate::gui::tree_view::draw_expand_collapse_buttons;
 use crate::gui::tree_view::group_files_with_renames;
 use crate::gui::tree_view::show_rename_group_with_output_path;
 use crate::image_processing::get_output_dir;
+use crate::name_length_unit::name_length_unit;
 use eframe::egui::Color32;
 use eframe::egui::ScrollArea;
 use eframe::egui::{self};
@@ -22,26 +25,78 @@ pub fn draw_output_preview_tile(ui: &mut egui::Ui, state: &mut AppState) {
             let process_all_btn = egui::Button::new("▶ Process All");
             if state.process_all_running {
                 // Cancel button appears while processing
-                if ui.add(egui::Button::new("✖ CANCEL")).clicked() {
+                if ui
+                    .add(egui::Button::new("✖ CANCEL"))
+                    .on_hover_text("Esc")
+                    .clicked()
+                {
                     state.cancel_process_all();
                 }
                 ui.add_enabled(false, process_all_btn);
-            } else if ui.add(process_all_btn).clicked() {
+            } else if ui
+                .add(process_all_btn)
+                .on_hover_text("Ctrl+Shift+P")
+                .clicked()
+            {
                 state.process_all();
             }
 
+            // Resume button - only when an interrupted batch left a checkpoint
+            if !state.process_all_running
+                && state.resume_batch_available
+                && ui
+                    .button("⏯ Resume last batch")
+                    .on_hover_text(
+                        "Continue the interrupted Process All, skipping images it already wrote",
+                    )
+                    .clicked()
+            {
+                state.resume_last_batch();
+            }
+
+            // Retry button - only after a batch that had failures
+            if !state.process_all_running
+                && !state.last_batch_errors.is_empty()
+                && ui
+                    .button(format!(
+                        "↻ Retry failed ({})",
+                        state.last_batch_errors.len()
+                    ))
+                    .on_hover_text("Reprocess only the files that failed in the last batch")
+                    .clicked()
+            {
+                state.retry_failed();
+            }
+
+            if ui
+                .add_enabled(
+                    !state.process_all_running,
+                    egui::Button::new("▦ Contact sheet"),
+                )
+                .on_hover_text("Tile the processed outputs into contact-sheet.png for review")
+                .clicked()
+            {
+                state.write_contact_sheet();
+            }
+
             // Process Selected button - disable while processing or if nothing selected
             let process_selected_btn = egui::Button::new("▶ Process Selected");
             let can_process_selected =
                 !state.process_all_running && state.selected_input_file.is_some();
             if !can_process_selected {
                 ui.add_enabled(false, process_selected_btn);
-            } else if ui.add(process_selected_btn).clicked() {
+            } else if ui
+                .add(process_selected_btn)
+                .on_hover_text("Ctrl+P")
+                .clicked()
+            {
                 state.process_selected();
             }
         });
     });
 
+    draw_image_filter(ui, state);
+
     // Show processing progress if running
     if state.process_all_running {
         if let Some((current, total)) = state.process_all_progress {
@@ -75,17 +130,43 @@ pub fn draw_output_preview_tile(ui: &mut egui::Ui, state: &mut AppState) {
         ui.add_space(8.0);
         ui.colored_label(Color32::RED, "●");
         ui.label("too long");
+        ui.add_space(8.0);
+        ui.colored_label(NO_CROP_COLOR, "●");
+        ui.label("no crop detected")
+            .on_hover_text("Cropping found nothing to trim when this image was last processed");
     });
 
-    ui.label("Click an image to preview:");
+    ui.horizontal(|ui| {
+        ui.label("Click an image to preview:");
+        draw_expand_collapse_buttons(ui, &mut state.output_tree_open);
+    });
     ui.separator();
 
-    let grouped = group_files_with_renames(
-        &state.input_paths,
-        &state.image_files,
-        &state.renamed_files,
-        state.max_name_length,
-    );
+    let mut grouped = if state.draft_rule_preview {
+        ui.weak("Showing names with the draft rule from the rename rules tile applied");
+        group_files_with_renames(
+            &state.input_paths,
+            &state.filtered_draft_rename_previews(),
+            &state.draft_rules(),
+            state.natural_sort,
+        )
+    } else {
+        group_files_with_renames(
+            &state.input_paths,
+            &state.filtered_rename_previews(),
+            &state.rename_rules,
+            state.natural_sort,
+        )
+    };
+    for (_, files_info) in &mut grouped {
+        for info in files_info {
+            info.no_crop_detected = state.no_crop_files.contains(&info.original_input_path);
+            info.integrity_warning = state
+                .suspicious_files
+                .get(&info.original_input_path)
+                .cloned();
+        }
+    }
 
     ScrollArea::both()
         .id_salt("output_preview_scroll")
@@ -93,14 +174,15 @@ pub fn draw_output_preview_tile(ui: &mut egui::Ui, state: &mut AppState) {
         .show(ui, |ui| {
             for (input_path, files_info) in &grouped {
                 // Show with -output suffix
-                let output_dir = get_output_dir(input_path);
+                let output_dir = get_output_dir(input_path, state.output_root.as_deref());
                 let result = show_rename_group_with_output_path(
                     ui,
-                    input_path,
+                    &output_dir,
                     &output_dir,
                     files_info,
                     state.max_name_length,
                     state.selected_input_file.as_ref(),
+                    state.output_tree_open,
                 );
                 if let Some(clicked) = result.clicked_path {
                     state.select_file(&clicked);
@@ -108,3 +190,34 @@ pub fn draw_output_preview_tile(ui: &mut egui::Ui, state: &mut AppState) {
             }
         });
 }
+
+/// File name glob restricting which images are previewed and processed, with the match count
+fn draw_image_filter(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.horizontal(|ui| {
+        ui.label("Filter:");
+        ui.add(
+            egui::TextEdit::singleline(&mut state.image_filter)
+                .hint_text("*-front.*")
+                .desired_width(160.0),
+        )
+        .on_hover_text("Only show and process images whose file name matches this glob");
+        if state.image_filter.trim().is_empty() {
+            return;
+        }
+        match state.image_name_filter() {
+            Ok(_) => {
+                ui.label(format!(
+                    "{} of {} images match",
+                    state.filtered_image_files().len(),
+                    state.image_files.len()
+                ));
+            }
+            Err(e) => {
+                ui.colored_label(Color32::RED, e.to_string());
+            }
+        }
+        if ui.small_button("✖").on_hover_text("Clear filter").clicked() {
+            state.image_filter.clear();
+        }
+    });
+}
@@ -1,5 +1,6 @@
 //! Pan and zoom functionality for image previews
 
+use crate::scroll_zoom::scroll_zoom;
 use eframe::egui::Color32;
 use eframe::egui::Pos2;
 use eframe::egui::Rect;
@@ -7,6 +8,42 @@ use eframe::egui::TextureHandle;
 use eframe::egui::Vec2;
 use eframe::egui::{self};
 
+/// Side length of the magnifier loupe in screen points
+const LOUPE_SIZE: f32 = 160.0;
+/// How much the loupe enlarges the image relative to the current view
+const LOUPE_MAGNIFICATION: f32 = 4.0;
+/// Smallest on-screen size of an image pixel at which the pixel grid is drawn
+const MIN_PIXEL_GRID_SPACING: f32 = 6.0;
+
+/// Grid painted over a preview for checking alignment and composition
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PreviewGrid {
+    #[default]
+    Off,
+    /// Lines dividing the image into thirds
+    Thirds,
+    /// Image pixel boundaries, once zoomed in far enough to see them
+    Pixels,
+}
+
+impl PreviewGrid {
+    fn next(self) -> Self {
+        match self {
+            PreviewGrid::Off => PreviewGrid::Thirds,
+            PreviewGrid::Thirds => PreviewGrid::Pixels,
+            PreviewGrid::Pixels => PreviewGrid::Off,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            PreviewGrid::Off => "Grid: off",
+            PreviewGrid::Thirds => "Grid: thirds",
+            PreviewGrid::Pixels => "Grid: pixels",
+        }
+    }
+}
+
 /// State for pan and zoom on an image preview
 #[derive(Clone, Debug, Default)]
 pub struct PanZoomState {
@@ -20,6 +57,13 @@ pub struct PanZoomState {
     pub fit_scale: f32,
     /// Whether the user interacted with this preview this frame
     pub dirty: bool,
+    /// Cursor position over the image this frame, normalized to 0..1 across the image
+    pub hover_uv: Option<Pos2>,
+    /// Where the image was clicked this frame, normalized like `hover_uv`
+    pub clicked_uv: Option<Pos2>,
+    /// Where to draw the magnifier loupe, normalized like `hover_uv`; shared across synced
+    /// previews so each magnifies the same spot
+    pub loupe_uv: Option<Pos2>,
 }
 
 impl PanZoomState {
@@ -30,6 +74,9 @@ impl PanZoomState {
             initialized: false,
             fit_scale: 1.0,
             dirty: false,
+            hover_uv: None,
+            clicked_uv: None,
+            loupe_uv: None,
         }
     }
 
@@ -46,6 +93,43 @@ impl PanZoomState {
         self.fit_scale * self.zoom_multiplier
     }
 
+    /// Upper bound for `zoom_multiplier`: 10x fit, or 4x actual pixels for images that are
+    /// scaled down a lot to fit
+    pub fn max_zoom_multiplier(&self) -> f32 {
+        10.0_f32.max(4.0 / self.fit_scale)
+    }
+
+    /// Zoom so one texel covers one screen point, keeping the point at the view center fixed
+    pub fn zoom_to_actual_pixels(&mut self) {
+        if self.fit_scale <= 0.0 {
+            return;
+        }
+        let old_zoom = self.actual_zoom();
+        self.zoom_multiplier = 1.0 / self.fit_scale;
+        self.offset *= self.actual_zoom() / old_zoom;
+        self.dirty = true;
+    }
+
+    /// Fit the whole image in the view
+    pub fn zoom_to_fit(&mut self) {
+        self.zoom_multiplier = 1.0;
+        self.offset = Vec2::ZERO;
+        self.dirty = true;
+    }
+
+    /// Zoom for a vertical scroll of `scroll` points using the saved scroll zoom preferences,
+    /// keeping the point at `anchor` (relative to the view center) under the cursor
+    pub fn zoom_by_scroll(&mut self, scroll: f32, anchor: Option<Vec2>) {
+        let old_zoom = self.actual_zoom();
+        self.zoom_multiplier = (self.zoom_multiplier * scroll_zoom().zoom_factor(scroll))
+            .clamp(0.1, self.max_zoom_multiplier());
+        if let Some(anchor) = anchor {
+            let scale_change = self.actual_zoom() / old_zoom;
+            self.offset = self.offset * scale_change + anchor * (1.0 - scale_change);
+        }
+        self.dirty = true;
+    }
+
     /// Sync from another pan/zoom state (copies multiplier and offset, not `fit_scale`)
     pub fn sync_from(&mut self, other: &PanZoomState) {
         self.zoom_multiplier = other.zoom_multiplier;
@@ -54,12 +138,11 @@ impl PanZoomState {
 }
 
 /// Draw a texture with pan and zoom support
-#[expect(clippy::cast_possible_truncation)]
 pub fn draw_pan_zoom_image(
     ui: &mut egui::Ui,
     texture: &TextureHandle,
     state: &mut PanZoomState,
-    _id_salt: &str,
+    id_salt: &str,
 ) {
     let available = ui.available_size();
     let tex_size = texture.size_vec2();
@@ -77,10 +160,6 @@ pub fn draw_pan_zoom_image(
         state.initialized = true;
     }
 
-    // Calculate the display size based on actual zoom
-    let actual_zoom = state.actual_zoom();
-    let display_size = tex_size * actual_zoom;
-
     // Allocate space for the image area
     let (rect, response) = ui.allocate_exact_size(available, egui::Sense::click_and_drag());
 
@@ -94,32 +173,37 @@ pub fn draw_pan_zoom_image(
     if response.hovered() {
         let scroll = ui.input(|i| i.raw_scroll_delta.y);
         if scroll != 0.0 {
-            let old_zoom = actual_zoom;
-            let zoom_factor = 1.0 + scroll * 0.001;
-            state.zoom_multiplier = (state.zoom_multiplier * zoom_factor).clamp(0.1, 10.0);
-            let new_zoom = state.actual_zoom();
-
-            // Zoom towards the mouse position
-            if let Some(hover_pos) = response.hover_pos() {
-                let mouse_rel = hover_pos - rect.center();
-                let scale_change = new_zoom / old_zoom;
-                state.offset = state.offset * scale_change + mouse_rel * (1.0 - scale_change);
+            let anchor = response.hover_pos().map(|p| p - rect.center());
+            state.zoom_by_scroll(scroll, anchor);
+        }
+
+        // 1 for actual pixels, 0 to fit, unless a text field has the keyboard
+        if !ui.ctx().wants_keyboard_input() {
+            if ui.input(|i| i.key_pressed(egui::Key::Num1)) {
+                state.zoom_to_actual_pixels();
+            }
+            if ui.input(|i| i.key_pressed(egui::Key::Num0)) {
+                state.zoom_to_fit();
             }
-            state.dirty = true;
         }
     }
 
     // Double-click or right-click to reset view
     if response.double_clicked() || response.secondary_clicked() {
-        state.zoom_multiplier = 1.0;
-        state.offset = Vec2::ZERO;
-        state.dirty = true;
+        state.zoom_to_fit();
     }
 
+    // Zoom changes above apply from this frame
+    let actual_zoom = state.actual_zoom();
+    let display_size = tex_size * actual_zoom;
+
     // Calculate image position (centered with offset)
     let image_center = rect.center() + state.offset;
     let image_rect = Rect::from_center_size(image_center, display_size);
 
+    state.hover_uv = hover_uv(&response, image_rect);
+    state.clicked_uv = state.hover_uv.filter(|_| response.clicked());
+
     // Use a clipped painter to respect tile boundaries
     let painter = ui.painter().with_clip_rect(rect);
 
@@ -128,6 +212,16 @@ pub fn draw_pan_zoom_image(
 
     painter.image(texture.id(), image_rect, uv, Color32::WHITE);
 
+    if let Some((loupe_rect, loupe_uv)) = state.loupe_uv.map(|uv| loupe_rects(uv, image_rect)) {
+        painter.rect_filled(loupe_rect, 0.0, Color32::BLACK);
+        painter.image(texture.id(), loupe_rect, loupe_uv, Color32::WHITE);
+        draw_loupe_frame(&painter, loupe_rect);
+    }
+
+    let grid_id = egui::Id::new(id_salt).with("grid");
+    let grid = ui.data(|d| d.get_temp::<PreviewGrid>(grid_id).unwrap_or_default());
+    draw_grid(&painter, image_rect, Some(tex_size), grid);
+
     // Draw a subtle border around the view area
     painter.rect_stroke(
         rect,
@@ -136,13 +230,44 @@ pub fn draw_pan_zoom_image(
         egui::epaint::StrokeKind::Inside,
     );
 
-    // Show zoom level hint on hover
+    draw_zoom_controls(ui, rect, state, grid_id);
+
     if response.hovered() {
-        let zoom_percent = (actual_zoom * 100.0).round() as i32;
-        response.on_hover_text(format!(
-            "{zoom_percent}% - Scroll to zoom, drag to pan, double-click to reset"
-        ));
+        response.on_hover_text(
+            "Scroll to zoom, drag to pan, 1 for actual pixels, 0 or double-click to fit",
+        );
+    }
+}
+
+/// Current zoom percentage with 100%, fit and grid buttons in the top-right corner of `rect`
+#[expect(clippy::cast_possible_truncation)]
+fn draw_zoom_controls(ui: &mut egui::Ui, rect: Rect, state: &mut PanZoomState, grid_id: egui::Id) {
+    let mut overlay = ui.new_child(
+        egui::UiBuilder::new()
+            .max_rect(rect.shrink(4.0))
+            .layout(egui::Layout::right_to_left(egui::Align::Min)),
+    );
+    draw_grid_toggle(&mut overlay, grid_id);
+    if overlay
+        .small_button("Fit")
+        .on_hover_text("Fit the whole image (0)")
+        .clicked()
+    {
+        state.zoom_to_fit();
+    }
+    if overlay
+        .small_button("100%")
+        .on_hover_text("Show actual pixels (1)")
+        .clicked()
+    {
+        state.zoom_to_actual_pixels();
     }
+    let zoom_percent = (state.actual_zoom() * 100.0).round() as i32;
+    overlay.label(
+        egui::RichText::new(format!("{zoom_percent}%"))
+            .monospace()
+            .background_color(Color32::from_black_alpha(160)),
+    );
 }
 
 /// Draw an image from a URI with pan and zoom support
@@ -151,7 +276,7 @@ pub fn draw_pan_zoom_image_uri(
     ui: &mut egui::Ui,
     uri: &str,
     state: &mut PanZoomState,
-    _id_salt: &str,
+    id_salt: &str,
 ) {
     let available = ui.available_size();
 
@@ -180,18 +305,8 @@ pub fn draw_pan_zoom_image_uri(
     if response.hovered() {
         let scroll = ui.input(|i| i.raw_scroll_delta.y);
         if scroll != 0.0 {
-            let old_zoom = state.actual_zoom();
-            let zoom_factor = 1.0 + scroll * 0.001;
-            state.zoom_multiplier = (state.zoom_multiplier * zoom_factor).clamp(0.1, 10.0);
-            let new_zoom = state.actual_zoom();
-
-            // Zoom towards the mouse position
-            if let Some(hover_pos) = response.hover_pos() {
-                let mouse_rel = hover_pos - rect.center();
-                let scale_change = new_zoom / old_zoom;
-                state.offset = state.offset * scale_change + mouse_rel * (1.0 - scale_change);
-            }
-            state.dirty = true;
+            let anchor = response.hover_pos().map(|p| p - rect.center());
+            state.zoom_by_scroll(scroll, anchor);
         }
     }
 
@@ -239,8 +354,29 @@ pub fn draw_pan_zoom_image_uri(
         });
     }
 
+    state.hover_uv = hover_uv(&response, image_rect);
+    state.clicked_uv = state.hover_uv.filter(|_| response.clicked());
+
     // Draw a subtle border around the view area (get painter after child UI is done)
     let painter = ui.painter().with_clip_rect(rect);
+
+    if let Some((loupe_rect, loupe_uv)) = state.loupe_uv.map(|uv| loupe_rects(uv, image_rect)) {
+        painter.rect_filled(loupe_rect, 0.0, Color32::BLACK);
+        let mut loupe_ui = ui.new_child(egui::UiBuilder::new().max_rect(loupe_rect));
+        loupe_ui.set_clip_rect(rect);
+        loupe_ui.add(
+            egui::Image::new(uri)
+                .uv(loupe_uv)
+                .fit_to_exact_size(loupe_rect.size()),
+        );
+        draw_loupe_frame(&painter, loupe_rect);
+    }
+
+    // The source resolution isn't known here, so only the thirds grid can be drawn
+    let grid_id = egui::Id::new(id_salt).with("grid");
+    let grid = ui.data(|d| d.get_temp::<PreviewGrid>(grid_id).unwrap_or_default());
+    draw_grid(&painter, image_rect, None, grid);
+
     painter.rect_stroke(
         rect,
         0.0,
@@ -248,6 +384,13 @@ pub fn draw_pan_zoom_image_uri(
         egui::epaint::StrokeKind::Inside,
     );
 
+    let mut overlay = ui.new_child(
+        egui::UiBuilder::new()
+            .max_rect(rect.shrink(4.0))
+            .layout(egui::Layout::right_to_left(egui::Align::Min)),
+    );
+    draw_grid_toggle(&mut overlay, grid_id);
+
     // Show zoom level hint on hover
     if response.hovered() {
         let zoom_percent = (actual_zoom * 100.0).round() as i32;
@@ -256,3 +399,88 @@ pub fn draw_pan_zoom_image_uri(
         ));
     }
 }
+
+/// Cursor position over `image_rect` normalized to 0..1, if it is over the image
+fn hover_uv(response: &egui::Response, image_rect: Rect) -> Option<Pos2> {
+    let pos = response.hover_pos().filter(|p| image_rect.contains(*p))?;
+    Some(((pos - image_rect.min) / image_rect.size()).to_pos2())
+}
+
+/// Screen rect of a loupe centered on `uv` within `image_rect`, and the texture region it shows
+fn loupe_rects(uv: Pos2, image_rect: Rect) -> (Rect, Rect) {
+    let center = image_rect.min + uv.to_vec2() * image_rect.size();
+    let screen = Rect::from_center_size(center, Vec2::splat(LOUPE_SIZE));
+    let half = Vec2::splat(LOUPE_SIZE / LOUPE_MAGNIFICATION / 2.0) / image_rect.size();
+    (screen, Rect::from_min_max(uv - half, uv + half))
+}
+
+/// Border and crosshair marking the magnified pixel
+fn draw_loupe_frame(painter: &egui::Painter, loupe_rect: Rect) {
+    let stroke = egui::Stroke::new(1.0, Color32::from_rgb(0xFF, 0xA5, 0x00));
+    let center = loupe_rect.center();
+    painter.hline(loupe_rect.x_range(), center.y, stroke);
+    painter.vline(center.x, loupe_rect.y_range(), stroke);
+    painter.rect_stroke(
+        loupe_rect,
+        0.0,
+        egui::Stroke::new(2.0, Color32::from_gray(200)),
+        egui::epaint::StrokeKind::Outside,
+    );
+}
+
+/// Button cycling the grid overlay stored under `grid_id`
+fn draw_grid_toggle(ui: &mut egui::Ui, grid_id: egui::Id) {
+    let grid = ui.data(|d| d.get_temp::<PreviewGrid>(grid_id).unwrap_or_default());
+    if ui
+        .small_button(grid.label())
+        .on_hover_text("Cycle the grid overlay: rule of thirds, then image pixels when zoomed in")
+        .clicked()
+    {
+        ui.data_mut(|d| d.insert_temp(grid_id, grid.next()));
+    }
+}
+
+/// Paint `grid` over `image_rect`. `source_size` is the image's resolution in pixels, needed for
+/// the pixel grid.
+fn draw_grid(
+    painter: &egui::Painter,
+    image_rect: Rect,
+    source_size: Option<Vec2>,
+    grid: PreviewGrid,
+) {
+    let stroke = egui::Stroke::new(1.0, Color32::from_white_alpha(110));
+    match grid {
+        PreviewGrid::Off => {}
+        PreviewGrid::Thirds => {
+            for t in [1.0 / 3.0, 2.0 / 3.0] {
+                let x = image_rect.left() + image_rect.width() * t;
+                let y = image_rect.top() + image_rect.height() * t;
+                painter.vline(x, image_rect.y_range(), stroke);
+                painter.hline(image_rect.x_range(), y, stroke);
+            }
+        }
+        PreviewGrid::Pixels => {
+            let Some(source_size) = source_size.filter(|s| s.x > 0.0 && s.y > 0.0) else {
+                return;
+            };
+            let spacing = image_rect.size() / source_size;
+            if spacing.x < MIN_PIXEL_GRID_SPACING || spacing.y < MIN_PIXEL_GRID_SPACING {
+                return;
+            }
+            // Only the lines inside the visible part of the image
+            let visible = painter.clip_rect().intersect(image_rect);
+            let mut x = image_rect.left()
+                + ((visible.left() - image_rect.left()) / spacing.x).ceil() * spacing.x;
+            while x <= visible.right() {
+                painter.vline(x, visible.y_range(), stroke);
+                x += spacing.x;
+            }
+            let mut y = image_rect.top()
+                + ((visible.top() - image_rect.top()) / spacing.y).ceil() * spacing.y;
+            while y <= visible.bottom() {
+                painter.hline(visible.x_range(), y, stroke);
+                y += spacing.y;
+            }
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//! Processing results - lists the files that failed in the last batch run, how long its
+//! stages took and how much smaller its outputs are than the inputs
+
+use crate::gui::state::AppState;
+use crate::gui::tree_view::format_size;
+use eframe::egui;
+
+/// Draw the processing results UI
+pub fn draw_processing_results_tile(ui: &mut egui::Ui, state: &mut AppState) {
+    if let Some(timing) = &state.last_batch_timing {
+        egui::CollapsingHeader::new("Last batch timing")
+            .id_salt("last_batch_timing")
+            .show(ui, |ui| {
+                ui.label(egui::RichText::new(timing.to_string()).monospace());
+            });
+        ui.separator();
+    }
+
+    if let Some(conversion) = &state.last_batch_conversion {
+        ui.horizontal(|ui| {
+            ui.label(format!(
+                "{} → {} ({:.1}% saved)",
+                format_size(conversion.total_input_bytes),
+                format_size(conversion.total_output_bytes),
+                conversion.percent_saved
+            ));
+            if ui
+                .button("Copy CSV")
+                .on_hover_text("Copy the per-file size report as CSV")
+                .clicked()
+            {
+                ui.ctx().copy_text(conversion.to_csv());
+            }
+        });
+        egui::CollapsingHeader::new("Last batch sizes")
+            .id_salt("last_batch_conversion")
+            .show(ui, |ui| {
+                egui::ScrollArea::vertical()
+                    .id_salt("last_batch_conversion_scroll")
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("last_batch_conversion_grid")
+                            .striped(true)
+                            .num_columns(4)
+                            .show(ui, |ui| {
+                                for entry in &conversion.files {
+                                    let name = std::path::Path::new(&entry.input)
+                                        .file_name()
+                                        .map(|s| s.to_string_lossy().to_string())
+                                        .unwrap_or_default();
+                                    ui.label(name).on_hover_text(&entry.input);
+                                    ui.label(format_size(entry.input_size));
+                                    ui.label(format_size(entry.output_size));
+                                    ui.label(format!("{:.1}%", entry.percent_saved()));
+                                    ui.end_row();
+                                }
+                            });
+                    });
+            });
+        ui.separator();
+    }
+
+    if state.last_batch_errors.is_empty() {
+        ui.label("The last batch completed without errors.");
+        return;
+    }
+
+    ui.horizontal(|ui| {
+        ui.colored_label(
+            egui::Color32::RED,
+            format!("{} files failed", state.last_batch_errors.len()),
+        );
+        if ui.button("Copy").clicked() {
+            let text = state
+                .last_batch_errors
+                .iter()
+                .map(|f| format!("{}\t{}", f.path.display(), f.error))
+                .collect::<Vec<_>>()
+                .join("\n");
+            ui.ctx().copy_text(text);
+        }
+        if ui
+            .add_enabled(
+                !state.process_all_running,
+                egui::Button::new("Retry failed"),
+            )
+            .clicked()
+        {
+            state.retry_failed();
+        }
+    });
+    ui.separator();
+
+    egui::ScrollArea::vertical()
+        .id_salt("processing_results_scroll")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            egui::Grid::new("processing_results_grid")
+                .striped(true)
+                .num_columns(3)
+                .show(ui, |ui| {
+                    for failed in &state.last_batch_errors {
+                        let name = failed
+                            .path
+                            .file_name()
+                            .map(|s| s.to_string_lossy().to_string())
+                            .unwrap_or_default();
+                        ui.label(name)
+                            .on_hover_text(failed.path.display().to_string());
+                        let kind = egui::RichText::new(failed.error.kind());
+                        if failed.error.is_retryable() {
+                            ui.label(kind).on_hover_text("May succeed if retried");
+                        } else {
+                            ui.label(kind.weak())
+                                .on_hover_text("Retrying won't help until the input changes");
+                        }
+                        ui.label(failed.error.to_string());
+                        ui.end_row();
+                    }
+                });
+        });
+}
@@ -1,83 +1,49 @@
+use crate::app_home::APP_HOME;
 use crate::cli::command::search::search_command::OutputFormat;
 use crate::cli::command::search::search_command::SearchArgs;
+use crate::cli::command::search::search_result_ok::PriceSummary;
+use crate::cli::command::search::search_result_ok::ResultItem;
+use crate::cli::command::search::search_result_ok::sort_by_price;
+pub use crate::cli::command::search::suggest::suggest_search;
 use crate::gui::state::AppState;
 use crate::gui::state::BackgroundMessage;
+use crate::gui::state::PriceSort;
+use crate::gui::state::ProductThumbnail;
+use crate::gui::tree_view::encoded_image_texture;
+use crate::product_thumbnails::load_thumbnail;
+use crate::search_history;
 use chrono::Local;
 use eframe::egui::Button;
+use eframe::egui::Popup;
+use eframe::egui::PopupCloseBehavior;
 use eframe::egui::RichText;
 use eframe::egui::ScrollArea;
+use eframe::egui::SetOpenCommand;
 use eframe::egui::TextEdit;
+use eframe::egui::TextureHandle;
 use eframe::egui::{self};
 use facet_pretty::PrettyPrinter;
-use regex::Regex;
-use std::path::Path;
+use std::collections::HashMap;
+use tokio::runtime::Handle;
 use tokio::sync::mpsc::UnboundedSender;
 
-/// Suggest search args given a filename.
-/// If a six-digit SKU is found (\b(\d{6})\b) suggest a SKU search, otherwise
-/// suggest a query formed by replacing hyphens with spaces, inserting spaces
-/// before camel-case boundaries (but not inside ALL-CAPS), stripping numbers,
-/// and omitting any single-character tokens.
-pub fn suggest_search(filename: &str) -> SearchArgs {
-    let re_sku = Regex::new(r"\b(\d{6})\b").unwrap();
-    let re_digits = Regex::new(r"\d+").unwrap();
-    // Insert spaces for transitions like "HTMLParser" -> "HTML Parser"
-    let re_camel_acronym = Regex::new(r"([A-Z]+)([A-Z][a-z])").unwrap();
-    // Insert spaces for transitions like "forestGreen" -> "forest Green"
-    let re_camel = Regex::new(r"([a-z0-9])([A-Z])").unwrap();
-
-    // Use file stem (strip extension) when possible
-    let stem = Path::new(filename)
-        .file_stem()
-        .map_or_else(|| filename.to_string(), |s| s.to_string_lossy().to_string());
-
-    if let Some(cap) = re_sku.captures(&stem) {
-        let sku = cap.get(1).unwrap().as_str().to_string();
-        return SearchArgs {
-            query: None,
-            sku: Some(sku),
-            no_cache: false,
-            output: OutputFormat::Json,
-        };
-    }
-
-    // Replace hyphens/underscores with spaces first
-    let with_spaces = stem.replace(['-', '_'], " ");
-
-    // Insert spaces for camel/pascal boundaries. Do the acronym rule first so
-    // ALL-CAPS words aren't split internally ("ALL" stays "ALL").
-    let with_caps = re_camel_acronym
-        .replace_all(&with_spaces, "$1 $2")
-        .to_string();
-    let with_caps = re_camel.replace_all(&with_caps, "$1 $2").to_string();
-
-    // Strip digits
-    let stripped = re_digits.replace_all(&with_caps, "").to_string();
-
-    // Collapse whitespace, trim and remove any single-character tokens
-    let suggestion = stripped
-        .split_whitespace()
-        .filter(|s| s.chars().count() > 1)
-        .collect::<Vec<_>>()
-        .join(" ")
-        .trim()
-        .to_string();
-
-    SearchArgs {
-        query: if suggestion.is_empty() {
-            Some(stem)
-        } else {
-            Some(suggestion)
-        },
-        sku: None,
-        no_cache: false,
-        output: OutputFormat::Json,
-    }
+/// Split a comma-separated SKU field into individual SKUs, dropping blanks.
+pub fn parse_sku_list(text: &str) -> Vec<String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
 }
 
 // Spawn a tokio task to perform a product search and forward the result to the background channel.
-fn spawn_product_search(tx: UnboundedSender<BackgroundMessage>, args: SearchArgs) {
-    tokio::spawn(async move {
+fn spawn_product_search(
+    runtime: &Handle,
+    tx: UnboundedSender<BackgroundMessage>,
+    args: SearchArgs,
+) {
+    runtime.spawn(async move {
+        let query = args.query.clone();
         match args.search().await {
             Ok(res) => {
                 // Prettify once on the background thread and send both the parsed struct and the prettified string
@@ -85,6 +51,7 @@ fn spawn_product_search(tx: UnboundedSender<BackgroundMessage>, args: SearchArgs
                 let pretty = facet_json::to_string_pretty(&res.results)
                     .unwrap_or(PrettyPrinter::new().with_colors(false).format(&res.results));
                 let _ = tx.send(BackgroundMessage::ProductSearchResult {
+                    query,
                     result: Some(res),
                     pretty: Some(pretty),
                     error: None,
@@ -93,9 +60,10 @@ fn spawn_product_search(tx: UnboundedSender<BackgroundMessage>, args: SearchArgs
             }
             Err(e) => {
                 let _ = tx.send(BackgroundMessage::ProductSearchResult {
+                    query,
                     result: None,
                     pretty: None,
-                    error: Some(format!("Search failed: {e}")),
+                    error: Some(e.to_string()),
                     received_at: Local::now(),
                 });
             }
@@ -103,12 +71,202 @@ fn spawn_product_search(tx: UnboundedSender<BackgroundMessage>, args: SearchArgs
     });
 }
 
+/// Search for the query and SKUs currently in the fields, clearing the previous results
+fn submit_search(state: &mut AppState) {
+    // Clear previous results so UI doesn't appear stale while waiting
+    state.product_search_result_raw = None;
+    state.product_search_result_pretty.clear();
+    state.product_search_error = None;
+    state.product_search_last_response = None;
+    state.product_search_show_raw = false;
+
+    // Perform search in background: spawn tokio task
+    let query = state.product_search_query.clone();
+    let sku = parse_sku_list(&state.product_search_sku);
+    let tx = state.background_sender.clone();
+
+    let args = SearchArgs {
+        query: if query.is_empty() { None } else { Some(query) },
+        sku,
+        no_cache: false,
+        output: OutputFormat::Json,
+        fields: vec![],
+    };
+    spawn_product_search(&state.runtime, tx, args);
+}
+
+/// Recent queries matching the query field, shown below it while it has focus. Clicking one
+/// fills it in and searches again.
+fn draw_recent_queries(ui: &mut egui::Ui, state: &mut AppState, query_resp: &egui::Response) {
+    let typed = state.product_search_query.to_lowercase();
+    let matches: Vec<String> = state
+        .product_search_history
+        .iter()
+        .filter(|q| q.to_lowercase().contains(&typed) && **q != state.product_search_query)
+        .cloned()
+        .collect();
+    if matches.is_empty() {
+        return;
+    }
+
+    let popup = Popup::from_response(query_resp)
+        .open_memory(query_resp.has_focus().then_some(SetOpenCommand::Bool(true)))
+        .close_behavior(PopupCloseBehavior::CloseOnClickOutside)
+        .width(query_resp.rect.width());
+    let popup_id = popup.get_id();
+    let mut chosen = None;
+    let mut clear = false;
+    popup.show(|ui| {
+        for query in matches {
+            if ui.selectable_label(false, &query).clicked() {
+                chosen = Some(query);
+            }
+        }
+        ui.separator();
+        clear = ui.small_button("Clear history").clicked();
+    });
+
+    if let Some(query) = chosen {
+        state.product_search_query = query;
+        state.product_search_use_suggestion = false;
+        Popup::close_id(ui.ctx(), popup_id);
+        submit_search(state);
+    }
+    if clear {
+        if let Err(e) = search_history::clear_queries(&APP_HOME) {
+            tracing::error!("Failed to clear search history: {}", e);
+        }
+        state.product_search_history.clear();
+        Popup::close_id(ui.ctx(), popup_id);
+    }
+}
+
+/// Show how the API matched the query, and a clickable "Did you mean" correction that searches
+/// again with the corrected query
+fn draw_query_correction(ui: &mut egui::Ui, state: &mut AppState) {
+    let Some(raw) = &state.product_search_result_raw else {
+        return;
+    };
+    let suggestion = raw
+        .did_you_mean
+        .as_ref()
+        .and_then(|d| d.query.clone())
+        .filter(|q| !q.trim().is_empty());
+    let query_info = raw.query.clone();
+
+    let mut rerun = None;
+    ui.horizontal(|ui| {
+        if let Some(info) = &query_info
+            && let Some(match_type) = &info.match_type
+        {
+            let badge = ui.label(
+                RichText::new(match_type)
+                    .small()
+                    .background_color(ui.visuals().faint_bg_color),
+            );
+            if let Some(corrected) = &info.corrected {
+                let original = info.original.as_deref().unwrap_or_default();
+                badge.on_hover_text(format!(
+                    "Searched for \"{corrected}\" instead of \"{original}\""
+                ));
+            }
+        }
+        if let Some(suggestion) = &suggestion {
+            ui.label("Did you mean:");
+            if ui.link(suggestion).clicked() {
+                rerun = Some(suggestion.clone());
+            }
+        }
+    });
+
+    if let Some(query) = rerun {
+        state.product_search_query = query;
+        state.product_search_use_suggestion = false;
+        submit_search(state);
+    }
+}
+
+/// Edge length of the square result thumbnails
+const THUMBNAIL_SIZE: f32 = 48.0;
+
+/// Draw a search result's thumbnail, fetching it the first time it is seen. A placeholder
+/// stands in while it loads, when it fails, or when the result has none.
+fn draw_result_thumbnail(
+    ui: &mut egui::Ui,
+    thumbnails: &mut HashMap<String, ProductThumbnail>,
+    textures: &mut HashMap<String, TextureHandle>,
+    runtime: &Handle,
+    sender: &UnboundedSender<BackgroundMessage>,
+    url: Option<&str>,
+) {
+    let size = egui::vec2(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+    let placeholder = |ui: &mut egui::Ui| {
+        ui.add_sized(size, egui::Label::new(RichText::new("🖼").weak()))
+            .on_hover_text("No thumbnail");
+    };
+    let Some(url) = url.filter(|u| !u.is_empty()) else {
+        placeholder(ui);
+        return;
+    };
+
+    if !textures.contains_key(url) {
+        match thumbnails.get(url) {
+            None => {
+                thumbnails.insert(url.to_owned(), ProductThumbnail::Loading);
+                let url = url.to_owned();
+                let tx = sender.clone();
+                runtime.spawn(async move {
+                    let result = load_thumbnail(&APP_HOME, &url)
+                        .await
+                        .map_err(|e| e.to_string());
+                    let _ = tx.send(BackgroundMessage::ProductThumbnailLoaded { url, result });
+                });
+                ui.add_sized(size, egui::Spinner::new());
+                return;
+            }
+            Some(ProductThumbnail::Loading) => {
+                ui.add_sized(size, egui::Spinner::new());
+                return;
+            }
+            Some(ProductThumbnail::Failed) => {
+                placeholder(ui);
+                return;
+            }
+            Some(ProductThumbnail::Loaded(bytes)) => {
+                let name = format!("product_thumb_{url}");
+                if let Some(texture) = encoded_image_texture(ui.ctx(), name, bytes) {
+                    textures.insert(url.to_owned(), texture);
+                } else {
+                    thumbnails.insert(url.to_owned(), ProductThumbnail::Failed);
+                    placeholder(ui);
+                    return;
+                }
+            }
+        }
+    }
+    let texture = &textures[url];
+    ui.add_sized(
+        size,
+        egui::Image::new((texture.id(), texture.size_vec2())).max_size(size),
+    );
+}
+
 #[expect(clippy::too_many_lines)]
-pub fn draw_product_search_tile(ui: &mut egui::Ui, state: &mut AppState) {
+pub fn draw_product_search_tile(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    thumbnail_textures: &mut HashMap<String, TextureHandle>,
+) {
     // Keep a cloned copy of the prettified JSON for read-only display
     let pretty_text = state.product_search_result_pretty.clone();
 
     ui.vertical(|ui| {
+        if crate::offline::is_offline() {
+            ui.label(
+                RichText::new("Offline: only cached results are available")
+                    .color(egui::Color32::YELLOW),
+            );
+        }
         ui.label("Query:");
         let query_resp =
             ui.add(TextEdit::singleline(&mut state.product_search_query).desired_width(f32::MAX));
@@ -118,27 +276,9 @@ pub fn draw_product_search_tile(ui: &mut egui::Ui, state: &mut AppState) {
         }
         // Submit on Enter
         if query_resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-            // Clear previous results so UI doesn't appear stale while waiting
-            state.product_search_result_raw = None;
-            state.product_search_result_pretty.clear();
-            state.product_search_last_response = None;
-            state.product_search_show_raw = false;
-
-            let query = state.product_search_query.clone();
-            let sku = if state.product_search_sku.is_empty() {
-                None
-            } else {
-                Some(state.product_search_sku.clone())
-            };
-            let tx = state.background_sender.clone();
-            let args = SearchArgs {
-                query: if query.is_empty() { None } else { Some(query) },
-                sku,
-                no_cache: false,
-                output: OutputFormat::Json,
-            };
-            spawn_product_search(tx, args);
+            submit_search(state);
         }
+        draw_recent_queries(ui, state, &query_resp);
 
         ui.label("SKU:");
         let sku_resp =
@@ -147,26 +287,7 @@ pub fn draw_product_search_tile(ui: &mut egui::Ui, state: &mut AppState) {
             state.product_search_use_suggestion = false;
         }
         if sku_resp.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
-            // Clear previous results so UI doesn't appear stale while waiting
-            state.product_search_result_raw = None;
-            state.product_search_result_pretty.clear();
-            state.product_search_last_response = None;
-            state.product_search_show_raw = false;
-
-            let query = state.product_search_query.clone();
-            let sku = if state.product_search_sku.is_empty() {
-                None
-            } else {
-                Some(state.product_search_sku.clone())
-            };
-            let tx = state.background_sender.clone();
-            let args = SearchArgs {
-                query: if query.is_empty() { None } else { Some(query) },
-                sku,
-                no_cache: false,
-                output: OutputFormat::Json,
-            };
-            spawn_product_search(tx, args);
+            submit_search(state);
         }
 
         // Show suggested query for the selected item, if any
@@ -178,8 +299,8 @@ pub fn draw_product_search_tile(ui: &mut egui::Ui, state: &mut AppState) {
                 let suggestion = suggest_search(&filename);
                 ui.horizontal(|ui| {
                     ui.label(RichText::new("Suggested:").strong());
-                    if let Some(sku) = &suggestion.sku {
-                        ui.label(format!("SKU: {sku}"));
+                    if !suggestion.sku.is_empty() {
+                        ui.label(format!("SKU: {}", suggestion.sku.join(", ")));
                     } else if let Some(q) = &suggestion.query {
                         ui.label(q);
                     }
@@ -190,8 +311,8 @@ pub fn draw_product_search_tile(ui: &mut egui::Ui, state: &mut AppState) {
                         .changed()
                         && state.product_search_use_suggestion
                     {
-                        if let Some(s) = &suggestion.sku {
-                            state.product_search_sku.clone_from(s);
+                        if !suggestion.sku.is_empty() {
+                            state.product_search_sku = suggestion.sku.join(", ");
                         }
                         if let Some(q) = &suggestion.query {
                             state.product_search_query.clone_from(q);
@@ -200,10 +321,10 @@ pub fn draw_product_search_tile(ui: &mut egui::Ui, state: &mut AppState) {
 
                     // Keep fields synced to the latest suggestion while the option is active
                     if state.product_search_use_suggestion {
-                        if let Some(s) = &suggestion.sku {
-                            state.product_search_sku.clone_from(s);
-                        } else {
+                        if suggestion.sku.is_empty() {
                             state.product_search_sku.clear();
+                        } else {
+                            state.product_search_sku = suggestion.sku.join(", ");
                         }
                         if let Some(q) = &suggestion.query {
                             state.product_search_query.clone_from(q);
@@ -222,32 +343,22 @@ pub fn draw_product_search_tile(ui: &mut egui::Ui, state: &mut AppState) {
         }
 
         if ui.add(Button::new("Submit")).clicked() {
-            // Clear previous results so UI doesn't appear stale while waiting
-            state.product_search_result_raw = None;
-            state.product_search_result_pretty.clear();
-            state.product_search_last_response = None;
-            state.product_search_show_raw = false;
-
-            // Perform search in background: spawn tokio task
-            let query = state.product_search_query.clone();
-            let sku = if state.product_search_sku.is_empty() {
-                None
-            } else {
-                Some(state.product_search_sku.clone())
-            };
-            let tx = state.background_sender.clone();
-
-            let args = SearchArgs {
-                query: if query.is_empty() { None } else { Some(query) },
-                sku,
-                no_cache: false,
-                output: OutputFormat::Json,
-            };
-            spawn_product_search(tx, args);
+            submit_search(state);
         }
 
         ui.add_space(6.0);
 
+        if let Some(description) = state.manual_description.clone() {
+            ui.horizontal(|ui| {
+                ui.label(RichText::new("Embedding:").strong());
+                ui.label(description.lines().next().unwrap_or_default());
+                if ui.small_button("Clear").clicked() {
+                    state.manual_description = None;
+                    state.manual_description_item = None;
+                }
+            });
+        }
+
         if ui.button("Copy").clicked() {
             ui.ctx().copy_text(pretty_text.clone());
         }
@@ -263,6 +374,7 @@ pub fn draw_product_search_tile(ui: &mut egui::Ui, state: &mut AppState) {
         } else {
             ui.label(RichText::new("No response yet").italics());
         }
+        draw_query_correction(ui, state);
 
         // Height left in this column:
         let remaining = ui.available_size_before_wrap().y;
@@ -275,20 +387,70 @@ pub fn draw_product_search_tile(ui: &mut egui::Ui, state: &mut AppState) {
             ScrollArea::vertical().show(ui, |ui| {
                 // Pretty listing: name and price per item
                 if let Some(ref raw) = state.product_search_result_raw {
-                    if let Some(results) = &raw.results {
-                        for item in results {
+                    let results = raw.results.as_deref().unwrap_or_default();
+                    if results.is_empty() {
+                        ui.label("No matching products");
+                    } else {
+                        ui.horizontal(|ui| {
+                            ui.label("Order:");
+                            let sort = &mut state.product_search_price_sort;
+                            ui.selectable_value(sort, PriceSort::ApiOrder, "API order");
+                            ui.selectable_value(sort, PriceSort::Ascending, "Price ⬆");
+                            ui.selectable_value(sort, PriceSort::Descending, "Price ⬇");
+                        });
+                        if let Some(summary) = PriceSummary::from_items(results) {
+                            ui.label(
+                                RichText::new(format!(
+                                    "min ${:.2} · median ${:.2} · max ${:.2}",
+                                    summary.min, summary.median, summary.max
+                                ))
+                                .italics(),
+                            );
+                        }
+
+                        let mut items: Vec<&ResultItem> = results.iter().collect();
+                        match state.product_search_price_sort {
+                            PriceSort::ApiOrder => {}
+                            PriceSort::Ascending => sort_by_price(&mut items, false),
+                            PriceSort::Descending => sort_by_price(&mut items, true),
+                        }
+                        for item in items {
                             let name = item.name.as_deref().unwrap_or("<no name>");
                             let price =
                                 item.price.as_ref().map(|p| p.0.clone()).unwrap_or_default();
+                            let key = item.key();
+                            let is_chosen =
+                                state.manual_description_item.as_deref() == Some(key.as_str());
                             ui.horizontal(|ui| {
+                                draw_result_thumbnail(
+                                    ui,
+                                    &mut state.product_thumbnails,
+                                    thumbnail_textures,
+                                    &state.runtime,
+                                    &state.background_sender,
+                                    item.thumbnail_image_url.as_deref(),
+                                );
+                                if is_chosen {
+                                    ui.label(RichText::new("✔").strong());
+                                } else if ui
+                                    .small_button("Use this")
+                                    .on_hover_text("Embed this item's details when processing")
+                                    .clicked()
+                                {
+                                    state.manual_description = Some(item.embed_description());
+                                    state.manual_description_item = Some(key.clone());
+                                }
                                 ui.label(name);
                                 ui.add_space(6.0);
                                 ui.label(RichText::new(format!("${price}")).monospace());
                             });
                         }
-                    } else {
-                        ui.label("No results");
                     }
+                } else if let Some(error) = &state.product_search_error {
+                    ui.colored_label(
+                        ui.visuals().error_fg_color,
+                        format!("Search error: {error}"),
+                    );
                 } else {
                     ui.label("No results");
                 }
@@ -2,17 +2,33 @@
 
 use crate::app_home::APP_HOME;
 use crate::gui::state::AppState;
+use crate::rename_rules::RenameRule;
+use crate::rename_rules::SkippedRuleFile;
 use eframe::egui::ScrollArea;
 use eframe::egui::{self};
+use std::path::Path;
+use tracing::error;
+use tracing::info;
 
 /// Draw the rename rules tile UI
 pub fn draw_rename_rules_tile(ui: &mut egui::Ui, state: &mut AppState) {
     if ui.checkbox(&mut state.rename_rules_enabled, "Enable rename rules").changed() {
+        if let Err(e) = crate::rename_rules::set_rules_enabled(&APP_HOME, state.rename_rules_enabled)
+        {
+            error!("Failed to save whether rename rules are enabled: {}", e);
+        }
         state.rename_preview_key = 0; // Invalidate cache
     }
     if ui.checkbox(&mut state.rename_hyphenate, "Hyphenate camelCase").changed() {
         state.rename_preview_key = 0; // Invalidate cache
     }
+    if ui.checkbox(&mut state.rename_auto_truncate, "Auto-truncate over-long names").changed() {
+        state.rename_preview_key = 0; // Invalidate cache
+    }
+    ui.add_space(4.0);
+
+    draw_skipped_rules(ui, &state.rename_rules_skipped);
+    draw_draft_rule(ui, state);
     ui.add_space(4.0);
 
     ui.label("Find & Replace Rules:");
@@ -23,6 +39,7 @@ pub fn draw_rename_rules_tile(ui: &mut egui::Ui, state: &mut AppState) {
         .auto_shrink([false, false])
         .show(ui, |ui| {
             let mut rules_to_remove = Vec::new();
+            let mut rules_to_duplicate = Vec::new();
             let mut rules_changed = Vec::new();
 
             for rule in &mut state.rename_rules {
@@ -33,6 +50,9 @@ pub fn draw_rename_rules_tile(ui: &mut egui::Ui, state: &mut AppState) {
                         if ui.small_button("✖").clicked() {
                             rules_to_remove.push(rule.id);
                         }
+                        if ui.small_button("❐").on_hover_text("Duplicate").clicked() {
+                            rules_to_duplicate.push(rule.id);
+                        }
 
                         ui.label("Find:");
                         if ui
@@ -80,13 +100,32 @@ pub fn draw_rename_rules_tile(ui: &mut egui::Ui, state: &mut AppState) {
 
             // Apply changes
             for id in rules_to_remove {
-                let _ = crate::rename_rules::remove_rule(&APP_HOME, id);
+                match crate::rename_rules::remove_rule(&APP_HOME, id) {
+                    Ok(true) => info!("Removed rename rule"),
+                    Ok(false) => {}
+                    Err(e) => error!("Failed to remove rename rule: {}", e),
+                }
                 state.rename_rules.retain(|r| r.id != id);
                 state.rename_preview_key = 0; // Invalidate cache
             }
 
+            for id in rules_to_duplicate {
+                match crate::rename_rules::duplicate_rule(&APP_HOME, id) {
+                    Ok(Some(copy)) => {
+                        info!("Duplicated rename rule");
+                        state.rename_rules.push(copy);
+                        state.rename_preview_key = 0; // Invalidate cache
+                    }
+                    Ok(None) => {}
+                    Err(e) => error!("Failed to duplicate rename rule: {}", e),
+                }
+            }
+
             for rule in rules_changed {
-                let _ = crate::rename_rules::write_rule(&APP_HOME, &rule);
+                // Saved on every keystroke, so only failures are reported
+                if let Err(e) = crate::rename_rules::write_rule(&APP_HOME, &rule) {
+                    error!("Failed to save rename rule: {}", e);
+                }
                 state.rename_preview_key = 0; // Invalidate cache
             }
 
@@ -94,10 +133,107 @@ pub fn draw_rename_rules_tile(ui: &mut egui::Ui, state: &mut AppState) {
 
             if ui.button("+ Add Rule").clicked() {
                 let new_rule = crate::rename_rules::RenameRule::default();
-                if crate::rename_rules::add_rule(&APP_HOME, &new_rule).is_ok() {
-                    state.rename_rules.push(new_rule);
-                    state.rename_preview_key = 0;
+                match crate::rename_rules::add_rule(&APP_HOME, &new_rule) {
+                    Ok(_) => {
+                        info!("Added rename rule");
+                        state.rename_rules.push(new_rule);
+                        state.rename_preview_key = 0;
+                    }
+                    Err(e) => error!("Failed to add rename rule: {}", e),
                 }
             }
         });
 }
+
+/// Editor for a rule that is tried out before it is saved. While previewing, the output tree
+/// shows it applied after the saved rules and the names it changes are listed next to the
+/// names the saved rules give.
+fn draw_draft_rule(ui: &mut egui::Ui, state: &mut AppState) {
+    ui.group(|ui| {
+        ui.horizontal_wrapped(|ui| {
+            ui.label("Draft find:");
+            ui.add(egui::TextEdit::singleline(&mut state.draft_rule.find).desired_width(150.0));
+            ui.label("Replace:");
+            ui.add(egui::TextEdit::singleline(&mut state.draft_rule.replace).desired_width(150.0));
+        });
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut state.draft_rule.case_sensitive, "case sensitive");
+            ui.checkbox(
+                &mut state.draft_rule.only_when_name_too_long,
+                "only when name too long",
+            );
+        });
+        ui.horizontal(|ui| {
+            ui.toggle_value(&mut state.draft_rule_preview, "Preview this rule")
+                .on_hover_text("Show the output tree with this rule applied after the saved ones");
+            if ui
+                .add_enabled(
+                    !state.draft_rule.find.is_empty(),
+                    egui::Button::new("Save as rule"),
+                )
+                .clicked()
+            {
+                let blank = RenameRule {
+                    only_when_name_too_long: false,
+                    ..RenameRule::default()
+                };
+                let rule = std::mem::replace(&mut state.draft_rule, blank);
+                match crate::rename_rules::add_rule(&APP_HOME, &rule) {
+                    Ok(_) => {
+                        info!("Rule saved: '{}' -> '{}'", rule.find, rule.replace);
+                        state.rename_rules.push(rule);
+                        state.draft_rule_preview = false;
+                    }
+                    Err(e) => error!("Failed to save rename rule: {}", e),
+                }
+            }
+        });
+
+        if !state.draft_rule_preview {
+            return;
+        }
+        let changes: Vec<_> = state
+            .rename_previews
+            .iter()
+            .zip(&state.draft_rename_previews)
+            .filter(|(saved, draft)| saved.renamed != draft.renamed)
+            .collect();
+        let name = |p: &Path| {
+            p.file_name()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default()
+        };
+        ui.label(format!("Changes {} name(s):", changes.len()));
+        ScrollArea::vertical()
+            .id_salt("draft_rule_changes")
+            .max_height(120.0)
+            .show(ui, |ui| {
+                for (saved, draft) in changes {
+                    ui.label(format!(
+                        "{} → {}",
+                        name(&saved.renamed),
+                        name(&draft.renamed)
+                    ));
+                }
+            });
+    });
+}
+
+/// Warn about rule files that failed to load, with the reasons on hover
+fn draw_skipped_rules(ui: &mut egui::Ui, skipped: &[SkippedRuleFile]) {
+    if skipped.is_empty() {
+        return;
+    }
+    let details = skipped
+        .iter()
+        .map(|s| format!("{}: {}", s.path.display(), s.error))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let plural = if skipped.len() == 1 { "" } else { "s" };
+    ui.colored_label(
+        egui::Color32::YELLOW,
+        format!("⚠ {} rule{plural} failed to load", skipped.len()),
+    )
+    .on_hover_text(details);
+    ui.add_space(4.0);
+}
@@ -3,10 +3,92 @@
 use crate::gui::state::AppState;
 use crate::gui::tiles::pan_zoom::PanZoomState;
 use crate::gui::tiles::pan_zoom::draw_pan_zoom_image;
+use crate::image_processing::BackgroundDetection;
+use crate::image_processing::BinarizationMode;
+use crate::image_processing::render_threshold_preview;
+use eframe::egui::ScrollArea;
 use eframe::egui::TextureHandle;
 use eframe::egui::TextureOptions;
 use eframe::egui::{self};
+use std::path::Path;
 use std::path::PathBuf;
+use tracing::warn;
+
+/// Thresholds shown side by side when comparing thresholds
+pub const MONTAGE_THRESHOLDS: [u8; 4] = [10, 20, 40, 80];
+
+/// Textures for the threshold comparison grid, rebuilt when the image or preview settings change
+#[derive(Default)]
+pub struct ThresholdMontage {
+    key: Option<MontageKey>,
+    textures: Vec<(u8, TextureHandle)>,
+}
+
+/// Inputs the montage textures were rendered from
+#[derive(Clone, PartialEq)]
+struct MontageKey {
+    path: PathBuf,
+    detection: BackgroundDetection,
+    background_override: Option<[u8; 3]>,
+    mode: BinarizationMode,
+    box_thickness: u8,
+    crop_enabled: bool,
+}
+
+impl ThresholdMontage {
+    /// Re-render the montage from the cached thumbnail of `path` if anything it depends on changed
+    fn refresh(&mut self, ctx: &egui::Context, state: &AppState, path: &Path) {
+        let key = MontageKey {
+            path: path.to_path_buf(),
+            detection: state.background_detection,
+            background_override: state.background_override,
+            mode: state.binarization_mode,
+            box_thickness: state.box_thickness,
+            crop_enabled: state.crop_to_content,
+        };
+        if self.key.as_ref() == Some(&key) {
+            return;
+        }
+        self.textures.clear();
+        // Leave the key unset until the thumbnail is cached so we retry next frame
+        let Some(cached) = state.image_cache.get(path) else {
+            return;
+        };
+        self.key = Some(key);
+        let thumbnail = match image::load_from_memory(&cached.thumbnail_data) {
+            Ok(thumbnail) => thumbnail,
+            Err(e) => {
+                warn!("Failed to decode thumbnail for {}: {}", path.display(), e);
+                return;
+            }
+        };
+
+        // The box thickness is tuned for the full preview, so scale it down for the thumbnail
+        let box_thickness = (state.box_thickness / 8).max(1);
+        for threshold in MONTAGE_THRESHOLDS {
+            let preview = render_threshold_preview(
+                &thumbnail,
+                threshold,
+                state.background_detection,
+                state.background_override,
+                state.binarization_mode,
+                box_thickness,
+                state.crop_to_content,
+            );
+            let size = [preview.width() as _, preview.height() as _];
+            let color_image = egui::ColorImage::from_rgba_unmultiplied(
+                size,
+                preview.as_flat_samples().as_slice(),
+            );
+            let texture = ctx.load_texture(
+                format!("threshold_montage_{threshold}"),
+                color_image,
+                TextureOptions::default(),
+            );
+            self.textures.push((threshold, texture));
+        }
+    }
+}
 
 /// Draw the threshold preview tile
 pub fn draw_threshold_preview_tile(
@@ -15,6 +97,7 @@ pub fn draw_threshold_preview_tile(
     threshold_texture: &mut Option<TextureHandle>,
     threshold_texture_path: &mut Option<PathBuf>,
     pan_zoom: &mut PanZoomState,
+    montage: &mut ThresholdMontage,
 ) {
     let mut should_clear = false;
 
@@ -34,11 +117,23 @@ pub fn draw_threshold_preview_tile(
 
             let response = ui.label(format!("{filename} (threshold)"));
             response.on_hover_text(input_path.display().to_string());
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                ui.checkbox(&mut state.threshold_compare, "Compare")
+                    .on_hover_text("Show several thresholds at once; click one to use it");
+            });
         });
+        if !state.crop_to_content {
+            ui.weak("Crop is off: the dashed box is a preview only");
+        }
         ui.separator();
 
-        // Show the threshold preview if we have output info
-        if let Some(ref output_info) = state.selected_output_info {
+        if state.threshold_compare {
+            let input_path = input_path.clone();
+            montage.refresh(ui.ctx(), state, &input_path);
+            draw_threshold_montage(ui, state, montage);
+        } else if let Some(ref output_info) = state.selected_output_info {
+            // Show the threshold preview if we have output info
             // Always reload the texture since we need to regenerate when settings change
             let needs_reload = threshold_texture_path.as_ref() != Some(input_path)
                 || threshold_texture.is_none()
@@ -59,7 +154,6 @@ pub fn draw_threshold_preview_tile(
                         TextureOptions::default(),
                     ));
                     *threshold_texture_path = Some(input_path.clone());
-                    pan_zoom.reset(); // Reset pan/zoom when loading new image
                 }
             }
 
@@ -110,3 +204,50 @@ pub fn draw_threshold_preview_tile(
         pan_zoom.reset();
     }
 }
+
+/// Draw the threshold comparison grid; clicking a preview makes its threshold the crop threshold
+fn draw_threshold_montage(ui: &mut egui::Ui, state: &mut AppState, montage: &ThresholdMontage) {
+    if montage.textures.is_empty() {
+        ui.vertical_centered(|ui| {
+            ui.add_space(20.0);
+            ui.spinner();
+            ui.label("Waiting for thumbnail...");
+        });
+        return;
+    }
+
+    let mut picked = None;
+    ScrollArea::vertical()
+        .id_salt("threshold_montage_scroll")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            let cell = ((ui.available_width() - ui.spacing().item_spacing.x * 3.0) / 2.0).max(32.0);
+            egui::Grid::new("threshold_montage").show(ui, |ui| {
+                for (i, (threshold, texture)) in montage.textures.iter().enumerate() {
+                    ui.vertical(|ui| {
+                        let size = texture.size_vec2() * (cell / texture.size_vec2().max_elem());
+                        let button = egui::Button::image(egui::Image::new((texture.id(), size)))
+                            .selected(*threshold == state.crop_threshold);
+                        if ui
+                            .add(button)
+                            .on_hover_text(format!("Use threshold {threshold}"))
+                            .clicked()
+                        {
+                            picked = Some(*threshold);
+                        }
+                        ui.label(format!("Threshold {threshold}"));
+                    });
+                    if i % 2 == 1 {
+                        ui.end_row();
+                    }
+                }
+            });
+        });
+
+    if let Some(threshold) = picked
+        && threshold != state.crop_threshold
+    {
+        state.crop_threshold = threshold;
+        state.update_selected_output_info();
+    }
+}
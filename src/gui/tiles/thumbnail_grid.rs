@@ -0,0 +1,102 @@
+//! Thumbnail grid tile - shows cached thumbnails of all image files in a wrapping grid
+
+use crate::gui::state::AppState;
+use crate::gui::state::LoadingState;
+use crate::gui::tree_view::thumbnail_texture;
+use eframe::egui::Color32;
+use eframe::egui::ScrollArea;
+use eframe::egui::Sense;
+use eframe::egui::TextureHandle;
+use eframe::egui::{self};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// Edge length of each thumbnail cell
+const CELL_SIZE: f32 = 96.0;
+
+/// Draw the thumbnail grid tile UI
+pub fn draw_thumbnail_grid_tile(
+    ui: &mut egui::Ui,
+    state: &mut AppState,
+    thumbnail_textures: &mut HashMap<PathBuf, TextureHandle>,
+) {
+    if state.image_files_loading.is_loading() {
+        ui.horizontal(|ui| {
+            ui.spinner();
+            ui.label("Discovering image files...");
+        });
+        return;
+    }
+
+    if let LoadingState::Failed(ref error) = state.image_files_loading {
+        ui.colored_label(Color32::RED, format!("Error: {error}"));
+        return;
+    }
+
+    if state.image_files.is_empty() {
+        ui.label("(no image files found)");
+        return;
+    }
+
+    let files = state.sorted_image_files();
+    let mut clicked_file: Option<PathBuf> = None;
+    let mut visible_uncached: Vec<PathBuf> = Vec::new();
+
+    ScrollArea::vertical()
+        .id_salt("thumbnail_grid_scroll")
+        .auto_shrink([false, false])
+        .show(ui, |ui| {
+            ui.horizontal_wrapped(|ui| {
+                for path in &files {
+                    let selected = state.selected_input_file.as_ref() == Some(path);
+                    let name = path
+                        .file_name()
+                        .map(|s| s.to_string_lossy().to_string())
+                        .unwrap_or_default();
+
+                    let frame = egui::Frame::group(ui.style()).stroke(if selected {
+                        egui::Stroke::new(2.0, Color32::LIGHT_BLUE)
+                    } else {
+                        ui.visuals().widgets.noninteractive.bg_stroke
+                    });
+                    let response = frame
+                        .show(ui, |ui| {
+                            ui.set_width(CELL_SIZE);
+                            ui.vertical_centered(|ui| {
+                                if let Some(info) = state.image_cache.get(path) {
+                                    let texture =
+                                        thumbnail_texture(ui.ctx(), thumbnail_textures, path, info);
+                                    ui.add(
+                                        egui::Image::new((texture.id(), texture.size_vec2()))
+                                            .max_size(egui::vec2(CELL_SIZE, CELL_SIZE)),
+                                    );
+                                } else {
+                                    ui.allocate_ui(egui::vec2(CELL_SIZE, CELL_SIZE), |ui| {
+                                        ui.centered_and_justified(|ui| {
+                                            ui.spinner();
+                                        });
+                                    });
+                                }
+                                ui.add(
+                                    egui::Label::new(egui::RichText::new(&name).small()).truncate(),
+                                );
+                            });
+                        })
+                        .response
+                        .interact(Sense::click())
+                        .on_hover_text(path.display().to_string());
+                    if response.clicked() {
+                        clicked_file = Some(path.clone());
+                    }
+                    if !state.image_cache.contains_key(path) && ui.is_rect_visible(response.rect) {
+                        visible_uncached.push(path.clone());
+                    }
+                }
+            });
+        });
+    state.request_visible_thumbnails(&visible_uncached);
+
+    if let Some(clicked) = clicked_file {
+        state.select_file(&clicked);
+    }
+}
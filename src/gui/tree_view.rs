@@ -1,6 +1,15 @@
 //! Tree view helper functions for displaying file hierarchies
 
+use crate::file_manager::reveal_in_file_manager;
+use crate::gui::image_cache::ImageCache;
 use crate::gui::state::CachedImageInfo;
+use crate::image_processing::ExifSummary;
+use crate::image_processing::get_output_dir;
+use crate::image_processing::read_exif_data;
+use crate::name_length_unit::name_length_unit;
+use crate::natural_sort::natural_path_cmp;
+use crate::rename_rules::RenamePreview;
+use crate::rename_rules::RenameRule;
 use eframe::egui::Color32;
 use eframe::egui::Sense;
 use eframe::egui::TextureHandle;
@@ -16,9 +25,6 @@ fn depth_to_space(depth: usize) -> f32 {
 }
 use std::path::Path;
 use std::path::PathBuf;
-#[cfg(windows)]
-use teamy_windows::shell::select::open_folder_and_select_items;
-use tracing::debug;
 
 /// A simple tree node for displaying paths hierarchically
 #[derive(Default, Debug)]
@@ -27,20 +33,64 @@ pub struct TreeNode {
     pub is_file: bool,
     /// Full path to the file (only set for leaf nodes)
     pub full_path: Option<PathBuf>,
+    /// Insertion position; children are shown in the order their paths were given
+    pub order: usize,
+}
+
+/// Expand/collapse-all state of one tree. Folder headers are keyed by `generation`, so
+/// bumping it gives every header fresh egui state that starts out at `all_open`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TreeOpenState {
+    generation: u32,
+    /// Whether every folder starts open (None = only the top levels)
+    all_open: Option<bool>,
+}
+
+impl TreeOpenState {
+    /// Open or close every folder of the tree
+    pub fn set_all_open(&mut self, open: bool) {
+        self.generation = self.generation.wrapping_add(1);
+        self.all_open = Some(open);
+    }
+
+    /// Collapsing header for a folder, open by default when `open_by_default` unless every
+    /// folder was expanded or collapsed
+    fn header(self, text: String, open_by_default: bool) -> egui::CollapsingHeader {
+        egui::CollapsingHeader::new(&text)
+            .id_salt((text, self.generation))
+            .default_open(self.all_open.unwrap_or(open_by_default))
+    }
+}
+
+/// Expand all / Collapse all buttons for a tree
+pub fn draw_expand_collapse_buttons(ui: &mut egui::Ui, open: &mut TreeOpenState) {
+    if ui.small_button("⊞ Expand all").clicked() {
+        open.set_all_open(true);
+    }
+    if ui.small_button("⊟ Collapse all").clicked() {
+        open.set_all_open(false);
+    }
 }
 
 /// Context for rendering tree nodes with image cache
 #[expect(missing_debug_implementations)]
 pub struct TreeRenderContext<'a> {
-    pub image_cache: &'a HashMap<PathBuf, CachedImageInfo>,
+    pub image_cache: &'a ImageCache,
     pub images_loading: &'a HashSet<PathBuf>,
     pub thumbnail_textures: &'a mut HashMap<PathBuf, TextureHandle>,
+    /// Prefetched EXIF per path; images the prefetch hasn't reached are read on first hover
+    pub exif_summaries: &'a mut HashMap<PathBuf, Result<ExifSummary, String>>,
+    /// Expand/collapse-all state of the tree
+    pub open_state: TreeOpenState,
+    /// Uncached images that were drawn on screen this frame, in draw order
+    pub visible_uncached: Vec<PathBuf>,
 }
 
 /// Build a tree from relative paths, storing full paths for files
 #[must_use]
 pub fn build_path_tree(paths: &[PathBuf], base_path: &Path) -> TreeNode {
     let mut root = TreeNode::default();
+    let mut next_order = 0;
 
     for path in paths {
         let mut current = &mut root;
@@ -49,7 +99,13 @@ pub fn build_path_tree(paths: &[PathBuf], base_path: &Path) -> TreeNode {
 
         for (idx, component) in components.into_iter().enumerate() {
             let name = component.as_os_str().to_string_lossy().to_string();
-            current = current.children.entry(name).or_default();
+            current = current.children.entry(name).or_insert_with(|| {
+                next_order += 1;
+                TreeNode {
+                    order: next_order,
+                    ..TreeNode::default()
+                }
+            });
 
             // Mark leaf node with full path
             if idx == len - 1 {
@@ -88,7 +144,7 @@ pub fn show_tree_children_with_cache(
 ) -> TreeResult {
     let mut result = TreeResult::default();
     let mut sorted_children: Vec<_> = node.children.iter().collect();
-    sorted_children.sort_by_key(|(k, _)| *k);
+    sorted_children.sort_by_key(|(_, child)| child.order);
 
     // We need to handle ctx mutability carefully
     if let Some(ctx) = ctx {
@@ -147,7 +203,7 @@ pub fn show_tree_node_with_cache(
     depth: usize,
     file_color: Option<Color32>,
     selected_path: Option<&PathBuf>,
-    ctx: Option<&mut TreeRenderContext<'_>>,
+    mut ctx: Option<&mut TreeRenderContext<'_>>,
 ) -> TreeResult {
     let mut result = TreeResult::default();
 
@@ -206,42 +262,20 @@ pub fn show_tree_node_with_cache(
                 result.clicked_path.clone_from(&node.full_path);
             }
 
+            if cached_info.is_none()
+                && ui.is_rect_visible(response.rect)
+                && let (Some(ctx), Some(path)) = (ctx.as_mut(), &node.full_path)
+            {
+                ctx.visible_uncached.push(path.clone());
+            }
+
             // Tooltip with thumbnail and path
             if let Some(ref path) = node.full_path {
                 let hover_response = if let Some(info) = cached_info {
                     if let Some(ctx) = ctx {
                         // Show image tooltip with thumbnail
                         let texture =
-                            ctx.thumbnail_textures
-                                .entry(path.clone())
-                                .or_insert_with(|| {
-                                    // Load thumbnail texture
-                                    if let Ok(image) = image::load_from_memory(&info.thumbnail_data)
-                                    {
-                                        let size = [image.width() as _, image.height() as _];
-                                        let rgba = image.to_rgba8();
-                                        let pixels = rgba.as_flat_samples();
-                                        let color_image = egui::ColorImage::from_rgba_unmultiplied(
-                                            size,
-                                            pixels.as_slice(),
-                                        );
-                                        ui.ctx().load_texture(
-                                            format!("thumb_{}", path.display()),
-                                            color_image,
-                                            TextureOptions::default(),
-                                        )
-                                    } else {
-                                        // Fallback: 1x1 transparent texture
-                                        ui.ctx().load_texture(
-                                            "thumb_fallback",
-                                            egui::ColorImage::new(
-                                                [1, 1],
-                                                vec![Color32::TRANSPARENT],
-                                            ),
-                                            TextureOptions::default(),
-                                        )
-                                    }
-                                });
+                            thumbnail_texture(ui.ctx(), ctx.thumbnail_textures, path, &info);
 
                         response.on_hover_ui(|ui| {
                             ui.vertical(|ui| {
@@ -249,6 +283,15 @@ pub fn show_tree_node_with_cache(
                                 ui.add_space(4.0);
                                 ui.label(format!("{}x{}", info.width, info.height));
                                 ui.label(format_size(info.file_size));
+                                let summary =
+                                    ctx.exif_summaries.entry(path.clone()).or_insert_with(|| {
+                                        read_exif_data(path).map_err(|e| e.to_string())
+                                    });
+                                if let Ok(summary) = summary {
+                                    for (label, value) in summary.snippet() {
+                                        ui.label(format!("{label}: {value}"));
+                                    }
+                                }
                                 ui.add_space(4.0);
                                 ui.label(egui::RichText::new(path.display().to_string()).small());
                             });
@@ -273,22 +316,63 @@ pub fn show_tree_node_with_cache(
         // Directory with children
         let header_text = format!("📁 {name}");
 
+        let open_state = ctx.as_ref().map(|c| c.open_state).unwrap_or_default();
         ui.horizontal(|ui| {
             ui.add_space(depth_to_space(depth));
-            egui::CollapsingHeader::new(header_text)
-                .default_open(depth < 2)
-                .show(ui, |ui| {
-                    result = show_tree_children_with_cache(ui, node, depth + 1, selected_path, ctx);
-                });
+            open_state.header(header_text, depth < 2).show(ui, |ui| {
+                result = show_tree_children_with_cache(ui, node, depth + 1, selected_path, ctx);
+            });
         });
     }
 
     result
 }
 
+/// Get (creating on first use) the texture for a cached image's thumbnail
+pub fn thumbnail_texture<'a>(
+    ctx: &egui::Context,
+    thumbnail_textures: &'a mut HashMap<PathBuf, TextureHandle>,
+    path: &Path,
+    info: &CachedImageInfo,
+) -> &'a TextureHandle {
+    thumbnail_textures
+        .entry(path.to_path_buf())
+        .or_insert_with(|| {
+            encoded_image_texture(
+                ctx,
+                format!("thumb_{}", path.display()),
+                &info.thumbnail_data,
+            )
+            .unwrap_or_else(|| {
+                // Fallback: 1x1 transparent texture
+                ctx.load_texture(
+                    "thumb_fallback",
+                    egui::ColorImage::new([1, 1], vec![Color32::TRANSPARENT]),
+                    TextureOptions::default(),
+                )
+            })
+        })
+}
+
+/// Decode encoded image bytes (PNG, JPEG, ...) into a texture, or `None` if they don't decode
+#[must_use]
+pub fn encoded_image_texture(
+    ctx: &egui::Context,
+    name: String,
+    data: &[u8],
+) -> Option<TextureHandle> {
+    let image = image::load_from_memory(data).ok()?;
+    let size = [image.width() as _, image.height() as _];
+    let rgba = image.to_rgba8();
+    let pixels = rgba.as_flat_samples();
+    let color_image = egui::ColorImage::from_rgba_unmultiplied(size, pixels.as_slice());
+    Some(ctx.load_texture(name, color_image, TextureOptions::default()))
+}
+
 /// Format file size in human-readable form
 #[expect(clippy::cast_precision_loss)]
-fn format_size(bytes: u64) -> String {
+#[must_use]
+pub fn format_size(bytes: u64) -> String {
     const KB: u64 = 1024;
     const MB: u64 = KB * 1024;
 
@@ -301,24 +385,14 @@ fn format_size(bytes: u64) -> String {
     }
 }
 
-/// Reveal `path` in the host file manager (Explorer/Finder/xdg-open).
+/// Reveal `path` in the host file manager, logging any failure.
 fn open_in_explorer(path: &Path) {
-    debug!("Opening in explorer: {}", path.display());
-
-    #[cfg(windows)]
-    {
-        // Pass the path as a single-element iterator. Passing `path` directly can
-        // cause the function to iterate the path's components (treating it like an
-        // iterator), which results in incorrect behavior (e.g., paths like `C:\`).
-        if let Err(e) = open_folder_and_select_items(&[path]) {
-            tracing::error!("Failed to open in explorer: {:?}", e);
-        }
+    if let Err(e) = reveal_in_file_manager(path) {
+        tracing::error!("{}", e);
     }
-    #[cfg(not(windows))]
-    tracing::warn!("Not implemented for this platform - open in explorer");
 }
 
-/// Group image files by which input directory they belong to.
+/// Group image files by which input directory they belong to, keeping the order of `image_files`.
 /// Returns a list of (`input_path`, `relative_file_paths`) tuples.
 #[must_use]
 pub fn group_files_by_input(
@@ -338,7 +412,6 @@ pub fn group_files_by_input(
         }
 
         if !relative_files.is_empty() {
-            relative_files.sort();
             result.push((input_path.clone(), relative_files));
         }
     }
@@ -346,6 +419,22 @@ pub fn group_files_by_input(
     result
 }
 
+/// Keep the files whose name contains `needle`, ignoring case. A blank needle keeps all.
+#[must_use]
+pub fn filter_by_file_name(files: Vec<PathBuf>, needle: &str) -> Vec<PathBuf> {
+    let needle = needle.trim().to_lowercase();
+    if needle.is_empty() {
+        return files;
+    }
+    files
+        .into_iter()
+        .filter(|f| {
+            f.file_name()
+                .is_some_and(|n| n.to_string_lossy().to_lowercase().contains(&needle))
+        })
+        .collect()
+}
+
 /// Show a group of files under an input directory
 pub fn show_input_group(
     ui: &mut egui::Ui,
@@ -378,7 +467,8 @@ pub fn show_input_group_with_cache(
 
     let header_text = format!("📁 {} ({} images)", display_name, relative_files.len());
 
-    let header = egui::CollapsingHeader::new(header_text).default_open(true);
+    let open_state = ctx.as_ref().map(|c| c.open_state).unwrap_or_default();
+    let header = open_state.header(header_text, true);
 
     let response = header.show(ui, |ui| {
         let tree = build_path_tree(relative_files, input_path);
@@ -409,45 +499,65 @@ pub struct FileRenameInfo {
     pub was_renamed: bool,
     /// Whether the file name is too long
     pub is_too_long: bool,
+    /// The rules that changed the name, as `#<position> "find" "replace"`, in the order they fired
+    pub applied_rules: Vec<String>,
+    /// Whether the last processing of this file found nothing to crop
+    pub no_crop_detected: bool,
+    /// Why the last processing of this file found the input damaged (e.g. truncated)
+    pub integrity_warning: Option<String>,
 }
 
-/// Group files with their rename status by input directory
+/// Color for files where cropping was on but nothing was trimmed
+pub const NO_CROP_COLOR: Color32 = Color32::from_rgb(0x64, 0x95, 0xED);
+
+/// Color for files that decoded but look damaged
+pub const SUSPICIOUS_COLOR: Color32 = Color32::from_rgb(0xDA, 0x70, 0xD6);
+
+/// Group files with their rename status by input directory, sorted by new path
+/// (numeric-aware when `natural_sort` is set). `rules` are the rules the previews were made
+/// with, used to describe which ones fired.
 #[must_use]
 pub fn group_files_with_renames(
     input_paths: &[PathBuf],
-    original_files: &[PathBuf],
-    renamed_files: &[PathBuf],
-    max_name_length: usize,
+    previews: &[RenamePreview],
+    rules: &[RenameRule],
+    natural_sort: bool,
 ) -> Vec<(PathBuf, Vec<FileRenameInfo>)> {
     let mut result: Vec<(PathBuf, Vec<FileRenameInfo>)> = Vec::new();
 
     for input_path in input_paths {
         let mut files_info = Vec::new();
 
-        for (original, renamed) in original_files.iter().zip(renamed_files.iter()) {
+        for preview in previews {
             if let (Ok(_orig_relative), Ok(new_relative)) = (
-                original.strip_prefix(input_path),
-                renamed.strip_prefix(input_path),
+                preview.original.strip_prefix(input_path),
+                preview.renamed.strip_prefix(input_path),
             ) {
-                let orig_name = original.file_name().and_then(|s| s.to_str()).unwrap_or("");
-                let new_name = new_relative
-                    .file_name()
-                    .and_then(|s| s.to_str())
-                    .unwrap_or("");
-                let was_renamed = orig_name != new_name;
-                let is_too_long = new_name.len() > max_name_length;
-
                 files_info.push(FileRenameInfo {
-                    original_input_path: original.clone(),
+                    original_input_path: preview.original.clone(),
                     new_path: new_relative.to_path_buf(),
-                    was_renamed,
-                    is_too_long,
+                    was_renamed: preview.changed,
+                    is_too_long: preview.too_long,
+                    applied_rules: preview
+                        .applied_rules
+                        .iter()
+                        .filter_map(|id| {
+                            let pos = rules.iter().position(|r| r.id == *id)?;
+                            Some(format!("#{} {}", pos + 1, rules[pos]))
+                        })
+                        .collect(),
+                    no_crop_detected: false,
+                    integrity_warning: None,
                 });
             }
         }
 
         if !files_info.is_empty() {
-            files_info.sort_by(|a, b| a.new_path.cmp(&b.new_path));
+            if natural_sort {
+                files_info.sort_by(|a, b| natural_path_cmp(&a.new_path, &b.new_path));
+            } else {
+                files_info.sort_by(|a, b| a.new_path.cmp(&b.new_path));
+            }
             result.push((input_path.clone(), files_info));
         }
     }
@@ -457,20 +567,28 @@ pub fn group_files_with_renames(
 
 /// A tree node for renamed files with rename status
 #[derive(Default, Debug)]
+#[expect(clippy::struct_excessive_bools)]
 pub struct RenameTreeNode {
     pub children: HashMap<String, RenameTreeNode>,
     pub is_file: bool,
     pub was_renamed: bool,
     pub is_too_long: bool,
+    pub no_crop_detected: bool,
+    pub integrity_warning: Option<String>,
+    /// Descriptions of the rename rules that fired on this file
+    pub applied_rules: Vec<String>,
     /// The original input file path (for selection tracking)
     pub original_input_path: Option<PathBuf>,
     pub full_path: Option<PathBuf>,
+    /// Insertion position; children are shown in the order their files were given
+    pub order: usize,
 }
 
 /// Build a tree from files with rename info
 #[must_use]
 pub fn build_rename_tree(files: &[FileRenameInfo], input_path: &Path) -> RenameTreeNode {
     let mut root = RenameTreeNode::default();
+    let mut next_order = 0;
 
     for file_info in files {
         let mut current = &mut root;
@@ -479,12 +597,23 @@ pub fn build_rename_tree(files: &[FileRenameInfo], input_path: &Path) -> RenameT
 
         for (idx, component) in components.into_iter().enumerate() {
             let name = component.as_os_str().to_string_lossy().to_string();
-            current = current.children.entry(name).or_default();
+            current = current.children.entry(name).or_insert_with(|| {
+                next_order += 1;
+                RenameTreeNode {
+                    order: next_order,
+                    ..RenameTreeNode::default()
+                }
+            });
 
             if idx == len - 1 {
                 current.is_file = true;
                 current.was_renamed = file_info.was_renamed;
                 current.is_too_long = file_info.is_too_long;
+                current.applied_rules.clone_from(&file_info.applied_rules);
+                current.no_crop_detected = file_info.no_crop_detected;
+                current
+                    .integrity_warning
+                    .clone_from(&file_info.integrity_warning);
                 current.original_input_path = Some(file_info.original_input_path.clone());
                 current.full_path = Some(input_path.join(&file_info.new_path));
             }
@@ -500,13 +629,15 @@ pub fn show_rename_tree_children(
     node: &RenameTreeNode,
     depth: usize,
     selected_path: Option<&PathBuf>,
+    open_state: TreeOpenState,
 ) -> TreeResult {
     let mut result = TreeResult::default();
     let mut sorted_children: Vec<_> = node.children.iter().collect();
-    sorted_children.sort_by_key(|(k, _)| *k);
+    sorted_children.sort_by_key(|(_, child)| child.order);
 
     for (child_name, child_node) in sorted_children {
-        let child_result = show_rename_tree_node(ui, child_name, child_node, depth, selected_path);
+        let child_result =
+            show_rename_tree_node(ui, child_name, child_node, depth, selected_path, open_state);
         if child_result.clicked_path.is_some() {
             result = child_result;
         }
@@ -522,15 +653,21 @@ pub fn show_rename_tree_node(
     node: &RenameTreeNode,
     depth: usize,
     selected_path: Option<&PathBuf>,
+    open_state: TreeOpenState,
 ) -> TreeResult {
     let mut result = TreeResult::default();
 
     if node.children.is_empty() {
-        // Leaf node (file) - red if too long, orange if renamed, green otherwise
+        // Leaf node (file) - violet if the input looks damaged, red if too long, blue if
+        // nothing was cropped, orange if renamed, green otherwise
         ui.horizontal(|ui| {
             ui.add_space(depth_to_space(depth));
-            let color = if node.is_too_long {
+            let color = if node.integrity_warning.is_some() {
+                SUSPICIOUS_COLOR
+            } else if node.is_too_long {
                 Color32::RED
+            } else if node.no_crop_detected {
+                NO_CROP_COLOR
             } else if node.was_renamed {
                 Color32::from_rgb(0xFF, 0xA5, 0x00) // Orange
             } else {
@@ -543,7 +680,12 @@ pub fn show_rename_tree_node(
                 .as_ref()
                 .is_some_and(|p| Some(p) == selected_path);
 
-            let label_text = format!("🖼 {} ({})", name, name.len());
+            let icon = if node.integrity_warning.is_some() {
+                "⚠"
+            } else {
+                "🖼"
+            };
+            let label_text = format!("{} {} ({})", icon, name, name.len());
             let response = if is_selected {
                 ui.add(
                     egui::Label::new(egui::RichText::new(&label_text).color(color).underline())
@@ -562,50 +704,57 @@ pub fn show_rename_tree_node(
             }
 
             // Tooltip with output path info
-            if let Some(ref path) = node.full_path {
+            let response = if let Some(ref path) = node.full_path {
                 let mut tooltip = format!("Output: {}", path.display());
                 if let Some(ref orig) = node.original_input_path {
                     let _ = write!(tooltip, "\nInput: {}", orig.display());
                 }
-                let response = response.on_hover_text(tooltip);
-
-                // Context menu to open the file in Explorer/Finder (prefer output path)
-                if let Some(open_path) = node
-                    .full_path
-                    .as_ref()
-                    .or(node.original_input_path.as_ref())
-                {
-                    response.context_menu(|ui| {
-                        if ui.button("Open in explorer").clicked() {
-                            if open_path.exists() {
-                                open_in_explorer(open_path);
-                            } else {
-                                tracing::error!(
-                                    "Cannot open in explorer: path does not exist: {}",
-                                    open_path.display()
-                                );
-                            }
-                            ui.close();
-                        }
-                    });
+                if let Some(ref warning) = node.integrity_warning {
+                    let _ = write!(tooltip, "\nWarning: {warning}");
                 }
+                if node.was_renamed && !node.applied_rules.is_empty() {
+                    tooltip.push_str("\nRenamed by:");
+                    for rule in &node.applied_rules {
+                        let _ = write!(tooltip, "\n  {rule}");
+                    }
+                }
+                response.on_hover_text(tooltip)
             } else {
-                // If only original_input_path is available (no full_path tooltip), allow context menu on the label
-                if let Some(open_path) = node.original_input_path.as_ref() {
-                    response.context_menu(|ui| {
-                        if ui.button("Open in explorer").clicked() {
-                            if open_path.exists() {
-                                open_in_explorer(open_path);
-                            } else {
-                                tracing::error!(
-                                    "Cannot open in explorer: path does not exist: {}",
-                                    open_path.display()
-                                );
-                            }
-                            ui.close();
+                response
+            };
+
+            // Context menu to open the file in Explorer/Finder (prefer output path) or copy
+            // either path
+            if let Some(open_path) = node
+                .full_path
+                .as_ref()
+                .or(node.original_input_path.as_ref())
+            {
+                response.context_menu(|ui| {
+                    if ui.button("Open in explorer").clicked() {
+                        if open_path.exists() {
+                            open_in_explorer(open_path);
+                        } else {
+                            tracing::error!(
+                                "Cannot open in explorer: path does not exist: {}",
+                                open_path.display()
+                            );
                         }
-                    });
-                }
+                        ui.close();
+                    }
+                    if let Some(output) = &node.full_path
+                        && ui.button("Copy output path").clicked()
+                    {
+                        ui.ctx().copy_text(output.display().to_string());
+                        ui.close();
+                    }
+                    if let Some(input) = &node.original_input_path
+                        && ui.button("Copy input path").clicked()
+                    {
+                        ui.ctx().copy_text(input.display().to_string());
+                        ui.close();
+                    }
+                });
             }
         });
     } else {
@@ -614,43 +763,47 @@ pub fn show_rename_tree_node(
 
         ui.horizontal(|ui| {
             ui.add_space(depth_to_space(depth));
-            egui::CollapsingHeader::new(header_text)
-                .default_open(depth < 2)
-                .show(ui, |ui| {
-                    result = show_rename_tree_children(ui, node, depth + 1, selected_path);
-                });
+            open_state.header(header_text, depth < 2).show(ui, |ui| {
+                result = show_rename_tree_children(ui, node, depth + 1, selected_path, open_state);
+            });
         });
     }
 
     result
 }
 
-/// Show a group of renamed files under an input directory
+/// Show a group of renamed files under an input directory, whose outputs go under
+/// `output_root` if one is set
 pub fn show_rename_group(
     ui: &mut egui::Ui,
     input_path: &Path,
+    output_root: Option<&Path>,
     files: &[FileRenameInfo],
     max_name_length: usize,
     selected_path: Option<&PathBuf>,
+    open_state: TreeOpenState,
 ) -> TreeResult {
     show_rename_group_with_output_path(
         ui,
-        input_path,
+        &get_output_dir(input_path, output_root),
         input_path,
         files,
         max_name_length,
         selected_path,
+        open_state,
     )
 }
 
-/// Show a group of renamed files with a custom output path display
+/// Show a group of renamed files with a custom output path display; the context menu opens
+/// `output_dir`
 pub fn show_rename_group_with_output_path(
     ui: &mut egui::Ui,
-    _input_path: &Path,
+    output_dir: &Path,
     output_path: &Path,
     files: &[FileRenameInfo],
     max_name_length: usize,
     selected_path: Option<&PathBuf>,
+    open_state: TreeOpenState,
 ) -> TreeResult {
     let mut result = TreeResult::default();
 
@@ -666,6 +819,11 @@ pub fn show_rename_group_with_output_path(
 
     let renamed_count = files.iter().filter(|f| f.was_renamed).count();
     let too_long_count = files.iter().filter(|f| f.is_too_long).count();
+    let no_crop_count = files.iter().filter(|f| f.no_crop_detected).count();
+    let suspicious_count = files
+        .iter()
+        .filter(|f| f.integrity_warning.is_some())
+        .count();
 
     let mut header_text = format!("📁 {} ({} files", display_name, files.len(),);
     if renamed_count > 0 {
@@ -674,16 +832,23 @@ pub fn show_rename_group_with_output_path(
     if too_long_count > 0 {
         let _ = write!(
             header_text,
-            ", {too_long_count} too long (>{max_name_length} chars)"
+            ", {too_long_count} too long (>{max_name_length} {})",
+            name_length_unit().name()
         );
     }
+    if no_crop_count > 0 {
+        let _ = write!(header_text, ", {no_crop_count} no crop detected");
+    }
+    if suspicious_count > 0 {
+        let _ = write!(header_text, ", {suspicious_count} possibly damaged");
+    }
     header_text.push(')');
 
-    let header = egui::CollapsingHeader::new(header_text).default_open(true);
+    let header = open_state.header(header_text, true);
 
     let response = header.show(ui, |ui| {
         let tree = build_rename_tree(files, output_path);
-        result = show_rename_tree_children(ui, &tree, 0, selected_path);
+        result = show_rename_tree_children(ui, &tree, 0, selected_path, open_state);
     });
 
     if !parent_path.is_empty() {
@@ -693,8 +858,32 @@ pub fn show_rename_group_with_output_path(
                 open_in_explorer(output_path);
                 ui.close();
             }
+            // Disabled until something has been processed into the group's output folder
+            if ui
+                .add_enabled(
+                    has_outputs(output_dir),
+                    egui::Button::new("Open output folder"),
+                )
+                .on_disabled_hover_text("Nothing has been processed for this input yet")
+                .clicked()
+            {
+                match std::fs::create_dir_all(output_dir) {
+                    Ok(()) => open_in_explorer(output_dir),
+                    Err(e) => tracing::error!(
+                        "Cannot create output folder {}: {}",
+                        output_dir.display(),
+                        e
+                    ),
+                }
+                ui.close();
+            }
         });
     }
 
     result
 }
+
+/// Whether anything has been written to `output_dir` yet
+fn has_outputs(output_dir: &Path) -> bool {
+    std::fs::read_dir(output_dir).is_ok_and(|mut entries| entries.next().is_some())
+}
@@ -0,0 +1,87 @@
+//! File extensions recognized as images when discovering input files. The list is loaded from
+//! an app home and passed to [`crate::image_processing::is_image_file`] by whoever discovers
+//! files, e.g. through [`crate::pipeline::PipelineConfig::image_extensions`].
+
+use crate::app_home::AppHome;
+use crate::atomic_write::write_atomic;
+use std::fs;
+
+const FILE_NAME: &str = "image_extensions.txt";
+
+/// Extensions recognized when no custom list has been saved (plus `heic`/`heif` when built with
+/// the `heic` feature)
+pub const DEFAULT_IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "webp", "tiff"];
+
+/// The built-in extension list as owned strings
+#[must_use]
+pub fn default_image_extensions() -> Vec<String> {
+    let defaults = DEFAULT_IMAGE_EXTENSIONS.iter();
+    #[cfg(feature = "heic")]
+    let defaults = defaults.chain(crate::image_processing::HEIF_EXTENSIONS);
+    defaults.map(ToString::to_string).collect()
+}
+
+/// Lowercase, strip leading dots, and drop blanks and duplicates (keeping the first occurrence)
+#[must_use]
+pub fn normalize_extensions<S: AsRef<str>>(extensions: &[S]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for ext in extensions {
+        let ext = ext.as_ref().trim().trim_start_matches('.').to_lowercase();
+        if !ext.is_empty() && !out.contains(&ext) {
+            out.push(ext);
+        }
+    }
+    out
+}
+
+/// Load the persisted extension list, falling back to [`DEFAULT_IMAGE_EXTENSIONS`] when none
+/// has been saved.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read.
+pub fn load_image_extensions(home: &AppHome) -> eyre::Result<Vec<String>> {
+    let path = home.file_path(FILE_NAME);
+    if !path.exists() {
+        return Ok(default_image_extensions());
+    }
+    let s = fs::read_to_string(&path)?;
+    Ok(normalize_extensions(&s.lines().collect::<Vec<_>>()))
+}
+
+/// Persist the extension list (`None` restores the defaults)
+///
+/// # Errors
+///
+/// Returns an error if the setting cannot be written or removed.
+pub fn set_image_extensions(home: &AppHome, extensions: Option<&[String]>) -> eyre::Result<()> {
+    let path = home.file_path(FILE_NAME);
+    if let Some(extensions) = extensions {
+        home.ensure_dir()?;
+        write_atomic(&path, normalize_extensions(extensions).join("\n"))?;
+    } else if path.exists() {
+        fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn defaults_until_saved_and_after_reset() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+        assert_eq!(load_image_extensions(&home)?, default_image_extensions());
+
+        let custom = vec!["PNG".to_string(), ".heic".to_string(), "png".to_string()];
+        set_image_extensions(&home, Some(&custom))?;
+        assert_eq!(load_image_extensions(&home)?, vec!["png", "heic"]);
+
+        set_image_extensions(&home, None)?;
+        assert_eq!(load_image_extensions(&home)?, default_image_extensions());
+        Ok(())
+    }
+}
@@ -1,22 +1,70 @@
 //! Image processing utilities for the CM application
 
+use crate::atomic_write::write_atomic;
 use crate::gui::state::CachedImageInfo;
+use crate::input_overrides::InputOverrides;
+use crate::process_timing::StageTimings;
+use crate::process_timing::TimingSummary;
+use crate::text_render;
+use crate::watermark::WatermarkSettings;
+use crate::watermark::apply_watermark;
+use exif::In;
+use exif::Tag;
 use eyre::Result;
 use eyre::eyre;
+use facet::Facet;
+use image::AnimationDecoder;
 use image::DynamicImage;
+use image::GrayImage;
 use image::ImageFormat;
+use image::Luma;
 use image::Rgba;
 use image::RgbaImage;
+use image::codecs::gif::GifDecoder;
+use image::codecs::webp::WebPDecoder;
 use img_parts::ImageEXIF;
+use img_parts::ImageICC;
 use img_parts::jpeg::Jpeg;
 use img_parts::png::Png;
+use img_parts::webp::WebP;
+#[cfg(feature = "heic")]
+use libheif_rs::ColorSpace;
+#[cfg(feature = "heic")]
+use libheif_rs::HeifContext;
+#[cfg(feature = "heic")]
+use libheif_rs::LibHeif;
+#[cfg(feature = "heic")]
+use libheif_rs::RgbChroma;
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::io::BufReader;
 use std::io::Cursor;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::time::Instant;
+use tracing::field::Empty;
+use tracing::info;
+use tracing::warn;
+
+/// Suffix appended to an input directory's name to form its sibling output directory
+pub const OUTPUT_DIR_SUFFIX: &str = "-output";
 
 /// Maximum preview dimension (width or height)
 const MAX_PREVIEW_SIZE: u32 = 1024;
 
+/// Skew cap used when [`ProcessingSettings::max_deskew_degrees`] is unset
+pub const DEFAULT_MAX_DESKEW_DEGREES: f32 = 10.0;
+
 /// Result of processing a single image
 #[derive(Clone, Debug)]
 pub struct ProcessedImage {
@@ -34,6 +82,9 @@ pub struct ProcessedImage {
     pub output_height: u32,
     /// Whether cropping was applied
     pub was_cropped: bool,
+    /// Cropping was requested but no content bounds smaller than the image were found, which
+    /// usually means the background was not detected (e.g. a busy or full-bleed photo)
+    pub no_crop_detected: bool,
     /// Estimated output file size
     pub estimated_size: u64,
     /// Binarized threshold preview data (PNG encoded, downsampled for preview)
@@ -42,6 +93,14 @@ pub struct ProcessedImage {
     pub output_preview_data: Vec<u8>,
     /// Crop bounds (x, y, width, height) if cropping was applied
     pub crop_bounds: Option<(u32, u32, u32, u32)>,
+    /// Time spent in each stage; decode and write are filled in by [`process_and_write`]
+    pub timings: StageTimings,
+    /// BLAKE3 hash of `data` as lowercase hex, when [`ProcessingSettings::hash_outputs`] is set
+    pub content_hash: Option<String>,
+    /// Why the input looks damaged even though it decoded, see [`integrity_warning`]
+    pub integrity_warning: Option<String>,
+    /// Set when the input embeds a non-sRGB color profile, see [`color_profile_warning`]
+    pub color_profile_warning: Option<String>,
 }
 
 /// Binarization mode for threshold preview
@@ -52,33 +111,280 @@ pub enum BinarizationMode {
     KeepBlack,
 }
 
+/// How the background color is derived from the sampled edge pixels
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BackgroundDetection {
+    /// Mean of the samples; blends into a color that may match nothing when edges are mixed
+    Average,
+    /// Most common sampled color
+    #[default]
+    Mode,
+    /// Per-channel median of the samples
+    Median,
+}
+
+impl BackgroundDetection {
+    pub const ALL: [Self; 3] = [Self::Average, Self::Mode, Self::Median];
+
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Average => "Average",
+            Self::Mode => "Most common",
+            Self::Median => "Median",
+        }
+    }
+}
+
+/// How much JPEG outputs subsample their color (chroma) channels
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum JpegSubsampling {
+    /// Whatever the encoder picks (4:2:0)
+    #[default]
+    Auto,
+    /// Full color resolution; keeps saturated edges such as red text crisp
+    S444,
+    /// Half horizontal color resolution
+    S422,
+    /// Half horizontal and vertical color resolution; smallest files
+    S420,
+}
+
+impl JpegSubsampling {
+    pub const ALL: [Self; 4] = [Self::Auto, Self::S444, Self::S422, Self::S420];
+
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Auto => "Auto",
+            Self::S444 => "4:4:4",
+            Self::S422 => "4:2:2",
+            Self::S420 => "4:2:0",
+        }
+    }
+
+    /// The sampling factor to set on the encoder, or `None` to leave its default
+    fn sampling_factor(self) -> Option<jpeg_encoder::SamplingFactor> {
+        match self {
+            Self::Auto => None,
+            Self::S444 => Some(jpeg_encoder::SamplingFactor::R_4_4_4),
+            Self::S422 => Some(jpeg_encoder::SamplingFactor::R_4_2_2),
+            Self::S420 => Some(jpeg_encoder::SamplingFactor::R_4_2_0),
+        }
+    }
+}
+
+/// What batch processing does with an input that exists but cannot be decoded as an image
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DecodeErrorPolicy {
+    /// Count it as a failure
+    #[default]
+    Error,
+    /// Leave it out of the outputs and log a warning
+    Skip,
+    /// Write the original bytes to the output path unchanged
+    CopyThrough,
+}
+
+impl DecodeErrorPolicy {
+    pub const ALL: [Self; 3] = [Self::Error, Self::Skip, Self::CopyThrough];
+
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Error => "Fail",
+            Self::Skip => "Skip",
+            Self::CopyThrough => "Copy unchanged",
+        }
+    }
+}
+
+/// What batch processing does when an output file already exists
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutputConflictPolicy {
+    /// Replace the existing file
+    #[default]
+    Overwrite,
+    /// Keep the existing file and leave the input out
+    Skip,
+    /// Write next to it with ` (1)`, ` (2)`, ... before the extension
+    Rename,
+}
+
+impl OutputConflictPolicy {
+    pub const ALL: [Self; 3] = [Self::Overwrite, Self::Skip, Self::Rename];
+
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Overwrite => "Overwrite",
+            Self::Skip => "Skip",
+            Self::Rename => "Keep both",
+        }
+    }
+}
+
 /// Image processing settings
+#[expect(clippy::struct_excessive_bools)]
 #[derive(Clone, Debug, Default)]
 pub struct ProcessingSettings {
     /// Whether to crop whitespace/transparency from images
     pub crop_to_content: bool,
     /// Threshold value for crop detection (0-255)
     pub crop_threshold: u8,
+    /// How the background color is picked from the image edges
+    pub background_detection: BackgroundDetection,
+    /// Background color to crop against instead of detecting one from the edges, for images
+    /// whose border would be mistaken for the background
+    pub background_override: Option<[u8; 3]>,
+    /// Smallest width/height a crop may produce (0 = no minimum); smaller crops are expanded
+    pub min_output_dimension: u32,
     /// Binarization preview mode
     pub binarization_mode: BinarizationMode,
     /// Thickness of the red bounding box (1-10)
     pub box_thickness: u8,
+    /// Straighten slightly rotated scans before cropping
+    pub deskew: bool,
+    /// Largest skew, in degrees, that deskewing will correct (0 = [`DEFAULT_MAX_DESKEW_DEGREES`])
+    pub max_deskew_degrees: f32,
+    /// Added to every color channel before cropping (-255 to 255, 0 = unchanged)
+    pub brightness: i32,
+    /// Contrast change in percent (-100 to 100, 0 = unchanged)
+    pub contrast: f32,
+    /// Gamma correction; above 1 brightens midtones (0 or 1 = unchanged)
+    pub gamma: f32,
+    /// Unsharp mask blur radius applied before encoding (0 = no sharpening)
+    pub sharpen_sigma: f32,
+    /// Smallest brightness difference the unsharp mask will exaggerate
+    pub sharpen_threshold: i32,
     /// JPEG quality (1-100, default 90)
     pub jpeg_quality: u8,
+    /// Write progressive rather than baseline JPEGs, which render coarse-to-fine while loading
+    pub jpeg_progressive: bool,
+    /// Chroma subsampling of JPEG outputs
+    pub jpeg_subsampling: JpegSubsampling,
+    /// PNG deflate level (1-9, higher is smaller but slower; 0 = the encoder's fast default)
+    pub png_compression: u8,
+    /// Reduce PNGs to an indexed palette of at most 256 colors
+    pub png_quantize: bool,
+    /// Format to write outputs in (None = the input's own format)
+    pub output_format: Option<ImageFormat>,
+    /// AVIF quality (1-100, 0 = default 80)
+    pub avif_quality: u8,
+    /// AVIF encoder speed (1 = slowest/smallest, 10 = fastest; 0 = default 6)
+    pub avif_speed: u8,
+    /// Text or image composited over the output
+    pub watermark: Option<WatermarkSettings>,
     /// Optional description to write to image metadata
     pub description: Option<String>,
+    /// Descriptions for particular inputs, written in place of `description` (e.g. found by a
+    /// product search on each file name)
+    pub input_descriptions: Arc<BTreeMap<PathBuf, String>>,
+    /// Optional EXIF `Artist` (photographer) to write to image metadata
+    pub artist: Option<String>,
+    /// Optional EXIF `Copyright` to write to image metadata
+    pub copyright: Option<String>,
+    /// Optional keywords (semicolon-separated) to write as EXIF `XPKeywords`
+    pub keywords: Option<String>,
+    /// Copy the source file's modified/accessed times onto the output
+    pub preserve_timestamps: bool,
+    /// How batch processing handles inputs that fail to decode
+    pub on_decode_error: DecodeErrorPolicy,
+    /// How batch processing handles outputs that already exist
+    pub on_conflict: OutputConflictPolicy,
+    /// Hash each output with BLAKE3 and write the hash next to it as `<output>.blake3`
+    pub hash_outputs: bool,
+    /// Write every output straight into the output directory with its nested directories
+    /// folded into the file name (see [`get_flat_output_path`])
+    pub flatten: bool,
+    /// Directory collecting every output as `<output_root>/<input name>/...`, instead of an
+    /// `-output` sibling of each input (see [`crate::output_root`])
+    pub output_root: Option<PathBuf>,
+    /// Decode, crop and encode as usual but create no directories and write no files, so a
+    /// batch can be checked before it is run
+    pub dry_run: bool,
+    /// Leave animated GIF/WebP inputs out instead of processing their first frame
+    pub skip_animated: bool,
+    /// Convert inputs with a non-sRGB embedded color profile to sRGB (needs the `lcms2`
+    /// feature); otherwise their pixels are kept as they are and only a warning is given
+    pub convert_to_srgb: bool,
+    /// Set to stop a batch early; inputs not started by then fail with
+    /// [`ProcessError::Cancelled`]
+    pub cancel: Option<Arc<AtomicBool>>,
+}
+
+impl ProcessingSettings {
+    /// The description written to the output of `input`
+    #[must_use]
+    pub fn description_for(&self, input: &Path) -> Option<String> {
+        self.input_descriptions
+            .get(input)
+            .cloned()
+            .or_else(|| self.description.clone())
+    }
+
+    /// Whether `cancel` has been raised
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::SeqCst))
+    }
+
+    /// The EXIF fields these settings write to the output of `input`
+    #[must_use]
+    pub fn exif_fields(&self, input: &Path) -> ExifFields {
+        ExifFields {
+            description: self.description_for(input),
+            artist: self.artist.clone(),
+            copyright: self.copyright.clone(),
+            keywords: self.keywords.clone(),
+        }
+    }
+}
+
+/// Textual EXIF fields that cm can read and write. `None` or empty values are not written; when
+/// merged into existing EXIF, `None` keeps the existing value and an empty one removes it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExifFields {
+    /// `ImageDescription` (0x010E)
+    pub description: Option<String>,
+    /// `Artist` (0x013B)
+    pub artist: Option<String>,
+    /// `Copyright` (0x8298)
+    pub copyright: Option<String>,
+    /// `XPKeywords` (0x9C9E), semicolon-separated as Windows Explorer expects
+    pub keywords: Option<String>,
+}
+
+impl ExifFields {
+    /// True when no field has a non-empty value
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        [
+            &self.description,
+            &self.artist,
+            &self.copyright,
+            &self.keywords,
+        ]
+        .iter()
+        .all(|v| v.as_deref().is_none_or(str::is_empty))
+    }
 }
 
 /// Detect the image format from the file extension
-fn detect_format_from_path(path: &Path) -> ImageFormat {
+#[must_use]
+pub fn detect_format_from_path(path: &Path) -> ImageFormat {
     path.extension()
         .and_then(|ext| ext.to_str())
         .map_or(ImageFormat::Png, |ext| match ext.to_lowercase().as_str() {
-            "jpg" | "jpeg" => ImageFormat::Jpeg,
+            // HEIF photos are re-encoded as JPEG since `image` cannot write HEIF
+            "jpg" | "jpeg" | "heic" | "heif" => ImageFormat::Jpeg,
             "webp" => ImageFormat::WebP,
             "gif" => ImageFormat::Gif,
             "bmp" => ImageFormat::Bmp,
             "tiff" | "tif" => ImageFormat::Tiff,
+            "avif" => ImageFormat::Avif,
             _ => ImageFormat::Png, // Default to PNG for unknown formats
         })
 }
@@ -102,16 +408,363 @@ fn downsample_for_preview(img: &DynamicImage) -> DynamicImage {
     img.resize(new_width, new_height, image::imageops::FilterType::Triangle)
 }
 
+/// Apply brightness, contrast and gamma through a per-channel lookup table. Alpha is left
+/// alone, and `img` is returned untouched when every adjustment is a no-op.
+#[expect(clippy::cast_possible_truncation)]
+#[expect(clippy::cast_precision_loss)]
+#[expect(clippy::cast_sign_loss)]
+fn adjust_tone(img: DynamicImage, brightness: i32, contrast: f32, gamma: f32) -> DynamicImage {
+    let gamma = if gamma > 0.0 { gamma } else { 1.0 };
+    if brightness == 0 && contrast == 0.0 && (gamma - 1.0).abs() < f32::EPSILON {
+        return img;
+    }
+    // Same contrast curve as `image::imageops::contrast`
+    let contrast = ((100.0 + contrast.clamp(-100.0, 100.0)) / 100.0).powi(2);
+    let lut: [u8; 256] = std::array::from_fn(|v| {
+        let v = (v as f32 + brightness as f32).clamp(0.0, 255.0) / 255.0;
+        let v = ((v - 0.5) * contrast + 0.5).clamp(0.0, 1.0);
+        (v.powf(1.0 / gamma) * 255.0).round() as u8
+    });
+
+    let mut rgba = img.to_rgba8();
+    for pixel in rgba.pixels_mut() {
+        for channel in &mut pixel.0[..3] {
+            *channel = lut[usize::from(*channel)];
+        }
+    }
+    DynamicImage::ImageRgba8(rgba)
+}
+
+/// Rotate `img` back by its [`estimate_skew_angle`], filling the exposed corners with the
+/// background color. The canvas keeps its size so cropping can trim the corners afterwards.
+fn deskew(img: DynamicImage, settings: &ProcessingSettings) -> DynamicImage {
+    let max_degrees = if settings.max_deskew_degrees > 0.0 {
+        settings.max_deskew_degrees
+    } else {
+        DEFAULT_MAX_DESKEW_DEGREES
+    };
+    let angle = estimate_skew_angle(
+        &img,
+        settings.crop_threshold,
+        settings.background_detection,
+        settings.background_override,
+        max_degrees,
+    );
+    if angle.abs() < 0.05 {
+        return img;
+    }
+    let rgba = img.to_rgba8();
+    let fill = background_color(
+        &rgba,
+        settings.background_detection,
+        settings.background_override,
+    );
+    DynamicImage::ImageRgba8(rotate_about_center(&rgba, -angle, fill))
+}
+
+/// Estimate how many degrees clockwise the content of a scan is rotated, within
+/// `±max_degrees`. Rotating the image by the negative of the result straightens it.
+///
+/// Uses a projection profile: content edges are projected onto rows at each candidate angle,
+/// and the angle whose row histogram is most sharply peaked wins. Content is told apart from
+/// the background the same way as when cropping.
+#[must_use]
+#[expect(clippy::cast_possible_truncation)]
+#[expect(clippy::cast_precision_loss)]
+#[expect(clippy::cast_sign_loss)]
+pub fn estimate_skew_angle(
+    img: &DynamicImage,
+    threshold: u8,
+    detection: BackgroundDetection,
+    background_override: Option<[u8; 3]>,
+    max_degrees: f32,
+) -> f32 {
+    let rgba = downsample_for_preview(img).to_rgba8();
+    let (width, height) = rgba.dimensions();
+    if max_degrees <= 0.0 || width < 3 || height < 3 {
+        return 0.0;
+    }
+    let background = background_color(&rgba, detection, background_override);
+    let is_content = |x: u32, y: u32| {
+        !is_background_pixel_with_threshold(*rgba.get_pixel(x, y), background, threshold)
+    };
+
+    // Content pixels next to background, relative to the image center
+    let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+    let mut edges = Vec::new();
+    for y in 1..height - 1 {
+        for x in 1..width - 1 {
+            if is_content(x, y)
+                && !(is_content(x - 1, y)
+                    && is_content(x + 1, y)
+                    && is_content(x, y - 1)
+                    && is_content(x, y + 1))
+            {
+                edges.push((x as f32 - cx, y as f32 - cy));
+            }
+        }
+    }
+    if edges.is_empty() {
+        return 0.0;
+    }
+
+    // Each edge is split between its two nearest rows so the score changes smoothly with angle
+    let reach = cx.hypot(cy).ceil() + 1.0;
+    let mut bins = vec![0.0f32; reach as usize * 2 + 2];
+    let mut score = |degrees: f32| {
+        let (sin, cos) = degrees.to_radians().sin_cos();
+        bins.fill(0.0);
+        for &(x, y) in &edges {
+            let row = y * cos - x * sin + reach;
+            let below = row.floor();
+            let index = below as usize;
+            bins[index] += 1.0 - (row - below);
+            bins[index + 1] += row - below;
+        }
+        bins.iter().map(|n| f64::from(n * n)).sum::<f64>()
+    };
+    let mut best_near = |center: f32, span: f32, step: f32| {
+        let steps = (span / step).round() as i32;
+        (-steps..=steps)
+            .map(|i| (center + i as f32 * step).clamp(-max_degrees, max_degrees))
+            .map(|degrees| (degrees, score(degrees)))
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(center, |(degrees, _)| degrees)
+    };
+
+    // Coarse sweep across the whole range, then refine around the best coarse angle
+    let coarse = best_near(0.0, max_degrees, 0.5);
+    best_near(coarse, 0.5, 0.05)
+}
+
+/// Rotate `img` clockwise by `degrees` about its center with bilinear sampling, keeping its
+/// size. Pixels that fall outside the source are filled with `fill`.
+#[expect(clippy::cast_possible_truncation)]
+#[expect(clippy::cast_precision_loss)]
+#[expect(clippy::cast_sign_loss)]
+fn rotate_about_center(img: &RgbaImage, degrees: f32, fill: Rgba<u8>) -> RgbaImage {
+    let (width, height) = img.dimensions();
+    let (sin, cos) = degrees.to_radians().sin_cos();
+    let (cx, cy) = (width as f32 / 2.0, height as f32 / 2.0);
+    let sample = |x: i64, y: i64| {
+        if x < 0 || y < 0 || x >= i64::from(width) || y >= i64::from(height) {
+            fill
+        } else {
+            *img.get_pixel(x as u32, y as u32)
+        }
+    };
+
+    RgbaImage::from_fn(width, height, |u, v| {
+        // Map each output pixel back to where it came from in the source
+        let dx = u as f32 + 0.5 - cx;
+        let dy = v as f32 + 0.5 - cy;
+        let sx = dx * cos + dy * sin + cx - 0.5;
+        let sy = -dx * sin + dy * cos + cy - 0.5;
+        let (x0, y0) = (sx.floor(), sy.floor());
+        let (fx, fy) = (sx - x0, sy - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+        let corners = [
+            (sample(x0, y0), (1.0 - fx) * (1.0 - fy)),
+            (sample(x0 + 1, y0), fx * (1.0 - fy)),
+            (sample(x0, y0 + 1), (1.0 - fx) * fy),
+            (sample(x0 + 1, y0 + 1), fx * fy),
+        ];
+        Rgba(std::array::from_fn(|c| {
+            let value: f32 = corners
+                .iter()
+                .map(|(pixel, weight)| f32::from(pixel[c]) * weight)
+                .sum();
+            value.round().clamp(0.0, 255.0) as u8
+        }))
+    })
+}
+
+/// Apply an unsharp mask to counter softness, or return `img` untouched when `sigma` is not
+/// positive
+fn sharpen(img: DynamicImage, sigma: f32, threshold: i32) -> DynamicImage {
+    if sigma > 0.0 {
+        img.unsharpen(sigma, threshold)
+    } else {
+        img
+    }
+}
+
+/// Extensions of HEIF containers, which the `image` crate cannot decode
+pub const HEIF_EXTENSIONS: &[&str] = &["heic", "heif"];
+
+/// Whether `path` has a HEIC/HEIF extension
+#[must_use]
+pub fn is_heif_path(path: &Path) -> bool {
+    path.extension()
+        .and_then(|s| s.to_str())
+        .is_some_and(|ext| HEIF_EXTENSIONS.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+}
+
+/// Open an image, decoding HEIC/HEIF through libheif when built with the `heic` feature.
+/// Animated GIFs and WebPs decode to their first frame.
+/// # Errors
+/// Returns an error if the image cannot be read or decoded.
+pub fn open_image(path: &Path) -> Result<DynamicImage> {
+    #[cfg(feature = "heic")]
+    if is_heif_path(path) {
+        return decode_heif(path)
+            .map_err(|e| eyre!("Failed to decode HEIF image {}: {}", path.display(), e));
+    }
+    if let Ok(mut frames) = first_frames(path, 2)
+        && !frames.is_empty()
+    {
+        if frames.len() > 1 {
+            info!("{} is animated; using its first frame", path.display());
+        }
+        return Ok(frames.swap_remove(0));
+    }
+    image::open(path).map_err(|e| eyre!("Failed to open image {}: {}", path.display(), e))
+}
+
+/// Bytes at the end of a file searched for the format's end marker
+const INTEGRITY_TAIL_LEN: u64 = 64;
+
+/// Why a decoded image looks damaged, or `None` when it looks fine: it decoded to zero pixels,
+/// or it is a JPEG or PNG whose end marker is missing. Decoders fill in the rest of a truncated
+/// file instead of failing, so such images come out partly grey or black.
+#[must_use]
+pub fn integrity_warning(path: &Path, img: &DynamicImage) -> Option<String> {
+    if img.width() == 0 || img.height() == 0 {
+        return Some(format!(
+            "Decoded to an empty {}x{} image",
+            img.width(),
+            img.height()
+        ));
+    }
+    let (head, tail) = read_head_and_tail(path).ok()?;
+    let contains = |marker: &[u8]| tail.windows(marker.len()).any(|w| w == marker);
+    match image::guess_format(&head).ok()? {
+        ImageFormat::Jpeg if !contains(&[0xFF, 0xD9]) => {
+            Some("JPEG end marker is missing, the file may be truncated".to_owned())
+        }
+        ImageFormat::Png if !contains(b"IEND") => {
+            Some("PNG end chunk is missing, the file may be truncated".to_owned())
+        }
+        _ => None,
+    }
+}
+
+/// The first bytes of the file at `path` (enough to guess its format) and the last
+/// [`INTEGRITY_TAIL_LEN`] bytes
+fn read_head_and_tail(path: &Path) -> std::io::Result<(Vec<u8>, Vec<u8>)> {
+    let mut file = std::fs::File::open(path)?;
+    let mut head = Vec::new();
+    (&mut file).take(16).read_to_end(&mut head)?;
+    let len = file.metadata()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(INTEGRITY_TAIL_LEN)))?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail)?;
+    Ok((head, tail))
+}
+
+/// Whether `path` is a GIF or WebP with more than one frame
+#[must_use]
+pub fn is_animated(path: &Path) -> bool {
+    first_frames(path, 2).is_ok_and(|frames| frames.len() > 1)
+}
+
+/// Decode up to `limit` frames of a GIF or an animated WebP; empty for anything else
+fn first_frames(path: &Path, limit: usize) -> Result<Vec<DynamicImage>> {
+    let format = image::ImageReader::open(path)?
+        .with_guessed_format()?
+        .format();
+    let file = BufReader::new(std::fs::File::open(path)?);
+    let frames = match format {
+        Some(ImageFormat::Gif) => GifDecoder::new(file)?.into_frames(),
+        Some(ImageFormat::WebP) => {
+            let decoder = WebPDecoder::new(file)?;
+            if !decoder.has_animation() {
+                return Ok(Vec::new());
+            }
+            decoder.into_frames()
+        }
+        _ => return Ok(Vec::new()),
+    };
+    frames
+        .take(limit)
+        .map(|frame| Ok(DynamicImage::ImageRgba8(frame?.into_buffer())))
+        .collect()
+}
+
+/// Decode the primary image of a HEIF container into RGBA
+#[cfg(feature = "heic")]
+fn decode_heif(path: &Path) -> Result<DynamicImage> {
+    let context = HeifContext::read_from_file(&path.to_string_lossy())?;
+    let handle = context.primary_image_handle()?;
+    let image = LibHeif::new().decode(&handle, ColorSpace::Rgb(RgbChroma::Rgba), None)?;
+    let plane = image
+        .planes()
+        .interleaved
+        .ok_or_else(|| eyre!("Decoded image has no interleaved plane"))?;
+
+    // Rows may be padded, so copy only the visible pixels of each one
+    let row_bytes = plane.width as usize * 4;
+    let mut pixels = Vec::with_capacity(row_bytes * plane.height as usize);
+    for row in plane.data.chunks(plane.stride).take(plane.height as usize) {
+        pixels.extend_from_slice(&row[..row_bytes]);
+    }
+    RgbaImage::from_raw(plane.width, plane.height, pixels)
+        .map(DynamicImage::ImageRgba8)
+        .ok_or_else(|| eyre!("Decoded image buffer has an unexpected size"))
+}
+
 /// Load and process an image according to settings
 /// # Errors
 /// Returns an error if the image cannot be loaded or processed.
 pub fn process_image(path: &Path, settings: &ProcessingSettings) -> Result<ProcessedImage> {
-    // Detect original format for output
-    let output_format = detect_format_from_path(path);
+    if settings.skip_animated && is_animated(path) {
+        return Err(eyre!(
+            "{} is animated and animated inputs are skipped",
+            path.display()
+        ));
+    }
+    process_decoded(
+        path,
+        open_image(path)?,
+        encoded_format(path, settings),
+        settings,
+    )
+}
 
-    // Load the image
-    let img =
-        image::open(path).map_err(|e| eyre!("Failed to open image {}: {}", path.display(), e))?;
+/// [`process_image`] for an image that has already been decoded from `path`, encoded as
+/// `output_format`
+fn process_decoded(
+    path: &Path,
+    img: DynamicImage,
+    output_format: ImageFormat,
+    settings: &ProcessingSettings,
+) -> Result<ProcessedImage> {
+    let crop_start = Instant::now();
+    let integrity_warning = integrity_warning(path, &img);
+    if let Some(warning) = &integrity_warning {
+        warn!("{}: {}", path.display(), warning);
+    }
+    // Outputs carry no color profile, so pixels tagged with another one look off as sRGB
+    let foreign_profile = read_icc_profile(path).filter(|icc| !is_srgb_profile(icc));
+    let color_profile_warning = foreign_profile.as_deref().map(color_profile_warning);
+    if let Some(warning) = &color_profile_warning {
+        warn!("{}: {}", path.display(), warning);
+    }
+    let img = match &foreign_profile {
+        Some(icc) if settings.convert_to_srgb => convert_to_srgb(&img, icc).unwrap_or_else(|e| {
+            warn!("{}: {}", path.display(), e);
+            img
+        }),
+        _ => img,
+    };
+
+    // Adjust tone first so crop detection sees the corrected image
+    let img = adjust_tone(img, settings.brightness, settings.contrast, settings.gamma);
+    let img = if settings.deskew {
+        deskew(img, settings)
+    } else {
+        img
+    };
 
     let original_width = img.width();
     let original_height = img.height();
@@ -126,13 +779,34 @@ pub fn process_image(path: &Path, settings: &ProcessingSettings) -> Result<Proce
     let threshold_preview_data = create_threshold_preview(
         &preview_img,
         settings.crop_threshold,
+        settings.background_detection,
+        settings.background_override,
         settings.binarization_mode,
         box_thickness,
+        settings.crop_to_content,
     )?;
 
     // Apply processing steps
+    let mut no_crop_detected = false;
     let (processed, was_cropped, crop_bounds) = if settings.crop_to_content {
-        let (cropped, bounds) = crop_to_content_with_threshold(&img, settings.crop_threshold);
+        let (mut cropped, mut bounds) = crop_to_content_with_threshold(
+            &img,
+            settings.crop_threshold,
+            settings.background_detection,
+            settings.background_override,
+        );
+        no_crop_detected = bounds == (0, 0, original_width, original_height);
+        // Grow crops that came out too small back towards the minimum size
+        let expanded = expand_to_minimum(
+            bounds,
+            original_width,
+            original_height,
+            settings.min_output_dimension,
+        );
+        if expanded != bounds {
+            bounds = expanded;
+            cropped = img.crop_imm(bounds.0, bounds.1, bounds.2, bounds.3);
+        }
         let did_crop = cropped.width() != original_width || cropped.height() != original_height;
         (
             cropped,
@@ -142,9 +816,21 @@ pub fn process_image(path: &Path, settings: &ProcessingSettings) -> Result<Proce
     } else {
         (img, false, None)
     };
+    let mut processed = sharpen(
+        processed,
+        settings.sharpen_sigma,
+        settings.sharpen_threshold,
+    );
+    if let Some(watermark) = &settings.watermark {
+        let mut rgba = processed.to_rgba8();
+        apply_watermark(&mut rgba, watermark)?;
+        processed = DynamicImage::ImageRgba8(rgba);
+    }
 
     let output_width = processed.width();
     let output_height = processed.height();
+    let crop = crop_start.elapsed();
+    let encode_start = Instant::now();
 
     // Create downsampled preview for GUI display (always PNG for fast decoding)
     let output_preview_img = downsample_for_preview(&processed);
@@ -155,19 +841,34 @@ pub fn process_image(path: &Path, settings: &ProcessingSettings) -> Result<Proce
         .map_err(|e| eyre!("Failed to encode output preview: {}", e))?;
 
     // Encode full-resolution output using the original format
-    let mut data = encode_image(&processed, output_format, settings.jpeg_quality)?;
+    let mut data = encode_image(&processed, output_format, settings)?;
 
-    // If we have a description, embed it as EXIF metadata
-    if let Some(ref description) = settings.description
-        && !description.is_empty()
-    {
+    // If we have a description or other metadata, embed it as EXIF
+    let exif_fields = settings.exif_fields(path);
+    if !exif_fields.is_empty() {
         // Read existing EXIF from source if available
         let existing_exif = read_exif_bytes(path);
-        let exif_data = merge_description_into_exif(existing_exif.as_deref(), description);
+        let exif_data = merge_fields_into_exif(existing_exif.as_deref(), &exif_fields)
+            .unwrap_or_else(|e| {
+                warn!(
+                    "Writing only cm's EXIF fields to the output of {}: {}",
+                    path.display(),
+                    e
+                );
+                create_exif_with_fields(&exif_fields)
+            });
         data = embed_exif(&data, output_format, &exif_data)?;
     }
 
     let estimated_size = data.len() as u64;
+    let content_hash = settings
+        .hash_outputs
+        .then(|| blake3::hash(&data).to_hex().to_string());
+    let timings = StageTimings {
+        crop,
+        encode: encode_start.elapsed(),
+        ..StageTimings::default()
+    };
 
     Ok(ProcessedImage {
         data,
@@ -177,19 +878,48 @@ pub fn process_image(path: &Path, settings: &ProcessingSettings) -> Result<Proce
         output_width,
         output_height,
         was_cropped,
+        no_crop_detected,
         estimated_size,
         threshold_preview_data,
         output_preview_data,
         crop_bounds,
+        timings,
+        content_hash,
+        integrity_warning,
+        color_profile_warning,
     })
 }
 
 /// Encode an image to the specified format
-fn encode_image(img: &DynamicImage, format: ImageFormat, jpeg_quality: u8) -> Result<Vec<u8>> {
+fn encode_image(
+    img: &DynamicImage,
+    format: ImageFormat,
+    settings: &ProcessingSettings,
+) -> Result<Vec<u8>> {
     let mut data = Vec::new();
     let mut cursor = Cursor::new(&mut data);
+    let jpeg_quality = settings.jpeg_quality;
 
     match format {
+        ImageFormat::Jpeg
+            if settings.jpeg_progressive || settings.jpeg_subsampling != JpegSubsampling::Auto =>
+        {
+            // The `image` crate only writes baseline 4:2:0 JPEGs
+            let quality = if jpeg_quality == 0 { 90 } else { jpeg_quality };
+            let rgb = img.to_rgb8();
+            let width = u16::try_from(rgb.width())
+                .map_err(|_| eyre!("Image is too wide for JPEG: {} px", rgb.width()))?;
+            let height = u16::try_from(rgb.height())
+                .map_err(|_| eyre!("Image is too tall for JPEG: {} px", rgb.height()))?;
+            let mut encoder = jpeg_encoder::Encoder::new(&mut cursor, quality);
+            encoder.set_progressive(settings.jpeg_progressive);
+            if let Some(factor) = settings.jpeg_subsampling.sampling_factor() {
+                encoder.set_sampling_factor(factor);
+            }
+            encoder
+                .encode(rgb.as_raw(), width, height, jpeg_encoder::ColorType::Rgb)
+                .map_err(|e| eyre!("Failed to encode JPEG: {}", e))?;
+        }
         ImageFormat::Jpeg => {
             // Use JPEG encoder with quality setting
             let quality = if jpeg_quality == 0 { 90 } else { jpeg_quality };
@@ -205,14 +935,60 @@ fn encode_image(img: &DynamicImage, format: ImageFormat, jpeg_quality: u8) -> Re
                 )
                 .map_err(|e| eyre!("Failed to encode JPEG: {}", e))?;
         }
+        #[cfg(feature = "avif")]
+        ImageFormat::Avif => {
+            let quality = if settings.avif_quality == 0 {
+                80
+            } else {
+                settings.avif_quality
+            };
+            let speed = if settings.avif_speed == 0 {
+                6
+            } else {
+                settings.avif_speed
+            };
+            let encoder = image::codecs::avif::AvifEncoder::new_with_speed_quality(
+                &mut cursor,
+                speed,
+                quality,
+            );
+            img.write_with_encoder(encoder)
+                .map_err(|e| eyre!("Failed to encode AVIF: {}", e))?;
+        }
+        #[cfg(not(feature = "avif"))]
+        ImageFormat::Avif => {
+            eyre::bail!("AVIF output requires building with the `avif` feature");
+        }
         ImageFormat::WebP => {
             // WebP uses quality-like encoding
             img.write_to(&mut cursor, ImageFormat::WebP)
                 .map_err(|e| eyre!("Failed to encode WebP: {}", e))?;
         }
+        ImageFormat::Bmp => {
+            // The BMP encoder only takes 8-bit pixels
+            let img = if img.color().has_alpha() {
+                DynamicImage::ImageRgba8(img.to_rgba8())
+            } else {
+                DynamicImage::ImageRgb8(img.to_rgb8())
+            };
+            img.write_to(&mut cursor, ImageFormat::Bmp)
+                .map_err(|e| eyre!("Failed to encode BMP: {}", e))?;
+        }
+        ImageFormat::Tiff => {
+            img.write_to(&mut cursor, ImageFormat::Tiff)
+                .map_err(|e| eyre!("Failed to encode TIFF: {}", e))?;
+        }
+        _ if settings.png_quantize => {
+            encode_indexed_png(img, settings.png_compression, &mut cursor)?;
+        }
         _ => {
             // Default to PNG for other formats (lossless)
-            img.write_to(&mut cursor, ImageFormat::Png)
+            let encoder = image::codecs::png::PngEncoder::new_with_quality(
+                &mut cursor,
+                png_compression_type(settings.png_compression),
+                image::codecs::png::FilterType::Adaptive,
+            );
+            img.write_with_encoder(encoder)
                 .map_err(|e| eyre!("Failed to encode PNG: {}", e))?;
         }
     }
@@ -220,7 +996,86 @@ fn encode_image(img: &DynamicImage, format: ImageFormat, jpeg_quality: u8) -> Re
     Ok(data)
 }
 
-/// Read existing EXIF data from a source file
+/// `image` compression for a `png_compression` setting (0 = encoder default, else level 1-9)
+fn png_compression_type(level: u8) -> image::codecs::png::CompressionType {
+    if level == 0 {
+        image::codecs::png::CompressionType::default()
+    } else {
+        image::codecs::png::CompressionType::Level(level.min(9))
+    }
+}
+
+/// Write `img` as an 8-bit indexed PNG. Images with at most 256 distinct colors keep them
+/// exactly; anything else is quantized with `NeuQuant`.
+fn encode_indexed_png(img: &DynamicImage, compression: u8, out: impl std::io::Write) -> Result<()> {
+    let rgba = img.to_rgba8();
+    let (palette, indices) = palettize(&rgba);
+
+    let mut encoder = png::Encoder::new(out, rgba.width(), rgba.height());
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_deflate_compression(if compression == 0 {
+        png::DeflateCompression::default()
+    } else {
+        png::DeflateCompression::Level(compression.min(9))
+    });
+    encoder.set_palette(
+        palette
+            .iter()
+            .flat_map(|c| [c[0], c[1], c[2]])
+            .collect::<Vec<_>>(),
+    );
+    if palette.iter().any(|c| c[3] != u8::MAX) {
+        encoder.set_trns(palette.iter().map(|c| c[3]).collect::<Vec<_>>());
+    }
+    let mut writer = encoder
+        .write_header()
+        .map_err(|e| eyre!("Failed to encode PNG: {}", e))?;
+    writer
+        .write_image_data(&indices)
+        .map_err(|e| eyre!("Failed to encode PNG: {}", e))?;
+    writer
+        .finish()
+        .map_err(|e| eyre!("Failed to encode PNG: {}", e))?;
+    Ok(())
+}
+
+/// A palette of at most 256 RGBA colors and each pixel's index into it
+fn palettize(rgba: &RgbaImage) -> (Vec<[u8; 4]>, Vec<u8>) {
+    let mut exact: HashMap<[u8; 4], u8> = HashMap::new();
+    let mut palette = Vec::new();
+    let mut indices = Vec::with_capacity(rgba.as_raw().len() / 4);
+    for pixel in rgba.pixels() {
+        let index = if let Some(&index) = exact.get(&pixel.0) {
+            index
+        } else {
+            let Ok(index) = u8::try_from(palette.len()) else {
+                break;
+            };
+            exact.insert(pixel.0, index);
+            palette.push(pixel.0);
+            index
+        };
+        indices.push(index);
+    }
+    if indices.len() == rgba.as_raw().len() / 4 {
+        return (palette, indices);
+    }
+
+    let quantizer = color_quant::NeuQuant::new(10, 256, rgba.as_raw());
+    let palette = quantizer
+        .color_map_rgba()
+        .chunks_exact(4)
+        .map(|c| [c[0], c[1], c[2], c[3]])
+        .collect();
+    let indices = rgba
+        .pixels()
+        .map(|p| u8::try_from(quantizer.index_of(&p.0)).unwrap_or(u8::MAX))
+        .collect();
+    (palette, indices)
+}
+
+/// Read existing EXIF data (TIFF-structured, without the `Exif\0\0` prefix) from a source file
 fn read_exif_bytes(path: &Path) -> Option<Vec<u8>> {
     let data = std::fs::read(path).ok()?;
     let format = detect_format_from_path(path);
@@ -234,75 +1089,556 @@ fn read_exif_bytes(path: &Path) -> Option<Vec<u8>> {
             let png = Png::from_bytes(data.into()).ok()?;
             png.exif().map(|e| e.to_vec())
         }
+        ImageFormat::WebP => {
+            let webp = WebP::from_bytes(data.into()).ok()?;
+            webp.exif().map(|e| e.to_vec())
+        }
+        // A TIFF file is itself the TIFF structure EXIF uses
+        ImageFormat::Tiff => Some(data),
         _ => None,
     }
 }
 
-/// Create a minimal EXIF segment with `ImageDescription` tag
-/// The EXIF format is complex; this creates a simple TIFF-based EXIF structure
-#[expect(clippy::cast_possible_truncation)]
-fn create_exif_with_description(description: &str) -> Vec<u8> {
-    // EXIF uses TIFF format. We'll create a minimal structure:
-    // - TIFF header (8 bytes)
-    // - IFD0 with ImageDescription tag (0x010E)
+/// The ICC color profile embedded in a JPEG, PNG or WebP file, if it has one
+#[must_use]
+pub fn read_icc_profile(path: &Path) -> Option<Vec<u8>> {
+    let data = std::fs::read(path).ok()?;
+    match image::guess_format(&data).ok()? {
+        ImageFormat::Jpeg => Jpeg::from_bytes(data.into()).ok()?.icc_profile(),
+        ImageFormat::Png => Png::from_bytes(data.into()).ok()?.icc_profile(),
+        ImageFormat::WebP => WebP::from_bytes(data.into()).ok()?.icc_profile(),
+        _ => None,
+    }
+    .map(|icc| icc.to_vec())
+}
 
-    let desc_bytes = description.as_bytes();
-    let desc_len = desc_bytes.len() as u32 + 1; // +1 for null terminator
+/// Big-endian `u32` at `at`, as an offset or length
+fn icc_u32(bytes: &[u8], at: usize) -> Option<usize> {
+    let b = bytes.get(at..at.checked_add(4)?)?;
+    usize::try_from(u32::from_be_bytes([b[0], b[1], b[2], b[3]])).ok()
+}
 
-    // Calculate offsets
-    let ifd0_offset: u32 = 8; // Right after TIFF header
-    let ifd0_entries: u16 = 1; // Just ImageDescription
-    let ifd0_size = 2 + 12 * ifd0_entries as usize + 4; // entry count + entries + next IFD pointer
-    let data_offset: u32 = ifd0_offset + ifd0_size as u32;
+/// The name an ICC profile gives itself in its `desc` tag, read from either the ICC v2
+/// `desc` type (ASCII) or the v4 `mluc` type (first UTF-16 record)
+#[must_use]
+pub fn icc_profile_description(icc: &[u8]) -> Option<String> {
+    let tag_count = icc_u32(icc, 128)?;
+    let (offset, size) = (0..tag_count.min(256))
+        .map(|i| 132 + i * 12)
+        .find(|&entry| icc.get(entry..entry + 4) == Some(b"desc".as_slice()))
+        .and_then(|entry| Some((icc_u32(icc, entry + 4)?, icc_u32(icc, entry + 8)?)))?;
+    let tag = icc.get(offset..offset.checked_add(size)?)?;
+    let text = match tag.get(..4)? {
+        b"desc" => {
+            let len = icc_u32(tag, 8)?;
+            String::from_utf8_lossy(tag.get(12..12usize.checked_add(len)?)?).into_owned()
+        }
+        b"mluc" => {
+            let len = icc_u32(tag, 20)?;
+            let start = icc_u32(tag, 24)?;
+            let units: Vec<u16> = tag
+                .get(start..start.checked_add(len)?)?
+                .chunks_exact(2)
+                .map(|c| u16::from_be_bytes([c[0], c[1]]))
+                .collect();
+            String::from_utf16_lossy(&units)
+        }
+        _ => return None,
+    };
+    let text = text.trim_end_matches('\0').trim();
+    (!text.is_empty()).then(|| text.to_owned())
+}
 
-    let mut exif = Vec::new();
+/// Whether an ICC profile describes sRGB, or isn't an RGB profile at all (grayscale and CMYK
+/// profiles are left alone). Profiles are recognized by name, as sRGB profiles from different
+/// vendors differ byte for byte.
+#[must_use]
+pub fn is_srgb_profile(icc: &[u8]) -> bool {
+    if icc.get(16..20) != Some(b"RGB ".as_slice()) {
+        return true;
+    }
+    icc_profile_description(icc).is_some_and(|name| {
+        let name = name.to_ascii_lowercase();
+        name.contains("srgb") || name.contains("iec61966-2")
+    })
+}
 
-    // TIFF header (little-endian)
-    exif.extend_from_slice(b"II"); // Little-endian marker
-    exif.extend_from_slice(&42u16.to_le_bytes()); // TIFF magic number
-    exif.extend_from_slice(&ifd0_offset.to_le_bytes()); // Offset to IFD0
+/// Warning for an input whose embedded profile `icc` is not sRGB
+#[must_use]
+pub fn color_profile_warning(icc: &[u8]) -> String {
+    let name = icc_profile_description(icc).unwrap_or_else(|| "unnamed".to_owned());
+    format!("Embedded color profile \"{name}\" is not sRGB; colors may shift")
+}
 
-    // IFD0
-    exif.extend_from_slice(&ifd0_entries.to_le_bytes()); // Number of entries
+/// Convert `img` from the color space of the ICC profile `icc` to sRGB
+#[cfg(feature = "lcms2")]
+fn convert_to_srgb(img: &DynamicImage, icc: &[u8]) -> Result<DynamicImage> {
+    let source = lcms2::Profile::new_icc(icc)
+        .map_err(|e| eyre!("Failed to read the embedded color profile: {}", e))?;
+    let transform: lcms2::Transform<[u8; 4], [u8; 4]> = lcms2::Transform::new(
+        &source,
+        lcms2::PixelFormat::RGBA_8,
+        &lcms2::Profile::new_srgb(),
+        lcms2::PixelFormat::RGBA_8,
+        lcms2::Intent::Perceptual,
+    )
+    .map_err(|e| {
+        eyre!(
+            "Failed to convert the embedded color profile to sRGB: {}",
+            e
+        )
+    })?;
+    let mut rgba = img.to_rgba8();
+    let mut pixels: Vec<[u8; 4]> = rgba.pixels().map(|p| p.0).collect();
+    transform.transform_in_place(&mut pixels);
+    for (pixel, converted) in rgba.pixels_mut().zip(pixels) {
+        pixel.0 = converted;
+    }
+    Ok(DynamicImage::ImageRgba8(rgba))
+}
 
-    // ImageDescription tag (0x010E)
-    exif.extend_from_slice(&0x010Eu16.to_le_bytes()); // Tag
-    exif.extend_from_slice(&2u16.to_le_bytes()); // Type: ASCII
-    exif.extend_from_slice(&desc_len.to_le_bytes()); // Count
-    if desc_len <= 4 {
-        // Value fits in offset field
-        let mut value = [0u8; 4];
-        value[..desc_bytes.len()].copy_from_slice(desc_bytes);
-        exif.extend_from_slice(&value);
-    } else {
-        // Value stored at data_offset
-        exif.extend_from_slice(&data_offset.to_le_bytes());
+#[cfg(not(feature = "lcms2"))]
+fn convert_to_srgb(_img: &DynamicImage, _icc: &[u8]) -> Result<DynamicImage> {
+    eyre::bail!("Converting to sRGB requires building with the `lcms2` feature")
+}
+
+/// Parse the EXIF metadata of an image file (JPEG, PNG, WebP or TIFF; other containers
+/// supported by the `exif` crate are tried as a fallback)
+/// # Errors
+/// Returns an error if the file cannot be read or contains no parseable EXIF.
+pub fn read_exif(path: &Path) -> Result<exif::Exif> {
+    if let Some(raw) = read_exif_bytes(path) {
+        return exif::Reader::new()
+            .read_raw(raw)
+            .map_err(|e| eyre!("Failed to read EXIF: {}", e));
     }
+    let file = std::fs::File::open(path).map_err(|e| eyre!("Failed to open file: {}", e))?;
+    exif::Reader::new()
+        .read_from_container(&mut std::io::BufReader::new(file))
+        .map_err(|e| eyre!("Failed to read EXIF: {}", e))
+}
 
-    // Next IFD pointer (0 = no more IFDs)
-    exif.extend_from_slice(&0u32.to_le_bytes());
+/// EXIF of an image organized by IFD (Image File Directory), kept by the GUI so the
+/// description tile and tooltips don't re-read the file
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExifSummary {
+    /// Primary image data (IFD0)
+    pub primary: BTreeMap<String, String>,
+    /// EXIF-specific data
+    pub exif: BTreeMap<String, String>,
+    /// GPS data
+    pub gps: BTreeMap<String, String>,
+    /// Thumbnail data (IFD1)
+    pub thumbnail: BTreeMap<String, String>,
+    /// Interoperability data
+    pub interop: BTreeMap<String, String>,
+    /// Maker notes (often proprietary)
+    pub maker_notes: BTreeMap<String, String>,
+}
 
-    // Description data (if longer than 4 bytes)
-    if desc_len > 4 {
-        exif.extend_from_slice(desc_bytes);
-        exif.push(0); // Null terminator
+impl ExifSummary {
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.primary.is_empty()
+            && self.exif.is_empty()
+            && self.gps.is_empty()
+            && self.thumbnail.is_empty()
+            && self.interop.is_empty()
+            && self.maker_notes.is_empty()
     }
 
-    exif
+    /// A few identifying fields (capture date, camera) as label/value pairs, for tooltips
+    #[must_use]
+    pub fn snippet(&self) -> Vec<(&'static str, String)> {
+        [("Taken", Tag::DateTimeOriginal), ("Camera", Tag::Model)]
+            .into_iter()
+            .filter_map(|(label, tag)| {
+                let value = self.primary.get(&tag.to_string())?;
+                let value = value.trim_matches('"').trim();
+                (!value.is_empty()).then(|| (label, value.to_owned()))
+            })
+            .collect()
+    }
 }
 
-/// Merge a description into existing EXIF data, or create new EXIF with just the description
-fn merge_description_into_exif(existing_exif: Option<&[u8]>, description: &str) -> Vec<u8> {
-    // For simplicity, we just create new EXIF with the description
-    // A more sophisticated implementation would parse and modify existing EXIF
-    // but that's quite complex. The description will be the main metadata we care about.
-    let _ = existing_exif; // Acknowledge but don't use for now
-    create_exif_with_description(description)
+/// Read every EXIF field of an image file, grouped by IFD
+/// # Errors
+/// Returns an error if the file cannot be read or contains no parseable EXIF.
+pub fn read_exif_data(path: &Path) -> Result<ExifSummary> {
+    let exif = read_exif(path)?;
+
+    let mut data = ExifSummary::default();
+
+    for field in exif.fields() {
+        let tag_name = field.tag.to_string();
+        let value = field.display_value().with_unit(&exif).to_string();
+
+        // Categorize by IFD
+        match field.ifd_num {
+            In::PRIMARY => {
+                data.primary.insert(tag_name, value);
+            }
+            In::THUMBNAIL => {
+                data.thumbnail.insert(tag_name, value);
+            }
+            _ => {
+                // Check for specific tag categories
+                if is_gps_tag(field.tag) {
+                    data.gps.insert(tag_name, value);
+                } else if is_exif_tag(field.tag) {
+                    data.exif.insert(tag_name, value);
+                } else if is_interop_tag(field.tag) {
+                    data.interop.insert(tag_name, value);
+                } else if is_maker_note_tag(field.tag) {
+                    data.maker_notes.insert(tag_name, value);
+                } else {
+                    // Default to primary for unknown IFDs
+                    data.primary.insert(tag_name, value);
+                }
+            }
+        }
+    }
+
+    Ok(data)
 }
 
-/// Embed EXIF data into image bytes
-fn embed_exif(image_data: &[u8], format: ImageFormat, exif_data: &[u8]) -> Result<Vec<u8>> {
-    match format {
+/// Check if a tag is GPS-related
+fn is_gps_tag(tag: Tag) -> bool {
+    matches!(
+        tag,
+        Tag::GPSVersionID
+            | Tag::GPSLatitudeRef
+            | Tag::GPSLatitude
+            | Tag::GPSLongitudeRef
+            | Tag::GPSLongitude
+            | Tag::GPSAltitudeRef
+            | Tag::GPSAltitude
+            | Tag::GPSTimeStamp
+            | Tag::GPSSatellites
+            | Tag::GPSStatus
+            | Tag::GPSMeasureMode
+            | Tag::GPSDOP
+            | Tag::GPSSpeedRef
+            | Tag::GPSSpeed
+            | Tag::GPSTrackRef
+            | Tag::GPSTrack
+            | Tag::GPSImgDirectionRef
+            | Tag::GPSImgDirection
+            | Tag::GPSMapDatum
+            | Tag::GPSDestLatitudeRef
+            | Tag::GPSDestLatitude
+            | Tag::GPSDestLongitudeRef
+            | Tag::GPSDestLongitude
+            | Tag::GPSDestBearingRef
+            | Tag::GPSDestBearing
+            | Tag::GPSDestDistanceRef
+            | Tag::GPSDestDistance
+            | Tag::GPSProcessingMethod
+            | Tag::GPSAreaInformation
+            | Tag::GPSDateStamp
+            | Tag::GPSDifferential
+    )
+}
+
+/// Check if a tag is EXIF-specific
+fn is_exif_tag(tag: Tag) -> bool {
+    matches!(
+        tag,
+        Tag::ExposureTime
+            | Tag::FNumber
+            | Tag::ExposureProgram
+            | Tag::SpectralSensitivity
+            | Tag::PhotographicSensitivity
+            | Tag::SensitivityType
+            | Tag::StandardOutputSensitivity
+            | Tag::RecommendedExposureIndex
+            | Tag::ISOSpeed
+            | Tag::ISOSpeedLatitudeyyy
+            | Tag::ISOSpeedLatitudezzz
+            | Tag::ExifVersion
+            | Tag::DateTimeOriginal
+            | Tag::DateTimeDigitized
+            | Tag::OffsetTime
+            | Tag::OffsetTimeOriginal
+            | Tag::OffsetTimeDigitized
+            | Tag::ComponentsConfiguration
+            | Tag::CompressedBitsPerPixel
+            | Tag::ShutterSpeedValue
+            | Tag::ApertureValue
+            | Tag::BrightnessValue
+            | Tag::ExposureBiasValue
+            | Tag::MaxApertureValue
+            | Tag::SubjectDistance
+            | Tag::MeteringMode
+            | Tag::LightSource
+            | Tag::Flash
+            | Tag::FocalLength
+            | Tag::SubjectArea
+            | Tag::MakerNote
+            | Tag::UserComment
+            | Tag::SubSecTime
+            | Tag::SubSecTimeOriginal
+            | Tag::SubSecTimeDigitized
+            | Tag::FlashpixVersion
+            | Tag::ColorSpace
+            | Tag::PixelXDimension
+            | Tag::PixelYDimension
+            | Tag::RelatedSoundFile
+            | Tag::FlashEnergy
+            | Tag::FocalPlaneXResolution
+            | Tag::FocalPlaneYResolution
+            | Tag::FocalPlaneResolutionUnit
+            | Tag::SubjectLocation
+            | Tag::ExposureIndex
+            | Tag::SensingMethod
+            | Tag::FileSource
+            | Tag::SceneType
+            | Tag::CFAPattern
+            | Tag::CustomRendered
+            | Tag::ExposureMode
+            | Tag::WhiteBalance
+            | Tag::DigitalZoomRatio
+            | Tag::FocalLengthIn35mmFilm
+            | Tag::SceneCaptureType
+            | Tag::GainControl
+            | Tag::Contrast
+            | Tag::Saturation
+            | Tag::Sharpness
+            | Tag::DeviceSettingDescription
+            | Tag::SubjectDistanceRange
+            | Tag::ImageUniqueID
+            | Tag::CameraOwnerName
+            | Tag::BodySerialNumber
+            | Tag::LensSpecification
+            | Tag::LensMake
+            | Tag::LensModel
+            | Tag::LensSerialNumber
+            | Tag::Gamma
+    )
+}
+
+/// Check if a tag is interoperability-related
+fn is_interop_tag(tag: Tag) -> bool {
+    matches!(
+        tag,
+        Tag::InteroperabilityIndex | Tag::InteroperabilityVersion
+    )
+}
+
+/// Check if a tag is a maker note
+fn is_maker_note_tag(tag: Tag) -> bool {
+    matches!(tag, Tag::MakerNote)
+}
+
+/// EXIF tag number of `XPKeywords`, which the `exif` crate has no constant for
+const XP_KEYWORDS_TAG: u16 = 0x9C9E;
+
+/// A single IFD0 entry for [`create_exif_with_fields`]
+struct IfdEntry {
+    tag: u16,
+    /// TIFF field type (1 = BYTE, 2 = ASCII)
+    field_type: u16,
+    /// Complete value bytes, including any terminator. The entry count is their length, so
+    /// the count always matches the bytes written, whether inline or in the data area.
+    value: Vec<u8>,
+}
+
+impl IfdEntry {
+    /// ASCII entry: the text followed by its null terminator
+    fn ascii(tag: u16, text: &str) -> Self {
+        let mut value = text.as_bytes().to_vec();
+        value.push(0);
+        Self {
+            tag,
+            field_type: 2,
+            value,
+        }
+    }
+
+    /// `XP*` entry: BYTE-typed UTF-16LE text followed by a UTF-16 null terminator
+    fn xp(tag: u16, text: &str) -> Self {
+        let mut value: Vec<u8> = text.encode_utf16().flat_map(u16::to_le_bytes).collect();
+        value.extend_from_slice(&[0, 0]);
+        Self {
+            tag,
+            field_type: 1,
+            value,
+        }
+    }
+}
+
+/// Create a minimal EXIF segment with the given textual fields in IFD0
+/// The EXIF format is complex; this creates a simple TIFF-based EXIF structure
+#[expect(clippy::cast_possible_truncation)]
+fn create_exif_with_fields(fields: &ExifFields) -> Vec<u8> {
+    // EXIF uses TIFF format. We'll create a minimal structure:
+    // - TIFF header (8 bytes)
+    // - IFD0 with one entry per field, sorted by tag as TIFF requires
+    // - Data area holding values that don't fit in an entry's 4-byte value field
+
+    let mut entries = Vec::new();
+    if let Some(description) = fields.description.as_deref().filter(|s| !s.is_empty()) {
+        entries.push(IfdEntry::ascii(0x010E, description));
+    }
+    if let Some(artist) = fields.artist.as_deref().filter(|s| !s.is_empty()) {
+        entries.push(IfdEntry::ascii(0x013B, artist));
+    }
+    if let Some(copyright) = fields.copyright.as_deref().filter(|s| !s.is_empty()) {
+        entries.push(IfdEntry::ascii(0x8298, copyright));
+    }
+    if let Some(keywords) = fields.keywords.as_deref().filter(|s| !s.is_empty()) {
+        entries.push(IfdEntry::xp(XP_KEYWORDS_TAG, keywords));
+    }
+
+    // Calculate offsets
+    let ifd0_offset: u32 = 8; // Right after TIFF header
+    let ifd0_entries = entries.len() as u16;
+    let ifd0_size = 2 + 12 * ifd0_entries as usize + 4; // entry count + entries + next IFD pointer
+    let data_offset: u32 = ifd0_offset + ifd0_size as u32;
+
+    let mut exif = Vec::new();
+    let mut data = Vec::new();
+
+    // TIFF header (little-endian)
+    exif.extend_from_slice(b"II"); // Little-endian marker
+    exif.extend_from_slice(&42u16.to_le_bytes()); // TIFF magic number
+    exif.extend_from_slice(&ifd0_offset.to_le_bytes()); // Offset to IFD0
+
+    // IFD0
+    exif.extend_from_slice(&ifd0_entries.to_le_bytes()); // Number of entries
+    for entry in &entries {
+        exif.extend_from_slice(&entry.tag.to_le_bytes());
+        exif.extend_from_slice(&entry.field_type.to_le_bytes());
+        exif.extend_from_slice(&(entry.value.len() as u32).to_le_bytes()); // Count
+        if entry.value.len() <= 4 {
+            // Value (terminator included) fits in offset field, left-justified and zero-padded
+            let mut value = [0u8; 4];
+            value[..entry.value.len()].copy_from_slice(&entry.value);
+            exif.extend_from_slice(&value);
+        } else {
+            // Value stored in the data area; TIFF requires offsets on a word boundary
+            let offset = data_offset + data.len() as u32;
+            exif.extend_from_slice(&offset.to_le_bytes());
+            data.extend_from_slice(&entry.value);
+            if data.len() % 2 == 1 {
+                data.push(0);
+            }
+        }
+    }
+
+    // Next IFD pointer (0 = no more IFDs)
+    exif.extend_from_slice(&0u32.to_le_bytes());
+
+    exif.extend_from_slice(&data);
+    exif
+}
+
+/// Tags that describe how the source's own pixels are stored: size, sample layout, compression
+/// and where the strips or tiles are. A TIFF source's EXIF is the whole file, so these come
+/// with it, and they are wrong for the re-encoded output.
+const RASTER_TAGS: [Tag; 17] = [
+    Tag::ImageWidth,
+    Tag::ImageLength,
+    Tag::BitsPerSample,
+    Tag::Compression,
+    Tag::PhotometricInterpretation,
+    Tag::StripOffsets,
+    Tag::SamplesPerPixel,
+    Tag::RowsPerStrip,
+    Tag::StripByteCounts,
+    Tag::PlanarConfiguration,
+    // Predictor, TileWidth, TileLength, TileOffsets, TileByteCounts, ExtraSamples, SampleFormat
+    Tag(exif::Context::Tiff, 317),
+    Tag(exif::Context::Tiff, 322),
+    Tag(exif::Context::Tiff, 323),
+    Tag(exif::Context::Tiff, 324),
+    Tag(exif::Context::Tiff, 325),
+    Tag(exif::Context::Tiff, 338),
+    Tag(exif::Context::Tiff, 339),
+];
+
+/// Merge fields into existing EXIF data, or create new EXIF with just those fields.
+///
+/// Every other tag of `existing_exif` (camera, dates, GPS, orientation, the thumbnail) is kept,
+/// except the [`RASTER_TAGS`] describing the source's pixel layout.
+/// A managed tag whose field is `None` keeps its existing value; an empty field removes it.
+/// # Errors
+/// Returns an error if `existing_exif` cannot be parsed or rewritten.
+fn merge_fields_into_exif(existing_exif: Option<&[u8]>, fields: &ExifFields) -> Result<Vec<u8>> {
+    let Some(existing) = existing_exif else {
+        return Ok(create_exif_with_fields(fields));
+    };
+    let exif = exif::Reader::new()
+        .read_raw(existing.to_vec())
+        .map_err(|e| eyre!("Failed to parse existing EXIF: {}", e))?;
+
+    let managed = [
+        (Tag::ImageDescription, &fields.description),
+        (Tag::Artist, &fields.artist),
+        (Tag::Copyright, &fields.copyright),
+        (Tag(exif::Context::Tiff, XP_KEYWORDS_TAG), &fields.keywords),
+    ];
+    let dropped = |field: &exif::Field| {
+        RASTER_TAGS.contains(&field.tag)
+            || (field.ifd_num == In::PRIMARY
+                && managed
+                    .iter()
+                    .any(|(tag, value)| *tag == field.tag && value.is_some()))
+    };
+    let new_fields: Vec<exif::Field> = managed
+        .iter()
+        .filter_map(|(tag, value)| {
+            let text = value.as_deref().filter(|s| !s.is_empty())?;
+            let value = if tag.number() == XP_KEYWORDS_TAG {
+                exif::Value::Byte(IfdEntry::xp(XP_KEYWORDS_TAG, text).value)
+            } else {
+                exif::Value::Ascii(vec![text.as_bytes().to_vec()])
+            };
+            Some(exif::Field {
+                tag: *tag,
+                ifd_num: In::PRIMARY,
+                value,
+            })
+        })
+        .collect();
+
+    let mut writer = exif::experimental::Writer::new();
+    let mut field_count = 0;
+    for field in exif.fields().filter(|f| !dropped(f)).chain(&new_fields) {
+        writer.push_field(field);
+        field_count += 1;
+    }
+    if field_count == 0 {
+        return Ok(create_exif_with_fields(fields));
+    }
+    if let Some(thumbnail) = thumbnail_jpeg(&exif) {
+        writer.set_jpeg(thumbnail, In::THUMBNAIL);
+    }
+    let mut merged = Cursor::new(Vec::new());
+    writer
+        .write(&mut merged, exif.little_endian())
+        .map_err(|e| eyre!("Failed to rewrite EXIF: {}", e))?;
+    Ok(merged.into_inner())
+}
+
+/// The JPEG thumbnail stored in IFD1 of `exif`, if any
+fn thumbnail_jpeg(exif: &exif::Exif) -> Option<&[u8]> {
+    let offset = exif
+        .get_field(Tag::JPEGInterchangeFormat, In::THUMBNAIL)?
+        .value
+        .get_uint(0)?;
+    let length = exif
+        .get_field(Tag::JPEGInterchangeFormatLength, In::THUMBNAIL)?
+        .value
+        .get_uint(0)?;
+    let start = usize::try_from(offset).ok()?;
+    let end = start.checked_add(usize::try_from(length).ok()?)?;
+    exif.buf().get(start..end)
+}
+
+/// Embed EXIF data into image bytes
+fn embed_exif(image_data: &[u8], format: ImageFormat, exif_data: &[u8]) -> Result<Vec<u8>> {
+    match format {
         ImageFormat::Jpeg => {
             let mut jpeg = Jpeg::from_bytes(image_data.to_vec().into())
                 .map_err(|e| eyre!("Failed to parse JPEG for EXIF embedding: {}", e))?;
@@ -330,27 +1666,133 @@ fn embed_exif(image_data: &[u8], format: ImageFormat, exif_data: &[u8]) -> Resul
     }
 }
 
-/// Create a binarized threshold preview of the image
+/// Text of an ASCII EXIF field in the primary image, with multiple strings joined by newlines
+fn ascii_field(exif: &exif::Exif, tag: exif::Tag) -> Option<String> {
+    let field = exif.get_field(tag, exif::In::PRIMARY)?;
+    match &field.value {
+        exif::Value::Ascii(parts) => Some(
+            parts
+                .iter()
+                .map(|p| String::from_utf8_lossy(p).to_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        _ => Some(field.display_value().to_string()),
+    }
+}
+
+/// Text of a UTF-16LE `XP*` EXIF field in the primary image
+fn xp_field(exif: &exif::Exif, tag: u16) -> Option<String> {
+    let field = exif.get_field(exif::Tag(exif::Context::Tiff, tag), exif::In::PRIMARY)?;
+    let exif::Value::Byte(bytes) = &field.value else {
+        return None;
+    };
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|pair| u16::from_le_bytes([pair[0], pair[1]]))
+        .take_while(|&unit| unit != 0)
+        .collect();
+    Some(String::from_utf16_lossy(&units))
+}
+
+/// Read the textual EXIF fields cm manages from an image file; missing fields are `None`
+#[must_use]
+pub fn read_exif_fields(path: &Path) -> ExifFields {
+    let Ok(exif) = read_exif(path) else {
+        return ExifFields::default();
+    };
+    ExifFields {
+        description: ascii_field(&exif, exif::Tag::ImageDescription),
+        artist: ascii_field(&exif, exif::Tag::Artist),
+        copyright: ascii_field(&exif, exif::Tag::Copyright),
+        keywords: xp_field(&exif, XP_KEYWORDS_TAG),
+    }
+}
+
+/// Embed `fields` into the EXIF metadata of `input_path` and write the result to `output_path`
+/// (which may be the same file to edit in place). Image data is not re-encoded and the other
+/// EXIF tags are kept.
+/// # Errors
+/// Returns an error if the file cannot be read or written, its format does not support EXIF, or
+/// its existing EXIF cannot be parsed.
+pub fn write_exif_fields_to_file(
+    input_path: &Path,
+    output_path: &Path,
+    fields: &ExifFields,
+) -> Result<()> {
+    let format = detect_format_from_path(input_path);
+    if !matches!(format, ImageFormat::Jpeg | ImageFormat::Png) {
+        return Err(eyre!(
+            "Writing EXIF is only supported for JPEG and PNG: {}",
+            input_path.display()
+        ));
+    }
+    let data = std::fs::read(input_path)?;
+    let existing_exif = read_exif_bytes(input_path);
+    let exif_data = merge_fields_into_exif(existing_exif.as_deref(), fields)?;
+    let data = embed_exif(&data, format, &exif_data)?;
+    // Usually the original itself, so never leave it half-written
+    write_atomic(output_path, data)
+}
+
+/// Create a binarized threshold preview of the image, encoded as PNG
 fn create_threshold_preview(
     img: &DynamicImage,
     threshold: u8,
+    detection: BackgroundDetection,
+    background_override: Option<[u8; 3]>,
     mode: BinarizationMode,
     box_thickness: u8,
+    crop_enabled: bool,
 ) -> Result<Vec<u8>> {
+    let binary_img = render_threshold_preview(
+        img,
+        threshold,
+        detection,
+        background_override,
+        mode,
+        box_thickness,
+        crop_enabled,
+    );
+
+    // Encode to PNG
+    let mut data = Vec::new();
+    let mut cursor = Cursor::new(&mut data);
+    DynamicImage::ImageRgba8(binary_img)
+        .write_to(&mut cursor, ImageFormat::Png)
+        .map_err(|e| eyre!("Failed to encode threshold preview: {}", e))?;
+
+    Ok(data)
+}
+
+/// Binarize the image against its background (detected, or `background_override`) and draw
+/// the crop box over the content. The box is dashed when `crop_enabled` is off, since the
+/// output won't actually be cropped.
+#[must_use]
+pub fn render_threshold_preview(
+    img: &DynamicImage,
+    threshold: u8,
+    detection: BackgroundDetection,
+    background_override: Option<[u8; 3]>,
+    mode: BinarizationMode,
+    box_thickness: u8,
+    crop_enabled: bool,
+) -> RgbaImage {
     let rgba = img.to_rgba8();
     let (width, height) = rgba.dimensions();
 
     // Sample edge pixels to determine background color
-    let background_color = sample_edge_color(&rgba);
+    let background_color = background_color(&rgba, detection, background_override);
+    let threshold = effective_threshold(&rgba, threshold, background_override);
+
+    let mask = content_mask(&rgba, background_color, threshold);
 
     // Create binarized image
     let mut binary_img = RgbaImage::new(width, height);
 
     for y in 0..height {
         for x in 0..width {
-            let pixel = rgba.get_pixel(x, y);
-            let is_background =
-                is_background_pixel_with_threshold(*pixel, background_color, threshold);
+            let is_background = mask.get_pixel(x, y)[0] == 0;
 
             // Set pixel color based on mode
             let output_pixel = match mode {
@@ -375,7 +1817,7 @@ fn create_threshold_preview(
     }
 
     // Draw red bounding box if there's content to crop
-    let bounds = find_content_bounds(&rgba, background_color, threshold);
+    let bounds = find_content_bounds(width, height, |x, y| mask.get_pixel(x, y)[0] != 0);
     if let Some((min_x, min_y, max_x, max_y)) = bounds {
         draw_bounding_box(
             &mut binary_img,
@@ -384,22 +1826,38 @@ fn create_threshold_preview(
             max_x,
             max_y,
             u32::from(box_thickness),
+            !crop_enabled,
         );
     }
 
-    // Encode to PNG
-    let mut data = Vec::new();
-    let mut cursor = Cursor::new(&mut data);
-    DynamicImage::ImageRgba8(binary_img)
-        .write_to(&mut cursor, ImageFormat::Png)
-        .map_err(|e| eyre!("Failed to encode threshold preview: {}", e))?;
+    binary_img
+}
 
-    Ok(data)
+/// The color content is told apart from: `background_override` when set, otherwise the
+/// edge color picked by `detection`
+fn background_color(
+    img: &RgbaImage,
+    detection: BackgroundDetection,
+    background_override: Option<[u8; 3]>,
+) -> Rgba<u8> {
+    match background_override {
+        Some([r, g, b]) => Rgba([r, g, b, 255]),
+        None => sample_edge_color(img, detection),
+    }
 }
 
-/// Sample edge pixels to determine the most common background color
-#[expect(clippy::cast_possible_truncation)]
-fn sample_edge_color(img: &RgbaImage) -> Rgba<u8> {
+/// The crop threshold to use for `img`. When most of its edge is transparent the sampled color
+/// is meaningless (transparent pixels keep whatever RGB they were saved with), so the threshold
+/// drops to 0 and only the alpha channel separates content from background.
+fn effective_threshold(img: &RgbaImage, threshold: u8, background_override: Option<[u8; 3]>) -> u8 {
+    // The median alpha is transparent when at least half the edge samples are
+    let alpha_mask = background_override.is_none()
+        && sample_edge_color(img, BackgroundDetection::Median)[3] < 10;
+    if alpha_mask { 0 } else { threshold }
+}
+
+/// Sample edge pixels and reduce them to a single background color using `detection`
+fn sample_edge_color(img: &RgbaImage, detection: BackgroundDetection) -> Rgba<u8> {
     let (width, height) = img.dimensions();
 
     if width == 0 || height == 0 {
@@ -424,30 +1882,59 @@ fn sample_edge_color(img: &RgbaImage) -> Rgba<u8> {
         }
     }
 
-    // Return the average color (simple approach)
     if samples.is_empty() {
         return Rgba([255, 255, 255, 255]);
     }
 
-    let mut r_sum: u64 = 0;
-    let mut g_sum: u64 = 0;
-    let mut b_sum: u64 = 0;
-    let mut a_sum: u64 = 0;
-
-    for pixel in &samples {
-        r_sum += u64::from(pixel[0]);
-        g_sum += u64::from(pixel[1]);
-        b_sum += u64::from(pixel[2]);
-        a_sum += u64::from(pixel[3]);
+    match detection {
+        BackgroundDetection::Average => average_color(&samples),
+        BackgroundDetection::Mode => mode_color(&samples),
+        BackgroundDetection::Median => median_color(&samples),
     }
+}
 
+/// Per-channel mean of `samples`
+#[expect(clippy::cast_possible_truncation)]
+fn average_color(samples: &[Rgba<u8>]) -> Rgba<u8> {
+    let mut sums = [0u64; 4];
+    for pixel in samples {
+        for (sum, channel) in sums.iter_mut().zip(pixel.0) {
+            *sum += u64::from(channel);
+        }
+    }
     let count = samples.len() as u64;
-    Rgba([
-        (r_sum / count) as u8,
-        (g_sum / count) as u8,
-        (b_sum / count) as u8,
-        (a_sum / count) as u8,
-    ])
+    Rgba(sums.map(|sum| (sum / count) as u8))
+}
+
+/// Most frequent exact color in `samples`; ties go to the color seen first
+fn mode_color(samples: &[Rgba<u8>]) -> Rgba<u8> {
+    let mut counts: Vec<(Rgba<u8>, usize)> = Vec::new();
+    for pixel in samples {
+        match counts.iter_mut().find(|(color, _)| color == pixel) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((*pixel, 1)),
+        }
+    }
+    // `max_by_key` returns the last maximum, so search in reverse to prefer the first
+    counts
+        .iter()
+        .rev()
+        .max_by_key(|(_, count)| *count)
+        .map_or(Rgba([255, 255, 255, 255]), |(color, _)| *color)
+}
+
+/// Per-channel median of `samples`
+fn median_color(samples: &[Rgba<u8>]) -> Rgba<u8> {
+    let mut channels: [Vec<u8>; 4] = Default::default();
+    for pixel in samples {
+        for (values, channel) in channels.iter_mut().zip(pixel.0) {
+            values.push(channel);
+        }
+    }
+    Rgba(channels.map(|mut values| {
+        values.sort_unstable();
+        values[values.len() / 2]
+    }))
 }
 
 /// Check if a pixel is background based on threshold
@@ -473,16 +1960,26 @@ fn is_background_pixel_with_threshold(
     distance < f64::from(threshold)
 }
 
-/// Find content bounds using threshold - optimized edge-inward scanning
+/// Which pixels of `img` are content: 255 where a pixel is at least `threshold` away from
+/// `background`, 0 where it is background
+fn content_mask(img: &RgbaImage, background: Rgba<u8>, threshold: u8) -> GrayImage {
+    GrayImage::from_fn(img.width(), img.height(), |x, y| {
+        if is_background_pixel_with_threshold(*img.get_pixel(x, y), background, threshold) {
+            Luma([0])
+        } else {
+            Luma([255])
+        }
+    })
+}
+
+/// Find the bounds of the pixels for which `is_content` holds - optimized edge-inward scanning
 /// Instead of scanning every pixel, we scan from each edge inward until we find content.
 /// This is much faster for images where content is roughly centered with padding.
 fn find_content_bounds(
-    img: &RgbaImage,
-    background: Rgba<u8>,
-    threshold: u8,
+    width: u32,
+    height: u32,
+    is_content: impl Fn(u32, u32) -> bool,
 ) -> Option<(u32, u32, u32, u32)> {
-    let (width, height) = img.dimensions();
-
     if width == 0 || height == 0 {
         return None;
     }
@@ -491,8 +1988,7 @@ fn find_content_bounds(
     let mut min_y = 0u32;
     'top: for y in 0..height {
         for x in 0..width {
-            let pixel = img.get_pixel(x, y);
-            if !is_background_pixel_with_threshold(*pixel, background, threshold) {
+            if is_content(x, y) {
                 min_y = y;
                 break 'top;
             }
@@ -509,8 +2005,7 @@ fn find_content_bounds(
     let mut max_y = height - 1;
     'bottom: for y in (min_y..height).rev() {
         for x in 0..width {
-            let pixel = img.get_pixel(x, y);
-            if !is_background_pixel_with_threshold(*pixel, background, threshold) {
+            if is_content(x, y) {
                 max_y = y;
                 break 'bottom;
             }
@@ -521,8 +2016,7 @@ fn find_content_bounds(
     let mut min_x = 0u32;
     'left: for x in 0..width {
         for y in min_y..=max_y {
-            let pixel = img.get_pixel(x, y);
-            if !is_background_pixel_with_threshold(*pixel, background, threshold) {
+            if is_content(x, y) {
                 min_x = x;
                 break 'left;
             }
@@ -534,8 +2028,7 @@ fn find_content_bounds(
     let mut max_x = width - 1;
     'right: for x in (min_x..width).rev() {
         for y in min_y..=max_y {
-            let pixel = img.get_pixel(x, y);
-            if !is_background_pixel_with_threshold(*pixel, background, threshold) {
+            if is_content(x, y) {
                 max_x = x;
                 break 'right;
             }
@@ -553,14 +2046,18 @@ fn draw_bounding_box(
     max_x: u32,
     max_y: u32,
     thickness: u32,
+    dashed: bool,
 ) {
     let red = Rgba([255, 0, 0, 255]);
 
     let (width, height) = img.dimensions();
+    // Dashes and gaps are a few line widths long so they stay visible at any thickness
+    let dash = (thickness * 4).max(4);
+    let drawn = |along: u32| !dashed || (along / dash) % 2 == 0;
 
     // Draw top and bottom edges
     for x in min_x..=max_x {
-        if x < width {
+        if x < width && drawn(x - min_x) {
             for t in 0..thickness {
                 if min_y + t < height {
                     img.put_pixel(x, min_y + t, red);
@@ -574,7 +2071,7 @@ fn draw_bounding_box(
 
     // Draw left and right edges
     for y in min_y..=max_y {
-        if y < height {
+        if y < height && drawn(y - min_y) {
             for t in 0..thickness {
                 if min_x + t < width {
                     img.put_pixel(min_x + t, y, red);
@@ -587,11 +2084,14 @@ fn draw_bounding_box(
     }
 }
 
-/// Crop an image to its content using threshold-based detection
+/// Crop an image to its content using threshold-based detection, against
+/// `background_override` when set instead of the detected edge color
 #[must_use]
 pub fn crop_to_content_with_threshold(
     img: &DynamicImage,
     threshold: u8,
+    detection: BackgroundDetection,
+    background_override: Option<[u8; 3]>,
 ) -> (DynamicImage, (u32, u32, u32, u32)) {
     let rgba = img.to_rgba8();
     let (width, height) = rgba.dimensions();
@@ -601,12 +2101,23 @@ pub fn crop_to_content_with_threshold(
     }
 
     // Sample edge to determine background color
-    let background_color = sample_edge_color(&rgba);
+    let background_color = background_color(&rgba, detection, background_override);
+    let threshold = effective_threshold(&rgba, threshold, background_override);
 
-    // Find bounds of non-background content
-    if let Some((min_x, min_y, max_x, max_y)) =
-        find_content_bounds(&rgba, background_color, threshold)
-    {
+    // Find bounds of non-background content, classifying only the pixels the scan reaches
+    let bounds = find_content_bounds(width, height, |x, y| {
+        !is_background_pixel_with_threshold(*rgba.get_pixel(x, y), background_color, threshold)
+    });
+    crop_to_bounds(img, bounds)
+}
+
+/// Crop `img` to inclusive content `bounds` (min x, min y, max x, max y), returning the crop
+/// and its (x, y, width, height); the whole image when there is no content
+fn crop_to_bounds(
+    img: &DynamicImage,
+    bounds: Option<(u32, u32, u32, u32)>,
+) -> (DynamicImage, (u32, u32, u32, u32)) {
+    if let Some((min_x, min_y, max_x, max_y)) = bounds {
         // Crop to the content bounds
         let crop_width = max_x - min_x + 1;
         let crop_height = max_y - min_y + 1;
@@ -617,8 +2128,65 @@ pub fn crop_to_content_with_threshold(
         )
     } else {
         // No content found, return original
-        (img.clone(), (0, 0, width, height))
+        (img.clone(), (0, 0, img.width(), img.height()))
+    }
+}
+
+/// [`crop_to_content_with_threshold`] that also returns the content mask it cropped by, for
+/// the whole uncropped image (255 = content, 0 = background), so later steps can reuse it.
+/// Building the mask classifies every pixel, so use this only when the mask is needed.
+#[must_use]
+pub fn crop_to_content_with_mask(
+    img: &DynamicImage,
+    threshold: u8,
+    detection: BackgroundDetection,
+    background_override: Option<[u8; 3]>,
+) -> (DynamicImage, (u32, u32, u32, u32), GrayImage) {
+    let rgba = img.to_rgba8();
+    let (width, height) = rgba.dimensions();
+
+    if width == 0 || height == 0 {
+        return (
+            img.clone(),
+            (0, 0, width, height),
+            GrayImage::new(width, height),
+        );
+    }
+
+    // Sample edge to determine background color
+    let background_color = background_color(&rgba, detection, background_override);
+    let threshold = effective_threshold(&rgba, threshold, background_override);
+    let mask = content_mask(&rgba, background_color, threshold);
+
+    // Find bounds of non-background content
+    let bounds = find_content_bounds(width, height, |x, y| mask.get_pixel(x, y)[0] != 0);
+    let (cropped, bounds) = crop_to_bounds(img, bounds);
+    (cropped, bounds, mask)
+}
+
+/// Expand crop `bounds` (x, y, width, height) so each side is at least `min` pixels, growing
+/// symmetrically around the crop and shifting back inside the image at its edges. Sides are
+/// capped at the image size.
+#[must_use]
+pub fn expand_to_minimum(
+    bounds: (u32, u32, u32, u32),
+    image_width: u32,
+    image_height: u32,
+    min: u32,
+) -> (u32, u32, u32, u32) {
+    let (x, width) = expand_span(bounds.0, bounds.2, image_width, min);
+    let (y, height) = expand_span(bounds.1, bounds.3, image_height, min);
+    (x, y, width, height)
+}
+
+/// [`expand_to_minimum`] along one axis
+fn expand_span(start: u32, len: u32, total: u32, min: u32) -> (u32, u32) {
+    let target = min.min(total);
+    if len >= target {
+        return (start, len);
     }
+    let start = start.saturating_sub((target - len) / 2);
+    (start.min(total - target), target)
 }
 
 /// Crop an image to its content, removing whitespace/transparent padding
@@ -676,170 +2244,970 @@ fn is_background_pixel(pixel: image::Rgba<u8>) -> bool {
     r >= threshold && g >= threshold && b >= threshold
 }
 
-/// Get the output directory for an input path (appends -output to directory name)
+/// Write processed image data to `output_path`, copying the source timestamps if enabled
+/// # Errors
+/// Returns an error if the file cannot be written or its timestamps cannot be set.
+pub fn write_output(
+    input_path: &Path,
+    output_path: &Path,
+    processed: &ProcessedImage,
+    settings: &ProcessingSettings,
+) -> Result<()> {
+    std::fs::write(output_path, &processed.data)?;
+    if let Some(hash) = &processed.content_hash {
+        let name = output_path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        // Same layout as `b3sum`, so the sidecar can be checked with `b3sum --check`
+        std::fs::write(hash_sidecar_path(output_path), format!("{hash}  {name}\n"))?;
+    }
+    copy_timestamps(input_path, output_path, settings)
+}
+
+/// Where [`write_output`] puts the hash of `output_path`: the same path with `.blake3` appended
+#[must_use]
+pub fn hash_sidecar_path(output_path: &Path) -> PathBuf {
+    let mut path = output_path.as_os_str().to_owned();
+    path.push(".blake3");
+    PathBuf::from(path)
+}
+
+/// Give `output_path` the modified/accessed times of `input_path` if `settings` ask for it
+fn copy_timestamps(
+    input_path: &Path,
+    output_path: &Path,
+    settings: &ProcessingSettings,
+) -> Result<()> {
+    if settings.preserve_timestamps {
+        let meta = std::fs::metadata(input_path)?;
+        filetime::set_file_times(
+            output_path,
+            filetime::FileTime::from_last_access_time(&meta),
+            filetime::FileTime::from_last_modification_time(&meta),
+        )?;
+    }
+    Ok(())
+}
+
+/// What [`process_and_write`] did with one input
+#[derive(Debug)]
+pub enum ProcessOutcome {
+    /// The input was processed and the result written
+    Processed(ProcessedImage),
+    /// The input could not be decoded and was left out
+    Skipped,
+    /// The input could not be decoded and was copied to the output unchanged
+    CopiedThrough,
+}
+
+/// Process `input_path` and write the result to `output_path`, or only log what would be written
+/// under `settings.dry_run`. Inputs that exist but fail to decode are handled according to
+/// `settings.on_decode_error`.
+/// # Errors
+/// Returns [`ProcessError::DecodeFailed`] if the input is missing or (under
+/// [`DecodeErrorPolicy::Error`]) can't be decoded, [`ProcessError::ProcessingFailed`] if
+/// processing fails and [`ProcessError::WriteFailed`] if the output can't be written.
+pub fn process_and_write(
+    input_path: &Path,
+    output_path: &Path,
+    settings: &ProcessingSettings,
+) -> std::result::Result<ProcessOutcome, ProcessError> {
+    let span = tracing::debug_span!(
+        "process_image",
+        path = %input_path.display(),
+        decode_ms = Empty,
+        crop_ms = Empty,
+        encode_ms = Empty,
+        write_ms = Empty,
+    );
+    let _guard = span.enter();
+    if settings.skip_animated && is_animated(input_path) {
+        info!("Skipping animated {}", input_path.display());
+        return Ok(ProcessOutcome::Skipped);
+    }
+    let decode_start = Instant::now();
+    let img = match open_image(input_path) {
+        Ok(img) => img,
+        Err(e) if !input_path.is_file() => return Err(ProcessError::DecodeFailed(e.to_string())),
+        Err(e) => {
+            return match settings.on_decode_error {
+                DecodeErrorPolicy::Error => Err(ProcessError::DecodeFailed(e.to_string())),
+                DecodeErrorPolicy::Skip => {
+                    warn!("Skipping {}: {}", input_path.display(), e);
+                    Ok(ProcessOutcome::Skipped)
+                }
+                DecodeErrorPolicy::CopyThrough if settings.dry_run => {
+                    warn!("Would copy {} unchanged: {}", input_path.display(), e);
+                    Ok(ProcessOutcome::CopiedThrough)
+                }
+                DecodeErrorPolicy::CopyThrough => {
+                    warn!("Copying {} unchanged: {}", input_path.display(), e);
+                    std::fs::copy(input_path, output_path)
+                        .map_err(|e| ProcessError::WriteFailed(e.to_string()))?;
+                    copy_timestamps(input_path, output_path, settings)
+                        .map_err(|e| ProcessError::WriteFailed(e.to_string()))?;
+                    Ok(ProcessOutcome::CopiedThrough)
+                }
+            };
+        }
+    };
+    let decode = decode_start.elapsed();
+    let output_format = encoded_format(output_path, settings);
+    let mut processed = process_decoded(input_path, img, output_format, settings)
+        .map_err(|e| ProcessError::ProcessingFailed(e.to_string()))?;
+    let write_start = Instant::now();
+    if settings.dry_run {
+        info!(
+            "Would write {} ({}x{}{})",
+            output_path.display(),
+            processed.output_width,
+            processed.output_height,
+            if processed.was_cropped {
+                ", cropped"
+            } else {
+                ""
+            }
+        );
+    } else {
+        write_output(input_path, output_path, &processed, settings)
+            .map_err(|e| ProcessError::WriteFailed(e.to_string()))?;
+    }
+    processed.timings.decode = decode;
+    processed.timings.write = write_start.elapsed();
+    processed.timings.record_in(&span);
+    Ok(ProcessOutcome::Processed(processed))
+}
+
+/// Get the output directory for an input path: `<output_root>/<input name>` when an output root
+/// is given, otherwise an `-output` sibling of the input
 #[must_use]
-pub fn get_output_dir(input_path: &Path) -> PathBuf {
+pub fn get_output_dir(input_path: &Path, output_root: Option<&Path>) -> PathBuf {
+    let name = input_path
+        .file_name()
+        .map(|s| s.to_string_lossy())
+        .unwrap_or_default();
+    if let Some(output_root) = output_root {
+        return output_root.join(name.as_ref());
+    }
     if let Some(parent) = input_path.parent()
-        && let Some(name) = input_path.file_name()
+        && input_path.file_name().is_some()
     {
-        let output_name = format!("{}-output", name.to_string_lossy());
+        let output_name = format!("{name}{OUTPUT_DIR_SUFFIX}");
         return parent.join(output_name);
     }
     // Fallback
-    input_path.with_file_name(format!(
-        "{}-output",
-        input_path
-            .file_name()
-            .map(|s| s.to_string_lossy())
-            .unwrap_or_default()
-    ))
+    input_path.with_file_name(format!("{name}{OUTPUT_DIR_SUFFIX}"))
 }
 
-/// Get the output path for a file given its input path and the original input root
+/// Get the output path for a file given its input path, the original input root and the output
+/// root collecting all outputs, if any (see [`get_output_dir`])
 #[must_use]
 pub fn get_output_path(
     file_path: &Path,
     input_root: &Path,
     renamed_filename: &str,
+    output_root: Option<&Path>,
 ) -> Option<PathBuf> {
     // Get relative path from input root
     let relative = file_path.strip_prefix(input_root).ok()?;
 
     // Get output root directory
-    let output_root = get_output_dir(input_root);
+    let output_dir = get_output_dir(input_root, output_root);
 
-    // Build output path: output_root + relative_dir + renamed_filename
-    let mut output_path = output_root;
+    // Build output path: output_dir + relative_dir + renamed_filename
+    let mut output_path = output_dir;
     if let Some(parent) = relative.parent() {
         output_path = output_path.join(parent);
     }
     output_path = output_path.join(renamed_filename);
 
+    // HEIF inputs are written as JPEG, so name the output to match
+    if is_heif_path(&output_path) {
+        output_path.set_extension("jpg");
+    }
+
     Some(output_path)
 }
-/// Process and write all images
-/// # Errors
-/// Returns an error if processing any image fails.
-#[expect(clippy::type_complexity)]
-pub fn process_all_images(
-    input_files: &[PathBuf],
-    renamed_files: &[PathBuf],
-    input_roots: &[PathBuf],
-    settings: &ProcessingSettings,
-    progress_callback: Option<&dyn Fn(usize, usize, &Path)>,
-) -> Result<ProcessAllResult> {
-    let mut processed_count = 0;
-    let skipped_count = 0;
-    let mut error_count = 0;
-    let mut errors: Vec<String> = Vec::new();
 
-    let total = input_files.len();
+/// Output path for `file_path` in a flat layout: directly in `output_root`, or in the output
+/// directory of `input_root` when there is none, named after the directories it was nested in
+/// (`sub_dir_name.png`). With an output root the input root's name leads, so several inputs can
+/// share it.
+#[must_use]
+pub fn get_flat_output_path(
+    file_path: &Path,
+    input_root: &Path,
+    renamed_filename: &str,
+    output_root: Option<&Path>,
+) -> Option<PathBuf> {
+    let relative = file_path.strip_prefix(input_root).ok()?;
+    let mut parts = Vec::new();
+    let output_dir = if let Some(output_root) = output_root {
+        parts.push(input_root.file_name()?.to_string_lossy().to_string());
+        output_root.to_path_buf()
+    } else {
+        get_output_dir(input_root, None)
+    };
+    if let Some(parent) = relative.parent() {
+        parts.extend(
+            parent
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string()),
+        );
+    }
+    parts.push(renamed_filename.to_owned());
+    let mut output_path = output_dir.join(parts.join("_"));
 
-    for (i, (input_file, renamed_file)) in input_files.iter().zip(renamed_files.iter()).enumerate()
-    {
-        if let Some(cb) = progress_callback {
-            cb(i + 1, total, input_file);
-        }
+    // HEIF inputs are written as JPEG, so name the output to match
+    if is_heif_path(&output_path) {
+        output_path.set_extension("jpg");
+    }
 
-        // Find which input root this file belongs to
-        let input_root = input_roots.iter().find(|r| input_file.starts_with(r));
-        let Some(input_root) = input_root else {
-            errors.push(format!("Could not find root for: {}", input_file.display()));
-            error_count += 1;
-            continue;
-        };
+    Some(output_path)
+}
 
-        // Get the renamed filename
-        let renamed_name = renamed_file
-            .file_name()
-            .map(|s| s.to_string_lossy().to_string())
-            .unwrap_or_default();
+/// `output_path` with the extension of `format` when outputs are converted to another format
+#[must_use]
+pub fn with_output_format(mut output_path: PathBuf, format: Option<ImageFormat>) -> PathBuf {
+    if let Some(extension) = format.and_then(|f| f.extensions_str().first()) {
+        output_path.set_extension(extension);
+    }
+    output_path
+}
 
-        // Calculate output path
-        let Some(output_path) = get_output_path(input_file, input_root, &renamed_name) else {
-            errors.push(format!(
-                "Could not calculate output path for: {}",
-                input_file.display()
-            ));
-            error_count += 1;
-            continue;
-        };
+/// Format an image written to `path` is encoded in: the converted format when the settings
+/// set one, otherwise the one `path`'s extension names, with PNG standing in for formats that
+/// can't be written (GIF, unknown extensions)
+#[must_use]
+pub fn encoded_format(path: &Path, settings: &ProcessingSettings) -> ImageFormat {
+    match settings
+        .output_format
+        .unwrap_or_else(|| detect_format_from_path(path))
+    {
+        format @ (ImageFormat::Jpeg
+        | ImageFormat::Png
+        | ImageFormat::WebP
+        | ImageFormat::Bmp
+        | ImageFormat::Tiff
+        | ImageFormat::Avif) => format,
+        _ => ImageFormat::Png,
+    }
+}
 
-        // Create output directory if needed
-        if let Some(parent) = output_path.parent()
-            && let Err(e) = std::fs::create_dir_all(parent)
-        {
-            errors.push(format!(
-                "Failed to create directory {}: {}",
-                parent.display(),
-                e
-            ));
-            error_count += 1;
-            continue;
-        }
+/// `output_path` with the extension of `format` when its own extension names another format,
+/// so the name matches the bytes written to it
+#[must_use]
+pub fn with_matching_extension(mut output_path: PathBuf, format: ImageFormat) -> PathBuf {
+    let matches = output_path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            format
+                .extensions_str()
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(ext))
+        });
+    if !matches && let Some(extension) = format.extensions_str().first() {
+        output_path.set_extension(extension);
+    }
+    output_path
+}
 
-        // Process the image
-        match process_image(input_file, settings) {
-            Ok(processed) => {
-                // Write output file
-                if let Err(e) = std::fs::write(&output_path, &processed.data) {
-                    errors.push(format!("Failed to write {}: {}", output_path.display(), e));
-                    error_count += 1;
-                } else {
-                    processed_count += 1;
-                }
+/// Check if a path has one of `extensions` (see [`crate::image_extensions`]), ignoring case
+#[must_use]
+pub fn is_image_file(path: &Path, extensions: &[String]) -> bool {
+    path.extension()
+        .and_then(|s| s.to_str())
+        .is_some_and(|ext| extensions.iter().any(|e| e.eq_ignore_ascii_case(ext)))
+}
+
+/// Output paths that more than one input would be written to, each with the inputs mapping
+/// there (in input order), sorted by output path
+#[must_use]
+pub fn find_output_collisions<'a>(
+    targets: impl IntoIterator<Item = (&'a Path, &'a Path)>,
+) -> Vec<(PathBuf, Vec<PathBuf>)> {
+    let mut by_output: BTreeMap<&Path, Vec<PathBuf>> = BTreeMap::new();
+    for (input, output) in targets {
+        by_output
+            .entry(output)
+            .or_default()
+            .push(input.to_path_buf());
+    }
+    by_output
+        .into_iter()
+        .filter(|(_, inputs)| inputs.len() > 1)
+        .map(|(output, inputs)| (output.to_path_buf(), inputs))
+        .collect()
+}
+
+/// `path` with ` (1)`, ` (2)`, ... inserted before the extension, whichever comes first that
+/// `taken` rejects; `path` itself when it isn't taken
+#[must_use]
+pub fn uniquify_path(path: &Path, taken: impl Fn(&Path) -> bool) -> PathBuf {
+    if !taken(path) {
+        return path.to_path_buf();
+    }
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|e| format!(".{}", e.to_string_lossy()))
+        .unwrap_or_default();
+    (1..)
+        .map(|n| path.with_file_name(format!("{stem} ({n}){extension}")))
+        .find(|candidate| !taken(candidate))
+        .unwrap_or_else(|| path.to_path_buf())
+}
+
+/// Where an output goes under `policy`: `None` when an existing file is to be kept, otherwise
+/// `output_path`, or under [`OutputConflictPolicy::Rename`] the first free
+/// [`uniquify_path`] variant that is neither on disk nor `taken`
+#[must_use]
+pub fn resolve_output_conflict(
+    output_path: &Path,
+    policy: OutputConflictPolicy,
+    taken: impl Fn(&Path) -> bool,
+) -> Option<PathBuf> {
+    match policy {
+        OutputConflictPolicy::Overwrite => Some(output_path.to_path_buf()),
+        OutputConflictPolicy::Skip => (!output_path.exists()).then(|| output_path.to_path_buf()),
+        OutputConflictPolicy::Rename if output_path.exists() => {
+            Some(uniquify_path(output_path, |p| p.exists() || taken(p)))
+        }
+        OutputConflictPolicy::Rename => Some(output_path.to_path_buf()),
+    }
+}
+
+/// [`resolve_output_conflict`] for each output of a batch in input order, where a renamed
+/// output also avoids every other input's output
+fn resolve_output_conflicts(
+    outputs: &[Option<(PathBuf, OutputConflictPolicy)>],
+) -> Vec<Option<PathBuf>> {
+    let mut claimed: HashSet<PathBuf> = outputs.iter().flatten().map(|(p, _)| p.clone()).collect();
+    outputs
+        .iter()
+        .map(|output| {
+            let (path, policy) = output.as_ref()?;
+            let resolved = resolve_output_conflict(path, *policy, |p| claimed.contains(p))?;
+            claimed.insert(resolved.clone());
+            Some(resolved)
+        })
+        .collect()
+}
+
+/// [`output_targets`], refusing a batch in which two inputs would be written to the same output
+/// path
+///
+/// # Errors
+///
+/// Returns an error listing each output path and the inputs that would all write to it.
+pub fn checked_output_targets<'a>(
+    input_files: &[PathBuf],
+    renamed_files: &[PathBuf],
+    input_roots: &'a [PathBuf],
+    settings: &ProcessingSettings,
+    overrides: &InputOverrides,
+) -> Result<Vec<Result<(&'a PathBuf, PathBuf), ProcessError>>> {
+    let targets = output_targets(input_files, renamed_files, input_roots, settings, overrides);
+    let collisions = find_output_collisions(input_files.iter().zip(&targets).filter_map(
+        |(input_file, target)| {
+            let (_, output_path) = target.as_ref().ok()?;
+            Some((input_file.as_path(), output_path.as_path()))
+        },
+    ));
+    if !collisions.is_empty() {
+        let listing: Vec<String> = collisions
+            .iter()
+            .map(|(output, inputs)| {
+                let inputs: Vec<String> = inputs.iter().map(|p| p.display().to_string()).collect();
+                format!("{} <- {}", output.display(), inputs.join(", "))
+            })
+            .collect();
+        eyre::bail!(
+            "{} output path(s) would be written by more than one input:\n{}",
+            collisions.len(),
+            listing.join("\n")
+        );
+    }
+    Ok(targets)
+}
+
+/// `targets` from [`output_targets`] with the [`OutputConflictPolicy`] of each input's root
+/// applied, as [`resolve_output_conflicts`] does; `None` where an existing output is kept
+#[must_use]
+pub fn resolve_target_conflicts<'a>(
+    targets: Vec<Result<(&'a PathBuf, PathBuf), ProcessError>>,
+    settings: &ProcessingSettings,
+    overrides: &InputOverrides,
+) -> Vec<Result<(&'a PathBuf, Option<PathBuf>), ProcessError>> {
+    let resolved = resolve_output_conflicts(
+        &targets
+            .iter()
+            .map(|target| {
+                let (input_root, output_path) = target.as_ref().ok()?;
+                let on_conflict = overrides.settings_for(input_root, settings).on_conflict;
+                Some((output_path.clone(), on_conflict))
+            })
+            .collect::<Vec<_>>(),
+    );
+    targets
+        .into_iter()
+        .zip(resolved)
+        .map(|(target, resolved)| target.map(|(input_root, _)| (input_root, resolved)))
+        .collect()
+}
+
+/// The input root and output path of each input of a batch, index for index, before any
+/// [`OutputConflictPolicy`] is applied. Flattened names that repeat are numbered; other inputs
+/// mapping to the same output are left for [`find_output_collisions`] to report.
+#[must_use]
+pub fn output_targets<'a>(
+    input_files: &[PathBuf],
+    renamed_files: &[PathBuf],
+    input_roots: &'a [PathBuf],
+    settings: &ProcessingSettings,
+    overrides: &InputOverrides,
+) -> Vec<Result<(&'a PathBuf, PathBuf), ProcessError>> {
+    let mut targets: Vec<_> = input_files
+        .iter()
+        .zip(renamed_files.iter())
+        .map(|(input_file, renamed_file)| {
+            // Find which input root this file belongs to
+            let Some(input_root) = input_roots.iter().find(|r| input_file.starts_with(r)) else {
+                return Err(ProcessError::NoInputRoot);
+            };
+
+            // Get the renamed filename
+            let renamed_name = renamed_file
+                .file_name()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            // Calculate output path, renamed to the converted format if any
+            let root_settings = overrides.settings_for(input_root, settings);
+            let output_path = if settings.flatten {
+                get_flat_output_path(
+                    input_file,
+                    input_root,
+                    &renamed_name,
+                    settings.output_root.as_deref(),
+                )
+            } else {
+                get_output_path(
+                    input_file,
+                    input_root,
+                    &renamed_name,
+                    settings.output_root.as_deref(),
+                )
+            };
+            let output_path = with_output_format(
+                output_path.ok_or(ProcessError::OutputPathFailed)?,
+                root_settings.output_format,
+            );
+            // A rename rule can leave an extension no encoder writes, e.g. `.gif` or `.txt`
+            let format = encoded_format(&output_path, &root_settings);
+            let matched = with_matching_extension(output_path.clone(), format);
+            if matched != output_path {
+                info!(
+                    "Writing {} as {} to match its {:?} contents",
+                    output_path.display(),
+                    matched.display(),
+                    format
+                );
             }
-            Err(e) => {
-                errors.push(format!("Failed to process {}: {}", input_file.display(), e));
+            Ok((input_root, matched))
+        })
+        .collect();
+
+    // Flattened names can repeat across directories, so number the repeats instead of failing
+    // on them, steering clear of every other input's output
+    if settings.flatten {
+        let mut claimed: HashSet<PathBuf> = targets
+            .iter()
+            .filter_map(|target| Some(target.as_ref().ok()?.1.clone()))
+            .collect();
+        let mut assigned = HashSet::new();
+        for (_, output_path) in targets.iter_mut().filter_map(|target| target.as_mut().ok()) {
+            if !assigned.insert(output_path.clone()) {
+                let unique = uniquify_path(output_path, |p| claimed.contains(p));
+                claimed.insert(unique.clone());
+                assigned.insert(unique.clone());
+                *output_path = unique;
+            }
+        }
+    }
+    targets
+}
+
+/// Process and write all images, applying any `overrides` for the input root each file is under.
+/// Progress goes to `progress_callback` and, as [`ProcessProgress`] events, to `events`.
+///
+/// Files are processed in parallel on the current rayon pool; run this inside
+/// [`rayon::ThreadPool::install`] to cap the number of jobs. Results are gathered in input order
+/// regardless of which file finishes first.
+/// # Errors
+/// Returns an error, before writing anything, if two inputs would be written to the same output
+/// path; failures of individual images are collected into the result instead.
+#[expect(clippy::type_complexity)]
+pub fn process_all_images(
+    input_files: &[PathBuf],
+    renamed_files: &[PathBuf],
+    input_roots: &[PathBuf],
+    settings: &ProcessingSettings,
+    overrides: &InputOverrides,
+    progress_callback: Option<&(dyn Fn(usize, usize, &Path) + Sync)>,
+    events: Option<&mpsc::Sender<ProcessProgress>>,
+) -> Result<ProcessAllResult> {
+    let mut processed_count = 0;
+    let mut skipped_count = 0;
+    let mut copied_count = 0;
+    let mut existing_count = 0;
+    let mut error_count = 0;
+    let mut errors: Vec<(PathBuf, ProcessError)> = Vec::new();
+    let mut report: Vec<ProcessReportEntry> = Vec::new();
+    let mut timing = TimingSummary::default();
+
+    // Resolve every input's root and output path up front so collisions are caught before
+    // anything is written
+    let targets =
+        checked_output_targets(input_files, renamed_files, input_roots, settings, overrides)?;
+
+    // Apply each root's conflict policy now that the natural output paths are known to be
+    // distinct, so renamed outputs can't land on another input's output either
+    let targets = resolve_target_conflicts(targets, settings, overrides);
+
+    let total = input_files.len();
+    let emit = |event: ProcessProgress| {
+        if let Some(events) = events {
+            // A consumer that stopped listening doesn't stop the batch
+            let _ = events.send(event);
+        }
+    };
+    emit(ProcessProgress::Started { total });
+
+    let started = AtomicUsize::new(0);
+    let items: Vec<(ProcessItemResult, Option<StageTimings>)> = input_files
+        .par_iter()
+        .zip(targets)
+        .enumerate()
+        .map(|(index, (input_file, target))| {
+            if let Some(cb) = progress_callback {
+                cb(
+                    started.fetch_add(1, Ordering::SeqCst) + 1,
+                    total,
+                    input_file,
+                );
+            }
+
+            let (item, timings) = match target {
+                _ if settings.is_cancelled() => {
+                    (ProcessItemResult::Failed(ProcessError::Cancelled), None)
+                }
+                Ok((input_root, Some(output_path))) => write_target(
+                    input_file,
+                    &output_path,
+                    &overrides.settings_for(input_root, settings),
+                ),
+                Ok((_, None)) => (ProcessItemResult::AlreadyExists, None),
+                Err(e) => (ProcessItemResult::Failed(e), None),
+            };
+            emit(ProcessProgress::Item {
+                index,
+                path: input_file.clone(),
+                result: item.clone(),
+            });
+            (item, timings)
+        })
+        .collect();
+
+    for (input_file, (item, timings)) in input_files.iter().zip(items) {
+        if let Some(timings) = timings {
+            timing.record(&timings);
+        }
+        match item {
+            ProcessItemResult::Processed(entry) => {
+                processed_count += 1;
+                report.push(entry);
+            }
+            ProcessItemResult::Skipped => skipped_count += 1,
+            ProcessItemResult::CopiedThrough => copied_count += 1,
+            ProcessItemResult::AlreadyExists => existing_count += 1,
+            ProcessItemResult::Failed(e) => {
+                errors.push((input_file.clone(), e));
                 error_count += 1;
             }
         }
     }
 
-    Ok(ProcessAllResult {
+    if !timing.is_empty() {
+        info!("{timing}");
+    }
+    let result = ProcessAllResult {
         processed_count,
         skipped_count,
+        copied_count,
+        existing_count,
         error_count,
         errors,
-    })
+        report,
+        timing,
+    };
+    emit(ProcessProgress::Finished {
+        result: result.clone(),
+    });
+    Ok(result)
+}
+
+/// Create the output directory and write one input to `output_path`, with the stage timings
+/// when it was processed
+#[must_use]
+pub fn write_target(
+    input_file: &Path,
+    output_path: &Path,
+    settings: &ProcessingSettings,
+) -> (ProcessItemResult, Option<StageTimings>) {
+    if !settings.dry_run
+        && let Some(parent) = output_path.parent()
+        && let Err(e) = std::fs::create_dir_all(parent)
+    {
+        return (
+            ProcessItemResult::Failed(ProcessError::DirCreateFailed {
+                dir: parent.to_path_buf(),
+                error: e.to_string(),
+            }),
+            None,
+        );
+    }
+    match process_and_write(input_file, output_path, settings) {
+        Ok(ProcessOutcome::Processed(processed)) => (
+            ProcessItemResult::Processed(ProcessReportEntry::new(
+                input_file,
+                output_path,
+                &processed,
+            )),
+            Some(processed.timings),
+        ),
+        Ok(ProcessOutcome::Skipped) => (ProcessItemResult::Skipped, None),
+        Ok(ProcessOutcome::CopiedThrough) => (ProcessItemResult::CopiedThrough, None),
+        Err(e) => (ProcessItemResult::Failed(e), None),
+    }
+}
+
+/// What happened to one input of a batch
+#[derive(Clone, Debug, PartialEq)]
+pub enum ProcessItemResult {
+    Processed(ProcessReportEntry),
+    /// Could not be decoded and was left out
+    Skipped,
+    /// Could not be decoded and was copied unchanged
+    CopiedThrough,
+    /// Its output already existed and was kept ([`OutputConflictPolicy::Skip`])
+    AlreadyExists,
+    Failed(ProcessError),
+}
+
+/// Why one input of a batch failed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProcessError {
+    /// The input could not be read or decoded
+    DecodeFailed(String),
+    /// Processing or encoding the decoded image failed
+    ProcessingFailed(String),
+    /// The output file could not be written
+    WriteFailed(String),
+    /// The input is not under any of the input roots
+    NoInputRoot,
+    /// No output path could be derived for the input
+    OutputPathFailed,
+    /// The output directory could not be created
+    DirCreateFailed { dir: PathBuf, error: String },
+    /// The batch was cancelled before the input was started
+    Cancelled,
+}
+
+impl ProcessError {
+    /// Short name of the failure kind, for grouping in the UI
+    #[must_use]
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ProcessError::DecodeFailed(_) => "decode",
+            ProcessError::ProcessingFailed(_) => "processing",
+            ProcessError::WriteFailed(_) => "write",
+            ProcessError::NoInputRoot => "no input root",
+            ProcessError::OutputPathFailed => "output path",
+            ProcessError::DirCreateFailed { .. } => "create dir",
+            ProcessError::Cancelled => "cancelled",
+        }
+    }
+
+    /// Whether trying again without changing the input may succeed, e.g. once a locked file
+    /// is closed or disk space is freed
+    #[must_use]
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ProcessError::WriteFailed(_) | ProcessError::DirCreateFailed { .. }
+        )
+    }
+}
+
+impl std::fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProcessError::DecodeFailed(e) => write!(f, "Failed to decode: {e}"),
+            ProcessError::ProcessingFailed(e) => write!(f, "Failed to process: {e}"),
+            ProcessError::WriteFailed(e) => write!(f, "Failed to write output: {e}"),
+            ProcessError::NoInputRoot => write!(f, "Not under any input root"),
+            ProcessError::OutputPathFailed => write!(f, "Could not calculate output path"),
+            ProcessError::DirCreateFailed { dir, error } => {
+                write!(f, "Failed to create directory {}: {error}", dir.display())
+            }
+            ProcessError::Cancelled => write!(f, "Cancelled before it was started"),
+        }
+    }
+}
+
+impl std::error::Error for ProcessError {}
+
+/// Progress of [`process_all_images`], for consumers that want structured events rather than
+/// the plain callback
+#[derive(Clone, Debug)]
+pub enum ProcessProgress {
+    /// Sent once the batch has passed its collision check, before any input is processed
+    Started { total: usize },
+    /// One input finished; `index` counts from 0 in input order
+    Item {
+        index: usize,
+        path: PathBuf,
+        result: ProcessItemResult,
+    },
+    /// Every input has been handled
+    Finished { result: ProcessAllResult },
+}
+
+impl ProcessProgress {
+    /// The `--progress-format ndjson` line for an [`ProcessProgress::Item`] of a batch of
+    /// `total` inputs, `None` for the other events
+    #[must_use]
+    pub fn progress_line(&self, total: usize) -> Option<ProgressLine> {
+        let ProcessProgress::Item {
+            index,
+            path,
+            result,
+        } = self
+        else {
+            return None;
+        };
+        let error = match result {
+            ProcessItemResult::Failed(e) => Some(e.to_string()),
+            _ => None,
+        };
+        Some(ProgressLine {
+            index: *index,
+            total,
+            path: path.display().to_string(),
+            ok: error.is_none(),
+            error,
+        })
+    }
+}
+
+/// One finished input of a batch, printed as a JSON line by `cm process --progress-format
+/// ndjson`
+#[derive(Clone, Debug, PartialEq, Eq, Facet)]
+pub struct ProgressLine {
+    /// Position of the input, counting from 0 in input order
+    pub index: usize,
+    pub total: usize,
+    pub path: String,
+    /// Whether the input was handled without failing (skipped and kept inputs count as ok)
+    pub ok: bool,
+    pub error: Option<String>,
 }
 
 /// Result of processing all images
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ProcessAllResult {
     pub processed_count: usize,
+    /// Inputs left out because they could not be decoded
     pub skipped_count: usize,
+    /// Inputs copied through unchanged because they could not be decoded
+    pub copied_count: usize,
+    /// Inputs left out because their output already existed
+    pub existing_count: usize,
     pub error_count: usize,
+    /// Each failed input with the reason, in input order
+    pub errors: Vec<(PathBuf, ProcessError)>,
+    /// One entry per successfully written image, in processing order
+    pub report: Vec<ProcessReportEntry>,
+    /// Per-stage timing over the processed images
+    pub timing: TimingSummary,
+}
+
+/// Crop rectangle within the original image
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Facet)]
+pub struct CropBounds {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Machine-readable audit of one processed image
+#[derive(Clone, Debug, PartialEq, Eq, Facet)]
+pub struct ProcessReportEntry {
+    pub input: String,
+    pub output: String,
+    pub was_cropped: bool,
+    pub no_crop_detected: bool,
+    pub crop_bounds: Option<CropBounds>,
+    pub original_width: u32,
+    pub original_height: u32,
+    pub output_width: u32,
+    pub output_height: u32,
+    pub estimated_size: u64,
+    /// BLAKE3 hash of the output, when hashing was requested
+    pub hash: Option<String>,
+    /// Why the input looks damaged even though it decoded (e.g. a truncated JPEG)
+    pub warning: Option<String>,
+    /// Set when the input embeds a color profile other than sRGB
+    pub color_profile: Option<String>,
+}
+
+impl ProcessReportEntry {
+    #[must_use]
+    pub fn new(input: &Path, output: &Path, processed: &ProcessedImage) -> Self {
+        Self {
+            input: input.display().to_string(),
+            output: output.display().to_string(),
+            was_cropped: processed.was_cropped,
+            no_crop_detected: processed.no_crop_detected,
+            crop_bounds: processed
+                .crop_bounds
+                .map(|(x, y, width, height)| CropBounds {
+                    x,
+                    y,
+                    width,
+                    height,
+                }),
+            original_width: processed.original_width,
+            original_height: processed.original_height,
+            output_width: processed.output_width,
+            output_height: processed.output_height,
+            estimated_size: processed.estimated_size,
+            hash: processed.content_hash.clone(),
+            warning: processed.integrity_warning.clone(),
+            color_profile: processed.color_profile_warning.clone(),
+        }
+    }
+}
+
+/// Report written by `cm process --report`
+#[derive(Clone, Debug, PartialEq, Eq, Facet)]
+pub struct ProcessReport {
+    pub files: Vec<ProcessReportEntry>,
     pub errors: Vec<String>,
 }
 
-/// Load image metadata and generate a thumbnail for caching
+impl From<ProcessAllResult> for ProcessReport {
+    fn from(result: ProcessAllResult) -> Self {
+        Self {
+            files: result.report,
+            errors: result
+                .errors
+                .iter()
+                .map(|(path, error)| format!("{}: {error}", path.display()))
+                .collect(),
+        }
+    }
+}
+
+/// Extrapolated output size for a batch of images
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchSizeEstimate {
+    /// Number of images that were actually processed to compute the estimate
+    pub sampled: usize,
+    /// Number of images the estimate covers
+    pub total_files: usize,
+    /// Sum of input file sizes across all files
+    pub total_input_bytes: u64,
+    /// Estimated sum of output file sizes across all files
+    pub estimated_output_bytes: u64,
+}
+
+/// Estimate the total output size of `files` by processing up to `sample` evenly spaced
+/// images and applying their average compression ratio to the total input size.
 /// # Errors
-/// Returns an error if the image cannot be loaded or metadata cannot be retrieved.
+/// Returns an error if none of the sampled images could be processed.
 #[expect(clippy::cast_possible_truncation)]
+#[expect(clippy::cast_precision_loss)]
 #[expect(clippy::cast_sign_loss)]
+pub fn estimate_batch_size(
+    files: &[PathBuf],
+    settings: &ProcessingSettings,
+    sample: usize,
+) -> Result<BatchSizeEstimate> {
+    let total_input_bytes: u64 = files
+        .iter()
+        .filter_map(|f| std::fs::metadata(f).ok())
+        .map(|m| m.len())
+        .sum();
+
+    let sample = sample.clamp(1, files.len().max(1));
+    let step = (files.len() / sample).max(1);
+    let mut sampled = 0;
+    let mut sampled_input: u64 = 0;
+    let mut sampled_output: u64 = 0;
+    for file in files.iter().step_by(step).take(sample) {
+        let Ok(meta) = std::fs::metadata(file) else {
+            continue;
+        };
+        match process_image(file, settings) {
+            Ok(processed) => {
+                sampled += 1;
+                sampled_input += meta.len();
+                sampled_output += processed.estimated_size;
+            }
+            Err(e) => {
+                warn!("Skipping {} in size estimate: {}", file.display(), e);
+            }
+        }
+    }
+
+    if sampled == 0 || sampled_input == 0 {
+        return Err(eyre!("No sampled images could be processed"));
+    }
+
+    let ratio = sampled_output as f64 / sampled_input as f64;
+    Ok(BatchSizeEstimate {
+        sampled,
+        total_files: files.len(),
+        total_input_bytes,
+        estimated_output_bytes: (total_input_bytes as f64 * ratio) as u64,
+    })
+}
+
+/// Load image metadata and generate a thumbnail for caching
+/// # Errors
+/// Returns an error if the image cannot be loaded or metadata cannot be retrieved.
 pub fn load_image_metadata(path: &Path, thumbnail_size: u32) -> Result<CachedImageInfo> {
-    // Get file size
-    let file_size = std::fs::metadata(path)
-        .map_err(|e| eyre!("Failed to get file metadata: {}", e))?
-        .len();
+    // Get file size and modification time
+    let metadata =
+        std::fs::metadata(path).map_err(|e| eyre!("Failed to get file metadata: {}", e))?;
+    let file_size = metadata.len();
+    let modified = metadata.modified().ok();
 
     // Load the image
-    let img =
-        image::open(path).map_err(|e| eyre!("Failed to open image {}: {}", path.display(), e))?;
+    let img = open_image(path)?;
 
     let width = img.width();
     let height = img.height();
 
-    // Generate thumbnail
-    let thumbnail = if width <= thumbnail_size && height <= thumbnail_size {
-        img
-    } else {
-        let scale = (f64::from(thumbnail_size) / f64::from(width.max(height))).min(1.0);
-        let new_width = (f64::from(width) * scale) as u32;
-        let new_height = (f64::from(height) * scale) as u32;
-        img.resize(new_width, new_height, image::imageops::FilterType::Triangle)
-    };
+    let thumbnail = make_thumbnail(img, thumbnail_size);
 
     // Encode thumbnail as PNG
     let mut thumbnail_data = Vec::new();
@@ -852,6 +3220,1946 @@ pub fn load_image_metadata(path: &Path, thumbnail_size: u32) -> Result<CachedIma
         width,
         height,
         file_size,
+        modified,
         thumbnail_data,
     })
 }
+
+/// Shrink `img` so neither side exceeds `size`, keeping its aspect ratio. Smaller images are
+/// returned as-is.
+#[expect(clippy::cast_possible_truncation)]
+#[expect(clippy::cast_sign_loss)]
+fn make_thumbnail(img: DynamicImage, size: u32) -> DynamicImage {
+    let (width, height) = (img.width(), img.height());
+    if width <= size && height <= size {
+        return img;
+    }
+    let scale = (f64::from(size) / f64::from(width.max(height))).min(1.0);
+    let new_width = (f64::from(width) * scale) as u32;
+    let new_height = (f64::from(height) * scale) as u32;
+    img.resize(new_width, new_height, image::imageops::FilterType::Triangle)
+}
+
+/// Darken `img` with dark gray text from `mask`, its top-left corner at `(left, top)`
+#[expect(clippy::cast_possible_truncation)]
+#[expect(clippy::cast_sign_loss)]
+fn draw_text_mask(img: &mut RgbaImage, mask: &text_render::TextMask, left: u32, top: u32) {
+    for row in 0..mask.height {
+        for column in 0..mask.width {
+            let coverage = mask.at(column, row);
+            let Some(pixel) = img.get_pixel_mut_checked(left + column, top + row) else {
+                continue;
+            };
+            if coverage > 0.0 {
+                let value = (255.0 - 215.0 * coverage).round() as u8;
+                for channel in &mut pixel.0[..3] {
+                    *channel = (*channel).min(value);
+                }
+            }
+        }
+    }
+}
+
+/// Layout of a contact sheet built by [`build_contact_sheet`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContactSheetOptions {
+    /// Thumbnails per row
+    pub columns: u32,
+    /// Width and height of each thumbnail cell, not counting its label
+    pub cell_size: u32,
+}
+
+impl Default for ContactSheetOptions {
+    fn default() -> Self {
+        Self {
+            columns: 6,
+            cell_size: 200,
+        }
+    }
+}
+
+impl ContactSheetOptions {
+    /// Height of the filename strip under each cell
+    #[must_use]
+    pub fn label_height(&self) -> u32 {
+        (self.cell_size / 8).clamp(12, 32)
+    }
+}
+
+/// Tile thumbnails of `paths` into a grid on a white sheet, with each file name under its
+/// thumbnail. Files that can't be opened get an empty gray cell so the grid stays aligned.
+///
+/// # Errors
+///
+/// Returns an error if the sheet would be too large to allocate.
+#[expect(clippy::cast_precision_loss)]
+pub fn build_contact_sheet(
+    paths: &[PathBuf],
+    options: &ContactSheetOptions,
+) -> Result<DynamicImage> {
+    let count = u32::try_from(paths.len()).unwrap_or(u32::MAX);
+    let columns = options.columns.clamp(1, count.max(1));
+    let rows = count.div_ceil(columns);
+    let cell = options.cell_size.max(1);
+    let label_height = options.label_height();
+    let row_height = cell.saturating_add(label_height);
+    let too_large = || {
+        eyre!(
+            "A contact sheet of {} images in {} columns of {}px cells is too large",
+            count,
+            columns,
+            cell
+        )
+    };
+    let width = columns.checked_mul(cell).ok_or_else(too_large)?;
+    let height = rows.checked_mul(row_height).ok_or_else(too_large)?;
+    // The pixel buffer is indexed with usize, so its byte length has to fit too
+    (u64::from(width) * u64::from(height))
+        .checked_mul(4)
+        .and_then(|bytes| usize::try_from(bytes).ok())
+        .ok_or_else(too_large)?;
+    let mut sheet = RgbaImage::from_pixel(width, height, Rgba([255, 255, 255, 255]));
+
+    let padding = (cell / 20).max(2);
+    // Space left for the thumbnail, blank cell and label once padded, never zero
+    let inner = cell.saturating_sub(padding * 2).max(1);
+    let label_px = label_height as f32 * 0.8;
+    for (index, path) in (0..count).zip(paths) {
+        let (x, y) = ((index % columns) * cell, (index / columns) * row_height);
+        match open_image(path) {
+            Ok(img) => {
+                let thumbnail = make_thumbnail(img, inner);
+                let offset_x = cell.saturating_sub(thumbnail.width()) / 2;
+                let offset_y = cell.saturating_sub(thumbnail.height()) / 2;
+                image::imageops::overlay(
+                    &mut sheet,
+                    &thumbnail.to_rgba8(),
+                    i64::from(x + offset_x),
+                    i64::from(y + offset_y),
+                );
+            }
+            Err(e) => {
+                warn!(
+                    "Leaving {} blank on the contact sheet: {}",
+                    path.display(),
+                    e
+                );
+                let blank = RgbaImage::from_pixel(inner, inner, Rgba([220, 220, 220, 255]));
+                image::imageops::overlay(
+                    &mut sheet,
+                    &blank,
+                    i64::from(x + padding),
+                    i64::from(y + padding),
+                );
+            }
+        }
+
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let label = text_render::truncate_to_width(&name, label_px, inner)
+            .and_then(|label| text_render::render_text(&label, label_px));
+        match label {
+            Ok(mask) => draw_text_mask(
+                &mut sheet,
+                &mask,
+                x + cell.saturating_sub(mask.width) / 2,
+                y + cell + label_height.saturating_sub(mask.height) / 2,
+            ),
+            Err(e) => warn!(
+                "Failed to label {} on the contact sheet: {}",
+                path.display(),
+                e
+            ),
+        }
+    }
+    Ok(DynamicImage::ImageRgba8(sheet))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn preserve_timestamps_copies_mtime() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let root = td.path().join("in");
+        std::fs::create_dir_all(&root)?;
+        let input = root.join("a.png");
+        RgbaImage::from_pixel(4, 4, Rgba([10, 20, 30, 255])).save(&input)?;
+        let old = filetime::FileTime::from_unix_time(1_600_000_000, 0);
+        filetime::set_file_times(&input, old, old)?;
+
+        let settings = ProcessingSettings {
+            jpeg_quality: 90,
+            preserve_timestamps: true,
+            ..ProcessingSettings::default()
+        };
+        let result = process_all_images(
+            std::slice::from_ref(&input),
+            std::slice::from_ref(&input),
+            std::slice::from_ref(&root),
+            &settings,
+            &InputOverrides::default(),
+            None,
+            None,
+        )?;
+        assert_eq!(result.processed_count, 1);
+
+        let output = get_output_path(&input, &root, "a.png", None).unwrap();
+        let mtime = filetime::FileTime::from_last_modification_time(&std::fs::metadata(output)?);
+        assert!((mtime.unix_seconds() - old.unix_seconds()).abs() <= 1);
+        Ok(())
+    }
+
+    #[test]
+    fn parallel_batch_matches_serial_batch() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let root = td.path().join("in");
+        std::fs::create_dir_all(&root)?;
+        let mut files = Vec::new();
+        for i in 0..6u8 {
+            let path = root.join(format!("{i}.png"));
+            RgbaImage::from_pixel(4 + u32::from(i), 4, Rgba([i * 40, 0, 0, 255])).save(&path)?;
+            files.push(path);
+        }
+        // One input outside every root fails in both runs
+        files.push(td.path().join("elsewhere.png"));
+
+        let settings = ProcessingSettings {
+            jpeg_quality: 90,
+            ..ProcessingSettings::default()
+        };
+        let run = |threads: usize| -> eyre::Result<ProcessAllResult> {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()?;
+            pool.install(|| {
+                process_all_images(
+                    &files,
+                    &files,
+                    std::slice::from_ref(&root),
+                    &settings,
+                    &InputOverrides::default(),
+                    None,
+                    None,
+                )
+            })
+        };
+        let serial = run(1)?;
+        let parallel = run(4)?;
+
+        assert_eq!(serial.processed_count, 6);
+        assert_eq!(parallel.processed_count, serial.processed_count);
+        assert_eq!(parallel.error_count, serial.error_count);
+        assert_eq!(parallel.errors, serial.errors);
+        assert_eq!(parallel.report, serial.report);
+        Ok(())
+    }
+
+    #[test]
+    fn batch_failures_carry_their_kind() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let root = td.path().join("in");
+        std::fs::create_dir_all(root.join("sub"))?;
+        let outside = td.path().join("outside.png");
+        RgbaImage::from_pixel(4, 4, Rgba([0, 0, 0, 255])).save(&outside)?;
+        let nested = root.join("sub").join("a.png");
+        RgbaImage::from_pixel(4, 4, Rgba([0, 0, 0, 255])).save(&nested)?;
+        // A file where the nested output directory should go blocks creating it
+        let blocked_dir = get_output_path(&nested, &root, "a.png", None)
+            .and_then(|p| p.parent().map(Path::to_path_buf))
+            .unwrap();
+        std::fs::create_dir_all(blocked_dir.parent().unwrap())?;
+        std::fs::write(&blocked_dir, "")?;
+
+        let files = vec![outside.clone(), nested.clone()];
+        let result = process_all_images(
+            &files,
+            &files,
+            std::slice::from_ref(&root),
+            &ProcessingSettings::default(),
+            &InputOverrides::default(),
+            None,
+            None,
+        )?;
+        assert_eq!(result.errors.len(), 2);
+        assert_eq!(result.errors[0], (outside, ProcessError::NoInputRoot));
+        assert_eq!(result.errors[1].0, nested);
+        let ProcessError::DirCreateFailed { dir, .. } = &result.errors[1].1 else {
+            panic!("expected a directory error, got {:?}", result.errors[1].1);
+        };
+        assert_eq!(dir, &blocked_dir);
+        assert!(result.errors[1].1.is_retryable());
+        Ok(())
+    }
+
+    #[test]
+    fn writing_over_a_directory_is_a_retryable_write_failure() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let input = td.path().join("a.png");
+        RgbaImage::from_pixel(4, 4, Rgba([0, 0, 0, 255])).save(&input)?;
+        let output = td.path().join("out.png");
+        std::fs::create_dir_all(&output)?;
+
+        let error = process_and_write(&input, &output, &ProcessingSettings::default())
+            .expect_err("a directory can't be overwritten with a file");
+        assert!(matches!(error, ProcessError::WriteFailed(_)), "{error}");
+        assert!(error.is_retryable());
+        Ok(())
+    }
+
+    #[test]
+    fn process_errors_describe_themselves() {
+        let decode = ProcessError::DecodeFailed("bad header".to_owned());
+        assert_eq!(decode.to_string(), "Failed to decode: bad header");
+        assert!(!decode.is_retryable());
+        assert!(!ProcessError::ProcessingFailed(String::new()).is_retryable());
+        assert!(!ProcessError::NoInputRoot.is_retryable());
+        assert_eq!(
+            ProcessError::OutputPathFailed.to_string(),
+            "Could not calculate output path"
+        );
+        assert_eq!(ProcessError::OutputPathFailed.kind(), "output path");
+        assert!(!ProcessError::OutputPathFailed.is_retryable());
+    }
+
+    #[test]
+    fn converted_outputs_take_the_format_extension() {
+        let path = PathBuf::from("out/photo.JPG");
+        assert_eq!(
+            with_output_format(path.clone(), Some(ImageFormat::Avif)),
+            PathBuf::from("out/photo.avif")
+        );
+        assert_eq!(with_output_format(path.clone(), None), path);
+        assert_eq!(
+            detect_format_from_path(Path::new("x.avif")),
+            ImageFormat::Avif
+        );
+    }
+
+    #[cfg(feature = "avif")]
+    #[test]
+    fn avif_output_is_smaller_than_png_for_photos() -> eyre::Result<()> {
+        // Smooth gradients with mild noise, like a photo rather than flat artwork
+        let mut seed = 1u32;
+        let img = RgbaImage::from_fn(128, 128, |x, y| {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            let noise = u8::try_from((seed >> 16) % 16).unwrap();
+            let r = u8::try_from(x * 2).unwrap().saturating_add(noise);
+            let g = u8::try_from(y * 2).unwrap().saturating_add(noise);
+            let b = u8::try_from((x + y) / 2).unwrap();
+            Rgba([r, g, b, 255])
+        });
+        let img = DynamicImage::ImageRgba8(img);
+        let settings = ProcessingSettings {
+            avif_speed: 10,
+            ..ProcessingSettings::default()
+        };
+        let png = encode_image(&img, ImageFormat::Png, &settings)?;
+        let avif = encode_image(&img, ImageFormat::Avif, &settings)?;
+        assert!(
+            avif.len() < png.len(),
+            "AVIF {} bytes, PNG {} bytes",
+            avif.len(),
+            png.len()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn report_has_entry_per_input_with_crop_flag() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let root = td.path().join("in");
+        std::fs::create_dir_all(&root)?;
+        let padded = root.join("padded.png");
+        let mut img = RgbaImage::from_pixel(20, 20, Rgba([255, 255, 255, 255]));
+        for x in 8..12 {
+            for y in 6..10 {
+                img.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+            }
+        }
+        img.save(&padded)?;
+        let solid = root.join("solid.png");
+        RgbaImage::from_pixel(8, 8, Rgba([0, 0, 0, 255])).save(&solid)?;
+
+        let settings = ProcessingSettings {
+            crop_to_content: true,
+            crop_threshold: 20,
+            jpeg_quality: 90,
+            ..ProcessingSettings::default()
+        };
+        let files = vec![padded.clone(), solid.clone()];
+        let result = process_all_images(
+            &files,
+            &files,
+            std::slice::from_ref(&root),
+            &settings,
+            &InputOverrides::default(),
+            None,
+            None,
+        )?;
+        assert_eq!(result.report.len(), 2);
+
+        let padded_entry = &result.report[0];
+        assert_eq!(padded_entry.input, padded.display().to_string());
+        assert!(padded_entry.was_cropped);
+        assert_eq!(
+            padded_entry.crop_bounds,
+            Some(CropBounds {
+                x: 8,
+                y: 6,
+                width: 4,
+                height: 4
+            })
+        );
+        assert_eq!(
+            (padded_entry.output_width, padded_entry.output_height),
+            (4, 4)
+        );
+
+        let solid_entry = &result.report[1];
+        assert_eq!(solid_entry.input, solid.display().to_string());
+        assert!(!solid_entry.was_cropped);
+        assert!(solid_entry.no_crop_detected);
+        assert_eq!(solid_entry.crop_bounds, None);
+        assert!(!padded_entry.no_crop_detected);
+
+        let json = facet_json::to_string(&ProcessReport::from(result))?;
+        assert!(json.contains("\"was_cropped\":true"));
+        Ok(())
+    }
+
+    /// White 40x40 image with a one pixel dark frame down the left edge and a dark square in the
+    /// middle, so the edge samples mix dark and white
+    fn framed_image() -> RgbaImage {
+        let mut img = RgbaImage::from_pixel(40, 40, Rgba([255, 255, 255, 255]));
+        for y in 0..40 {
+            img.put_pixel(0, y, Rgba([20, 20, 20, 255]));
+        }
+        for x in 15..25 {
+            for y in 15..25 {
+                img.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+            }
+        }
+        img
+    }
+
+    #[test]
+    fn mode_and_median_ignore_thin_dark_frame() {
+        let img = framed_image();
+        let white = Rgba([255, 255, 255, 255]);
+        assert_eq!(sample_edge_color(&img, BackgroundDetection::Mode), white);
+        assert_eq!(sample_edge_color(&img, BackgroundDetection::Median), white);
+        assert_ne!(sample_edge_color(&img, BackgroundDetection::Average), white);
+    }
+
+    #[test]
+    fn mode_detection_crops_framed_image() {
+        let img = DynamicImage::ImageRgba8(framed_image());
+
+        let (_, (_, _, width, _)) =
+            crop_to_content_with_threshold(&img, 20, BackgroundDetection::Average, None);
+        assert_eq!(
+            width, 40,
+            "averaged background matches nothing, so nothing is cropped"
+        );
+
+        // The frame is still content, but the white area right of the square is trimmed
+        let (_, bounds) = crop_to_content_with_threshold(&img, 20, BackgroundDetection::Mode, None);
+        assert_eq!(bounds, (0, 0, 25, 40));
+    }
+
+    #[test]
+    fn crop_mask_content_lies_exactly_within_the_bounds() {
+        let img = DynamicImage::ImageRgba8(framed_image());
+        let (cropped, bounds, mask) =
+            crop_to_content_with_mask(&img, 20, BackgroundDetection::Mode, None);
+        assert_eq!(mask.dimensions(), (img.width(), img.height()));
+        assert_eq!((cropped.width(), cropped.height()), (bounds.2, bounds.3));
+
+        let content: Vec<(u32, u32)> = mask
+            .enumerate_pixels()
+            .filter(|(_, _, p)| p[0] != 0)
+            .map(|(x, y, _)| (x, y))
+            .collect();
+        let min_x = content.iter().map(|&(x, _)| x).min().unwrap();
+        let max_x = content.iter().map(|&(x, _)| x).max().unwrap();
+        let min_y = content.iter().map(|&(_, y)| y).min().unwrap();
+        let max_y = content.iter().map(|&(_, y)| y).max().unwrap();
+        assert_eq!(bounds, (min_x, min_y, max_x - min_x + 1, max_y - min_y + 1));
+        assert_eq!(
+            crop_to_content_with_threshold(&img, 20, BackgroundDetection::Mode, None).1,
+            bounds
+        );
+    }
+
+    #[test]
+    fn transparent_edges_use_alpha_as_the_content_mask() {
+        // A black logo on a transparent background whose pixels are stored as black too
+        let mut logo = RgbaImage::from_pixel(32, 32, Rgba([0, 0, 0, 0]));
+        for x in 8..24 {
+            for y in 8..24 {
+                logo.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+            }
+        }
+        let img = DynamicImage::ImageRgba8(logo);
+
+        let preview = render_threshold_preview(
+            &img,
+            20,
+            BackgroundDetection::Average,
+            None,
+            BinarizationMode::KeepWhite,
+            1,
+            true,
+        );
+        assert_eq!(*preview.get_pixel(2, 2), Rgba([0, 0, 0, 255]));
+        assert_eq!(*preview.get_pixel(16, 16), Rgba([255, 255, 255, 255]));
+        assert_eq!(*preview.get_pixel(16, 8), Rgba([255, 0, 0, 255]));
+
+        let (_, bounds) =
+            crop_to_content_with_threshold(&img, 20, BackgroundDetection::Average, None);
+        assert_eq!(bounds, (8, 8, 16, 16));
+    }
+
+    #[test]
+    fn background_override_replaces_detected_color() {
+        let img = DynamicImage::ImageRgba8(framed_image());
+        let white = Some([255, 255, 255]);
+
+        // The averaged edge color matches nothing, but a fixed white background still crops
+        let (_, bounds) =
+            crop_to_content_with_threshold(&img, 20, BackgroundDetection::Average, white);
+        assert_eq!(bounds, (0, 0, 25, 40));
+
+        let preview = render_threshold_preview(
+            &img,
+            20,
+            BackgroundDetection::Average,
+            white,
+            BinarizationMode::KeepWhite,
+            1,
+            true,
+        );
+        assert_eq!(*preview.get_pixel(30, 5), Rgba([0, 0, 0, 255]));
+        assert_eq!(*preview.get_pixel(20, 20), Rgba([255, 255, 255, 255]));
+    }
+
+    #[test]
+    fn mode_prefers_first_color_on_ties() {
+        let a = Rgba([1, 2, 3, 255]);
+        let b = Rgba([4, 5, 6, 255]);
+        assert_eq!(mode_color(&[a, b, b, a]), a);
+        assert_eq!(mode_color(&[b, a, a, b]), b);
+    }
+
+    #[test]
+    fn small_crops_expand_symmetrically() {
+        assert_eq!(
+            expand_to_minimum((45, 40, 10, 20), 100, 100, 30),
+            (35, 35, 30, 30)
+        );
+        // Crops already at the minimum are untouched
+        assert_eq!(
+            expand_to_minimum((45, 40, 10, 20), 100, 100, 0),
+            (45, 40, 10, 20)
+        );
+        assert_eq!(
+            expand_to_minimum((10, 10, 50, 50), 100, 100, 30),
+            (10, 10, 50, 50)
+        );
+    }
+
+    #[test]
+    fn expansion_clamps_at_image_edges() {
+        // Near the top-left corner the crop shifts inwards rather than leaving the image
+        assert_eq!(
+            expand_to_minimum((2, 0, 4, 4), 100, 100, 20),
+            (0, 0, 20, 20)
+        );
+        // Near the bottom-right corner it shifts back
+        assert_eq!(
+            expand_to_minimum((95, 96, 4, 4), 100, 100, 20),
+            (80, 80, 20, 20)
+        );
+        // A minimum larger than the image is capped at the image size
+        assert_eq!(expand_to_minimum((5, 5, 4, 4), 30, 10, 50), (0, 0, 30, 10));
+    }
+
+    #[test]
+    fn process_image_applies_minimum_dimension() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let input = td.path().join("dot.png");
+        let mut img = RgbaImage::from_pixel(40, 40, Rgba([255, 255, 255, 255]));
+        for x in 19..21 {
+            for y in 19..21 {
+                img.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+            }
+        }
+        img.save(&input)?;
+
+        let settings = ProcessingSettings {
+            crop_to_content: true,
+            crop_threshold: 20,
+            min_output_dimension: 16,
+            ..ProcessingSettings::default()
+        };
+        let processed = process_image(&input, &settings)?;
+        assert!(processed.was_cropped);
+        assert_eq!((processed.output_width, processed.output_height), (16, 16));
+        assert_eq!(processed.crop_bounds, Some((12, 12, 16, 16)));
+        Ok(())
+    }
+
+    #[test]
+    fn progressive_toggle_changes_jpeg_encoding() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let input = td.path().join("gradient.jpg");
+        image::RgbImage::from_fn(64, 48, |x, y| {
+            image::Rgb([
+                u8::try_from(x * 4).unwrap(),
+                u8::try_from(y * 5).unwrap(),
+                128,
+            ])
+        })
+        .save(&input)?;
+        // Start-of-frame markers for baseline and progressive DCT
+        let has_marker = |data: &[u8], marker: u8| data.windows(2).any(|w| w == [0xFF, marker]);
+
+        let baseline = process_image(&input, &ProcessingSettings::default())?;
+        let progressive = process_image(
+            &input,
+            &ProcessingSettings {
+                jpeg_progressive: true,
+                ..ProcessingSettings::default()
+            },
+        )?;
+        assert_ne!(baseline.data, progressive.data);
+        assert!(has_marker(&baseline.data, 0xC0));
+        assert!(has_marker(&progressive.data, 0xC2));
+        assert_eq!(progressive.estimated_size, progressive.data.len() as u64);
+
+        let decoded = image::load_from_memory(&progressive.data)?;
+        assert_eq!((decoded.width(), decoded.height()), (64, 48));
+        Ok(())
+    }
+
+    #[test]
+    fn full_chroma_jpeg_keeps_red_text_sharper() -> eyre::Result<()> {
+        // One-pixel red strokes on white, the worst case for subsampled color
+        let img = DynamicImage::ImageRgb8(image::RgbImage::from_fn(64, 32, |x, _| {
+            if x % 4 == 1 {
+                image::Rgb([220, 0, 0])
+            } else {
+                image::Rgb([255, 255, 255])
+            }
+        }));
+        let encode = |jpeg_subsampling| {
+            let settings = ProcessingSettings {
+                jpeg_quality: 95,
+                jpeg_subsampling,
+                ..ProcessingSettings::default()
+            };
+            encode_image(&img, ImageFormat::Jpeg, &settings)
+        };
+        let error = |data: &[u8]| -> eyre::Result<u64> {
+            let decoded = image::load_from_memory(data)?.to_rgb8();
+            Ok(decoded
+                .as_raw()
+                .iter()
+                .zip(img.as_bytes())
+                .map(|(&a, &b)| u64::from(a.abs_diff(b)))
+                .sum())
+        };
+
+        let full = encode(JpegSubsampling::S444)?;
+        let quarter = encode(JpegSubsampling::S420)?;
+        assert_ne!(full, quarter);
+        assert!(error(&full)? < error(&quarter)?);
+        Ok(())
+    }
+
+    #[test]
+    fn bmp_and_tiff_inputs_are_written_in_their_own_format() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let img = image::RgbImage::from_pixel(6, 4, image::Rgb([10, 120, 240]));
+        for (name, format) in [("in.tiff", ImageFormat::Tiff), ("in.bmp", ImageFormat::Bmp)] {
+            let path = td.path().join(name);
+            img.save(&path)?;
+            let processed = process_image(&path, &ProcessingSettings::default())?;
+            assert_eq!(image::guess_format(&processed.data)?, format, "{name}");
+            let decoded = image::load_from_memory_with_format(&processed.data, format)?;
+            assert_eq!(decoded.to_rgb8(), img);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn contact_sheet_tiles_images_into_a_grid() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let paths: Vec<PathBuf> = [(40, 20), (10, 30), (25, 25)]
+            .iter()
+            .enumerate()
+            .map(|(i, &(w, h))| {
+                let path = td.path().join(format!("item-{i}.png"));
+                RgbaImage::from_pixel(w, h, Rgba([200, 30, 30, 255])).save(&path)?;
+                Ok(path)
+            })
+            .collect::<eyre::Result<_>>()?;
+        let options = ContactSheetOptions {
+            columns: 2,
+            cell_size: 100,
+        };
+
+        let sheet = build_contact_sheet(&paths, &options)?.to_rgba8();
+        let row_height = 100 + options.label_height();
+        assert_eq!(sheet.dimensions(), (200, 2 * row_height));
+
+        // Thumbnails are centered in their cells, the empty fourth cell stays white and every
+        // cell gets a label underneath
+        assert_eq!(sheet.get_pixel(50, 50).0, [200, 30, 30, 255]);
+        assert_eq!(sheet.get_pixel(150, 50).0, [200, 30, 30, 255]);
+        assert_eq!(sheet.get_pixel(50, row_height + 50).0, [200, 30, 30, 255]);
+        assert_eq!(
+            sheet.get_pixel(150, row_height + 50).0,
+            [255, 255, 255, 255]
+        );
+        let label_has_ink = |y: u32| (0..100).any(|x| sheet.get_pixel(x, y + 100 + 6).0[0] < 200);
+        assert!(label_has_ink(0));
+        assert!(label_has_ink(row_height));
+
+        // Columns never exceed the number of images
+        let wide = ContactSheetOptions {
+            columns: 10,
+            cell_size: 50,
+        };
+        assert_eq!(build_contact_sheet(&paths, &wide)?.width(), 150);
+        Ok(())
+    }
+
+    #[test]
+    fn contact_sheet_handles_extreme_cell_sizes() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let path = td.path().join("item.png");
+        RgbaImage::from_pixel(8, 8, Rgba([200, 30, 30, 255])).save(&path)?;
+        let missing = td.path().join("missing.png");
+        let paths = vec![path, missing];
+
+        // Cells smaller than their padding still get a thumbnail and a blank cell
+        for cell_size in 1..4 {
+            let tiny = ContactSheetOptions {
+                columns: 2,
+                cell_size,
+            };
+            let sheet = build_contact_sheet(&paths, &tiny)?;
+            assert_eq!(sheet.width(), 2 * cell_size);
+        }
+
+        // Sheets wider than u32 pixels are refused instead of overflowing
+        let huge = ContactSheetOptions {
+            columns: 2,
+            cell_size: u32::MAX / 2 + 1,
+        };
+        assert!(build_contact_sheet(&paths, &huge).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn files_under_an_overridden_root_use_its_settings() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let photos = td.path().join("photos");
+        let scans = td.path().join("scans");
+        let mut files = Vec::new();
+        for root in [&photos, &scans] {
+            std::fs::create_dir_all(root)?;
+            let file = root.join("item.jpg");
+            image::RgbImage::from_fn(64, 64, |x, y| {
+                image::Rgb([
+                    u8::try_from(x * 4).unwrap(),
+                    u8::try_from(y * 4).unwrap(),
+                    u8::try_from((x * y) % 256).unwrap(),
+                ])
+            })
+            .save(&file)?;
+            files.push(file);
+        }
+        let roots = [photos.clone(), scans.clone()];
+        let mut overrides = InputOverrides::default();
+        overrides.set(
+            &scans,
+            crate::input_overrides::SettingsOverride {
+                jpeg_quality: Some(10),
+                ..Default::default()
+            },
+        );
+
+        let settings = ProcessingSettings {
+            jpeg_quality: 95,
+            ..ProcessingSettings::default()
+        };
+        let result = process_all_images(&files, &files, &roots, &settings, &overrides, None, None)?;
+        assert_eq!(result.processed_count, 2);
+
+        // The scans output was encoded at the override's much lower quality
+        let [photo, scan] = [&photos, &scans].map(|root| {
+            let output = get_output_path(&root.join("item.jpg"), root, "item.jpg", None).unwrap();
+            std::fs::metadata(output).map(|m| m.len())
+        });
+        let (photo, scan) = (photo?, scan?);
+        assert!(scan * 2 < photo, "scan {scan} bytes vs photo {photo} bytes");
+        let direct = process_image(
+            &files[1],
+            &ProcessingSettings {
+                jpeg_quality: 10,
+                ..ProcessingSettings::default()
+            },
+        )?;
+        assert_eq!(scan, direct.data.len() as u64);
+        Ok(())
+    }
+
+    #[test]
+    fn per_input_descriptions_and_cancellation_apply_to_a_batch() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let root = td.path().join("in");
+        std::fs::create_dir_all(&root)?;
+        let files: Vec<PathBuf> = ["a.png", "b.png"].iter().map(|n| root.join(n)).collect();
+        for file in &files {
+            RgbaImage::from_pixel(4, 4, Rgba([200, 30, 30, 255])).save(file)?;
+        }
+        let roots = [root.clone()];
+        let settings = ProcessingSettings {
+            description: Some("Shared".to_owned()),
+            input_descriptions: Arc::new([(files[0].clone(), "Widget $4.99".to_owned())].into()),
+            ..ProcessingSettings::default()
+        };
+        let overrides = InputOverrides::default();
+        let result = process_all_images(&files, &files, &roots, &settings, &overrides, None, None)?;
+        assert_eq!(result.processed_count, 2);
+        let descriptions: Vec<_> = result
+            .report
+            .iter()
+            .map(|entry| read_exif_fields(Path::new(&entry.output)).description)
+            .collect();
+        assert_eq!(
+            descriptions,
+            [Some("Widget $4.99".to_owned()), Some("Shared".to_owned())]
+        );
+
+        // Once cancelled, no input is started
+        let cancelled = ProcessingSettings {
+            cancel: Some(Arc::new(AtomicBool::new(true))),
+            ..settings
+        };
+        let result =
+            process_all_images(&files, &files, &roots, &cancelled, &overrides, None, None)?;
+        assert_eq!(result.processed_count, 0);
+        assert!(
+            result
+                .errors
+                .iter()
+                .all(|(_, e)| *e == ProcessError::Cancelled)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn inputs_renamed_to_the_same_output_fail_before_writing() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let root = td.path().join("in");
+        std::fs::create_dir_all(&root)?;
+        let files = vec![root.join("a.png"), root.join("b.png"), root.join("c.png")];
+        for file in &files {
+            RgbaImage::from_pixel(4, 4, Rgba([10, 20, 30, 255])).save(file)?;
+        }
+        let renamed = vec![
+            root.join("same.png"),
+            root.join("same.png"),
+            root.join("c.png"),
+        ];
+
+        let error = process_all_images(
+            &files,
+            &renamed,
+            std::slice::from_ref(&root),
+            &ProcessingSettings::default(),
+            &InputOverrides::default(),
+            None,
+            None,
+        )
+        .unwrap_err()
+        .to_string();
+        assert!(error.contains("same.png"), "{error}");
+        assert!(
+            error.contains("a.png") && error.contains("b.png"),
+            "{error}"
+        );
+        assert!(!error.contains("c.png"), "{error}");
+
+        // Nothing was written, not even the input that didn't collide
+        assert!(!get_output_dir(&root, None).exists());
+        Ok(())
+    }
+
+    #[test]
+    fn dry_run_reports_outputs_without_writing() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let root = td.path().join("in");
+        std::fs::create_dir_all(&root)?;
+        let image = root.join("a.png");
+        let mut img = RgbaImage::from_pixel(20, 20, Rgba([255, 255, 255, 255]));
+        for x in 5..15 {
+            for y in 5..15 {
+                img.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+            }
+        }
+        img.save(&image)?;
+        let broken = root.join("broken.png");
+        std::fs::write(&broken, b"not a png")?;
+        let files = vec![image, broken];
+
+        let result = process_all_images(
+            &files,
+            &files,
+            std::slice::from_ref(&root),
+            &ProcessingSettings {
+                crop_to_content: true,
+                crop_threshold: 20,
+                on_decode_error: DecodeErrorPolicy::CopyThrough,
+                dry_run: true,
+                ..ProcessingSettings::default()
+            },
+            &InputOverrides::default(),
+            None,
+            None,
+        )?;
+        assert_eq!((result.processed_count, result.copied_count), (1, 1));
+        assert!(result.report[0].was_cropped);
+        assert!(result.report[0].estimated_size > 0);
+        assert!(!get_output_dir(&root, None).exists());
+        Ok(())
+    }
+
+    #[test]
+    fn uniquify_path_counts_up_before_the_extension() {
+        let taken = [
+            PathBuf::from("/out/a.png"),
+            PathBuf::from("/out/a (1).png"),
+            PathBuf::from("/out/README"),
+        ];
+        let is_taken = |p: &Path| taken.iter().any(|t| t == p);
+        assert_eq!(
+            uniquify_path(Path::new("/out/b.png"), is_taken),
+            PathBuf::from("/out/b.png")
+        );
+        assert_eq!(
+            uniquify_path(Path::new("/out/a.png"), is_taken),
+            PathBuf::from("/out/a (2).png")
+        );
+        assert_eq!(
+            uniquify_path(Path::new("/out/README"), is_taken),
+            PathBuf::from("/out/README (1)")
+        );
+    }
+
+    #[test]
+    fn existing_outputs_follow_the_conflict_policy() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let root = td.path().join("in");
+        std::fs::create_dir_all(&root)?;
+        let files = vec![root.join("a.png"), root.join("a (1).png")];
+        for file in &files {
+            RgbaImage::from_pixel(4, 4, Rgba([10, 20, 30, 255])).save(file)?;
+        }
+        let existing = get_output_path(&files[0], &root, "a.png", None).unwrap();
+        let out_dir = existing.parent().unwrap().to_path_buf();
+        let run = |on_conflict| {
+            std::fs::create_dir_all(&out_dir)?;
+            for entry in std::fs::read_dir(&out_dir)? {
+                std::fs::remove_file(entry?.path())?;
+            }
+            std::fs::write(&existing, b"keep me")?;
+            process_all_images(
+                &files[..1],
+                &files[..1],
+                std::slice::from_ref(&root),
+                &ProcessingSettings {
+                    on_conflict,
+                    ..ProcessingSettings::default()
+                },
+                &InputOverrides::default(),
+                None,
+                None,
+            )
+        };
+
+        let result = run(OutputConflictPolicy::Overwrite)?;
+        assert_eq!(result.processed_count, 1);
+        assert_ne!(std::fs::read(&existing)?, b"keep me");
+
+        let result = run(OutputConflictPolicy::Skip)?;
+        assert_eq!((result.processed_count, result.existing_count), (0, 1));
+        assert_eq!(std::fs::read(&existing)?, b"keep me");
+
+        let result = run(OutputConflictPolicy::Rename)?;
+        assert_eq!(result.processed_count, 1);
+        assert_eq!(std::fs::read(&existing)?, b"keep me");
+        assert_eq!(
+            result.report[0].output,
+            out_dir.join("a (1).png").display().to_string()
+        );
+
+        // A renamed output steps over another input's output in the same batch
+        std::fs::remove_file(out_dir.join("a (1).png"))?;
+        let result = process_all_images(
+            &files,
+            &files,
+            std::slice::from_ref(&root),
+            &ProcessingSettings {
+                on_conflict: OutputConflictPolicy::Rename,
+                ..ProcessingSettings::default()
+            },
+            &InputOverrides::default(),
+            None,
+            None,
+        )?;
+        let outputs: Vec<_> = result.report.iter().map(|e| e.output.clone()).collect();
+        assert_eq!(
+            outputs,
+            [
+                out_dir.join("a (2).png").display().to_string(),
+                out_dir.join("a (1).png").display().to_string()
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[expect(clippy::cast_possible_truncation)]
+    fn output_hash_is_stable_and_tracks_settings() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let input = td.path().join("photo.jpg");
+        RgbaImage::from_fn(32, 32, |x, y| {
+            Rgba([(x * 8) as u8, (y * 8) as u8, ((x + y) * 4) as u8, 255])
+        })
+        .save(&input)?;
+        let settings = |jpeg_quality| ProcessingSettings {
+            jpeg_quality,
+            hash_outputs: true,
+            ..ProcessingSettings::default()
+        };
+
+        let first = process_image(&input, &settings(90))?;
+        let again = process_image(&input, &settings(90))?;
+        let lower = process_image(&input, &settings(40))?;
+        let hash = first.content_hash.clone().unwrap();
+        assert_eq!(hash.len(), 64);
+        assert_eq!(again.content_hash.as_deref(), Some(hash.as_str()));
+        assert_ne!(lower.content_hash.as_deref(), Some(hash.as_str()));
+        assert!(
+            process_image(&input, &ProcessingSettings::default())?
+                .content_hash
+                .is_none()
+        );
+
+        let output = td.path().join("out.jpg");
+        write_output(&input, &output, &first, &settings(90))?;
+        assert_eq!(
+            std::fs::read_to_string(hash_sidecar_path(&output))?,
+            format!("{hash}  out.jpg\n")
+        );
+        assert_eq!(
+            ProcessReportEntry::new(&input, &output, &first).hash,
+            Some(hash)
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[expect(clippy::cast_possible_truncation)]
+    fn truncated_jpeg_gets_an_integrity_warning() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let whole = td.path().join("whole.jpg");
+        RgbaImage::from_fn(64, 64, |x, y| {
+            Rgba([(x * 4) as u8, (y * 4) as u8, ((x + y) * 2) as u8, 255])
+        })
+        .save(&whole)?;
+        let bytes = std::fs::read(&whole)?;
+        let truncated = td.path().join("truncated.jpg");
+        std::fs::write(&truncated, &bytes[..bytes.len() * 2 / 3])?;
+
+        let img = open_image(&whole)?;
+        assert_eq!(integrity_warning(&whole, &img), None);
+        let warning = integrity_warning(&truncated, &img).unwrap();
+        assert!(warning.contains("truncated"), "{warning}");
+
+        // Decoders may fill in the missing part; when they do, the warning reaches the report
+        if let Ok(processed) = process_image(&truncated, &ProcessingSettings::default()) {
+            let entry = ProcessReportEntry::new(&truncated, &whole, &processed);
+            assert_eq!(entry.warning, Some(warning));
+        }
+        Ok(())
+    }
+
+    /// A minimal RGB ICC profile whose `desc` tag (ICC v2 ASCII type) names it `name`
+    fn icc_profile_named(name: &str) -> Vec<u8> {
+        let be = |n: usize| u32::try_from(n).unwrap().to_be_bytes();
+        let mut desc = b"desc\0\0\0\0".to_vec();
+        desc.extend_from_slice(&be(name.len() + 1));
+        desc.extend_from_slice(name.as_bytes());
+        desc.push(0);
+        let mut icc = vec![0u8; 128];
+        icc[16..20].copy_from_slice(b"RGB ");
+        icc.extend_from_slice(&be(1));
+        icc.extend_from_slice(b"desc");
+        icc.extend_from_slice(&be(128 + 4 + 12));
+        icc.extend_from_slice(&be(desc.len()));
+        icc.extend_from_slice(&desc);
+        icc
+    }
+
+    #[test]
+    fn non_srgb_icc_profile_is_detected_and_reported() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let plain = td.path().join("plain.jpg");
+        image::RgbImage::from_pixel(16, 16, image::Rgb([200, 30, 30])).save(&plain)?;
+        assert_eq!(read_icc_profile(&plain), None);
+        let with_profile = |name: &str| -> eyre::Result<PathBuf> {
+            let mut jpeg = Jpeg::from_bytes(std::fs::read(&plain)?.into())?;
+            jpeg.set_icc_profile(Some(icc_profile_named(name).into()));
+            let mut data = Vec::new();
+            jpeg.encoder().write_to(&mut data)?;
+            let path = td.path().join(format!("{name}.jpg"));
+            std::fs::write(&path, data)?;
+            Ok(path)
+        };
+
+        let p3 = with_profile("Display P3")?;
+        let icc = read_icc_profile(&p3).unwrap();
+        assert_eq!(icc_profile_description(&icc).as_deref(), Some("Display P3"));
+        assert!(!is_srgb_profile(&icc));
+        let srgb = with_profile("sRGB IEC61966-2.1")?;
+        assert!(is_srgb_profile(&read_icc_profile(&srgb).unwrap()));
+
+        let settings = ProcessingSettings::default();
+        let processed = process_image(&p3, &settings)?;
+        let entry = ProcessReportEntry::new(&p3, &plain, &processed);
+        assert!(
+            entry
+                .color_profile
+                .is_some_and(|w| w.contains("Display P3"))
+        );
+        assert_eq!(process_image(&srgb, &settings)?.color_profile_warning, None);
+        Ok(())
+    }
+
+    #[test]
+    fn animated_gif_is_processed_as_its_first_frame_or_skipped() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let input = td.path().join("spinner.gif");
+        let red = Rgba([255, 0, 0, 255]);
+        let blue = Rgba([0, 0, 255, 255]);
+        image::codecs::gif::GifEncoder::new(std::fs::File::create(&input)?).encode_frames(
+            [red, blue].map(|color| image::Frame::new(RgbaImage::from_pixel(8, 8, color))),
+        )?;
+        assert!(is_animated(&input));
+
+        let output = td.path().join("out.gif");
+        let outcome = process_and_write(&input, &output, &ProcessingSettings::default())?;
+        assert!(matches!(outcome, ProcessOutcome::Processed(_)));
+        assert!(!is_animated(&output));
+        assert_eq!(open_image(&output)?.to_rgba8().get_pixel(0, 0), &red);
+
+        std::fs::remove_file(&output)?;
+        let skip = ProcessingSettings {
+            skip_animated: true,
+            ..ProcessingSettings::default()
+        };
+        let outcome = process_and_write(&input, &output, &skip)?;
+        assert!(matches!(outcome, ProcessOutcome::Skipped));
+        assert!(!output.exists());
+        assert!(process_image(&input, &skip).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn threshold_preview_box_is_dashed_when_crop_is_off() {
+        let mut img = RgbaImage::from_pixel(64, 64, Rgba([255, 255, 255, 255]));
+        for x in 8..56 {
+            for y in 8..56 {
+                img.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+            }
+        }
+        let img = DynamicImage::ImageRgba8(img);
+        let red_on_top_edge = |crop_enabled| {
+            let preview = render_threshold_preview(
+                &img,
+                20,
+                BackgroundDetection::default(),
+                None,
+                BinarizationMode::default(),
+                1,
+                crop_enabled,
+            );
+            (8..56)
+                .filter(|&x| *preview.get_pixel(x, 8) == Rgba([255, 0, 0, 255]))
+                .count()
+        };
+        assert_eq!(red_on_top_edge(true), 48);
+        let dashed = red_on_top_edge(false);
+        assert!(dashed > 0 && dashed < 48, "{dashed} red pixels");
+    }
+
+    #[test]
+    fn flatten_writes_nested_inputs_to_unique_flat_names() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let root = td.path().join("in");
+        std::fs::create_dir_all(root.join("sub").join("deeper"))?;
+        let files = vec![
+            root.join("a.png"),
+            root.join("sub").join("a.png"),
+            root.join("sub_a.png"),
+            root.join("sub").join("deeper").join("b.png"),
+        ];
+        for file in &files {
+            RgbaImage::from_pixel(4, 4, Rgba([10, 20, 30, 255])).save(file)?;
+        }
+
+        let result = process_all_images(
+            &files,
+            &files,
+            std::slice::from_ref(&root),
+            &ProcessingSettings {
+                flatten: true,
+                ..ProcessingSettings::default()
+            },
+            &InputOverrides::default(),
+            None,
+            None,
+        )?;
+        assert_eq!(result.processed_count, 4);
+        let out_dir = get_output_dir(&root, None);
+        let mut names: Vec<String> = std::fs::read_dir(&out_dir)?
+            .map(|entry| Ok(entry?.file_name().to_string_lossy().to_string()))
+            .collect::<std::io::Result<_>>()?;
+        names.sort();
+        assert_eq!(
+            names,
+            ["a.png", "sub_a (1).png", "sub_a.png", "sub_deeper_b.png"]
+        );
+
+        assert_eq!(
+            get_flat_output_path(&files[3], &root, "b.png", Some(Path::new("/flat"))),
+            Some(PathBuf::from("/flat/in_sub_deeper_b.png"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn progress_lines_are_one_json_object_per_input() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let root = td.path().join("in");
+        std::fs::create_dir_all(&root)?;
+        let good = root.join("good.png");
+        RgbaImage::from_pixel(4, 4, Rgba([10, 20, 30, 255])).save(&good)?;
+        let broken = root.join("broken.png");
+        std::fs::write(&broken, b"not an image")?;
+        let files = vec![good.clone(), broken.clone()];
+
+        let (sender, receiver) = mpsc::channel();
+        process_all_images(
+            &files,
+            &files,
+            std::slice::from_ref(&root),
+            &ProcessingSettings::default(),
+            &InputOverrides::default(),
+            None,
+            Some(&sender),
+        )?;
+        drop(sender);
+        let mut total = 0;
+        let mut lines = Vec::new();
+        for event in receiver {
+            if let ProcessProgress::Started { total: started } = event {
+                total = started;
+            }
+            if let Some(line) = event.progress_line(total) {
+                lines.push((line.index, facet_json::to_string(&line)?));
+            }
+        }
+        lines.sort();
+
+        assert_eq!(lines.len(), 2);
+        let (good_line, broken_line) = (&lines[0].1, &lines[1].1);
+        assert!(!good_line.contains('\n') && !broken_line.contains('\n'));
+        assert!(
+            good_line.starts_with("{\"index\":0,\"total\":2,"),
+            "{good_line}"
+        );
+        assert!(good_line.contains("\"ok\":true"), "{good_line}");
+        assert!(
+            broken_line.starts_with("{\"index\":1,\"total\":2,"),
+            "{broken_line}"
+        );
+        assert!(broken_line.contains("\"ok\":false"), "{broken_line}");
+        assert!(broken_line.contains("Failed to decode"), "{broken_line}");
+        Ok(())
+    }
+
+    #[test]
+    fn progress_events_describe_each_input() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let root = td.path().join("in");
+        std::fs::create_dir_all(&root)?;
+        let good = root.join("good.png");
+        RgbaImage::from_pixel(4, 4, Rgba([10, 20, 30, 255])).save(&good)?;
+        let broken = root.join("broken.png");
+        std::fs::write(&broken, b"not an image")?;
+        let files = vec![good.clone(), broken.clone()];
+
+        let (sender, receiver) = mpsc::channel();
+        let result = process_all_images(
+            &files,
+            &files,
+            std::slice::from_ref(&root),
+            &ProcessingSettings::default(),
+            &InputOverrides::default(),
+            None,
+            Some(&sender),
+        )?;
+        drop(sender);
+        let mut events: Vec<_> = receiver.into_iter().collect();
+        assert_eq!(events.len(), 4);
+        // Inputs are processed in parallel, so item events arrive in completion order
+        events[1..3].sort_by_key(|event| match event {
+            ProcessProgress::Item { index, .. } => *index,
+            _ => usize::MAX,
+        });
+
+        assert!(matches!(events[0], ProcessProgress::Started { total: 2 }));
+        let ProcessProgress::Item {
+            index: 0,
+            path,
+            result: ProcessItemResult::Processed(entry),
+        } = &events[1]
+        else {
+            panic!(
+                "expected the first input to be processed, got {:?}",
+                events[1]
+            );
+        };
+        assert_eq!(path, &good);
+        assert_eq!(entry, &result.report[0]);
+        let ProcessProgress::Item {
+            index: 1,
+            path,
+            result: ProcessItemResult::Failed(error),
+        } = &events[2]
+        else {
+            panic!("expected the second input to fail, got {:?}", events[2]);
+        };
+        assert_eq!(path, &broken);
+        assert!(matches!(error, ProcessError::DecodeFailed(_)), "{error}");
+        let ProcessProgress::Finished { result: finished } = &events[3] else {
+            panic!("expected a final summary, got {:?}", events[3]);
+        };
+        assert_eq!(
+            (finished.processed_count, finished.error_count),
+            (result.processed_count, result.error_count)
+        );
+        assert_eq!((finished.processed_count, finished.error_count), (1, 1));
+        Ok(())
+    }
+
+    #[test]
+    fn decode_error_policies() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let root = td.path().join("in");
+        std::fs::create_dir_all(&root)?;
+        let good = root.join("good.png");
+        RgbaImage::from_pixel(4, 4, Rgba([10, 20, 30, 255])).save(&good)?;
+        let bad = root.join("notes.jpg");
+        std::fs::write(&bad, "not actually a picture")?;
+        let inputs = [good.clone(), bad.clone()];
+        let run = |on_decode_error| {
+            process_all_images(
+                &inputs,
+                &inputs,
+                std::slice::from_ref(&root),
+                &ProcessingSettings {
+                    on_decode_error,
+                    ..ProcessingSettings::default()
+                },
+                &InputOverrides::default(),
+                None,
+                None,
+            )
+        };
+        let bad_output = get_output_path(&bad, &root, "notes.jpg", None).unwrap();
+
+        let result = run(DecodeErrorPolicy::Error)?;
+        assert_eq!((result.processed_count, result.error_count), (1, 1));
+        assert_eq!(result.errors[0].0, bad);
+        assert!(matches!(result.errors[0].1, ProcessError::DecodeFailed(_)));
+        assert!(!bad_output.exists());
+
+        let result = run(DecodeErrorPolicy::Skip)?;
+        assert_eq!((result.processed_count, result.error_count), (1, 0));
+        assert_eq!((result.skipped_count, result.copied_count), (1, 0));
+        assert!(!bad_output.exists());
+
+        let result = run(DecodeErrorPolicy::CopyThrough)?;
+        assert_eq!((result.processed_count, result.error_count), (1, 0));
+        assert_eq!((result.skipped_count, result.copied_count), (0, 1));
+        assert_eq!(std::fs::read(&bad_output)?, b"not actually a picture");
+        assert_eq!(result.report.len(), 1);
+
+        // Missing files are still errors, whatever the policy
+        let missing = root.join("gone.jpg");
+        let output = td.path().join("gone-out.jpg");
+        let settings = ProcessingSettings {
+            on_decode_error: DecodeErrorPolicy::CopyThrough,
+            ..ProcessingSettings::default()
+        };
+        assert!(matches!(
+            process_and_write(&missing, &output, &settings),
+            Err(ProcessError::DecodeFailed(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn deskew_straightens_a_rotated_rectangle() -> eyre::Result<()> {
+        let white = Rgba([255, 255, 255, 255]);
+        let straight = RgbaImage::from_fn(240, 180, |x, y| {
+            if (60..180).contains(&x) && (60..120).contains(&y) {
+                Rgba([0, 0, 0, 255])
+            } else {
+                white
+            }
+        });
+        let estimate = |img: &RgbaImage| {
+            estimate_skew_angle(
+                &DynamicImage::ImageRgba8(img.clone()),
+                40,
+                BackgroundDetection::default(),
+                None,
+                DEFAULT_MAX_DESKEW_DEGREES,
+            )
+        };
+        assert!(estimate(&straight).abs() < 0.2);
+
+        let skewed = rotate_about_center(&straight, 3.0, white);
+        let angle = estimate(&skewed);
+        assert!((angle - 3.0).abs() < 0.2, "estimated {angle}");
+
+        let td = tempdir()?;
+        let input = td.path().join("scan.png");
+        skewed.save(&input)?;
+        let processed = process_image(
+            &input,
+            &ProcessingSettings {
+                deskew: true,
+                crop_threshold: 40,
+                ..ProcessingSettings::default()
+            },
+        )?;
+        let straightened = image::load_from_memory(&processed.data)?.to_rgba8();
+        assert_eq!(straightened.dimensions(), skewed.dimensions());
+        let residual = estimate(&straightened);
+        assert!(residual.abs() < 0.2, "residual skew {residual}");
+        Ok(())
+    }
+
+    #[test]
+    fn brightness_raises_mean_luminance() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let input = td.path().join("dim.png");
+        RgbaImage::from_fn(16, 16, |x, y| {
+            let v = u8::try_from(20 + x * 3 + y).unwrap();
+            Rgba([v, v, v, 255])
+        })
+        .save(&input)?;
+        let mean_luma = |settings: &ProcessingSettings| -> eyre::Result<f64> {
+            let processed = process_image(&input, settings)?;
+            let luma = image::load_from_memory(&processed.data)?.to_luma8();
+            Ok(luma.pixels().map(|p| f64::from(p.0[0])).sum::<f64>() / 256.0)
+        };
+
+        let unchanged = mean_luma(&ProcessingSettings::default())?;
+        let brighter = mean_luma(&ProcessingSettings {
+            brightness: 40,
+            ..ProcessingSettings::default()
+        })?;
+        assert!(brighter > unchanged + 30.0, "{brighter} vs {unchanged}");
+        let gamma = mean_luma(&ProcessingSettings {
+            gamma: 2.0,
+            ..ProcessingSettings::default()
+        })?;
+        assert!(gamma > unchanged, "{gamma} vs {unchanged}");
+
+        // The defaults leave pixels exactly as they were
+        let untouched = adjust_tone(image::open(&input)?, 0, 0.0, 0.0);
+        assert_eq!(untouched.to_rgba8(), image::open(&input)?.to_rgba8());
+        Ok(())
+    }
+
+    #[test]
+    fn sharpening_changes_output_pixels() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let input = td.path().join("edge.png");
+        RgbaImage::from_fn(32, 32, |x, _| {
+            let v = u8::try_from(x * 8).unwrap();
+            Rgba([v, v, v, 255])
+        })
+        .save(&input)?;
+        let decode = |sharpen_sigma| -> eyre::Result<RgbaImage> {
+            let processed = process_image(
+                &input,
+                &ProcessingSettings {
+                    sharpen_sigma,
+                    ..ProcessingSettings::default()
+                },
+            )?;
+            Ok(image::load_from_memory(&processed.data)?.to_rgba8())
+        };
+
+        let original = image::open(&input)?.to_rgba8();
+        assert_eq!(decode(0.0)?, original);
+        let sharpened = decode(2.0)?;
+        assert_eq!(sharpened.dimensions(), original.dimensions());
+        assert_ne!(sharpened, original);
+        Ok(())
+    }
+
+    #[test]
+    fn png_compression_and_quantization_shrink_output() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let input = td.path().join("gradient.png");
+        RgbaImage::from_fn(128, 96, |x, y| {
+            Rgba([
+                u8::try_from(x * 2).unwrap(),
+                u8::try_from(y * 2).unwrap(),
+                u8::try_from((x + y) % 256).unwrap(),
+                255,
+            ])
+        })
+        .save(&input)?;
+        let encode = |png_compression, png_quantize| {
+            process_image(
+                &input,
+                &ProcessingSettings {
+                    png_compression,
+                    png_quantize,
+                    ..ProcessingSettings::default()
+                },
+            )
+        };
+
+        let fastest = encode(1, false)?;
+        let best = encode(9, false)?;
+        assert!(best.data.len() < fastest.data.len());
+        assert_eq!(best.estimated_size, best.data.len() as u64);
+        assert_eq!(
+            image::load_from_memory(&best.data)?.to_rgba8().as_raw(),
+            image::open(&input)?.to_rgba8().as_raw()
+        );
+
+        // Noise barely deflates as truecolor but packs into one palette index per pixel
+        let noisy = td.path().join("noise.png");
+        let mut seed = 0x2545_f491_u32;
+        RgbaImage::from_fn(128, 96, |_, _| {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            Rgba(seed.to_le_bytes())
+        })
+        .save(&noisy)?;
+        let encode_noisy = |png_quantize| {
+            process_image(
+                &noisy,
+                &ProcessingSettings {
+                    png_compression: 9,
+                    png_quantize,
+                    ..ProcessingSettings::default()
+                },
+            )
+        };
+        let truecolor = encode_noisy(false)?;
+        let quantized = encode_noisy(true)?;
+        assert!(quantized.data.len() < truecolor.data.len());
+        let decoded = image::load_from_memory(&quantized.data)?;
+        assert_eq!((decoded.width(), decoded.height()), (128, 96));
+        Ok(())
+    }
+
+    #[test]
+    fn quantizing_few_colors_keeps_them_exact() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let input = td.path().join("logo.png");
+        let logo = RgbaImage::from_fn(32, 32, |x, y| match (x / 8 + y / 8) % 3 {
+            0 => Rgba([255, 0, 0, 255]),
+            1 => Rgba([0, 0, 255, 128]),
+            _ => Rgba([0, 0, 0, 0]),
+        });
+        logo.save(&input)?;
+
+        let quantized = process_image(
+            &input,
+            &ProcessingSettings {
+                png_quantize: true,
+                ..ProcessingSettings::default()
+            },
+        )?;
+        let decoded = image::load_from_memory(&quantized.data)?.to_rgba8();
+        assert_eq!(decoded.as_raw(), logo.as_raw());
+        Ok(())
+    }
+
+    #[test]
+    fn heif_outputs_are_named_as_jpeg() {
+        let root = Path::new("/photos");
+        let output = get_output_path(
+            &root.join("trip/IMG_0001.HEIC"),
+            root,
+            "IMG_0001.HEIC",
+            None,
+        )
+        .unwrap();
+        assert_eq!(output, Path::new("/photos-output/trip/IMG_0001.jpg"));
+        assert_eq!(
+            detect_format_from_path(Path::new("IMG_0001.heif")),
+            ImageFormat::Jpeg
+        );
+    }
+
+    #[test]
+    fn image_file_check_uses_given_extensions() {
+        let custom = vec!["heic".to_string(), "png".to_string()];
+        assert!(is_image_file(Path::new("a/IMG_1.HEIC"), &custom));
+        assert!(is_image_file(Path::new("b.png"), &custom));
+        assert!(!is_image_file(Path::new("c.gif"), &custom));
+        assert!(!is_image_file(Path::new("no_extension"), &custom));
+    }
+
+    #[test]
+    fn reads_exif_from_tiff() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let path = td.path().join("scan.tif");
+        let field = exif::Field {
+            tag: exif::Tag::DateTimeOriginal,
+            ifd_num: exif::In::PRIMARY,
+            value: exif::Value::Ascii(vec![b"2024:01:02 03:04:05".to_vec()]),
+        };
+        let mut writer = exif::experimental::Writer::new();
+        writer.push_field(&field);
+        let mut buf = Cursor::new(Vec::new());
+        writer
+            .write(&mut buf, true)
+            .map_err(|e| eyre!("write tiff: {}", e))?;
+        std::fs::write(&path, buf.into_inner())?;
+
+        let exif = read_exif(&path)?;
+        let field = exif
+            .get_field(exif::Tag::DateTimeOriginal, exif::In::PRIMARY)
+            .ok_or_else(|| eyre!("DateTimeOriginal missing"))?;
+        assert_eq!(field.display_value().to_string(), "2024-01-02 03:04:05");
+        Ok(())
+    }
+
+    #[test]
+    fn write_description_roundtrips() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let input = td.path().join("a.png");
+        RgbaImage::from_pixel(4, 4, Rgba([10, 20, 30, 255])).save(&input)?;
+        assert_eq!(read_exif_fields(&input).description, None);
+
+        let copy = td.path().join("a-described.png");
+        let fields = ExifFields {
+            description: Some("Blue widget $4.99".to_string()),
+            ..ExifFields::default()
+        };
+        write_exif_fields_to_file(&input, &copy, &fields)?;
+        assert_eq!(read_exif_fields(&copy), fields);
+        assert_eq!(read_exif_fields(&input).description, None);
+        Ok(())
+    }
+
+    /// A JPEG at `path` whose EXIF already has camera, date, GPS and orientation tags and the
+    /// description "Old caption"
+    fn save_jpeg_with_camera_exif(path: &Path) -> eyre::Result<()> {
+        let fields = [
+            (Tag::Make, In::PRIMARY, ascii_value("ExampleCam")),
+            (Tag::Orientation, In::PRIMARY, exif::Value::Short(vec![6])),
+            (
+                Tag::ImageDescription,
+                In::PRIMARY,
+                ascii_value("Old caption"),
+            ),
+            (
+                Tag::DateTimeOriginal,
+                In::PRIMARY,
+                ascii_value("2024:01:02 03:04:05"),
+            ),
+            (Tag::GPSLatitudeRef, In::PRIMARY, ascii_value("N")),
+        ]
+        .map(|(tag, ifd_num, value)| exif::Field {
+            tag,
+            ifd_num,
+            value,
+        });
+        let mut writer = exif::experimental::Writer::new();
+        for field in &fields {
+            writer.push_field(field);
+        }
+        let mut raw = Cursor::new(Vec::new());
+        writer
+            .write(&mut raw, false)
+            .map_err(|e| eyre!("write exif: {}", e))?;
+
+        let mut jpeg = Vec::new();
+        image::RgbImage::from_pixel(4, 4, image::Rgb([10, 20, 30]))
+            .write_to(&mut Cursor::new(&mut jpeg), ImageFormat::Jpeg)?;
+        std::fs::write(path, embed_exif(&jpeg, ImageFormat::Jpeg, raw.get_ref())?)?;
+        Ok(())
+    }
+
+    #[test]
+    fn tiff_raster_tags_are_not_copied_to_the_output() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let input = td.path().join("scan.tif");
+        let fields = [
+            (Tag::ImageWidth, exif::Value::Long(vec![4])),
+            (Tag::ImageLength, exif::Value::Long(vec![4])),
+            (Tag::BitsPerSample, exif::Value::Short(vec![8, 8, 8])),
+            (Tag::Compression, exif::Value::Short(vec![1])),
+            (Tag::PhotometricInterpretation, exif::Value::Short(vec![2])),
+            (Tag::SamplesPerPixel, exif::Value::Short(vec![3])),
+            (Tag::RowsPerStrip, exif::Value::Long(vec![4])),
+            (Tag::Make, ascii_value("ExampleScanner")),
+        ]
+        .map(|(tag, value)| exif::Field {
+            tag,
+            ifd_num: In::PRIMARY,
+            value,
+        });
+        let pixels = [200u8; 4 * 4 * 3];
+        let strips: [&[u8]; 1] = [&pixels];
+        let mut writer = exif::experimental::Writer::new();
+        for field in &fields {
+            writer.push_field(field);
+        }
+        writer.set_strips(&strips, In::PRIMARY);
+        let mut tiff = Cursor::new(Vec::new());
+        writer
+            .write(&mut tiff, true)
+            .map_err(|e| eyre!("write tiff: {}", e))?;
+        std::fs::write(&input, tiff.into_inner())?;
+
+        let settings = ProcessingSettings {
+            crop_to_content: false,
+            output_format: Some(ImageFormat::Png),
+            description: Some("Scanned widget".to_string()),
+            ..ProcessingSettings::default()
+        };
+        let output = td.path().join("scan.png");
+        std::fs::write(&output, process_image(&input, &settings)?.data)?;
+
+        let exif = read_exif(&output)?;
+        assert_eq!(
+            ascii_field(&exif, Tag::ImageDescription).as_deref(),
+            Some("Scanned widget")
+        );
+        assert_eq!(
+            ascii_field(&exif, Tag::Make).as_deref(),
+            Some("ExampleScanner")
+        );
+        for tag in RASTER_TAGS {
+            assert!(
+                exif.fields().all(|f| f.tag != tag),
+                "{tag} should not be copied from the TIFF"
+            );
+        }
+        Ok(())
+    }
+
+    fn ascii_value(text: &str) -> exif::Value {
+        exif::Value::Ascii(vec![text.as_bytes().to_vec()])
+    }
+
+    #[test]
+    fn writing_a_description_in_place_keeps_the_other_tags() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let path = td.path().join("photo.jpg");
+        save_jpeg_with_camera_exif(&path)?;
+
+        let fields = ExifFields {
+            description: Some("New caption".to_string()),
+            ..ExifFields::default()
+        };
+        write_exif_fields_to_file(&path, &path, &fields)?;
+
+        let exif = read_exif(&path)?;
+        assert_eq!(
+            ascii_field(&exif, Tag::ImageDescription).as_deref(),
+            Some("New caption")
+        );
+        assert_eq!(ascii_field(&exif, Tag::Make).as_deref(), Some("ExampleCam"));
+        assert_eq!(
+            exif.get_field(Tag::Orientation, In::PRIMARY)
+                .and_then(|f| f.value.get_uint(0)),
+            Some(6)
+        );
+        assert!(exif.get_field(Tag::DateTimeOriginal, In::PRIMARY).is_some());
+        assert!(exif.get_field(Tag::GPSLatitudeRef, In::PRIMARY).is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn writing_artist_copyright_and_keywords_merges_with_existing_tags() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let path = td.path().join("photo.jpg");
+        save_jpeg_with_camera_exif(&path)?;
+
+        let tagged = ExifFields {
+            description: None,
+            artist: Some("Jo Photographer".to_string()),
+            copyright: Some("(c) 2026 Example Co.".to_string()),
+            keywords: Some("widget; blue".to_string()),
+        };
+        write_exif_fields_to_file(&path, &path, &tagged)?;
+        assert_eq!(
+            read_exif_fields(&path),
+            ExifFields {
+                description: Some("Old caption".to_string()),
+                ..tagged.clone()
+            }
+        );
+
+        // An empty value removes the tag, `None` leaves it alone
+        let cleared = ExifFields {
+            artist: Some(String::new()),
+            ..ExifFields::default()
+        };
+        write_exif_fields_to_file(&path, &path, &cleared)?;
+        let fields = read_exif_fields(&path);
+        assert_eq!(fields.artist, None);
+        assert_eq!(fields.copyright, tagged.copyright);
+        assert_eq!(fields.keywords, tagged.keywords);
+
+        let exif = read_exif(&path)?;
+        assert_eq!(ascii_field(&exif, Tag::Make).as_deref(), Some("ExampleCam"));
+        assert!(exif.get_field(Tag::Orientation, In::PRIMARY).is_some());
+        Ok(())
+    }
+
+    fn roundtrip_fields(fields: &ExifFields) -> eyre::Result<ExifFields> {
+        let td = tempdir()?;
+        let input = td.path().join("a.jpg");
+        image::RgbImage::from_pixel(4, 4, image::Rgb([10, 20, 30])).save(&input)?;
+        let output = td.path().join("b.jpg");
+        write_exif_fields_to_file(&input, &output, fields)?;
+        Ok(read_exif_fields(&output))
+    }
+
+    #[test]
+    fn artist_reads_back() -> eyre::Result<()> {
+        let fields = ExifFields {
+            artist: Some("Jo Photographer".to_string()),
+            ..ExifFields::default()
+        };
+        assert_eq!(roundtrip_fields(&fields)?, fields);
+        Ok(())
+    }
+
+    #[test]
+    fn copyright_reads_back() -> eyre::Result<()> {
+        let fields = ExifFields {
+            copyright: Some("(c) 2026 Example Co.".to_string()),
+            ..ExifFields::default()
+        };
+        assert_eq!(roundtrip_fields(&fields)?, fields);
+        Ok(())
+    }
+
+    #[test]
+    fn keywords_read_back() -> eyre::Result<()> {
+        let fields = ExifFields {
+            keywords: Some("widget; blue; café".to_string()),
+            ..ExifFields::default()
+        };
+        assert_eq!(roundtrip_fields(&fields)?, fields);
+        Ok(())
+    }
+
+    #[test]
+    fn all_fields_read_back_together() -> eyre::Result<()> {
+        // Mix of inline (<= 4 bytes) and offset values to exercise offset computation
+        let fields = ExifFields {
+            description: Some("A longer product description".to_string()),
+            artist: Some("Jo".to_string()),
+            copyright: Some("Example Co.".to_string()),
+            keywords: Some("a".to_string()),
+        };
+        assert_eq!(roundtrip_fields(&fields)?, fields);
+        Ok(())
+    }
+
+    #[test]
+    fn description_roundtrips_at_inline_and_offset_lengths() -> eyre::Result<()> {
+        // 1 and 3 chars fit inline with their terminator, 4 just overflows, 50 is an odd-length
+        // offset value followed by another entry
+        for len in [1, 3, 4, 50] {
+            let description = "d".repeat(len);
+            let raw = create_exif_with_fields(&ExifFields {
+                description: Some(description.clone()),
+                artist: Some("Jo Photographer".to_string()),
+                ..ExifFields::default()
+            });
+            let exif = exif::Reader::new()
+                .read_raw(raw)
+                .map_err(|e| eyre!("len {}: {}", len, e))?;
+            let field = exif
+                .get_field(exif::Tag::ImageDescription, exif::In::PRIMARY)
+                .ok_or_else(|| eyre!("len {}: ImageDescription missing", len))?;
+            let exif::Value::Ascii(parts) = &field.value else {
+                return Err(eyre!("len {}: ImageDescription is not ASCII", len));
+            };
+            assert_eq!(parts, &vec![description.into_bytes()], "len {len}");
+            assert_eq!(
+                ascii_field(&exif, exif::Tag::Artist).as_deref(),
+                Some("Jo Photographer"),
+                "len {len}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn output_path_defaults_to_sibling_dir() {
+        let root = Path::new("/photos/shoes");
+        let file = root.join("red").join("a.jpg");
+        assert_eq!(
+            get_output_path(&file, root, "b.jpg", None),
+            Some(PathBuf::from("/photos/shoes-output/red/b.jpg"))
+        );
+    }
+
+    #[test]
+    fn output_path_uses_output_root_when_set() {
+        let out = Path::new("/collected");
+        let root = Path::new("/photos/shoes");
+        let file = root.join("red").join("a.jpg");
+        assert_eq!(
+            get_output_path(&file, root, "b.jpg", Some(out)),
+            Some(PathBuf::from("/collected/shoes/red/b.jpg"))
+        );
+        assert_eq!(
+            get_output_dir(root, Some(out)),
+            PathBuf::from("/collected/shoes")
+        );
+    }
+}
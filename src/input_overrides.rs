@@ -0,0 +1,207 @@
+//! Processing settings that replace the global ones for files under particular input roots,
+//! so e.g. a folder of flatbed scans can be deskewed while phone photos are left alone
+
+use crate::app_home::AppHome;
+use crate::atomic_write::write_atomic;
+use crate::image_processing::ProcessingSettings;
+use facet::Facet;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+const FILE_NAME: &str = "input_overrides.json";
+
+/// Settings for one input root. `None` fields keep the global value.
+#[derive(Clone, Debug, Default, PartialEq, Facet)]
+pub struct SettingsOverride {
+    pub crop_to_content: Option<bool>,
+    pub crop_threshold: Option<u8>,
+    pub min_output_dimension: Option<u32>,
+    pub deskew: Option<bool>,
+    pub brightness: Option<i32>,
+    pub contrast: Option<f32>,
+    pub gamma: Option<f32>,
+    pub sharpen_sigma: Option<f32>,
+    pub jpeg_quality: Option<u8>,
+}
+
+impl SettingsOverride {
+    /// Whether every field keeps the global value
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Replace the fields of `settings` that this override sets
+    pub fn apply_to(&self, settings: &mut ProcessingSettings) {
+        fn set<T: Copy>(target: &mut T, value: Option<T>) {
+            if let Some(value) = value {
+                *target = value;
+            }
+        }
+        set(&mut settings.crop_to_content, self.crop_to_content);
+        set(&mut settings.crop_threshold, self.crop_threshold);
+        set(
+            &mut settings.min_output_dimension,
+            self.min_output_dimension,
+        );
+        set(&mut settings.deskew, self.deskew);
+        set(&mut settings.brightness, self.brightness);
+        set(&mut settings.contrast, self.contrast);
+        set(&mut settings.gamma, self.gamma);
+        set(&mut settings.sharpen_sigma, self.sharpen_sigma);
+        set(&mut settings.jpeg_quality, self.jpeg_quality);
+    }
+}
+
+#[derive(Facet)]
+struct OverrideEntry {
+    input: String,
+    overrides: SettingsOverride,
+}
+
+#[derive(Facet)]
+struct OverridesFile {
+    entries: Vec<OverrideEntry>,
+}
+
+/// Setting overrides keyed by canonical input root
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InputOverrides {
+    by_root: BTreeMap<PathBuf, SettingsOverride>,
+}
+
+impl InputOverrides {
+    /// Input roots are stored canonicalized; fall back to the path as given if that fails
+    fn key(root: &Path) -> PathBuf {
+        dunce::canonicalize(root).unwrap_or_else(|_| root.to_path_buf())
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.by_root.is_empty()
+    }
+
+    /// The override for `root`, if one is stored
+    #[must_use]
+    pub fn get(&self, root: &Path) -> Option<&SettingsOverride> {
+        self.by_root
+            .get(root)
+            .or_else(|| self.by_root.get(&Self::key(root)))
+    }
+
+    /// Store the override for `root`; an empty override removes it
+    pub fn set(&mut self, root: &Path, overrides: SettingsOverride) {
+        let key = Self::key(root);
+        if overrides.is_empty() {
+            self.by_root.remove(&key);
+        } else {
+            self.by_root.insert(key, overrides);
+        }
+    }
+
+    /// `base` with the override for `root` applied
+    #[must_use]
+    pub fn settings_for(&self, root: &Path, base: &ProcessingSettings) -> ProcessingSettings {
+        let mut settings = base.clone();
+        if let Some(overrides) = self.get(root) {
+            overrides.apply_to(&mut settings);
+        }
+        settings
+    }
+}
+
+/// Load the persisted overrides; a missing file means none.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read or parsed.
+pub fn load_input_overrides(home: &AppHome) -> eyre::Result<InputOverrides> {
+    let path = home.file_path(FILE_NAME);
+    if !path.exists() {
+        return Ok(InputOverrides::default());
+    }
+    let text = fs::read_to_string(&path)?;
+    let file: OverridesFile = facet_json::from_str(&text)
+        .map_err(|e| eyre::eyre!("Failed to parse {}: {}", path.display(), e))?;
+    Ok(InputOverrides {
+        by_root: file
+            .entries
+            .into_iter()
+            .map(|entry| (PathBuf::from(entry.input), entry.overrides))
+            .collect(),
+    })
+}
+
+/// Persist `overrides`, removing the file when there are none.
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be written or removed.
+pub fn save_input_overrides(home: &AppHome, overrides: &InputOverrides) -> eyre::Result<()> {
+    let path = home.file_path(FILE_NAME);
+    if overrides.is_empty() {
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        return Ok(());
+    }
+    let file = OverridesFile {
+        entries: overrides
+            .by_root
+            .iter()
+            .map(|(input, overrides)| OverrideEntry {
+                input: input.display().to_string(),
+                overrides: overrides.clone(),
+            })
+            .collect(),
+    };
+    home.ensure_dir()?;
+    write_atomic(&path, facet_json::to_string(&file)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn overrides_roundtrip_and_apply_by_root() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().join("home"));
+        let scans = td.path().join("scans");
+        fs::create_dir_all(&scans)?;
+        assert!(load_input_overrides(&home)?.is_empty());
+
+        let mut overrides = InputOverrides::default();
+        overrides.set(
+            &scans,
+            SettingsOverride {
+                deskew: Some(true),
+                contrast: Some(12.5),
+                ..SettingsOverride::default()
+            },
+        );
+        save_input_overrides(&home, &overrides)?;
+        let loaded = load_input_overrides(&home)?;
+        assert_eq!(loaded, overrides);
+
+        let base = ProcessingSettings {
+            jpeg_quality: 80,
+            ..ProcessingSettings::default()
+        };
+        let settings = loaded.settings_for(&scans, &base);
+        assert!(settings.deskew);
+        assert!((settings.contrast - 12.5).abs() < f32::EPSILON);
+        assert_eq!(settings.jpeg_quality, 80);
+        assert!(!loaded.settings_for(td.path(), &base).deskew);
+
+        // Clearing the last override removes the file
+        overrides.set(&scans, SettingsOverride::default());
+        save_input_overrides(&home, &overrides)?;
+        assert!(!home.file_path(FILE_NAME).exists());
+        Ok(())
+    }
+}
@@ -1,9 +1,16 @@
 use crate::app_home::AppHome;
+use crate::atomic_write::write_atomic;
+use crate::image_extensions::load_image_extensions;
+use crate::image_processing::OUTPUT_DIR_SUFFIX;
+use crate::image_processing::is_image_file;
+use crate::output_root::load_output_root;
+use facet::Facet;
 use glob::glob;
-use std::collections::BTreeSet;
+use std::collections::HashSet;
 use std::fs;
-use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
+use tracing::debug;
 use tracing::warn;
 
 /// Returns the path to the `inputs.txt` file in the given `AppHome`
@@ -11,7 +18,10 @@ fn inputs_file_path(home: &AppHome) -> PathBuf {
     home.file_path("inputs.txt")
 }
 
-/// Load persisted inputs (one per line). Returns canonicalized `PathBufs` as stored.
+/// Load persisted inputs (one per line) in their stored order. Returns canonicalized `PathBufs`
+/// as stored; each line is normalized (trailing separators and repeated separators dropped) and
+/// repeated paths are collapsed to their first occurrence. A hand-edited file that needed any of
+/// this is rewritten in the normalized form.
 ///
 /// # Errors
 ///
@@ -28,63 +38,125 @@ pub fn load_inputs(home: &AppHome) -> eyre::Result<Vec<PathBuf>> {
         if trimmed.is_empty() {
             continue;
         }
-        v.push(PathBuf::from(trimmed));
+        v.push(normalize_input_line(trimmed));
+    }
+    let v = dedup_keep_first(v);
+    let text = inputs_text(&v);
+    if !v.is_empty() && text != s {
+        debug!("Normalizing {}", path.display());
+        if let Err(e) = write_atomic(&path, text) {
+            warn!("Failed to rewrite {}: {}", path.display(), e);
+        }
     }
     Ok(v)
 }
 
-/// Persist the provided set of canonical paths to the inputs file (one per line)
-fn save_inputs(home: &AppHome, paths: &BTreeSet<PathBuf>) -> eyre::Result<()> {
+/// Rebuild a stored path from its components, which drops trailing and repeated separators
+/// and uses the platform separator throughout
+fn normalize_input_line(line: &str) -> PathBuf {
+    Path::new(line).components().collect()
+}
+
+/// Remove repeated paths, keeping the first occurrence of each and the order of the rest
+fn dedup_keep_first(paths: impl IntoIterator<Item = PathBuf>) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    paths
+        .into_iter()
+        .filter(|p| seen.insert(p.clone()))
+        .collect()
+}
+
+/// The inputs file contents for `paths`: one per line, in order and without duplicates
+fn inputs_text(paths: &[PathBuf]) -> String {
+    let mut text = String::new();
+    for p in dedup_keep_first(paths.iter().cloned()) {
+        text.push_str(&p.display().to_string());
+        text.push('\n');
+    }
+    text
+}
+
+/// Persist the provided canonical paths to the inputs file (one per line), in order and
+/// without duplicates
+fn save_inputs(home: &AppHome, paths: &[PathBuf]) -> eyre::Result<()> {
     let path = inputs_file_path(home);
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
     }
-    let mut f = fs::OpenOptions::new()
-        .create(true)
-        .write(true)
-        .truncate(true)
-        .open(&path)?;
-    for p in paths {
-        writeln!(f, "{}", p.display())?;
-    }
-    Ok(())
+    write_atomic(&path, inputs_text(paths))
 }
 
-/// Add paths resolved from a glob pattern. Each matched path is canonicalized before being stored.
-/// Returns the list of newly added canonical paths.
+/// Persist a new order for the inputs. Paths in `ordered` that are not persisted inputs are
+/// ignored, and persisted inputs missing from `ordered` keep their place after the rest.
 ///
 /// # Errors
 ///
-/// Returns an error if globbing, canonicalizing paths, or loading inputs fails.
-pub fn add_from_glob(home: &AppHome, pattern: &str) -> eyre::Result<Vec<PathBuf>> {
-    let mut new = BTreeSet::new();
-
-    for entry in glob(pattern)? {
-        let p = entry.map_err(|e| eyre::eyre!("Glob pattern error: {}", e))?;
-        // canonicalize the matched path (fail if it cannot be canonicalized)
-        let cp = dunce::canonicalize(&p)?;
-        new.insert(cp);
-    }
+/// Returns an error if loading or saving inputs fails.
+pub fn set_input_order(home: &AppHome, ordered: &[PathBuf]) -> eyre::Result<()> {
+    let current = load_inputs(home)?;
+    let known: HashSet<&PathBuf> = current.iter().collect();
+    let reordered: Vec<PathBuf> = ordered
+        .iter()
+        .filter(|p| known.contains(p))
+        .chain(current.iter())
+        .cloned()
+        .collect();
+    save_inputs(home, &reordered)
+}
 
-    if new.is_empty() {
-        // No matches is not an error; return empty vector
-        return Ok(Vec::new());
-    }
+/// Outcome of adding inputs: the newly persisted paths and the paths that were skipped
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct AddedInputs {
+    /// Newly added canonical paths
+    pub added: Vec<PathBuf>,
+    /// Paths that could not be added, with the reason
+    pub skipped: Vec<(PathBuf, String)>,
+}
 
-    let mut current = load_inputs(home)?.into_iter().collect::<BTreeSet<_>>();
-    let added: Vec<PathBuf> = new.difference(&current).cloned().collect();
+/// Add paths resolved from a glob pattern. Each matched path is canonicalized before being stored;
+/// matches that cannot be read or canonicalized are skipped with a warning.
+///
+/// # Errors
+///
+/// Returns an error if the pattern is invalid or loading/saving inputs fails.
+pub fn add_from_glob(home: &AppHome, pattern: &str) -> eyre::Result<AddedInputs> {
+    let mut matched = Vec::new();
+    let mut skipped = Vec::new();
 
-    if added.is_empty() {
-        // Nothing to change
-        return Ok(Vec::new());
+    for entry in glob(pattern)? {
+        match entry {
+            Ok(p) => matched.push(p),
+            Err(e) => {
+                warn!("Skipping {}: {}", e.path().display(), e.error());
+                skipped.push((e.path().to_path_buf(), e.error().to_string()));
+            }
+        }
     }
 
-    for p in &added {
-        current.insert(p.clone());
-    }
+    let mut result = add_paths(home, &matched)?;
+    skipped.append(&mut result.skipped);
+    result.skipped = skipped;
+    Ok(result)
+}
 
-    save_inputs(home, &current)?;
-    Ok(added)
+/// Add the paths listed in a text file, one per line. Blank lines and lines starting with `#`
+/// are ignored, and relative paths are resolved against the list file's directory. Listed
+/// paths go through [`add_paths`], so missing ones are skipped rather than failing the import.
+///
+/// # Errors
+///
+/// Returns an error if the list file cannot be read or loading/saving inputs fails.
+pub fn add_from_list_file(home: &AppHome, list_file: &Path) -> eyre::Result<AddedInputs> {
+    let text = fs::read_to_string(list_file)
+        .map_err(|e| eyre::eyre!("Failed to read {}: {}", list_file.display(), e))?;
+    let base = list_file.parent().unwrap_or(Path::new(""));
+    let paths: Vec<PathBuf> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| base.join(line))
+        .collect();
+    add_paths(home, &paths)
 }
 
 /// Remove paths that match the glob pattern. Returns removed canonical paths.
@@ -93,7 +165,7 @@ pub fn add_from_glob(home: &AppHome, pattern: &str) -> eyre::Result<Vec<PathBuf>
 ///
 /// Returns an error if globbing or loading inputs fails.
 pub fn remove_from_glob(home: &AppHome, pattern: &str) -> eyre::Result<Vec<PathBuf>> {
-    let mut to_remove = BTreeSet::new();
+    let mut to_remove = HashSet::new();
 
     for entry in glob(pattern)? {
         let p = entry.map_err(|e| eyre::eyre!("Glob pattern error: {}", e))?;
@@ -111,52 +183,82 @@ pub fn remove_from_glob(home: &AppHome, pattern: &str) -> eyre::Result<Vec<PathB
         return Ok(Vec::new());
     }
 
-    let mut current = load_inputs(home)?.into_iter().collect::<BTreeSet<_>>();
-    let removed: Vec<PathBuf> = current.intersection(&to_remove).cloned().collect();
+    let (removed, kept): (Vec<PathBuf>, Vec<PathBuf>) = load_inputs(home)?
+        .into_iter()
+        .partition(|p| to_remove.contains(p));
 
     if removed.is_empty() {
         return Ok(Vec::new());
     }
 
-    for p in &removed {
-        current.remove(p);
-    }
-
-    save_inputs(home, &current)?;
+    save_inputs(home, &kept)?;
     Ok(removed)
 }
 
-/// Add a list of paths to the persisted inputs. Paths are canonicalized before storing.
+/// Add a list of paths to the persisted inputs. Paths are canonicalized before storing;
+/// paths that cannot be canonicalized (missing, dangling symlink, no permission) are skipped
+/// with a warning while the rest are still added.
 ///
 /// # Errors
 ///
-/// Returns an error if canonicalizing paths, loading inputs, or saving inputs fails.
-pub fn add_paths(home: &AppHome, paths: &[PathBuf]) -> eyre::Result<Vec<PathBuf>> {
-    let mut new = BTreeSet::new();
+/// Returns an error if loading or saving inputs fails.
+pub fn add_paths(home: &AppHome, paths: &[PathBuf]) -> eyre::Result<AddedInputs> {
+    let mut new = Vec::new();
+    let mut skipped = Vec::new();
 
     for p in paths {
-        // canonicalize the matched path (fail if it cannot be canonicalized)
-        let cp = dunce::canonicalize(p)?;
-        new.insert(cp);
+        match dunce::canonicalize(p) {
+            Ok(cp) => {
+                new.push(cp);
+            }
+            Err(e) => {
+                warn!("Skipping {}: {}", p.display(), e);
+                skipped.push((p.clone(), e.to_string()));
+            }
+        }
     }
 
     if new.is_empty() {
-        return Ok(Vec::new());
+        return Ok(AddedInputs {
+            added: Vec::new(),
+            skipped,
+        });
     }
 
-    let mut current = load_inputs(home)?.into_iter().collect::<BTreeSet<_>>();
-    let added: Vec<PathBuf> = new.difference(&current).cloned().collect();
+    // New paths are appended after the existing inputs, in the order given
+    let mut current = load_inputs(home)?;
+    let added: Vec<PathBuf> = dedup_keep_first(new)
+        .into_iter()
+        .filter(|p| !current.contains(p))
+        .collect();
 
-    if added.is_empty() {
-        return Ok(Vec::new());
+    if !added.is_empty() {
+        current.extend(added.iter().cloned());
+        save_inputs(home, &current)?;
     }
 
-    for p in &added {
-        current.insert(p.clone());
-    }
+    Ok(AddedInputs { added, skipped })
+}
 
-    save_inputs(home, &current)?;
-    Ok(added)
+/// Check that a path can be added as an input: it must exist, canonicalize, and be readable
+/// (directories listable, files openable).
+///
+/// # Errors
+///
+/// Returns a short human-readable reason when the path is rejected.
+pub fn check_accessible(path: &Path) -> Result<(), String> {
+    let describe = |e: std::io::Error| match e.kind() {
+        std::io::ErrorKind::NotFound => "does not exist".to_owned(),
+        std::io::ErrorKind::PermissionDenied => "permission denied".to_owned(),
+        _ => e.to_string(),
+    };
+    let canonical = dunce::canonicalize(path).map_err(describe)?;
+    if canonical.is_dir() {
+        fs::read_dir(&canonical).map_err(describe)?;
+    } else {
+        fs::File::open(&canonical).map_err(describe)?;
+    }
+    Ok(())
 }
 
 /// Remove all persisted inputs (clear the inputs list)
@@ -183,13 +285,19 @@ pub fn clear_all(home: &AppHome) -> eyre::Result<()> {
 }
 
 /// Remove a single path from the persisted inputs. Returns true if the path was present and removed.
+/// The target and the stored entries are compared canonicalized, so a differently spelled path
+/// to the same location (case, short names, `..`) still removes the stored entry.
 ///
 /// # Errors
 ///
 /// Returns an error if loading or saving inputs fails.
 pub fn remove_path(home: &AppHome, path_to_remove: &PathBuf) -> eyre::Result<bool> {
-    let mut current = load_inputs(home)?.into_iter().collect::<BTreeSet<_>>();
-    let was_present = current.remove(path_to_remove);
+    let canonical = |p: &PathBuf| dunce::canonicalize(p).unwrap_or_else(|_| p.clone());
+    let target = canonical(path_to_remove);
+    let mut current = load_inputs(home)?;
+    let before = current.len();
+    current.retain(|p| p != path_to_remove && canonical(p) != target);
+    let was_present = current.len() != before;
     if was_present {
         save_inputs(home, &current)?;
     }
@@ -203,18 +311,136 @@ pub fn remove_path(home: &AppHome, path_to_remove: &PathBuf) -> eyre::Result<boo
 ///
 /// Returns an error if loading inputs or reading directories fails.
 pub fn list_files(home: &AppHome) -> eyre::Result<Vec<PathBuf>> {
+    list_files_in(&load_inputs(home)?, load_output_root(home)?.as_deref())
+}
+
+/// Return all files contained in `roots`, the same way [`list_files`] does for the persisted
+/// inputs, skipping `-output` directories and `output_root`
+///
+/// # Errors
+///
+/// Returns an error if reading directories fails.
+pub fn list_files_in(roots: &[PathBuf], output_root: Option<&Path>) -> eyre::Result<Vec<PathBuf>> {
     let mut files = Vec::new();
-    for p in load_inputs(home)? {
+    for p in roots {
         if p.is_file() {
-            files.push(p);
+            files.push(p.clone());
         } else if p.is_dir() {
-            add_files_from_dir(&p, &mut files)?;
+            add_files_from_dir(p, output_root, &mut files)?;
         }
     }
     Ok(files)
 }
 
-fn add_files_from_dir(dir: &PathBuf, out: &mut Vec<PathBuf>) -> eyre::Result<()> {
+/// Count the files with one of `extensions` under `path` (the path itself if it is a file),
+/// skipping output directories the same way [`list_files_in`] does
+///
+/// # Errors
+///
+/// Returns an error if a directory cannot be read.
+pub fn count_images_under(
+    path: &Path,
+    output_root: Option<&Path>,
+    extensions: &[String],
+) -> eyre::Result<usize> {
+    Ok(images_under(path, output_root, extensions)?.len())
+}
+
+/// The files with one of `extensions` under `path` (the path itself if it is one)
+fn images_under(
+    path: &Path,
+    output_root: Option<&Path>,
+    extensions: &[String],
+) -> eyre::Result<Vec<PathBuf>> {
+    if path.is_file() {
+        return Ok(if is_image_file(path, extensions) {
+            vec![path.to_path_buf()]
+        } else {
+            Vec::new()
+        });
+    }
+    let mut files = Vec::new();
+    add_files_from_dir(&path.to_path_buf(), output_root, &mut files)?;
+    files.retain(|f| is_image_file(f, extensions));
+    Ok(files)
+}
+
+/// A persisted input with what it contributes to the working set
+#[derive(Clone, Debug, PartialEq, Eq, Facet)]
+pub struct InputSummary {
+    pub path: String,
+    /// `file`, `dir`, or `missing` when the path no longer exists
+    pub kind: String,
+    /// Recognized images under the input
+    pub image_count: usize,
+    /// Combined size of those images
+    pub total_bytes: u64,
+}
+
+impl InputSummary {
+    /// Inspect `path` on disk, counting files with one of `extensions` and skipping output
+    /// directories and `output_root`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a directory cannot be read.
+    pub fn gather(
+        path: &Path,
+        output_root: Option<&Path>,
+        extensions: &[String],
+    ) -> eyre::Result<Self> {
+        let kind = if path.is_file() {
+            "file"
+        } else if path.is_dir() {
+            "dir"
+        } else {
+            "missing"
+        };
+        let images = if kind == "missing" {
+            Vec::new()
+        } else {
+            images_under(path, output_root, extensions)?
+        };
+        Ok(Self {
+            path: path.display().to_string(),
+            kind: kind.to_owned(),
+            image_count: images.len(),
+            total_bytes: images
+                .iter()
+                .filter_map(|f| fs::metadata(f).ok())
+                .map(|m| m.len())
+                .sum(),
+        })
+    }
+}
+
+/// Summarize every persisted input, in their stored order
+///
+/// # Errors
+///
+/// Returns an error if loading inputs or reading a directory fails.
+pub fn summarize_inputs(home: &AppHome) -> eyre::Result<Vec<InputSummary>> {
+    let output_root = load_output_root(home)?;
+    let extensions = load_image_extensions(home)?;
+    load_inputs(home)?
+        .iter()
+        .map(|p| InputSummary::gather(p, output_root.as_deref(), &extensions))
+        .collect()
+}
+
+/// Whether a directory holds processed outputs (an `-output` sibling or `output_root`) and must
+/// not be read back as input, which would reprocess outputs on every run
+fn is_output_dir(dir: &Path, output_root: Option<&Path>) -> bool {
+    dir.file_name()
+        .is_some_and(|name| name.to_string_lossy().ends_with(OUTPUT_DIR_SUFFIX))
+        || output_root == Some(dir)
+}
+
+fn add_files_from_dir(
+    dir: &PathBuf,
+    output_root: Option<&Path>,
+    out: &mut Vec<PathBuf>,
+) -> eyre::Result<()> {
     for entry in fs::read_dir(dir)? {
         match entry {
             Ok(ent) => {
@@ -222,7 +448,11 @@ fn add_files_from_dir(dir: &PathBuf, out: &mut Vec<PathBuf>) -> eyre::Result<()>
                 if p.is_file() {
                     out.push(p);
                 } else if p.is_dir() {
-                    add_files_from_dir(&p, out)?;
+                    if is_output_dir(&p, output_root) {
+                        debug!("Skipping output directory {}", p.display());
+                        continue;
+                    }
+                    add_files_from_dir(&p, output_root, out)?;
                 }
             }
             Err(e) => {
@@ -233,9 +463,36 @@ fn add_files_from_dir(dir: &PathBuf, out: &mut Vec<PathBuf>) -> eyre::Result<()>
     Ok(())
 }
 
+/// Glob matched against file names only (e.g. `*-front.*`), ignoring case
+#[derive(Debug, Clone, PartialEq)]
+pub struct NameFilter(glob::Pattern);
+
+impl NameFilter {
+    /// Compile a file name glob
+    /// # Errors
+    /// Returns an error if the pattern is not a valid glob.
+    pub fn parse(pattern: &str) -> eyre::Result<Self> {
+        Ok(Self(glob::Pattern::new(pattern.trim()).map_err(|e| {
+            eyre::eyre!("Invalid filter pattern {pattern:?}: {e}")
+        })?))
+    }
+
+    /// Whether the file name of `path` matches the glob
+    #[must_use]
+    pub fn matches(&self, path: &Path) -> bool {
+        let options = glob::MatchOptions {
+            case_sensitive: false,
+            ..glob::MatchOptions::new()
+        };
+        path.file_name()
+            .is_some_and(|name| self.0.matches_with(&name.to_string_lossy(), options))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::image_extensions::default_image_extensions;
     use std::fs::File;
     use tempfile::tempdir;
 
@@ -251,7 +508,7 @@ mod tests {
         File::create(&file2)?;
 
         // add both
-        let added = add_from_glob(&home, &format!("{}/*.txt", td.path().display()))?;
+        let added = add_from_glob(&home, &format!("{}/*.txt", td.path().display()))?.added;
         assert_eq!(added.len(), 2);
 
         // list
@@ -278,7 +535,7 @@ mod tests {
         File::create(&file1)?;
 
         // add single path via add_paths
-        let added = add_paths(&home, &[file1.clone()])?;
+        let added = add_paths(&home, &[file1.clone()])?.added;
         assert_eq!(added.len(), 1);
 
         let listed = load_inputs(&home)?;
@@ -292,6 +549,21 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn remove_path_matches_non_canonical_spelling() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().join("home"));
+        let dir = td.path().join("photos");
+        fs::create_dir_all(dir.join("sub"))?;
+        add_paths(&home, &[dir.clone()])?;
+
+        let other_spelling = dir.join("sub").join("..");
+        assert!(remove_path(&home, &other_spelling)?);
+        assert!(load_inputs(&home)?.is_empty());
+        assert!(!remove_path(&home, &dir)?);
+        Ok(())
+    }
+
     #[test]
     fn list_files_recurses() -> eyre::Result<()> {
         let td = tempdir()?;
@@ -316,4 +588,224 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn add_from_list_file_skips_comments_and_blanks() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().join("home"));
+        let photos = td.path().join("photos");
+        fs::create_dir_all(&photos)?;
+        let single = td.path().join("single.png");
+        File::create(&single)?;
+
+        let list = td.path().join("list.txt");
+        fs::write(
+            &list,
+            format!(
+                "# scanned batches\n\n{}\n  single.png  \n# missing.png\nmissing.png\n",
+                photos.display()
+            ),
+        )?;
+
+        let result = add_from_list_file(&home, &list)?;
+        assert_eq!(result.added.len(), 2);
+        assert_eq!(result.skipped.len(), 1);
+        assert_eq!(result.skipped[0].0, td.path().join("missing.png"));
+        assert_eq!(
+            load_inputs(&home)?,
+            vec![dunce::canonicalize(&photos)?, dunce::canonicalize(&single)?]
+        );
+
+        assert!(add_from_list_file(&home, &td.path().join("nope.txt")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn add_paths_skips_missing() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+
+        let good = td.path().join("good.txt");
+        File::create(&good)?;
+        let missing = td.path().join("missing.txt");
+
+        let result = add_paths(&home, &[good, missing.clone()])?;
+        assert_eq!(result.added.len(), 1);
+        assert_eq!(result.skipped.len(), 1);
+        assert_eq!(result.skipped[0].0, missing);
+        assert_eq!(load_inputs(&home)?.len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn check_accessible_explains_rejections() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let file = td.path().join("a.png");
+        File::create(&file)?;
+
+        assert_eq!(check_accessible(td.path()), Ok(()));
+        assert_eq!(check_accessible(&file), Ok(()));
+        assert_eq!(
+            check_accessible(&td.path().join("missing")),
+            Err("does not exist".to_owned())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn count_images_under_ignores_other_files_and_outputs() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let dir = td.path().join("photos");
+        fs::create_dir_all(dir.join("sub"))?;
+        fs::create_dir_all(dir.join("photos-output"))?;
+        File::create(dir.join("a.png"))?;
+        File::create(dir.join("sub").join("b.JPG"))?;
+        File::create(dir.join("notes.txt"))?;
+        File::create(dir.join("photos-output").join("a.png"))?;
+
+        let extensions = default_image_extensions();
+        assert_eq!(count_images_under(&dir, None, &extensions)?, 2);
+        assert_eq!(
+            count_images_under(&dir.join("a.png"), None, &extensions)?,
+            1
+        );
+        assert_eq!(
+            count_images_under(&dir.join("notes.txt"), None, &extensions)?,
+            0
+        );
+        // An output root inside the input is skipped too, but only when it is the one passed in
+        assert_eq!(
+            count_images_under(&dir, Some(&dir.join("sub")), &extensions)?,
+            1
+        );
+        assert_eq!(count_images_under(&dir, None, &["txt".to_string()])?, 1);
+
+        let empty = td.path().join("empty");
+        fs::create_dir_all(&empty)?;
+        assert_eq!(count_images_under(&empty, None, &extensions)?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn summarize_inputs_json_shape() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().join("home"));
+        let dir = td.path().join("photos");
+        fs::create_dir_all(dir.join("sub"))?;
+        fs::write(dir.join("a.png"), b"12345")?;
+        fs::write(dir.join("sub").join("b.jpg"), b"123")?;
+        fs::write(dir.join("notes.txt"), b"ignored")?;
+        let single = td.path().join("single.png");
+        fs::write(&single, b"12")?;
+
+        add_paths(&home, &[dir, single])?;
+        let summaries = summarize_inputs(&home)?;
+        assert_eq!(
+            summaries
+                .iter()
+                .map(|s| (s.kind.as_str(), s.image_count, s.total_bytes))
+                .collect::<Vec<_>>(),
+            [("dir", 2, 8), ("file", 1, 2)]
+        );
+
+        let json = facet_json::to_string(&summaries)?;
+        assert!(json.starts_with("[{\"path\":"));
+        assert!(json.contains("\"kind\":\"dir\",\"image_count\":2,\"total_bytes\":8}"));
+        assert_eq!(facet_json::from_str::<Vec<InputSummary>>(&json)?, summaries);
+
+        let missing =
+            InputSummary::gather(&td.path().join("gone"), None, &default_image_extensions())?;
+        assert_eq!((missing.kind.as_str(), missing.image_count), ("missing", 0));
+        Ok(())
+    }
+
+    #[test]
+    fn name_filter_matches_file_names_only() -> eyre::Result<()> {
+        let filter = NameFilter::parse("*-front.*")?;
+        assert!(filter.matches(Path::new("/photos/shoe-front.jpg")));
+        assert!(filter.matches(Path::new("/photos/SHOE-FRONT.PNG")));
+        assert!(!filter.matches(Path::new("/photos/shoe-back.jpg")));
+        // The directory is not part of the match
+        assert!(!filter.matches(Path::new("/x-front.d/shoe.jpg")));
+        assert!(NameFilter::parse("[").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn order_is_preserved_across_save_and_load() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+        let paths: Vec<PathBuf> = ["c", "a", "b"].iter().map(|n| td.path().join(n)).collect();
+        for p in &paths {
+            fs::create_dir_all(p)?;
+        }
+
+        add_paths(&home, &paths)?;
+        let loaded = load_inputs(&home)?;
+        assert_eq!(
+            loaded
+                .iter()
+                .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+                .collect::<Vec<_>>(),
+            ["c", "a", "b"]
+        );
+
+        let reversed: Vec<PathBuf> = loaded.iter().rev().cloned().collect();
+        set_input_order(&home, &reversed)?;
+        assert_eq!(load_inputs(&home)?, reversed);
+        Ok(())
+    }
+
+    #[test]
+    fn duplicates_collapse_to_first_occurrence() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+        let a = PathBuf::from("/inputs/a");
+        let b = PathBuf::from("/inputs/b");
+
+        save_inputs(&home, &[b.clone(), a.clone(), b.clone()])?;
+        assert_eq!(load_inputs(&home)?, vec![b.clone(), a.clone()]);
+
+        // A hand-edited file with repeats loads deduplicated too
+        fs::write(inputs_file_path(&home), "/inputs/a\n/inputs/b\n/inputs/a\n")?;
+        assert_eq!(load_inputs(&home)?, vec![a, b]);
+        Ok(())
+    }
+
+    #[test]
+    fn trailing_separators_collapse_and_file_is_rewritten() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+        fs::write(
+            inputs_file_path(&home),
+            "/inputs/a/\n  /inputs/b\n/inputs/a\n/inputs//b//\n",
+        )?;
+
+        let expected = vec![PathBuf::from("/inputs/a"), PathBuf::from("/inputs/b")];
+        assert_eq!(load_inputs(&home)?, expected);
+        assert_eq!(
+            fs::read_to_string(inputs_file_path(&home))?,
+            inputs_text(&expected)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn list_files_skips_output_dirs() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+
+        let dir = td.path().join("photos");
+        let nested_output = dir.join("X-output");
+        fs::create_dir_all(&nested_output)?;
+        File::create(dir.join("a.png"))?;
+        File::create(nested_output.join("a.png"))?;
+
+        add_paths(&home, &[dir])?;
+        let files = list_files(&home)?;
+        assert_eq!(files.len(), 1);
+        assert!(!files[0].starts_with(&nested_output));
+        Ok(())
+    }
 }
@@ -0,0 +1,69 @@
+//! Counts from the most recent Process All or `cm process` run, kept so `cm stats` can report
+//! on the last batch after it has finished
+
+use crate::app_home::AppHome;
+use crate::atomic_write::write_atomic;
+use facet::Facet;
+use std::fs;
+
+const FILE_NAME: &str = "last_batch.json";
+
+/// Outcome of one batch
+#[derive(Clone, Debug, Default, PartialEq, Eq, Facet)]
+pub struct LastBatch {
+    /// Images written
+    pub processed: usize,
+    /// Images that failed
+    pub errors: usize,
+    /// Total size of the written outputs
+    pub output_bytes: u64,
+}
+
+/// Load the summary of the last batch, or `None` if no batch has finished yet
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read or parsed.
+pub fn load_last_batch(home: &AppHome) -> eyre::Result<Option<LastBatch>> {
+    let path = home.file_path(FILE_NAME);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(&path)?;
+    let batch = facet_json::from_str(&text)
+        .map_err(|e| eyre::eyre!("Failed to parse {}: {}", path.display(), e))?;
+    Ok(Some(batch))
+}
+
+/// Replace the persisted summary with `batch`
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be written.
+pub fn save_last_batch(home: &AppHome, batch: &LastBatch) -> eyre::Result<()> {
+    home.ensure_dir()?;
+    write_atomic(&home.file_path(FILE_NAME), facet_json::to_string(batch)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn last_batch_round_trips() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+        assert_eq!(load_last_batch(&home)?, None);
+
+        let batch = LastBatch {
+            processed: 12,
+            errors: 1,
+            output_bytes: 34_567,
+        };
+        save_last_batch(&home, &batch)?;
+        assert_eq!(load_last_batch(&home)?, Some(batch));
+        Ok(())
+    }
+}
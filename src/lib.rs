@@ -1,39 +1,84 @@
 #![deny(clippy::disallowed_methods)]
 
 pub mod app_home;
+pub mod atomic_write;
+pub mod batch_checkpoint;
 pub mod cache;
 pub mod cli;
+pub mod config_file;
+pub mod conversion_report;
+pub mod duplicates;
+pub mod file_manager;
 pub mod gui;
+pub mod image_extensions;
 pub mod image_processing;
+pub mod input_overrides;
 pub mod inputs;
+pub mod last_batch;
 pub mod max_name_length;
+pub mod name_length_unit;
+pub mod natural_sort;
+pub mod offline;
+pub mod output_root;
+pub mod pipeline;
+pub mod process_timing;
+pub mod product_thumbnails;
+pub mod quiet;
 pub mod rename_rules;
+pub mod scroll_zoom;
+pub mod search_history;
+pub mod search_manifest;
+pub mod search_rate_limit;
 pub mod session_id;
+pub mod site_host;
 pub mod site_id;
+pub mod source_stamp;
+pub mod text_render;
+pub mod thumbnail_cache_size;
+pub mod thumbnail_concurrency;
 pub mod tracing;
 pub mod user_id;
+pub mod watermark;
+pub mod window_state;
 
 use crate::cli::Cli;
+use crate::config_file::load_config;
 use clap::CommandFactory;
 use clap::FromArgMatches;
 pub use max_name_length::*;
+pub use offline::*;
+pub use quiet::*;
 pub use session_id::*;
 pub use site_id::*;
 pub use user_id::*;
 
 // Entrypoint matching the pattern in teamy-rust-cli
 /// # Errors
-/// Returns an error if CLI parsing fails or if tracing initialization fails or if the invoked command fails.
+/// Returns an error if CLI parsing fails or if the config file cannot be loaded or if tracing initialization fails or if the invoked command fails.
 pub fn main() -> eyre::Result<()> {
     color_eyre::install()?;
     let cli = Cli::command();
-    let cli = Cli::from_arg_matches(&cli.get_matches())?;
+    let mut cli = Cli::from_arg_matches(&cli.get_matches())?;
 
-    // Initialize tracing based on global args (debug and --json/--log-file)
+    // Before anything resolves APP_HOME
+    if let Some(dir) = &cli.global_args.app_home {
+        crate::app_home::override_app_home(dir.clone());
+    }
+
+    // Flags from the command line win over the config file, so parse again with its defaults
+    let config = load_config(&crate::app_home::APP_HOME, cli.global_args.config.as_deref())?;
+    if config.has_flag_defaults() {
+        cli = Cli::from_arg_matches(&config.apply_to(Cli::command()).get_matches())?;
+    }
+    cli.global_args.fill_from_config(&config);
+
+    // Initialize tracing based on global args (-q/-v/--debug and --json/--log-file)
     crate::tracing::init_tracing(
         cli.global_args.log_level(),
         &cli.global_args.json_log_behaviour(),
+        cli.global_args.log_rotation(),
     )?;
+    crate::app_home::check_app_home_writable();
 
     cli.invoke()?;
     Ok(())
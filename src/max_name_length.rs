@@ -1,4 +1,5 @@
 use crate::app_home::APP_HOME;
+use crate::app_home::AppHome;
 use std::env;
 use std::fs;
 use std::io::Write;
@@ -17,16 +18,25 @@ impl MaxNameLength {
     pub const DEFAULT: usize = 50;
     const FILE_NAME: &'static str = "max_name_length.txt";
 
+    /// [`MaxNameLength::load_from`] the default app home
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading or creating the file fails.
+    pub fn load() -> eyre::Result<MaxNameLength> {
+        Self::load_from(&APP_HOME)
+    }
+
     /// Loads resolving rules:
     /// 1. If $`CM_MAX_NAME_LENGTH` is set -> use it (and DO NOT create file)
-    /// 2. Otherwise, look for `${config_dir}/max_name_length.txt`
+    /// 2. Otherwise, look for `max_name_length.txt` in `home`
     ///    - if file exists, parse its trimmed contents
     ///    - otherwise, create the file containing the default and return default
     ///
     /// # Errors
     ///
-    /// Returns an error if the config file path cannot be determined or reading the file fails.
-    pub fn load() -> eyre::Result<MaxNameLength> {
+    /// Returns an error if reading or creating the file fails.
+    pub fn load_from(home: &AppHome) -> eyre::Result<MaxNameLength> {
         // 1. Env var
         if let Ok(envv) = env::var("CM_MAX_NAME_LENGTH") {
             if let Ok(v) = envv.trim().parse::<usize>() {
@@ -38,8 +48,8 @@ impl MaxNameLength {
             );
         }
 
-        // 2. Config file under config_dir/teamdman/cm/max_name_length.txt
-        let path = Self::config_file_path()?;
+        // 2. Config file in the app home
+        let path = home.file_path(Self::FILE_NAME);
         if path.exists() {
             let s = fs::read_to_string(&path)?.trim().to_string();
             if let Ok(v) = s.parse::<usize>() {
@@ -0,0 +1,152 @@
+//! Whether the max name length counts bytes or characters. Bytes match what file systems
+//! limit; characters match what people count, so accented names aren't flagged early.
+
+use crate::app_home::APP_HOME;
+use crate::app_home::AppHome;
+use crate::atomic_write::write_atomic;
+use std::fs;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use tracing::warn;
+
+const FILE_NAME: &str = "name_length_unit.txt";
+
+/// What a name's length is measured in
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum NameLengthUnit {
+    /// UTF-8 bytes
+    #[default]
+    Bytes,
+    /// Unicode scalar values
+    Chars,
+}
+
+impl NameLengthUnit {
+    pub const ALL: [NameLengthUnit; 2] = [NameLengthUnit::Bytes, NameLengthUnit::Chars];
+
+    /// Length of `name` in this unit
+    #[must_use]
+    pub fn measure(self, name: &str) -> usize {
+        match self {
+            NameLengthUnit::Bytes => name.len(),
+            NameLengthUnit::Chars => name.chars().count(),
+        }
+    }
+
+    /// Byte index ending the longest prefix of `s` that is at most `budget` long in this unit,
+    /// always on a character boundary
+    #[must_use]
+    pub fn prefix_end(self, s: &str, budget: usize) -> usize {
+        match self {
+            NameLengthUnit::Bytes => {
+                let mut end = budget.min(s.len());
+                while !s.is_char_boundary(end) {
+                    end -= 1;
+                }
+                end
+            }
+            NameLengthUnit::Chars => s.char_indices().nth(budget).map_or(s.len(), |(i, _)| i),
+        }
+    }
+
+    /// The word persisted in the settings file and shown next to lengths
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            NameLengthUnit::Bytes => "bytes",
+            NameLengthUnit::Chars => "chars",
+        }
+    }
+}
+
+/// Load the persisted unit, falling back to [`NameLengthUnit::Bytes`] when none has been saved
+/// or the file is not understood
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read.
+pub fn load_name_length_unit(home: &AppHome) -> eyre::Result<NameLengthUnit> {
+    let path = home.file_path(FILE_NAME);
+    if !path.exists() {
+        return Ok(NameLengthUnit::default());
+    }
+    let s = fs::read_to_string(&path)?;
+    if let Some(unit) = NameLengthUnit::ALL
+        .into_iter()
+        .find(|unit| unit.name().eq_ignore_ascii_case(s.trim()))
+    {
+        return Ok(unit);
+    }
+    warn!(
+        "Invalid {} contents: '{}', using default",
+        path.display(),
+        s.trim()
+    );
+    Ok(NameLengthUnit::default())
+}
+
+/// Persist the unit (`None` restores the default). When `home` is the default app home the
+/// in-memory [`NAME_LENGTH_UNIT`] is updated too.
+///
+/// # Errors
+///
+/// Returns an error if the setting cannot be written or removed.
+///
+/// # Panics
+///
+/// Panics if the [`NAME_LENGTH_UNIT`] mutex is poisoned.
+pub fn set_name_length_unit(home: &AppHome, unit: Option<NameLengthUnit>) -> eyre::Result<()> {
+    let path = home.file_path(FILE_NAME);
+    let effective = if let Some(unit) = unit {
+        home.ensure_dir()?;
+        write_atomic(&path, unit.name())?;
+        unit
+    } else {
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        NameLengthUnit::default()
+    };
+    if home.is_default() {
+        *NAME_LENGTH_UNIT.lock().unwrap() = effective;
+    }
+    Ok(())
+}
+
+/// The current unit, loaded from the default app home on first use
+pub static NAME_LENGTH_UNIT: LazyLock<Mutex<NameLengthUnit>> = LazyLock::new(|| {
+    Mutex::new(load_name_length_unit(&APP_HOME).unwrap_or_else(|e| {
+        warn!("Failed to load name length unit, using default: {}", e);
+        NameLengthUnit::default()
+    }))
+});
+
+/// Convenience accessor for [`NAME_LENGTH_UNIT`]
+///
+/// # Panics
+///
+/// Panics if the [`NAME_LENGTH_UNIT`] mutex is poisoned.
+#[must_use]
+pub fn name_length_unit() -> NameLengthUnit {
+    *NAME_LENGTH_UNIT.lock().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn unit_round_trips_and_defaults_to_bytes() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+        assert_eq!(load_name_length_unit(&home)?, NameLengthUnit::Bytes);
+
+        set_name_length_unit(&home, Some(NameLengthUnit::Chars))?;
+        assert_eq!(load_name_length_unit(&home)?, NameLengthUnit::Chars);
+
+        set_name_length_unit(&home, None)?;
+        assert_eq!(load_name_length_unit(&home)?, NameLengthUnit::Bytes);
+        Ok(())
+    }
+}
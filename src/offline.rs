@@ -0,0 +1,30 @@
+use std::env;
+use std::sync::LazyLock;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+/// Whether network access is disabled for this process.
+///
+/// Initialized from `CM_OFFLINE` (any value other than empty, `0` or `false`)
+/// and switched on by the global `--offline` flag.
+pub static OFFLINE: LazyLock<AtomicBool> =
+    LazyLock::new(|| AtomicBool::new(offline_from_env().unwrap_or(false)));
+
+/// What `CM_OFFLINE` asks for, or `None` when it isn't set
+#[must_use]
+pub fn offline_from_env() -> Option<bool> {
+    env::var("CM_OFFLINE")
+        .ok()
+        .map(|v| !matches!(v.trim().to_ascii_lowercase().as_str(), "" | "0" | "false"))
+}
+
+/// Returns true if commands must only use cached data
+#[must_use]
+pub fn is_offline() -> bool {
+    OFFLINE.load(Ordering::SeqCst)
+}
+
+/// Enable or disable offline mode for the rest of the process
+pub fn set_offline(offline: bool) {
+    OFFLINE.store(offline, Ordering::SeqCst);
+}
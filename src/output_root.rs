@@ -0,0 +1,68 @@
+//! Optional directory that collects all processed outputs, instead of writing each input's
+//! outputs to an `<input>-output` sibling directory. It is loaded from an app home and carried
+//! in [`crate::image_processing::ProcessingSettings::output_root`].
+
+use crate::app_home::AppHome;
+use crate::atomic_write::write_atomic;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+const FILE_NAME: &str = "output_root.txt";
+
+/// Load the persisted output root. A missing or blank file means outputs go next to the inputs.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read.
+pub fn load_output_root(home: &AppHome) -> eyre::Result<Option<PathBuf>> {
+    let path = home.file_path(FILE_NAME);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let s = fs::read_to_string(&path)?;
+    let s = s.trim();
+    Ok((!s.is_empty()).then(|| PathBuf::from(s)))
+}
+
+/// Persist the output root (`None` restores the `<input>-output` sibling layout)
+///
+/// # Errors
+///
+/// Returns an error if the setting cannot be written or removed.
+pub fn set_output_root(home: &AppHome, root: Option<&Path>) -> eyre::Result<()> {
+    let path = home.file_path(FILE_NAME);
+    match root {
+        Some(root) => {
+            home.ensure_dir()?;
+            write_atomic(&path, root.display().to_string())?;
+        }
+        None => {
+            if path.exists() {
+                fs::remove_file(&path)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn output_root_round_trip() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+        assert_eq!(load_output_root(&home)?, None);
+
+        let root = td.path().join("collected");
+        set_output_root(&home, Some(&root))?;
+        assert_eq!(load_output_root(&home)?, Some(root));
+
+        set_output_root(&home, None)?;
+        assert_eq!(load_output_root(&home)?, None);
+        Ok(())
+    }
+}
@@ -0,0 +1,483 @@
+//! Headless entry point for embedding: discover the images under a set of inputs, apply the
+//! rename rules and process them, without going through clap or eframe
+
+use crate::app_home::AppHome;
+use crate::image_extensions::default_image_extensions;
+use crate::image_extensions::load_image_extensions;
+use crate::image_processing::ProcessAllResult;
+use crate::image_processing::ProcessError;
+use crate::image_processing::ProcessItemResult;
+use crate::image_processing::ProcessProgress;
+use crate::image_processing::ProcessingSettings;
+use crate::image_processing::checked_output_targets;
+use crate::image_processing::encoded_format;
+use crate::image_processing::find_output_collisions;
+use crate::image_processing::get_output_dir;
+use crate::image_processing::hash_sidecar_path;
+use crate::image_processing::is_image_file;
+use crate::image_processing::output_targets;
+use crate::image_processing::process_all_images;
+use crate::image_processing::process_image;
+use crate::image_processing::resolve_target_conflicts;
+use crate::image_processing::uniquify_path;
+use crate::image_processing::write_target;
+use crate::input_overrides::InputOverrides;
+use crate::input_overrides::load_input_overrides;
+use crate::inputs;
+use crate::inputs::NameFilter;
+use crate::max_name_length::MaxNameLength;
+use crate::name_length_unit::load_name_length_unit;
+use crate::output_root::load_output_root;
+use crate::rename_rules::RenameOptions;
+use crate::rename_rules::RenameRule;
+use crate::rename_rules::apply_rules_seq;
+use crate::rename_rules::list_rules;
+use crate::rename_rules::rules_enabled;
+use crate::source_stamp::read_stamp;
+use crate::source_stamp::source_stamp;
+use crate::source_stamp::stamp_sidecar_path;
+use crate::source_stamp::stamped_outputs;
+use crate::source_stamp::write_stamp;
+use facet::Facet;
+use rayon::prelude::*;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use tracing::info;
+use tracing::warn;
+
+/// Everything a run needs: where the images are, how to rename them and how to process them
+#[derive(Clone, Debug)]
+pub struct PipelineConfig {
+    /// Input files and directories; they are canonicalized, and ones that don't exist are skipped
+    pub inputs: Vec<PathBuf>,
+    /// File extensions recognized as images when discovering files under the inputs
+    pub image_extensions: Vec<String>,
+    /// Only process images whose file name matches
+    pub filter: Option<NameFilter>,
+    /// Rename rules, applied in order
+    pub rules: Vec<RenameRule>,
+    pub rename_options: RenameOptions,
+    pub settings: ProcessingSettings,
+    /// Settings overrides per input root
+    pub overrides: InputOverrides,
+    /// How many images to process at once (`None` = the number of CPUs)
+    pub jobs: Option<usize>,
+}
+
+impl PipelineConfig {
+    /// Process the images with the default extensions under `inputs` with default settings, no
+    /// rename rules and no overrides
+    #[must_use]
+    pub fn new(inputs: Vec<PathBuf>) -> Self {
+        Self {
+            inputs,
+            image_extensions: default_image_extensions(),
+            filter: None,
+            rules: Vec::new(),
+            rename_options: RenameOptions::new(crate::MAX_NAME_LENGTH.load(Ordering::SeqCst)),
+            settings: ProcessingSettings::default(),
+            overrides: InputOverrides::default(),
+            jobs: None,
+        }
+    }
+
+    /// The persisted inputs, image extensions, rename rules, max name length and unit,
+    /// overrides and output root of `home`, with otherwise default settings
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of those cannot be loaded.
+    pub fn from_app_home(home: &AppHome) -> eyre::Result<Self> {
+        let mut config = Self::new(inputs::load_inputs(home)?);
+        config.image_extensions = load_image_extensions(home)?;
+        config.settings.output_root = load_output_root(home)?;
+        config.rules = list_rules(home)?.into_iter().map(|(_, r)| r).collect();
+        config.rename_options.max_name_length = MaxNameLength::load_from(home)?.as_usize();
+        config.rename_options.rules_enabled = rules_enabled(home)?;
+        config.rename_options.length_unit = load_name_length_unit(home)?;
+        config.overrides = load_input_overrides(home)?;
+        Ok(config)
+    }
+}
+
+/// The images a run would process and the names they would get
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PipelinePlan {
+    /// Canonical input roots
+    pub roots: Vec<PathBuf>,
+    /// Recognized images under the roots that pass the filter, in discovery order
+    pub files: Vec<PathBuf>,
+    /// `files` with the rename rules applied, index for index
+    pub renamed: Vec<PathBuf>,
+    /// Recognized images before the filter was applied
+    pub total: usize,
+}
+
+/// Discover the images under the inputs and work out their renamed paths, without writing
+/// anything
+///
+/// # Errors
+///
+/// Returns an error if an input directory cannot be read.
+pub fn plan(config: &PipelineConfig) -> eyre::Result<PipelinePlan> {
+    let roots: Vec<PathBuf> = config
+        .inputs
+        .iter()
+        .filter_map(|p| match dunce::canonicalize(p) {
+            Ok(cp) => Some(cp),
+            Err(e) => {
+                warn!("Skipping input {}: {}", p.display(), e);
+                None
+            }
+        })
+        .collect();
+    let all_files: Vec<PathBuf> =
+        inputs::list_files_in(&roots, config.settings.output_root.as_deref())?
+            .into_iter()
+            .filter(|p| is_image_file(p, &config.image_extensions))
+            .collect();
+    let total = all_files.len();
+    let files: Vec<PathBuf> = all_files
+        .into_iter()
+        .filter(|p| config.filter.as_ref().is_none_or(|f| f.matches(p)))
+        .collect();
+    let renamed = apply_rules_seq(&files, &config.rules, &config.rename_options);
+    Ok(PipelinePlan {
+        roots,
+        files,
+        renamed,
+        total,
+    })
+}
+
+/// What a run would do with one image
+#[derive(Clone, Debug, Default, PartialEq, Eq, Facet)]
+pub struct PlannedOutput {
+    pub source: String,
+    /// File name after the rename rules
+    pub renamed: String,
+    /// Where the image would be written; `None` when no output path could be worked out
+    pub output: Option<String>,
+    pub cropped: bool,
+    /// Size of the encoded output; `None` when the image could not be processed
+    pub estimated_size: Option<u64>,
+    /// Another image would be written to the same output path
+    pub collides: bool,
+    /// The output already exists and the on-conflict policy keeps it, so nothing is written
+    pub keeps_existing: bool,
+    /// Why the image could not be processed
+    pub error: Option<String>,
+}
+
+/// Work out the full effect of running `plan` with the settings of `config`: each image's
+/// renamed name, output path after the on-conflict policy, crop and encoded size, and which
+/// outputs collide. Every image is processed in memory and nothing is written.
+///
+/// # Errors
+///
+/// Returns an error if the worker pool cannot be started.
+pub fn preview(config: &PipelineConfig, plan: &PipelinePlan) -> eyre::Result<Vec<PlannedOutput>> {
+    let targets = output_targets(
+        &plan.files,
+        &plan.renamed,
+        &plan.roots,
+        &config.settings,
+        &config.overrides,
+    );
+    let collisions: HashSet<PathBuf> = find_output_collisions(
+        plan.files
+            .iter()
+            .zip(&targets)
+            .filter_map(|(input, target)| {
+                let (_, output) = target.as_ref().ok()?;
+                Some((input.as_path(), output.as_path()))
+            }),
+    )
+    .into_iter()
+    .map(|(output, _)| output)
+    .collect();
+    let resolved = resolve_target_conflicts(targets.clone(), &config.settings, &config.overrides);
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(config.jobs.unwrap_or(0))
+        .build()?;
+    Ok(pool.install(|| {
+        plan.files
+            .par_iter()
+            .zip(&plan.renamed)
+            .zip(targets.into_par_iter().zip(resolved))
+            .map(|((source, renamed), (target, resolved))| {
+                let mut planned = PlannedOutput {
+                    source: source.display().to_string(),
+                    renamed: renamed
+                        .file_name()
+                        .map(|s| s.to_string_lossy().to_string())
+                        .unwrap_or_default(),
+                    ..PlannedOutput::default()
+                };
+                let (root, output, resolved) = match (target, resolved) {
+                    (Ok((root, output)), Ok((_, resolved))) => (root, output, resolved),
+                    (Err(e), _) | (_, Err(e)) => {
+                        planned.error = Some(e.to_string());
+                        return planned;
+                    }
+                };
+                planned.collides = collisions.contains(&output);
+                let Some(output) = resolved else {
+                    planned.output = Some(output.display().to_string());
+                    planned.keeps_existing = true;
+                    return planned;
+                };
+                planned.output = Some(output.display().to_string());
+                let settings = config.overrides.settings_for(root, &config.settings);
+                // Encoded the way a real run would write it to `output`
+                let settings = ProcessingSettings {
+                    output_format: Some(encoded_format(&output, &settings)),
+                    ..settings
+                };
+                match process_image(source, &settings) {
+                    Ok(processed) => {
+                        planned.cropped = processed.was_cropped;
+                        planned.estimated_size = Some(processed.estimated_size);
+                    }
+                    Err(e) => planned.error = Some(e.to_string()),
+                }
+                planned
+            })
+            .collect()
+    }))
+}
+
+/// `planned` as CSV with a header row, quoting fields where needed
+#[must_use]
+pub fn preview_to_csv(planned: &[PlannedOutput]) -> String {
+    fn field(value: &str) -> String {
+        if value.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_owned()
+        }
+    }
+    let mut out = String::from(
+        "source,renamed,output,cropped,estimated_size,collides,keeps_existing,error\n",
+    );
+    for entry in planned {
+        let row = [
+            field(&entry.source),
+            field(&entry.renamed),
+            field(entry.output.as_deref().unwrap_or_default()),
+            entry.cropped.to_string(),
+            entry
+                .estimated_size
+                .map(|size| size.to_string())
+                .unwrap_or_default(),
+            entry.collides.to_string(),
+            entry.keeps_existing.to_string(),
+            field(entry.error.as_deref().unwrap_or_default()),
+        ];
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Process the images of `plan` with the settings of `config`, reporting each image to
+/// `progress` as it starts and sending [`ProcessProgress`] events to `events`
+///
+/// # Errors
+///
+/// Returns an error if the worker pool cannot be started or two inputs would be written to the
+/// same output path.
+pub fn execute(
+    config: &PipelineConfig,
+    plan: &PipelinePlan,
+    progress: Option<&(dyn Fn(usize, usize, &Path) + Sync)>,
+    events: Option<&mpsc::Sender<ProcessProgress>>,
+) -> eyre::Result<ProcessAllResult> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(config.jobs.unwrap_or(0))
+        .build()?;
+    pool.install(|| {
+        process_all_images(
+            &plan.files,
+            &plan.renamed,
+            &plan.roots,
+            &config.settings,
+            &config.overrides,
+            progress,
+            events,
+        )
+    })
+}
+
+/// Discover, rename and process every image under the inputs of `config`
+///
+/// # Errors
+///
+/// Returns an error if an input directory cannot be read, the worker pool cannot be started or
+/// two inputs would be written to the same output path. Failures of individual images are
+/// collected into the result instead.
+pub fn run(config: &PipelineConfig) -> eyre::Result<ProcessAllResult> {
+    execute(config, &plan(config)?, None, None)
+}
+
+/// What [`ensure_processed`] did
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EnsureReport {
+    /// Outputs that were missing or stale and have been written
+    pub written: Vec<PathBuf>,
+    /// Outputs whose stamp still matched their source and settings
+    pub up_to_date: usize,
+    /// Inputs left out by the decode error policy or kept by the conflict policy
+    pub skipped: usize,
+    pub errors: Vec<(PathBuf, ProcessError)>,
+    /// Stamped outputs whose source was removed or now writes elsewhere, deleted by `prune`
+    pub pruned: Vec<PathBuf>,
+}
+
+/// Bring the outputs of `plan` up to date with as few writes as possible: an output is only
+/// processed again when it is missing or the `.cmstamp` sidecar written next to it (see
+/// [`crate::source_stamp`]) no longer matches its source bytes and settings. With `prune`,
+/// stamped outputs whose source under the input roots was removed, or which the source no
+/// longer writes to, are deleted, so the output folders mirror the inputs. Outputs without a
+/// stamp are never pruned. Under `dry_run` nothing is written or deleted.
+///
+/// The on-conflict policy only applies to files the source didn't write itself: an output
+/// stamped with the same source, at the output path or one of the numbered variants the
+/// `Rename` policy writes, is that source's to bring up to date.
+///
+/// # Errors
+///
+/// Returns an error if the worker pool cannot be started, two inputs would be written to the
+/// same output path, or an output directory cannot be scanned for pruning.
+pub fn ensure_processed(
+    config: &PipelineConfig,
+    plan: &PipelinePlan,
+    prune: bool,
+) -> eyre::Result<EnsureReport> {
+    enum Freshness {
+        UpToDate,
+        Written,
+        Skipped,
+        Failed(ProcessError),
+    }
+    let refresh = |source: &Path, output: &Path, settings: &ProcessingSettings| {
+        let stamp = match source_stamp(source, settings) {
+            Ok(stamp) => stamp,
+            Err(e) => return Freshness::Failed(ProcessError::DecodeFailed(e.to_string())),
+        };
+        if output.is_file() && read_stamp(output).is_some_and(|s| s == stamp) {
+            return Freshness::UpToDate;
+        }
+        match write_target(source, output, settings).0 {
+            ProcessItemResult::Processed(_) | ProcessItemResult::CopiedThrough => {}
+            ProcessItemResult::Skipped | ProcessItemResult::AlreadyExists => {
+                return Freshness::Skipped;
+            }
+            ProcessItemResult::Failed(e) => return Freshness::Failed(e),
+        }
+        if !settings.dry_run
+            && let Err(e) = write_stamp(output, &stamp)
+        {
+            return Freshness::Failed(ProcessError::WriteFailed(e.to_string()));
+        }
+        Freshness::Written
+    };
+
+    let targets = checked_output_targets(
+        &plan.files,
+        &plan.renamed,
+        &plan.roots,
+        &config.settings,
+        &config.overrides,
+    )?;
+    let resolved = resolve_target_conflicts(targets.clone(), &config.settings, &config.overrides);
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(config.jobs.unwrap_or(0))
+        .build()?;
+    let items: Vec<(Option<PathBuf>, Freshness)> = pool.install(|| {
+        plan.files
+            .par_iter()
+            .zip(targets.into_par_iter().zip(resolved))
+            .map(|(source, (target, resolved))| {
+                let (root, natural, resolved) = match (target, resolved) {
+                    (Ok((root, natural)), Ok((_, resolved))) => (root, natural, resolved),
+                    (Err(e), _) | (_, Err(e)) => return (None, Freshness::Failed(e)),
+                };
+                // The first of the output path and its numbered variants that is free or was
+                // written from this source before
+                let own = uniquify_path(&natural, |p| {
+                    p.exists() && !read_stamp(p).is_some_and(|s| s.source == *source)
+                });
+                let output = if own.exists() {
+                    own
+                } else if let Some(resolved) = resolved {
+                    resolved
+                } else {
+                    return (None, Freshness::Skipped);
+                };
+                let settings = config.overrides.settings_for(root, &config.settings);
+                let freshness = refresh(source, &output, &settings);
+                (Some(output), freshness)
+            })
+            .collect()
+    });
+
+    let mut report = EnsureReport::default();
+    // Outputs of inputs that failed are kept too, so pruning leaves them alone
+    let mut current: HashSet<PathBuf> = HashSet::new();
+    for (source, (output, item)) in plan.files.iter().zip(items) {
+        match item {
+            Freshness::UpToDate => report.up_to_date += 1,
+            Freshness::Written => report.written.extend(output.clone()),
+            Freshness::Skipped => report.skipped += 1,
+            Freshness::Failed(e) => report.errors.push((source.clone(), e)),
+        }
+        current.extend(output);
+    }
+
+    if prune {
+        let planned: HashSet<&Path> = plan.files.iter().map(PathBuf::as_path).collect();
+        let output_root = config.settings.output_root.as_deref();
+        let mut dirs: Vec<PathBuf> = plan
+            .roots
+            .iter()
+            .map(|r| get_output_dir(r, output_root))
+            .collect();
+        if config.settings.flatten
+            && let Some(root) = output_root
+        {
+            dirs.push(root.to_path_buf());
+        }
+        dirs.sort();
+        dirs.dedup();
+        for (output, stamp) in stamped_outputs(&dirs)? {
+            // Sources that still exist but were filtered out keep their outputs
+            let orphaned = !current.contains(&output)
+                && plan.roots.iter().any(|r| stamp.source.starts_with(r))
+                && (!stamp.source.exists() || planned.contains(stamp.source.as_path()));
+            if !orphaned {
+                continue;
+            }
+            if config.settings.dry_run {
+                info!("Would prune {}", output.display());
+            } else {
+                for path in [
+                    output.clone(),
+                    stamp_sidecar_path(&output),
+                    hash_sidecar_path(&output),
+                ] {
+                    if path.exists() {
+                        std::fs::remove_file(&path)?;
+                    }
+                }
+                info!("Pruned {}", output.display());
+            }
+            report.pruned.push(output);
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,186 @@
+//! Per-stage timing of image processing, summarised over a batch to show whether decoding,
+//! cropping, encoding or writing dominates
+
+use std::fmt;
+use std::time::Duration;
+
+/// A step of processing one image
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// Reading and decoding the input file
+    Decode,
+    /// Tone, deskew, crop, sharpen and watermark
+    Crop,
+    /// Encoding the output (and its preview) and embedding EXIF
+    Encode,
+    /// Writing the output file
+    Write,
+}
+
+impl Stage {
+    pub const ALL: [Stage; 4] = [Stage::Decode, Stage::Crop, Stage::Encode, Stage::Write];
+
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Stage::Decode => "decode",
+            Stage::Crop => "crop",
+            Stage::Encode => "encode",
+            Stage::Write => "write",
+        }
+    }
+}
+
+/// How long each stage took for one image
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StageTimings {
+    pub decode: Duration,
+    pub crop: Duration,
+    pub encode: Duration,
+    pub write: Duration,
+}
+
+impl StageTimings {
+    #[must_use]
+    pub fn get(&self, stage: Stage) -> Duration {
+        match stage {
+            Stage::Decode => self.decode,
+            Stage::Crop => self.crop,
+            Stage::Encode => self.encode,
+            Stage::Write => self.write,
+        }
+    }
+
+    /// Sum of all stages
+    #[must_use]
+    pub fn total(&self) -> Duration {
+        Stage::ALL.iter().map(|&stage| self.get(stage)).sum()
+    }
+
+    /// Record each stage in milliseconds on a span declared with `decode_ms`, `crop_ms`,
+    /// `encode_ms` and `write_ms` fields
+    pub fn record_in(&self, span: &tracing::Span) {
+        for stage in Stage::ALL {
+            let millis = u64::try_from(self.get(stage).as_millis()).unwrap_or(u64::MAX);
+            span.record(format!("{}_ms", stage.name()).as_str(), millis);
+        }
+    }
+}
+
+/// Min/max/mean of one stage across a batch
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StageStats {
+    pub min: Duration,
+    pub max: Duration,
+    pub total: Duration,
+    pub count: u32,
+}
+
+impl StageStats {
+    fn record(&mut self, duration: Duration) {
+        if self.count == 0 || duration < self.min {
+            self.min = duration;
+        }
+        self.max = self.max.max(duration);
+        self.total += duration;
+        self.count += 1;
+    }
+
+    #[must_use]
+    pub fn mean(&self) -> Duration {
+        self.total.checked_div(self.count).unwrap_or_default()
+    }
+}
+
+/// Stage statistics over every image of a batch that was processed
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TimingSummary {
+    stages: [StageStats; 4],
+}
+
+impl TimingSummary {
+    /// Add one image's timings
+    pub fn record(&mut self, timings: &StageTimings) {
+        for (i, stage) in Stage::ALL.into_iter().enumerate() {
+            self.stages[i].record(timings.get(stage));
+        }
+    }
+
+    #[must_use]
+    pub fn stats(&self, stage: Stage) -> &StageStats {
+        let index = Stage::ALL.iter().position(|&s| s == stage).unwrap_or_default();
+        &self.stages[index]
+    }
+
+    /// Number of images recorded
+    #[must_use]
+    pub fn count(&self) -> u32 {
+        self.stages[0].count
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.count() == 0
+    }
+
+    /// Time spent in all stages of all images
+    #[must_use]
+    pub fn total(&self) -> Duration {
+        self.stages.iter().map(|s| s.total).sum()
+    }
+}
+
+impl fmt::Display for TimingSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Timing over {} images, {:.1?} total", self.count(), self.total())?;
+        for stage in Stage::ALL {
+            let stats = self.stats(stage);
+            write!(
+                f,
+                "\n  {}: min {:.1?}, max {:.1?}, mean {:.1?}, total {:.1?}",
+                stage.name(),
+                stats.min,
+                stats.max,
+                stats.mean(),
+                stats.total
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn summary_aggregates_stage_times() {
+        let mut summary = TimingSummary::default();
+        assert!(summary.is_empty());
+        summary.record(&StageTimings {
+            decode: ms(10),
+            crop: ms(5),
+            encode: ms(30),
+            write: ms(1),
+        });
+        summary.record(&StageTimings {
+            decode: ms(30),
+            crop: ms(7),
+            encode: ms(10),
+            write: ms(3),
+        });
+
+        assert_eq!(summary.count(), 2);
+        let decode = summary.stats(Stage::Decode);
+        assert_eq!((decode.min, decode.max, decode.mean()), (ms(10), ms(30), ms(20)));
+        let encode = summary.stats(Stage::Encode);
+        assert_eq!((encode.min, encode.max, encode.total), (ms(10), ms(30), ms(40)));
+        assert_eq!(summary.stats(Stage::Write).mean(), ms(2));
+        assert_eq!(summary.total(), ms(96));
+        assert!(summary.to_string().contains("decode: min 10.0ms"));
+    }
+}
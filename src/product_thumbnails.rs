@@ -0,0 +1,86 @@
+//! Product thumbnails from search results, downloaded once and cached on disk by URL
+
+use crate::app_home::AppHome;
+use crate::atomic_write::write_atomic;
+use crate::offline::is_offline;
+use sha2::Digest;
+use sha2::Sha256;
+use std::path::PathBuf;
+use tracing::debug;
+
+/// Where the thumbnail for `url` is cached. Named by a hash of the URL, as URLs can be
+/// arbitrarily long.
+#[must_use]
+pub fn cache_path(home: &AppHome, url: &str) -> PathBuf {
+    let hash = hex::encode(Sha256::digest(url.as_bytes()));
+    home.file_path("cache")
+        .join("product-thumbs")
+        .join(&hash[..16])
+}
+
+/// The cached thumbnail bytes for `url`, if it has been downloaded before
+#[must_use]
+pub fn read_cached(home: &AppHome, url: &str) -> Option<Vec<u8>> {
+    std::fs::read(cache_path(home, url)).ok()
+}
+
+/// The thumbnail image bytes for `url`, downloading and caching them on first use.
+///
+/// # Errors
+///
+/// Returns an error if the thumbnail isn't cached and can't be downloaded (including when
+/// offline), or if it can't be written to the cache.
+pub async fn load_thumbnail(home: &AppHome, url: &str) -> eyre::Result<Vec<u8>> {
+    if let Some(bytes) = read_cached(home, url) {
+        return Ok(bytes);
+    }
+    if is_offline() {
+        eyre::bail!("offline: thumbnail not cached for {url}");
+    }
+
+    debug!(url, "Downloading product thumbnail");
+    let user_agent = format!(
+        "{} v{} (+https://github.com/TeamDman/cm)",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION"),
+    );
+    let bytes = reqwest::Client::new()
+        .get(url)
+        .header(reqwest::header::USER_AGENT, user_agent)
+        .send()
+        .await?
+        .error_for_status()?
+        .bytes()
+        .await?;
+
+    let path = cache_path(home, url);
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    write_atomic(&path, &bytes)?;
+    Ok(bytes.to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn thumbnails_are_cached_per_url() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+        let a = "https://example.com/thumbs/a.jpg";
+        let b = "https://example.com/thumbs/b.jpg";
+        assert_ne!(cache_path(&home, a), cache_path(&home, b));
+        assert!(cache_path(&home, a).starts_with(td.path().join("cache").join("product-thumbs")));
+
+        assert_eq!(read_cached(&home, a), None);
+        let path = cache_path(&home, a);
+        std::fs::create_dir_all(path.parent().unwrap())?;
+        std::fs::write(&path, b"bytes")?;
+        assert_eq!(read_cached(&home, a).as_deref(), Some(&b"bytes"[..]));
+        assert_eq!(read_cached(&home, b), None);
+        Ok(())
+    }
+}
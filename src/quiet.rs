@@ -0,0 +1,18 @@
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+/// Whether commands should leave out progress and other chatter from their human output.
+///
+/// Switched on by the global `-q/--quiet` flag; errors and results are still printed.
+pub static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Returns true if progress output should be suppressed
+#[must_use]
+pub fn is_quiet() -> bool {
+    QUIET.load(Ordering::SeqCst)
+}
+
+/// Enable or disable quiet output for the rest of the process
+pub fn set_quiet(quiet: bool) {
+    QUIET.store(quiet, Ordering::SeqCst);
+}
@@ -1,20 +1,59 @@
+mod rename_pipeline;
 mod rename_rule;
 
 use crate::app_home::AppHome;
+use crate::atomic_write::write_atomic;
+pub use rename_pipeline::*;
 pub use rename_rule::*;
 use std::fs;
-use std::io::Write;
 use std::path::PathBuf;
-use std::sync::LazyLock;
 use std::sync::Mutex;
+use tracing::warn;
 use uuid::Uuid;
 
 const DIR_NAME: &str = "rename-rules";
 const FILE_EXT: &str = "txt";
+const ENABLED_FILE_NAME: &str = "rename_rules_enabled.txt";
+
+// Serializes all access to rule files: every add/write/remove/duplicate/list holds this for its
+// whole directory operation, so a listing never observes a half-finished mutation. Lock ordering:
+// take `RULES_DIR_LOCK` before `GLOBAL_RULE_CACHE`, never the other way round.
+static RULES_DIR_LOCK: Mutex<()> = Mutex::new(());
+
+/// Rule listing of the default home, kept until a mutation invalidates it
+struct RuleCache(Mutex<Option<RuleListing>>);
+
+impl RuleCache {
+    const fn new() -> Self {
+        Self(Mutex::new(None))
+    }
+
+    fn get(&self) -> Option<RuleListing> {
+        self.0.lock().unwrap().clone()
+    }
+
+    fn set(&self, listing: RuleListing) {
+        *self.0.lock().unwrap() = Some(listing);
+    }
+
+    fn invalidate(&self) {
+        *self.0.lock().unwrap() = None;
+    }
+}
 
 // Cache for global rules when accessed via APP_HOME
-static GLOBAL_RULE_CACHE: LazyLock<Mutex<Option<Vec<RenameRule>>>> =
-    LazyLock::new(|| Mutex::new(None));
+static GLOBAL_RULE_CACHE: RuleCache = RuleCache::new();
+
+/// Drop the cached rule list so the next `list_rules` on the default home re-reads the directory.
+///
+/// Every mutating operation in this module calls this regardless of which home it targets, since
+/// a non-default home may still resolve to the same directory as the default one.
+/// # Panics
+/// Panics if the global rule cache mutex cannot be locked.
+pub fn invalidate_cache() {
+    GLOBAL_RULE_CACHE.invalidate();
+}
+
 /// Ensure the rename rules directory exists and return its path
 fn dir_for(home: &AppHome) -> eyre::Result<PathBuf> {
     let dir = home.file_path(DIR_NAME);
@@ -33,6 +72,29 @@ pub fn rules_dir(home: &AppHome) -> eyre::Result<PathBuf> {
     dir_for(home)
 }
 
+/// Whether rename rules are globally enabled.
+///
+/// Defaults to enabled when the flag has never been written.
+/// # Errors
+/// Returns an error if the flag file exists but cannot be read.
+pub fn rules_enabled(home: &AppHome) -> eyre::Result<bool> {
+    let path = home.file_path(ENABLED_FILE_NAME);
+    if !path.exists() {
+        return Ok(true);
+    }
+    let s = fs::read_to_string(&path)?;
+    Ok(!s.trim().eq_ignore_ascii_case("false"))
+}
+
+/// Persist whether rename rules are globally enabled
+/// # Errors
+/// Returns an error if the flag file cannot be written.
+pub fn set_rules_enabled(home: &AppHome, enabled: bool) -> eyre::Result<()> {
+    home.ensure_dir()?;
+    write_atomic(&home.file_path(ENABLED_FILE_NAME), enabled.to_string())?;
+    Ok(())
+}
+
 /// List rule file paths sorted by name
 fn list_rule_files(home: &AppHome) -> eyre::Result<Vec<PathBuf>> {
     let dir = dir_for(home)?;
@@ -45,120 +107,325 @@ fn list_rule_files(home: &AppHome) -> eyre::Result<Vec<PathBuf>> {
     Ok(v)
 }
 
-/// Add a new rule file and return its assigned UUID
-/// # Errors
-/// Returns an error if the rule file cannot be written.
-/// # Panics
-/// Panics if the global rule cache mutex cannot be locked.
-pub fn add_rule(home: &AppHome, rule: &RenameRule) -> eyre::Result<Uuid> {
+/// Write a new rule file; the caller must hold `RULES_DIR_LOCK`
+fn add_rule_locked(home: &AppHome, rule: &RenameRule, cache: &RuleCache) -> eyre::Result<Uuid> {
     let dir = dir_for(home)?;
     let filename = format!("{}.{}", rule.id, FILE_EXT);
     let path = dir.join(filename);
-    let mut f = fs::OpenOptions::new()
-        .create(true)
-        .write(true)
-        .truncate(true)
-        .open(&path)?;
     let content = rule.to_file_text();
-    writeln!(f, "{content}")?;
-    // invalidate cache if default app home
-    if home.is_default() {
-        let mut cache = GLOBAL_RULE_CACHE.lock().unwrap();
-        *cache = None;
-    }
+    write_atomic(&path, format!("{content}\n"))?;
+    cache.invalidate();
     Ok(rule.id)
 }
 
+/// Add a new rule file and return its assigned UUID
+/// # Errors
+/// Returns an error if the rule file cannot be written.
+/// # Panics
+/// Panics if the rules directory or global rule cache mutex cannot be locked.
+pub fn add_rule(home: &AppHome, rule: &RenameRule) -> eyre::Result<Uuid> {
+    add_rule_in(home, rule, &GLOBAL_RULE_CACHE)
+}
+
+fn add_rule_in(home: &AppHome, rule: &RenameRule, cache: &RuleCache) -> eyre::Result<Uuid> {
+    let _dir_lock = RULES_DIR_LOCK.lock().unwrap();
+    add_rule_locked(home, rule, cache)
+}
+
 /// Remove a rule by UUID
 /// # Errors
 /// Returns an error if the rule file cannot be removed.
 /// # Panics
-/// Panics if the global rule cache mutex cannot be locked.
+/// Panics if the rules directory or global rule cache mutex cannot be locked.
 pub fn remove_rule(home: &AppHome, id: Uuid) -> eyre::Result<bool> {
+    remove_rule_in(home, id, &GLOBAL_RULE_CACHE)
+}
+
+fn remove_rule_in(home: &AppHome, id: Uuid, cache: &RuleCache) -> eyre::Result<bool> {
+    let _dir_lock = RULES_DIR_LOCK.lock().unwrap();
     let dir = dir_for(home)?;
     let path = dir.join(format!("{id}.{FILE_EXT}"));
     if !path.exists() {
         return Ok(false);
     }
     fs::remove_file(&path)?;
-    // invalidate cache if default app home
-    if home.is_default() {
-        let mut cache = GLOBAL_RULE_CACHE.lock().unwrap();
-        *cache = None;
-    }
+    cache.invalidate();
     Ok(true)
 }
 
+/// Duplicate a rule by UUID, writing a copy with a fresh id.
+///
+/// Rules have no explicit ordering, so the copy is appended like any newly added rule.
+/// Returns `None` if no rule with the given id exists.
+/// # Errors
+/// Returns an error if the original rule cannot be read or the copy cannot be written.
+/// # Panics
+/// Panics if the rules directory or global rule cache mutex cannot be locked.
+pub fn duplicate_rule(home: &AppHome, id: Uuid) -> eyre::Result<Option<RenameRule>> {
+    let _dir_lock = RULES_DIR_LOCK.lock().unwrap();
+    let dir = dir_for(home)?;
+    let path = dir.join(format!("{id}.{FILE_EXT}"));
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(&path)?;
+    let mut copy = RenameRule::from_file_text(&text)?;
+    copy.id = Uuid::new_v4();
+    add_rule_locked(home, &copy, &GLOBAL_RULE_CACHE)?;
+    Ok(Some(copy))
+}
+
 /// Write rule to file by id (create or overwrite)
 /// # Errors
 /// Returns an error if the rule file cannot be written.
 /// # Panics
-/// Panics if the global rule cache mutex cannot be locked.
+/// Panics if the rules directory or global rule cache mutex cannot be locked.
 pub fn write_rule(home: &AppHome, rule: &RenameRule) -> eyre::Result<()> {
+    write_rule_in(home, rule, &GLOBAL_RULE_CACHE)
+}
+
+fn write_rule_in(home: &AppHome, rule: &RenameRule, cache: &RuleCache) -> eyre::Result<()> {
+    let _dir_lock = RULES_DIR_LOCK.lock().unwrap();
     let dir = dir_for(home)?;
     let path = dir.join(format!("{}.{}", rule.id, FILE_EXT));
-    let mut f = fs::OpenOptions::new()
-        .create(true)
-        .write(true)
-        .truncate(true)
-        .open(&path)?;
     let content = rule.to_file_text();
-    write!(f, "{content}")?;
+    write_atomic(&path, content)?;
 
-    // invalidate cache if default app home
-    if home.is_default() {
-        let mut cache = GLOBAL_RULE_CACHE.lock().unwrap();
-        *cache = None;
-    }
+    cache.invalidate();
     Ok(())
 }
 
+/// A rule file that could not be read or parsed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SkippedRuleFile {
+    pub path: PathBuf,
+    pub error: String,
+}
+
+/// Parsed rules with their indices, plus the files that failed to load
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RuleListing {
+    pub rules: Vec<(usize, RenameRule)>,
+    pub skipped: Vec<SkippedRuleFile>,
+}
+
 /// List parsed rules with their indices
 /// # Errors
-/// Returns an error if the rules directory cannot be read or rules cannot be parsed.
+/// Returns an error if the rules directory cannot be read.
 /// # Panics
-/// Panics if the global rule cache mutex cannot be locked.
+/// Panics if the rules directory or global rule cache mutex cannot be locked.
 pub fn list_rules(home: &AppHome) -> eyre::Result<Vec<(usize, RenameRule)>> {
+    Ok(list_rules_with_skipped(home)?.rules)
+}
+
+/// List parsed rules along with any rule files that were skipped because they could not be read
+/// or parsed. Each skipped file is logged at `warn`.
+/// # Errors
+/// Returns an error if the rules directory cannot be read.
+/// # Panics
+/// Panics if the rules directory or global rule cache mutex cannot be locked.
+pub fn list_rules_with_skipped(home: &AppHome) -> eyre::Result<RuleListing> {
+    let _dir_lock = RULES_DIR_LOCK.lock().unwrap();
     // If this is the default app home, use cached list when available
-    if home.is_default() {
-        let cache_lock = GLOBAL_RULE_CACHE.lock().unwrap();
-        if let Some(cached) = cache_lock.as_ref() {
-            let out = cached
-                .iter()
-                .cloned()
-                .enumerate()
-                .map(|(i, r)| (i + 1, r))
-                .collect();
-            return Ok(out);
-        }
+    if home.is_default()
+        && let Some(cached) = GLOBAL_RULE_CACHE.get()
+    {
+        return Ok(cached);
     }
 
     let files = list_rule_files(home)?;
-    let mut out_rules = Vec::new();
+    let mut listing = RuleListing::default();
     for p in &files {
-        if let Ok(text) = std::fs::read_to_string(p)
-            && let Ok(mut rule) = RenameRule::from_file_text(&text)
-        {
-            // Parse id from filename (stem)
-            if let Some(stem) = p.file_stem().and_then(|s| s.to_str())
-                && let Ok(id) = Uuid::parse_str(stem)
-            {
-                rule.id = id;
+        // A corrupt rule file is skipped rather than failing the whole listing
+        let parsed = std::fs::read_to_string(p)
+            .map_err(eyre::Report::from)
+            .and_then(|text| RenameRule::from_file_text(&text));
+        match parsed {
+            Err(e) => {
+                warn!("Skipping rename rule {}: {}", p.display(), e);
+                listing.skipped.push(SkippedRuleFile {
+                    path: p.clone(),
+                    error: e.to_string(),
+                });
+            }
+            Ok(mut rule) => {
+                // Parse id from filename (stem)
+                if let Some(stem) = p.file_stem().and_then(|s| s.to_str())
+                    && let Ok(id) = Uuid::parse_str(stem)
+                {
+                    rule.id = id;
+                }
+                listing.rules.push((listing.rules.len() + 1, rule));
             }
-            out_rules.push(rule);
         }
     }
 
     // If default home, populate cache
     if home.is_default() {
-        let mut cache_lock = GLOBAL_RULE_CACHE.lock().unwrap();
-        *cache_lock = Some(out_rules.clone());
+        GLOBAL_RULE_CACHE.set(listing.clone());
     }
 
-    let out = out_rules
-        .into_iter()
-        .enumerate()
-        .map(|(i, r)| (i + 1, r))
-        .collect();
-    Ok(out)
+    Ok(listing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn duplicate_rule_copies_content_with_new_id() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+
+        let original = RenameRule {
+            find: "foo".to_string(),
+            replace: "bar".to_string(),
+            enabled: false,
+            case_sensitive: true,
+            only_when_name_too_long: true,
+            ..RenameRule::default()
+        };
+        add_rule(&home, &original)?;
+
+        let copy = duplicate_rule(&home, original.id)?.expect("rule should exist");
+        assert_ne!(copy.id, original.id);
+        assert_eq!(copy.find, original.find);
+        assert_eq!(copy.replace, original.replace);
+        assert_eq!(copy.enabled, original.enabled);
+        assert_eq!(copy.case_sensitive, original.case_sensitive);
+        assert_eq!(
+            copy.only_when_name_too_long,
+            original.only_when_name_too_long
+        );
+
+        let rules = list_rules(&home)?;
+        assert_eq!(rules.len(), 2);
+        assert!(rules.iter().any(|(_, r)| r.id == copy.id));
+
+        assert!(duplicate_rule(&home, Uuid::new_v4())?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn list_rules_skips_corrupt_files() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+        let good = RenameRule {
+            find: "foo".to_string(),
+            ..RenameRule::default()
+        };
+        add_rule(&home, &good)?;
+        let corrupt = dir_for(&home)?.join(format!("{}.{FILE_EXT}", Uuid::new_v4()));
+        fs::write(&corrupt, "only a find line")?;
+
+        let listing = list_rules_with_skipped(&home)?;
+        assert_eq!(listing.rules.len(), 1);
+        assert_eq!(listing.rules[0].1.id, good.id);
+        assert_eq!(listing.skipped.len(), 1);
+        assert_eq!(listing.skipped[0].path, corrupt);
+        assert!(listing.skipped[0].error.contains("replace line"));
+        Ok(())
+    }
+
+    #[test]
+    fn rules_enabled_flag_round_trip() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+
+        // Enabled by default when never written
+        assert!(rules_enabled(&home)?);
+
+        set_rules_enabled(&home, false)?;
+        assert!(!rules_enabled(&home)?);
+
+        set_rules_enabled(&home, true)?;
+        assert!(rules_enabled(&home)?);
+        Ok(())
+    }
+
+    #[test]
+    fn mutations_invalidate_cache_for_any_home() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+        // A cache of its own, so tests reading the default home's rules never see the stale one
+        let cache = RuleCache::new();
+        let stale = || cache.set(RuleListing::default());
+        let is_cleared = || cache.get().is_none();
+
+        let mut rule = RenameRule {
+            find: "a".to_string(),
+            replace: "b".to_string(),
+            ..RenameRule::default()
+        };
+        stale();
+        add_rule_in(&home, &rule, &cache)?;
+        assert!(is_cleared());
+
+        rule.replace = "c".to_string();
+        stale();
+        write_rule_in(&home, &rule, &cache)?;
+        assert!(is_cleared());
+
+        stale();
+        assert!(remove_rule_in(&home, rule.id, &cache)?);
+        assert!(is_cleared());
+        Ok(())
+    }
+
+    #[test]
+    fn add_then_list_is_consistent() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+
+        let first = RenameRule {
+            find: "one".to_string(),
+            ..RenameRule::default()
+        };
+        add_rule(&home, &first)?;
+        assert_eq!(list_rules(&home)?.len(), 1);
+
+        let second = RenameRule {
+            find: "two".to_string(),
+            ..RenameRule::default()
+        };
+        add_rule(&home, &second)?;
+        let ids: Vec<Uuid> = list_rules(&home)?.into_iter().map(|(_, r)| r.id).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(ids.contains(&first.id) && ids.contains(&second.id));
+
+        remove_rule(&home, first.id)?;
+        let rules = list_rules(&home)?;
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0].1.find, "two");
+        Ok(())
+    }
+
+    #[test]
+    fn concurrent_adds_lose_no_rules() -> eyre::Result<()> {
+        const THREADS: usize = 8;
+        const RULES_PER_THREAD: usize = 10;
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+
+        std::thread::scope(|scope| {
+            for t in 0..THREADS {
+                let home = &home;
+                scope.spawn(move || {
+                    for i in 0..RULES_PER_THREAD {
+                        let rule = RenameRule {
+                            find: format!("t{t}-r{i}"),
+                            ..RenameRule::default()
+                        };
+                        add_rule(home, &rule).unwrap();
+                        // Interleave listings with the writes of other threads
+                        list_rules(home).unwrap();
+                    }
+                });
+            }
+        });
+
+        assert_eq!(list_rules(&home)?.len(), THREADS * RULES_PER_THREAD);
+        Ok(())
+    }
 }
@@ -0,0 +1,432 @@
+use crate::name_length_unit::NameLengthUnit;
+use crate::rename_rules::RenameRule;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+/// Options controlling how [`apply_rules_seq`] renames files
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RenameOptions {
+    /// Names longer than this (in `length_unit`) are considered too long
+    pub max_name_length: usize,
+    /// Whether `max_name_length` counts bytes or characters
+    pub length_unit: NameLengthUnit,
+    /// Whether rename rules are applied at all
+    pub rules_enabled: bool,
+    /// Whether to hyphenate camelCase in the base name
+    pub hyphenate: bool,
+    /// Whether to truncate names that are still too long after all other steps
+    pub auto_truncate: bool,
+}
+
+impl RenameOptions {
+    /// Options with rules enabled and every optional step turned off
+    #[must_use]
+    pub fn new(max_name_length: usize) -> Self {
+        Self {
+            max_name_length,
+            length_unit: NameLengthUnit::default(),
+            rules_enabled: true,
+            hyphenate: false,
+            auto_truncate: false,
+        }
+    }
+
+    /// Whether `name` exceeds the max name length
+    #[must_use]
+    pub fn is_too_long(&self, name: &str) -> bool {
+        self.length_unit.measure(name) > self.max_name_length
+    }
+}
+
+/// Hyphenate camelCase strings by inserting '-' before uppercase letters that follow lowercase
+#[must_use]
+pub fn hyphenate_name(name: &str) -> String {
+    let mut result = String::new();
+    let chars: Vec<char> = name.chars().collect();
+    for (i, &c) in chars.iter().enumerate() {
+        if i > 0 && c.is_uppercase() && chars[i - 1].is_lowercase() {
+            result.push('-');
+        }
+        result.push(c);
+    }
+    result
+}
+
+/// Shorten a file name to at most `max_len` (counted in `unit`), keeping its extension.
+///
+/// The stem is cut on a character boundary so multibyte characters are never split.
+/// If the extension alone does not fit, the whole name is truncated instead.
+#[must_use]
+pub fn truncate_name(name: &str, max_len: usize, unit: NameLengthUnit) -> String {
+    if unit.measure(name) <= max_len {
+        return name.to_string();
+    }
+    let (stem, ext) = match name.rfind('.') {
+        Some(pos) if pos > 0 && unit.measure(&name[pos..]) < max_len => name.split_at(pos),
+        _ => (name, ""),
+    };
+    let cut = unit.prefix_end(stem, max_len - unit.measure(ext));
+    format!("{}{}", stem[..cut].trim_end(), ext)
+}
+
+/// Apply rename rules sequentially to file base names
+#[must_use]
+pub fn apply_rules_seq(
+    files: &[PathBuf],
+    rules: &[RenameRule],
+    options: &RenameOptions,
+) -> Vec<PathBuf> {
+    apply_rules_traced(files, rules, options)
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect()
+}
+
+/// [`apply_rules_seq`], also returning for each file the ids of the rules that fired on it (in
+/// the order they fired). A rule fires when its replacement changes the name.
+#[must_use]
+pub fn apply_rules_traced(
+    files: &[PathBuf],
+    rules: &[RenameRule],
+    options: &RenameOptions,
+) -> Vec<(PathBuf, Vec<Uuid>)> {
+    // Precompile regexes once per rule
+    let compiled: Vec<Option<regex::Regex>> = rules
+        .iter()
+        .map(|r| {
+            let mut builder = regex::RegexBuilder::new(&r.find);
+            if !r.case_sensitive {
+                builder.case_insensitive(true);
+            }
+            builder.build().ok()
+        })
+        .collect();
+
+    files
+        .iter()
+        .map(|path| {
+            let original = path
+                .file_name()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+
+            let mut cur = original.clone();
+            let mut fired = Vec::new();
+            if options.rules_enabled {
+                for (i, rule) in rules.iter().enumerate() {
+                    // Skip disabled rules
+                    if !rule.enabled {
+                        continue;
+                    }
+
+                    // Check if rule only applies when name is too long
+                    if rule.only_when_name_too_long && !options.is_too_long(&cur) {
+                        continue;
+                    }
+
+                    if let Some(re) = &compiled[i] {
+                        let replaced = re.replace_all(&cur, &rule.replace).to_string();
+                        if replaced != cur {
+                            cur = replaced;
+                            fired.push(rule.id);
+                        }
+                    }
+                }
+
+                if options.hyphenate {
+                    // Hyphenate the base name, preserving extension
+                    if let Some(dot_pos) = cur.rfind('.') {
+                        let base = &cur[..dot_pos];
+                        let ext = &cur[dot_pos..];
+                        cur = format!("{}{}", hyphenate_name(base), ext);
+                    } else {
+                        cur = hyphenate_name(&cur);
+                    }
+                }
+            }
+
+            // Final safety step so nothing leaves the pipeline over the limit
+            if options.auto_truncate {
+                cur = truncate_name(&cur, options.max_name_length, options.length_unit);
+            }
+
+            let renamed = if cur == original {
+                path.clone()
+            } else if let Some(parent) = path.parent() {
+                parent.join(cur)
+            } else {
+                PathBuf::from(cur)
+            };
+            (renamed, fired)
+        })
+        .collect()
+}
+
+/// What the rename rules do to one file
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenamePreview {
+    pub original: PathBuf,
+    /// `original` with the rules applied
+    pub renamed: PathBuf,
+    /// The rules changed the file name
+    pub changed: bool,
+    /// The renamed name is still longer than the max name length
+    pub too_long: bool,
+    /// Another file is renamed to the same path
+    pub collides: bool,
+    /// Ids of the rules that changed the name, in the order they fired
+    pub applied_rules: Vec<Uuid>,
+}
+
+/// Rename `files` with [`apply_rules_seq`] and describe the outcome for each, index for index
+#[must_use]
+pub fn preview(
+    files: &[PathBuf],
+    rules: &[RenameRule],
+    options: &RenameOptions,
+) -> Vec<RenamePreview> {
+    let renamed = apply_rules_traced(files, rules, options);
+    let mut targets: HashMap<&Path, usize> = HashMap::new();
+    for (path, _) in &renamed {
+        *targets.entry(path.as_path()).or_default() += 1;
+    }
+    files
+        .iter()
+        .zip(&renamed)
+        .map(|(original, (renamed, applied_rules))| {
+            let name = renamed
+                .file_name()
+                .map(|s| s.to_string_lossy())
+                .unwrap_or_default();
+            RenamePreview {
+                original: original.clone(),
+                renamed: renamed.clone(),
+                changed: original != renamed,
+                too_long: options.is_too_long(&name),
+                collides: targets.get(renamed.as_path()).is_some_and(|&n| n > 1),
+                applied_rules: applied_rules.clone(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_preserves_extension() {
+        let name = "a-very-long-product-name-that-goes-on.jpg";
+        let out = truncate_name(name, 20, NameLengthUnit::Bytes);
+        assert_eq!(out, "a-very-long-prod.jpg");
+        assert!(out.len() <= 20);
+    }
+
+    #[test]
+    fn truncate_does_not_split_multibyte_chars() {
+        // Each 'é' is two bytes; a 6-byte budget for the stem lands mid-character
+        let name = "ééééé.png";
+        let out = truncate_name(name, 9, NameLengthUnit::Bytes);
+        assert_eq!(out, "éé.png");
+        assert!(out.len() <= 9);
+    }
+
+    #[test]
+    fn auto_truncate_runs_after_rules() {
+        let files = vec![PathBuf::from("dir/SomeVeryLongFileName.jpg")];
+        let options = RenameOptions {
+            auto_truncate: true,
+            ..RenameOptions::new(12)
+        };
+        let out = apply_rules_seq(&files, &[], &options);
+        assert_eq!(out, vec![PathBuf::from("dir/SomeVery.jpg")]);
+    }
+
+    #[test]
+    fn accented_name_is_measured_in_the_chosen_unit() {
+        // 40 characters, but each 'é' takes two bytes, making 50
+        let name = format!("caf{}-{}.jpg", "é".repeat(10), "x".repeat(22));
+        assert_eq!(name.chars().count(), 40);
+        assert_eq!(name.len(), 50);
+        let bytes = RenameOptions {
+            auto_truncate: true,
+            ..RenameOptions::new(45)
+        };
+        let chars = RenameOptions {
+            length_unit: NameLengthUnit::Chars,
+            ..bytes
+        };
+        assert!(bytes.is_too_long(&name));
+        assert!(!chars.is_too_long(&name));
+
+        let files = vec![PathBuf::from(format!("dir/{name}"))];
+        let truncated = apply_rules_seq(&files, &[], &bytes)[0].clone();
+        let truncated = truncated.file_name().unwrap().to_str().unwrap();
+        assert!(truncated.len() <= 45 && truncated.ends_with(".jpg"));
+        assert_eq!(apply_rules_seq(&files, &[], &chars), files);
+
+        // Under chars the budget is characters, so the cut keeps whole accented letters
+        assert_eq!(
+            truncate_name(&name, 10, NameLengthUnit::Chars),
+            "cafééé.jpg"
+        );
+        assert_eq!(truncate_name(&name, 10, NameLengthUnit::Bytes), "café.jpg");
+    }
+
+    #[test]
+    fn only_when_too_long_rules_follow_the_unit() {
+        let files = vec![PathBuf::from("dir/ééééé-pack.png")];
+        let rules = [RenameRule {
+            find: "-pack".to_owned(),
+            replace: String::new(),
+            ..RenameRule::default()
+        }];
+        // 14 characters but 19 bytes
+        let bytes = RenameOptions::new(16);
+        let chars = RenameOptions {
+            length_unit: NameLengthUnit::Chars,
+            ..bytes
+        };
+        assert_eq!(
+            apply_rules_seq(&files, &rules, &bytes),
+            vec![PathBuf::from("dir/ééééé.png")]
+        );
+        assert_eq!(apply_rules_seq(&files, &rules, &chars), files);
+    }
+
+    #[test]
+    fn preview_reports_no_op_rules_as_unchanged() {
+        let files = vec![PathBuf::from("dir/photo.jpg")];
+        let rules = [
+            RenameRule {
+                find: "zzz".to_owned(),
+                replace: "y".to_owned(),
+                only_when_name_too_long: false,
+                ..RenameRule::default()
+            },
+            // Matches, but replaces the text with itself
+            RenameRule {
+                find: "photo".to_owned(),
+                replace: "photo".to_owned(),
+                only_when_name_too_long: false,
+                ..RenameRule::default()
+            },
+        ];
+        assert_eq!(
+            preview(&files, &rules, &RenameOptions::new(50)),
+            vec![RenamePreview {
+                original: files[0].clone(),
+                renamed: files[0].clone(),
+                changed: false,
+                too_long: false,
+                collides: false,
+                applied_rules: Vec::new(),
+            }]
+        );
+    }
+
+    #[test]
+    fn preview_flags_files_renamed_to_the_same_path() {
+        let files = vec![
+            PathBuf::from("dir/a-1.jpg"),
+            PathBuf::from("dir/a-2.jpg"),
+            PathBuf::from("other/a-3.jpg"),
+            PathBuf::from("dir/b.jpg"),
+        ];
+        let rules = [RenameRule {
+            find: r"-\d".to_owned(),
+            replace: String::new(),
+            only_when_name_too_long: false,
+            ..RenameRule::default()
+        }];
+        let previews = preview(&files, &rules, &RenameOptions::new(50));
+        let renamed: Vec<_> = previews.iter().map(|p| p.renamed.clone()).collect();
+        assert_eq!(
+            renamed,
+            vec![
+                PathBuf::from("dir/a.jpg"),
+                PathBuf::from("dir/a.jpg"),
+                PathBuf::from("other/a.jpg"),
+                PathBuf::from("dir/b.jpg"),
+            ]
+        );
+        let changed: Vec<_> = previews.iter().map(|p| p.changed).collect();
+        assert_eq!(changed, [true, true, true, false]);
+        // Same name in another directory is not a collision
+        let collides: Vec<_> = previews.iter().map(|p| p.collides).collect();
+        assert_eq!(collides, [true, true, false, false]);
+    }
+
+    #[test]
+    fn preview_judges_length_after_the_rules() {
+        let files = vec![
+            PathBuf::from("dir/short.jpg"),
+            PathBuf::from("dir/long-name-pack.jpg"),
+            PathBuf::from("dir/still-far-too-long.jpg"),
+        ];
+        let rules = [RenameRule {
+            find: "-pack".to_owned(),
+            replace: String::new(),
+            only_when_name_too_long: true,
+            ..RenameRule::default()
+        }];
+        let options = RenameOptions::new(14);
+        let previews = preview(&files, &rules, &options);
+        let too_long: Vec<_> = previews.iter().map(|p| p.too_long).collect();
+        assert_eq!(too_long, [false, false, true]);
+        assert_eq!(previews[1].renamed, PathBuf::from("dir/long-name.jpg"));
+
+        // With the rules off the original names are judged as they are
+        let disabled = RenameOptions {
+            rules_enabled: false,
+            ..options
+        };
+        let previews = preview(&files, &rules, &disabled);
+        assert!(previews.iter().all(|p| !p.changed));
+        let too_long: Vec<_> = previews.iter().map(|p| p.too_long).collect();
+        assert_eq!(too_long, [false, true, true]);
+    }
+
+    #[test]
+    fn traced_rename_records_only_rules_that_fired() {
+        let files = vec![
+            PathBuf::from("dir/IMG_draft_0001.jpg"),
+            PathBuf::from("dir/final.jpg"),
+        ];
+        let rule = |find: &str, replace: &str| RenameRule {
+            find: find.to_owned(),
+            replace: replace.to_owned(),
+            only_when_name_too_long: false,
+            ..RenameRule::default()
+        };
+        let rules = [
+            rule("IMG_", ""),
+            // Matches nothing
+            rule("zzz", "y"),
+            // Matches, but leaves the name as it was
+            rule("draft", "draft"),
+            RenameRule {
+                enabled: false,
+                ..rule("draft_", "")
+            },
+            rule("_0+", "-"),
+        ];
+        let traced = apply_rules_traced(&files, &rules, &RenameOptions::new(50));
+        assert_eq!(
+            traced,
+            vec![
+                (
+                    PathBuf::from("dir/draft-1.jpg"),
+                    vec![rules[0].id, rules[4].id]
+                ),
+                (PathBuf::from("dir/final.jpg"), Vec::new()),
+            ]
+        );
+        assert_eq!(
+            preview(&files, &rules, &RenameOptions::new(50))[0].applied_rules,
+            vec![rules[0].id, rules[4].id]
+        );
+    }
+}
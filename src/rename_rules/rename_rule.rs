@@ -48,39 +48,51 @@ impl RenameRule {
 
     /// Parse from file text (v2 format, also accepts legacy v1 format)
     ///
+    /// The on-disk format is line based:
+    ///
+    /// 1. The find pattern (a regex; may be empty)
+    /// 2. The replacement (may be empty)
+    /// 3. Zero or more flag lines, matched case-insensitively and ignoring surrounding
+    ///    whitespace: `disabled`, `case-sensitive`, `only-when-too-long`. Blank lines are
+    ///    skipped. The legacy v1 flags `case-insensitive`, `case insensitive`, `always`, and
+    ///    `when len > N` are still accepted.
+    ///
+    /// A flag that is absent leaves the rule enabled, case-insensitive, and always applied.
+    ///
     /// # Errors
     ///
-    /// Returns an error if the text format is invalid.
+    /// Returns an error if the find or replace line is missing, or a flag line is not recognized.
     pub fn from_file_text(text: &str) -> eyre::Result<Self> {
         let mut lines = text.lines();
-        let find = lines.next().unwrap_or("").to_string();
-        let replace = lines.next().unwrap_or("").to_string();
+        let find = lines
+            .next()
+            .ok_or_else(|| eyre::eyre!("Rule file is empty, expected a find line"))?
+            .to_string();
+        let replace = lines
+            .next()
+            .ok_or_else(|| eyre::eyre!("Rule file is missing the replace line"))?
+            .to_string();
 
         let mut enabled = true;
         let mut case_sensitive = false;
         let mut only_when_name_too_long = false;
 
-        for line in lines {
+        for (i, line) in lines.enumerate() {
             let l = line.trim().to_ascii_lowercase();
-            if l.is_empty() {
-                continue;
-            }
-            // v2 format
-            if l == "disabled" {
-                enabled = false;
-            } else if l == "case-sensitive" {
-                case_sensitive = true;
-            } else if l == "only-when-too-long" {
-                only_when_name_too_long = true;
-            }
-            // Legacy v1 format compatibility
-            else if l == "case-insensitive" || l == "case insensitive" {
-                case_sensitive = false; // already default
-            } else if l == "always" {
-                only_when_name_too_long = false; // already default
-            } else if l.starts_with("when ") || l.starts_with("len") {
+            match l.as_str() {
+                "" => {}
+                // v2 format
+                "disabled" => enabled = false,
+                "case-sensitive" => case_sensitive = true,
+                "only-when-too-long" => only_when_name_too_long = true,
+                // Legacy v1 format compatibility
+                "case-insensitive" | "case insensitive" => case_sensitive = false,
+                "always" => only_when_name_too_long = false,
                 // Legacy "when len > N" - treat as only_when_name_too_long
-                only_when_name_too_long = true;
+                _ if l.starts_with("when ") || l.starts_with("len") => {
+                    only_when_name_too_long = true;
+                }
+                _ => eyre::bail!("Unknown rule flag on line {}: {:?}", i + 3, line.trim()),
             }
         }
 
@@ -94,6 +106,16 @@ impl RenameRule {
         })
     }
 
+    /// Returns the compile error of the find pattern, if it is not a valid regex
+    #[must_use]
+    pub fn regex_error(&self) -> Option<String> {
+        let mut builder = regex::RegexBuilder::new(&self.find);
+        if !self.case_sensitive {
+            builder.case_insensitive(true);
+        }
+        builder.build().err().map(|e| e.to_string())
+    }
+
     /// Apply rule to a file name. Returns `Some(new_name)` if applied and changed, otherwise None.
     #[must_use]
     pub fn apply(&self, name: &str, max_name_length: usize) -> Option<String> {
@@ -155,3 +177,77 @@ impl FromStr for RenameRule {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn file_text_roundtrips_and_rejects_malformed() -> eyre::Result<()> {
+        let rule = RenameRule {
+            find: "pack".to_string(),
+            replace: String::new(),
+            enabled: false,
+            case_sensitive: true,
+            only_when_name_too_long: true,
+            ..Default::default()
+        };
+        let parsed = RenameRule::from_file_text(&rule.to_file_text())?;
+        assert_eq!(
+            RenameRule {
+                id: rule.id,
+                ..parsed
+            },
+            rule
+        );
+
+        let legacy = RenameRule::from_file_text("foo\r\nbar\r\n\r\nWhen len > 50\r\n")?;
+        assert!(legacy.only_when_name_too_long);
+
+        assert!(RenameRule::from_file_text("").is_err());
+        assert!(RenameRule::from_file_text("only find").is_err());
+        assert!(RenameRule::from_file_text("foo\nbar\nsometimes\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn fuzz_file_text_never_panics() {
+        // Every prefix of a valid file, cut on char boundaries
+        let valid = "pa\u{e9}ck\n\u{1f600}\ndisabled\ncase-sensitive\nonly-when-too-long\n";
+        for (i, _) in valid.char_indices() {
+            let _ = RenameRule::from_file_text(&valid[..i]);
+        }
+
+        // Random text stitched together from flags, separators, and noise
+        let pieces = [
+            "\n",
+            "\r\n",
+            " ",
+            "disabled",
+            "case-sensitive",
+            "only-when-too-long",
+            "when len",
+            "always",
+            "\u{0}",
+            "\u{feff}",
+            "\"",
+            "\u{e9}",
+        ];
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        for _ in 0..1000 {
+            let mut text = String::new();
+            for _ in 0..seed % 12 {
+                seed ^= seed << 13;
+                seed ^= seed >> 7;
+                seed ^= seed << 17;
+                let index = usize::try_from(seed % pieces.len() as u64).unwrap_or_default();
+                text.push_str(pieces[index]);
+            }
+            if let Ok(rule) = RenameRule::from_file_text(&text) {
+                assert!(text.lines().count() >= 2, "parsed a rule from {text:?}");
+                assert!(!rule.find.contains('\n') && !rule.replace.contains('\n'));
+            }
+            seed = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        }
+    }
+}
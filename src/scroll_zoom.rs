@@ -0,0 +1,203 @@
+//! How the scroll wheel zooms image previews: its sensitivity and whether it is inverted, for
+//! trackpads that scroll the other way or devices that report much larger deltas
+
+use crate::app_home::APP_HOME;
+use crate::app_home::AppHome;
+use crate::atomic_write::write_atomic;
+use std::fs;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use tracing::warn;
+
+const FILE_NAME: &str = "scroll_zoom.txt";
+
+/// Range accepted for [`ScrollZoom::sensitivity`]
+pub const SCROLL_ZOOM_SENSITIVITY_RANGE: std::ops::RangeInclusive<f32> = 0.1..=10.0;
+
+/// Zoom change per scrolled point at sensitivity 1
+const BASE_RATE: f32 = 0.001;
+
+/// Scroll zoom preferences
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScrollZoom {
+    /// Multiplier on the default zoom speed
+    pub sensitivity: f32,
+    /// Scrolling up zooms out instead of in
+    pub invert: bool,
+}
+
+impl Default for ScrollZoom {
+    fn default() -> Self {
+        Self {
+            sensitivity: 1.0,
+            invert: false,
+        }
+    }
+}
+
+impl ScrollZoom {
+    /// Factor to multiply the zoom by for a vertical scroll of `scroll` points. Always positive,
+    /// and scrolling the same distance each way cancels out.
+    #[must_use]
+    pub fn zoom_factor(&self, scroll: f32) -> f32 {
+        let direction = if self.invert { -1.0 } else { 1.0 };
+        (scroll * BASE_RATE * self.sensitivity * direction).exp()
+    }
+
+    fn clamped(self) -> Self {
+        Self {
+            sensitivity: self.sensitivity.clamp(
+                *SCROLL_ZOOM_SENSITIVITY_RANGE.start(),
+                *SCROLL_ZOOM_SENSITIVITY_RANGE.end(),
+            ),
+            ..self
+        }
+    }
+}
+
+/// Load the persisted preferences, falling back to the defaults when none have been saved or
+/// the file is not understood. The file holds the sensitivity, followed by `inverted` when the
+/// direction is flipped.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read.
+pub fn load_scroll_zoom(home: &AppHome) -> eyre::Result<ScrollZoom> {
+    let path = home.file_path(FILE_NAME);
+    if !path.exists() {
+        return Ok(ScrollZoom::default());
+    }
+    let s = fs::read_to_string(&path)?;
+    let mut words = s.split_whitespace();
+    let sensitivity = words.next().and_then(|w| w.parse::<f32>().ok());
+    let invert = words.next();
+    match (sensitivity, invert) {
+        (Some(sensitivity), None | Some("inverted")) if sensitivity > 0.0 => Ok(ScrollZoom {
+            sensitivity,
+            invert: invert.is_some(),
+        }
+        .clamped()),
+        _ => {
+            warn!(
+                "Invalid {} contents: '{}', using default",
+                path.display(),
+                s.trim()
+            );
+            Ok(ScrollZoom::default())
+        }
+    }
+}
+
+/// Persist the preferences (`None` restores the defaults), clamping the sensitivity to
+/// [`SCROLL_ZOOM_SENSITIVITY_RANGE`]. When `home` is the default app home the in-memory
+/// [`SCROLL_ZOOM`] is updated too.
+///
+/// # Errors
+///
+/// Returns an error if the setting cannot be written or removed.
+///
+/// # Panics
+///
+/// Panics if the [`SCROLL_ZOOM`] mutex is poisoned.
+pub fn set_scroll_zoom(home: &AppHome, scroll_zoom: Option<ScrollZoom>) -> eyre::Result<()> {
+    let path = home.file_path(FILE_NAME);
+    let effective = if let Some(scroll_zoom) = scroll_zoom {
+        let scroll_zoom = scroll_zoom.clamped();
+        home.ensure_dir()?;
+        let mut contents = scroll_zoom.sensitivity.to_string();
+        if scroll_zoom.invert {
+            contents.push_str(" inverted");
+        }
+        write_atomic(&path, contents)?;
+        scroll_zoom
+    } else {
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        ScrollZoom::default()
+    };
+    if home.is_default() {
+        *SCROLL_ZOOM.lock().unwrap() = effective;
+    }
+    Ok(())
+}
+
+/// The current preferences, loaded from the default app home on first use
+pub static SCROLL_ZOOM: LazyLock<Mutex<ScrollZoom>> = LazyLock::new(|| {
+    Mutex::new(load_scroll_zoom(&APP_HOME).unwrap_or_else(|e| {
+        warn!(
+            "Failed to load scroll zoom preferences, using default: {}",
+            e
+        );
+        ScrollZoom::default()
+    }))
+});
+
+/// Convenience accessor for [`SCROLL_ZOOM`]
+///
+/// # Panics
+///
+/// Panics if the [`SCROLL_ZOOM`] mutex is poisoned.
+#[must_use]
+pub fn scroll_zoom() -> ScrollZoom {
+    *SCROLL_ZOOM.lock().unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn round_trips_and_clamps() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+        assert_eq!(load_scroll_zoom(&home)?, ScrollZoom::default());
+
+        let inverted = ScrollZoom {
+            sensitivity: 2.5,
+            invert: true,
+        };
+        set_scroll_zoom(&home, Some(inverted))?;
+        assert_eq!(load_scroll_zoom(&home)?, inverted);
+
+        set_scroll_zoom(
+            &home,
+            Some(ScrollZoom {
+                sensitivity: 100.0,
+                invert: false,
+            }),
+        )?;
+        assert!((load_scroll_zoom(&home)?.sensitivity - 10.0).abs() < f32::EPSILON);
+
+        set_scroll_zoom(&home, None)?;
+        assert_eq!(load_scroll_zoom(&home)?, ScrollZoom::default());
+        Ok(())
+    }
+
+    #[test]
+    fn zoom_factor_direction_and_symmetry() {
+        let normal = ScrollZoom::default();
+        let inverted = ScrollZoom {
+            invert: true,
+            ..normal
+        };
+        assert!(normal.zoom_factor(50.0) > 1.0);
+        assert!(inverted.zoom_factor(50.0) < 1.0);
+        assert!((normal.zoom_factor(50.0) * normal.zoom_factor(-50.0) - 1.0).abs() < 1e-6);
+        // Huge deltas never flip the image by producing a negative factor
+        assert!(normal.zoom_factor(-100_000.0) > 0.0);
+    }
+
+    #[test]
+    fn unwritable_home_loads_defaults_and_rejects_saves() -> eyre::Result<()> {
+        // A regular file where the directory should be makes every write fail
+        let td = tempdir()?;
+        let blocker = td.path().join("blocker");
+        std::fs::write(&blocker, "")?;
+        let home = AppHome(blocker.join("home"));
+        assert_eq!(load_scroll_zoom(&home)?, ScrollZoom::default());
+        assert!(set_scroll_zoom(&home, Some(ScrollZoom::default())).is_err());
+        Ok(())
+    }
+}
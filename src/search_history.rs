@@ -0,0 +1,104 @@
+//! Recently searched product queries, most recent first, so they can be recalled instead of
+//! retyped
+
+use crate::app_home::AppHome;
+use crate::atomic_write::write_atomic;
+use std::fs;
+
+const FILE_NAME: &str = "search_history.txt";
+
+/// How many queries are remembered
+pub const MAX_SEARCH_HISTORY: usize = 20;
+
+/// Move `query` to the front of `history`, dropping the oldest entries beyond `cap`
+pub fn push_recent(history: &mut Vec<String>, query: &str, cap: usize) {
+    history.retain(|q| q != query);
+    history.insert(0, query.to_owned());
+    history.truncate(cap);
+}
+
+/// The remembered queries, most recent first
+///
+/// # Errors
+///
+/// Returns an error if the history file exists but cannot be read.
+pub fn list_queries(home: &AppHome) -> eyre::Result<Vec<String>> {
+    let path = home.file_path(FILE_NAME);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(fs::read_to_string(&path)?
+        .lines()
+        .map(str::trim)
+        .filter(|q| !q.is_empty())
+        .map(str::to_owned)
+        .collect())
+}
+
+/// Remember `query` as the most recent search, returning the updated history. Blank queries
+/// are ignored.
+///
+/// # Errors
+///
+/// Returns an error if the history cannot be read or written.
+pub fn add_query(home: &AppHome, query: &str) -> eyre::Result<Vec<String>> {
+    let mut history = list_queries(home)?;
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(history);
+    }
+    push_recent(&mut history, query, MAX_SEARCH_HISTORY);
+    home.ensure_dir()?;
+    write_atomic(&home.file_path(FILE_NAME), history.join("\n"))?;
+    Ok(history)
+}
+
+/// Forget all remembered queries
+///
+/// # Errors
+///
+/// Returns an error if the history file cannot be removed.
+pub fn clear_queries(home: &AppHome) -> eyre::Result<()> {
+    let path = home.file_path(FILE_NAME);
+    if path.exists() {
+        fs::remove_file(&path)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn history_keeps_the_most_recent_unique_queries() {
+        let mut history = Vec::new();
+        for query in ["a", "b", "c", "d"] {
+            push_recent(&mut history, query, 3);
+        }
+        assert_eq!(history, ["d", "c", "b"]);
+        // Searching again moves a query to the front instead of duplicating it
+        push_recent(&mut history, "b", 3);
+        assert_eq!(history, ["b", "d", "c"]);
+    }
+
+    #[test]
+    fn history_is_persisted_and_cleared() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+        assert!(list_queries(&home)?.is_empty());
+        for i in 0..=MAX_SEARCH_HISTORY {
+            add_query(&home, &format!("query {i}"))?;
+        }
+        add_query(&home, "  ")?;
+        let history = list_queries(&home)?;
+        assert_eq!(history.len(), MAX_SEARCH_HISTORY);
+        assert_eq!(history[0], format!("query {MAX_SEARCH_HISTORY}"));
+        assert_eq!(history.last().map(String::as_str), Some("query 1"));
+
+        clear_queries(&home)?;
+        assert!(list_queries(&home)?.is_empty());
+        Ok(())
+    }
+}
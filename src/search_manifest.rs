@@ -0,0 +1,171 @@
+//! A catalog manifest pairing each image with the top product search result for its file name
+
+use crate::cli::command::search::search_command::SearchArgs;
+use crate::cli::command::search::search_result_ok::SearchResultOk;
+use crate::cli::command::search::suggest::suggest_search;
+use facet::Facet;
+use std::path::PathBuf;
+use std::time::Duration;
+use tracing::warn;
+
+/// One image and the product it was matched to. Match fields are `None` when the image was not
+/// searched or nothing was found.
+#[derive(Clone, Debug, Default, PartialEq, Facet)]
+pub struct ManifestEntry {
+    pub filename: String,
+    pub sku: Option<String>,
+    pub matched_name: Option<String>,
+    pub price: Option<String>,
+    pub url: Option<String>,
+}
+
+/// How [`build_manifest`] searches
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ManifestOptions {
+    /// Only search images whose file name contains a SKU
+    pub only_if_sku: bool,
+    /// Pause between consecutive searches, to go easy on the API
+    pub delay: Duration,
+}
+
+/// Search for each of `files` by its suggested query and record the top result.
+///
+/// `search` performs one search; [`SearchArgs::search`] in practice. Failed searches are logged
+/// and leave the entry unmatched rather than aborting the manifest.
+pub async fn build_manifest(
+    files: &[PathBuf],
+    options: ManifestOptions,
+    mut search: impl AsyncFnMut(SearchArgs) -> eyre::Result<SearchResultOk>,
+) -> Vec<ManifestEntry> {
+    let mut entries = Vec::with_capacity(files.len());
+    let mut searched_any = false;
+    for file in files {
+        let filename = file
+            .file_name()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let mut entry = ManifestEntry {
+            filename: filename.clone(),
+            ..ManifestEntry::default()
+        };
+
+        let args = suggest_search(&filename);
+        if options.only_if_sku && args.sku.is_empty() {
+            entries.push(entry);
+            continue;
+        }
+        if searched_any && !options.delay.is_zero() {
+            tokio::time::sleep(options.delay).await;
+        }
+        searched_any = true;
+
+        match search(args).await {
+            Ok(result) => {
+                if let Some(item) = result.results.as_deref().and_then(<[_]>::first) {
+                    entry.sku = item.sku.as_ref().map(|s| s.0.clone());
+                    entry.matched_name.clone_from(&item.name);
+                    entry.price = item.price.as_ref().map(|p| p.0.clone());
+                    entry.url = item.url.as_ref().map(|u| u.0.clone());
+                }
+            }
+            Err(e) => warn!("Search for {} failed: {}", filename, e),
+        }
+        entries.push(entry);
+    }
+    entries
+}
+
+/// The manifest as CSV with a header row, quoting fields where needed
+#[must_use]
+pub fn to_csv(entries: &[ManifestEntry]) -> String {
+    fn field(value: &str) -> String {
+        if value.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_owned()
+        }
+    }
+    let mut out = String::from("filename,sku,matched_name,price,url\n");
+    for entry in entries {
+        let row = [
+            Some(entry.filename.as_str()),
+            entry.sku.as_deref(),
+            entry.matched_name.as_deref(),
+            entry.price.as_deref(),
+            entry.url.as_deref(),
+        ]
+        .map(|value| field(value.unwrap_or_default()));
+        out.push_str(&row.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn manifest_records_the_top_result_per_image() -> eyre::Result<()> {
+        let files = [
+            PathBuf::from("in/123456-front.jpg"),
+            PathBuf::from("in/ForestGreen, Large.png"),
+            PathBuf::from("in/Unknown.jpg"),
+        ];
+        let mut queries = Vec::new();
+        let search = async |args: SearchArgs| {
+            queries.push((args.query.clone(), args.sku.clone()));
+            let raw = if args.sku == ["123456"] {
+                r#"{ "results": [
+                    { "sku": "123456", "name": "Widget", "price": "9.99", "url": "/widget" },
+                    { "sku": "654321", "name": "Other", "price": "1.00" }
+                ] }"#
+            } else if args
+                .query
+                .as_deref()
+                .is_some_and(|q| q.starts_with("Forest Green"))
+            {
+                r#"{ "results": [{ "name": "Green \"Large\", tee", "price": "20" }] }"#
+            } else {
+                r#"{ "results": [] }"#
+            };
+            Ok(facet_json::from_str::<SearchResultOk>(raw)?)
+        };
+
+        let entries = build_manifest(&files, ManifestOptions::default(), search).await;
+        assert_eq!(queries.len(), 3);
+        assert_eq!(
+            entries[0],
+            ManifestEntry {
+                filename: "123456-front.jpg".to_owned(),
+                sku: Some("123456".to_owned()),
+                matched_name: Some("Widget".to_owned()),
+                price: Some("9.99".to_owned()),
+                url: Some("/widget".to_owned()),
+            }
+        );
+        assert_eq!(entries[2].matched_name, None);
+        assert_eq!(
+            to_csv(&entries),
+            "filename,sku,matched_name,price,url\n\
+             123456-front.jpg,123456,Widget,9.99,/widget\n\
+             \"ForestGreen, Large.png\",,\"Green \"\"Large\"\", tee\",20,\n\
+             Unknown.jpg,,,,\n"
+        );
+
+        // Gated on SKUs, only the first image is searched but every image is listed
+        let mut searched = 0;
+        let options = ManifestOptions {
+            only_if_sku: true,
+            ..ManifestOptions::default()
+        };
+        let entries = build_manifest(&files, options, async |_| {
+            searched += 1;
+            Ok(facet_json::from_str::<SearchResultOk>("{}")?)
+        })
+        .await;
+        assert_eq!(searched, 1);
+        assert_eq!(entries.len(), 3);
+        Ok(())
+    }
+}
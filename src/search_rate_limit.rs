@@ -0,0 +1,152 @@
+//! How often product searches may go out to the API, so large auto-search batches don't get
+//! rate limited
+
+use crate::app_home::APP_HOME;
+use crate::app_home::AppHome;
+use crate::atomic_write::write_atomic;
+use std::fs;
+use std::sync::LazyLock;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
+use tokio::sync::Mutex;
+use tracing::debug;
+use tracing::warn;
+
+const FILE_NAME: &str = "search_rate_limit.txt";
+
+/// Requests per second used until another rate is saved
+pub const DEFAULT_SEARCH_RATE: f64 = 2.0;
+
+/// Range accepted by [`set_search_rate`], in requests per second
+pub const SEARCH_RATE_RANGE: std::ops::RangeInclusive<f64> = 0.1..=20.0;
+
+/// Load the persisted rate, falling back to [`DEFAULT_SEARCH_RATE`] when none has been saved or
+/// the saved value is not a positive number.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read.
+pub fn load_search_rate(home: &AppHome) -> eyre::Result<f64> {
+    let path = home.file_path(FILE_NAME);
+    if !path.exists() {
+        return Ok(DEFAULT_SEARCH_RATE);
+    }
+    let s = fs::read_to_string(&path)?;
+    match s.trim().parse::<f64>() {
+        Ok(v) if v > 0.0 => Ok(v.clamp(*SEARCH_RATE_RANGE.start(), *SEARCH_RATE_RANGE.end())),
+        _ => {
+            warn!(
+                "Invalid {} contents: '{}', using default",
+                path.display(),
+                s.trim()
+            );
+            Ok(DEFAULT_SEARCH_RATE)
+        }
+    }
+}
+
+/// Persist the rate in requests per second (`None` restores the default), clamped to
+/// [`SEARCH_RATE_RANGE`]. When `home` is the default app home the in-memory [`SEARCH_RATE`] is
+/// updated too.
+///
+/// # Errors
+///
+/// Returns an error if the setting cannot be written or removed.
+pub fn set_search_rate(home: &AppHome, rate: Option<f64>) -> eyre::Result<()> {
+    let path = home.file_path(FILE_NAME);
+    let effective = if let Some(rate) = rate {
+        let rate = rate.clamp(*SEARCH_RATE_RANGE.start(), *SEARCH_RATE_RANGE.end());
+        home.ensure_dir()?;
+        write_atomic(&path, rate.to_string())?;
+        rate
+    } else {
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        DEFAULT_SEARCH_RATE
+    };
+    if home.is_default() {
+        SEARCH_RATE.store(effective.to_bits(), Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+/// The current rate as `f64` bits, loaded from the default app home on first use
+pub static SEARCH_RATE: LazyLock<AtomicU64> = LazyLock::new(|| {
+    let rate = load_search_rate(&APP_HOME).unwrap_or_else(|e| {
+        warn!("Failed to load search rate limit, using default: {}", e);
+        DEFAULT_SEARCH_RATE
+    });
+    AtomicU64::new(rate.to_bits())
+});
+
+/// Convenience accessor for [`SEARCH_RATE`], in requests per second
+#[must_use]
+pub fn search_rate() -> f64 {
+    f64::from_bits(SEARCH_RATE.load(Ordering::SeqCst))
+}
+
+/// Spaces events at least a minimum interval apart
+#[derive(Debug, Default)]
+pub struct MinInterval {
+    last: Option<Instant>,
+}
+
+impl MinInterval {
+    /// Reserve the next slot at or after `now`, returning how long to wait for it
+    pub fn reserve(&mut self, now: Instant, interval: Duration) -> Duration {
+        let slot = self.last.map_or(now, |last| (last + interval).max(now));
+        self.last = Some(slot);
+        slot - now
+    }
+}
+
+static LAST_SEARCH: LazyLock<Mutex<MinInterval>> =
+    LazyLock::new(|| Mutex::new(MinInterval::default()));
+
+/// Wait until another search request may be sent under the current [`search_rate`]
+pub async fn wait_for_search_slot() {
+    let interval = Duration::from_secs_f64(1.0 / search_rate());
+    let wait = LAST_SEARCH.lock().await.reserve(Instant::now(), interval);
+    if !wait.is_zero() {
+        debug!(wait_ms = wait.as_millis(), "Rate limiting search request");
+        tokio::time::sleep(wait).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn defaults_until_saved_and_clamps() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+        assert!((load_search_rate(&home)? - DEFAULT_SEARCH_RATE).abs() < f64::EPSILON);
+
+        set_search_rate(&home, Some(5.0))?;
+        assert!((load_search_rate(&home)? - 5.0).abs() < f64::EPSILON);
+        set_search_rate(&home, Some(1000.0))?;
+        assert!((load_search_rate(&home)? - SEARCH_RATE_RANGE.end()).abs() < f64::EPSILON);
+
+        set_search_rate(&home, None)?;
+        assert!((load_search_rate(&home)? - DEFAULT_SEARCH_RATE).abs() < f64::EPSILON);
+        Ok(())
+    }
+
+    #[test]
+    fn reservations_are_spaced_by_the_interval() {
+        let mut limiter = MinInterval::default();
+        let start = Instant::now();
+        let second = Duration::from_secs(1);
+        assert_eq!(limiter.reserve(start, second), Duration::ZERO);
+        // Requests arriving together queue up one interval apart
+        assert_eq!(limiter.reserve(start, second), second);
+        assert_eq!(limiter.reserve(start, second), second * 2);
+        // Once the queue has drained, the next request goes out immediately
+        assert_eq!(limiter.reserve(start + second * 5, second), Duration::ZERO);
+    }
+}
@@ -1,64 +1,24 @@
-use crate::app_home::APP_HOME;
-use chrono::DateTime;
-use chrono::Duration;
-use chrono::Local;
-use chrono::Utc;
-use std::fs;
-use std::io::Write;
-use std::path::PathBuf;
+use std::env;
 use std::sync::LazyLock;
+use std::sync::RwLock;
+use tracing::warn;
 use uuid::Uuid;
 
+/// Identifies one run of the program to Searchspring analytics; never persisted.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SessionId(pub Uuid);
 
 impl SessionId {
-    const FILE_NAME: &'static str = "session_id.txt";
-    const TTL_HOURS: i64 = 1;
-
-    /// # Errors
+    /// Loads `$CM_SESSION_ID` if set, otherwise generates a new id for this process.
     ///
-    /// This function does not return any errors.
-    pub fn config_file_path() -> eyre::Result<PathBuf> {
-        Ok(APP_HOME.file_path(Self::FILE_NAME))
-    }
-
     /// # Errors
     ///
-    /// Returns an error if loading or parsing the session ID fails.
+    /// Returns an error if `$CM_SESSION_ID` is not a valid UUID.
     pub fn load() -> eyre::Result<SessionId> {
-        let path = Self::config_file_path()?;
-        if path.exists() {
-            let s = fs::read_to_string(&path)?;
-            let mut lines = s.lines();
-            if let Some(id_line) = lines.next()
-                && let Some(exp_line) = lines.next()
-            {
-                let id = Uuid::parse_str(id_line.trim())?;
-                // New format: `expires {timestamp}` -> extract timestamp by splitting from the right
-                let exp_str = exp_line.rsplit(' ').next().unwrap_or(exp_line.trim());
-                let exp_dt = DateTime::parse_from_rfc3339(exp_str).map_err(|e| eyre::eyre!(e))?;
-
-                if exp_dt.with_timezone(&Utc) > Utc::now() {
-                    return Ok(SessionId(id));
-                }
-            }
+        if let Ok(envv) = env::var("CM_SESSION_ID") {
+            return Ok(SessionId(Uuid::parse_str(envv.trim())?));
         }
-
-        // missing or expired -> generate and persist
-        let id = Uuid::new_v4();
-        let expiry = Local::now() + Duration::hours(Self::TTL_HOURS);
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        let mut f = fs::OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(&path)?;
-        f.write_all(format!("{}\nexpires {}", id, expiry.to_rfc3339()).as_bytes())?;
-        f.flush()?;
-        Ok(SessionId(id))
+        Ok(SessionId(Uuid::new_v4()))
     }
 
     #[must_use]
@@ -67,8 +27,6 @@ impl SessionId {
     }
 }
 
-use tracing::warn;
-
 pub static SESSION_ID: LazyLock<SessionId> = LazyLock::new(|| match SessionId::load() {
     Ok(s) => s,
     Err(e) => {
@@ -79,3 +37,52 @@ pub static SESSION_ID: LazyLock<SessionId> = LazyLock::new(|| match SessionId::l
         SessionId(Uuid::new_v4())
     }
 });
+
+/// Session id from `--session-id`, taking precedence over [`SESSION_ID`]
+static SESSION_ID_OVERRIDE: RwLock<Option<SessionId>> = RwLock::new(None);
+
+/// Restores the previous session id override when dropped
+#[must_use = "the override is undone as soon as this is dropped"]
+pub struct SessionIdOverride(Option<SessionId>);
+
+impl Drop for SessionIdOverride {
+    fn drop(&mut self) {
+        *SESSION_ID_OVERRIDE.write().unwrap() = self.0.take();
+    }
+}
+
+/// Use `id` instead of this process's session id until the returned guard is dropped
+pub fn override_session_id(id: Uuid) -> SessionIdOverride {
+    let previous = SESSION_ID_OVERRIDE.write().unwrap().replace(SessionId(id));
+    SessionIdOverride(previous)
+}
+
+/// The session id in effect: the override when set, otherwise [`SESSION_ID`]
+#[must_use]
+pub fn current_session_id() -> SessionId {
+    SESSION_ID_OVERRIDE
+        .read()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| SESSION_ID.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The only test touching the override, so no other test sees it change
+    #[test]
+    fn override_lasts_until_its_guard_is_dropped() {
+        let outer = Uuid::new_v4();
+        let inner = Uuid::new_v4();
+        let outer_guard = override_session_id(outer);
+        {
+            let _inner_guard = override_session_id(inner);
+            assert_eq!(current_session_id(), SessionId(inner));
+        }
+        assert_eq!(current_session_id(), SessionId(outer));
+        drop(outer_guard);
+        assert_eq!(current_session_id(), SESSION_ID.clone());
+    }
+}
@@ -0,0 +1,114 @@
+//! The host searches are sent to, as a template filled in with the site id, for deployments
+//! that don't use the default Searchspring domain
+
+use crate::app_home::APP_HOME;
+use crate::app_home::AppHome;
+use crate::atomic_write::write_atomic;
+use std::fs;
+use std::sync::LazyLock;
+use std::sync::RwLock;
+use tracing::warn;
+
+const FILE_NAME: &str = "site_host.txt";
+
+/// Placeholder in a host template that is replaced with the site id
+pub const SITE_ID_PLACEHOLDER: &str = "{site_id}";
+
+/// Host template used until another is saved
+pub const DEFAULT_SITE_HOST: &str = "{site_id}.a.searchspring.io";
+
+/// Load the persisted host template, falling back to [`DEFAULT_SITE_HOST`] when none has been
+/// saved or the saved value is blank.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read.
+pub fn load_site_host(home: &AppHome) -> eyre::Result<String> {
+    let path = home.file_path(FILE_NAME);
+    if !path.exists() {
+        return Ok(DEFAULT_SITE_HOST.to_owned());
+    }
+    let s = fs::read_to_string(&path)?;
+    if s.trim().is_empty() {
+        warn!("Empty {}, using default", path.display());
+        return Ok(DEFAULT_SITE_HOST.to_owned());
+    }
+    Ok(s.trim().to_owned())
+}
+
+/// Persist the host template (`None` restores the default). When `home` is the default app
+/// home the in-memory [`SITE_HOST`] is updated too.
+///
+/// # Errors
+///
+/// Returns an error if the template is blank or contains a scheme or path, or if the setting
+/// cannot be written or removed.
+pub fn set_site_host(home: &AppHome, template: Option<&str>) -> eyre::Result<()> {
+    let path = home.file_path(FILE_NAME);
+    let effective = if let Some(template) = template {
+        let template = template.trim();
+        if template.is_empty() || template.contains(['/', ' ']) {
+            eyre::bail!(
+                "invalid host template '{template}': expected a host like {DEFAULT_SITE_HOST}"
+            );
+        }
+        home.ensure_dir()?;
+        write_atomic(&path, template)?;
+        template.to_owned()
+    } else {
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        DEFAULT_SITE_HOST.to_owned()
+    };
+    if home.is_default() {
+        *SITE_HOST.write().unwrap() = effective;
+    }
+    Ok(())
+}
+
+/// The current host template, loaded from the default app home on first use
+pub static SITE_HOST: LazyLock<RwLock<String>> = LazyLock::new(|| {
+    RwLock::new(load_site_host(&APP_HOME).unwrap_or_else(|e| {
+        warn!("Failed to load site host, using default: {}", e);
+        DEFAULT_SITE_HOST.to_owned()
+    }))
+});
+
+/// Convenience accessor for [`SITE_HOST`]
+#[must_use]
+pub fn site_host() -> String {
+    SITE_HOST.read().unwrap().clone()
+}
+
+/// The host for `site_id` under `template`
+#[must_use]
+pub fn host_for_site(template: &str, site_id: &str) -> String {
+    template.replace(SITE_ID_PLACEHOLDER, site_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn defaults_until_saved() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+        assert_eq!(load_site_host(&home)?, DEFAULT_SITE_HOST);
+
+        set_site_host(&home, Some("search.{site_id}.example.com"))?;
+        assert_eq!(load_site_host(&home)?, "search.{site_id}.example.com");
+        assert!(set_site_host(&home, Some("https://example.com/api")).is_err());
+        assert_eq!(load_site_host(&home)?, "search.{site_id}.example.com");
+
+        set_site_host(&home, None)?;
+        assert_eq!(load_site_host(&home)?, DEFAULT_SITE_HOST);
+        assert_eq!(
+            host_for_site(DEFAULT_SITE_HOST, "abc123"),
+            "abc123.a.searchspring.io"
+        );
+        Ok(())
+    }
+}
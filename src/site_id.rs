@@ -4,6 +4,7 @@ use std::fs;
 use std::io::Write;
 use std::path::PathBuf;
 use std::sync::LazyLock;
+use std::sync::RwLock;
 use tracing::warn;
 
 /// A strongly-typed wrapper around the site id string
@@ -97,3 +98,32 @@ pub static SITE_ID: LazyLock<SiteId> = LazyLock::new(|| {
         }
     }
 });
+
+/// Site id from `--site-id`, taking precedence over [`SITE_ID`]
+static SITE_ID_OVERRIDE: RwLock<Option<SiteId>> = RwLock::new(None);
+
+/// Restores the previous site id override when dropped
+#[must_use = "the override is undone as soon as this is dropped"]
+pub struct SiteIdOverride(Option<SiteId>);
+
+impl Drop for SiteIdOverride {
+    fn drop(&mut self) {
+        *SITE_ID_OVERRIDE.write().unwrap() = self.0.take();
+    }
+}
+
+/// Use `id` instead of the configured site id until the returned guard is dropped
+pub fn override_site_id(id: String) -> SiteIdOverride {
+    let previous = SITE_ID_OVERRIDE.write().unwrap().replace(SiteId(id));
+    SiteIdOverride(previous)
+}
+
+/// The site id in effect: the override when set, otherwise [`SITE_ID`]
+#[must_use]
+pub fn current_site_id() -> SiteId {
+    SITE_ID_OVERRIDE
+        .read()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| SITE_ID.clone())
+}
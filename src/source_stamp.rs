@@ -0,0 +1,147 @@
+//! Sidecars recording which source and settings produced an output, so a later run can tell an
+//! up-to-date output from a stale one and find outputs whose source has gone
+
+use crate::image_processing::DecodeErrorPolicy;
+use crate::image_processing::OutputConflictPolicy;
+use crate::image_processing::ProcessingSettings;
+use crate::inputs;
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Appended to an output's path to name its sidecar
+const STAMP_SUFFIX: &str = ".cmstamp";
+
+/// What an output was made from
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceStamp {
+    /// BLAKE3 of the source bytes and the settings that shape the output
+    pub stamp: String,
+    pub source: PathBuf,
+}
+
+/// Where the stamp of `output_path` is kept: the same path with `.cmstamp` appended
+#[must_use]
+pub fn stamp_sidecar_path(output_path: &Path) -> PathBuf {
+    let mut path = output_path.as_os_str().to_owned();
+    path.push(STAMP_SUFFIX);
+    PathBuf::from(path)
+}
+
+/// Stamp of `source` processed with `settings`. Settings that only change how a batch runs or
+/// where it writes, like dry runs, conflict handling, the output root and cancellation, are
+/// left out so they don't make outputs stale. Of the per-input descriptions only the one
+/// `source` gets counts.
+///
+/// # Errors
+///
+/// Returns an error if the source cannot be read.
+pub fn source_stamp(source: &Path, settings: &ProcessingSettings) -> eyre::Result<SourceStamp> {
+    let settings = ProcessingSettings {
+        dry_run: false,
+        on_conflict: OutputConflictPolicy::default(),
+        on_decode_error: DecodeErrorPolicy::default(),
+        output_root: None,
+        description: settings.description_for(source),
+        input_descriptions: Arc::default(),
+        cancel: None,
+        ..settings.clone()
+    };
+    let mut hasher = blake3::Hasher::new();
+    hasher.update(&fs::read(source)?);
+    hasher.update(format!("{settings:?}").as_bytes());
+    Ok(SourceStamp {
+        stamp: hasher.finalize().to_hex().to_string(),
+        source: source.to_path_buf(),
+    })
+}
+
+/// The stamp written next to `output_path`, if there is a readable one
+#[must_use]
+pub fn read_stamp(output_path: &Path) -> Option<SourceStamp> {
+    let text = fs::read_to_string(stamp_sidecar_path(output_path)).ok()?;
+    let (stamp, source) = text.trim_end_matches(['\r', '\n']).split_once("  ")?;
+    Some(SourceStamp {
+        stamp: stamp.to_owned(),
+        source: PathBuf::from(source),
+    })
+}
+
+/// Write `stamp` next to `output_path`
+///
+/// # Errors
+///
+/// Returns an error if the sidecar cannot be written.
+pub fn write_stamp(output_path: &Path, stamp: &SourceStamp) -> eyre::Result<()> {
+    fs::write(
+        stamp_sidecar_path(output_path),
+        format!("{}  {}\n", stamp.stamp, stamp.source.display()),
+    )?;
+    Ok(())
+}
+
+/// Every stamped output under `dirs`, with its stamp
+///
+/// # Errors
+///
+/// Returns an error if a directory cannot be read.
+pub fn stamped_outputs(dirs: &[PathBuf]) -> eyre::Result<Vec<(PathBuf, SourceStamp)>> {
+    let existing: Vec<PathBuf> = dirs.iter().filter(|d| d.is_dir()).cloned().collect();
+    Ok(inputs::list_files_in(&existing, None)?
+        .into_iter()
+        .filter_map(|sidecar| {
+            let output = PathBuf::from(sidecar.to_str()?.strip_suffix(STAMP_SUFFIX)?);
+            let stamp = read_stamp(&output)?;
+            Some((output, stamp))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn stamp_changes_with_the_source_and_output_settings_only() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let source = td.path().join("a.png");
+        fs::write(&source, b"one")?;
+        let settings = ProcessingSettings::default();
+        let stamp = source_stamp(&source, &settings)?;
+
+        let dry = ProcessingSettings {
+            dry_run: true,
+            ..settings.clone()
+        };
+        assert_eq!(source_stamp(&source, &dry)?, stamp);
+        let cropped = ProcessingSettings {
+            crop_to_content: true,
+            ..settings.clone()
+        };
+        assert_ne!(source_stamp(&source, &cropped)?, stamp);
+        // Only the description this source gets counts
+        let described = |input: &Path| ProcessingSettings {
+            input_descriptions: Arc::new([(input.to_path_buf(), "Widget".to_owned())].into()),
+            ..settings.clone()
+        };
+        assert_eq!(
+            source_stamp(&source, &described(&td.path().join("b.png")))?,
+            stamp
+        );
+        assert_ne!(source_stamp(&source, &described(&source))?, stamp);
+        fs::write(&source, b"two")?;
+        assert_ne!(source_stamp(&source, &settings)?, stamp);
+
+        let output = td.path().join("out").join("a.png");
+        fs::create_dir_all(output.parent().unwrap())?;
+        write_stamp(&output, &stamp)?;
+        assert_eq!(read_stamp(&output), Some(stamp.clone()));
+        assert_eq!(
+            stamped_outputs(&[td.path().join("out")])?,
+            vec![(output, stamp)]
+        );
+        Ok(())
+    }
+}
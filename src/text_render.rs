@@ -0,0 +1,125 @@
+//! Rasterizing single lines of text in the bundled Ubuntu font, for drawing onto outputs
+
+use ab_glyph::Font;
+use ab_glyph::FontRef;
+use ab_glyph::PxScale;
+use ab_glyph::ScaleFont;
+use ab_glyph::point;
+use eyre::Result;
+use eyre::eyre;
+
+/// Per-pixel glyph coverage (0 to 1) of one rendered line of text
+#[derive(Debug)]
+pub struct TextMask {
+    pub width: u32,
+    pub height: u32,
+    coverage: Vec<f32>,
+}
+
+impl TextMask {
+    /// Coverage at `(x, y)`, or 0 outside the mask
+    #[must_use]
+    pub fn at(&self, x: u32, y: u32) -> f32 {
+        if x < self.width && y < self.height {
+            self.coverage[(y * self.width + x) as usize]
+        } else {
+            0.0
+        }
+    }
+}
+
+fn font() -> Result<FontRef<'static>> {
+    FontRef::try_from_slice(epaint_default_fonts::UBUNTU_LIGHT)
+        .map_err(|e| eyre!("Failed to load bundled font: {}", e))
+}
+
+/// Width in pixels of `text` drawn at `px`
+///
+/// # Errors
+///
+/// Returns an error if the bundled font cannot be loaded.
+pub fn text_width(text: &str, px: f32) -> Result<f32> {
+    Ok(layout(&font()?, text, PxScale::from(px)).0)
+}
+
+/// Rasterize `text` at `px` into a mask sized to its advance width and line height
+///
+/// # Errors
+///
+/// Returns an error if the bundled font cannot be loaded.
+#[expect(clippy::cast_possible_truncation)]
+#[expect(clippy::cast_sign_loss)]
+pub fn render_text(text: &str, px: f32) -> Result<TextMask> {
+    let font = font()?;
+    let scale = PxScale::from(px);
+    let (text_width, glyphs) = layout(&font, text, scale);
+    let scaled = font.as_scaled(scale);
+    let (width, height) = (
+        text_width.ceil().max(1.0) as u32,
+        (scaled.ascent() - scaled.descent()).ceil().max(1.0) as u32,
+    );
+
+    let mut coverage = vec![0.0f32; (width * height) as usize];
+    for glyph in glyphs {
+        let Some(outlined) = font.outline_glyph(glyph) else {
+            continue;
+        };
+        let bounds = outlined.px_bounds();
+        outlined.draw(|gx, gy, c| {
+            let x = bounds.min.x as i64 + i64::from(gx);
+            let y = bounds.min.y as i64 + i64::from(gy);
+            if (0..i64::from(width)).contains(&x) && (0..i64::from(height)).contains(&y) {
+                let cell = &mut coverage[(y as u32 * width + x as u32) as usize];
+                *cell = (*cell + c).min(1.0);
+            }
+        });
+    }
+    Ok(TextMask {
+        width,
+        height,
+        coverage,
+    })
+}
+
+/// The longest prefix of `text` that fits in `max_width` pixels at `px`, with an ellipsis
+/// appended when anything was cut
+///
+/// # Errors
+///
+/// Returns an error if the bundled font cannot be loaded.
+#[expect(clippy::cast_precision_loss)]
+pub fn truncate_to_width(text: &str, px: f32, max_width: u32) -> Result<String> {
+    let font = font()?;
+    let scale = PxScale::from(px);
+    let max_width = max_width as f32;
+    if layout(&font, text, scale).0 <= max_width {
+        return Ok(text.to_owned());
+    }
+    let mut end = text.len();
+    while end > 0 {
+        end = text.floor_char_boundary(end - 1);
+        let candidate = format!("{}…", &text[..end]);
+        if layout(&font, &candidate, scale).0 <= max_width {
+            return Ok(candidate);
+        }
+    }
+    Ok(String::new())
+}
+
+/// Lay `text` out on one line, returning its advance width and positioned glyphs
+fn layout(font: &FontRef<'_>, text: &str, scale: PxScale) -> (f32, Vec<ab_glyph::Glyph>) {
+    let scaled = font.as_scaled(scale);
+    let mut caret = 0.0;
+    let mut previous = None;
+    let mut glyphs = Vec::new();
+    for c in text.chars() {
+        let id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            caret += scaled.kern(previous, id);
+        }
+        glyphs.push(id.with_scale_and_position(scale, point(caret, scaled.ascent())));
+        caret += scaled.h_advance(id);
+        previous = Some(id);
+    }
+    (caret, glyphs)
+}
@@ -0,0 +1,105 @@
+//! Memory budget, in megabytes, for thumbnails kept in the GUI image cache
+
+use crate::app_home::APP_HOME;
+use crate::app_home::AppHome;
+use crate::atomic_write::write_atomic;
+use std::fs;
+use std::sync::LazyLock;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use tracing::warn;
+
+const FILE_NAME: &str = "thumbnail_cache_mb.txt";
+
+/// Budget used when none has been saved
+pub const DEFAULT_THUMBNAIL_CACHE_MB: usize = 256;
+
+/// Load the persisted budget, falling back to [`DEFAULT_THUMBNAIL_CACHE_MB`] when none has been
+/// saved or the saved value is not a positive number.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read.
+pub fn load_thumbnail_cache_mb(home: &AppHome) -> eyre::Result<usize> {
+    let path = home.file_path(FILE_NAME);
+    if !path.exists() {
+        return Ok(DEFAULT_THUMBNAIL_CACHE_MB);
+    }
+    let s = fs::read_to_string(&path)?;
+    match s.trim().parse::<usize>() {
+        Ok(v) if v > 0 => Ok(v),
+        _ => {
+            warn!(
+                "Invalid {} contents: '{}', using default",
+                path.display(),
+                s.trim()
+            );
+            Ok(DEFAULT_THUMBNAIL_CACHE_MB)
+        }
+    }
+}
+
+/// Persist the budget (`None` restores the default; zero is raised to 1). When `home` is the
+/// default app home the in-memory [`THUMBNAIL_CACHE_MB`] is updated too.
+///
+/// # Errors
+///
+/// Returns an error if the setting cannot be written or removed.
+pub fn set_thumbnail_cache_mb(home: &AppHome, mb: Option<usize>) -> eyre::Result<()> {
+    let path = home.file_path(FILE_NAME);
+    let effective = if let Some(mb) = mb {
+        let mb = mb.max(1);
+        home.ensure_dir()?;
+        write_atomic(&path, mb.to_string())?;
+        mb
+    } else {
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        DEFAULT_THUMBNAIL_CACHE_MB
+    };
+    if home.is_default() {
+        THUMBNAIL_CACHE_MB.store(effective, Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+/// The current budget, loaded from the default app home on first use
+pub static THUMBNAIL_CACHE_MB: LazyLock<AtomicUsize> = LazyLock::new(|| {
+    AtomicUsize::new(load_thumbnail_cache_mb(&APP_HOME).unwrap_or_else(|e| {
+        warn!("Failed to load thumbnail cache size, using default: {}", e);
+        DEFAULT_THUMBNAIL_CACHE_MB
+    }))
+});
+
+/// Convenience accessor for [`THUMBNAIL_CACHE_MB`]
+#[must_use]
+pub fn thumbnail_cache_mb() -> usize {
+    THUMBNAIL_CACHE_MB.load(Ordering::SeqCst)
+}
+
+/// [`thumbnail_cache_mb`] in bytes
+#[must_use]
+pub fn thumbnail_cache_bytes() -> usize {
+    thumbnail_cache_mb().saturating_mul(1024 * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn defaults_until_saved() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+        assert_eq!(load_thumbnail_cache_mb(&home)?, DEFAULT_THUMBNAIL_CACHE_MB);
+
+        set_thumbnail_cache_mb(&home, Some(64))?;
+        assert_eq!(load_thumbnail_cache_mb(&home)?, 64);
+
+        set_thumbnail_cache_mb(&home, None)?;
+        assert_eq!(load_thumbnail_cache_mb(&home)?, DEFAULT_THUMBNAIL_CACHE_MB);
+        Ok(())
+    }
+}
@@ -0,0 +1,114 @@
+//! How many input images are read and thumbnailed at once when filling the image cache
+
+use crate::app_home::APP_HOME;
+use crate::app_home::AppHome;
+use crate::atomic_write::write_atomic;
+use std::fs;
+use std::sync::LazyLock;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use tracing::warn;
+
+const FILE_NAME: &str = "thumbnail_concurrency.txt";
+
+/// Upper bound accepted by [`set_thumbnail_concurrency`]
+pub const MAX_THUMBNAIL_CONCURRENCY: usize = 256;
+
+/// Number of CPUs, or 4 if it cannot be determined
+#[must_use]
+pub fn default_thumbnail_concurrency() -> usize {
+    std::thread::available_parallelism().map_or(4, std::num::NonZero::get)
+}
+
+/// Load the persisted limit, falling back to [`default_thumbnail_concurrency`] when none has
+/// been saved or the saved value is not a positive number.
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read.
+pub fn load_thumbnail_concurrency(home: &AppHome) -> eyre::Result<usize> {
+    let path = home.file_path(FILE_NAME);
+    if !path.exists() {
+        return Ok(default_thumbnail_concurrency());
+    }
+    let s = fs::read_to_string(&path)?;
+    match s.trim().parse::<usize>() {
+        Ok(v) if v > 0 => Ok(v.min(MAX_THUMBNAIL_CONCURRENCY)),
+        _ => {
+            warn!(
+                "Invalid {} contents: '{}', using default",
+                path.display(),
+                s.trim()
+            );
+            Ok(default_thumbnail_concurrency())
+        }
+    }
+}
+
+/// Persist the limit (`None` restores the default), clamped to
+/// `1..=`[`MAX_THUMBNAIL_CONCURRENCY`]. When `home` is the default app home the in-memory
+/// [`THUMBNAIL_CONCURRENCY`] is updated too.
+///
+/// # Errors
+///
+/// Returns an error if the setting cannot be written or removed.
+pub fn set_thumbnail_concurrency(home: &AppHome, limit: Option<usize>) -> eyre::Result<()> {
+    let path = home.file_path(FILE_NAME);
+    let effective = if let Some(limit) = limit {
+        let limit = limit.clamp(1, MAX_THUMBNAIL_CONCURRENCY);
+        home.ensure_dir()?;
+        write_atomic(&path, limit.to_string())?;
+        limit
+    } else {
+        if path.exists() {
+            fs::remove_file(&path)?;
+        }
+        default_thumbnail_concurrency()
+    };
+    if home.is_default() {
+        THUMBNAIL_CONCURRENCY.store(effective, Ordering::SeqCst);
+    }
+    Ok(())
+}
+
+/// The current limit, loaded from the default app home on first use
+pub static THUMBNAIL_CONCURRENCY: LazyLock<AtomicUsize> = LazyLock::new(|| {
+    AtomicUsize::new(load_thumbnail_concurrency(&APP_HOME).unwrap_or_else(|e| {
+        warn!("Failed to load thumbnail concurrency, using default: {}", e);
+        default_thumbnail_concurrency()
+    }))
+});
+
+/// Convenience accessor for [`THUMBNAIL_CONCURRENCY`]
+#[must_use]
+pub fn thumbnail_concurrency() -> usize {
+    THUMBNAIL_CONCURRENCY.load(Ordering::SeqCst)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn defaults_until_saved_and_clamps() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+        assert_eq!(
+            load_thumbnail_concurrency(&home)?,
+            default_thumbnail_concurrency()
+        );
+
+        set_thumbnail_concurrency(&home, Some(2))?;
+        assert_eq!(load_thumbnail_concurrency(&home)?, 2);
+        set_thumbnail_concurrency(&home, Some(0))?;
+        assert_eq!(load_thumbnail_concurrency(&home)?, 1);
+
+        set_thumbnail_concurrency(&home, None)?;
+        assert_eq!(
+            load_thumbnail_concurrency(&home)?,
+            default_thumbnail_concurrency()
+        );
+        Ok(())
+    }
+}
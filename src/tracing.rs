@@ -3,15 +3,26 @@ use chrono::Local;
 use egui_tracing::tracing::collector::EventCollector;
 use eyre::Result;
 use std::fs::File;
+use std::io::Write;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::LazyLock;
 use std::sync::Mutex;
+use std::sync::OnceLock;
+use tracing::Level;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
+use tracing_subscriber::Registry;
 use tracing_subscriber::filter::Directive;
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::fmt::format::DefaultFields;
+use tracing_subscriber::fmt::format::Format;
+use tracing_subscriber::fmt::format::Json;
 use tracing_subscriber::fmt::writer::BoxMakeWriter;
+use tracing_subscriber::layer::Layered;
 use tracing_subscriber::prelude::*;
+use tracing_subscriber::reload;
 use tracing_subscriber::util::SubscriberInitExt;
 
 // Global collector used by the GUI logs widget. Cloneable cheap handle.
@@ -28,12 +39,334 @@ pub fn default_json_log_path() -> PathBuf {
     let timestamp = Local::now().format("%Y-%m-%d_%Hh%Mm%Ss").to_string();
     PathBuf::from(format!("cm_log_{timestamp}.jsonl"))
 }
+
+/// Generate a default filename for exported in-memory logs
+#[must_use]
+pub fn default_log_export_path() -> PathBuf {
+    let timestamp = Local::now().format("%Y-%m-%d_%Hh%Mm%Ss").to_string();
+    PathBuf::from(format!("cm_logs_export_{timestamp}.txt"))
+}
+
+/// Format the collected events at or above `min_level` severity, one line per event.
+#[must_use]
+pub fn formatted_events(min_level: Level) -> Vec<(Level, String)> {
+    EVENT_COLLECTOR
+        .events()
+        .iter()
+        .filter(|event| event.level <= min_level)
+        .map(|event| {
+            let fields = event
+                .fields
+                .iter()
+                .map(|(k, v)| {
+                    let k: &str = k.as_ref();
+                    format!("{k}={v}")
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            let line = format!(
+                "{} {} {}: {}",
+                event.time.format("%Y-%m-%d %H:%M:%S%.3f"),
+                event.level,
+                event.target,
+                fields
+            );
+            (event.level, line)
+        })
+        .collect()
+}
+
+/// Write every event held by the in-memory collector to `path`, one line per event.
+/// Returns the number of events written.
+/// # Errors
+/// Returns an error if the file cannot be created or written.
+pub fn export_events(path: &Path) -> Result<usize> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::io::BufWriter::new(File::create(path)?);
+    let events = formatted_events(Level::TRACE);
+    for (_, line) in &events {
+        writeln!(file, "{line}")?;
+    }
+    file.flush()?;
+    Ok(events.len())
+}
+
+/// Size-based rotation settings for the JSON log file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LogRotation {
+    /// Rotate once the active file reaches this many bytes
+    pub max_bytes: u64,
+    /// Number of rotated files (`<name>.1` .. `<name>.N`) to keep
+    pub max_files: usize,
+}
+
+/// A log file that rolls over to numbered siblings once it grows past a size limit
+#[derive(Debug)]
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    written: u64,
+    rotation: LogRotation,
+}
+
+impl RotatingFile {
+    fn open(path: PathBuf, rotation: LogRotation) -> std::io::Result<Self> {
+        let file = File::create(&path)?;
+        Ok(Self {
+            path,
+            file,
+            written: 0,
+            rotation,
+        })
+    }
+
+    fn rotated_path(&self, n: usize) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(format!(".{n}"));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> std::io::Result<()> {
+        self.file.flush()?;
+        if self.rotation.max_files == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            let _ = std::fs::remove_file(self.rotated_path(self.rotation.max_files));
+            for n in (1..self.rotation.max_files).rev() {
+                let from = self.rotated_path(n);
+                if from.exists() {
+                    std::fs::rename(&from, self.rotated_path(n + 1))?;
+                }
+            }
+            std::fs::rename(&self.path, self.rotated_path(1))?;
+            self.file = File::create(&self.path)?;
+        }
+        self.written = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.written > 0 && self.written + buf.len() as u64 > self.rotation.max_bytes {
+            self.rotate()?;
+        }
+        let n = self.file.write(buf)?;
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Cheap cloneable handle handed out to the JSON layer for each event
+#[derive(Clone, Debug)]
+struct RotatingWriter(Arc<Mutex<RotatingFile>>);
+
+impl Write for RotatingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .map_err(|_| std::io::Error::other("log file lock poisoned"))?
+            .write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0
+            .lock()
+            .map_err(|_| std::io::Error::other("log file lock poisoned"))?
+            .flush()
+    }
+}
+
+/// The JSON file layer; the writer is boxed so plain and rotating files share one type
+type JsonLayer = tracing_subscriber::fmt::Layer<Registry, DefaultFields, Format<Json>, BoxMakeWriter>;
+
+/// Build a JSON layer writing to `path`, creating parent directories as needed
+fn json_layer(path: &Path, rotation: Option<LogRotation>) -> Result<JsonLayer> {
+    if let Some(parent) = path.parent()
+        && !parent.as_os_str().is_empty()
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let json_writer = if let Some(rotation) = rotation {
+        let writer = RotatingWriter(Arc::new(Mutex::new(RotatingFile::open(
+            path.to_path_buf(),
+            rotation,
+        )?)));
+        BoxMakeWriter::new(move || writer.clone())
+    } else {
+        let file = File::create(path)?;
+        let file = Arc::new(Mutex::new(file));
+        BoxMakeWriter::new(move || {
+            file.lock()
+                .expect("failed to lock json log file")
+                .try_clone()
+                .expect("failed to clone json log file handle")
+        })
+    };
+
+    Ok(tracing_subscriber::fmt::layer()
+        .event_format(tracing_subscriber::fmt::format().json())
+        .with_file(true)
+        .with_target(false)
+        .with_line_number(true)
+        .with_writer(json_writer))
+}
+
+/// Turns JSON file output on and off in a running subscriber
+#[derive(Clone)]
+pub struct JsonLogSwitch {
+    handle: reload::Handle<Option<JsonLayer>, Registry>,
+    rotation: Option<LogRotation>,
+    path: Arc<Mutex<Option<PathBuf>>>,
+}
+
+impl std::fmt::Debug for JsonLogSwitch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JsonLogSwitch")
+            .field("rotation", &self.rotation)
+            .field("path", &self.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl JsonLogSwitch {
+    /// A switch starting in the off state, with the layer it controls. The layer must sit
+    /// directly on the `Registry`.
+    fn new(rotation: Option<LogRotation>) -> (reload::Layer<Option<JsonLayer>, Registry>, Self) {
+        let (layer, handle) = reload::Layer::new(None);
+        let switch = Self {
+            handle,
+            rotation,
+            path: Arc::new(Mutex::new(None)),
+        };
+        (layer, switch)
+    }
+
+    /// The file JSON logs are currently written to, if enabled
+    /// # Panics
+    /// Panics if the path mutex is poisoned.
+    #[must_use]
+    pub fn path(&self) -> Option<PathBuf> {
+        self.path.lock().unwrap().clone()
+    }
+
+    /// Start writing JSON logs to `path` (truncating it), replacing any previous file
+    /// # Errors
+    /// Returns an error if the file cannot be created or the subscriber is gone.
+    /// # Panics
+    /// Panics if the path mutex is poisoned.
+    pub fn enable(&self, path: &Path) -> Result<()> {
+        let layer = json_layer(path, self.rotation)?;
+        self.handle
+            .reload(Some(layer))
+            .map_err(|e| eyre::eyre!("Failed to enable JSON logging: {e}"))?;
+        *self.path.lock().unwrap() = Some(path.to_path_buf());
+        info!(json_log_path = ?path, "JSON log output enabled");
+        Ok(())
+    }
+
+    /// Stop writing JSON logs
+    /// # Errors
+    /// Returns an error if the subscriber is gone.
+    /// # Panics
+    /// Panics if the path mutex is poisoned.
+    pub fn disable(&self) -> Result<()> {
+        self.handle
+            .reload(None)
+            .map_err(|e| eyre::eyre!("Failed to disable JSON logging: {e}"))?;
+        if let Some(path) = self.path.lock().unwrap().take() {
+            info!(json_log_path = ?path, "JSON log output disabled");
+        }
+        Ok(())
+    }
+}
+
+static JSON_LOG_SWITCH: OnceLock<JsonLogSwitch> = OnceLock::new();
+
+/// The switch for the global subscriber's JSON output, if tracing was initialized
+pub fn json_log_switch() -> Option<JsonLogSwitch> {
+    JSON_LOG_SWITCH.get().cloned()
+}
+
+/// What the level filter sits on: the JSON layer over the `Registry`
+type FilteredBase = Layered<reload::Layer<Option<JsonLayer>, Registry>, Registry>;
+
+/// Changes which events are captured by a running subscriber
+#[derive(Clone)]
+pub struct LogLevelSwitch {
+    handle: reload::Handle<EnvFilter, FilteredBase>,
+}
+
+impl std::fmt::Debug for LogLevelSwitch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LogLevelSwitch")
+            .field("level", &self.level())
+            .finish()
+    }
+}
+
+impl LogLevelSwitch {
+    /// A switch starting at `filter`, with the layer it controls. The layer must sit directly
+    /// on the JSON layer.
+    fn new(filter: EnvFilter) -> (reload::Layer<EnvFilter, FilteredBase>, Self) {
+        let (layer, handle) = reload::Layer::new(filter);
+        (layer, Self { handle })
+    }
+
+    /// The most verbose level currently captured
+    #[must_use]
+    pub fn level(&self) -> Option<LevelFilter> {
+        self.handle
+            .with_current(EnvFilter::max_level_hint)
+            .ok()
+            .flatten()
+    }
+
+    /// Capture events at `level` and above from now on, replacing any `RUST_LOG` directives
+    /// # Errors
+    /// Returns an error if the subscriber is gone.
+    pub fn set_level(&self, level: Level) -> Result<()> {
+        let filter = EnvFilter::builder()
+            .with_default_directive(LevelFilter::from_level(level).into())
+            .parse_lossy("");
+        self.handle
+            .reload(filter)
+            .map_err(|e| eyre::eyre!("Failed to change log level: {e}"))?;
+        info!(%level, "Log level changed");
+        Ok(())
+    }
+}
+
+static LOG_LEVEL_SWITCH: OnceLock<LogLevelSwitch> = OnceLock::new();
+
+/// The switch for the global subscriber's level filter, if tracing was initialized
+pub fn log_level_switch() -> Option<LogLevelSwitch> {
+    LOG_LEVEL_SWITCH.get().cloned()
+}
+
 /// Initialize tracing with the given level and JSON log behaviour
+///
+/// When `rotation` is set the JSON log file is rolled over to numbered siblings
+/// instead of growing without bound. JSON output can be toggled later through
+/// [`json_log_switch`].
 /// # Errors
 /// Returns an error if tracing cannot be initialized.
 /// # Panics
 /// Panics if the JSON log file cannot be locked or cloned.
-pub fn init_tracing(level: impl Into<Directive>, json_behaviour: &JsonLogBehaviour) -> Result<()> {
+pub fn init_tracing(
+    level: impl Into<Directive>,
+    json_behaviour: &JsonLogBehaviour,
+    rotation: Option<LogRotation>,
+) -> Result<()> {
     let default_directive: Directive = level.into();
     let env_filter = EnvFilter::builder()
         .with_default_directive(default_directive.clone())
@@ -46,56 +379,112 @@ pub fn init_tracing(level: impl Into<Directive>, json_behaviour: &JsonLogBehavio
         .pretty()
         .without_time();
 
+    let (env_filter, level_switch) = LogLevelSwitch::new(env_filter);
+    let (json_layer, json_switch) = JsonLogSwitch::new(rotation);
     if let Some(json_log_path) = json_behaviour.get_path() {
-        // Create parent directories if they don't exist
-        if let Some(parent) = json_log_path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-
-        let file = File::create(json_log_path.as_ref())?;
-        let file = Arc::new(Mutex::new(file));
-        let json_writer = {
-            let file = Arc::clone(&file);
-            BoxMakeWriter::new(move || {
-                file.lock()
-                    .expect("failed to lock json log file")
-                    .try_clone()
-                    .expect("failed to clone json log file handle")
-            })
-        };
-
-        let json_format = tracing_subscriber::fmt::format().json();
-        let json_layer = tracing_subscriber::fmt::layer()
-            .event_format(json_format)
-            .with_file(true)
-            .with_target(false)
-            .with_line_number(true)
-            .with_writer(json_writer);
-
-        if let Err(error) = tracing_subscriber::registry()
-            .with(env_filter)
-            .with(stderr_layer)
-            .with(json_layer)
-            .with(EVENT_COLLECTOR.clone())
-            .try_init()
-        {
-            tracing::warn!("Failed to initialize tracing subscriber: {}", error);
-            // fallback: still print to stderr so CI/test runs see the message
-            eprintln!("Failed to initialize tracing subscriber: {error}");
-            return Ok(());
-        }
+        json_switch.enable(&json_log_path)?;
+    }
 
-        info!(?json_log_path, "JSON log output initialized");
-    } else if let Err(error) = tracing_subscriber::registry()
+    if let Err(error) = tracing_subscriber::registry()
+        .with(json_layer)
         .with(env_filter)
         .with(stderr_layer)
         .with(EVENT_COLLECTOR.clone())
         .try_init()
     {
         tracing::warn!("Failed to initialize tracing subscriber: {}", error);
+        // fallback: still print to stderr so CI/test runs see the message
         eprintln!("Failed to initialize tracing subscriber: {error}");
         return Ok(());
     }
+    let _ = JSON_LOG_SWITCH.set(json_switch);
+    let _ = LOG_LEVEL_SWITCH.set(level_switch);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn rotating_file_rolls_over() -> Result<()> {
+        let td = tempdir()?;
+        let path = td.path().join("log.jsonl");
+        let mut file = RotatingFile::open(
+            path.clone(),
+            LogRotation {
+                max_bytes: 10,
+                max_files: 2,
+            },
+        )?;
+        for _ in 0..4 {
+            file.write_all(b"12345678\n")?;
+        }
+        file.flush()?;
+
+        assert_eq!(std::fs::read_to_string(&path)?, "12345678\n");
+        assert!(td.path().join("log.jsonl.1").exists());
+        assert!(td.path().join("log.jsonl.2").exists());
+        assert!(!td.path().join("log.jsonl.3").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn json_log_switch_toggles_file_output() -> Result<()> {
+        let td = tempdir()?;
+        let path = td.path().join("logs").join("toggle.jsonl");
+        let (layer, switch) = JsonLogSwitch::new(None);
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || -> Result<()> {
+            info!("before enabling");
+            switch.enable(&path)?;
+            info!("while enabled");
+            switch.disable()?;
+            info!("after disabling");
+            Ok(())
+        })?;
+
+        let text = std::fs::read_to_string(&path)?;
+        assert!(text.lines().count() >= 1);
+        assert!(text.lines().all(|l| l.starts_with('{') && l.ends_with('}')));
+        assert!(text.contains("\"while enabled\""));
+        assert!(!text.contains("before enabling"));
+        assert!(!text.contains("after disabling"));
+        assert_eq!(switch.path(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn log_level_switch_changes_captured_events() -> Result<()> {
+        let (json_layer, _json_switch) = JsonLogSwitch::new(None);
+        let (filter_layer, level_switch) =
+            LogLevelSwitch::new(EnvFilter::new(Level::INFO.as_str()));
+        let collector = EventCollector::default();
+        let subscriber = tracing_subscriber::registry()
+            .with(json_layer)
+            .with(filter_layer)
+            .with(collector.clone());
+
+        tracing::subscriber::with_default(subscriber, || -> Result<()> {
+            tracing::debug!("hidden at info");
+            assert_eq!(level_switch.level(), Some(LevelFilter::INFO));
+            level_switch.set_level(Level::DEBUG)?;
+            tracing::debug!("shown at debug");
+            level_switch.set_level(Level::WARN)?;
+            info!("hidden at warn");
+            Ok(())
+        })?;
+
+        let messages: Vec<String> = collector
+            .events()
+            .iter()
+            .filter_map(|e| e.fields.get("message").cloned())
+            .collect();
+        assert!(messages.iter().any(|m| m == "shown at debug"));
+        assert!(!messages.iter().any(|m| m.starts_with("hidden")));
+        Ok(())
+    }
+}
@@ -1,20 +1,21 @@
 use crate::app_home::APP_HOME;
-use chrono::DateTime;
-use chrono::Duration;
-use chrono::Local;
-use chrono::Utc;
+use crate::app_home::AppHome;
+use crate::atomic_write::write_atomic;
+use std::env;
 use std::fs;
-use std::io::Write;
 use std::path::PathBuf;
 use std::sync::LazyLock;
+use std::sync::RwLock;
+use tracing::warn;
 use uuid::Uuid;
 
+/// Identifies this installation to Searchspring analytics. Generated once and kept, so every
+/// launch counts as the same user.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct UserId(pub Uuid);
 
 impl UserId {
     const FILE_NAME: &'static str = "user_id.txt";
-    const TTL_DAYS: i64 = 4;
 
     /// # Errors
     ///
@@ -23,41 +24,49 @@ impl UserId {
         Ok(APP_HOME.file_path(Self::FILE_NAME))
     }
 
+    /// Loads `$CM_USER_ID` if set, otherwise the id persisted in the default app home.
+    ///
     /// # Errors
     ///
     /// Returns an error if loading or parsing the user ID fails.
     pub fn load() -> eyre::Result<UserId> {
-        let path = Self::config_file_path()?;
+        if let Ok(envv) = env::var("CM_USER_ID") {
+            return Ok(UserId(Uuid::parse_str(envv.trim())?));
+        }
+        Self::load_from(&APP_HOME)
+    }
+
+    /// Loads the id persisted under `home`, generating and persisting one on first use.
+    ///
+    /// Files from older versions hold an expiry line after the id; it is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or written.
+    pub fn load_from(home: &AppHome) -> eyre::Result<UserId> {
+        let path = home.file_path(Self::FILE_NAME);
         if path.exists() {
             let s = fs::read_to_string(&path)?;
-            let mut lines = s.lines();
-            if let Some(id_line) = lines.next()
-                && let Some(exp_line) = lines.next()
-            {
-                let id = Uuid::parse_str(id_line.trim())?;
-                // New format: `expires {timestamp}` -> extract timestamp by splitting from the right
-                let exp_str = exp_line.rsplit(' ').next().unwrap_or(exp_line.trim());
-                let exp_dt = DateTime::parse_from_rfc3339(exp_str).map_err(|e| eyre::eyre!(e))?;
-
-                if exp_dt.with_timezone(&Utc) > Utc::now() {
-                    return Ok(UserId(id));
-                }
+            if let Some(Ok(id)) = s.lines().next().map(|line| Uuid::parse_str(line.trim())) {
+                return Ok(UserId(id));
             }
+            warn!(
+                "Invalid {} contents, generating a new user id",
+                path.display()
+            );
         }
+        Self::reset_in(home)
+    }
 
-        // missing or expired -> generate and persist
+    /// Generate a new id and persist it under `home`, replacing any existing one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn reset_in(home: &AppHome) -> eyre::Result<UserId> {
         let id = Uuid::new_v4();
-        let expiry = Local::now() + Duration::days(Self::TTL_DAYS);
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        let mut f = fs::OpenOptions::new()
-            .create(true)
-            .write(true)
-            .truncate(true)
-            .open(&path)?;
-        f.write_all(format!("{}\nexpires {}", id, expiry.to_rfc3339()).as_bytes())?;
-        f.flush()?;
+        home.ensure_dir()?;
+        write_atomic(&home.file_path(Self::FILE_NAME), id.to_string())?;
         Ok(UserId(id))
     }
 
@@ -67,8 +76,6 @@ impl UserId {
     }
 }
 
-use tracing::warn;
-
 pub static USER_ID: LazyLock<UserId> = LazyLock::new(|| match UserId::load() {
     Ok(u) => u,
     Err(e) => {
@@ -79,3 +86,69 @@ pub static USER_ID: LazyLock<UserId> = LazyLock::new(|| match UserId::load() {
         UserId(Uuid::new_v4())
     }
 });
+
+/// User id from `--user-id`, taking precedence over [`USER_ID`]
+static USER_ID_OVERRIDE: RwLock<Option<UserId>> = RwLock::new(None);
+
+/// Restores the previous user id override when dropped
+#[must_use = "the override is undone as soon as this is dropped"]
+pub struct UserIdOverride(Option<UserId>);
+
+impl Drop for UserIdOverride {
+    fn drop(&mut self) {
+        *USER_ID_OVERRIDE.write().unwrap() = self.0.take();
+    }
+}
+
+/// Use `id` instead of the persisted user id until the returned guard is dropped
+pub fn override_user_id(id: Uuid) -> UserIdOverride {
+    let previous = USER_ID_OVERRIDE.write().unwrap().replace(UserId(id));
+    UserIdOverride(previous)
+}
+
+/// The user id in effect: the override when set, otherwise [`USER_ID`]
+#[must_use]
+pub fn current_user_id() -> UserId {
+    USER_ID_OVERRIDE
+        .read()
+        .unwrap()
+        .clone()
+        .unwrap_or_else(|| USER_ID.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn persisted_user_id_is_stable_until_reset() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+        let first = UserId::load_from(&home)?;
+        assert_eq!(UserId::load_from(&home)?, first);
+
+        // Ids written with an expiry by older versions are kept
+        let legacy = Uuid::new_v4();
+        fs::write(
+            home.file_path(UserId::FILE_NAME),
+            format!("{legacy}\nexpires 2000-01-01T00:00:00+00:00"),
+        )?;
+        assert_eq!(UserId::load_from(&home)?, UserId(legacy));
+
+        let reset = UserId::reset_in(&home)?;
+        assert_ne!(reset, UserId(legacy));
+        assert_eq!(UserId::load_from(&home)?, reset);
+        Ok(())
+    }
+
+    // The only test touching the override, so no other test sees it change
+    #[test]
+    fn override_lasts_until_its_guard_is_dropped() {
+        let id = Uuid::new_v4();
+        let guard = override_user_id(id);
+        assert_eq!(current_user_id(), UserId(id));
+        drop(guard);
+        assert_eq!(current_user_id(), USER_ID.clone());
+    }
+}
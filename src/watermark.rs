@@ -0,0 +1,211 @@
+//! Semi-transparent text or image watermarks composited onto processed outputs
+
+use crate::image_processing::open_image;
+use crate::text_render;
+use crate::text_render::text_width;
+use eyre::Result;
+use eyre::eyre;
+use image::Rgba;
+use image::RgbaImage;
+use std::path::PathBuf;
+
+/// Size a text watermark is laid out at before being scaled to its target width
+const MEASURE_PX: f32 = 100.0;
+
+/// What a watermark draws
+#[derive(Clone, Debug, PartialEq)]
+pub enum WatermarkContent {
+    /// Text rendered in the bundled Ubuntu font, white with a dark shadow
+    Text(String),
+    /// An image file, drawn with its own transparency
+    Image(PathBuf),
+}
+
+/// Where on the image a watermark is placed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WatermarkPosition {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    #[default]
+    BottomRight,
+    Center,
+}
+
+impl WatermarkPosition {
+    pub const ALL: [Self; 5] = [
+        Self::TopLeft,
+        Self::TopRight,
+        Self::BottomLeft,
+        Self::BottomRight,
+        Self::Center,
+    ];
+
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::TopLeft => "Top left",
+            Self::TopRight => "Top right",
+            Self::BottomLeft => "Bottom left",
+            Self::BottomRight => "Bottom right",
+            Self::Center => "Center",
+        }
+    }
+
+    /// Top-left corner for a `mark` sized overlay on an `image` sized canvas, `margin` pixels
+    /// in from the edges it hugs
+    fn origin(self, image: (u32, u32), mark: (u32, u32), margin: i64) -> (i64, i64) {
+        let far = |total: u32, size: u32| i64::from(total) - i64::from(size) - margin;
+        let middle = |total: u32, size: u32| (i64::from(total) - i64::from(size)) / 2;
+        match self {
+            Self::TopLeft => (margin, margin),
+            Self::TopRight => (far(image.0, mark.0), margin),
+            Self::BottomLeft => (margin, far(image.1, mark.1)),
+            Self::BottomRight => (far(image.0, mark.0), far(image.1, mark.1)),
+            Self::Center => (middle(image.0, mark.0), middle(image.1, mark.1)),
+        }
+    }
+}
+
+/// A watermark and how it is laid over the output
+#[derive(Clone, Debug, PartialEq)]
+pub struct WatermarkSettings {
+    pub content: WatermarkContent,
+    pub position: WatermarkPosition,
+    /// 0 (invisible) to 1 (fully opaque)
+    pub opacity: f32,
+    /// Width of the watermark as a fraction of the image width
+    pub scale: f32,
+}
+
+/// Composite `settings` onto `img` in place. Empty text draws nothing.
+///
+/// # Errors
+///
+/// Returns an error if an image watermark cannot be loaded.
+#[expect(clippy::cast_possible_truncation)]
+#[expect(clippy::cast_precision_loss)]
+#[expect(clippy::cast_sign_loss)]
+pub fn apply_watermark(img: &mut RgbaImage, settings: &WatermarkSettings) -> Result<()> {
+    let (width, height) = img.dimensions();
+    let target_width = (width as f32 * settings.scale.clamp(0.01, 1.0))
+        .round()
+        .max(1.0) as u32;
+    let mut mark = match &settings.content {
+        WatermarkContent::Text(text) if text.trim().is_empty() => return Ok(()),
+        WatermarkContent::Text(text) => render_text(text.trim(), target_width)?,
+        WatermarkContent::Image(path) => {
+            let source = open_image(path)
+                .map_err(|e| eyre!("Failed to load watermark {}: {}", path.display(), e))?;
+            let target_height = (u64::from(source.height()) * u64::from(target_width)
+                / u64::from(source.width().max(1)))
+            .max(1);
+            source
+                .resize_exact(
+                    target_width,
+                    u32::try_from(target_height).unwrap_or(u32::MAX),
+                    image::imageops::FilterType::Triangle,
+                )
+                .to_rgba8()
+        }
+    };
+
+    let opacity = settings.opacity.clamp(0.0, 1.0);
+    for pixel in mark.pixels_mut() {
+        pixel[3] = (f32::from(pixel[3]) * opacity).round() as u8;
+    }
+    let margin = i64::from(width.min(height) / 50);
+    let (x, y) = settings
+        .position
+        .origin((width, height), mark.dimensions(), margin);
+    image::imageops::overlay(img, &mark, x, y);
+    Ok(())
+}
+
+/// Render `text` as white glyphs with a dark drop shadow, sized to roughly `target_width`
+#[expect(clippy::cast_possible_truncation)]
+#[expect(clippy::cast_precision_loss)]
+#[expect(clippy::cast_sign_loss)]
+fn render_text(text: &str, target_width: u32) -> Result<RgbaImage> {
+    let measured = text_width(text, MEASURE_PX)?;
+    if measured <= 0.0 {
+        return Ok(RgbaImage::new(1, 1));
+    }
+    let px = MEASURE_PX * target_width as f32 / measured;
+    let mask = text_render::render_text(text, px)?;
+
+    // White text over a black copy offset down and to the right, so it reads on any background
+    let shadow = ((px / 24.0).round() as u32).max(1);
+    Ok(RgbaImage::from_fn(
+        mask.width + shadow,
+        mask.height + shadow,
+        |x, y| {
+            let text = mask.at(x, y);
+            let under = if x >= shadow && y >= shadow {
+                mask.at(x - shadow, y - shadow) * (1.0 - text)
+            } else {
+                0.0
+            };
+            let alpha = text + under;
+            if alpha <= 0.0 {
+                return Rgba([0, 0, 0, 0]);
+            }
+            let value = (255.0 * text / alpha).round() as u8;
+            Rgba([value, value, value, (alpha * 255.0).round() as u8])
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn watermarks_land_in_their_corner() -> eyre::Result<()> {
+        let white = Rgba([255, 255, 255, 255]);
+        let blank = RgbaImage::from_pixel(200, 100, white);
+        let changed = |img: &RgbaImage, xs: std::ops::Range<u32>, ys: std::ops::Range<u32>| {
+            ys.flat_map(|y| xs.clone().map(move |x| (x, y)))
+                .any(|(x, y)| *img.get_pixel(x, y) != white)
+        };
+
+        let mut text = blank.clone();
+        apply_watermark(
+            &mut text,
+            &WatermarkSettings {
+                content: WatermarkContent::Text("PROOF".to_owned()),
+                position: WatermarkPosition::BottomRight,
+                opacity: 0.8,
+                scale: 0.4,
+            },
+        )?;
+        assert!(changed(&text, 100..200, 50..100));
+        assert!(!changed(&text, 0..100, 0..50));
+
+        // Image watermarks are scaled to the requested width
+        let td = tempdir()?;
+        let logo = td.path().join("logo.png");
+        RgbaImage::from_pixel(10, 5, Rgba([255, 0, 0, 255])).save(&logo)?;
+        let mut stamped = blank.clone();
+        apply_watermark(
+            &mut stamped,
+            &WatermarkSettings {
+                content: WatermarkContent::Image(logo),
+                position: WatermarkPosition::TopLeft,
+                opacity: 0.5,
+                scale: 0.25,
+            },
+        )?;
+        assert!(changed(&stamped, 0..60, 0..30));
+        assert!(!changed(&stamped, 60..200, 0..100));
+        let [r, g, _, a] = stamped.get_pixel(20, 10).0;
+        assert_eq!(r, 255);
+        assert!(a >= 254);
+        assert!(
+            (120..136).contains(&g),
+            "half-opacity red over white, got green {g}"
+        );
+        Ok(())
+    }
+}
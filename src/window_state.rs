@@ -0,0 +1,129 @@
+//! Window geometry and panel visibility of the GUI, saved when it closes so it reopens where it
+//! was left
+
+use crate::app_home::AppHome;
+use crate::atomic_write::write_atomic;
+use facet::Facet;
+use std::fs;
+use tracing::warn;
+
+const FILE_NAME: &str = "window_state.json";
+
+/// Smallest width/height restored, so a bad save can't reopen a window too small to use
+pub const MIN_WINDOW_SIZE: f32 = 200.0;
+
+/// Where the window was and which panels were open
+#[derive(Clone, Copy, Debug, PartialEq, Facet)]
+pub struct WindowState {
+    /// Outer left edge, in points
+    pub x: f32,
+    /// Outer top edge, in points
+    pub y: f32,
+    /// Inner width, in points
+    pub width: f32,
+    /// Inner height, in points
+    pub height: f32,
+    pub maximized: bool,
+    pub logs_visible: bool,
+    pub about_open: bool,
+}
+
+impl Default for WindowState {
+    fn default() -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width: 1280.0,
+            height: 800.0,
+            maximized: false,
+            logs_visible: false,
+            about_open: false,
+        }
+    }
+}
+
+impl WindowState {
+    /// Whether the geometry is fit to restore: finite coordinates and a usable size
+    #[must_use]
+    pub fn is_usable(&self) -> bool {
+        [self.x, self.y, self.width, self.height]
+            .iter()
+            .all(|v| v.is_finite())
+            && self.width >= MIN_WINDOW_SIZE
+            && self.height >= MIN_WINDOW_SIZE
+    }
+}
+
+/// Load the saved window state, or `None` if nothing usable has been saved
+///
+/// # Errors
+///
+/// Returns an error if the file exists but cannot be read.
+pub fn load_window_state(home: &AppHome) -> eyre::Result<Option<WindowState>> {
+    let path = home.file_path(FILE_NAME);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(&path)?;
+    let parsed: Result<WindowState, _> = facet_json::from_str(&text);
+    match parsed {
+        Ok(state) if state.is_usable() => Ok(Some(state)),
+        Ok(_) => {
+            warn!("Ignoring unusable window geometry in {}", path.display());
+            Ok(None)
+        }
+        Err(e) => {
+            warn!("Invalid {}: {}, using defaults", path.display(), e);
+            Ok(None)
+        }
+    }
+}
+
+/// Replace the saved window state with `state`
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be written.
+pub fn save_window_state(home: &AppHome, state: &WindowState) -> eyre::Result<()> {
+    home.ensure_dir()?;
+    write_atomic(&home.file_path(FILE_NAME), facet_json::to_string(state)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn window_state_round_trips_and_rejects_unusable_geometry() -> eyre::Result<()> {
+        let td = tempdir()?;
+        let home = AppHome(td.path().to_path_buf());
+        assert_eq!(load_window_state(&home)?, None);
+
+        let state = WindowState {
+            x: -1200.5,
+            y: 40.0,
+            width: 1024.0,
+            height: 700.25,
+            maximized: true,
+            logs_visible: true,
+            about_open: false,
+        };
+        save_window_state(&home, &state)?;
+        assert_eq!(load_window_state(&home)?, Some(state));
+
+        save_window_state(
+            &home,
+            &WindowState {
+                width: 20.0,
+                ..state
+            },
+        )?;
+        assert_eq!(load_window_state(&home)?, None);
+
+        fs::write(home.file_path(FILE_NAME), "not json")?;
+        assert_eq!(load_window_state(&home)?, None);
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+//! End-to-end runs of the `cm` binary against a throwaway `--app-home`
+
+use assert_cmd::Command;
+use image::Rgb;
+use image::RgbImage;
+use predicates::prelude::*;
+use std::path::Path;
+
+/// `cm` with its settings kept in `home`, isolated from the environment of the test run
+fn cm(home: &Path) -> Command {
+    let mut cmd = Command::cargo_bin("cm").unwrap();
+    cmd.arg("--app-home").arg(home);
+    for var in [
+        "CM_APP_HOME",
+        "CM_CONFIG_DIR",
+        "CM_MAX_NAME_LENGTH",
+        "CM_OFFLINE",
+    ] {
+        cmd.env_remove(var);
+    }
+    cmd
+}
+
+#[test]
+fn inputs_can_be_added_listed_and_removed() -> eyre::Result<()> {
+    let td = tempfile::tempdir()?;
+    let home = td.path().join("home");
+    let photos = dunce::canonicalize(td.path())?.join("photos");
+    std::fs::create_dir_all(&photos)?;
+    let pattern = photos.display().to_string();
+
+    cm(&home)
+        .args(["input", "add", &pattern])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Added:"));
+    cm(&home)
+        .args(["input", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(pattern.as_str()));
+    cm(&home)
+        .args(["input", "remove", &pattern])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed:"));
+    cm(&home)
+        .args(["input", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty());
+    Ok(())
+}
+
+#[test]
+fn input_add_without_a_pattern_fails() -> eyre::Result<()> {
+    let td = tempfile::tempdir()?;
+    cm(td.path())
+        .args(["input", "add"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--from-file"));
+    Ok(())
+}
+
+#[test]
+fn rename_rules_can_be_added_listed_and_removed() -> eyre::Result<()> {
+    let td = tempfile::tempdir()?;
+    let output = cm(td.path())
+        .args(["rename-rule", "add", "photo", "item"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let stdout = String::from_utf8(output)?;
+    let id = stdout
+        .strip_prefix("Added rule ")
+        .and_then(|rest| rest.split(':').next())
+        .expect("rule id in the output")
+        .to_owned();
+
+    cm(td.path())
+        .args(["rename-rule", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(id.as_str()));
+    cm(td.path())
+        .args(["rename-rule", "remove", &id])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("Removed rule {id}")));
+    cm(td.path())
+        .args(["rename-rule", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(id.as_str()).not());
+    Ok(())
+}
+
+#[test]
+fn process_dry_run_reports_renames_without_writing() -> eyre::Result<()> {
+    let td = tempfile::tempdir()?;
+    let home = td.path().join("home");
+    let photos = dunce::canonicalize(td.path())?.join("photos");
+    std::fs::create_dir_all(&photos)?;
+    RgbImage::from_pixel(8, 8, Rgb([200, 30, 30])).save(photos.join("photo-1.png"))?;
+
+    cm(&home)
+        .args(["input", "add", &photos.display().to_string()])
+        .assert()
+        .success();
+    cm(&home)
+        .args(["rename-rule", "add", "photo", "item"])
+        .assert()
+        .success();
+    cm(&home)
+        .args(["process", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(
+            predicate::str::contains("item-1.png").and(predicate::str::contains(
+                "Would process 1 images (0 failed)",
+            )),
+        );
+    assert!(!td.path().join("photos-output").exists());
+    Ok(())
+}
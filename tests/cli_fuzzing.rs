@@ -2,6 +2,25 @@ use arbitrary::Arbitrary;
 use clap::Parser;
 use cm::cli::Cli;
 use cm::cli::to_args::ToArgs;
+use std::ffi::OsString;
+
+/// Render `cli` with `to_args`, parse it back and check nothing was lost
+fn assert_roundtrip(cli: &Cli, context: &str) {
+    let mut full_args: Vec<OsString> = vec!["test-exe".into()];
+    full_args.extend(cli.to_args());
+
+    let parsed_cli = match Cli::try_parse_from(&full_args) {
+        Ok(parsed) => parsed,
+        Err(e) => panic!(
+            "Failed to parse CLI args for {context}: {e}\nOriginal CLI: {cli:?}\nArgs: {full_args:?}"
+        ),
+    };
+
+    assert!(
+        *cli == parsed_cli,
+        "CLI roundtrip failed for {context}:\nOriginal: {cli:?}\nParsed: {parsed_cli:?}\nArgs: {full_args:?}"
+    );
+}
 
 #[test]
 fn fuzz_cli_args_roundtrip() {
@@ -18,24 +37,63 @@ fn fuzz_cli_args_roundtrip() {
             }
         };
 
-        let args = cli.to_args();
-
-        let mut full_args = vec!["test-exe".into()];
-        full_args.extend(args);
+        assert_roundtrip(&cli, &format!("iteration {i}"));
+    }
+}
 
-        let parsed_cli = match Cli::try_parse_from(&full_args) {
-            Ok(parsed) => parsed,
-            Err(e) => panic!(
-                "Failed to parse CLI args on iteration {}: {}\nOriginal CLI: {:?}\nArgs: {:?}",
-                i, e, cli, full_args
-            ),
-        };
+#[test]
+fn fuzz_cli_args_roundtrip_for_varied_bytes() {
+    // xorshift, so every run checks the same inputs while still hitting bytes like `-` and `=`
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    for seed in 0..500 {
+        let data: Vec<u8> = (0..512)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state.to_le_bytes()[0]
+            })
+            .collect();
+        let mut rng = arbitrary::Unstructured::new(&data);
+        let cli = Cli::arbitrary(&mut rng).expect("Failed to generate CLI instance");
+        assert_roundtrip(&cli, &format!("seed {seed}"));
+    }
+}
 
-        if cli != parsed_cli {
-            panic!(
-                "CLI roundtrip failed on iteration {}:\nOriginal: {:?}\nParsed: {:?}\nArgs: {:?}",
-                i, cli, parsed_cli, full_args
-            );
-        }
+#[test]
+fn values_that_look_like_flags_roundtrip() {
+    let cases: [&[&str]; 6] = [
+        &[
+            "--log-file",
+            "--site-id=-abc",
+            "rename-rule",
+            "add",
+            "--",
+            "-x",
+            "-y",
+        ],
+        &[
+            "--log-file=-log.jsonl",
+            "search",
+            "--sku=-1",
+            "--",
+            "-query",
+        ],
+        &[
+            "--app-home=-home",
+            "input",
+            "add",
+            "--from-file=-list.txt",
+            "--",
+            "-*.png",
+        ],
+        &["input", "remove", "--", "--"],
+        &["site", "set-host", "--", "-{site_id}.example"],
+        &["process", "--filter=-front", "--report=-report.json"],
+    ];
+    for args in cases {
+        let cli = Cli::try_parse_from(std::iter::once("cm").chain(args.iter().copied()))
+            .unwrap_or_else(|e| panic!("Failed to parse {args:?}: {e}"));
+        assert_roundtrip(&cli, &format!("{args:?}"));
     }
 }
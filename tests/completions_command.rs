@@ -0,0 +1,20 @@
+use clap::Parser;
+use cm::cli::Cli;
+use cm::cli::command::completions::completions_command::CompletionShell;
+use cm::cli::command::completions::completions_command::CompletionsArgs;
+
+#[test]
+fn completions_parses() {
+    assert!(Cli::try_parse_from(&["cm", "completions", "powershell"]).is_ok());
+}
+
+#[test]
+fn bash_completions_generate() {
+    let mut out = Vec::new();
+    CompletionsArgs {
+        shell: CompletionShell::Bash,
+    }
+    .write_to(&mut out);
+    let script = String::from_utf8(out).unwrap();
+    assert!(script.contains("completions"));
+}
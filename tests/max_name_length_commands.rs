@@ -15,3 +15,10 @@ fn max_name_length_set_parses() {
 fn max_name_length_reset_parses() {
     assert!(Cli::try_parse_from(&["cm", "max-name-length", "reset"]).is_ok());
 }
+
+#[test]
+fn max_name_length_unit_parses() {
+    assert!(Cli::try_parse_from(&["cm", "max-name-length", "unit"]).is_ok());
+    assert!(Cli::try_parse_from(&["cm", "max-name-length", "unit", "chars"]).is_ok());
+    assert!(Cli::try_parse_from(&["cm", "max-name-length", "unit", "words"]).is_err());
+}
@@ -0,0 +1,264 @@
+use cm::app_home::AppHome;
+use cm::image_extensions::set_image_extensions;
+use cm::image_processing::OutputConflictPolicy;
+use cm::image_processing::get_output_dir;
+use cm::output_root::set_output_root;
+use cm::pipeline;
+use cm::pipeline::PipelineConfig;
+use cm::rename_rules::RenameRule;
+use image::Rgba;
+use image::RgbaImage;
+use std::path::Path;
+
+#[test]
+fn pipeline_renames_and_processes_a_folder() -> eyre::Result<()> {
+    let td = tempfile::tempdir()?;
+    let photos = td.path().join("photos");
+    std::fs::create_dir_all(&photos)?;
+    RgbaImage::from_pixel(8, 8, Rgba([200, 30, 30, 255])).save(photos.join("photo-1.png"))?;
+    RgbaImage::from_pixel(4, 4, Rgba([30, 200, 30, 255])).save(photos.join("photo-2.png"))?;
+    std::fs::write(photos.join("notes.txt"), "not an image")?;
+
+    let mut config = PipelineConfig::new(vec![photos.clone()]);
+    config.rules.push(RenameRule {
+        find: "photo".to_owned(),
+        replace: "item".to_owned(),
+        only_when_name_too_long: false,
+        ..RenameRule::default()
+    });
+
+    let plan = pipeline::plan(&config)?;
+    assert_eq!(plan.total, 2);
+    let mut names: Vec<_> = plan
+        .renamed
+        .iter()
+        .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+        .collect();
+    names.sort();
+    assert_eq!(names, ["item-1.png", "item-2.png"]);
+
+    let result = pipeline::run(&config)?;
+    assert_eq!((result.processed_count, result.error_count), (2, 0));
+    for entry in &result.report {
+        let output = Path::new(&entry.output);
+        assert!(output.is_file(), "missing {}", output.display());
+        assert!(
+            output
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .starts_with("item-")
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn preview_matches_the_outputs_of_a_real_run() -> eyre::Result<()> {
+    let td = tempfile::tempdir()?;
+    let photos = td.path().join("photos");
+    std::fs::create_dir_all(photos.join("sub"))?;
+    let mut framed = RgbaImage::from_pixel(32, 32, Rgba([255, 255, 255, 255]));
+    for x in 8..24 {
+        for y in 8..24 {
+            framed.put_pixel(x, y, Rgba([20, 20, 120, 255]));
+        }
+    }
+    framed.save(photos.join("framed.png"))?;
+    RgbaImage::from_pixel(8, 8, Rgba([200, 30, 30, 255])).save(photos.join("sub/plain.png"))?;
+
+    let mut config = PipelineConfig::new(vec![photos.clone()]);
+    config.settings.crop_to_content = true;
+    config.settings.crop_threshold = 20;
+    let plan = pipeline::plan(&config)?;
+    let mut planned = pipeline::preview(&config, &plan)?;
+    planned.sort_by(|a, b| a.source.cmp(&b.source));
+    assert_eq!(planned.len(), 2);
+    assert!(planned.iter().all(|p| !p.collides && p.error.is_none()));
+    // Nothing was written by the preview
+    assert!(
+        planned
+            .iter()
+            .all(|p| !Path::new(p.output.as_ref().unwrap()).exists())
+    );
+
+    let result = pipeline::execute(&config, &plan, None, None)?;
+    let mut report = result.report;
+    report.sort_by(|a, b| a.input.cmp(&b.input));
+    assert_eq!(report.len(), planned.len());
+    for (planned, written) in planned.iter().zip(&report) {
+        assert_eq!(planned.source, written.input);
+        assert_eq!(planned.output.as_deref(), Some(written.output.as_str()));
+        assert_eq!(planned.cropped, written.was_cropped);
+        assert_eq!(planned.estimated_size, Some(written.estimated_size));
+        assert!(Path::new(&written.output).is_file());
+    }
+    assert!(planned.iter().any(|p| p.cropped));
+
+    let csv = pipeline::preview_to_csv(&planned);
+    assert!(csv.starts_with(
+        "source,renamed,output,cropped,estimated_size,collides,keeps_existing,error\n"
+    ));
+    assert_eq!(csv.lines().count(), 3);
+    Ok(())
+}
+
+#[test]
+fn preview_applies_the_on_conflict_policy_like_a_real_run() -> eyre::Result<()> {
+    let td = tempfile::tempdir()?;
+    let photos = td.path().join("photos");
+    std::fs::create_dir_all(&photos)?;
+    RgbaImage::from_pixel(8, 8, Rgba([200, 30, 30, 255])).save(photos.join("a.png"))?;
+
+    let mut config = PipelineConfig::new(vec![photos.clone()]);
+    let plan = pipeline::plan(&config)?;
+    let existing = get_output_dir(&plan.roots[0], None).join("a.png");
+    pipeline::execute(&config, &plan, None, None)?;
+    assert!(existing.is_file());
+
+    config.settings.on_conflict = OutputConflictPolicy::Skip;
+    let kept = pipeline::preview(&config, &plan)?;
+    assert!(kept[0].keeps_existing);
+    assert_eq!(kept[0].estimated_size, None);
+    assert_eq!(kept[0].output, Some(existing.display().to_string()));
+
+    config.settings.on_conflict = OutputConflictPolicy::Rename;
+    let renamed = pipeline::preview(&config, &plan)?;
+    assert!(!renamed[0].keeps_existing);
+    let result = pipeline::execute(&config, &plan, None, None)?;
+    assert_eq!(
+        renamed[0].output.as_deref(),
+        Some(result.report[0].output.as_str())
+    );
+    assert!(result.report[0].output.ends_with("a (1).png"));
+    Ok(())
+}
+
+#[test]
+fn ensure_processed_only_rewrites_stale_outputs_and_prunes_removed_sources() -> eyre::Result<()> {
+    let td = tempfile::tempdir()?;
+    let photos = td.path().join("photos");
+    std::fs::create_dir_all(&photos)?;
+    RgbaImage::from_pixel(8, 8, Rgba([200, 30, 30, 255])).save(photos.join("a.png"))?;
+    RgbaImage::from_pixel(8, 8, Rgba([30, 200, 30, 255])).save(photos.join("b.png"))?;
+
+    let config = PipelineConfig::new(vec![photos.clone()]);
+    let output_dir = get_output_dir(&pipeline::plan(&config)?.roots[0], None);
+    let ensure = |prune| pipeline::ensure_processed(&config, &pipeline::plan(&config)?, prune);
+    let first = ensure(false)?;
+    assert_eq!((first.written.len(), first.up_to_date), (2, 0));
+    assert!(output_dir.join("a.png.cmstamp").is_file());
+
+    // Nothing changed, so nothing is written
+    let again = ensure(false)?;
+    assert_eq!((again.written.len(), again.up_to_date), (0, 2));
+
+    // Added and changed sources are written, the rest is left alone
+    RgbaImage::from_pixel(8, 8, Rgba([30, 30, 200, 255])).save(photos.join("c.png"))?;
+    RgbaImage::from_pixel(4, 4, Rgba([10, 10, 10, 255])).save(photos.join("a.png"))?;
+    let changed = ensure(false)?;
+    let mut written = changed.written.clone();
+    written.sort();
+    assert_eq!(
+        written,
+        [output_dir.join("a.png"), output_dir.join("c.png")]
+    );
+    assert_eq!(changed.up_to_date, 1);
+    assert_eq!(image::open(output_dir.join("a.png"))?.width(), 4);
+
+    // A removed source keeps its output until pruning is asked for
+    std::fs::remove_file(photos.join("b.png"))?;
+    assert!(ensure(false)?.pruned.is_empty());
+    assert!(output_dir.join("b.png").is_file());
+    let pruned = ensure(true)?;
+    assert_eq!(pruned.pruned, [output_dir.join("b.png")]);
+    assert_eq!(pruned.up_to_date, 2);
+    assert!(!output_dir.join("b.png").exists());
+    assert!(!output_dir.join("b.png.cmstamp").exists());
+    assert!(output_dir.join("a.png").is_file() && output_dir.join("c.png").is_file());
+    Ok(())
+}
+
+#[test]
+fn ensure_processed_refuses_colliding_inputs_and_keeps_renamed_outputs_fresh() -> eyre::Result<()> {
+    let td = tempfile::tempdir()?;
+    let photos = td.path().join("photos");
+    std::fs::create_dir_all(&photos)?;
+    RgbaImage::from_pixel(8, 8, Rgba([200, 30, 30, 255])).save(photos.join("a.png"))?;
+    RgbaImage::from_pixel(8, 8, Rgba([30, 200, 30, 255])).save(photos.join("b.png"))?;
+
+    let mut config = PipelineConfig::new(vec![photos.clone()]);
+    config.rules.push(RenameRule {
+        find: "b.png".to_owned(),
+        replace: "a.png".to_owned(),
+        only_when_name_too_long: false,
+        ..RenameRule::default()
+    });
+    let plan = pipeline::plan(&config)?;
+    let output_dir = get_output_dir(&plan.roots[0], None);
+    assert!(pipeline::ensure_processed(&config, &plan, false).is_err());
+    assert!(!output_dir.join("a.png").exists());
+
+    // A file the source didn't write is renamed around once, then that output is kept fresh
+    config.rules.clear();
+    config.settings.on_conflict = OutputConflictPolicy::Rename;
+    let plan = pipeline::plan(&config)?;
+    std::fs::create_dir_all(&output_dir)?;
+    std::fs::write(output_dir.join("a.png"), b"not ours")?;
+    let first = pipeline::ensure_processed(&config, &plan, true)?;
+    assert!(first.written.contains(&output_dir.join("a (1).png")));
+    let again = pipeline::ensure_processed(&config, &plan, true)?;
+    assert_eq!((again.written.len(), again.up_to_date), (0, 2));
+    assert!(again.pruned.is_empty());
+    assert!(!output_dir.join("a (2).png").exists());
+    assert_eq!(std::fs::read(output_dir.join("a.png"))?, b"not ours");
+    Ok(())
+}
+
+#[test]
+fn output_bytes_follow_the_extension_a_rule_gives_the_name() -> eyre::Result<()> {
+    let td = tempfile::tempdir()?;
+    let photos = td.path().join("photos");
+    std::fs::create_dir_all(&photos)?;
+    image::RgbImage::from_pixel(8, 8, image::Rgb([200, 30, 30])).save(photos.join("a.jpg"))?;
+    image::RgbImage::from_pixel(8, 8, image::Rgb([30, 200, 30])).save(photos.join("b.jpg"))?;
+
+    let mut config = PipelineConfig::new(vec![photos.clone()]);
+    for (find, replace) in [("a.jpg", "a.png"), ("b.jpg", "b.gif")] {
+        config.rules.push(RenameRule {
+            find: find.to_owned(),
+            replace: replace.to_owned(),
+            only_when_name_too_long: false,
+            ..RenameRule::default()
+        });
+    }
+    let result = pipeline::run(&config)?;
+    let mut outputs: Vec<_> = result.report.iter().map(|e| e.output.clone()).collect();
+    outputs.sort();
+    assert_eq!(outputs.len(), 2);
+    // `.png` is kept and written as PNG; GIF can't be written, so `.gif` is corrected
+    for output in &outputs {
+        assert!(output.ends_with(".png"), "{output}");
+        let bytes = std::fs::read(output)?;
+        assert_eq!(image::guess_format(&bytes)?, image::ImageFormat::Png);
+    }
+    Ok(())
+}
+
+#[test]
+fn config_from_app_home_reads_the_home_it_is_given() -> eyre::Result<()> {
+    let td = tempfile::tempdir()?;
+    let home = AppHome(td.path().join("home"));
+    let root = td.path().join("collected");
+    set_output_root(&home, Some(&root))?;
+    set_image_extensions(&home, Some(&["heic".to_owned()]))?;
+    std::fs::write(home.file_path("max_name_length.txt"), "17")?;
+
+    let config = PipelineConfig::from_app_home(&home)?;
+    assert_eq!(config.settings.output_root, Some(root));
+    assert_eq!(config.image_extensions, ["heic"]);
+    if std::env::var_os("CM_MAX_NAME_LENGTH").is_none() {
+        assert_eq!(config.rename_options.max_name_length, 17);
+    }
+    Ok(())
+}
@@ -20,3 +20,31 @@ fn site_reset_parses() {
 fn search_parses() {
     assert!(Cli::try_parse_from(&["cm", "search", "hello"]).is_ok());
 }
+
+#[test]
+fn search_offline_parses() {
+    let cli = Cli::try_parse_from(&["cm", "--offline", "search", "hello"]).unwrap();
+    assert!(cli.global_args.offline);
+}
+
+#[test]
+fn doctor_json_parses() {
+    assert!(Cli::try_parse_from(&["cm", "doctor", "--json"]).is_ok());
+}
+
+#[test]
+fn app_home_path_parses() {
+    assert!(Cli::try_parse_from(&["cm", "app-home", "path"]).is_ok());
+}
+
+#[test]
+fn version_json_parses_and_version_flag_still_works() {
+    assert!(Cli::try_parse_from(&["cm", "version", "--json"]).is_ok());
+    let err = Cli::try_parse_from(&["cm", "--version"]).unwrap_err();
+    assert_eq!(err.kind(), clap::error::ErrorKind::DisplayVersion);
+}
+
+#[test]
+fn process_filter_parses() {
+    assert!(Cli::try_parse_from(&["cm", "process", "--filter", "*-front.*", "--dry-run"]).is_ok());
+}